@@ -0,0 +1,282 @@
+//!
+//! Macros for [axum-test](https://docs.rs/axum-test).
+//!
+//! These are re-exported by `axum-test` when the `macros` feature is enabled,
+//! and should be used from there.
+//!
+
+#![forbid(unsafe_code)]
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use quote::quote_spanned;
+use syn::meta::ParseNestedMeta;
+use syn::parse_macro_input;
+use syn::spanned::Spanned;
+use syn::FnArg;
+use syn::ItemFn;
+use syn::Path;
+use syn::Result;
+use syn::Type;
+
+/// Marks an async function as a test, which is given a `TestServer`.
+///
+/// See `axum_test::test` for documentation.
+#[proc_macro_attribute]
+pub fn test(args: TokenStream, item: TokenStream) -> TokenStream {
+    let mut test_args = TestArgs::default();
+    let args_parser = syn::meta::parser(|meta| test_args.parse(meta));
+    parse_macro_input!(args with args_parser);
+
+    let test_fn = parse_macro_input!(item as ItemFn);
+
+    expand_test(test_args, test_fn)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[derive(Default)]
+struct TestArgs {
+    server: Option<Path>,
+    setup: Option<Path>,
+    teardown: Option<Path>,
+}
+
+impl TestArgs {
+    fn parse(&mut self, meta: ParseNestedMeta) -> Result<()> {
+        if meta.path.is_ident("server") {
+            self.server = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("setup") {
+            self.setup = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("teardown") {
+            self.teardown = Some(meta.value()?.parse()?);
+        } else {
+            return Err(meta.error("unknown argument, expected `server`, `setup`, or `teardown`"));
+        }
+
+        Ok(())
+    }
+}
+
+/// How the server is passed to the test function.
+enum ServerArg {
+    Owned,
+    Ref,
+    RefMut,
+}
+
+fn expand_test(args: TestArgs, test_fn: ItemFn) -> Result<TokenStream2> {
+    let ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = test_fn;
+
+    if sig.asyncness.is_none() {
+        return Err(syn::Error::new(
+            sig.fn_token.span(),
+            "the `async` keyword is missing from the test function",
+        ));
+    }
+
+    let server_fn = args.server.ok_or_else(|| {
+        syn::Error::new(
+            sig.ident.span(),
+            "missing `server` argument, i.e. `#[axum_test::test(server = new_test_server)]`",
+        )
+    })?;
+
+    let server_arg = match sig.inputs.len() {
+        1 => parse_server_arg(&sig.inputs[0])?,
+        _ => {
+            return Err(syn::Error::new(
+                sig.inputs.span(),
+                "the test function must take exactly one argument, the `TestServer`",
+            ))
+        }
+    };
+
+    let server_pass = match server_arg {
+        ServerArg::Owned => quote! { server },
+        ServerArg::Ref => quote! { &server },
+        ServerArg::RefMut => quote! { &mut server },
+    };
+
+    if matches!(server_arg, ServerArg::Owned) && args.teardown.is_some() {
+        return Err(syn::Error::new(
+            sig.inputs.span(),
+            "a `teardown` hook needs the server afterwards, take it by reference (`&TestServer`) instead",
+        ));
+    }
+
+    let name = &sig.ident;
+    let output = &sig.output;
+    let inputs = &sig.inputs;
+    let inner_name = syn::Ident::new(&format!("__axum_test_inner_{name}"), name.span());
+
+    let setup = args.setup.map(|setup_fn| {
+        quote_spanned! {setup_fn.span()=>
+            #setup_fn(&server).await;
+        }
+    });
+
+    let run_test = match args.teardown {
+        None => quote! {
+            #inner_name(#server_pass).await
+        },
+        Some(teardown_fn) => quote! {
+            let result = ::axum_test::test_harness::catch_unwind(#inner_name(#server_pass)).await;
+            #teardown_fn(&server).await;
+            ::axum_test::test_harness::resume_unwind(result)
+        },
+    };
+
+    Ok(quote! {
+        #[::core::prelude::v1::test]
+        #(#attrs)*
+        #vis fn #name() #output {
+            async fn #inner_name(#inputs) #output #block
+
+            ::axum_test::test_harness::block_on(async {
+                #[allow(unused_mut)]
+                let mut server = #server_fn();
+                #setup
+                #run_test
+            })
+        }
+    })
+}
+
+fn parse_server_arg(arg: &FnArg) -> Result<ServerArg> {
+    let FnArg::Typed(pat_type) = arg else {
+        return Err(syn::Error::new(
+            arg.span(),
+            "the test function cannot take `self`",
+        ));
+    };
+
+    let server_arg = match pat_type.ty.as_ref() {
+        Type::Reference(reference) if reference.mutability.is_some() => ServerArg::RefMut,
+        Type::Reference(_) => ServerArg::Ref,
+        _ => ServerArg::Owned,
+    };
+
+    Ok(server_arg)
+}
+
+#[cfg(test)]
+mod test_expand_test {
+    use super::expand_test;
+    use super::TestArgs;
+    use syn::parse_quote;
+    use syn::ItemFn;
+
+    fn new_args() -> TestArgs {
+        TestArgs {
+            server: Some(parse_quote!(new_test_server)),
+            setup: None,
+            teardown: None,
+        }
+    }
+
+    fn expand_error(args: TestArgs, test_fn: ItemFn) -> String {
+        match expand_test(args, test_fn) {
+            Ok(_) => panic!("Expected the test to fail to expand"),
+            Err(error) => error.to_string(),
+        }
+    }
+
+    #[test]
+    fn it_should_expand_async_test_taking_server_by_reference() {
+        let test_fn = parse_quote! {
+            async fn it_should_ping(server: &TestServer) {}
+        };
+
+        let expanded = expand_test(new_args(), test_fn).unwrap().to_string();
+        assert!(expanded.contains("__axum_test_inner_it_should_ping"));
+        assert!(expanded.contains("block_on"));
+    }
+
+    #[test]
+    fn it_should_error_when_async_is_missing() {
+        let test_fn = parse_quote! {
+            fn it_should_ping(server: &TestServer) {}
+        };
+
+        let error = expand_error(new_args(), test_fn);
+        assert_eq!(
+            error,
+            "the `async` keyword is missing from the test function"
+        );
+    }
+
+    #[test]
+    fn it_should_error_when_server_is_missing() {
+        let args = TestArgs {
+            server: None,
+            ..new_args()
+        };
+        let test_fn = parse_quote! {
+            async fn it_should_ping(server: &TestServer) {}
+        };
+
+        let error = expand_error(args, test_fn);
+        assert_eq!(
+            error,
+            "missing `server` argument, i.e. `#[axum_test::test(server = new_test_server)]`"
+        );
+    }
+
+    #[test]
+    fn it_should_error_when_owned_server_has_teardown() {
+        let args = TestArgs {
+            teardown: Some(parse_quote!(teardown)),
+            ..new_args()
+        };
+        let test_fn = parse_quote! {
+            async fn it_should_ping(server: TestServer) {}
+        };
+
+        let error = expand_error(args, test_fn);
+        assert_eq!(
+            error,
+            "a `teardown` hook needs the server afterwards, take it by reference (`&TestServer`) instead"
+        );
+    }
+
+    #[test]
+    fn it_should_error_when_not_taking_exactly_one_argument() {
+        let test_fn = parse_quote! {
+            async fn it_should_ping() {}
+        };
+
+        let error = expand_error(new_args(), test_fn);
+        assert_eq!(
+            error,
+            "the test function must take exactly one argument, the `TestServer`"
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_test_args_parse {
+    use super::TestArgs;
+    use quote::quote;
+    use syn::parse::Parser;
+
+    #[test]
+    fn it_should_error_on_unknown_argument() {
+        let mut test_args = TestArgs::default();
+        let args_parser = syn::meta::parser(|meta| test_args.parse(meta));
+
+        let error = args_parser
+            .parse2(quote! { server = new_test_server, cleanup = cleanup })
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "unknown argument, expected `server`, `setup`, or `teardown`"
+        );
+    }
+}
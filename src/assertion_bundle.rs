@@ -0,0 +1,273 @@
+use http::header::HeaderName;
+use http::HeaderValue;
+use http::StatusCode;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::sync::Arc;
+
+use crate::AssertionError;
+use crate::ResponseValidator;
+use crate::TestResponse;
+
+type AssertionBundleCheck = dyn Fn(&TestResponse) -> Result<(), AssertionError> + Send + Sync;
+
+/// A named set of assertions, checked together against a [`TestResponse`].
+///
+/// This is for sharing the same baseline checks across many tests,
+/// such as every JSON endpoint returning a request id.
+/// Bundles are checked using [`TestResponse::assert_bundle()`](crate::TestResponse::assert_bundle()),
+/// or against every response by adding them with
+/// [`TestServerBuilder::add_assertion_bundle()`](crate::TestServerBuilder::add_assertion_bundle()).
+///
+/// All checks in the bundle are run, and every failure is reported.
+///
+/// ```rust
+/// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+/// #
+/// use axum::Json;
+/// use axum::Router;
+/// use axum::routing::get;
+/// use axum_test::AssertionBundle;
+/// use axum_test::TestServer;
+/// use serde_json::json;
+///
+/// let app = Router::new()
+///     .route("/todo", get(|| async {
+///         ([("x-request-id", "abc123")], Json(json!({ "title": "Buy milk" })))
+///     }));
+/// let server = TestServer::new(app)?;
+///
+/// let standard_json = AssertionBundle::new("standard-json")
+///     .status_ok()
+///     .content_type_json()
+///     .header_present("x-request-id");
+///
+/// server.get("/todo").await.assert_bundle(&standard_json);
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct AssertionBundle {
+    name: String,
+    checks: Vec<Arc<AssertionBundleCheck>>,
+}
+
+impl AssertionBundle {
+    /// Creates a new empty bundle, with the name given.
+    ///
+    /// The name is included in failure messages.
+    pub fn new<N>(name: N) -> Self
+    where
+        N: Into<String>,
+    {
+        Self {
+            name: name.into(),
+            checks: Vec::new(),
+        }
+    }
+
+    /// The name of this bundle.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Adds a check for the response having the status code given.
+    #[must_use]
+    pub fn status(self, expected_status_code: StatusCode) -> Self {
+        self.add_check(move |response| response.check_status(expected_status_code))
+    }
+
+    /// Adds a check for the response having a 200 status code.
+    #[must_use]
+    pub fn status_ok(self) -> Self {
+        self.add_check(TestResponse::check_status_ok)
+    }
+
+    /// Adds a check for the response having a status code in the 2xx range.
+    #[must_use]
+    pub fn status_success(self) -> Self {
+        self.add_check(TestResponse::check_status_success)
+    }
+
+    /// Adds a check for the response having the `Content-Type` given.
+    #[must_use]
+    pub fn content_type(self, expected_content_type: &str) -> Self {
+        let expected_content_type = expected_content_type.to_string();
+        self.add_check(move |response| response.check_content_type(&expected_content_type))
+    }
+
+    /// Adds a check for the response having an `application/json` `Content-Type`.
+    #[must_use]
+    pub fn content_type_json(self) -> Self {
+        self.content_type(mime::APPLICATION_JSON.as_ref())
+    }
+
+    /// Adds a check for the header named being present in the response.
+    ///
+    /// # Panics
+    ///
+    /// If the name given is not a valid header name.
+    #[must_use]
+    pub fn header_present<N>(self, name: N) -> Self
+    where
+        N: TryInto<HeaderName>,
+        N::Error: Debug,
+    {
+        let header_name: HeaderName = name
+            .try_into()
+            .expect("Failed to build HeaderName from name given");
+
+        self.add_check(move |response| response.check_contains_header(header_name.as_str()))
+    }
+
+    /// Adds a check for the header named being present in the response,
+    /// and matching the value given.
+    ///
+    /// # Panics
+    ///
+    /// If the name or value given are not a valid header name or value.
+    #[must_use]
+    pub fn header<N, V>(self, name: N, value: V) -> Self
+    where
+        N: TryInto<HeaderName>,
+        N::Error: Debug,
+        V: TryInto<HeaderValue>,
+        V::Error: Debug,
+    {
+        let header_name: HeaderName = name
+            .try_into()
+            .expect("Failed to build HeaderName from name given");
+        let header_value: HeaderValue = value
+            .try_into()
+            .expect("Failed to build HeaderValue from value given");
+
+        self.add_check(move |response| {
+            response.check_header(header_name.as_str(), header_value.clone())
+        })
+    }
+
+    /// Adds a custom check to the bundle,
+    /// such as one of the `check_*` methods on [`TestResponse`].
+    #[must_use]
+    pub fn add_check<F>(mut self, check: F) -> Self
+    where
+        F: Fn(&TestResponse) -> Result<(), AssertionError> + Send + Sync + 'static,
+    {
+        self.checks.push(Arc::new(check));
+        self
+    }
+
+    /// Runs every check in the bundle against the response given,
+    /// returning an error listing all of the checks that failed.
+    pub(crate) fn check(&self, response: &TestResponse) -> Result<(), AssertionError> {
+        let failures = self
+            .checks
+            .iter()
+            .filter_map(|check| check(response).err())
+            .collect::<Vec<_>>();
+
+        if failures.is_empty() {
+            return Ok(());
+        }
+
+        let mut message = format!(
+            "Assertion bundle '{}' failed {} of {} checks",
+            self.name,
+            failures.len(),
+            self.checks.len()
+        );
+        for failure in failures {
+            message.push_str("\n    - ");
+            message.push_str(failure.message());
+        }
+
+        Err(AssertionError::new(message))
+    }
+}
+
+impl From<AssertionBundle> for ResponseValidator {
+    fn from(bundle: AssertionBundle) -> Self {
+        Self::new(move |response| Ok(bundle.check(response)?))
+    }
+}
+
+impl Debug for AssertionBundle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("AssertionBundle")
+            .field("name", &self.name)
+            .field("checks", &self.checks.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test_check {
+    use super::*;
+
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Json;
+    use axum::Router;
+    use serde_json::json;
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .route(
+                "/json",
+                get(|| async { ([("x-request-id", "abc123")], Json(json!({ "ok": true }))) }),
+            )
+            .route("/text", get(|| async { "hello" }));
+
+        TestServer::new(app).unwrap()
+    }
+
+    fn new_standard_json() -> AssertionBundle {
+        AssertionBundle::new("standard-json")
+            .status_ok()
+            .content_type_json()
+            .header_present("x-request-id")
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_when_all_checks_pass() {
+        let server = new_test_server();
+        let response = server.get("/json").await;
+
+        assert!(new_standard_json().check(&response).is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_should_report_every_failed_check() {
+        let server = new_test_server();
+        let response = server.get("/text").await;
+
+        let error = new_standard_json().check(&response).unwrap_err();
+        let message = error.message();
+
+        assert!(message.starts_with("Assertion bundle 'standard-json' failed 2 of 3 checks"));
+        assert!(message.contains("Expected content type 'application/json'"));
+        assert!(message.contains("Expected header 'x-request-id' to be present"));
+    }
+
+    #[tokio::test]
+    async fn it_should_run_custom_checks() {
+        let server = new_test_server();
+        let response = server.get("/text").await;
+        let bundle =
+            AssertionBundle::new("greeting").add_check(|response| response.check_text("hello"));
+
+        assert!(bundle.check(&response).is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_should_check_header_values() {
+        let server = new_test_server();
+        let response = server.get("/json").await;
+        let bundle = AssertionBundle::new("request-id").header("x-request-id", "xyz789");
+
+        assert!(bundle.check(&response).is_err());
+    }
+}
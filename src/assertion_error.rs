@@ -0,0 +1,93 @@
+use std::error::Error as StdError;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+
+/// The error returned when a check on a [`TestResponse`](crate::TestResponse) fails.
+///
+/// Every `assert_*` function on `TestResponse` has a `check_*` equivalent,
+/// which returns this error instead of panicking.
+/// This allows assertions to be composed inside of helper functions,
+/// used with `?`, or collected together.
+///
+/// ```rust
+/// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+/// #
+/// use axum::Router;
+/// use axum::routing::get;
+/// use axum_test::AssertionError;
+/// use axum_test::TestResponse;
+/// use axum_test::TestServer;
+///
+/// fn check_is_greeting(response: &TestResponse) -> Result<(), AssertionError> {
+///     response.check_status_ok()?;
+///     response.check_text_contains("hello")?;
+///
+///     Ok(())
+/// }
+///
+/// let app = Router::new()
+///     .route(&"/greet", get(|| async { "hello world" }));
+/// let server = TestServer::new(app)?;
+///
+/// let response = server.get(&"/greet").await;
+/// check_is_greeting(&response)?;
+/// #
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssertionError {
+    message: String,
+}
+
+impl AssertionError {
+    /// Creates a new error, with the message given.
+    pub fn new<M>(message: M) -> Self
+    where
+        M: Into<String>,
+    {
+        Self {
+            message: message.into(),
+        }
+    }
+
+    /// The message describing why the check failed.
+    #[must_use]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for AssertionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl StdError for AssertionError {}
+
+impl From<anyhow::Error> for AssertionError {
+    fn from(error: anyhow::Error) -> Self {
+        Self::new(format!("{error:#}"))
+    }
+}
+
+#[cfg(test)]
+mod test_from_anyhow {
+    use super::*;
+    use anyhow::anyhow;
+    use anyhow::Context;
+
+    #[test]
+    fn it_should_include_context_and_cause_in_message() {
+        let error = Err::<(), _>(anyhow!("invalid json"))
+            .context("Deserializing response")
+            .unwrap_err();
+
+        let assertion_error = AssertionError::from(error);
+        assert_eq!(
+            assertion_error.message(),
+            "Deserializing response: invalid json"
+        );
+    }
+}
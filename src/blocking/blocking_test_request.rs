@@ -0,0 +1,230 @@
+use anyhow::Result;
+use bytes::Bytes;
+use cookie::Cookie;
+use http::HeaderName;
+use http::HeaderValue;
+use serde::Serialize;
+use std::fmt::Debug;
+use std::fmt::Display;
+use std::future::IntoFuture;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+
+use crate::multipart::MultipartForm;
+use crate::TestRequest;
+use crate::TestResponse;
+
+/// A [`TestRequest`] which is sent by blocking, using [`BlockingTestRequest::send()`].
+///
+/// These are created by the [`BlockingTestServer`](crate::blocking::BlockingTestServer).
+/// The most common parts of the `TestRequest` API are mirrored here,
+/// and the rest can be reached using [`BlockingTestRequest::map()`].
+#[derive(Debug)]
+#[must_use = "call `send()` to send the request"]
+pub struct BlockingTestRequest {
+    runtime: Arc<Runtime>,
+    request: TestRequest,
+}
+
+impl BlockingTestRequest {
+    pub(crate) fn new(runtime: Arc<Runtime>, request: TestRequest) -> Self {
+        Self { runtime, request }
+    }
+
+    /// Changes the underlying [`TestRequest`],
+    /// for the parts of its API which are not mirrored here.
+    ///
+    /// ```rust
+    /// # fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum_test::blocking::BlockingTestServer;
+    ///
+    /// let app = Router::new();
+    /// let server = BlockingTestServer::new(app)?;
+    ///
+    /// let response = server.get(&"/todo")
+    ///     .map(|request| request.authorization("Basic am9lOnNlY3JldA=="))
+    ///     .send();
+    /// #
+    /// # Ok(()) }
+    /// ```
+    pub fn map<F>(self, mapper: F) -> Self
+    where
+        F: FnOnce(TestRequest) -> TestRequest,
+    {
+        Self {
+            runtime: self.runtime,
+            request: mapper(self.request),
+        }
+    }
+
+    /// Sets the body of the request, with the content type of 'application/json'.
+    ///
+    /// See [`TestRequest::json()`].
+    pub fn json<J>(self, body: &J) -> Self
+    where
+        J: ?Sized + Serialize,
+    {
+        self.map(|request| request.json(body))
+    }
+
+    /// Sets the body of the request, with the content type of 'application/x-www-form-urlencoded'.
+    ///
+    /// See [`TestRequest::form()`].
+    pub fn form<F>(self, body: &F) -> Self
+    where
+        F: ?Sized + Serialize,
+    {
+        self.map(|request| request.form(body))
+    }
+
+    /// Sets the body of the request to a multipart form.
+    ///
+    /// See [`TestRequest::multipart()`].
+    pub fn multipart(self, multipart: MultipartForm) -> Self {
+        self.map(|request| request.multipart(multipart))
+    }
+
+    /// Set raw text as the body of the request,
+    /// and sets the content type to `text/plain`.
+    pub fn text<T>(self, raw_text: T) -> Self
+    where
+        T: Display,
+    {
+        self.map(|request| request.text(raw_text))
+    }
+
+    /// Set raw bytes as the body of the request.
+    ///
+    /// The content type is left unchanged.
+    pub fn bytes(self, body_bytes: Bytes) -> Self {
+        self.map(|request| request.bytes(body_bytes))
+    }
+
+    /// Set the content type to use for this request in the header.
+    pub fn content_type(self, content_type: &str) -> Self {
+        self.map(|request| request.content_type(content_type))
+    }
+
+    /// Adds a Cookie to be sent with this request.
+    pub fn add_cookie(self, cookie: Cookie<'_>) -> Self {
+        self.map(|request| request.add_cookie(cookie))
+    }
+
+    /// Adds a query param onto the end of the request.
+    pub fn add_query_param<V>(self, key: &str, value: V) -> Self
+    where
+        V: Serialize,
+    {
+        self.map(|request| request.add_query_param(key, value))
+    }
+
+    /// Adds a header to be sent with this request.
+    pub fn add_header<N, V>(self, name: N, value: V) -> Self
+    where
+        N: TryInto<HeaderName>,
+        N::Error: Debug,
+        V: TryInto<HeaderValue>,
+        V::Error: Debug,
+    {
+        self.map(|request| request.add_header(name, value))
+    }
+
+    /// Sets the authorization header to a bearer token.
+    pub fn authorization_bearer<T>(self, authorization_bearer_token: T) -> Self
+    where
+        T: Display,
+    {
+        self.map(|request| request.authorization_bearer(authorization_bearer_token))
+    }
+
+    /// Marks that this request is expected to always return a HTTP
+    /// status code within the 2xx range (200 to 299).
+    pub fn expect_success(self) -> Self {
+        self.map(TestRequest::expect_success)
+    }
+
+    /// Marks that this request is expected to return a HTTP status code
+    /// outside of the 2xx range.
+    pub fn expect_failure(self) -> Self {
+        self.map(TestRequest::expect_failure)
+    }
+
+    /// Sends the request, blocking until the response is returned.
+    ///
+    /// This will panic if the request fails to send,
+    /// or if the response does not match what was expected.
+    pub fn send(self) -> TestResponse {
+        self.runtime.block_on(self.request.into_future())
+    }
+
+    /// Sends the request, blocking until the response is returned.
+    ///
+    /// This is the blocking version of [`TestRequest::try_send()`].
+    pub fn try_send(self) -> Result<TestResponse> {
+        self.runtime.block_on(self.request.try_send())
+    }
+}
+
+#[cfg(test)]
+mod test_send {
+    use crate::blocking::BlockingTestServer;
+    use axum::routing::get;
+    use axum::routing::post;
+    use axum::Json;
+    use axum::Router;
+    use serde_json::json;
+    use serde_json::Value;
+
+    #[test]
+    fn it_should_send_json() {
+        let app = Router::new().route(
+            "/echo",
+            post(|Json(body): Json<Value>| async move { Json(body) }),
+        );
+        let server = BlockingTestServer::new(app).unwrap();
+
+        server
+            .post("/echo")
+            .json(&json!({ "name": "Joe" }))
+            .send()
+            .assert_json(&json!({ "name": "Joe" }));
+    }
+
+    #[test]
+    fn it_should_send_headers_set_using_map() {
+        let app = Router::new().route(
+            "/header",
+            get(|headers: http::HeaderMap| async move {
+                headers["x-custom"].to_str().unwrap().to_string()
+            }),
+        );
+        let server = BlockingTestServer::new(app).unwrap();
+
+        server
+            .get("/header")
+            .map(|request| request.add_header("x-custom", "hello"))
+            .send()
+            .assert_text("hello");
+    }
+
+    #[test]
+    #[should_panic]
+    fn it_should_panic_when_the_expected_state_is_not_met() {
+        let app = Router::new();
+        let server = BlockingTestServer::new(app).unwrap();
+
+        server.get("/missing").expect_success().send();
+    }
+
+    #[test]
+    fn it_should_return_the_response_from_try_send() {
+        let app = Router::new().route("/ping", get(|| async { "pong!" }));
+        let server = BlockingTestServer::new(app).unwrap();
+
+        let response = server.get("/ping").try_send().unwrap();
+
+        response.assert_text("pong!");
+    }
+}
@@ -0,0 +1,183 @@
+use anyhow::Context;
+use http::Method;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::runtime::Builder as RuntimeBuilder;
+use tokio::runtime::Runtime;
+
+use crate::blocking::BlockingTestRequest;
+use crate::transport_layer::IntoTransportLayer;
+use crate::Error;
+use crate::TestServer;
+use crate::TestServerConfig;
+
+/// A [`TestServer`] which blocks on each request, rather than being awaited.
+///
+/// This owns the Tokio runtime the server runs on,
+/// which is shut down when the `BlockingTestServer` is dropped.
+///
+/// See the [`blocking`](crate::blocking) module for an example.
+#[derive(Debug)]
+pub struct BlockingTestServer {
+    // The server is dropped before the runtime it was started on.
+    server: TestServer,
+    runtime: Arc<Runtime>,
+}
+
+impl BlockingTestServer {
+    /// Starts a new runtime, and runs the application on it.
+    ///
+    /// This is the blocking version of [`TestServer::new()`].
+    pub fn new<A>(app: A) -> Result<Self, Error>
+    where
+        A: IntoTransportLayer,
+    {
+        Self::new_with_config(app, TestServerConfig::default())
+    }
+
+    /// Similar to [`BlockingTestServer::new()`], with a customised configuration.
+    ///
+    /// This can take a [`TestServerConfig`] or a [`TestServerBuilder`](crate::TestServerBuilder),
+    /// and is the blocking version of [`TestServer::new_with_config()`].
+    pub fn new_with_config<A, C>(app: A, config: C) -> Result<Self, Error>
+    where
+        A: IntoTransportLayer,
+        C: Into<TestServerConfig>,
+    {
+        let runtime = RuntimeBuilder::new_current_thread()
+            .enable_all()
+            .build()
+            .context("Failed to start Tokio runtime, for BlockingTestServer")?;
+
+        // The HTTP transports bind and spawn onto the runtime as they are built.
+        let server = {
+            let _runtime_guard = runtime.enter();
+            TestServer::new_with_config(app, config)?
+        };
+
+        Ok(Self {
+            server,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// Returns the underlying [`TestServer`],
+    /// for the parts of the API which do not send requests.
+    #[must_use]
+    pub fn server(&self) -> &TestServer {
+        &self.server
+    }
+
+    /// Blocks on the future given, running it on the runtime of this server.
+    ///
+    /// This is for the parts of the async API not mirrored here,
+    /// such as `server.block_on(server.server().get("/ws").into_websocket())`.
+    pub fn block_on<F>(&self, future: F) -> F::Output
+    where
+        F: Future,
+    {
+        self.runtime.block_on(future)
+    }
+
+    /// Creates a HTTP GET request to the path.
+    pub fn get(&self, path: &str) -> BlockingTestRequest {
+        self.method(Method::GET, path)
+    }
+
+    /// Creates a HTTP POST request to the given path.
+    pub fn post(&self, path: &str) -> BlockingTestRequest {
+        self.method(Method::POST, path)
+    }
+
+    /// Creates a HTTP PATCH request to the path.
+    pub fn patch(&self, path: &str) -> BlockingTestRequest {
+        self.method(Method::PATCH, path)
+    }
+
+    /// Creates a HTTP PUT request to the path.
+    pub fn put(&self, path: &str) -> BlockingTestRequest {
+        self.method(Method::PUT, path)
+    }
+
+    /// Creates a HTTP DELETE request to the path.
+    pub fn delete(&self, path: &str) -> BlockingTestRequest {
+        self.method(Method::DELETE, path)
+    }
+
+    /// Creates a HTTP request, to the method and path provided.
+    pub fn method(&self, method: Method, path: &str) -> BlockingTestRequest {
+        let request = self.server.method(method, path);
+
+        BlockingTestRequest::new(Arc::clone(&self.runtime), request)
+    }
+}
+
+#[cfg(test)]
+mod test_new {
+    use super::*;
+    use axum::routing::get;
+    use axum::Router;
+
+    #[test]
+    fn it_should_serve_requests_with_mock_transport() {
+        let app = Router::new().route("/ping", get(|| async { "pong!" }));
+        let server = BlockingTestServer::new(app).unwrap();
+
+        server.get("/ping").send().assert_text("pong!");
+    }
+
+    #[test]
+    fn it_should_serve_requests_with_http_transport() {
+        let app = Router::new().route("/ping", get(|| async { "pong!" }));
+        let server =
+            BlockingTestServer::new_with_config(app, TestServer::builder().http_transport())
+                .unwrap();
+
+        server.get("/ping").send().assert_text("pong!");
+        server.get("/ping").send().assert_text("pong!");
+    }
+
+    #[test]
+    fn it_should_keep_cookies_between_requests() {
+        use axum_extra::extract::cookie::Cookie;
+        use axum_extra::extract::cookie::CookieJar;
+
+        let app = Router::new()
+            .route(
+                "/login",
+                get(|jar: CookieJar| async move { jar.add(Cookie::new("session", "abc")) }),
+            )
+            .route(
+                "/session",
+                get(|jar: CookieJar| async move {
+                    jar.get("session")
+                        .map(|cookie| cookie.value().to_string())
+                        .unwrap_or_default()
+                }),
+            );
+        let server =
+            BlockingTestServer::new_with_config(app, TestServer::builder().save_cookies()).unwrap();
+
+        server.get("/login").send();
+        server.get("/session").send().assert_text("abc");
+    }
+}
+
+#[cfg(test)]
+mod test_block_on {
+    use super::*;
+    use axum::routing::get;
+    use axum::Router;
+
+    #[test]
+    fn it_should_run_futures_on_the_server_runtime() {
+        let app = Router::new().route("/ping", get(|| async { "pong!" }));
+        let server =
+            BlockingTestServer::new_with_config(app, TestServer::builder().http_transport())
+                .unwrap();
+
+        let text = server.block_on(async { server.server().get("/ping").await.text() });
+
+        assert_eq!(text, "pong!");
+    }
+}
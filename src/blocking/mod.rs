@@ -0,0 +1,36 @@
+//!
+//! A blocking version of the [`TestServer`](crate::TestServer),
+//! for test harnesses, build scripts, and tools which cannot be made async.
+//!
+//! The [`BlockingTestServer`] runs its own Tokio runtime,
+//! and each request blocks the current thread until the response is returned.
+//! The [`TestResponse`](crate::TestResponse) returned is the same as the async API.
+//!
+//! These must not be used from within an async context, such as a `#[tokio::test]`,
+//! as Tokio does not allow blocking on a runtime from inside another.
+//!
+//! ```rust
+//! # fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+//! #
+//! use axum::Router;
+//! use axum::routing::get;
+//! use axum_test::blocking::BlockingTestServer;
+//!
+//! let app = Router::new()
+//!     .route(&"/ping", get(|| async { "pong!" }));
+//!
+//! let server = BlockingTestServer::new(app)?;
+//!
+//! server.get(&"/ping")
+//!     .send()
+//!     .assert_text("pong!");
+//! #
+//! # Ok(()) }
+//! ```
+//!
+
+mod blocking_test_server;
+pub use self::blocking_test_server::*;
+
+mod blocking_test_request;
+pub use self::blocking_test_request::*;
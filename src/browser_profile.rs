@@ -0,0 +1,101 @@
+use http::header;
+use http::HeaderName;
+use http::HeaderValue;
+
+const SEC_CH_UA: HeaderName = HeaderName::from_static("sec-ch-ua");
+const SEC_CH_UA_MOBILE: HeaderName = HeaderName::from_static("sec-ch-ua-mobile");
+const SEC_CH_UA_PLATFORM: HeaderName = HeaderName::from_static("sec-ch-ua-platform");
+
+/// A set of headers matching those sent by a real client,
+/// such as a browser, a command line tool, or a search engine crawler.
+///
+/// This is set on a request using [`TestRequest::browser_profile()`](crate::TestRequest::browser_profile()).
+/// Headers in the profile replace any of the same name already set on the request.
+///
+/// ```rust
+/// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+/// #
+/// use axum::Router;
+/// use axum_test::BrowserProfile;
+/// use axum_test::TestServer;
+///
+/// let app = Router::new();
+/// let server = TestServer::new(app)?;
+///
+/// let response = server
+///     .get(&"/")
+///     .browser_profile(BrowserProfile::googlebot())
+///     .await;
+/// #
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrowserProfile {
+    headers: Vec<(HeaderName, HeaderValue)>,
+}
+
+impl BrowserProfile {
+    /// Google Chrome, running on desktop Windows.
+    pub fn chrome() -> Self {
+        Self {
+            headers: vec![
+                (
+                    header::USER_AGENT,
+                    HeaderValue::from_static("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36"),
+                ),
+                (
+                    header::ACCEPT,
+                    HeaderValue::from_static("text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,image/apng,*/*;q=0.8"),
+                ),
+                (
+                    header::ACCEPT_LANGUAGE,
+                    HeaderValue::from_static("en-US,en;q=0.9"),
+                ),
+                (
+                    SEC_CH_UA,
+                    HeaderValue::from_static(
+                        r#""Not_A Brand";v="8", "Chromium";v="120", "Google Chrome";v="120""#,
+                    ),
+                ),
+                (SEC_CH_UA_MOBILE, HeaderValue::from_static("?0")),
+                (SEC_CH_UA_PLATFORM, HeaderValue::from_static(r#""Windows""#)),
+            ],
+        }
+    }
+
+    /// The `curl` command line tool.
+    pub fn curl() -> Self {
+        Self {
+            headers: vec![
+                (header::USER_AGENT, HeaderValue::from_static("curl/8.5.0")),
+                (header::ACCEPT, HeaderValue::from_static("*/*")),
+            ],
+        }
+    }
+
+    /// Google's search engine crawler.
+    pub fn googlebot() -> Self {
+        Self {
+            headers: vec![
+                (
+                    header::USER_AGENT,
+                    HeaderValue::from_static(
+                        "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)",
+                    ),
+                ),
+                (
+                    header::ACCEPT,
+                    HeaderValue::from_static(
+                        "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8",
+                    ),
+                ),
+            ],
+        }
+    }
+
+    /// The headers sent for this profile.
+    #[must_use]
+    pub fn headers(&self) -> &[(HeaderName, HeaderValue)] {
+        &self.headers
+    }
+}
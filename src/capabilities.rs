@@ -0,0 +1,150 @@
+/// Describes which optional features `axum-test` was built with.
+///
+/// This is returned by [`capabilities()`], and can be used by shared test helpers
+/// to skip, or explain, tests which need a feature that is turned off.
+///
+/// ```rust
+/// let capabilities = axum_test::capabilities();
+///
+/// if !capabilities.yaml {
+///     println!("skipping Yaml tests, enable the `yaml` feature to run them");
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Capabilities {
+    /// Built with `pretty-assertions`, for coloured diffs in assertion failures.
+    pub pretty_assertions: bool,
+
+    /// Built with `yaml`, for Yaml request and response bodies.
+    pub yaml: bool,
+
+    /// Built with `msgpack`, for MsgPack request and response bodies.
+    pub msgpack: bool,
+
+    /// Built with `reqwest`, for sending requests using Reqwest.
+    pub reqwest: bool,
+
+    /// Built with `shuttle`, for running Shuttle services.
+    pub shuttle: bool,
+
+    /// Built with `typed-routing`, for building requests from typed paths.
+    pub typed_routing: bool,
+
+    /// Built with `ws`, for WebSockets.
+    pub ws: bool,
+
+    /// Built with `macros`, for the `#[axum_test::test]` attribute.
+    pub macros: bool,
+
+    /// Built with `html`, for querying HTML responses with CSS selectors.
+    pub html: bool,
+
+    /// Built with `regex`, for asserting response text using regular expressions.
+    pub regex: bool,
+
+    /// Built with `archives`, for inspecting zip archive responses.
+    pub archives: bool,
+
+    /// Built with `webhooks`, for catching webhooks and verifying their signatures.
+    pub webhooks: bool,
+
+    /// Built with `mail`, for capturing emails sent over SMTP.
+    pub mail: bool,
+
+    /// Built with `jsonapi`, for reading JSON:API documents.
+    pub jsonapi: bool,
+
+    /// Built with `rejections`, for asserting on axum extractor rejections.
+    pub rejections: bool,
+
+    /// Built with `multipart-echo`, for the `multipart_echo` handler and its assertions.
+    pub multipart_echo: bool,
+
+    /// Built with `blocking`, for the [`blocking`](crate::blocking) API.
+    pub blocking: bool,
+
+    /// Built with `tus`, for uploading files using the tus resumable upload protocol.
+    pub tus: bool,
+
+    /// Built with `matched-route`, for asserting which route handled a request.
+    pub matched_route: bool,
+
+    /// Built with `etag`, for checking `ETag` headers against the response body.
+    pub etag: bool,
+
+    /// Built with `jwe`, for minting encrypted JWTs.
+    pub jwe: bool,
+
+    /// Built with `dyn-features`.
+    ///
+    /// In this mode the Yaml, MsgPack, WebSocket, Reqwest, and typed routing methods are always available,
+    /// and return an [`Error::MissingFeature`](crate::Error::MissingFeature) when their feature is turned off.
+    /// They only panic where the method cannot return an error, such as `TestResponse::yaml()`.
+    ///
+    /// Where a method returns a type from a missing dependency, such as the Reqwest `RequestBuilder`,
+    /// it returns a `Result` holding the error instead.
+    pub dyn_features: bool,
+}
+
+/// Returns which optional features `axum-test` was built with.
+#[must_use]
+pub fn capabilities() -> Capabilities {
+    Capabilities {
+        pretty_assertions: cfg!(feature = "pretty-assertions"),
+        yaml: cfg!(feature = "yaml"),
+        msgpack: cfg!(feature = "msgpack"),
+        reqwest: cfg!(feature = "reqwest"),
+        shuttle: cfg!(feature = "shuttle"),
+        typed_routing: cfg!(feature = "typed-routing"),
+        ws: cfg!(feature = "ws"),
+        macros: cfg!(feature = "macros"),
+        html: cfg!(feature = "html"),
+        regex: cfg!(feature = "regex"),
+        archives: cfg!(feature = "archives"),
+        webhooks: cfg!(feature = "webhooks"),
+        mail: cfg!(feature = "mail"),
+        jsonapi: cfg!(feature = "jsonapi"),
+        rejections: cfg!(feature = "rejections"),
+        multipart_echo: cfg!(feature = "multipart-echo"),
+        blocking: cfg!(feature = "blocking"),
+        tus: cfg!(feature = "tus"),
+        matched_route: cfg!(feature = "matched-route"),
+        etag: cfg!(feature = "etag"),
+        jwe: cfg!(feature = "jwe"),
+        dyn_features: cfg!(feature = "dyn-features"),
+    }
+}
+
+#[cfg(test)]
+mod test_capabilities {
+    use super::*;
+
+    #[test]
+    fn it_should_report_features_built_with() {
+        let capabilities = capabilities();
+
+        assert_eq!(capabilities.yaml, cfg!(feature = "yaml"));
+        assert_eq!(capabilities.msgpack, cfg!(feature = "msgpack"));
+        assert_eq!(capabilities.ws, cfg!(feature = "ws"));
+        assert_eq!(capabilities.reqwest, cfg!(feature = "reqwest"));
+        assert_eq!(capabilities.typed_routing, cfg!(feature = "typed-routing"));
+        assert_eq!(capabilities.html, cfg!(feature = "html"));
+        assert_eq!(capabilities.regex, cfg!(feature = "regex"));
+        assert_eq!(capabilities.archives, cfg!(feature = "archives"));
+        assert_eq!(capabilities.webhooks, cfg!(feature = "webhooks"));
+        assert_eq!(capabilities.mail, cfg!(feature = "mail"));
+        assert_eq!(capabilities.jsonapi, cfg!(feature = "jsonapi"));
+        assert_eq!(capabilities.rejections, cfg!(feature = "rejections"));
+        assert_eq!(
+            capabilities.multipart_echo,
+            cfg!(feature = "multipart-echo")
+        );
+        assert_eq!(capabilities.blocking, cfg!(feature = "blocking"));
+        assert_eq!(capabilities.tus, cfg!(feature = "tus"));
+        assert_eq!(capabilities.matched_route, cfg!(feature = "matched-route"));
+        assert_eq!(capabilities.etag, cfg!(feature = "etag"));
+        assert_eq!(capabilities.jwe, cfg!(feature = "jwe"));
+        assert_eq!(capabilities.dyn_features, cfg!(feature = "dyn-features"));
+    }
+}
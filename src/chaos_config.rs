@@ -0,0 +1,161 @@
+use std::time::Duration;
+
+use crate::Error;
+
+/// Configuration for injecting failures into requests made by the [`TestServer`](crate::TestServer).
+/// This is for testing how code copes when things go wrong, such as retry logic.
+///
+/// It is set using [`TestServerBuilder::with_chaos()`](crate::TestServerBuilder::with_chaos()),
+/// or the [`TestServerConfig::chaos`](crate::TestServerConfig::chaos) field.
+///
+/// Failures are chosen at random, using a seeded random number generator.
+/// Reusing the same `seed` will reproduce the same failures in the same order.
+///
+/// It implements [`Default`] to ease building, which injects no failures.
+///
+/// ```rust
+/// use axum_test::ChaosConfig;
+///
+/// // 10% of requests will return a 500 error.
+/// let chaos = ChaosConfig {
+///     server_error_rate: 0.1,
+///     seed: 42,
+///     ..ChaosConfig::default()
+/// };
+/// ```
+///
+/// Chaos is applied to requests made through the `TestServer`,
+/// such as [`TestServer::get()`](crate::TestServer::get()).
+///
+/// Awaiting a request whose connection was dropped will panic.
+/// Use [`TestRequest::try_send()`](crate::TestRequest::try_send()) to receive the error instead,
+/// such as when testing retry logic.
+///
+/// The rates must be within `0.0` to `1.0`,
+/// otherwise building the `TestServer` will return an error.
+#[derive(Debug, Clone)]
+pub struct ChaosConfig {
+    /// The seed for the random number generator, used to pick which requests fail.
+    ///
+    /// **Defaults** to 0.
+    pub seed: u64,
+
+    /// The probability, from `0.0` to `1.0`, of a request returning
+    /// a `500 Internal Server Error`. The request will not reach your application.
+    ///
+    /// **Defaults** to 0.0.
+    pub server_error_rate: f64,
+
+    /// The probability, from `0.0` to `1.0`, of the connection being dropped
+    /// before a response is received. This causes sending the request to fail.
+    ///
+    /// **Defaults** to 0.0.
+    pub dropped_connection_rate: f64,
+
+    /// The probability, from `0.0` to `1.0`, of the response body being cut short.
+    /// Truncated bodies keep their headers, including any `Content-Length`.
+    ///
+    /// **Defaults** to 0.0.
+    pub truncated_body_rate: f64,
+
+    /// Extra latency added to every request, before it is sent.
+    ///
+    /// **Defaults** to none.
+    pub latency: Option<Duration>,
+}
+
+impl ChaosConfig {
+    /// Creates a default `ChaosConfig`, which injects no failures.
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+/// Rates are compared by their exact bits, so a config always equals itself.
+impl PartialEq for ChaosConfig {
+    fn eq(&self, other: &Self) -> bool {
+        self.seed == other.seed
+            && self.server_error_rate.to_bits() == other.server_error_rate.to_bits()
+            && self.dropped_connection_rate.to_bits() == other.dropped_connection_rate.to_bits()
+            && self.truncated_body_rate.to_bits() == other.truncated_body_rate.to_bits()
+            && self.latency == other.latency
+    }
+}
+
+impl Eq for ChaosConfig {}
+
+impl Default for ChaosConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            server_error_rate: 0.0,
+            dropped_connection_rate: 0.0,
+            truncated_body_rate: 0.0,
+            latency: None,
+        }
+    }
+}
+
+impl ChaosConfig {
+    pub(crate) fn validate(&self) -> Result<(), Error> {
+        let rates = [
+            ("server_error_rate", self.server_error_rate),
+            ("dropped_connection_rate", self.dropped_connection_rate),
+            ("truncated_body_rate", self.truncated_body_rate),
+        ];
+
+        for (name, rate) in rates {
+            if !(0.0..=1.0).contains(&rate) {
+                return Err(Error::InvalidConfig {
+                    message: format!(
+                        "ChaosConfig {name} must be within 0.0 to 1.0, received {rate}"
+                    ),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_validate {
+    use super::*;
+
+    #[test]
+    fn it_should_accept_rates_within_range() {
+        let chaos = ChaosConfig {
+            server_error_rate: 1.0,
+            dropped_connection_rate: 0.0,
+            truncated_body_rate: 0.5,
+            ..ChaosConfig::default()
+        };
+
+        assert!(chaos.validate().is_ok());
+    }
+
+    #[test]
+    fn it_should_reject_rates_outside_range() {
+        let chaos = ChaosConfig {
+            server_error_rate: 1.5,
+            ..ChaosConfig::default()
+        };
+
+        let error = chaos.validate().unwrap_err();
+        assert!(matches!(error, Error::InvalidConfig { .. }));
+        assert_eq!(
+            error.to_string(),
+            "ChaosConfig server_error_rate must be within 0.0 to 1.0, received 1.5"
+        );
+    }
+
+    #[test]
+    fn it_should_reject_nan_rates() {
+        let chaos = ChaosConfig {
+            truncated_body_rate: f64::NAN,
+            ..ChaosConfig::default()
+        };
+
+        assert!(chaos.validate().is_err());
+    }
+}
@@ -0,0 +1,35 @@
+/// Configuration for automatically handling CSRF tokens,
+/// set using [`TestServer::enable_csrf()`](crate::TestServer::enable_csrf()).
+///
+/// The token is read from responses, from either a cookie or a header with the names given.
+/// It is then sent back on all later mutating requests (i.e. `POST`, `PUT`, `PATCH`, and `DELETE`),
+/// as both the cookie and the header.
+///
+/// ```rust
+/// use axum_test::CsrfConfig;
+///
+/// let csrf = CsrfConfig::new("csrf", "x-csrf-token");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsrfConfig {
+    /// The name of the cookie carrying the CSRF token.
+    pub cookie: String,
+
+    /// The name of the header to send the CSRF token in.
+    /// Responses carrying this header will also update the token.
+    pub header: String,
+}
+
+impl CsrfConfig {
+    /// Creates a `CsrfConfig`, using the cookie and header names given.
+    pub fn new<C, H>(cookie: C, header: H) -> Self
+    where
+        C: Into<String>,
+        H: Into<String>,
+    {
+        Self {
+            cookie: cookie.into(),
+            header: header.into(),
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use std::error::Error as StdError;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::io::Error as IoError;
+use std::net::SocketAddr;
+
+/// The error returned when building a [`TestServer`](crate::TestServer),
+/// building urls to the server, or sending requests through the transport layer.
+///
+/// This can be matched on to handle specific failures,
+/// such as retrying when a port is already in use.
+/// It can also be converted into an [`anyhow::Error`] using `?`.
+///
+/// ```rust
+/// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+/// #
+/// use axum::Router;
+/// use axum_test::Error;
+/// use axum_test::TestServer;
+/// use std::io::ErrorKind;
+///
+/// let result = TestServer::builder()
+///     .http_transport_with_ip_port(None, Some(8123))
+///     .build(Router::new());
+///
+/// match result {
+///     Ok(_server) => { /* run tests */ }
+///     Err(Error::PortBindFailed { source, .. }) if source.kind() == ErrorKind::AddrInUse => {
+///         /* retry on a different port */
+///     }
+///     Err(error) => return Err(error.into()),
+/// }
+/// #
+/// # Ok(()) }
+/// ```
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Failed to bind the server to its address,
+    /// such as when the port is already in use.
+    PortBindFailed {
+        address: SocketAddr,
+        source: IoError,
+    },
+
+    /// Failed to find, or reserve, a port for the server to run on.
+    PortReserveFailed { message: String },
+
+    /// A url or path given could not be used.
+    InvalidUrl { url: String, message: String },
+
+    /// A request to a different scheme or host was rejected,
+    /// as `restrict_requests_with_http_schema` is turned on.
+    SchemeRejected { path: String },
+
+    /// The server has no address, as it is running with mock transport.
+    NoServerAddress,
+
+    /// The [`TestServerConfig`](crate::TestServerConfig) given is invalid.
+    InvalidConfig { message: String },
+
+    /// The server is no longer running, so requests cannot be sent to it.
+    TransportClosed,
+
+    /// The response body was larger than the maximum allowed,
+    /// set using [`TestServerBuilder::max_buffered_body()`](crate::TestServerBuilder::max_buffered_body()).
+    BodyTooLarge { limit: usize },
+
+    /// A method was called which needs a feature axum-test was not built with.
+    /// This is only returned in `dyn-features` mode, where those methods are kept.
+    MissingFeature { method: String, feature: String },
+
+    /// Any other error, such as from a custom [`IntoTransportLayer`](crate::transport_layer::IntoTransportLayer),
+    /// or a request failing to be sent.
+    Other(anyhow::Error),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::PortBindFailed { address, .. } => {
+                write!(f, "Failed to bind server to {address}")
+            }
+            Self::PortReserveFailed { message } => {
+                write!(f, "Failed to reserve port for server, {message}")
+            }
+            Self::InvalidUrl { url, message } => write!(f, "Invalid url '{url}', {message}"),
+            Self::SchemeRejected { path } => write!(
+                f,
+                "Request disallowed for path '{path}', requests are only allowed to local server. Turn off 'restrict_requests_with_http_schema' to change this."
+            ),
+            Self::NoServerAddress => write!(
+                f,
+                "No local address for server, need to run with HTTP transport to have a server address"
+            ),
+            Self::InvalidConfig { message } => write!(f, "{message}"),
+            Self::TransportClosed => write!(f, "Server is no longer running"),
+            Self::BodyTooLarge { limit } => write!(
+                f,
+                "Response body exceeded the maximum buffered size of {limit} bytes"
+            ),
+            Self::MissingFeature { method, feature } => write!(
+                f,
+                "`{method}` requires axum-test to be built with the `{feature}` feature, add it to the axum-test features in your Cargo.toml"
+            ),
+            Self::Other(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::PortBindFailed { source, .. } => Some(source),
+            Self::Other(error) => error.source(),
+            _ => None,
+        }
+    }
+}
+
+impl From<anyhow::Error> for Error {
+    fn from(error: anyhow::Error) -> Self {
+        Self::Other(error)
+    }
+}
+
+#[cfg(test)]
+mod test_into_anyhow {
+    use super::*;
+
+    #[test]
+    fn it_should_convert_into_anyhow_keeping_the_error() {
+        let error: anyhow::Error = Error::TransportClosed.into();
+
+        assert!(matches!(
+            error.downcast_ref::<Error>(),
+            Some(Error::TransportClosed)
+        ));
+    }
+}
+
+#[cfg(test)]
+mod test_display {
+    use super::*;
+    use anyhow::anyhow;
+    use std::io::ErrorKind;
+
+    #[test]
+    fn it_should_not_repeat_the_source_for_port_bind_failed() {
+        let error = Error::PortBindFailed {
+            address: "127.0.0.1:8123".parse().unwrap(),
+            source: IoError::new(ErrorKind::AddrInUse, "address in use"),
+        };
+
+        assert_eq!(error.to_string(), "Failed to bind server to 127.0.0.1:8123");
+        assert_eq!(error.source().unwrap().to_string(), "address in use");
+    }
+
+    #[test]
+    fn it_should_not_repeat_the_source_for_other() {
+        let error = Error::Other(anyhow!("address in use").context("Failed to connect"));
+
+        assert_eq!(error.to_string(), "Failed to connect");
+        assert_eq!(error.source().unwrap().to_string(), "address in use");
+    }
+
+    #[test]
+    fn it_should_show_the_whole_chain_through_anyhow() {
+        let error = Error::Other(anyhow!("address in use").context("Failed to connect"));
+        let error = anyhow::Error::from(error);
+
+        assert_eq!(format!("{error:#}"), "Failed to connect: address in use");
+    }
+}
@@ -0,0 +1,249 @@
+use anyhow::anyhow;
+use anyhow::Context;
+use anyhow::Result;
+use serde_json::Value;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::sync::Arc;
+
+type ErrorBodyValidator = dyn Fn(&Value) -> Result<()> + Send + Sync;
+
+/// Describes the shape every error response body is expected to have.
+///
+/// When set using [`TestServerBuilder::expect_error_body_schema()`](crate::TestServerBuilder::expect_error_body_schema()),
+/// all responses with a 4xx or 5xx status code are checked against it,
+/// and the request will panic if the body does not match.
+///
+/// It can be built from a Json object, mapping field names to the type they must be.
+/// The types are `"string"`, `"number"`, `"boolean"`, `"object"`, `"array"`, `"null"`, and `"any"`.
+/// Additional fields in the body are allowed.
+///
+/// ```rust
+/// use axum_test::ErrorBodySchema;
+/// use serde_json::json;
+///
+/// let schema = ErrorBodySchema::try_from(json!({
+///     "code": "string",
+///     "message": "string",
+///     "trace_id": "string",
+/// })).unwrap();
+/// ```
+///
+/// Or from a closure, for checks a Json object cannot express:
+///
+/// ```rust
+/// use anyhow::ensure;
+/// use axum_test::ErrorBodySchema;
+/// use serde_json::Value;
+///
+/// let schema = ErrorBodySchema::from_fn(|body: &Value| {
+///     ensure!(body["code"].is_string(), "missing error code");
+///     Ok(())
+/// });
+/// ```
+#[derive(Clone)]
+pub struct ErrorBodySchema {
+    validator: Arc<ErrorBodyValidator>,
+}
+
+impl ErrorBodySchema {
+    /// Builds a schema from a closure,
+    /// which returns an error if the Json body given does not match.
+    pub fn from_fn<F>(validator: F) -> Self
+    where
+        F: Fn(&Value) -> Result<()> + Send + Sync + 'static,
+    {
+        Self {
+            validator: Arc::new(validator),
+        }
+    }
+
+    /// Checks the raw response body given matches this schema.
+    /// The body must be Json.
+    pub fn validate(&self, body: &[u8]) -> Result<()> {
+        let json = serde_json::from_slice::<Value>(body).with_context(|| {
+            format!(
+                "Expected error body to be Json, received '{}'",
+                String::from_utf8_lossy(body)
+            )
+        })?;
+
+        (self.validator)(&json)
+    }
+}
+
+impl TryFrom<Value> for ErrorBodySchema {
+    type Error = anyhow::Error;
+
+    fn try_from(schema: Value) -> Result<Self> {
+        let fields = match schema {
+            Value::Object(fields) => fields,
+            _ => {
+                return Err(anyhow!(
+                    "Error body schema must be a Json object, received {schema}"
+                ))
+            }
+        };
+
+        let mut field_types = Vec::with_capacity(fields.len());
+        for (field_name, field_type) in fields {
+            let field_type = field_type
+                .as_str()
+                .with_context(|| {
+                    format!("Error body schema type for field '{field_name}' must be a string")
+                })?
+                .to_string();
+            is_json_type(&Value::Null, &field_type)?;
+
+            field_types.push((field_name, field_type));
+        }
+
+        Ok(Self::from_fn(move |body| {
+            for (field_name, field_type) in &field_types {
+                let field = body
+                    .get(field_name)
+                    .with_context(|| format!("Error body is missing field '{field_name}'"))?;
+
+                if !is_json_type(field, field_type)? {
+                    return Err(anyhow!(
+                        "Error body field '{field_name}' expected to be {field_type}, received {field}"
+                    ));
+                }
+            }
+
+            Ok(())
+        }))
+    }
+}
+
+fn is_json_type(value: &Value, json_type: &str) -> Result<bool> {
+    let is_type = match json_type {
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "null" => value.is_null(),
+        "any" => true,
+        _ => return Err(anyhow!("Unknown error body schema type '{json_type}'")),
+    };
+
+    Ok(is_type)
+}
+
+impl Debug for ErrorBodySchema {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("ErrorBodySchema").finish_non_exhaustive()
+    }
+}
+
+/// Two schemas are equal when they share the same function,
+/// such as when one is cloned from the other.
+impl PartialEq for ErrorBodySchema {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.validator, &other.validator)
+    }
+}
+
+impl Eq for ErrorBodySchema {}
+
+#[cfg(test)]
+mod test_validate {
+    use super::*;
+    use serde_json::json;
+
+    fn new_schema() -> ErrorBodySchema {
+        ErrorBodySchema::try_from(json!({
+            "code": "string",
+            "message": "string",
+            "trace_id": "any",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn it_should_pass_for_body_matching_schema() {
+        let body = json!({ "code": "E1", "message": "oops", "trace_id": 123, "extra": true });
+
+        let result = new_schema().validate(body.to_string().as_bytes());
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn it_should_fail_for_missing_field() {
+        let body = json!({ "code": "E1", "message": "oops" });
+
+        let error = new_schema()
+            .validate(body.to_string().as_bytes())
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Error body is missing field 'trace_id'");
+    }
+
+    #[test]
+    fn it_should_fail_for_field_of_wrong_type() {
+        let body = json!({ "code": 1, "message": "oops", "trace_id": "abc" });
+
+        let error = new_schema()
+            .validate(body.to_string().as_bytes())
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Error body field 'code' expected to be string, received 1"
+        );
+    }
+
+    #[test]
+    fn it_should_fail_for_non_json_body() {
+        let error = new_schema().validate(b"Internal error").unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Expected error body to be Json, received 'Internal error'"
+        );
+    }
+
+    #[test]
+    fn it_should_run_closures() {
+        let schema = ErrorBodySchema::from_fn(|body| {
+            anyhow::ensure!(body.is_array(), "not an array");
+            Ok(())
+        });
+
+        assert!(schema.validate(b"[]").is_ok());
+        assert!(schema.validate(b"{}").is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_try_from {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn it_should_fail_for_non_object_schema() {
+        let error = ErrorBodySchema::try_from(json!(["code"])).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            r#"Error body schema must be a Json object, received ["code"]"#
+        );
+    }
+
+    #[test]
+    fn it_should_fail_for_unknown_field_type() {
+        let error = ErrorBodySchema::try_from(json!({ "code": "text" })).unwrap_err();
+
+        assert_eq!(error.to_string(), "Unknown error body schema type 'text'");
+    }
+
+    #[test]
+    fn it_should_fail_for_non_string_field_type() {
+        let error = ErrorBodySchema::try_from(json!({ "code": 1 })).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Error body schema type for field 'code' must be a string"
+        );
+    }
+}
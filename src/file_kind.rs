@@ -0,0 +1,101 @@
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+
+/// Common binary file formats, recognised by the signature at the start of their bytes.
+///
+/// This is used with [`TestResponse::assert_body_magic()`](crate::TestResponse::assert_body_magic()),
+/// as a cheap check that a binary endpoint returned the right kind of file.
+///
+/// ```rust
+/// use axum_test::FileKind;
+///
+/// assert!(FileKind::Png.is_match(b"\x89PNG\r\n\x1a\n..."));
+/// assert_eq!(FileKind::detect(b"%PDF-1.7\n..."), Some(FileKind::Pdf));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FileKind {
+    /// A zip archive, including formats built on zip such as `.docx` and `.jar`.
+    Zip,
+
+    /// A PNG image.
+    Png,
+
+    /// A gzip compressed file.
+    Gzip,
+
+    /// A PDF document.
+    Pdf,
+
+    /// A WebAssembly binary module.
+    Wasm,
+}
+
+impl FileKind {
+    const ALL: [Self; 5] = [Self::Zip, Self::Png, Self::Gzip, Self::Pdf, Self::Wasm];
+
+    /// The bytes every file of this kind starts with.
+    #[must_use]
+    pub fn magic_bytes(self) -> &'static [u8] {
+        match self {
+            Self::Zip => b"PK\x03\x04",
+            Self::Png => b"\x89PNG\r\n\x1a\n",
+            Self::Gzip => b"\x1f\x8b",
+            Self::Pdf => b"%PDF-",
+            Self::Wasm => b"\0asm",
+        }
+    }
+
+    /// Returns true if the bytes given start with the signature for this kind of file.
+    #[must_use]
+    pub fn is_match(self, bytes: &[u8]) -> bool {
+        bytes.starts_with(self.magic_bytes())
+    }
+
+    /// Returns the kind of file the bytes given start with, if it is recognised.
+    #[must_use]
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.is_match(bytes))
+    }
+}
+
+impl Display for FileKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let name = match self {
+            Self::Zip => "zip",
+            Self::Png => "png",
+            Self::Gzip => "gzip",
+            Self::Pdf => "pdf",
+            Self::Wasm => "wasm",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+#[cfg(test)]
+mod test_detect {
+    use super::*;
+
+    #[test]
+    fn it_should_detect_all_kinds_from_their_magic_bytes() {
+        for kind in FileKind::ALL {
+            let mut bytes = kind.magic_bytes().to_vec();
+            bytes.extend_from_slice(b"rest of the file");
+
+            assert_eq!(FileKind::detect(&bytes), Some(kind));
+        }
+    }
+
+    #[test]
+    fn it_should_not_detect_unknown_bytes() {
+        assert_eq!(FileKind::detect(b"hello world"), None);
+        assert_eq!(FileKind::detect(b""), None);
+    }
+
+    #[test]
+    fn it_should_not_match_truncated_signatures() {
+        assert!(!FileKind::Png.is_match(b"\x89PNG"));
+    }
+}
@@ -0,0 +1,62 @@
+use sha1::Sha1;
+use sha2::Digest;
+use sha2::Sha256;
+use sha2::Sha384;
+use sha2::Sha512;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+
+/// The hash used to compute a value from a response body,
+/// such as with [`TestResponse::assert_etag_matches_body_hash()`](crate::TestResponse::assert_etag_matches_body_hash()).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl HashAlgorithm {
+    pub(crate) fn digest(self, bytes: &[u8]) -> Vec<u8> {
+        match self {
+            Self::Sha1 => Sha1::digest(bytes).to_vec(),
+            Self::Sha256 => Sha256::digest(bytes).to_vec(),
+            Self::Sha384 => Sha384::digest(bytes).to_vec(),
+            Self::Sha512 => Sha512::digest(bytes).to_vec(),
+        }
+    }
+}
+
+impl Display for HashAlgorithm {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let name = match self {
+            Self::Sha1 => "SHA-1",
+            Self::Sha256 => "SHA-256",
+            Self::Sha384 => "SHA-384",
+            Self::Sha512 => "SHA-512",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+#[cfg(test)]
+mod test_digest {
+    use super::*;
+    use crate::internals::encode_hex;
+
+    #[test]
+    fn it_should_hash_using_the_algorithm() {
+        assert_eq!(
+            encode_hex(&HashAlgorithm::Sha1.digest(b"abc")),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(
+            encode_hex(&HashAlgorithm::Sha256.digest(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(HashAlgorithm::Sha384.digest(b"abc").len(), 48);
+        assert_eq!(HashAlgorithm::Sha512.digest(b"abc").len(), 64);
+    }
+}
@@ -0,0 +1,264 @@
+use http::HeaderName;
+use http::HeaderValue;
+use std::fmt::Debug;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::panic::catch_unwind;
+use std::panic::AssertUnwindSafe;
+
+use crate::internals::panic_message;
+use crate::internals::ExpectedState;
+use crate::TestResponse;
+use crate::TestServer;
+
+/// Sends a `GET` request for every combination of the header values given,
+/// created by [`TestServer::matrix()`](crate::TestServer::matrix()).
+///
+/// This is for testing content negotiation, where the response
+/// is expected to vary across headers such as `Accept` and `Accept-Language`.
+#[derive(Debug)]
+#[must_use = "the matrix must be run, by calling `run`"]
+pub struct HeaderMatrix<'a> {
+    server: &'a TestServer,
+    path: String,
+    variations: Vec<(HeaderName, Vec<HeaderValue>)>,
+}
+
+impl<'a> HeaderMatrix<'a> {
+    pub(crate) fn new(server: &'a TestServer, path: &str) -> Self {
+        Self {
+            server,
+            path: path.to_string(),
+            variations: Vec::new(),
+        }
+    }
+
+    /// Adds a header to vary, sending a request with each of the values given
+    /// for every combination of the other headers.
+    pub fn vary_header<N, I, V>(mut self, name: N, values: I) -> Self
+    where
+        N: TryInto<HeaderName>,
+        N::Error: Debug,
+        I: IntoIterator<Item = V>,
+        V: TryInto<HeaderValue>,
+        V::Error: Debug,
+    {
+        let header_name: HeaderName = name
+            .try_into()
+            .expect("Failed to convert header name to HeaderName");
+        let header_values = values
+            .into_iter()
+            .map(|value| {
+                value
+                    .try_into()
+                    .expect("Failed to convert header value to HeaderValue")
+            })
+            .collect();
+
+        self.variations.push((header_name, header_values));
+        self
+    }
+
+    /// Returns every combination of the headers, in the order they were added.
+    #[must_use]
+    pub fn combinations(&self) -> Vec<HeaderCombination> {
+        let mut combinations = vec![HeaderCombination::default()];
+
+        for (header_name, header_values) in &self.variations {
+            combinations = combinations
+                .into_iter()
+                .flat_map(|combination| {
+                    header_values.iter().map(move |header_value| {
+                        let mut headers = combination.headers.clone();
+                        headers.push((header_name.clone(), header_value.clone()));
+                        HeaderCombination { headers }
+                    })
+                })
+                .collect();
+        }
+
+        combinations
+    }
+
+    /// Sends a request for every combination, and calls the function given with each response.
+    ///
+    /// All combinations are sent, even when the function panics on one of them.
+    /// Once they are all sent, this panics listing every combination which failed.
+    pub async fn run<F>(self, mut assert_response: F)
+    where
+        F: FnMut(&TestResponse, &HeaderCombination),
+    {
+        let combinations = self.combinations();
+        let mut failures = Vec::new();
+
+        for combination in &combinations {
+            let mut request = self
+                .server
+                .get(&self.path)
+                .expect_state(ExpectedState::None);
+            for (header_name, header_value) in combination.headers() {
+                request = request.add_header(header_name.clone(), header_value.clone());
+            }
+            let response = request.await;
+
+            let result = catch_unwind(AssertUnwindSafe(|| {
+                assert_response(&response, combination);
+            }));
+            if let Err(panic) = result {
+                failures.push(format!("    {combination}: {}", panic_message(&*panic)));
+            }
+        }
+
+        if !failures.is_empty() {
+            panic!(
+                "{} of {} header combinations failed, for request GET {},\n{}",
+                failures.len(),
+                combinations.len(),
+                self.path,
+                failures.join("\n"),
+            );
+        }
+    }
+}
+
+/// One combination of headers sent by a [`HeaderMatrix`](crate::HeaderMatrix).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeaderCombination {
+    headers: Vec<(HeaderName, HeaderValue)>,
+}
+
+impl HeaderCombination {
+    /// Returns the headers sent, in the order they were added to the matrix.
+    #[must_use]
+    pub fn headers(&self) -> &[(HeaderName, HeaderValue)] {
+        &self.headers
+    }
+
+    /// Returns the value sent for the header given, if it was varied.
+    #[must_use]
+    pub fn get<N>(&self, name: N) -> Option<&HeaderValue>
+    where
+        N: TryInto<HeaderName>,
+        N::Error: Debug,
+    {
+        let header_name: HeaderName = name
+            .try_into()
+            .expect("Failed to convert header name to HeaderName");
+
+        self.headers
+            .iter()
+            .find(|(name, _)| *name == header_name)
+            .map(|(_, value)| value)
+    }
+}
+
+impl Display for HeaderCombination {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        if self.headers.is_empty() {
+            return write!(f, "(no headers)");
+        }
+
+        for (i, (header_name, header_value)) in self.headers.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{header_name}: {header_value:?}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_run {
+    use crate::TestServer;
+    use axum::http::header;
+    use axum::http::HeaderMap;
+    use axum::routing::get;
+    use axum::Router;
+    use std::sync::Mutex;
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new().route(
+            "/greeting",
+            get(|headers: HeaderMap| async move {
+                let language = headers
+                    .get(header::ACCEPT_LANGUAGE)
+                    .and_then(|value| value.to_str().ok())
+                    .unwrap_or("en");
+
+                match language {
+                    "de" => "Hallo",
+                    _ => "Hello",
+                }
+            }),
+        );
+
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_build_every_combination_in_order() {
+        let server = new_test_server();
+
+        let combinations = server
+            .matrix("/greeting")
+            .vary_header("accept", ["application/json", "text/html"])
+            .vary_header("accept-language", ["en", "de"])
+            .combinations()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            combinations,
+            vec![
+                r#"accept: "application/json", accept-language: "en""#,
+                r#"accept: "application/json", accept-language: "de""#,
+                r#"accept: "text/html", accept-language: "en""#,
+                r#"accept: "text/html", accept-language: "de""#,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn it_should_send_headers_for_each_combination() {
+        let server = new_test_server();
+        let received = Mutex::new(Vec::new());
+
+        server
+            .matrix("/greeting")
+            .vary_header("accept", ["application/json", "text/html"])
+            .vary_header("accept-language", ["en", "de"])
+            .run(|response, combination| {
+                let expected = match combination.get("accept-language").unwrap().as_bytes() {
+                    b"de" => "Hallo",
+                    _ => "Hello",
+                };
+                response.assert_text(expected);
+                received.lock().unwrap().push(response.text());
+            })
+            .await;
+
+        assert_eq!(
+            *received.lock().unwrap(),
+            vec!["Hello", "Hallo", "Hello", "Hallo"]
+        );
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "2 of 4 header combinations failed")]
+    async fn it_should_panic_listing_failed_combinations() {
+        let server = new_test_server();
+
+        server
+            .matrix("/greeting")
+            .vary_header("accept", ["application/json", "text/html"])
+            .vary_header("accept-language", ["en", "de"])
+            .run(|response, _| {
+                response.assert_text("Hello");
+            })
+            .await;
+    }
+}
@@ -0,0 +1,389 @@
+use scraper::Html;
+use url::Url;
+
+use crate::internals::check;
+use crate::internals::check_eq;
+use crate::internals::element_text;
+use crate::internals::parse_css_selector;
+use crate::internals::OrPanic;
+use crate::AssertionError;
+use crate::HtmlForm;
+
+/// A HTML response, parsed for querying and asserting with CSS selectors.
+///
+/// This is returned from [`TestResponse::html()`](crate::TestResponse::html()).
+///
+/// The HTML is parsed the same way browsers parse it, using the [`scraper`] crate,
+/// and selectors support the CSS syntax it supports.
+/// This includes pseudo classes such as `li:first-child` and `a:not(.next)`.
+///
+/// Text is compared with whitespace collapsed and trimmed,
+/// the same way a browser displays it.
+///
+/// ```rust
+/// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+/// #
+/// use axum::Router;
+/// use axum::response::Html;
+/// use axum::routing::get;
+/// use axum_test::TestServer;
+///
+/// let app = Router::new()
+///     .route(&"/", get(|| async {
+///         Html(r#"<h1>Welcome</h1><a class="next" href="/page/2">Next</a>"#)
+///     }));
+/// let server = TestServer::new(app)?;
+///
+/// let html = server.get(&"/").await.html();
+/// html.assert_selector_text("h1", "Welcome");
+///
+/// let next_page = html.select_attr("a.next", "href").unwrap();
+/// server.get(&next_page).await;
+/// #
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone)]
+pub struct HtmlDocument {
+    html: Html,
+    request_url: Url,
+    debug_request_format: String,
+}
+
+impl HtmlDocument {
+    pub(crate) fn new(html: &str, request_url: Url, debug_request_format: String) -> Self {
+        Self {
+            html: Html::parse_document(html),
+            request_url,
+            debug_request_format,
+        }
+    }
+
+    /// Returns the text of the first element matching the selector,
+    /// or `None` if nothing matches.
+    ///
+    /// This will panic if the selector is invalid.
+    #[must_use]
+    pub fn select_text(&self, selector: &str) -> Option<String> {
+        let selector = parse_css_selector(selector).unwrap();
+        let element = self.html.select(&selector).next()?;
+
+        Some(element_text(element))
+    }
+
+    /// Returns the text of all elements matching the selector, in document order.
+    ///
+    /// This will panic if the selector is invalid.
+    #[must_use]
+    pub fn select_all_text(&self, selector: &str) -> Vec<String> {
+        let selector = parse_css_selector(selector).unwrap();
+
+        self.html.select(&selector).map(element_text).collect()
+    }
+
+    /// Returns the value of an attribute, on the first element matching the selector
+    /// which has that attribute.
+    ///
+    /// This is useful for building follow up requests,
+    /// such as reading the `href` of a link, or the `action` of a form.
+    ///
+    /// This will panic if the selector is invalid.
+    #[must_use]
+    pub fn select_attr(&self, selector: &str, attribute: &str) -> Option<String> {
+        let selector = parse_css_selector(selector).unwrap();
+        let maybe_value = self
+            .html
+            .select(&selector)
+            .find_map(|element| element.value().attr(attribute))
+            .map(ToString::to_string);
+
+        maybe_value
+    }
+
+    /// Returns the form matching the selector, for filling in and submitting.
+    ///
+    /// See [`HtmlForm`] for details.
+    ///
+    /// This will panic if the selector is invalid,
+    /// or if it does not match a `<form>` element.
+    #[must_use]
+    pub fn form(&self, selector: &str) -> HtmlForm {
+        let debug_request_format = &self.debug_request_format;
+        let css_selector = parse_css_selector(selector).unwrap();
+        let maybe_form = self
+            .html
+            .select(&css_selector)
+            .find(|element| element.value().name() == "form");
+
+        let Some(form) = maybe_form else {
+            panic!("Expected a form matching '{selector}', none found, for request {debug_request_format}");
+        };
+
+        HtmlForm::from_element(form, &self.request_url)
+    }
+
+    /// Returns the `href` of the first `<a>` or `<link>` with the `rel` given,
+    /// or else of the first element matching it as a CSS selector.
+    pub(crate) fn find_link(&self, rel_or_selector: &str) -> Option<String> {
+        let links_selector = parse_css_selector("a[rel], link[rel]").unwrap();
+        let maybe_rel_href = self
+            .html
+            .select(&links_selector)
+            .filter(|element| {
+                element
+                    .value()
+                    .attr("rel")
+                    .unwrap_or_default()
+                    .split_whitespace()
+                    .any(|rel| rel.eq_ignore_ascii_case(rel_or_selector))
+            })
+            .find_map(|element| element.value().attr("href"));
+
+        if let Some(href) = maybe_rel_href {
+            return Some(href.to_string());
+        }
+
+        let css_selector = parse_css_selector(rel_or_selector).ok()?;
+        let maybe_selector_href = self
+            .html
+            .select(&css_selector)
+            .find_map(|element| element.value().attr("href"))
+            .map(ToString::to_string);
+
+        maybe_selector_href
+    }
+
+    /// Asserts at least one element matches the selector.
+    #[track_caller]
+    pub fn assert_selector_exists(&self, selector: &str) {
+        self.check_selector_exists(selector).or_panic()
+    }
+
+    /// Checks at least one element matches the selector.
+    ///
+    /// This is the non-panicking version of [`HtmlDocument::assert_selector_exists()`].
+    pub fn check_selector_exists(&self, selector: &str) -> Result<(), AssertionError> {
+        let css_selector = parse_css_selector(selector)?;
+        let debug_request_format = &self.debug_request_format;
+        let has_match = self.html.select(&css_selector).next().is_some();
+
+        check(
+            has_match,
+            format_args!(
+                "Expected an element matching '{selector}', none found, for request {debug_request_format}"
+            ),
+        )
+    }
+
+    /// Asserts the first element matching the selector has the text given.
+    ///
+    /// Whitespace within the element's text is collapsed and trimmed before comparing.
+    #[track_caller]
+    pub fn assert_selector_text(&self, selector: &str, expected_text: &str) {
+        self.check_selector_text(selector, expected_text).or_panic()
+    }
+
+    /// Checks the first element matching the selector has the text given.
+    ///
+    /// This is the non-panicking version of [`HtmlDocument::assert_selector_text()`].
+    pub fn check_selector_text(
+        &self,
+        selector: &str,
+        expected_text: &str,
+    ) -> Result<(), AssertionError> {
+        let css_selector = parse_css_selector(selector)?;
+        let debug_request_format = &self.debug_request_format;
+
+        let Some(element) = self.html.select(&css_selector).next() else {
+            return Err(AssertionError::new(format!(
+                "Expected an element matching '{selector}', none found, for request {debug_request_format}"
+            )));
+        };
+
+        check_eq(
+            expected_text,
+            element_text(element).as_str(),
+            format_args!(
+                "Expected text of element matching '{selector}' to match, for request {debug_request_format}"
+            ),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test_select {
+    use super::*;
+
+    const HTML: &str = r#"
+        <h1>
+            Welcome
+            back
+        </h1>
+        <ul>
+            <li><a class="page" href="/page/1">One</a></li>
+            <li><a class="page next" href="/page/2">Two</a></li>
+        </ul>
+        <p title="Caf&eacute; &ndash; &#x27;open&#39;">Caf&eacute; &mdash; &hellip;</p>
+    "#;
+
+    fn new_document() -> HtmlDocument {
+        HtmlDocument::new(
+            HTML,
+            Url::parse("http://localhost/").unwrap(),
+            "GET /".to_string(),
+        )
+    }
+
+    #[test]
+    fn it_should_select_text() {
+        let document = new_document();
+
+        assert_eq!(document.select_text("h1"), Some("Welcome back".to_string()));
+        assert_eq!(document.select_text("h2"), None);
+        assert_eq!(document.select_all_text("a.page"), vec!["One", "Two"]);
+    }
+
+    #[test]
+    fn it_should_select_attributes() {
+        let document = new_document();
+
+        assert_eq!(
+            document.select_attr("a.next", "href"),
+            Some("/page/2".to_string())
+        );
+        assert_eq!(document.select_attr("a.next", "title"), None);
+    }
+
+    #[test]
+    fn it_should_select_with_pseudo_classes() {
+        let document = new_document();
+
+        assert_eq!(
+            document.select_text("li:first-child a"),
+            Some("One".to_string())
+        );
+        assert_eq!(document.select_all_text("a:not(.next)"), vec!["One"]);
+        assert_eq!(
+            document.select_attr("li:last-of-type > a", "href"),
+            Some("/page/2".to_string())
+        );
+    }
+
+    #[test]
+    fn it_should_decode_named_and_numeric_entities() {
+        let document = new_document();
+
+        assert_eq!(document.select_text("p"), Some("Café — …".to_string()));
+        assert_eq!(
+            document.select_attr("p", "title"),
+            Some("Café – 'open'".to_string())
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn it_should_panic_on_invalid_selector() {
+        let _ = new_document().select_text("a[");
+    }
+}
+
+#[cfg(test)]
+mod test_assert_selector_exists {
+    use super::*;
+
+    fn new_document(html: &str) -> HtmlDocument {
+        HtmlDocument::new(
+            html,
+            Url::parse("http://localhost/").unwrap(),
+            "GET /".to_string(),
+        )
+    }
+
+    #[test]
+    fn it_should_pass_when_selector_matches() {
+        let document = new_document(r#"<form id="login"></form>"#);
+
+        document.assert_selector_exists("form#login");
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected an element matching 'form#signup', none found")]
+    fn it_should_fail_when_selector_does_not_match() {
+        let document = new_document(r#"<form id="login"></form>"#);
+
+        document.assert_selector_exists("form#signup");
+    }
+
+    #[test]
+    fn it_should_return_error_for_invalid_selector() {
+        let document = new_document(r#"<form id="login"></form>"#);
+        let error = document.check_selector_exists("form[").unwrap_err();
+
+        assert!(error
+            .message()
+            .contains("Failed to parse CSS selector 'form['"));
+    }
+}
+
+#[cfg(test)]
+mod test_assert_selector_text {
+    use super::*;
+
+    fn new_document(html: &str) -> HtmlDocument {
+        HtmlDocument::new(
+            html,
+            Url::parse("http://localhost/").unwrap(),
+            "GET /".to_string(),
+        )
+    }
+
+    #[test]
+    fn it_should_pass_when_text_matches() {
+        let document = new_document("<h1>  Welcome\n</h1>");
+
+        document.assert_selector_text("h1", "Welcome");
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected text of element matching 'h1' to match, for request GET /")]
+    fn it_should_fail_when_text_differs() {
+        let document = new_document("<h1>Goodbye</h1>");
+
+        document.assert_selector_text("h1", "Welcome");
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected an element matching 'h2', none found")]
+    fn it_should_fail_when_nothing_matches() {
+        let document = new_document("<h1>Welcome</h1>");
+
+        document.assert_selector_text("h2", "Welcome");
+    }
+}
+
+#[cfg(test)]
+mod test_form {
+    use super::*;
+
+    #[test]
+    fn it_should_return_form_matching_selector() {
+        let document = HtmlDocument::new(
+            r#"<form id="signup" action="/signup"></form><form id="login" action="/login"></form>"#,
+            Url::parse("http://localhost/").unwrap(),
+            "GET /".to_string(),
+        );
+
+        assert_eq!(document.form("#login").action(), "/login");
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected a form matching 'div', none found, for request GET /")]
+    fn it_should_panic_when_selector_is_not_a_form() {
+        let document = HtmlDocument::new(
+            "<div></div>",
+            Url::parse("http://localhost/").unwrap(),
+            "GET /".to_string(),
+        );
+
+        let _ = document.form("div");
+    }
+}
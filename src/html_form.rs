@@ -0,0 +1,422 @@
+use http::Method;
+use scraper::ElementRef;
+use url::Url;
+
+use crate::internals::element_text;
+use crate::internals::resolve_relative_url;
+use crate::TestRequest;
+use crate::TestServer;
+
+/// A form read from a HTML response, for filling in and submitting.
+///
+/// This is returned from [`HtmlDocument::form()`](crate::HtmlDocument::form()).
+/// It starts with the values already in the form,
+/// including hidden inputs such as CSRF tokens,
+/// checked checkboxes and radio buttons, and selected options.
+///
+/// The form is submitted to its `action`, using its `method`,
+/// in the same way a browser would.
+/// `GET` forms send their fields as query parameters,
+/// and all other forms send them as an url encoded body.
+///
+/// ```rust
+/// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+/// #
+/// use axum::Form;
+/// use axum::Router;
+/// use axum::response::Html;
+/// use axum::routing::get;
+/// use axum::routing::post;
+/// use axum_test::TestServer;
+/// use std::collections::HashMap;
+///
+/// let app = Router::new()
+///     .route(&"/login", get(|| async {
+///         Html(r#"
+///             <form id="login" action="/login" method="post">
+///                 <input type="hidden" name="csrf" value="abc123">
+///                 <input type="text" name="username">
+///                 <input type="password" name="password">
+///             </form>
+///         "#)
+///     }))
+///     .route(&"/login", post(|Form(fields): Form<HashMap<String, String>>| async move {
+///         format!("logged in as {}", fields["username"])
+///     }));
+/// let server = TestServer::new(app)?;
+///
+/// server.get(&"/login").await
+///     .html()
+///     .form("form#login")
+///     .set("username", "Joe")
+///     .set("password", "hunter2")
+///     .submit(&server)
+///     .await
+///     .assert_text("logged in as Joe");
+/// #
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HtmlForm {
+    action: String,
+    method: Method,
+    fields: Vec<(String, String)>,
+}
+
+impl HtmlForm {
+    pub(crate) fn from_element(form: ElementRef<'_>, page_url: &Url) -> Self {
+        let action = form
+            .value()
+            .attr("action")
+            .map(str::trim)
+            .unwrap_or_default();
+        let action = resolve_relative_url(page_url, action);
+
+        let method = match form.value().attr("method") {
+            Some(method) if method.eq_ignore_ascii_case("post") => Method::POST,
+            _ => Method::GET,
+        };
+
+        let fields = descendant_elements(form)
+            .filter(|element| element.value().attr("disabled").is_none())
+            .flat_map(read_field)
+            .collect();
+
+        Self {
+            action,
+            method,
+            fields,
+        }
+    }
+
+    /// The path the form will be submitted to.
+    #[must_use]
+    pub fn action(&self) -> &str {
+        &self.action
+    }
+
+    /// The method the form will be submitted with.
+    #[must_use]
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// The fields which will be submitted, in the order they will be sent.
+    #[must_use]
+    pub fn fields(&self) -> &[(String, String)] {
+        &self.fields
+    }
+
+    /// Returns the value of the first field with the name given.
+    #[must_use]
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(field_name, _)| field_name == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Sets the value of a field.
+    ///
+    /// This replaces the value of the first field with that name,
+    /// or adds the field if the form does not have it.
+    #[must_use]
+    pub fn set<N, V>(mut self, name: N, value: V) -> Self
+    where
+        N: Into<String>,
+        V: Into<String>,
+    {
+        let name = name.into();
+        let value = value.into();
+
+        match self
+            .fields
+            .iter_mut()
+            .find(|(field_name, _)| *field_name == name)
+        {
+            Some((_, field_value)) => *field_value = value,
+            None => self.fields.push((name, value)),
+        }
+
+        self
+    }
+
+    /// Removes all fields with the name given,
+    /// such as to uncheck a checkbox.
+    #[must_use]
+    pub fn remove(mut self, name: &str) -> Self {
+        self.fields.retain(|(field_name, _)| field_name != name);
+        self
+    }
+
+    /// Builds the request to submit this form to the server given.
+    pub fn submit(self, server: &TestServer) -> TestRequest {
+        let request = server.method(self.method.clone(), &self.action);
+
+        if self.method == Method::GET {
+            request.add_query_params(&self.fields)
+        } else {
+            request.form(&self.fields)
+        }
+    }
+}
+
+/// All elements within the element given, in document order.
+fn descendant_elements(element: ElementRef<'_>) -> impl Iterator<Item = ElementRef<'_>> {
+    element.descendants().skip(1).filter_map(ElementRef::wrap)
+}
+
+/// Returns the name and value(s) submitted for a form element,
+/// or nothing if it is not a field, or would not be submitted.
+fn read_field(element: ElementRef<'_>) -> Vec<(String, String)> {
+    let attribute = |name| element.value().attr(name);
+    let Some(name) = attribute("name").filter(|name| !name.is_empty()) else {
+        return Vec::new();
+    };
+    let name = name.to_string();
+
+    match element.value().name() {
+        "input" => {
+            let input_type = attribute("type").unwrap_or("text").to_ascii_lowercase();
+            let value = attribute("value").unwrap_or_default();
+
+            match input_type.as_str() {
+                "submit" | "button" | "image" | "reset" | "file" => Vec::new(),
+                "checkbox" | "radio" => {
+                    if attribute("checked").is_none() {
+                        return Vec::new();
+                    }
+
+                    let value = attribute("value").unwrap_or("on");
+                    vec![(name, value.to_string())]
+                }
+                _ => vec![(name, value.to_string())],
+            }
+        }
+        "textarea" => vec![(name, element.text().collect())],
+        "select" => {
+            let options: Vec<ElementRef<'_>> = descendant_elements(element)
+                .filter(|option| option.value().name() == "option")
+                .filter(|option| option.value().attr("disabled").is_none())
+                .collect();
+            let option_value = |option: &ElementRef<'_>| {
+                option
+                    .value()
+                    .attr("value")
+                    .map(ToString::to_string)
+                    .unwrap_or_else(|| element_text(*option))
+            };
+
+            let selected: Vec<(String, String)> = options
+                .iter()
+                .filter(|option| option.value().attr("selected").is_some())
+                .map(|option| (name.clone(), option_value(option)))
+                .collect();
+
+            let is_multiple = attribute("multiple").is_some();
+            if !selected.is_empty() || is_multiple {
+                return selected;
+            }
+
+            options
+                .first()
+                .map(|option| vec![(name, option_value(option))])
+                .unwrap_or_default()
+        }
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod test_from_element {
+    use super::*;
+    use crate::internals::parse_css_selector;
+    use scraper::Html;
+
+    fn parse_form(html: &str) -> HtmlForm {
+        let html = Html::parse_document(html);
+        let selector = parse_css_selector("form").unwrap();
+        let form = html.select(&selector).next().unwrap();
+        let page_url = Url::parse("http://localhost/users/new?step=2").unwrap();
+
+        HtmlForm::from_element(form, &page_url)
+    }
+
+    fn fields(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn it_should_read_action_and_method() {
+        let form = parse_form(r#"<form action="/login" method="POST"></form>"#);
+
+        assert_eq!(form.action(), "/login");
+        assert_eq!(form.method(), &Method::POST);
+    }
+
+    #[test]
+    fn it_should_default_to_get_on_the_current_page() {
+        let form = parse_form("<form></form>");
+
+        assert_eq!(form.action(), "/users/new?step=2");
+        assert_eq!(form.method(), &Method::GET);
+    }
+
+    #[test]
+    fn it_should_resolve_relative_actions() {
+        let form = parse_form(r#"<form action="create?draft=1#top"></form>"#);
+
+        assert_eq!(form.action(), "/users/create?draft=1");
+    }
+
+    #[test]
+    fn it_should_keep_actions_on_other_servers_absolute() {
+        let form = parse_form(r#"<form action="https://example.com/login"></form>"#);
+
+        assert_eq!(form.action(), "https://example.com/login");
+    }
+
+    #[test]
+    fn it_should_read_initial_field_values() {
+        let form = parse_form(
+            r#"
+            <form>
+                <input type="hidden" name="csrf" value="abc">
+                <input name="username" value="joe">
+                <input type="password" name="password">
+                <input type="checkbox" name="remember" checked>
+                <input type="checkbox" name="newsletter" value="yes">
+                <input type="radio" name="plan" value="free">
+                <input type="radio" name="plan" value="pro" checked>
+                <input type="submit" name="action" value="Save">
+                <input name="disabled" value="x" disabled>
+                <input value="no name">
+                <textarea name="bio">
+Hello &amp; welcome</textarea>
+                <select name="country">
+                    <option value="uk">UK</option>
+                    <option value="fr" selected>France</option>
+                </select>
+                <select name="language">
+                    <option>English</option>
+                    <option>French</option>
+                </select>
+                <select name="tags" multiple>
+                    <option selected>a</option>
+                    <option>b</option>
+                    <option selected>c</option>
+                </select>
+            </form>
+            "#,
+        );
+
+        assert_eq!(
+            form.fields(),
+            fields(&[
+                ("csrf", "abc"),
+                ("username", "joe"),
+                ("password", ""),
+                ("remember", "on"),
+                ("plan", "pro"),
+                ("bio", "Hello & welcome"),
+                ("country", "fr"),
+                ("language", "English"),
+                ("tags", "a"),
+                ("tags", "c"),
+            ])
+        );
+    }
+
+    #[test]
+    fn it_should_set_and_remove_fields() {
+        let form = parse_form(
+            r#"<form><input name="username" value="joe"><input type="checkbox" name="remember" checked></form>"#,
+        )
+        .set("username", "kate")
+        .set("password", "hunter2")
+        .remove("remember");
+
+        assert_eq!(
+            form.fields(),
+            fields(&[("username", "kate"), ("password", "hunter2")])
+        );
+        assert_eq!(form.field("username"), Some("kate"));
+        assert_eq!(form.field("remember"), None);
+    }
+}
+
+#[cfg(test)]
+mod test_submit {
+    use crate::TestServer;
+    use axum::extract::Query;
+    use axum::response::Html;
+    use axum::routing::get;
+    use axum::routing::post;
+    use axum::Form;
+    use axum::Router;
+    use std::collections::BTreeMap;
+
+    const LOGIN_PAGE: &str = r#"
+        <form id="login" action="/login" method="post">
+            <input type="hidden" name="csrf" value="abc123">
+            <input name="username">
+        </form>
+        <form id="search" action="/search">
+            <input name="q">
+        </form>
+    "#;
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .route("/", get(|| async { Html(LOGIN_PAGE) }))
+            .route(
+                "/login",
+                post(|Form(fields): Form<BTreeMap<String, String>>| async move {
+                    format!("{fields:?}")
+                }),
+            )
+            .route(
+                "/search",
+                get(
+                    |Query(fields): Query<BTreeMap<String, String>>| async move {
+                        format!("{fields:?}")
+                    },
+                ),
+            );
+
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_post_form_with_hidden_fields() {
+        let server = new_test_server();
+
+        server
+            .get("/")
+            .await
+            .html()
+            .form("form#login")
+            .set("username", "joe")
+            .submit(&server)
+            .await
+            .assert_text(r#"{"csrf": "abc123", "username": "joe"}"#);
+    }
+
+    #[tokio::test]
+    async fn it_should_send_get_form_as_query() {
+        let server = new_test_server();
+
+        server
+            .get("/")
+            .await
+            .html()
+            .form("form#search")
+            .set("q", "rust")
+            .submit(&server)
+            .await
+            .assert_text(r#"{"q": "rust"}"#);
+    }
+}
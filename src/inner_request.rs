@@ -0,0 +1,159 @@
+use anyhow::Context;
+use bytes::Bytes;
+use http::HeaderMap;
+use http::HeaderName;
+use http::HeaderValue;
+use http::Method;
+use http::Uri;
+use std::fmt::Debug;
+use std::fmt::Display;
+
+#[cfg(feature = "pretty-assertions")]
+use pretty_assertions::assert_eq;
+
+///
+/// The request as it was received by the inner handler,
+/// of a [`TestServer`](crate::TestServer) built using [`TestServer::for_layer()`](crate::TestServer::for_layer()).
+///
+/// This allows you to assert on what a layer changed, before the request reached the handler.
+///
+#[derive(Debug, Clone)]
+pub struct InnerRequest {
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap<HeaderValue>,
+    body: Bytes,
+}
+
+impl InnerRequest {
+    pub(crate) fn new(
+        method: Method,
+        uri: Uri,
+        headers: HeaderMap<HeaderValue>,
+        body: Bytes,
+    ) -> Self {
+        Self {
+            method,
+            uri,
+            headers,
+            body,
+        }
+    }
+
+    /// The method of the request the inner handler received.
+    #[must_use]
+    pub fn method(&self) -> Method {
+        self.method.clone()
+    }
+
+    /// The uri of the request the inner handler received.
+    #[must_use]
+    pub fn uri(&self) -> Uri {
+        self.uri.clone()
+    }
+
+    /// The headers of the request the inner handler received.
+    #[must_use]
+    pub fn headers(&self) -> &HeaderMap<HeaderValue> {
+        &self.headers
+    }
+
+    /// Finds a header with the given name.
+    /// If there are multiple headers with the same name,
+    /// then only the first will be returned.
+    ///
+    /// `None` is returned when no header was found.
+    #[must_use]
+    pub fn maybe_header<N>(&self, name: N) -> Option<HeaderValue>
+    where
+        N: TryInto<HeaderName>,
+        N::Error: Debug,
+    {
+        let header_name = name
+            .try_into()
+            .expect("Failed to build HeaderName from name given");
+        self.headers.get(header_name).map(|h| h.to_owned())
+    }
+
+    /// The raw body of the request the inner handler received.
+    #[must_use]
+    pub fn as_bytes(&self) -> &Bytes {
+        &self.body
+    }
+
+    /// The body of the request the inner handler received, as a UTF-8 string.
+    #[must_use]
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).to_string()
+    }
+
+    /// Asserts the header named was present on the request the inner handler received.
+    #[track_caller]
+    pub fn assert_contains_header<N>(&self, name: N)
+    where
+        N: TryInto<HeaderName> + Display + Clone,
+        N::Error: Debug,
+    {
+        let debug_header_name = name.clone();
+        let has_header = self.maybe_header(name).is_some();
+
+        assert!(
+            has_header,
+            "Expected header '{debug_header_name}' to be present in inner request, header was not found, for request {} {}",
+            self.method, self.uri
+        );
+    }
+
+    /// Asserts the header named was _not_ present on the request the inner handler received.
+    #[track_caller]
+    pub fn assert_not_contains_header<N>(&self, name: N)
+    where
+        N: TryInto<HeaderName> + Display + Clone,
+        N::Error: Debug,
+    {
+        let debug_header_name = name.clone();
+        let has_header = self.maybe_header(name).is_some();
+
+        assert!(
+            !has_header,
+            "Expected header '{debug_header_name}' to not be present in inner request, header was found, for request {} {}",
+            self.method, self.uri
+        );
+    }
+
+    /// Asserts the header named on the request the inner handler received matches the value given.
+    #[track_caller]
+    pub fn assert_header<N, V>(&self, name: N, value: V)
+    where
+        N: TryInto<HeaderName> + Display + Clone,
+        N::Error: Debug,
+        V: TryInto<HeaderValue>,
+        V::Error: Debug,
+    {
+        let debug_header_name = name.clone();
+        let expected_header_value = value
+            .try_into()
+            .expect("Could not turn given value into HeaderValue");
+        let found_header_value = self
+            .maybe_header(name)
+            .with_context(|| {
+                format!(
+                    "Expected header '{debug_header_name}' to be present in inner request, header was not found, for request {} {}",
+                    self.method, self.uri
+                )
+            })
+            .unwrap();
+
+        assert_eq!(expected_header_value, found_header_value);
+    }
+
+    /// Asserts the body the inner handler received matches the text given.
+    #[track_caller]
+    pub fn assert_text<C>(&self, expected: C)
+    where
+        C: AsRef<str>,
+    {
+        let expected_contents = expected.as_ref();
+        assert_eq!(expected_contents, &self.text());
+    }
+}
@@ -0,0 +1,92 @@
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::sync::Arc;
+
+use crate::internals::ProbeReading;
+
+/// A measurement taken around each request sent by a [`TestServer`](crate::TestServer),
+/// such as counting the database queries a handler runs.
+///
+/// Probes are added using [`TestServerBuilder::add_probe()`](crate::TestServerBuilder::add_probe()).
+/// Before each request is sent [`InstrumentationProbe::before_request()`] is called,
+/// and once the response is received [`InstrumentationProbe::after_request()`] is called.
+/// The reading it returns is kept on the response,
+/// and is retrieved using [`TestResponse::probe()`](crate::TestResponse::probe()).
+///
+/// Probes are shared by all requests made by the server,
+/// so readings are only reliable when requests are not sent concurrently.
+///
+/// See [`QueryCounter`](crate::QueryCounter) for a probe which counts queries.
+pub trait InstrumentationProbe: Send + Sync + 'static {
+    /// The reading taken for each request.
+    type Reading: Clone + Send + Sync + 'static;
+
+    /// Called before each request is sent.
+    fn before_request(&self);
+
+    /// Called after each response is received,
+    /// returning what was measured since [`InstrumentationProbe::before_request()`].
+    fn after_request(&self) -> Self::Reading;
+}
+
+trait ErasedProbe: Send + Sync {
+    fn before_request(&self);
+
+    fn after_request(&self) -> ProbeReading;
+}
+
+impl<P> ErasedProbe for P
+where
+    P: InstrumentationProbe,
+{
+    fn before_request(&self) {
+        InstrumentationProbe::before_request(self)
+    }
+
+    fn after_request(&self) -> ProbeReading {
+        ProbeReading::new(InstrumentationProbe::after_request(self))
+    }
+}
+
+/// An [`InstrumentationProbe`] held by a [`TestServerConfig`](crate::TestServerConfig).
+#[derive(Clone)]
+pub struct ProbeHandle {
+    probe: Arc<dyn ErasedProbe>,
+}
+
+impl ProbeHandle {
+    /// Wraps the probe given, for adding to a [`TestServerConfig`](crate::TestServerConfig).
+    pub fn new<P>(probe: P) -> Self
+    where
+        P: InstrumentationProbe,
+    {
+        Self {
+            probe: Arc::new(probe),
+        }
+    }
+
+    pub(crate) fn before_request(&self) {
+        self.probe.before_request()
+    }
+
+    pub(crate) fn after_request(&self) -> ProbeReading {
+        self.probe.after_request()
+    }
+}
+
+impl Debug for ProbeHandle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("ProbeHandle").finish_non_exhaustive()
+    }
+}
+
+/// Two handles are equal when they share the same function,
+/// such as when one is cloned from the other.
+impl PartialEq for ProbeHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.probe, &other.probe)
+    }
+}
+
+impl Eq for ProbeHandle {}
@@ -0,0 +1,217 @@
+use serde::Serialize;
+use std::fmt::Debug;
+use std::fmt::Display;
+
+use crate::internals::DataMask;
+use crate::AssertionError;
+
+/// Returns an error, with the message given, if the condition is false.
+pub fn check<M>(condition: bool, message: M) -> Result<(), AssertionError>
+where
+    M: Display,
+{
+    if condition {
+        Ok(())
+    } else {
+        Err(AssertionError::new(message.to_string()))
+    }
+}
+
+/// Returns an error if the two values are not equal.
+///
+/// The error includes the message given,
+/// followed by both values (as a diff when pretty assertions are enabled).
+pub fn check_eq<E, R, M>(expected: &E, received: &R, message: M) -> Result<(), AssertionError>
+where
+    E: PartialEq<R> + Debug + ?Sized,
+    R: Debug + ?Sized,
+    M: Display,
+{
+    if expected == received {
+        return Ok(());
+    }
+
+    #[cfg(feature = "pretty-assertions")]
+    {
+        let comparison = pretty_assertions::Comparison::new(expected, received);
+        Err(AssertionError::new(format!(
+            "{message}\n\nDiff < expected / received > :\n{comparison}"
+        )))
+    }
+
+    #[cfg(not(feature = "pretty-assertions"))]
+    {
+        Err(AssertionError::new(format!(
+            "{message}\n  expected: {expected:?}\n  received: {received:?}"
+        )))
+    }
+}
+
+/// Returns an error if the two texts are not equal, like [`check_eq()`],
+/// with the values at the masked Json paths hidden in the error when the texts are Json.
+pub fn check_text_eq<M>(
+    expected: &str,
+    received: &str,
+    data_mask: &DataMask,
+    message: M,
+) -> Result<(), AssertionError>
+where
+    M: Display,
+{
+    if expected == received {
+        return Ok(());
+    }
+
+    Err(masked_mismatch(
+        data_mask.masked_text(expected).as_ref(),
+        data_mask.masked_text(received).as_ref(),
+        message,
+    ))
+}
+
+/// Returns an error if the two values are not equal, like [`check_eq()`].
+///
+/// The values are shown as Json, with the values at the masked Json paths hidden in the error.
+pub fn check_serialized_eq<T, M>(
+    expected: &T,
+    received: &T,
+    data_mask: &DataMask,
+    message: M,
+) -> Result<(), AssertionError>
+where
+    T: PartialEq + Serialize + ?Sized,
+    M: Display,
+{
+    if expected == received {
+        return Ok(());
+    }
+
+    match (
+        serde_json::to_value(expected),
+        serde_json::to_value(received),
+    ) {
+        (Ok(expected), Ok(received)) => Err(masked_mismatch(
+            &data_mask.masked_json(&expected),
+            &data_mask.masked_json(&received),
+            message,
+        )),
+        _ => Err(AssertionError::new(format!(
+            "{message}, the values are not shown as they could not be masked"
+        ))),
+    }
+}
+
+/// Builds the error for two values which are not equal, which have already been masked.
+fn masked_mismatch<E, R, M>(expected: &E, received: &R, message: M) -> AssertionError
+where
+    E: PartialEq<R> + Debug + ?Sized,
+    R: Debug + ?Sized,
+    M: Display,
+{
+    match check_eq(expected, received, &message) {
+        Err(error) => error,
+        Ok(()) => AssertionError::new(format!("{message}, the differences are within masked data")),
+    }
+}
+
+/// Turns a failed check into a panic.
+pub trait OrPanic {
+    fn or_panic(self);
+}
+
+impl OrPanic for Result<(), AssertionError> {
+    #[track_caller]
+    fn or_panic(self) {
+        if let Err(error) = self {
+            panic!("{error}");
+        }
+    }
+}
+
+/// Unwraps a result, panicking with the message given and the error received.
+///
+/// Unlike `Result::expect`, the message is only built when it fails,
+/// so it can include details such as the request.
+/// Callers should be `#[track_caller]`, so the panic points at the users test.
+pub trait ExpectWith<T> {
+    fn expect_with<F, M>(self, message: F) -> T
+    where
+        F: FnOnce() -> M,
+        M: Display;
+}
+
+impl<T, E> ExpectWith<T> for Result<T, E>
+where
+    E: Debug,
+{
+    #[track_caller]
+    fn expect_with<F, M>(self, message: F) -> T
+    where
+        F: FnOnce() -> M,
+        M: Display,
+    {
+        match self {
+            Ok(value) => value,
+            Err(error) => panic!("{}, received {error:?}", message()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_check_eq {
+    use super::*;
+
+    #[test]
+    fn it_should_pass_for_equal_values() {
+        let result = check_eq("abc", "abc", "Expected text to match");
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn it_should_include_message_and_values_on_failure() {
+        let error = check_eq("abc", "xyz", "Expected text to match").unwrap_err();
+        let message = error.message();
+
+        assert!(message.starts_with("Expected text to match"));
+        assert!(message.contains("abc"));
+        assert!(message.contains("xyz"));
+    }
+}
+
+#[cfg(test)]
+mod test_or_panic {
+    use super::*;
+
+    #[test]
+    fn it_should_not_panic_on_ok() {
+        Ok(()).or_panic();
+    }
+
+    #[test]
+    #[should_panic(expected = "it failed")]
+    fn it_should_panic_with_message_on_error() {
+        Err(AssertionError::new("it failed")).or_panic();
+    }
+}
+
+#[cfg(test)]
+mod test_expect_with {
+    use super::*;
+
+    #[test]
+    fn it_should_return_value_when_ok() {
+        let result: Result<u32, String> = Ok(123);
+
+        assert_eq!(result.expect_with(|| "Failed to read number"), 123);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Failed to read number for request GET /todo, received \"not a number\""
+    )]
+    fn it_should_panic_with_message_and_error_when_err() {
+        let result: Result<u32, String> = Err("not a number".to_string());
+
+        result.expect_with(|| format!("Failed to read number for request {}", "GET /todo"));
+    }
+}
@@ -0,0 +1,130 @@
+use anyhow::Result;
+use axum::body::Body;
+use bytes::Bytes;
+use http::header;
+use http::HeaderValue;
+use http::Request;
+use http_body::Frame;
+use http_body_util::BodyExt;
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+
+/// The chunk size used for requests sent using `TestRequest::chunked()`.
+pub const DEFAULT_CHUNK_SIZE: usize = 8 * 1024;
+
+/// Overrides for how the body of a request is framed when sent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BodyFraming {
+    pub maybe_chunk_size: Option<usize>,
+    pub maybe_declared_content_length: Option<u64>,
+}
+
+impl BodyFraming {
+    /// Rewrites the request to be sent with the framing given.
+    ///
+    /// Chunked requests have their body split into a stream of chunks, with no known length,
+    /// and the declared content length replaces any `Content-Length` header regardless of the body.
+    pub async fn apply(self, request: Request<Body>) -> Result<Request<Body>> {
+        let (mut parts, mut body) = request.into_parts();
+
+        if let Some(chunk_size) = self.maybe_chunk_size {
+            let bytes = body.collect().await?.to_bytes();
+            body = Body::new(ChunkedBody::new(bytes, chunk_size));
+
+            parts.headers.remove(header::CONTENT_LENGTH);
+            parts.headers.insert(
+                header::TRANSFER_ENCODING,
+                HeaderValue::from_static("chunked"),
+            );
+        }
+
+        if let Some(content_length) = self.maybe_declared_content_length {
+            parts
+                .headers
+                .insert(header::CONTENT_LENGTH, HeaderValue::from(content_length));
+        }
+
+        Ok(Request::from_parts(parts, body))
+    }
+}
+
+/// A body which sends its contents as a series of chunks,
+/// without declaring its size up front.
+#[derive(Debug)]
+struct ChunkedBody {
+    chunks: VecDeque<Bytes>,
+}
+
+impl ChunkedBody {
+    fn new(mut bytes: Bytes, chunk_size: usize) -> Self {
+        let chunk_size = chunk_size.max(1);
+        let mut chunks = VecDeque::with_capacity(bytes.len().div_ceil(chunk_size));
+        while !bytes.is_empty() {
+            let chunk = bytes.split_to(chunk_size.min(bytes.len()));
+            chunks.push_back(chunk);
+        }
+
+        Self { chunks }
+    }
+}
+
+impl http_body::Body for ChunkedBody {
+    type Data = Bytes;
+    type Error = Infallible;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        Poll::Ready(self.chunks.pop_front().map(|chunk| Ok(Frame::data(chunk))))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.chunks.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test_apply {
+    use super::*;
+
+    fn new_request(body: &'static str) -> Request<Body> {
+        Request::builder()
+            .header(header::CONTENT_LENGTH, body.len())
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_split_body_into_chunks() {
+        let framing = BodyFraming {
+            maybe_chunk_size: Some(4),
+            ..BodyFraming::default()
+        };
+        let request = framing.apply(new_request("0123456789")).await.unwrap();
+
+        assert_eq!(request.headers()[header::TRANSFER_ENCODING], "chunked");
+        assert_eq!(request.headers().get(header::CONTENT_LENGTH), None);
+
+        let mut body = request.into_body();
+        let mut chunks = Vec::new();
+        while let Some(frame) = body.frame().await {
+            chunks.push(frame.unwrap().into_data().unwrap());
+        }
+        assert_eq!(chunks, vec!["0123", "4567", "89"]);
+    }
+
+    #[tokio::test]
+    async fn it_should_declare_content_length() {
+        let framing = BodyFraming {
+            maybe_declared_content_length: Some(100),
+            ..BodyFraming::default()
+        };
+        let request = framing.apply(new_request("0123456789")).await.unwrap();
+
+        assert_eq!(request.headers()[header::CONTENT_LENGTH], "100");
+    }
+}
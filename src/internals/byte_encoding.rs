@@ -0,0 +1,167 @@
+use anyhow::anyhow;
+use anyhow::Result;
+use base64::alphabet;
+use base64::engine::general_purpose::GeneralPurpose;
+use base64::engine::general_purpose::GeneralPurposeConfig;
+use base64::engine::DecodePaddingMode;
+use base64::Engine;
+
+/// Encodes the bytes as standard base64, with padding.
+pub fn encode_base64(bytes: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+/// Encodes the bytes as URL safe base64, without padding.
+#[cfg(feature = "jwe")]
+pub fn encode_base64_url(bytes: &[u8]) -> String {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Decodes base64 in either the standard or URL safe alphabet, but not a mix of the two.
+///
+/// Padding is optional, and whitespace (such as line breaks) is ignored.
+pub fn decode_base64(encoded: &str) -> Result<Vec<u8>> {
+    let without_whitespace = encoded
+        .bytes()
+        .filter(|b| !b.is_ascii_whitespace())
+        .collect::<Vec<u8>>();
+    let is_url_safe = without_whitespace.iter().any(|b| *b == b'-' || *b == b'_');
+    let alphabet = if is_url_safe {
+        &alphabet::URL_SAFE
+    } else {
+        &alphabet::STANDARD
+    };
+    let padding_mode = if without_whitespace.contains(&b'=') {
+        DecodePaddingMode::RequireCanonical
+    } else {
+        DecodePaddingMode::RequireNone
+    };
+
+    // Trailing bits which are not zero are rejected, as the config does not allow them by default.
+    let config = GeneralPurposeConfig::new().with_decode_padding_mode(padding_mode);
+    GeneralPurpose::new(alphabet, config)
+        .decode(without_whitespace)
+        .map_err(|err| anyhow!("Invalid base64 in '{encoded}', {err}"))
+}
+
+/// Encodes the bytes as lowercase hex.
+pub fn encode_hex(bytes: &[u8]) -> String {
+    hex::encode(bytes)
+}
+
+/// Decodes hex in either upper or lower case.
+///
+/// Whitespace (such as line breaks) is ignored.
+pub fn decode_hex(encoded: &str) -> Result<Vec<u8>> {
+    let without_whitespace = encoded
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace())
+        .collect::<String>();
+
+    hex::decode(without_whitespace).map_err(|err| anyhow!("Invalid hex in '{encoded}', {err}"))
+}
+
+#[cfg(test)]
+mod test_encode_base64 {
+    use super::*;
+
+    #[test]
+    fn it_should_encode_with_padding() {
+        assert_eq!(encode_base64(b""), "");
+        assert_eq!(encode_base64(b"f"), "Zg==");
+        assert_eq!(encode_base64(b"fo"), "Zm8=");
+        assert_eq!(encode_base64(b"foo"), "Zm9v");
+        assert_eq!(encode_base64(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[cfg(feature = "jwe")]
+    #[test]
+    fn it_should_encode_url_safe_without_padding() {
+        assert_eq!(encode_base64_url(b"f"), "Zg");
+        assert_eq!(encode_base64_url(&[251, 255]), "-_8");
+    }
+}
+
+#[cfg(test)]
+mod test_decode_base64 {
+    use super::*;
+
+    #[test]
+    fn it_should_decode_with_padding() {
+        assert_eq!(decode_base64("").unwrap(), b"");
+        assert_eq!(decode_base64("Zg==").unwrap(), b"f");
+        assert_eq!(decode_base64("Zm8=").unwrap(), b"fo");
+        assert_eq!(decode_base64("Zm9v").unwrap(), b"foo");
+        assert_eq!(decode_base64("Zm9vYmFy").unwrap(), b"foobar");
+    }
+
+    #[test]
+    fn it_should_decode_without_padding() {
+        assert_eq!(decode_base64("Zg").unwrap(), b"f");
+        assert_eq!(decode_base64("Zm8").unwrap(), b"fo");
+    }
+
+    #[test]
+    fn it_should_decode_the_url_safe_alphabet() {
+        assert_eq!(decode_base64("-_8=").unwrap(), [0xfb, 0xff]);
+        assert_eq!(decode_base64("+/8=").unwrap(), [0xfb, 0xff]);
+    }
+
+    #[test]
+    fn it_should_error_when_mixing_alphabets() {
+        assert!(decode_base64("+_8=").is_err());
+        assert!(decode_base64("-/8=").is_err());
+    }
+
+    #[test]
+    fn it_should_error_for_non_canonical_trailing_bits() {
+        assert!(decode_base64("Zh==").is_err());
+        assert!(decode_base64("Zm9=").is_err());
+    }
+
+    #[test]
+    fn it_should_ignore_whitespace() {
+        assert_eq!(decode_base64("Zm9v\r\nYmFy\n").unwrap(), b"foobar");
+    }
+
+    #[test]
+    fn it_should_error_for_invalid_base64() {
+        assert!(decode_base64("Zm9v!").is_err());
+        assert!(decode_base64("Z").is_err());
+        assert!(decode_base64("Zg=").is_err());
+        assert!(decode_base64("Zg==Zg==").is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_encode_hex {
+    use super::*;
+
+    #[test]
+    fn it_should_encode_as_lowercase() {
+        assert_eq!(encode_hex(b""), "");
+        assert_eq!(encode_hex(&[0x00, 0xab, 0xff]), "00abff");
+    }
+}
+
+#[cfg(test)]
+mod test_decode_hex {
+    use super::*;
+
+    #[test]
+    fn it_should_decode_either_case() {
+        assert_eq!(decode_hex("00abff").unwrap(), [0x00, 0xab, 0xff]);
+        assert_eq!(decode_hex("00ABFF").unwrap(), [0x00, 0xab, 0xff]);
+    }
+
+    #[test]
+    fn it_should_ignore_whitespace() {
+        assert_eq!(decode_hex("00 ab\nff").unwrap(), [0x00, 0xab, 0xff]);
+    }
+
+    #[test]
+    fn it_should_error_for_invalid_hex() {
+        assert!(decode_hex("0g").is_err());
+        assert!(decode_hex("abc").is_err());
+    }
+}
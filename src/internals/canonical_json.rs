@@ -0,0 +1,165 @@
+use serde_json::Number;
+use serde_json::Value;
+
+/// Serializes the Json value given in the canonical form of
+/// [RFC 8785](https://www.rfc-editor.org/rfc/rfc8785) (JCS).
+///
+/// Object keys are sorted by their UTF-16 code units,
+/// there is no whitespace, and numbers are written as ECMAScript would write them.
+pub fn format_canonical_json(value: &Value) -> String {
+    let mut output = String::new();
+    write_canonical_json(&mut output, value);
+    output
+}
+
+fn write_canonical_json(output: &mut String, value: &Value) {
+    match value {
+        Value::Null => output.push_str("null"),
+        Value::Bool(true) => output.push_str("true"),
+        Value::Bool(false) => output.push_str("false"),
+        Value::Number(number) => output.push_str(&format_canonical_number(number)),
+        Value::String(string) => write_canonical_string(output, string),
+        Value::Array(values) => {
+            output.push('[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    output.push(',');
+                }
+                write_canonical_json(output, value);
+            }
+            output.push(']');
+        }
+        Value::Object(object) => {
+            let mut entries = object.iter().collect::<Vec<_>>();
+            entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+
+            output.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    output.push(',');
+                }
+                write_canonical_string(output, key);
+                output.push(':');
+                write_canonical_json(output, value);
+            }
+            output.push('}');
+        }
+    }
+}
+
+fn write_canonical_string(output: &mut String, string: &str) {
+    output.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\u{08}' => output.push_str("\\b"),
+            '\u{09}' => output.push_str("\\t"),
+            '\u{0A}' => output.push_str("\\n"),
+            '\u{0C}' => output.push_str("\\f"),
+            '\u{0D}' => output.push_str("\\r"),
+            c if c < '\u{20}' => output.push_str(&format!("\\u{:04x}", c as u32)),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+}
+
+/// Numbers are held as IEEE 754 doubles under JCS,
+/// so integers beyond 2^53 lose precision, as they would in JavaScript.
+fn format_canonical_number(number: &Number) -> String {
+    let float = number.as_f64().unwrap_or_default();
+    if float == 0.0 {
+        return "0".to_string();
+    }
+
+    // The `{:e}` format gives the shortest digits which round trip,
+    // i.e. `1.2345e-7`, which is then laid out following ECMAScript's `Number.toString`.
+    let scientific = format!("{:e}", float.abs());
+    let (mantissa, exponent) = scientific
+        .split_once('e')
+        .expect("Scientific format should always have an exponent");
+    let digits = mantissa.replace('.', "");
+    let exponent = exponent
+        .parse::<i32>()
+        .expect("Scientific format exponent should be an integer");
+
+    let sign = if float < 0.0 { "-" } else { "" };
+    let num_digits = digits.len() as i32;
+    let decimal_point = exponent + 1;
+
+    let formatted = if num_digits <= decimal_point && decimal_point <= 21 {
+        format!(
+            "{digits}{}",
+            "0".repeat((decimal_point - num_digits) as usize)
+        )
+    } else if 0 < decimal_point && decimal_point <= 21 {
+        let (whole, fraction) = digits.split_at(decimal_point as usize);
+        format!("{whole}.{fraction}")
+    } else if -6 < decimal_point && decimal_point <= 0 {
+        format!("0.{}{digits}", "0".repeat((-decimal_point) as usize))
+    } else {
+        let (first, rest) = digits.split_at(1);
+        let fraction = if rest.is_empty() {
+            String::new()
+        } else {
+            format!(".{rest}")
+        };
+        let exponent_sign = if exponent < 0 { "-" } else { "+" };
+
+        format!("{first}{fraction}e{exponent_sign}{}", exponent.abs())
+    };
+
+    format!("{sign}{formatted}")
+}
+
+#[cfg(test)]
+mod test_format_canonical_json {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn it_should_sort_keys_and_remove_whitespace() {
+        let value = json!({ "b": [1, { "z": true, "a": null }], "a": "text" });
+
+        assert_eq!(
+            format_canonical_json(&value),
+            r#"{"a":"text","b":[1,{"a":null,"z":true}]}"#
+        );
+    }
+
+    #[test]
+    fn it_should_sort_keys_by_utf16_code_units() {
+        // '\u{10000}' is encoded as the surrogate pair 0xD800 0xDC00, which sorts before 0xFB33.
+        let value = json!({ "\u{FB33}": 1, "\u{10000}": 2, "\r": 3, "1": 4 });
+
+        assert_eq!(
+            format_canonical_json(&value),
+            "{\"\\r\":3,\"1\":4,\"\u{10000}\":2,\"\u{FB33}\":1}"
+        );
+    }
+
+    #[test]
+    fn it_should_only_escape_what_is_required() {
+        let value = json!("\u{20ac}$\u{000F}\u{000a}A'\u{0042}\u{0022}\u{005c}\\\"/");
+
+        assert_eq!(
+            format_canonical_json(&value),
+            "\"\u{20ac}$\\u000f\\nA'B\\\"\\\\\\\\\\\"/\""
+        );
+    }
+
+    #[test]
+    fn it_should_format_numbers_as_ecmascript() {
+        // Taken from the examples in RFC 8785, which have more precision than a double holds.
+        let value: Value = serde_json::from_str(
+            "[333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001, -0.0, 100, -7, 1e21, 1e20, 0.000001, 0.0000001]",
+        )
+        .unwrap();
+
+        assert_eq!(
+            format_canonical_json(&value),
+            "[333333333.3333333,1e+30,4.5,0.002,1e-27,0,100,-7,1e+21,100000000000000000000,0.000001,1e-7]"
+        );
+    }
+}
@@ -0,0 +1,201 @@
+use axum::body::Body;
+use axum::response::Response;
+use http::HeaderMap;
+use http::HeaderValue;
+use http::StatusCode;
+use std::any::Any;
+use std::future::Future;
+use std::panic::catch_unwind;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+
+use crate::internals::decode_base64;
+use crate::internals::encode_base64;
+
+/// Carries the message of a handler panic from the transport to the `TestRequest`,
+/// which removes it before the response is seen.
+///
+/// The message is base64 encoded, as it may not be a valid header value.
+const PANIC_MESSAGE_HEADER: &str = "x-axum-test-panic";
+
+/// Marks a request as sent by a `TestRequest`, which takes the panic message from the response.
+///
+/// This is removed before the request reaches the application,
+/// and responses to requests without it never carry the panic message.
+const CATCH_PANIC_REQUEST_HEADER: &str = "x-axum-test-catch-panic";
+
+/// Wraps a future, returning the message of any panic raised whilst polling it.
+///
+/// When not catching, panics are left to unwind as normal.
+pub struct CatchPanic<F> {
+    inner: Pin<Box<F>>,
+    is_catching: bool,
+}
+
+impl<F> CatchPanic<F> {
+    pub fn new(inner: F, is_catching: bool) -> Self {
+        Self {
+            inner: Box::pin(inner),
+            is_catching,
+        }
+    }
+}
+
+impl<F> Future for CatchPanic<F>
+where
+    F: Future,
+{
+    type Output = Result<F::Output, String>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if !self.is_catching {
+            return self.inner.as_mut().poll(cx).map(Ok);
+        }
+
+        let inner = self.inner.as_mut();
+        match catch_unwind(AssertUnwindSafe(|| inner.poll(cx))) {
+            Ok(Poll::Pending) => Poll::Pending,
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Err(panic) => Poll::Ready(Err(panic_message(panic.as_ref()))),
+        }
+    }
+}
+
+/// Returns the message a panic was raised with, where it has one.
+pub fn panic_message(panic: &(dyn Any + Send)) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        return message.to_string();
+    }
+
+    if let Some(message) = panic.downcast_ref::<String>() {
+        return message.clone();
+    }
+
+    "Box<dyn Any>".to_string()
+}
+
+/// Builds the `500 Internal Server Error` returned in place of a handler which panicked.
+///
+/// The message is only included when given,
+/// which is for requests marked using [`add_catch_panic_marker()`].
+pub fn build_panic_response(maybe_message: Option<&str>) -> Response {
+    let mut response = Response::new(Body::from("Handler panicked"));
+    *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+
+    if let Some(message) = maybe_message {
+        let header_value = HeaderValue::from_str(&encode_base64(message.as_bytes()))
+            .expect("Base64 should always be a valid header value");
+        response
+            .headers_mut()
+            .insert(PANIC_MESSAGE_HEADER, header_value);
+    }
+
+    response
+}
+
+/// Marks the request as wanting the panic message of its handler, if it panics.
+pub fn add_catch_panic_marker(headers: &mut HeaderMap) {
+    headers.insert(CATCH_PANIC_REQUEST_HEADER, HeaderValue::from_static("1"));
+}
+
+/// Removes the mark added by [`add_catch_panic_marker()`], returning if it was found.
+pub fn take_catch_panic_marker(headers: &mut HeaderMap) -> bool {
+    headers.remove(CATCH_PANIC_REQUEST_HEADER).is_some()
+}
+
+/// Removes the panic message added by [`build_panic_response()`], returning it if found.
+pub fn take_panic_message(headers: &mut HeaderMap) -> Option<String> {
+    let header_value = headers.remove(PANIC_MESSAGE_HEADER)?;
+    let message_bytes = header_value
+        .to_str()
+        .ok()
+        .and_then(|encoded| decode_base64(encoded).ok())?;
+
+    Some(String::from_utf8_lossy(&message_bytes).to_string())
+}
+
+#[cfg(test)]
+mod test_catch_panic {
+    use super::*;
+
+    #[tokio::test]
+    async fn it_should_return_the_output_when_not_panicking() {
+        let output = CatchPanic::new(async { 123 }, true).await;
+
+        assert_eq!(output, Ok(123));
+    }
+
+    #[tokio::test]
+    async fn it_should_return_str_panic_messages() {
+        let output = CatchPanic::new(async { panic!("oh no") }, true).await;
+
+        assert_eq!(output, Err::<(), _>("oh no".to_string()));
+    }
+
+    #[tokio::test]
+    async fn it_should_return_formatted_panic_messages() {
+        let code = 123;
+        let output = CatchPanic::new(async move { panic!("failed with {code}") }, true).await;
+
+        assert_eq!(output, Err::<(), _>("failed with 123".to_string()));
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "oh no")]
+    async fn it_should_not_catch_panics_when_not_catching() {
+        let _ = CatchPanic::new(async { panic!("oh no") }, false).await;
+    }
+}
+
+#[cfg(test)]
+mod test_take_panic_message {
+    use super::*;
+
+    #[test]
+    fn it_should_take_the_message_from_a_panic_response() {
+        let mut response = build_panic_response(Some("oh no\nnot again"));
+
+        let message = take_panic_message(response.headers_mut());
+
+        assert_eq!(message.as_deref(), Some("oh no\nnot again"));
+        assert!(response.headers().get(PANIC_MESSAGE_HEADER).is_none());
+    }
+
+    #[test]
+    fn it_should_not_add_the_message_when_not_given() {
+        let mut response = build_panic_response(None);
+
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(take_panic_message(response.headers_mut()), None);
+    }
+
+    #[test]
+    fn it_should_return_none_without_a_panic() {
+        let mut headers = HeaderMap::new();
+
+        assert_eq!(take_panic_message(&mut headers), None);
+    }
+}
+
+#[cfg(test)]
+mod test_take_catch_panic_marker {
+    use super::*;
+
+    #[test]
+    fn it_should_take_the_marker_added() {
+        let mut headers = HeaderMap::new();
+        add_catch_panic_marker(&mut headers);
+
+        assert!(take_catch_panic_marker(&mut headers));
+        assert!(headers.is_empty());
+    }
+
+    #[test]
+    fn it_should_return_false_without_the_marker() {
+        let mut headers = HeaderMap::new();
+
+        assert!(!take_catch_panic_marker(&mut headers));
+    }
+}
@@ -0,0 +1,125 @@
+use anyhow::anyhow;
+use anyhow::Result;
+use std::collections::HashMap;
+use url::Url;
+
+/// Replaces each `{{name}}` in the text given with the variable of that name,
+/// as used in Postman collections and Insomnia exports.
+///
+/// Insomnia's `{{ _.name }}` form is also supported.
+/// This returns an error if a variable is not found.
+pub fn substitute_collection_variables(
+    text: &str,
+    variables: &HashMap<String, String>,
+) -> Result<String> {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        let end = rest[start..]
+            .find("}}")
+            .ok_or_else(|| anyhow!("Unclosed variable in '{text}'"))?;
+        let name = rest[start + 2..start + end].trim();
+        let name = name.strip_prefix("_.").unwrap_or(name);
+        let value = variables
+            .get(name)
+            .ok_or_else(|| anyhow!("Unknown variable '{name}' in '{text}'"))?;
+
+        output.push_str(&rest[..start]);
+        output.push_str(value);
+        rest = &rest[start + end + 2..];
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// Turns the url of a collection request into a path for the `TestServer`,
+/// by removing the scheme and host of absolute urls.
+pub fn collection_url_to_path(url: &str) -> String {
+    if let Ok(parsed) = Url::parse(url) {
+        if parsed.has_host() {
+            return match parsed.query() {
+                Some(query) => format!("{}?{query}", parsed.path()),
+                None => parsed.path().to_string(),
+            };
+        }
+    }
+
+    if url.starts_with('/') {
+        return url.to_string();
+    }
+
+    // Urls without a scheme, such as `localhost:3000/users`.
+    let (first_segment, rest) = url.split_once('/').unwrap_or((url, ""));
+    let is_host = first_segment == "localhost" || first_segment.contains(['.', ':']);
+    if is_host {
+        format!("/{rest}")
+    } else {
+        format!("/{url}")
+    }
+}
+
+#[cfg(test)]
+mod test_substitute_collection_variables {
+    use super::*;
+
+    fn variables() -> HashMap<String, String> {
+        HashMap::from([
+            ("base_url".to_string(), "http://localhost".to_string()),
+            ("user_id".to_string(), "123".to_string()),
+        ])
+    }
+
+    #[test]
+    fn it_should_replace_postman_variables() {
+        let output =
+            substitute_collection_variables("{{base_url}}/users/{{user_id}}", &variables());
+        assert_eq!(output.unwrap(), "http://localhost/users/123");
+    }
+
+    #[test]
+    fn it_should_replace_insomnia_variables() {
+        let output = substitute_collection_variables("{{ _.base_url }}/users", &variables());
+        assert_eq!(output.unwrap(), "http://localhost/users");
+    }
+
+    #[test]
+    fn it_should_error_on_unknown_variables() {
+        let error =
+            substitute_collection_variables("/users/{{missing}}", &variables()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unknown variable 'missing' in '/users/{{missing}}'"
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_collection_url_to_path {
+    use super::*;
+
+    #[test]
+    fn it_should_strip_scheme_and_host() {
+        assert_eq!(
+            collection_url_to_path("https://api.example.com/users?page=2"),
+            "/users?page=2"
+        );
+    }
+
+    #[test]
+    fn it_should_keep_paths() {
+        assert_eq!(collection_url_to_path("/users"), "/users");
+        assert_eq!(collection_url_to_path("users"), "/users");
+    }
+
+    #[test]
+    fn it_should_strip_hosts_without_a_scheme() {
+        assert_eq!(collection_url_to_path("localhost:3000/users"), "/users");
+        assert_eq!(
+            collection_url_to_path("api.example.com/users/1"),
+            "/users/1"
+        );
+        assert_eq!(collection_url_to_path("localhost"), "/");
+    }
+}
@@ -0,0 +1,48 @@
+use cookie::CookieJar;
+
+/// Returns true if a cookie saved with the `Domain` given should be sent to the host.
+///
+/// This follows browsers, where the domain matches itself and all of its subdomains.
+pub fn is_cookie_domain_match(cookie_domain: &str, host: &str) -> bool {
+    let cookie_domain = cookie_domain.trim_start_matches('.').to_ascii_lowercase();
+    let host = host.to_ascii_lowercase();
+
+    host == cookie_domain || host.ends_with(&format!(".{cookie_domain}"))
+}
+
+/// Returns the cookies from the jar which would be sent to the host given.
+///
+/// Cookies without a `Domain` are sent to all hosts.
+pub fn filter_cookies_for_host(cookies: &CookieJar, host: &str) -> CookieJar {
+    let mut filtered = CookieJar::new();
+    for cookie in cookies.iter() {
+        let is_match = cookie
+            .domain()
+            .map_or(true, |domain| is_cookie_domain_match(domain, host));
+
+        if is_match {
+            filtered.add_original(cookie.clone());
+        }
+    }
+
+    filtered
+}
+
+#[cfg(test)]
+mod test_is_cookie_domain_match {
+    use super::*;
+
+    #[test]
+    fn it_should_match_the_domain_and_subdomains() {
+        assert!(is_cookie_domain_match("app.local", "app.local"));
+        assert!(is_cookie_domain_match(".app.local", "auth.app.local"));
+        assert!(is_cookie_domain_match("App.Local", "www.app.local"));
+    }
+
+    #[test]
+    fn it_should_not_match_other_domains() {
+        assert!(!is_cookie_domain_match("app.local", "otherapp.local"));
+        assert!(!is_cookie_domain_match("auth.app.local", "www.app.local"));
+        assert!(!is_cookie_domain_match("auth.app.local", "app.local"));
+    }
+}
@@ -0,0 +1,90 @@
+use anyhow::Context;
+use anyhow::Result;
+use cookie::Cookie;
+use http::header::SET_COOKIE;
+use http::HeaderMap;
+use http::HeaderName;
+use http::HeaderValue;
+
+use crate::CsrfConfig;
+
+/// The CSRF token last seen by the `TestServer`,
+/// and the names used to send it.
+#[derive(Debug, Clone)]
+pub struct CsrfState {
+    cookie_name: String,
+    header_name: HeaderName,
+    maybe_token: Option<String>,
+}
+
+/// The CSRF token to send with a single request.
+#[derive(Debug, Clone)]
+pub struct CsrfToken {
+    pub cookie: Cookie<'static>,
+    pub header_name: HeaderName,
+    pub header_value: HeaderValue,
+}
+
+impl CsrfState {
+    pub fn new(config: CsrfConfig) -> Result<Self> {
+        let header_name = HeaderName::try_from(&config.header).with_context(|| {
+            format!("Failed to build CSRF header name, from '{}'", config.header)
+        })?;
+
+        Ok(Self {
+            cookie_name: config.cookie,
+            header_name,
+            maybe_token: None,
+        })
+    }
+
+    /// Returns the same CSRF settings, without the token received so far.
+    pub fn without_token(&self) -> Self {
+        Self {
+            cookie_name: self.cookie_name.clone(),
+            header_name: self.header_name.clone(),
+            maybe_token: None,
+        }
+    }
+
+    /// Stores the token found in the response headers, if there is one.
+    ///
+    /// The header is preferred over the cookie, when a response carries both.
+    pub fn update_from_headers(&mut self, headers: &HeaderMap) {
+        let maybe_header_token = headers
+            .get(&self.header_name)
+            .and_then(|value| value.to_str().ok())
+            .map(ToString::to_string);
+
+        let maybe_cookie_token = || {
+            headers
+                .get_all(SET_COOKIE)
+                .iter()
+                .rev()
+                .filter_map(|value| value.to_str().ok())
+                .filter_map(|value| Cookie::parse(value).ok())
+                .find(|cookie| cookie.name() == self.cookie_name)
+                .map(|cookie| cookie.value().to_string())
+        };
+
+        if let Some(token) = maybe_header_token.or_else(maybe_cookie_token) {
+            self.maybe_token = Some(token);
+        }
+    }
+
+    /// Returns the token to send, if one has been received.
+    pub fn token(&self) -> Result<Option<CsrfToken>> {
+        let Some(token) = &self.maybe_token else {
+            return Ok(None);
+        };
+
+        let header_value = HeaderValue::from_str(token)
+            .with_context(|| format!("Failed to build CSRF header value, from token '{token}'"))?;
+
+        Ok(Some(CsrfToken {
+            cookie: Cookie::new(self.cookie_name.clone(), token.clone()),
+            header_name: self.header_name.clone(),
+            header_value,
+        }))
+    }
+}
@@ -0,0 +1,366 @@
+use anyhow::anyhow;
+use anyhow::Context;
+use anyhow::Result;
+use http::Method;
+
+use crate::internals::collection_url_to_path;
+use crate::internals::encode_base64;
+
+/// The parts of a `curl` command needed to build a request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CurlCommand {
+    pub method: Method,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub maybe_body: Option<String>,
+}
+
+/// Parses the common options of a `curl` command, such as
+/// `curl -X POST -H 'content-type: application/json' -d '{"name":"Joe"}' http://localhost/users`.
+///
+/// Options which only change how curl runs (i.e. `-s`, `-v`, `-L`, `-k`) are ignored.
+/// Unknown options return an error, rather than being silently dropped.
+pub fn parse_curl_command(command: &str) -> Result<CurlCommand> {
+    let args = split_shell_words(command)?;
+    let mut args = args.into_iter().peekable();
+    if args.peek().map(String::as_str) == Some("curl") {
+        args.next();
+    }
+
+    let mut maybe_method = None;
+    let mut maybe_url = None;
+    let mut headers = Vec::new();
+    let mut data = Vec::new();
+    let mut is_get = false;
+
+    while let Some(arg) = args.next() {
+        // Supports `--request POST` and `--request=POST`,
+        // and short options with the value attached, such as `-XPOST`.
+        let (flag, maybe_inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => {
+                (flag.to_string(), Some(value.to_string()))
+            }
+            _ => match split_short_option_value(&arg) {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg.clone(), None),
+            },
+        };
+        let mut value = || {
+            maybe_inline_value
+                .clone()
+                .or_else(|| args.next())
+                .with_context(|| format!("Expected a value after curl option '{flag}'"))
+        };
+
+        match flag.as_str() {
+            "-X" | "--request" => {
+                let method = value()?;
+                maybe_method = Some(
+                    Method::from_bytes(method.as_bytes())
+                        .with_context(|| format!("Unknown method '{method}'"))?,
+                );
+            }
+            "-H" | "--header" => {
+                let header = value()?;
+                let (name, header_value) = header.split_once(':').with_context(|| {
+                    format!("Expected header 'name: value', received '{header}'")
+                })?;
+                headers.push((name.trim().to_string(), header_value.trim().to_string()));
+            }
+            "--data-raw" => data.push(value()?),
+            "-d" | "--data" | "--data-binary" | "--data-ascii" => {
+                data.push(data_value(&flag, value()?)?)
+            }
+            "--json" => {
+                data.push(data_value(&flag, value()?)?);
+                headers.push(("content-type".to_string(), "application/json".to_string()));
+                headers.push(("accept".to_string(), "application/json".to_string()));
+            }
+            "-u" | "--user" => {
+                let credentials = value()?;
+                headers.push((
+                    "authorization".to_string(),
+                    format!("Basic {}", encode_base64(credentials.as_bytes())),
+                ));
+            }
+            "-b" | "--cookie" => headers.push(("cookie".to_string(), value()?)),
+            "-A" | "--user-agent" => headers.push(("user-agent".to_string(), value()?)),
+            "-e" | "--referer" => headers.push(("referer".to_string(), value()?)),
+            "--url" => maybe_url = Some(value()?),
+            "-G" | "--get" => is_get = true,
+            "-I" | "--head" => maybe_method = Some(Method::HEAD),
+            "-s" | "-S" | "-sS" | "-v" | "-i" | "-k" | "-L" | "-f" | "--silent"
+            | "--show-error" | "--verbose" | "--include" | "--insecure" | "--location"
+            | "--fail" | "--compressed" => {}
+            "-o" | "--output" | "-m" | "--max-time" | "--connect-timeout" => {
+                value()?;
+            }
+            flag if flag.starts_with('-') && flag.len() > 1 => {
+                return Err(anyhow!("Unsupported curl option '{flag}'"));
+            }
+            _ => {
+                if maybe_url.is_some() {
+                    return Err(anyhow!(
+                        "Expected one url in curl command, found another '{arg}'"
+                    ));
+                }
+                maybe_url = Some(arg);
+            }
+        }
+    }
+
+    let url = maybe_url.context("Expected curl command to have a url")?;
+    let mut path = collection_url_to_path(&url);
+    let maybe_data = (!data.is_empty()).then(|| data.join("&"));
+
+    // With `-G` the data is sent as the query.
+    if is_get {
+        if let Some(data) = &maybe_data {
+            let separator = if path.contains('?') { '&' } else { '?' };
+            path = format!("{path}{separator}{data}");
+        }
+
+        return Ok(CurlCommand {
+            method: maybe_method.unwrap_or(Method::GET),
+            path,
+            headers,
+            maybe_body: None,
+        });
+    }
+
+    let has_content_type = headers
+        .iter()
+        .any(|(name, _)| name.eq_ignore_ascii_case("content-type"));
+    if maybe_data.is_some() && !has_content_type {
+        headers.push((
+            "content-type".to_string(),
+            "application/x-www-form-urlencoded".to_string(),
+        ));
+    }
+
+    let default_method = if maybe_data.is_some() {
+        Method::POST
+    } else {
+        Method::GET
+    };
+
+    Ok(CurlCommand {
+        method: maybe_method.unwrap_or(default_method),
+        path,
+        headers,
+        maybe_body: maybe_data,
+    })
+}
+
+/// The short options which take a value, which curl allows to be attached (i.e. `-XPOST`).
+const SHORT_OPTIONS_WITH_VALUES: [&str; 9] = ["-X", "-H", "-d", "-u", "-b", "-A", "-e", "-o", "-m"];
+
+/// Splits a short option with its value attached, such as `-XPOST`, into the option and value.
+fn split_short_option_value(arg: &str) -> Option<(&str, &str)> {
+    if arg.starts_with("--") || arg.len() <= 2 {
+        return None;
+    }
+
+    let flag = arg.get(..2)?;
+    let value = arg.get(2..)?;
+    SHORT_OPTIONS_WITH_VALUES
+        .contains(&flag)
+        .then_some((flag, value))
+}
+
+/// Curl reads data starting with `@` from a file, which is not supported.
+fn data_value(flag: &str, value: String) -> Result<String> {
+    if value.starts_with('@') {
+        return Err(anyhow!(
+            "Reading data from a file is not supported, for curl option '{flag} {value}', use '--data-raw' or pass the data inline"
+        ));
+    }
+
+    Ok(value)
+}
+
+/// Splits a command into words as a POSIX shell would,
+/// handling single quotes, double quotes, backslash escapes, and line continuations.
+fn split_shell_words(command: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut maybe_word: Option<String> = None;
+    let mut chars = command.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                let word = maybe_word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err(anyhow!("Unclosed single quote in curl command")),
+                    }
+                }
+            }
+            '"' => {
+                let word = maybe_word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => word.push(c),
+                            Some('\n') => {}
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => return Err(anyhow!("Unclosed double quote in curl command")),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err(anyhow!("Unclosed double quote in curl command")),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                Some('\n') | Some('\r') | None => {}
+                Some(c) => maybe_word.get_or_insert_with(String::new).push(c),
+            },
+            c if c.is_whitespace() => {
+                if let Some(word) = maybe_word.take() {
+                    words.push(word);
+                }
+            }
+            c => maybe_word.get_or_insert_with(String::new).push(c),
+        }
+    }
+
+    words.extend(maybe_word);
+    Ok(words)
+}
+
+#[cfg(test)]
+mod test_parse_curl_command {
+    use super::*;
+
+    #[test]
+    fn it_should_parse_method_headers_and_data() {
+        let command = parse_curl_command(
+            r#"curl -X PUT -H 'content-type: application/json' -H "x-api-key: abc" -d '{"name":"Joe"}' http://localhost:3000/users/1"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            command,
+            CurlCommand {
+                method: Method::PUT,
+                path: "/users/1".to_string(),
+                headers: vec![
+                    ("content-type".to_string(), "application/json".to_string()),
+                    ("x-api-key".to_string(), "abc".to_string()),
+                ],
+                maybe_body: Some(r#"{"name":"Joe"}"#.to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn it_should_default_to_form_posts_when_sending_data() {
+        let command = parse_curl_command("curl -d name=Joe -d age=30 /users").unwrap();
+
+        assert_eq!(command.method, Method::POST);
+        assert_eq!(command.maybe_body.as_deref(), Some("name=Joe&age=30"));
+        assert_eq!(
+            command.headers,
+            vec![(
+                "content-type".to_string(),
+                "application/x-www-form-urlencoded".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn it_should_send_data_as_query_with_get() {
+        let command =
+            parse_curl_command("curl -G -d page=2 --data-raw size=10 '/users?sort=name'").unwrap();
+
+        assert_eq!(command.method, Method::GET);
+        assert_eq!(command.path, "/users?sort=name&page=2&size=10");
+        assert_eq!(command.maybe_body, None);
+    }
+
+    #[test]
+    fn it_should_handle_line_continuations_and_ignored_flags() {
+        let command = parse_curl_command(
+            "curl -sS --location \\\n  --request=DELETE \\\n  --user joe:secret \\\n  'https://api.example.com/users/1'",
+        )
+        .unwrap();
+
+        assert_eq!(command.method, Method::DELETE);
+        assert_eq!(command.path, "/users/1");
+        assert_eq!(
+            command.headers,
+            vec![(
+                "authorization".to_string(),
+                "Basic am9lOnNlY3JldA==".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn it_should_parse_short_options_with_attached_values() {
+        let command = parse_curl_command(
+            r#"curl -XPOST -H'Accept: application/json' -d'{"name":"Joe"}' /users"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            command,
+            CurlCommand {
+                method: Method::POST,
+                path: "/users".to_string(),
+                headers: vec![
+                    ("Accept".to_string(), "application/json".to_string()),
+                    (
+                        "content-type".to_string(),
+                        "application/x-www-form-urlencoded".to_string()
+                    ),
+                ],
+                maybe_body: Some(r#"{"name":"Joe"}"#.to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn it_should_parse_attached_method() {
+        let command = parse_curl_command("curl -XDELETE /users/1").unwrap();
+
+        assert_eq!(command.method, Method::DELETE);
+        assert_eq!(command.path, "/users/1");
+    }
+
+    #[test]
+    fn it_should_error_on_data_from_a_file() {
+        let error = parse_curl_command("curl -d @body.json /users").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Reading data from a file is not supported, for curl option '-d @body.json', use '--data-raw' or pass the data inline"
+        );
+
+        assert!(parse_curl_command("curl -d@body.json /users").is_err());
+        assert!(parse_curl_command("curl --json @body.json /users").is_err());
+    }
+
+    #[test]
+    fn it_should_keep_data_raw_starting_with_at() {
+        let command = parse_curl_command("curl --data-raw @joe /users").unwrap();
+        assert_eq!(command.maybe_body.as_deref(), Some("@joe"));
+    }
+
+    #[test]
+    fn it_should_error_on_unsupported_options() {
+        let error = parse_curl_command("curl -F file=@photo.png /upload").unwrap_err();
+        assert_eq!(error.to_string(), "Unsupported curl option '-F'");
+    }
+
+    #[test]
+    fn it_should_error_without_a_url() {
+        let error = parse_curl_command("curl -X GET").unwrap_err();
+        assert_eq!(error.to_string(), "Expected curl command to have a url");
+    }
+}
@@ -0,0 +1,288 @@
+use http::header;
+use http::HeaderName;
+use serde_json::Value;
+use std::borrow::Cow;
+
+use crate::Error;
+
+/// Headers whose values are always masked when describing a request.
+const DEFAULT_MASKED_HEADERS: [HeaderName; 3] = [
+    header::AUTHORIZATION,
+    header::PROXY_AUTHORIZATION,
+    header::COOKIE,
+];
+
+/// The value shown in place of masked data.
+pub const MASKED_VALUE: &str = "***";
+
+/// The headers and Json values to hide in output produced by the crate,
+/// such as panic messages, so secrets do not leak into logs.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DataMask {
+    headers: Vec<HeaderName>,
+    json_paths: Vec<Vec<JsonPathSegment>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum JsonPathSegment {
+    Key(String),
+    Index(usize),
+    Wildcard,
+    RecursiveKey(String),
+}
+
+impl DataMask {
+    pub fn new(headers: &[String], json_paths: &[String]) -> Result<Self, Error> {
+        let headers = headers
+            .iter()
+            .map(|header_name| {
+                HeaderName::try_from(header_name.as_str()).map_err(|_| Error::InvalidConfig {
+                    message: format!("Masked header '{header_name}' is not a valid header name"),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let json_paths = json_paths
+            .iter()
+            .map(|json_path| {
+                parse_json_path(json_path).map_err(|message| Error::InvalidConfig {
+                    message: format!("Masked Json path '{json_path}' is invalid, {message}"),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            headers,
+            json_paths,
+        })
+    }
+
+    /// Returns true if values of this header should be hidden.
+    pub fn is_masked_header(&self, header_name: &HeaderName) -> bool {
+        DEFAULT_MASKED_HEADERS.contains(header_name) || self.headers.contains(header_name)
+    }
+
+    /// Replaces the values at the masked Json paths with `***`.
+    pub fn mask_json(&self, value: &mut Value) {
+        self.mask_json_with(value, MASKED_VALUE);
+    }
+
+    /// Replaces the values at the masked Json paths with the placeholder given.
+    pub fn mask_json_with(&self, value: &mut Value, placeholder: &str) {
+        for json_path in &self.json_paths {
+            mask_json_path(value, json_path, placeholder);
+        }
+    }
+
+    /// Returns true if any Json paths are masked.
+    pub fn has_json_paths(&self) -> bool {
+        !self.json_paths.is_empty()
+    }
+
+    /// Returns a copy of the Json given, with the values at the masked Json paths replaced.
+    pub fn masked_json(&self, value: &Value) -> Value {
+        let mut masked = value.clone();
+        self.mask_json(&mut masked);
+        masked
+    }
+
+    /// Returns the text given, with the values at the masked Json paths replaced
+    /// when the text is Json. Other text is returned unchanged.
+    pub fn masked_text<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if !self.has_json_paths() {
+            return Cow::Borrowed(text);
+        }
+
+        let Ok(value) = serde_json::from_str::<Value>(text) else {
+            return Cow::Borrowed(text);
+        };
+
+        let masked = self.masked_json(&value);
+        if masked == value {
+            return Cow::Borrowed(text);
+        }
+
+        Cow::Owned(masked.to_string())
+    }
+}
+
+/// Parses paths such as `$.password`, `$.users[*].token`, `$.items[0]`, and `$..secret`.
+fn parse_json_path(json_path: &str) -> Result<Vec<JsonPathSegment>, String> {
+    let mut rest = json_path
+        .strip_prefix('$')
+        .ok_or_else(|| "it must start with '$'".to_string())?;
+    let mut segments = Vec::new();
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("..") {
+            let (key, after) = split_key(after)?;
+            segments.push(JsonPathSegment::RecursiveKey(key.to_string()));
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix('.') {
+            let (key, after) = split_key(after)?;
+            segments.push(match key {
+                "*" => JsonPathSegment::Wildcard,
+                key => JsonPathSegment::Key(key.to_string()),
+            });
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let (inner, after) = after
+                .split_once(']')
+                .ok_or_else(|| "it has a '[' without a closing ']'".to_string())?;
+            segments.push(parse_bracket_segment(inner)?);
+            rest = after;
+        } else {
+            return Err(format!("unexpected '{rest}'"));
+        }
+    }
+
+    if segments.is_empty() {
+        return Err("it must select a value within the Json".to_string());
+    }
+
+    Ok(segments)
+}
+
+fn split_key(path: &str) -> Result<(&str, &str), String> {
+    let end = path.find(['.', '[']).unwrap_or(path.len());
+    let (key, rest) = path.split_at(end);
+    if key.is_empty() {
+        return Err("it has an empty key".to_string());
+    }
+
+    Ok((key, rest))
+}
+
+fn parse_bracket_segment(inner: &str) -> Result<JsonPathSegment, String> {
+    if inner == "*" {
+        return Ok(JsonPathSegment::Wildcard);
+    }
+
+    let maybe_quoted_key = inner
+        .strip_prefix('\'')
+        .and_then(|key| key.strip_suffix('\''))
+        .or_else(|| {
+            inner
+                .strip_prefix('"')
+                .and_then(|key| key.strip_suffix('"'))
+        });
+    if let Some(key) = maybe_quoted_key {
+        return Ok(JsonPathSegment::Key(key.to_string()));
+    }
+
+    inner
+        .parse::<usize>()
+        .map(JsonPathSegment::Index)
+        .map_err(|_| format!("'[{inner}]' is not an index, '*', or quoted key"))
+}
+
+fn mask_json_path(value: &mut Value, segments: &[JsonPathSegment], placeholder: &str) {
+    let Some((segment, rest)) = segments.split_first() else {
+        *value = Value::String(placeholder.to_string());
+        return;
+    };
+
+    match segment {
+        JsonPathSegment::Key(key) => {
+            if let Some(child) = value.as_object_mut().and_then(|object| object.get_mut(key)) {
+                mask_json_path(child, rest, placeholder);
+            }
+        }
+        JsonPathSegment::Index(index) => {
+            if let Some(child) = value.as_array_mut().and_then(|array| array.get_mut(*index)) {
+                mask_json_path(child, rest, placeholder);
+            }
+        }
+        JsonPathSegment::Wildcard => {
+            for child in children_mut(value) {
+                mask_json_path(child, rest, placeholder);
+            }
+        }
+        JsonPathSegment::RecursiveKey(key) => {
+            if let Some(child) = value.as_object_mut().and_then(|object| object.get_mut(key)) {
+                mask_json_path(child, rest, placeholder);
+            }
+
+            for child in children_mut(value) {
+                mask_json_path(child, segments, placeholder);
+            }
+        }
+    }
+}
+
+fn children_mut(value: &mut Value) -> Vec<&mut Value> {
+    match value {
+        Value::Object(object) => object.values_mut().collect(),
+        Value::Array(array) => array.iter_mut().collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod test_mask_json {
+    use super::*;
+    use serde_json::json;
+
+    fn new_data_mask(json_paths: &[&str]) -> DataMask {
+        let json_paths = json_paths
+            .iter()
+            .map(|json_path| json_path.to_string())
+            .collect::<Vec<_>>();
+
+        DataMask::new(&[], &json_paths).unwrap()
+    }
+
+    #[test]
+    fn it_should_mask_top_level_keys() {
+        let mut value = json!({ "name": "Joe", "password": "hunter2" });
+        new_data_mask(&["$.password"]).mask_json(&mut value);
+
+        assert_eq!(value, json!({ "name": "Joe", "password": "***" }));
+    }
+
+    #[test]
+    fn it_should_mask_within_arrays() {
+        let mut value = json!({ "users": [{ "token": "a" }, { "token": "b" }], "items": [1, 2] });
+        new_data_mask(&["$.users[*].token", "$.items[0]"]).mask_json(&mut value);
+
+        assert_eq!(
+            value,
+            json!({ "users": [{ "token": "***" }, { "token": "***" }], "items": ["***", 2] })
+        );
+    }
+
+    #[test]
+    fn it_should_mask_keys_at_any_depth() {
+        let mut value = json!({ "secret": 1, "nested": { "deeper": { "secret": 2 } } });
+        new_data_mask(&["$..secret"]).mask_json(&mut value);
+
+        assert_eq!(
+            value,
+            json!({ "secret": "***", "nested": { "deeper": { "secret": "***" } } })
+        );
+    }
+
+    #[test]
+    fn it_should_mask_text_which_is_json() {
+        let data_mask = new_data_mask(&["$.password"]);
+
+        assert_eq!(
+            data_mask.masked_text(r#"{ "name": "Joe", "password": "hunter2" }"#),
+            r#"{"name":"Joe","password":"***"}"#
+        );
+        assert_eq!(data_mask.masked_text("hunter2"), "hunter2");
+        assert_eq!(
+            data_mask.masked_text(r#"{ "name": "Joe" }"#),
+            r#"{ "name": "Joe" }"#
+        );
+    }
+
+    #[test]
+    fn it_should_reject_invalid_paths() {
+        let json_paths = ["password".to_string()];
+        assert!(DataMask::new(&[], &json_paths).is_err());
+
+        let json_paths = ["$.items[first]".to_string()];
+        assert!(DataMask::new(&[], &json_paths).is_err());
+    }
+}
@@ -11,60 +11,105 @@ pub struct DebugResponseBody<'a>(pub &'a TestResponse);
 
 impl Display for DebugResponseBody<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
-        match self.0.maybe_content_type() {
-            Some(content_type) => {
-                match content_type.as_str() {
-                    // Json
-                    "application/json" | "text/json" => write_json(f, self.0),
-
-                    // Msgpack
-                    "application/msgpack" => write!(f, "<MsgPack>"),
-
-                    // Yaml
-                    #[cfg(feature = "yaml")]
-                    "application/yaml" | "application/x-yaml" | "text/yaml" => {
-                        write_yaml(f, self.0)
-                    }
-
-                    #[cfg(not(feature = "yaml"))]
-                    "application/yaml" | "application/x-yaml" | "text/yaml" => {
-                        write_text(f, &self.0.text())
-                    }
-
-                    // Text Content
-                    s if s.starts_with("text/") => write_text(f, &self.0.text()),
-
-                    // Byte Streams
-                    "application/octet-stream" => {
-                        let len = self.0.as_bytes().len();
-                        write!(f, "<Bytes, with len {}>", ByteSize(len as u64))
-                    }
-
-                    // Unknown content type
-                    _ => {
-                        let len = self.0.as_bytes().len();
-                        write!(
-                            f,
-                            "<Unknown content type, with len {}>",
-                            ByteSize(len as u64)
-                        )
-                    }
+        write_body(f, self.0, Some(MAX_TEXT_PRINT_LEN))
+    }
+}
+
+/// The same as [`DebugResponseBody`], without cutting off long text.
+pub struct FullResponseBody<'a>(pub &'a TestResponse);
+
+impl Display for FullResponseBody<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write_body(f, self.0, None)
+    }
+}
+
+fn write_body(
+    f: &mut Formatter<'_>,
+    response: &TestResponse,
+    maybe_max_text_len: Option<usize>,
+) -> FmtResult {
+    match response.maybe_content_type() {
+        Some(content_type) => {
+            match content_type.as_str() {
+                // Json
+                "application/json" | "text/json" => write_json(f, response),
+
+                // Msgpack
+                "application/msgpack" => write!(f, "<MsgPack>"),
+
+                // Yaml
+                #[cfg(feature = "yaml")]
+                "application/yaml" | "application/x-yaml" | "text/yaml" => write_yaml(f, response),
+
+                #[cfg(not(feature = "yaml"))]
+                "application/yaml" | "application/x-yaml" | "text/yaml" => {
+                    write_masked_text(f, response, maybe_max_text_len)
+                }
+
+                // Text Content
+                s if s.starts_with("text/") => write_masked_text(f, response, maybe_max_text_len),
+
+                // Byte Streams
+                "application/octet-stream" => {
+                    let len = response.as_bytes().len();
+                    write!(f, "<Bytes, with len {}>", ByteSize(len as u64))
+                }
+
+                // Unknown content type
+                _ => {
+                    let len = response.as_bytes().len();
+                    write!(
+                        f,
+                        "<Unknown content type, with len {}>",
+                        ByteSize(len as u64)
+                    )
                 }
             }
+        }
+
+        // We just default to text
+        _ => write_masked_text(f, response, maybe_max_text_len),
+    }
+}
 
-            // We just default to text
-            _ => write_text(f, &self.0.text()),
+/// Writes the body as text, with the values at masked Json paths hidden when the text is Json.
+fn write_masked_text(
+    f: &mut Formatter<'_>,
+    response: &TestResponse,
+    maybe_max_len: Option<usize>,
+) -> FmtResult {
+    let text = response.text();
+    let masked_text = response.data_mask().masked_text(&text);
+
+    write_text(f, &masked_text, maybe_max_len)
+}
+
+/// The content type and body of a response, for including in failure messages.
+pub struct DebugResponseContent<'a>(pub &'a TestResponse);
+
+impl Display for DebugResponseContent<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let debug_body = DebugResponseBody(self.0);
+
+        match self.0.maybe_header(http::header::CONTENT_TYPE) {
+            Some(content_type) => write!(
+                f,
+                "content type '{}', and body {debug_body}",
+                String::from_utf8_lossy(content_type.as_bytes())
+            ),
+            None => write!(f, "no content type, and body {debug_body}"),
         }
     }
 }
 
-fn write_text(f: &mut Formatter<'_>, text: &str) -> FmtResult {
-    let len = text.len();
+fn write_text(f: &mut Formatter<'_>, text: &str, maybe_max_len: Option<usize>) -> FmtResult {
+    let max_len = maybe_max_len.unwrap_or(usize::MAX);
 
-    if len < MAX_TEXT_PRINT_LEN {
+    if text.len() < max_len {
         write!(f, "'{}'", text)
     } else {
-        let text_start = text.chars().take(MAX_TEXT_PRINT_LEN);
+        let text_start = text.chars().take(max_len);
         write!(f, "'")?;
         for c in text_start {
             write!(f, "{c}")?;
@@ -87,7 +132,9 @@ fn write_json(f: &mut Formatter<'_>, response: &TestResponse) -> FmtResult {
                 response.text()
             )
         }
-        Ok(body) => {
+        Ok(mut body) => {
+            response.data_mask().mask_json(&mut body);
+
             let pretty_raw = serde_json::to_string_pretty(&body)
                 .expect("Failed to reserialise serde_json::Value of request body");
             write!(f, "{pretty_raw}")
@@ -109,9 +156,23 @@ fn write_yaml(f: &mut Formatter<'_>, response: &TestResponse) -> FmtResult {
             )
         }
         Ok(body) => {
-            let pretty_raw = serde_yaml::to_string(&body)
-                .expect("Failed to reserialise serde_yaml::Value of request body");
-            write!(f, "{pretty_raw}")
+            let data_mask = response.data_mask();
+            if !data_mask.has_json_paths() {
+                let pretty_raw = serde_yaml::to_string(&body)
+                    .expect("Failed to reserialise serde_yaml::Value of request body");
+                return write!(f, "{pretty_raw}");
+            }
+
+            // Masking is done on the body as Json, as that is what the masked paths describe.
+            match serde_json::to_value(&body) {
+                Err(_) => write!(f, "<Yaml, hidden as it could not be masked>"),
+                Ok(mut json_body) => {
+                    data_mask.mask_json(&mut json_body);
+                    let pretty_raw = serde_yaml::to_string(&json_body)
+                        .expect("Failed to reserialise masked Yaml of request body");
+                    write!(f, "{pretty_raw}")
+                }
+            }
         }
     }
 }
@@ -149,6 +210,31 @@ mod test_fmt {
         assert_eq!(output, "'Blah blah'");
     }
 
+    #[tokio::test]
+    async fn it_should_display_content_type_with_body() {
+        let router = Router::new().route("/text", get(|| async { "Blah blah" }));
+        let response = TestServer::new(router).unwrap().get("/text").await;
+
+        let debug_content = DebugResponseContent(&response);
+        let output = format!("{debug_content}");
+
+        assert_eq!(
+            output,
+            "content type 'text/plain; charset=utf-8', and body 'Blah blah'"
+        );
+    }
+
+    #[tokio::test]
+    async fn it_should_display_missing_content_type_with_body() {
+        let router = Router::new().route("/empty", get(|| async { Body::empty() }));
+        let response = TestServer::new(router).unwrap().get("/empty").await;
+
+        let debug_content = DebugResponseContent(&response);
+        let output = format!("{debug_content}");
+
+        assert_eq!(output, "no content type, and body ''");
+    }
+
     #[tokio::test]
     async fn it_should_cutoff_very_long_text() {
         let router = Router::new().route(
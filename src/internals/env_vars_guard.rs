@@ -0,0 +1,188 @@
+use anyhow::anyhow;
+use anyhow::Result;
+use std::env;
+use std::ffi::OsString;
+use std::sync::Condvar;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::thread;
+use std::thread::ThreadId;
+use std::time::Duration;
+use std::time::Instant;
+use tokio::runtime::Handle;
+use tokio::runtime::RuntimeFlavor;
+use tokio::task::block_in_place;
+
+/// The thread holding the environment variables, and how many guards it holds.
+///
+/// The lock is reentrant for the thread holding it,
+/// so one test can build multiple servers with environment variables.
+static ENV_VARS_HOLDER: Mutex<Option<(ThreadId, usize)>> = Mutex::new(None);
+static ENV_VARS_RELEASED: Condvar = Condvar::new();
+
+/// How long to wait for another thread to release the environment variables.
+///
+/// This is bounded, as a second server built with environment variables
+/// from another thread within the same test can never be given them.
+const ENV_VARS_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Sets environment variables for as long as this is alive,
+/// restoring their previous values when dropped.
+///
+/// Only one thread can hold guards at a time,
+/// so tests running in parallel wait for each other.
+/// An error is returned if they are not released in time.
+#[derive(Debug)]
+pub struct EnvVarsGuard {
+    previous_values: Vec<(String, Option<OsString>)>,
+}
+
+impl EnvVarsGuard {
+    pub fn new(env_vars: &[(String, String)]) -> Result<Self> {
+        Self::new_with_timeout(env_vars, ENV_VARS_TIMEOUT)
+    }
+
+    fn new_with_timeout(env_vars: &[(String, String)], timeout: Duration) -> Result<Self> {
+        // Waiting is moved off the worker, so other tasks on the runtime keep running.
+        let is_multi_thread = Handle::try_current()
+            .is_ok_and(|handle| handle.runtime_flavor() == RuntimeFlavor::MultiThread);
+        if is_multi_thread {
+            block_in_place(|| acquire_env_vars(timeout))?;
+        } else {
+            acquire_env_vars(timeout)?;
+        }
+
+        let previous_values = env_vars
+            .iter()
+            .map(|(key, value)| {
+                let previous_value = env::var_os(key);
+                env::set_var(key, value);
+
+                (key.clone(), previous_value)
+            })
+            .collect();
+
+        Ok(Self { previous_values })
+    }
+}
+
+impl Drop for EnvVarsGuard {
+    fn drop(&mut self) {
+        // Reversed, so a key set twice is restored to its value from before both.
+        for (key, previous_value) in self.previous_values.drain(..).rev() {
+            match previous_value {
+                Some(previous_value) => env::set_var(&key, previous_value),
+                None => env::remove_var(&key),
+            }
+        }
+
+        release_env_vars();
+    }
+}
+
+fn acquire_env_vars(timeout: Duration) -> Result<()> {
+    let current_thread = thread::current().id();
+    let deadline = Instant::now() + timeout;
+    let mut holder = ENV_VARS_HOLDER
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+
+    loop {
+        match *holder {
+            None => {
+                *holder = Some((current_thread, 1));
+                return Ok(());
+            }
+            Some((thread_id, count)) if thread_id == current_thread => {
+                *holder = Some((thread_id, count + 1));
+                return Ok(());
+            }
+            Some(_) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    return Err(anyhow!(
+                        "Timed out after {timeout:?} waiting for environment variables held by another thread. \
+                        Tests wait for each other to set environment variables, \
+                        and a server built with them from a spawned thread or task can wait on a server in the same test. \
+                        Build the servers on the same thread, or only give environment variables to one of them"
+                    ));
+                }
+
+                holder = ENV_VARS_RELEASED
+                    .wait_timeout(holder, remaining)
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .0;
+            }
+        }
+    }
+}
+
+fn release_env_vars() {
+    let mut holder = ENV_VARS_HOLDER
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+
+    *holder = match *holder {
+        Some((thread_id, count)) if count > 1 => Some((thread_id, count - 1)),
+        _ => None,
+    };
+
+    if holder.is_none() {
+        ENV_VARS_RELEASED.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod test_env_vars_guard {
+    use super::*;
+
+    #[test]
+    fn it_should_set_and_restore_env_vars() {
+        let key = "AXUM_TEST_ENV_VARS_GUARD_RESTORE";
+        env::remove_var(key);
+
+        let guard = EnvVarsGuard::new(&[(key.to_string(), "on".to_string())]).unwrap();
+        assert_eq!(env::var(key).unwrap(), "on");
+
+        drop(guard);
+        assert!(env::var(key).is_err());
+    }
+
+    #[test]
+    fn it_should_allow_nested_guards_on_the_same_thread() {
+        let key = "AXUM_TEST_ENV_VARS_GUARD_NESTED";
+
+        let outer = EnvVarsGuard::new(&[(key.to_string(), "outer".to_string())]).unwrap();
+        let inner = EnvVarsGuard::new(&[(key.to_string(), "inner".to_string())]).unwrap();
+        assert_eq!(env::var(key).unwrap(), "inner");
+
+        drop(inner);
+        assert_eq!(env::var(key).unwrap(), "outer");
+
+        drop(outer);
+        assert!(env::var(key).is_err());
+    }
+
+    #[test]
+    fn it_should_error_when_another_thread_holds_them_past_the_timeout() {
+        let key = "AXUM_TEST_ENV_VARS_GUARD_TIMEOUT";
+        let guard = EnvVarsGuard::new(&[(key.to_string(), "first".to_string())]).unwrap();
+
+        let result = thread::spawn(move || {
+            EnvVarsGuard::new_with_timeout(
+                &[(key.to_string(), "second".to_string())],
+                Duration::from_millis(50),
+            )
+            .map(drop)
+        })
+        .join()
+        .unwrap();
+
+        let error = result.unwrap_err().to_string();
+        assert!(error.starts_with("Timed out after 50ms waiting for environment variables"));
+        assert_eq!(env::var(key).unwrap(), "first");
+
+        drop(guard);
+        assert!(env::var(key).is_err());
+    }
+}
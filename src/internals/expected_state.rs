@@ -2,6 +2,8 @@
 pub enum ExpectedState {
     Success,
     Failure,
+    #[cfg(feature = "ws")]
+    UpgradeRejected,
     None,
 }
 
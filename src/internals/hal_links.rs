@@ -0,0 +1,58 @@
+use serde_json::Value;
+
+/// Returns the `href` of the first link with the relation given,
+/// from the `_links` of a HAL document.
+///
+/// A relation can hold a single link object, or an array of them.
+pub fn find_hal_link(document: &Value, rel: &str) -> Option<String> {
+    let link = match document.get("_links")?.get(rel)? {
+        Value::Array(links) => links.first()?,
+        link => link,
+    };
+
+    link.get("href")?.as_str().map(ToString::to_string)
+}
+
+#[cfg(test)]
+mod test_find_hal_link {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn it_should_find_single_links() {
+        let document = json!({
+            "_links": {
+                "self": { "href": "/users/1" },
+            },
+        });
+
+        assert_eq!(
+            find_hal_link(&document, "self"),
+            Some("/users/1".to_string())
+        );
+    }
+
+    #[test]
+    fn it_should_find_first_link_in_arrays() {
+        let document = json!({
+            "_links": {
+                "item": [{ "href": "/users/1" }, { "href": "/users/2" }],
+            },
+        });
+
+        assert_eq!(
+            find_hal_link(&document, "item"),
+            Some("/users/1".to_string())
+        );
+    }
+
+    #[test]
+    fn it_should_return_none_for_missing_links() {
+        assert_eq!(find_hal_link(&json!({}), "self"), None);
+        assert_eq!(find_hal_link(&json!({ "_links": {} }), "self"), None);
+        assert_eq!(
+            find_hal_link(&json!({ "_links": { "self": {} } }), "self"),
+            None
+        );
+    }
+}
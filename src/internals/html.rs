@@ -0,0 +1,55 @@
+use anyhow::anyhow;
+use anyhow::Result;
+use scraper::ElementRef;
+use scraper::Selector;
+
+/// Parses a CSS selector, with an error naming the selector if it is invalid.
+pub fn parse_css_selector(selector: &str) -> Result<Selector> {
+    Selector::parse(selector)
+        .map_err(|err| anyhow!("Failed to parse CSS selector '{selector}', {err}"))
+}
+
+/// The text within the element given, with whitespace collapsed and trimmed,
+/// the same way a browser displays it.
+pub fn element_text(element: ElementRef<'_>) -> String {
+    element
+        .text()
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod test_parse_css_selector {
+    use super::*;
+
+    #[test]
+    fn it_should_parse_pseudo_classes() {
+        assert!(parse_css_selector("li:first-child > a:not(.next)").is_ok());
+    }
+
+    #[test]
+    fn it_should_return_error_naming_invalid_selectors() {
+        let error = parse_css_selector("a!").unwrap_err();
+
+        assert!(error
+            .to_string()
+            .starts_with("Failed to parse CSS selector 'a!'"));
+    }
+}
+
+#[cfg(test)]
+mod test_element_text {
+    use super::*;
+    use scraper::Html;
+
+    #[test]
+    fn it_should_collapse_whitespace_across_elements() {
+        let html = Html::parse_fragment("<p>\n  Hello   <b>big</b>\n world<i>!</i>  </p>");
+        let selector = parse_css_selector("p").unwrap();
+        let element = html.select(&selector).next().unwrap();
+
+        assert_eq!(element_text(element), "Hello big world!");
+    }
+}
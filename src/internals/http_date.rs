@@ -0,0 +1,54 @@
+use anyhow::Context;
+use anyhow::Result;
+use cookie::time::OffsetDateTime;
+use std::time::SystemTime;
+
+/// Formats the date in the HTTP date format,
+/// i.e. `Sun, 06 Nov 1994 08:49:37 GMT`.
+pub fn format_http_date(date: OffsetDateTime) -> String {
+    httpdate::fmt_http_date(SystemTime::from(date))
+}
+
+/// Parses a date in the HTTP date format.
+pub fn parse_http_date(raw_date: &str) -> Result<OffsetDateTime> {
+    let date = httpdate::parse_http_date(raw_date)
+        .with_context(|| format!("Failed to parse HTTP date '{raw_date}'"))?;
+
+    Ok(OffsetDateTime::from(date))
+}
+
+#[cfg(test)]
+mod test_format_http_date {
+    use super::*;
+    use cookie::time::OffsetDateTime;
+
+    #[test]
+    fn it_should_format_in_http_date_format() {
+        let date = OffsetDateTime::from_unix_timestamp(784111777).unwrap();
+
+        assert_eq!(format_http_date(date), "Sun, 06 Nov 1994 08:49:37 GMT");
+    }
+}
+
+#[cfg(test)]
+mod test_parse_http_date {
+    use super::*;
+    use cookie::time::OffsetDateTime;
+
+    #[test]
+    fn it_should_parse_http_date_format() {
+        let date = parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT").unwrap();
+
+        assert_eq!(
+            date,
+            OffsetDateTime::from_unix_timestamp(784111777).unwrap()
+        );
+    }
+
+    #[test]
+    fn it_should_error_on_invalid_dates() {
+        let result = parse_http_date("yesterday");
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,154 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::ServerSharedState;
+
+/// What is being tracked as in flight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InFlightKind {
+    Request,
+    WebSocket,
+}
+
+#[derive(Debug, Clone)]
+struct InFlightEntry {
+    kind: InFlightKind,
+    description: String,
+    started_at: Instant,
+}
+
+/// Requests which have been sent but not yet received a response,
+/// and WebSockets which are still open.
+#[derive(Debug, Default)]
+pub struct InFlight {
+    next_id: u64,
+    entries: BTreeMap<u64, InFlightEntry>,
+}
+
+impl InFlight {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts tracking the request or WebSocket described,
+    /// returning an id for finishing it.
+    pub fn start(&mut self, kind: InFlightKind, description: String) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        self.entries.insert(
+            id,
+            InFlightEntry {
+                kind,
+                description,
+                started_at: Instant::now(),
+            },
+        );
+
+        id
+    }
+
+    pub fn finish(&mut self, id: u64) {
+        self.entries.remove(&id);
+    }
+
+    pub fn report(&self) -> InFlightReport {
+        let now = Instant::now();
+        let lines = |kind: InFlightKind| {
+            self.entries
+                .values()
+                .filter(|entry| entry.kind == kind)
+                .map(|entry| {
+                    let elapsed = now.duration_since(entry.started_at);
+                    format!("{}, for {elapsed:.2?}", entry.description)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        InFlightReport {
+            requests: lines(InFlightKind::Request),
+            websockets: lines(InFlightKind::WebSocket),
+        }
+    }
+}
+
+/// A description of everything in flight at one point in time.
+#[derive(Debug, Clone)]
+pub struct InFlightReport {
+    requests: Vec<String>,
+    websockets: Vec<String>,
+}
+
+impl Display for InFlightReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        for (title, lines) in [
+            ("Requests in flight", &self.requests),
+            ("WebSockets open", &self.websockets),
+        ] {
+            if lines.is_empty() {
+                writeln!(f, "{title}: none")?;
+                continue;
+            }
+
+            writeln!(f, "{title}: {}", lines.len())?;
+            for line in lines {
+                writeln!(f, "    {line}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Stops tracking the request or WebSocket when dropped,
+/// including when the future sending a request is cancelled.
+#[derive(Debug)]
+pub struct InFlightHandle {
+    server_state: Arc<Mutex<ServerSharedState>>,
+    id: u64,
+}
+
+impl InFlightHandle {
+    pub fn new(server_state: Arc<Mutex<ServerSharedState>>, id: u64) -> Self {
+        Self { server_state, id }
+    }
+}
+
+impl Drop for InFlightHandle {
+    fn drop(&mut self) {
+        // Errors are ignored, as the lock is only poisoned when the test has already panicked.
+        let _ = ServerSharedState::finish_in_flight(&self.server_state, self.id);
+    }
+}
+
+#[cfg(test)]
+mod test_report {
+    use super::*;
+
+    #[test]
+    fn it_should_list_entries_started_and_not_finished() {
+        let mut in_flight = InFlight::new();
+        let first = in_flight.start(InFlightKind::Request, "GET /first".to_string());
+        in_flight.start(InFlightKind::Request, "GET /second".to_string());
+        in_flight.start(InFlightKind::WebSocket, "GET /ws".to_string());
+        in_flight.finish(first);
+
+        let report = in_flight.report().to_string();
+
+        assert!(!report.contains("GET /first"));
+        assert!(report.contains("Requests in flight: 1\n    GET /second, for "));
+        assert!(report.contains("WebSockets open: 1\n    GET /ws, for "));
+    }
+
+    #[test]
+    fn it_should_report_none_when_empty() {
+        let report = InFlight::new().report().to_string();
+
+        assert_eq!(report, "Requests in flight: none\nWebSockets open: none\n");
+    }
+}
@@ -0,0 +1,190 @@
+use anyhow::Context;
+use anyhow::Result;
+use http::HeaderName;
+use http::HeaderValue;
+use http::Method;
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::internals::collection_url_to_path;
+use crate::internals::substitute_collection_variables;
+use crate::ScenarioBody;
+use crate::ScenarioStep;
+
+/// Reads the requests of an Insomnia export (format 4), in the order they were exported.
+///
+/// Variables are taken from the environments in the export,
+/// with the variables given taking precedence.
+/// Insomnia exports do not hold assertions, so no responses are expected.
+pub fn read_insomnia_export(
+    export: &Value,
+    variables: &HashMap<String, String>,
+) -> Result<Vec<ScenarioStep>> {
+    let resources = export
+        .get("resources")
+        .and_then(Value::as_array)
+        .context("Expected Insomnia export to have a 'resources' array")?;
+    let resource_type = |resource: &Value| {
+        resource
+            .get("_type")
+            .and_then(Value::as_str)
+            .map(ToString::to_string)
+    };
+
+    let mut all_variables = HashMap::new();
+    for resource in resources {
+        if resource_type(resource).as_deref() != Some("environment") {
+            continue;
+        }
+
+        for (key, value) in resource
+            .get("data")
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+        {
+            let value = match value {
+                Value::String(value) => value.clone(),
+                value => value.to_string(),
+            };
+            all_variables.insert(key.clone(), value);
+        }
+    }
+    all_variables.extend(variables.clone());
+
+    resources
+        .iter()
+        .filter(|resource| resource_type(resource).as_deref() == Some("request"))
+        .map(|resource| {
+            let name = resource
+                .get("name")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+
+            read_request(resource, name.clone(), &all_variables)
+                .with_context(|| format!("Failed to read Insomnia request '{name}'"))
+        })
+        .collect()
+}
+
+fn read_request(
+    request: &Value,
+    name: String,
+    variables: &HashMap<String, String>,
+) -> Result<ScenarioStep> {
+    let method_str = request
+        .get("method")
+        .and_then(Value::as_str)
+        .unwrap_or("GET");
+    let method = Method::from_bytes(method_str.to_ascii_uppercase().as_bytes())
+        .with_context(|| format!("Unknown method '{method_str}'"))?;
+
+    let url = request
+        .get("url")
+        .and_then(Value::as_str)
+        .context("Expected request to have a 'url'")?;
+    let path = collection_url_to_path(&substitute_collection_variables(url, variables)?);
+
+    let mut headers = Vec::new();
+    for header in request
+        .get("headers")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        if header.get("disabled").and_then(Value::as_bool) == Some(true) {
+            continue;
+        }
+
+        let key = header
+            .get("name")
+            .and_then(Value::as_str)
+            .context("Expected header to have a 'name'")?;
+        let value = header
+            .get("value")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let value = substitute_collection_variables(value, variables)?;
+
+        headers.push((
+            HeaderName::from_bytes(key.as_bytes())
+                .with_context(|| format!("Invalid header name '{key}'"))?,
+            HeaderValue::from_str(&value)
+                .with_context(|| format!("Invalid value for header '{key}'"))?,
+        ));
+    }
+
+    let body_text = request
+        .get("body")
+        .and_then(|body| body.get("text"))
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let body = if body_text.is_empty() {
+        ScenarioBody::None
+    } else {
+        let body_text = substitute_collection_variables(body_text, variables)?;
+        let mime_type = request
+            .get("body")
+            .and_then(|body| body.get("mimeType"))
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+
+        if mime_type.contains("json") {
+            let json =
+                serde_json::from_str::<Value>(&body_text).context("Failed to read body as Json")?;
+            ScenarioBody::Json(json)
+        } else {
+            ScenarioBody::Text(body_text)
+        }
+    };
+
+    Ok(ScenarioStep {
+        name,
+        method,
+        path,
+        headers,
+        body,
+        expected_status: None,
+        expected_json_contains: None,
+    })
+}
+
+#[cfg(test)]
+mod test_read_insomnia_export {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn it_should_read_requests_with_environment_variables() {
+        let export = json!({
+            "_type": "export",
+            "__export_format": 4,
+            "resources": [
+                {
+                    "_type": "request",
+                    "name": "Create user",
+                    "method": "POST",
+                    "url": "{{ _.base_url }}/users",
+                    "headers": [{ "name": "x-api-key", "value": "{{ _.api_key }}" }],
+                    "body": { "mimeType": "application/json", "text": "{\"name\": \"Joe\"}" },
+                },
+                { "_type": "workspace", "name": "My API" },
+                {
+                    "_type": "environment",
+                    "data": { "base_url": "http://localhost:3000", "api_key": "env-key" },
+                },
+            ],
+        });
+        let variables = HashMap::from([("api_key".to_string(), "my-key".to_string())]);
+
+        let steps = read_insomnia_export(&export, &variables).unwrap();
+
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].name, "Create user");
+        assert_eq!(steps[0].method, Method::POST);
+        assert_eq!(steps[0].path, "/users");
+        assert_eq!(steps[0].headers[0].1, "my-key");
+        assert_eq!(steps[0].body, ScenarioBody::Json(json!({ "name": "Joe" })));
+    }
+}
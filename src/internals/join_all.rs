@@ -0,0 +1,66 @@
+use std::future::poll_fn;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::Poll;
+
+/// Runs all of the futures concurrently, returning their outputs in the order given.
+pub async fn join_all<F>(futures: Vec<F>) -> Vec<F::Output>
+where
+    F: Future,
+{
+    let mut futures: Vec<Pin<Box<F>>> = futures.into_iter().map(Box::pin).collect();
+    let mut outputs: Vec<Option<F::Output>> = futures.iter().map(|_| None).collect();
+
+    poll_fn(|cx| {
+        let mut is_pending = false;
+        for (future, output) in futures.iter_mut().zip(outputs.iter_mut()) {
+            if output.is_some() {
+                continue;
+            }
+
+            match future.as_mut().poll(cx) {
+                Poll::Ready(result) => *output = Some(result),
+                Poll::Pending => is_pending = true,
+            }
+        }
+
+        if is_pending {
+            Poll::Pending
+        } else {
+            Poll::Ready(())
+        }
+    })
+    .await;
+
+    outputs.into_iter().flatten().collect()
+}
+
+#[cfg(test)]
+mod test_join_all {
+    use super::*;
+    use std::time::Duration;
+    use tokio::time::sleep;
+
+    #[tokio::test]
+    async fn it_should_return_outputs_in_order_given() {
+        let futures = [30, 10, 20]
+            .into_iter()
+            .map(|delay| async move {
+                sleep(Duration::from_millis(delay)).await;
+                delay
+            })
+            .collect();
+
+        assert_eq!(join_all(futures).await, vec![30, 10, 20]);
+    }
+
+    #[tokio::test]
+    async fn it_should_run_futures_concurrently() {
+        let futures = (0..10).map(|_| sleep(Duration::from_millis(50))).collect();
+
+        let started_at = tokio::time::Instant::now();
+        join_all(futures).await;
+
+        assert!(started_at.elapsed() < Duration::from_millis(400));
+    }
+}
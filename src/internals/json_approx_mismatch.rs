@@ -0,0 +1,137 @@
+use serde_json::Value;
+
+use crate::JsonTolerance;
+
+/// Compares the two Json values, allowing numbers to differ within the tolerance given.
+///
+/// Returns a description of the first difference found,
+/// or `None` if they match.
+pub fn find_json_approx_mismatch(
+    expected: &Value,
+    received: &Value,
+    tolerance: JsonTolerance,
+) -> Option<String> {
+    find_mismatch_at_path("$", expected, received, tolerance)
+}
+
+fn find_mismatch_at_path(
+    path: &str,
+    expected: &Value,
+    received: &Value,
+    tolerance: JsonTolerance,
+) -> Option<String> {
+    match (expected, received) {
+        (Value::Number(expected_number), Value::Number(received_number)) => {
+            let expected_f64 = expected_number.as_f64()?;
+            let received_f64 = received_number.as_f64()?;
+
+            if tolerance.is_within(expected_f64, received_f64) {
+                None
+            } else {
+                Some(format!(
+                    "at {path}, expected {expected_number}, received {received_number}"
+                ))
+            }
+        }
+        (Value::Array(expected_items), Value::Array(received_items)) => {
+            if expected_items.len() != received_items.len() {
+                return Some(format!(
+                    "at {path}, expected array of len {}, received array of len {}",
+                    expected_items.len(),
+                    received_items.len()
+                ));
+            }
+
+            expected_items
+                .iter()
+                .zip(received_items)
+                .enumerate()
+                .find_map(|(i, (expected_item, received_item))| {
+                    let item_path = format!("{path}[{i}]");
+                    find_mismatch_at_path(&item_path, expected_item, received_item, tolerance)
+                })
+        }
+        (Value::Object(expected_fields), Value::Object(received_fields)) => {
+            if let Some(key) = received_fields
+                .keys()
+                .find(|key| !expected_fields.contains_key(*key))
+            {
+                return Some(format!("at {path}.{key}, received unexpected field"));
+            }
+
+            expected_fields.iter().find_map(|(key, expected_field)| {
+                let field_path = format!("{path}.{key}");
+                match received_fields.get(key) {
+                    None => Some(format!("at {field_path}, field is missing")),
+                    Some(received_field) => find_mismatch_at_path(
+                        &field_path,
+                        expected_field,
+                        received_field,
+                        tolerance,
+                    ),
+                }
+            })
+        }
+        _ if expected == received => None,
+        _ => Some(format!(
+            "at {path}, expected {expected}, received {received}"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test_find_json_approx_mismatch {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn it_should_match_floats_within_tolerance_in_nested_values() {
+        let expected = json!({ "points": [{ "lat": 51.5074, "lng": -0.1278 }] });
+        let received = json!({ "points": [{ "lat": 51.50740001, "lng": -0.12779999 }] });
+
+        let output = find_json_approx_mismatch(&expected, &received, 0.0001.into());
+        assert_eq!(output, None);
+    }
+
+    #[test]
+    fn it_should_report_path_of_number_outside_tolerance() {
+        let expected = json!({ "points": [{ "lat": 51.5074 }] });
+        let received = json!({ "points": [{ "lat": 51.6 }] });
+
+        let output = find_json_approx_mismatch(&expected, &received, 0.0001.into());
+        assert_eq!(
+            output,
+            Some("at $.points[0].lat, expected 51.5074, received 51.6".to_string())
+        );
+    }
+
+    #[test]
+    fn it_should_report_missing_and_unexpected_fields() {
+        let expected = json!({ "name": "Joe", "price": 1.0 });
+
+        let missing = find_json_approx_mismatch(&expected, &json!({ "name": "Joe" }), 0.1.into());
+        assert_eq!(missing, Some("at $.price, field is missing".to_string()));
+
+        let unexpected = find_json_approx_mismatch(
+            &expected,
+            &json!({ "name": "Joe", "price": 1.0, "age": 20 }),
+            0.1.into(),
+        );
+        assert_eq!(
+            unexpected,
+            Some("at $.age, received unexpected field".to_string())
+        );
+    }
+
+    #[test]
+    fn it_should_compare_non_numbers_exactly() {
+        let expected = json!({ "name": "Joe" });
+        let received = json!({ "name": "Julia" });
+
+        let output = find_json_approx_mismatch(&expected, &received, 1000.0.into());
+        assert_eq!(
+            output,
+            Some(r#"at $.name, expected "Joe", received "Julia""#.to_string())
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use crate::LinkEntry;
+
+/// Parses the value of a `Link` header, such as
+/// `</page/2>; rel="next", </page/9>; rel="last"`.
+///
+/// Malformed links are skipped.
+pub fn parse_link_header(header: &str) -> Vec<LinkEntry> {
+    split_outside_quotes(header, ',')
+        .into_iter()
+        .filter_map(parse_link)
+        .collect()
+}
+
+fn parse_link(link: &str) -> Option<LinkEntry> {
+    let link = link.trim();
+    let target_end = link.find('>')?;
+    let target = link.strip_prefix('<')?[..target_end - 1].trim().to_string();
+
+    let params = split_outside_quotes(&link[target_end + 1..], ';')
+        .into_iter()
+        .filter_map(|param| {
+            let (name, value) = param.split_once('=')?;
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .unwrap_or(value);
+
+            Some((name.trim().to_ascii_lowercase(), value.to_string()))
+        })
+        .collect();
+
+    Some(LinkEntry { target, params })
+}
+
+/// Splits on the separator given, ignoring separators inside quotes or `<...>` targets.
+fn split_outside_quotes(value: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut is_quoted = false;
+    let mut is_target = false;
+    let mut start = 0;
+
+    for (index, c) in value.char_indices() {
+        match c {
+            '"' if !is_target => is_quoted = !is_quoted,
+            '<' if !is_quoted => is_target = true,
+            '>' if !is_quoted => is_target = false,
+            c if c == separator && !is_quoted && !is_target => {
+                parts.push(&value[start..index]);
+                start = index + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+
+    parts.push(&value[start..]);
+    parts
+        .into_iter()
+        .filter(|part| !part.trim().is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod test_parse_link_header {
+    use super::*;
+
+    #[test]
+    fn it_should_parse_multiple_links() {
+        let links = parse_link_header(r#"</page/2>; rel="next", </page/9>; rel=last"#);
+
+        assert_eq!(
+            links,
+            vec![
+                LinkEntry {
+                    target: "/page/2".to_string(),
+                    params: vec![("rel".to_string(), "next".to_string())],
+                },
+                LinkEntry {
+                    target: "/page/9".to_string(),
+                    params: vec![("rel".to_string(), "last".to_string())],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn it_should_keep_separators_inside_targets_and_quotes() {
+        let links = parse_link_header(
+            r#"<https://example.com/search?a=1,2;b=3>; title="one, two; three"; REL="next""#,
+        );
+
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].target, "https://example.com/search?a=1,2;b=3");
+        assert_eq!(
+            links[0].params,
+            vec![
+                ("title".to_string(), "one, two; three".to_string()),
+                ("rel".to_string(), "next".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_should_match_any_of_multiple_rels() {
+        let links = parse_link_header(r#"</page/9>; rel="next LAST""#);
+
+        assert!(links[0].has_rel("next"));
+        assert!(links[0].has_rel("last"));
+        assert!(!links[0].has_rel("prev"));
+    }
+
+    #[test]
+    fn it_should_skip_malformed_links() {
+        let links = parse_link_header(r#"/no-brackets; rel="next", </ok>; rel="prev""#);
+
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].target, "/ok");
+    }
+}
@@ -0,0 +1,62 @@
+/// Guesses the mime type of a file from the extension at the end of the path given,
+/// for the file types commonly served as static assets.
+///
+/// These match those guessed by `tower_http::services::ServeDir`.
+pub fn guess_mime_from_extension(path: &str) -> Option<&'static str> {
+    let file_name = path.rsplit('/').next()?;
+    let (_, extension) = file_name.rsplit_once('.')?;
+
+    let mime = match extension.to_ascii_lowercase().as_str() {
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "js" | "mjs" => "text/javascript",
+        "json" | "map" => "application/json",
+        "webmanifest" => "application/manifest+json",
+        "txt" => "text/plain",
+        "csv" => "text/csv",
+        "xml" => "text/xml",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+        "ico" => "image/x-icon",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "wasm" => "application/wasm",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        _ => return None,
+    };
+
+    Some(mime)
+}
+
+#[cfg(test)]
+mod test_guess_mime_from_extension {
+    use super::*;
+
+    #[test]
+    fn it_should_guess_from_extension() {
+        assert_eq!(
+            guess_mime_from_extension("/assets/app.js"),
+            Some("text/javascript")
+        );
+        assert_eq!(guess_mime_from_extension("/logo.PNG"), Some("image/png"));
+        assert_eq!(guess_mime_from_extension("index.min.css"), Some("text/css"));
+    }
+
+    #[test]
+    fn it_should_not_guess_without_known_extension() {
+        assert_eq!(guess_mime_from_extension("/assets/app"), None);
+        assert_eq!(guess_mime_from_extension("/assets.v2/app"), None);
+        assert_eq!(guess_mime_from_extension("/assets/app.unknown"), None);
+    }
+}
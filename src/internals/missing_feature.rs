@@ -0,0 +1,10 @@
+use crate::Error;
+
+/// The error for a method called when `axum-test` was built in `dyn-features` mode,
+/// without the feature that method needs.
+pub fn missing_feature_error(method: &str, feature: &str) -> Error {
+    Error::MissingFeature {
+        method: method.to_string(),
+        feature: feature.to_string(),
+    }
+}
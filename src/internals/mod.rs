@@ -6,12 +6,130 @@ mod websockets;
 #[cfg(feature = "ws")]
 pub use self::websockets::*;
 
+#[cfg(feature = "html")]
+mod html;
+#[cfg(feature = "html")]
+pub use self::html::*;
+
+mod assertion_checks;
+pub use self::assertion_checks::*;
+
+mod data_mask;
+pub use self::data_mask::*;
+
+mod env_vars_guard;
+pub use self::env_vars_guard::*;
+
+mod response_decoders;
+pub use self::response_decoders::*;
+
+mod request_encoders;
+pub use self::request_encoders::*;
+
 mod debug_response_body;
 pub use self::debug_response_body::*;
 
+mod recorded_request;
+pub use self::recorded_request::*;
+
+mod replayable_request;
+pub use self::replayable_request::*;
+
+mod probe_reading;
+pub use self::probe_reading::*;
+
+mod readiness_check;
+pub use self::readiness_check::*;
+
+mod csrf_state;
+pub use self::csrf_state::*;
+
+mod link_header;
+pub use self::link_header::*;
+
+mod hal_links;
+pub use self::hal_links::*;
+
+mod relative_url;
+pub use self::relative_url::*;
+
+mod odata_query_param;
+pub use self::odata_query_param::*;
+
+mod collection_variables;
+pub use self::collection_variables::*;
+
+mod postman_collection;
+pub use self::postman_collection::*;
+
+mod insomnia_export;
+pub use self::insomnia_export::*;
+
+mod curl_command;
+pub use self::curl_command::*;
+
+mod byte_encoding;
+pub use self::byte_encoding::*;
+
+mod cookie_domain;
+pub use self::cookie_domain::*;
+
+mod request_log_line;
+pub use self::request_log_line::*;
+
+mod join_all;
+pub use self::join_all::*;
+
+mod text_pattern;
+pub use self::text_pattern::*;
+
+mod mime_from_extension;
+pub use self::mime_from_extension::*;
+
+mod body_framing;
+pub use self::body_framing::*;
+
+mod in_flight;
+pub use self::in_flight::*;
+
+#[cfg(all(
+    feature = "dyn-features",
+    not(all(
+        feature = "yaml",
+        feature = "msgpack",
+        feature = "ws",
+        feature = "reqwest",
+        feature = "typed-routing"
+    ))
+))]
+mod missing_feature;
+#[cfg(all(
+    feature = "dyn-features",
+    not(all(
+        feature = "yaml",
+        feature = "msgpack",
+        feature = "ws",
+        feature = "reqwest",
+        feature = "typed-routing"
+    ))
+))]
+pub use self::missing_feature::*;
+
+mod http_date;
+pub use self::http_date::*;
+
 mod expected_state;
 pub use self::expected_state::*;
 
+mod json_approx_mismatch;
+pub use self::json_approx_mismatch::*;
+
+mod canonical_json;
+pub use self::canonical_json::*;
+
+mod route_pattern;
+pub use self::route_pattern::*;
+
 mod format_status_code_range;
 pub use self::format_status_code_range::*;
 
@@ -30,5 +148,23 @@ pub use self::try_into_range_bounds::*;
 mod starting_tcp_setup;
 pub use self::starting_tcp_setup::*;
 
+mod serve_tracker;
+pub use self::serve_tracker::*;
+
+mod catch_panic;
+pub use self::catch_panic::*;
+
+mod raw_response_head;
+pub use self::raw_response_head::*;
+
+mod receive_buffer_size;
+pub use self::receive_buffer_size::*;
+
 mod with_this_mut;
 pub use self::with_this_mut::*;
+
+mod seeded_rng;
+pub use self::seeded_rng::*;
+
+mod unicode_url;
+pub use self::unicode_url::*;
@@ -0,0 +1,52 @@
+use std::fmt::Write;
+
+/// Builds an OData system query option, such as `$filter=age%20gt%2020`.
+///
+/// The `$` of the name is kept as is, as some OData servers only accept it unencoded.
+/// The value is percent encoded, with spaces as `%20` rather than `+`.
+pub fn build_odata_query_param(option: &str, value: &str) -> String {
+    let mut query_param = format!("${option}=");
+
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            query_param.push(char::from(byte));
+        } else {
+            write!(query_param, "%{byte:02X}").unwrap();
+        }
+    }
+
+    query_param
+}
+
+#[cfg(test)]
+mod test_build_odata_query_param {
+    use super::*;
+
+    #[test]
+    fn it_should_encode_spaces_as_percent_20() {
+        assert_eq!(
+            build_odata_query_param("filter", "age gt 20"),
+            "$filter=age%20gt%2020"
+        );
+    }
+
+    #[test]
+    fn it_should_encode_quotes_and_reserved_characters() {
+        assert_eq!(
+            build_odata_query_param("filter", "name eq 'O''Brien & Sons'"),
+            "$filter=name%20eq%20%27O%27%27Brien%20%26%20Sons%27"
+        );
+        assert_eq!(
+            build_odata_query_param("orderby", "name desc,age"),
+            "$orderby=name%20desc%2Cage"
+        );
+    }
+
+    #[test]
+    fn it_should_encode_non_ascii_as_utf8() {
+        assert_eq!(
+            build_odata_query_param("filter", "city eq 'Zürich'"),
+            "$filter=city%20eq%20%27Z%C3%BCrich%27"
+        );
+    }
+}
@@ -0,0 +1,468 @@
+use anyhow::anyhow;
+use anyhow::Context;
+use anyhow::Result;
+use http::HeaderName;
+use http::HeaderValue;
+use http::Method;
+use http::StatusCode;
+use serde_json::Map;
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::internals::collection_url_to_path;
+use crate::internals::substitute_collection_variables;
+use crate::ScenarioBody;
+use crate::ScenarioStep;
+
+/// Reads the requests of a Postman collection (v2.1), in order,
+/// including those inside folders.
+///
+/// The variables given take precedence over the collection's own variables.
+/// Test scripts are turned into expectations using [`read_postman_test_script`].
+pub fn read_postman_collection(
+    collection: &Value,
+    variables: &HashMap<String, String>,
+) -> Result<Vec<ScenarioStep>> {
+    let mut all_variables = HashMap::new();
+    for variable in collection
+        .get("variable")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        if let (Some(key), Some(value)) = (
+            variable.get("key").and_then(Value::as_str),
+            variable.get("value"),
+        ) {
+            all_variables.insert(key.to_string(), value_to_variable(value));
+        }
+    }
+    all_variables.extend(variables.clone());
+
+    let items = collection
+        .get("item")
+        .and_then(Value::as_array)
+        .context("Expected Postman collection to have an 'item' array")?;
+
+    let mut steps = Vec::new();
+    read_items(items, "", &all_variables, &mut steps)?;
+    Ok(steps)
+}
+
+fn read_items(
+    items: &[Value],
+    folder: &str,
+    variables: &HashMap<String, String>,
+    steps: &mut Vec<ScenarioStep>,
+) -> Result<()> {
+    for item in items {
+        let name = item.get("name").and_then(Value::as_str).unwrap_or_default();
+        let full_name = if folder.is_empty() {
+            name.to_string()
+        } else {
+            format!("{folder} / {name}")
+        };
+
+        if let Some(sub_items) = item.get("item").and_then(Value::as_array) {
+            read_items(sub_items, &full_name, variables, steps)?;
+            continue;
+        }
+
+        let step = read_item(item, full_name.clone(), variables)
+            .with_context(|| format!("Failed to read Postman request '{full_name}'"))?;
+        steps.push(step);
+    }
+
+    Ok(())
+}
+
+fn read_item(
+    item: &Value,
+    name: String,
+    variables: &HashMap<String, String>,
+) -> Result<ScenarioStep> {
+    let request = item
+        .get("request")
+        .context("Expected item to have a 'request'")?;
+
+    // Requests can be written as just the url.
+    if let Value::String(url) = request {
+        let path = collection_url_to_path(&substitute_collection_variables(url, variables)?);
+        return Ok(ScenarioStep {
+            name,
+            method: Method::GET,
+            path,
+            headers: Vec::new(),
+            body: ScenarioBody::None,
+            expected_status: None,
+            expected_json_contains: None,
+        });
+    }
+
+    let method_str = request
+        .get("method")
+        .and_then(Value::as_str)
+        .unwrap_or("GET");
+    let method = Method::from_bytes(method_str.to_ascii_uppercase().as_bytes())
+        .with_context(|| format!("Unknown method '{method_str}'"))?;
+
+    let url = match request.get("url") {
+        Some(Value::String(url)) => url.as_str(),
+        Some(url) => url
+            .get("raw")
+            .and_then(Value::as_str)
+            .context("Expected request url to have a 'raw' string")?,
+        None => return Err(anyhow!("Expected request to have a 'url'")),
+    };
+    let path = collection_url_to_path(&substitute_collection_variables(url, variables)?);
+
+    let mut headers = Vec::new();
+    for header in request
+        .get("header")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        if header.get("disabled").and_then(Value::as_bool) == Some(true) {
+            continue;
+        }
+
+        let key = header
+            .get("key")
+            .and_then(Value::as_str)
+            .context("Expected header to have a 'key'")?;
+        let value = header
+            .get("value")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let value = substitute_collection_variables(value, variables)?;
+
+        headers.push((
+            HeaderName::from_bytes(key.as_bytes())
+                .with_context(|| format!("Invalid header name '{key}'"))?,
+            HeaderValue::from_str(&value)
+                .with_context(|| format!("Invalid value for header '{key}'"))?,
+        ));
+    }
+
+    let body = match request.get("body") {
+        None | Some(Value::Null) => ScenarioBody::None,
+        Some(body) => read_body(body, variables)?,
+    };
+
+    let script = item
+        .get("event")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|event| event.get("listen").and_then(Value::as_str) == Some("test"))
+        .filter_map(|event| event.get("script")?.get("exec"))
+        .flat_map(|exec| match exec {
+            Value::Array(lines) => lines.iter().filter_map(Value::as_str).collect::<Vec<_>>(),
+            Value::String(line) => vec![line.as_str()],
+            _ => Vec::new(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let (expected_status, expected_json_contains) = read_postman_test_script(&script);
+
+    Ok(ScenarioStep {
+        name,
+        method,
+        path,
+        headers,
+        body,
+        expected_status,
+        expected_json_contains,
+    })
+}
+
+fn read_body(body: &Value, variables: &HashMap<String, String>) -> Result<ScenarioBody> {
+    let mode = body.get("mode").and_then(Value::as_str).unwrap_or("raw");
+    if mode != "raw" {
+        return Err(anyhow!(
+            "Unsupported body mode '{mode}', only 'raw' bodies are supported"
+        ));
+    }
+
+    let raw = body.get("raw").and_then(Value::as_str).unwrap_or_default();
+    if raw.is_empty() {
+        return Ok(ScenarioBody::None);
+    }
+
+    let raw = substitute_collection_variables(raw, variables)?;
+    let language = body
+        .get("options")
+        .and_then(|options| options.get("raw"))
+        .and_then(|raw| raw.get("language"))
+        .and_then(Value::as_str);
+    if language == Some("json") {
+        let json =
+            serde_json::from_str::<Value>(&raw).context("Failed to read raw body as Json")?;
+        return Ok(ScenarioBody::Json(json));
+    }
+
+    Ok(ScenarioBody::Text(raw))
+}
+
+fn value_to_variable(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// Turns the basic assertions of a Postman test script into an expected status,
+/// and a Json value the response must contain.
+///
+/// The statements understood are:
+///
+///  * `pm.response.to.have.status(200)`
+///  * `pm.expect(pm.response.code).to.eql(200)` (or `to.equal`)
+///  * `pm.expect(pm.response.json().user.name).to.eql("Joe")` (or `to.equal`)
+///  * the same, using a variable assigned from `pm.response.json()`
+///
+/// All other statements are ignored.
+pub fn read_postman_test_script(script: &str) -> (Option<StatusCode>, Option<Value>) {
+    let mut expected_status = None;
+    let mut expected_json = Map::new();
+
+    // i.e. `var jsonData = pm.response.json();`
+    let mut json_sources = vec!["pm.response.json()".to_string()];
+    for statement in script.split([';', '\n']) {
+        let statement = statement.trim();
+        let maybe_declaration = ["var ", "let ", "const "]
+            .iter()
+            .find_map(|keyword| statement.strip_prefix(keyword));
+        if let Some((name, value)) =
+            maybe_declaration.and_then(|declaration| declaration.split_once('='))
+        {
+            if value.trim() == "pm.response.json()" {
+                json_sources.push(name.trim().to_string());
+            }
+        }
+    }
+
+    let mut rest = script;
+    while let Some(start) = rest.find("pm.response.to.have.status(") {
+        rest = &rest[start + "pm.response.to.have.status(".len()..];
+        if let Some(status) = take_parenthesised(rest).and_then(parse_status) {
+            expected_status = Some(status);
+        }
+    }
+
+    let mut rest = script;
+    while let Some(start) = rest.find("pm.expect(") {
+        rest = &rest[start + "pm.expect(".len()..];
+        let Some(subject) = take_parenthesised(rest) else {
+            continue;
+        };
+        let after_subject = &rest[subject.len() + 1..];
+        let Some(expected) = ["to.eql(", "to.equal(", "to.be.eql(", "to.deep.equal("]
+            .iter()
+            .find_map(|matcher| after_subject.strip_prefix('.')?.strip_prefix(matcher))
+            .and_then(take_parenthesised)
+        else {
+            continue;
+        };
+        let subject = subject.trim();
+
+        if subject == "pm.response.code" {
+            if let Some(status) = parse_status(expected) {
+                expected_status = Some(status);
+            }
+            continue;
+        }
+
+        let maybe_json_path = json_sources
+            .iter()
+            .find_map(|source| subject.strip_prefix(source.as_str())?.strip_prefix('.'));
+        if let (Some(json_path), Some(value)) = (maybe_json_path, parse_script_value(expected)) {
+            insert_json_path(&mut expected_json, json_path, value);
+        }
+    }
+
+    let expected_json = (!expected_json.is_empty()).then_some(Value::Object(expected_json));
+    (expected_status, expected_json)
+}
+
+/// Returns the text up to the closing parenthesis,
+/// for text which follows an opening one.
+fn take_parenthesised(text: &str) -> Option<&str> {
+    let mut depth = 0;
+    let mut maybe_quote = None;
+
+    for (i, c) in text.char_indices() {
+        match (maybe_quote, c) {
+            (Some(quote), c) if c == quote => maybe_quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => maybe_quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') if depth == 0 => return Some(&text[..i]),
+            (None, ')') => depth -= 1,
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn parse_status(text: &str) -> Option<StatusCode> {
+    text.trim()
+        .parse::<u16>()
+        .ok()
+        .and_then(|status| StatusCode::from_u16(status).ok())
+}
+
+fn parse_script_value(text: &str) -> Option<Value> {
+    let text = text.trim();
+    if let Some(single_quoted) = text
+        .strip_prefix('\'')
+        .and_then(|text| text.strip_suffix('\''))
+    {
+        return Some(Value::String(single_quoted.to_string()));
+    }
+
+    serde_json::from_str(text).ok()
+}
+
+/// Inserts the value at a dotted path, such as `user.name`.
+/// Paths with array indexes are skipped.
+fn insert_json_path(object: &mut Map<String, Value>, path: &str, value: Value) {
+    if path.contains(['[', ']', '(', ')']) {
+        return;
+    }
+
+    let mut segments = path.split('.').peekable();
+    let mut current = object;
+    while let Some(segment) = segments.next() {
+        if segments.peek().is_none() {
+            current.insert(segment.to_string(), value);
+            return;
+        }
+
+        let next = current
+            .entry(segment.to_string())
+            .or_insert_with(|| Value::Object(Map::new()));
+        let Value::Object(next) = next else {
+            return;
+        };
+        current = next;
+    }
+}
+
+#[cfg(test)]
+mod test_read_postman_test_script {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn it_should_read_status_assertions() {
+        let (status, json) = read_postman_test_script(
+            r#"pm.test("created", function () { pm.response.to.have.status(201); });"#,
+        );
+        assert_eq!(status, Some(StatusCode::CREATED));
+        assert_eq!(json, None);
+
+        let (status, _) = read_postman_test_script("pm.expect(pm.response.code).to.eql(404);");
+        assert_eq!(status, Some(StatusCode::NOT_FOUND));
+    }
+
+    #[test]
+    fn it_should_read_json_assertions() {
+        let (_, json) = read_postman_test_script(
+            r#"
+            var jsonData = pm.response.json();
+            pm.expect(jsonData.user.name).to.eql("Joe");
+            pm.expect(pm.response.json().user.age).to.equal(30);
+            pm.expect(jsonData.status).to.eql('active');
+            pm.expect(jsonData.items[0]).to.eql(1);
+            pm.expect(jsonData.count).to.be.above(1);
+            "#,
+        );
+
+        assert_eq!(
+            json,
+            Some(json!({
+                "user": { "name": "Joe", "age": 30 },
+                "status": "active",
+            }))
+        );
+    }
+
+    #[test]
+    fn it_should_ignore_scripts_without_assertions() {
+        assert_eq!(
+            read_postman_test_script("console.log(pm.response.text());"),
+            (None, None)
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_read_postman_collection {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn it_should_read_requests_in_folders() {
+        let collection = json!({
+            "info": { "schema": "https://schema.getpostman.com/json/collection/v2.1.0/collection.json" },
+            "variable": [
+                { "key": "base_url", "value": "https://api.example.com" },
+                { "key": "user_id", "value": "1" },
+            ],
+            "item": [
+                {
+                    "name": "Users",
+                    "item": [{
+                        "name": "Get user",
+                        "request": {
+                            "method": "GET",
+                            "url": { "raw": "{{base_url}}/users/{{user_id}}" },
+                            "header": [
+                                { "key": "x-api-key", "value": "{{api_key}}" },
+                                { "key": "x-debug", "value": "true", "disabled": true },
+                            ],
+                        },
+                    }],
+                },
+                {
+                    "name": "Ping",
+                    "request": "{{base_url}}/ping",
+                },
+            ],
+        });
+        let variables = HashMap::from([
+            ("api_key".to_string(), "my-key".to_string()),
+            ("user_id".to_string(), "2".to_string()),
+        ]);
+
+        let steps = read_postman_collection(&collection, &variables).unwrap();
+
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].name, "Users / Get user");
+        assert_eq!(steps[0].path, "/users/2");
+        assert_eq!(steps[0].headers.len(), 1);
+        assert_eq!(steps[0].headers[0].1, "my-key");
+        assert_eq!(steps[1].name, "Ping");
+        assert_eq!(steps[1].path, "/ping");
+    }
+
+    #[test]
+    fn it_should_error_on_unsupported_body_modes() {
+        let collection = json!({
+            "item": [{
+                "name": "Upload",
+                "request": {
+                    "method": "POST",
+                    "url": "/upload",
+                    "body": { "mode": "formdata", "formdata": [] },
+                },
+            }],
+        });
+
+        let error = read_postman_collection(&collection, &HashMap::new()).unwrap_err();
+        assert!(format!("{error:#}").contains("Unsupported body mode 'formdata'"));
+    }
+}
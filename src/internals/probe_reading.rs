@@ -0,0 +1,41 @@
+use std::any::Any;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::sync::Arc;
+
+/// A reading taken by an instrumentation probe while a request ran,
+/// kept on the response with its type erased.
+#[derive(Clone)]
+pub struct ProbeReading {
+    reading: Arc<dyn Any + Send + Sync>,
+    type_name: &'static str,
+}
+
+impl ProbeReading {
+    pub fn new<R>(reading: R) -> Self
+    where
+        R: Send + Sync + 'static,
+    {
+        Self {
+            reading: Arc::new(reading),
+            type_name: ::std::any::type_name::<R>(),
+        }
+    }
+
+    /// Returns a copy of the reading, if it is of the type given.
+    pub fn downcast<R>(&self) -> Option<R>
+    where
+        R: Clone + 'static,
+    {
+        self.reading.downcast_ref::<R>().cloned()
+    }
+}
+
+impl Debug for ProbeReading {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_tuple("ProbeReading")
+            .field(&self.type_name)
+            .finish()
+    }
+}
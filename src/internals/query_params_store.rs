@@ -4,6 +4,7 @@ use smallvec::SmallVec;
 use std::fmt::Display;
 use std::fmt::Formatter;
 use std::fmt::Result as FmtResult;
+use url::form_urlencoded;
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct QueryParamsStore {
@@ -35,6 +36,28 @@ impl QueryParamsStore {
         self.query_params.clear();
     }
 
+    /// Removes all params with the key given,
+    /// including those inside params added with many keys at once.
+    pub fn remove(&mut self, key: &str) {
+        for query in &mut self.query_params {
+            let is_removing = |pair: &str| {
+                form_urlencoded::parse(pair.as_bytes())
+                    .next()
+                    .is_some_and(|(pair_key, _)| pair_key == key)
+            };
+
+            if query.split('&').any(is_removing) {
+                *query = query
+                    .split('&')
+                    .filter(|pair| !is_removing(pair))
+                    .collect::<Vec<&str>>()
+                    .join("&");
+            }
+        }
+
+        self.query_params.retain(|query| !query.is_empty());
+    }
+
     pub fn is_empty(&self) -> bool {
         self.query_params.is_empty()
     }
@@ -138,3 +161,42 @@ mod test_add_raw {
         assert_eq!("key=value&another=value&more=value", params.to_string());
     }
 }
+
+#[cfg(test)]
+mod test_remove {
+    use crate::internals::QueryParamsStore;
+
+    #[test]
+    fn it_should_remove_params_with_key() {
+        let mut params = QueryParamsStore::new();
+
+        params.add([("page", "1"), ("sort", "name")]).unwrap();
+        params.add_raw("page=2".to_string());
+        params.add_raw("flag".to_string());
+        params.remove("page");
+
+        assert_eq!("sort=name&flag", params.to_string());
+    }
+
+    #[test]
+    fn it_should_remove_encoded_keys() {
+        let mut params = QueryParamsStore::new();
+
+        params
+            .add([("filter[name]", "joe"), ("other", "value")])
+            .unwrap();
+        params.remove("filter[name]");
+
+        assert_eq!("other=value", params.to_string());
+    }
+
+    #[test]
+    fn it_should_remove_single_keys() {
+        let mut params = QueryParamsStore::new();
+
+        params.add_raw("flag".to_string());
+        params.remove("flag");
+
+        assert!(params.is_empty());
+    }
+}
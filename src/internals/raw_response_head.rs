@@ -0,0 +1,151 @@
+use bytes::Bytes;
+use std::io::IoSlice;
+use std::io::Result as IoResult;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::ready;
+use std::task::Context;
+use std::task::Poll;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
+use tokio::io::ReadBuf;
+
+/// Added to a request to ask the transport to capture the raw head of the response.
+///
+/// This is only supported by the HTTP transport, for HTTP/1 requests.
+#[derive(Debug, Clone, Copy)]
+pub struct CaptureRawResponseHead;
+
+/// The raw bytes of a response head, exactly as it was read from the connection.
+/// This is added to the extensions of the response.
+#[derive(Debug, Clone)]
+pub struct RawResponseHead(pub Bytes);
+
+/// Wraps a connection, keeping a copy of the bytes read until a final response head is found.
+#[derive(Debug)]
+pub struct RecordingStream<S> {
+    inner: S,
+    recorded: Arc<Mutex<Vec<u8>>>,
+    is_recording: bool,
+}
+
+impl<S> RecordingStream<S> {
+    pub fn new(inner: S, recorded: Arc<Mutex<Vec<u8>>>) -> Self {
+        Self {
+            inner,
+            recorded,
+            is_recording: true,
+        }
+    }
+}
+
+impl<S> AsyncRead for RecordingStream<S>
+where
+    S: AsyncRead + Unpin,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<IoResult<()>> {
+        let filled_before = buf.filled().len();
+        ready!(Pin::new(&mut self.inner).poll_read(cx, buf))?;
+
+        if self.is_recording {
+            let mut recorded = self.recorded.lock().expect("Failed to lock recorded bytes");
+            recorded.extend_from_slice(&buf.filled()[filled_before..]);
+            let is_head_found = find_final_head(&recorded).is_some();
+            drop(recorded);
+
+            self.is_recording = !is_head_found;
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<S> AsyncWrite for RecordingStream<S>
+where
+    S: AsyncWrite + Unpin,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<IoResult<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<IoResult<usize>> {
+        Pin::new(&mut self.inner).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<IoResult<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<IoResult<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+/// Finds the first response head which is not informational (i.e. skipping a `100 Continue`),
+/// returning it with the blank line at the end.
+pub fn find_final_head(bytes: &[u8]) -> Option<&[u8]> {
+    let mut start = 0;
+
+    loop {
+        let head_length = bytes[start..]
+            .windows(4)
+            .position(|window| window == b"\r\n\r\n")?
+            + 4;
+        let head = &bytes[start..start + head_length];
+
+        let is_informational = head.starts_with(b"HTTP/") && head.get(9) == Some(&b'1');
+        if !is_informational {
+            return Some(head);
+        }
+
+        start += head_length;
+    }
+}
+
+#[cfg(test)]
+mod test_find_final_head {
+    use super::*;
+
+    #[test]
+    fn it_should_find_head_before_the_body() {
+        let bytes = b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello";
+
+        assert_eq!(
+            find_final_head(bytes),
+            Some(&b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\n"[..])
+        );
+    }
+
+    #[test]
+    fn it_should_skip_informational_heads() {
+        let bytes =
+            b"HTTP/1.1 103 Early Hints\r\nLink: </a.css>\r\n\r\nHTTP/1.1 204 No Content\r\n\r\n";
+
+        assert_eq!(
+            find_final_head(bytes),
+            Some(&b"HTTP/1.1 204 No Content\r\n\r\n"[..])
+        );
+    }
+
+    #[test]
+    fn it_should_return_none_for_incomplete_heads() {
+        assert_eq!(find_final_head(b"HTTP/1.1 200 OK\r\nContent-Le"), None);
+    }
+}
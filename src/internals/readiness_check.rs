@@ -0,0 +1,72 @@
+use anyhow::anyhow;
+use anyhow::Result;
+use axum::body::Body;
+use http::Method;
+use http::Request;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::OnceCell;
+use tokio::time::sleep;
+use tokio::time::Instant;
+use url::Url;
+
+use crate::transport_layer::TransportLayer;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Waits for the server to return a 2xx response from an endpoint,
+/// before the first request is sent.
+#[derive(Debug)]
+pub struct ReadinessCheck {
+    transport: Arc<dyn TransportLayer>,
+    url: Url,
+    timeout: Duration,
+    is_ready: OnceCell<()>,
+}
+
+impl ReadinessCheck {
+    pub fn new(transport: Arc<dyn TransportLayer>, url: Url, timeout: Duration) -> Self {
+        Self {
+            transport,
+            url,
+            timeout,
+            is_ready: OnceCell::new(),
+        }
+    }
+
+    /// Polls the server until it is ready. This only happens once,
+    /// and later calls return immediately.
+    pub async fn wait_until_ready(&self) -> Result<()> {
+        self.is_ready
+            .get_or_try_init(|| self.poll_until_ready())
+            .await?;
+
+        Ok(())
+    }
+
+    async fn poll_until_ready(&self) -> Result<()> {
+        let deadline = Instant::now() + self.timeout;
+
+        loop {
+            let request = Request::builder()
+                .method(Method::GET)
+                .uri(self.url.as_str())
+                .body(Body::empty())?;
+
+            let maybe_response = self.transport.send(request).await;
+            if matches!(maybe_response, Ok(response) if response.status().is_success()) {
+                return Ok(());
+            }
+
+            if Instant::now() >= deadline {
+                return Err(anyhow!(
+                    "Server was not ready within {:?}, expected 2xx response from GET {}",
+                    self.timeout,
+                    self.url
+                ));
+            }
+
+            sleep(POLL_INTERVAL).await;
+        }
+    }
+}
@@ -0,0 +1,5 @@
+/// Added to a request to ask the transport to connect with a receive buffer of the size given, in bytes.
+///
+/// This is only supported by the HTTP transport, for HTTP/1 requests.
+#[derive(Debug, Clone, Copy)]
+pub struct ReceiveBufferSize(pub usize);
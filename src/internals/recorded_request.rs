@@ -0,0 +1,13 @@
+use bytes::Bytes;
+use http::HeaderMap;
+use http::HeaderValue;
+
+/// A copy of the headers and body sent with a request,
+/// kept on the response for inspecting afterwards.
+///
+/// The body is only kept if it is within the configured size limit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedRequest {
+    pub headers: HeaderMap<HeaderValue>,
+    pub maybe_body: Option<Bytes>,
+}
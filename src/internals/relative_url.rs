@@ -0,0 +1,53 @@
+use url::Url;
+
+/// Resolves a link found in a response against the url of the request,
+/// in the same way a browser would.
+///
+/// Links on the same server are returned as a path (with any query),
+/// so they are sent to the `TestServer`.
+/// Links to other servers are returned as absolute urls.
+pub fn resolve_relative_url(request_url: &Url, target: &str) -> String {
+    let Ok(mut target_url) = request_url.join(target) else {
+        return target.to_string();
+    };
+    target_url.set_fragment(None);
+
+    if target_url.origin() != request_url.origin() {
+        return target_url.to_string();
+    }
+
+    match target_url.query() {
+        Some(query) => format!("{}?{query}", target_url.path()),
+        None => target_url.path().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test_resolve_relative_url {
+    use super::*;
+
+    fn resolve(target: &str) -> String {
+        let request_url = Url::parse("http://localhost/users/new?step=2").unwrap();
+        resolve_relative_url(&request_url, target)
+    }
+
+    #[test]
+    fn it_should_return_current_page_for_empty_target() {
+        assert_eq!(resolve(""), "/users/new?step=2");
+    }
+
+    #[test]
+    fn it_should_resolve_absolute_and_relative_paths() {
+        assert_eq!(resolve("/login"), "/login");
+        assert_eq!(resolve("create?draft=1#top"), "/users/create?draft=1");
+        assert_eq!(resolve("http://localhost/other"), "/other");
+    }
+
+    #[test]
+    fn it_should_keep_urls_on_other_servers_absolute() {
+        assert_eq!(
+            resolve("https://example.com/login"),
+            "https://example.com/login"
+        );
+    }
+}
@@ -0,0 +1,14 @@
+use bytes::Bytes;
+use http::HeaderMap;
+use http::HeaderValue;
+
+/// A copy of the headers and body sent with a request,
+/// kept so the request can be sent again.
+///
+/// This is only kept for requests sent with an `Idempotency-Key`,
+/// to avoid holding a copy of every request body.
+#[derive(Debug, Clone)]
+pub struct ReplayableRequest {
+    pub headers: HeaderMap<HeaderValue>,
+    pub body: Bytes,
+}
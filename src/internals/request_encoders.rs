@@ -0,0 +1,85 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::sync::Arc;
+
+use crate::internals::content_type_essence;
+
+type RequestEncoderFn = dyn Fn(&Value) -> Result<Vec<u8>> + Send + Sync;
+
+/// Encoders for request bodies, registered by content type,
+/// for use by [`TestRequest::body_as()`](crate::TestRequest::body_as()).
+#[derive(Clone, Default)]
+pub struct RequestEncoders {
+    encoders: Vec<(String, Arc<RequestEncoderFn>)>,
+}
+
+impl RequestEncoders {
+    /// Adds the encoder for the content type given,
+    /// replacing any encoder already registered for it.
+    pub fn register<F>(&mut self, content_type: &str, encoder: F)
+    where
+        F: Fn(&Value) -> Result<Vec<u8>> + Send + Sync + 'static,
+    {
+        let essence = content_type_essence(content_type);
+
+        self.encoders
+            .retain(|(registered, _)| *registered != essence);
+        self.encoders.push((essence, Arc::new(encoder)));
+    }
+
+    /// Returns the encoder registered for the content type given, if there is one.
+    ///
+    /// Parameters, such as the `charset`, are ignored when matching.
+    pub fn find(&self, content_type: &str) -> Option<&RequestEncoderFn> {
+        let essence = content_type_essence(content_type);
+
+        self.encoders
+            .iter()
+            .find(|(registered, _)| *registered == essence)
+            .map(|(_, encoder)| encoder.as_ref())
+    }
+}
+
+impl Debug for RequestEncoders {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let content_types: Vec<&str> = self
+            .encoders
+            .iter()
+            .map(|(content_type, _)| content_type.as_str())
+            .collect();
+
+        f.debug_struct("RequestEncoders")
+            .field("content_types", &content_types)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test_find {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn it_should_find_encoder_ignoring_case_and_parameters() {
+        let mut encoders = RequestEncoders::default();
+        encoders.register("application/vnd.my+cbor", |_| Ok(b"encoded".to_vec()));
+
+        let encoder = encoders
+            .find("Application/Vnd.My+CBOR; charset=utf-8")
+            .unwrap();
+        assert_eq!(encoder(&json!(null)).unwrap(), b"encoded");
+    }
+
+    #[test]
+    fn it_should_replace_encoder_registered_for_the_same_content_type() {
+        let mut encoders = RequestEncoders::default();
+        encoders.register("application/vnd.my+cbor", |_| Ok(b"first".to_vec()));
+        encoders.register("application/vnd.my+cbor", |_| Ok(b"second".to_vec()));
+
+        let encoder = encoders.find("application/vnd.my+cbor").unwrap();
+        assert_eq!(encoder(&json!(null)).unwrap(), b"second");
+    }
+}
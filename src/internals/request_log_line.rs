@@ -0,0 +1,102 @@
+use http::Method;
+use http::StatusCode;
+use std::env;
+use std::time::Duration;
+use url::Url;
+
+/// The environment variable which turns on logging for all test servers.
+pub const VERBOSE_ENV_VAR: &str = "AXUM_TEST_VERBOSE";
+
+/// Returns true if `AXUM_TEST_VERBOSE` is set to anything other than empty, `0`, or `false`.
+pub fn is_verbose_env_enabled() -> bool {
+    env::var(VERBOSE_ENV_VAR).is_ok_and(|value| is_verbose_env_value(&value))
+}
+
+fn is_verbose_env_value(value: &str) -> bool {
+    let value = value.trim();
+    !value.is_empty() && value != "0" && !value.eq_ignore_ascii_case("false")
+}
+
+/// Formats a single aligned line describing a request and its response.
+///
+/// The body size is `None` for streamed responses, which are logged as `streaming`.
+pub fn format_request_log_line(
+    method: &Method,
+    url: &Url,
+    status_code: StatusCode,
+    duration: Duration,
+    maybe_body_size: Option<usize>,
+) -> String {
+    let path = match url.query() {
+        Some(query) => format!("{}?{query}", url.path()),
+        None => url.path().to_string(),
+    };
+    let method = method.as_str();
+    let duration_ms = duration.as_secs_f64() * 1000.0;
+    let body_size = match maybe_body_size {
+        Some(body_size) => format!("{body_size:>9} bytes"),
+        None => format!("{:>15}", "streaming"),
+    };
+
+    format!(
+        "axum-test {method:<7} {path:<40} {} {duration_ms:>9.2}ms {body_size}",
+        status_code.as_u16()
+    )
+}
+
+#[cfg(test)]
+mod test_format_request_log_line {
+    use super::*;
+
+    #[test]
+    fn it_should_format_aligned_line() {
+        let url = Url::parse("http://localhost/users?page=2").unwrap();
+        let line = format_request_log_line(
+            &Method::GET,
+            &url,
+            StatusCode::OK,
+            Duration::from_micros(1500),
+            Some(42),
+        );
+
+        assert_eq!(
+            line,
+            "axum-test GET     /users?page=2                            200      1.50ms        42 bytes"
+        );
+    }
+
+    #[test]
+    fn it_should_format_streaming_line_in_place_of_body_size() {
+        let url = Url::parse("http://localhost/events").unwrap();
+        let line = format_request_log_line(
+            &Method::GET,
+            &url,
+            StatusCode::OK,
+            Duration::from_micros(1500),
+            None,
+        );
+
+        assert_eq!(
+            line,
+            "axum-test GET     /events                                  200      1.50ms       streaming"
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_is_verbose_env_value {
+    use super::*;
+
+    #[test]
+    fn it_should_be_enabled_for_truthy_values() {
+        assert!(is_verbose_env_value("1"));
+        assert!(is_verbose_env_value("true"));
+    }
+
+    #[test]
+    fn it_should_be_disabled_for_falsy_values() {
+        assert!(!is_verbose_env_value(""));
+        assert!(!is_verbose_env_value("0"));
+        assert!(!is_verbose_env_value("FALSE"));
+    }
+}
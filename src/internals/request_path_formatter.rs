@@ -1,7 +1,11 @@
+use http::header;
 use http::Method;
 use std::fmt;
 
+use crate::internals::DataMask;
 use crate::internals::QueryParamsStore;
+use crate::internals::RecordedRequest;
+use crate::internals::MASKED_VALUE;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct RequestPathFormatter<'a> {
@@ -10,6 +14,7 @@ pub struct RequestPathFormatter<'a> {
     /// This is the path that the user requested.
     user_requested_path: &'a str,
     query_params: Option<&'a QueryParamsStore>,
+    maybe_recorded_request: Option<(&'a RecordedRequest, &'a DataMask)>,
 }
 
 impl<'a> RequestPathFormatter<'a> {
@@ -22,8 +27,67 @@ impl<'a> RequestPathFormatter<'a> {
             method,
             user_requested_path,
             query_params,
+            maybe_recorded_request: None,
         }
     }
+
+    /// Adds the content type, body size, and headers of the request sent to the output.
+    ///
+    /// The values of secret headers, such as `Authorization` and `Cookie`,
+    /// and those in the mask given, are hidden.
+    pub fn with_recorded_request(
+        mut self,
+        recorded_request: &'a RecordedRequest,
+        data_mask: &'a DataMask,
+    ) -> Self {
+        self.maybe_recorded_request = Some((recorded_request, data_mask));
+        self
+    }
+
+    fn fmt_recorded_request(
+        f: &mut fmt::Formatter<'_>,
+        recorded_request: &RecordedRequest,
+        data_mask: &DataMask,
+    ) -> fmt::Result {
+        let mut details = Vec::new();
+
+        if let Some(content_type) = recorded_request.headers.get(header::CONTENT_TYPE) {
+            details.push(format!(
+                "content-type: {}",
+                String::from_utf8_lossy(content_type.as_bytes())
+            ));
+        }
+
+        match &recorded_request.maybe_body {
+            Some(body) if body.is_empty() => {}
+            Some(body) => details.push(format!("body: {} bytes", body.len())),
+            None => details.push("body: not recorded".to_string()),
+        }
+
+        for (header_name, header_value) in &recorded_request.headers {
+            if header_name == header::CONTENT_TYPE
+                || header_name == header::CONTENT_LENGTH
+                || header_name == header::HOST
+            {
+                continue;
+            }
+
+            if data_mask.is_masked_header(header_name) {
+                details.push(format!("{header_name}: {MASKED_VALUE}"));
+            } else {
+                details.push(format!(
+                    "{header_name}: {}",
+                    String::from_utf8_lossy(header_value.as_bytes())
+                ));
+            }
+        }
+
+        if details.is_empty() {
+            return Ok(());
+        }
+
+        write!(f, " ({})", details.join(", "))
+    }
 }
 
 impl fmt::Display for RequestPathFormatter<'_> {
@@ -33,16 +97,23 @@ impl fmt::Display for RequestPathFormatter<'_> {
 
         match self.query_params {
             None => {
-                write!(f, "{method} {user_requested_path}")
+                write!(f, "{method} {user_requested_path}")?;
             }
             Some(query_params) => {
                 if query_params.is_empty() {
-                    write!(f, "{method} {user_requested_path}")
+                    write!(f, "{method} {user_requested_path}")?;
                 } else {
-                    write!(f, "{method} {user_requested_path}?{query_params}")
+                    write!(f, "{method} {user_requested_path}?{query_params}")?;
                 }
             }
         }
+
+        match self.maybe_recorded_request {
+            None => Ok(()),
+            Some((recorded_request, data_mask)) => {
+                Self::fmt_recorded_request(f, recorded_request, data_mask)
+            }
+        }
     }
 }
 
@@ -79,3 +150,86 @@ mod test_fmt {
         assert_eq!(output, "GET /donkeys?value=123&another-value");
     }
 }
+
+#[cfg(test)]
+mod test_fmt_with_recorded_request {
+    use super::*;
+    use bytes::Bytes;
+    use http::HeaderMap;
+    use http::HeaderValue;
+
+    #[test]
+    fn it_should_not_add_details_for_empty_requests() {
+        let recorded_request = RecordedRequest {
+            headers: HeaderMap::new(),
+            maybe_body: Some(Bytes::new()),
+        };
+        let data_mask = DataMask::default();
+        let debug = RequestPathFormatter::new(&Method::GET, "/donkeys", None)
+            .with_recorded_request(&recorded_request, &data_mask);
+
+        assert_eq!(debug.to_string(), "GET /donkeys");
+    }
+
+    #[test]
+    fn it_should_add_content_type_body_size_and_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        );
+        headers.insert("x-request-id", HeaderValue::from_static("abc"));
+        let recorded_request = RecordedRequest {
+            headers,
+            maybe_body: Some(Bytes::from_static(b"{}")),
+        };
+        let data_mask = DataMask::default();
+        let debug = RequestPathFormatter::new(&Method::POST, "/donkeys", None)
+            .with_recorded_request(&recorded_request, &data_mask);
+
+        assert_eq!(
+            debug.to_string(),
+            "POST /donkeys (content-type: application/json, body: 2 bytes, x-request-id: abc)"
+        );
+    }
+
+    #[test]
+    fn it_should_mask_secret_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer secret"),
+        );
+        headers.insert(header::COOKIE, HeaderValue::from_static("session=secret"));
+        let recorded_request = RecordedRequest {
+            headers,
+            maybe_body: None,
+        };
+        let data_mask = DataMask::default();
+        let debug = RequestPathFormatter::new(&Method::GET, "/donkeys", None)
+            .with_recorded_request(&recorded_request, &data_mask);
+
+        assert_eq!(
+            debug.to_string(),
+            "GET /donkeys (body: not recorded, authorization: ***, cookie: ***)"
+        );
+    }
+
+    #[test]
+    fn it_should_mask_headers_given() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", HeaderValue::from_static("secret"));
+        let recorded_request = RecordedRequest {
+            headers,
+            maybe_body: None,
+        };
+        let data_mask = DataMask::new(&["X-Api-Key".to_string()], &[]).unwrap();
+        let debug = RequestPathFormatter::new(&Method::GET, "/donkeys", None)
+            .with_recorded_request(&recorded_request, &data_mask);
+
+        assert_eq!(
+            debug.to_string(),
+            "GET /donkeys (body: not recorded, x-api-key: ***)"
+        );
+    }
+}
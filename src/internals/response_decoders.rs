@@ -0,0 +1,102 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::sync::Arc;
+
+type ResponseDecoderFn = dyn Fn(&[u8]) -> Result<Value> + Send + Sync;
+
+/// Decoders for response bodies, registered by content type,
+/// for use by [`TestResponse::decode()`](crate::TestResponse::decode()).
+#[derive(Clone, Default)]
+pub struct ResponseDecoders {
+    decoders: Vec<(String, Arc<ResponseDecoderFn>)>,
+}
+
+impl ResponseDecoders {
+    /// Adds the decoder for the content type given,
+    /// replacing any decoder already registered for it.
+    pub fn register<F>(&mut self, content_type: &str, decoder: F)
+    where
+        F: Fn(&[u8]) -> Result<Value> + Send + Sync + 'static,
+    {
+        let essence = content_type_essence(content_type);
+
+        self.decoders
+            .retain(|(registered, _)| *registered != essence);
+        self.decoders.push((essence, Arc::new(decoder)));
+    }
+
+    /// Returns the decoder registered for the content type given, if there is one.
+    ///
+    /// Parameters, such as the `charset`, are ignored when matching.
+    pub fn find(&self, content_type: &str) -> Option<&ResponseDecoderFn> {
+        let essence = content_type_essence(content_type);
+
+        self.decoders
+            .iter()
+            .find(|(registered, _)| *registered == essence)
+            .map(|(_, decoder)| decoder.as_ref())
+    }
+}
+
+impl Debug for ResponseDecoders {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let content_types: Vec<&str> = self
+            .decoders
+            .iter()
+            .map(|(content_type, _)| content_type.as_str())
+            .collect();
+
+        f.debug_struct("ResponseDecoders")
+            .field("content_types", &content_types)
+            .finish()
+    }
+}
+
+/// Returns the content type without parameters, in lowercase.
+/// i.e. `Application/Vnd.My+CBOR; charset=utf-8` becomes `application/vnd.my+cbor`.
+pub fn content_type_essence(content_type: &str) -> String {
+    content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod test_find {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn it_should_find_decoder_ignoring_case_and_parameters() {
+        let mut decoders = ResponseDecoders::default();
+        decoders.register("application/vnd.my+cbor", |_| Ok(json!("decoded")));
+
+        let decoder = decoders
+            .find("Application/Vnd.My+CBOR; charset=utf-8")
+            .unwrap();
+        assert_eq!(decoder(b"").unwrap(), json!("decoded"));
+    }
+
+    #[test]
+    fn it_should_not_find_decoder_for_other_content_types() {
+        let mut decoders = ResponseDecoders::default();
+        decoders.register("application/vnd.my+cbor", |_| Ok(json!("decoded")));
+
+        assert!(decoders.find("application/cbor").is_none());
+    }
+
+    #[test]
+    fn it_should_replace_decoder_registered_for_the_same_content_type() {
+        let mut decoders = ResponseDecoders::default();
+        decoders.register("application/vnd.my+cbor", |_| Ok(json!("first")));
+        decoders.register("application/vnd.my+cbor", |_| Ok(json!("second")));
+
+        let decoder = decoders.find("application/vnd.my+cbor").unwrap();
+        assert_eq!(decoder(b"").unwrap(), json!("second"));
+    }
+}
@@ -0,0 +1,55 @@
+/// Returns true if the path given would be matched by the Axum route pattern given.
+///
+/// Patterns use Axum's syntax, where `:name` matches a single segment,
+/// and `*name` matches all remaining segments.
+pub fn is_route_match(route: &str, path: &str) -> bool {
+    let mut route_segments = route.split('/');
+    let mut path_segments = path.split('/');
+
+    loop {
+        match (route_segments.next(), path_segments.next()) {
+            (None, None) => return true,
+            (Some(route_segment), Some(_)) if route_segment.starts_with('*') => return true,
+            (Some(route_segment), Some(path_segment)) => {
+                let is_segment_match = if route_segment.starts_with(':') {
+                    !path_segment.is_empty()
+                } else {
+                    route_segment == path_segment
+                };
+
+                if !is_segment_match {
+                    return false;
+                }
+            }
+            _ => return false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_is_route_match {
+    use super::*;
+
+    #[test]
+    fn it_should_match_static_routes_exactly() {
+        assert!(is_route_match("/login", "/login"));
+        assert!(!is_route_match("/login", "/logout"));
+        assert!(!is_route_match("/login", "/login/extra"));
+        assert!(!is_route_match("/login", "/login/"));
+    }
+
+    #[test]
+    fn it_should_match_params_against_a_single_segment() {
+        assert!(is_route_match("/users/:id", "/users/123"));
+        assert!(is_route_match("/users/:id/posts", "/users/abc/posts"));
+        assert!(!is_route_match("/users/:id", "/users/"));
+        assert!(!is_route_match("/users/:id", "/users/123/posts"));
+    }
+
+    #[test]
+    fn it_should_match_wildcards_against_remaining_segments() {
+        assert!(is_route_match("/files/*path", "/files/a"));
+        assert!(is_route_match("/files/*path", "/files/a/b/c"));
+        assert!(!is_route_match("/files/*path", "/files"));
+    }
+}
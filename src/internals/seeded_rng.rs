@@ -0,0 +1,145 @@
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+/// A small deterministic random number generator (SplitMix64).
+///
+/// This exists so behaviour driven by randomness can be reproduced,
+/// by reusing the same seed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Creates a generator seeded from the current time.
+    ///
+    /// A counter is mixed in, so generators created at the same instant still differ.
+    pub fn from_time() -> Self {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_nanos() as u64)
+            .unwrap_or_default();
+        let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+
+        Self::new(nanos ^ count.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in the range `0.0..1.0`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns a random (version 4) UUID, formatted in lowercase hex.
+    pub fn next_uuid_v4(&mut self) -> String {
+        let mut bytes = [0_u8; 16];
+        bytes[..8].copy_from_slice(&self.next_u64().to_be_bytes());
+        bytes[8..].copy_from_slice(&self.next_u64().to_be_bytes());
+
+        // Set the version and variant bits.
+        bytes[6] = (bytes[6] & 0x0f) | 0x40;
+        bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+        let hex: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+        format!(
+            "{}-{}-{}-{}-{}",
+            &hex[0..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..32]
+        )
+    }
+
+    /// Returns true with the probability given, where `1.0` is always.
+    pub fn next_bool(&mut self, probability: f64) -> bool {
+        probability > 0.0 && self.next_f64() < probability
+    }
+}
+
+#[cfg(test)]
+mod test_next_u64 {
+    use super::*;
+
+    #[test]
+    fn it_should_produce_the_same_sequence_for_the_same_seed() {
+        let mut first = SeededRng::new(123);
+        let mut second = SeededRng::new(123);
+
+        for _ in 0..10 {
+            assert_eq!(first.next_u64(), second.next_u64());
+        }
+    }
+
+    #[test]
+    fn it_should_produce_different_sequences_for_different_seeds() {
+        let mut first = SeededRng::new(123);
+        let mut second = SeededRng::new(456);
+
+        assert_ne!(first.next_u64(), second.next_u64());
+    }
+}
+
+#[cfg(test)]
+mod test_next_uuid_v4 {
+    use super::*;
+
+    #[test]
+    fn it_should_format_as_version_4_uuid() {
+        let uuid = SeededRng::new(123).next_uuid_v4();
+        let groups: Vec<&str> = uuid.split('-').collect();
+
+        assert_eq!(uuid.len(), 36);
+        assert_eq!(
+            groups.iter().map(|group| group.len()).collect::<Vec<_>>(),
+            vec![8, 4, 4, 4, 12]
+        );
+        assert!(groups[2].starts_with('4'));
+        assert!(matches!(
+            groups[3].chars().next(),
+            Some('8'..='9' | 'a'..='b')
+        ));
+    }
+
+    #[test]
+    fn it_should_produce_different_uuids() {
+        let mut rng = SeededRng::new(123);
+
+        assert_ne!(rng.next_uuid_v4(), rng.next_uuid_v4());
+    }
+}
+
+#[cfg(test)]
+mod test_next_bool {
+    use super::*;
+
+    #[test]
+    fn it_should_never_be_true_for_zero_probability() {
+        let mut rng = SeededRng::new(123);
+
+        assert!((0..1000).all(|_| !rng.next_bool(0.0)));
+    }
+
+    #[test]
+    fn it_should_always_be_true_for_one_probability() {
+        let mut rng = SeededRng::new(123);
+
+        assert!((0..1000).all(|_| rng.next_bool(1.0)));
+    }
+}
@@ -0,0 +1,232 @@
+use axum::body::Body;
+use axum::extract::Request;
+use axum::response::Response;
+use bytes::Bytes;
+use http_body::Frame;
+use http_body::SizeHint;
+use std::convert::Infallible;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::task::ready;
+use std::task::Context;
+use std::task::Poll;
+use tower::Service;
+
+use crate::internals::build_panic_response;
+use crate::internals::take_catch_panic_marker;
+use crate::internals::CatchPanic;
+
+/// Tracks the connections, and requests, passing through a running service.
+#[derive(Debug, Default)]
+pub struct ServeTracker {
+    open_connections: AtomicUsize,
+    active_requests: AtomicUsize,
+    has_stopped: AtomicBool,
+    is_catching_panics: bool,
+}
+
+impl ServeTracker {
+    /// Creates a tracker, which returns a `500` response for handlers which panic
+    /// when `is_catching_panics` is set.
+    pub fn new(is_catching_panics: bool) -> Self {
+        Self {
+            is_catching_panics,
+            ..Self::default()
+        }
+    }
+
+    /// Wraps the service for a new connection, counting it as open until it is dropped.
+    pub fn track_connection<S>(self: &Arc<Self>, service: S) -> TrackedService<S> {
+        TrackedService {
+            inner: service,
+            tracker: Arc::clone(self),
+            _connection: Arc::new(ConnectionGuard::new(Arc::clone(self))),
+        }
+    }
+
+    /// Marks the service as having stopped without panicking.
+    pub fn mark_stopped(&self) {
+        self.has_stopped.store(true, Ordering::SeqCst);
+    }
+
+    pub fn has_stopped(&self) -> bool {
+        self.has_stopped.load(Ordering::SeqCst)
+    }
+
+    pub fn open_connections(&self) -> usize {
+        self.open_connections.load(Ordering::SeqCst)
+    }
+
+    pub fn active_requests(&self) -> usize {
+        self.active_requests.load(Ordering::SeqCst)
+    }
+}
+
+/// Held by every clone of the service for a connection,
+/// which Hyper drops once the connection is closed.
+#[derive(Debug)]
+struct ConnectionGuard(Arc<ServeTracker>);
+
+impl ConnectionGuard {
+    fn new(tracker: Arc<ServeTracker>) -> Self {
+        tracker.open_connections.fetch_add(1, Ordering::SeqCst);
+        Self(tracker)
+    }
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.0.open_connections.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Held from when a request is received, until its response body is dropped.
+#[derive(Debug)]
+struct RequestGuard(Arc<ServeTracker>);
+
+impl RequestGuard {
+    fn new(tracker: Arc<ServeTracker>) -> Self {
+        tracker.active_requests.fetch_add(1, Ordering::SeqCst);
+        Self(tracker)
+    }
+}
+
+impl Drop for RequestGuard {
+    fn drop(&mut self) {
+        self.0.active_requests.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TrackedService<S> {
+    inner: S,
+    tracker: Arc<ServeTracker>,
+    _connection: Arc<ConnectionGuard>,
+}
+
+impl<S> Service<Request> for TrackedService<S>
+where
+    S: Service<Request, Response = Response, Error = Infallible>,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = TrackedFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request) -> Self::Future {
+        let request_guard = RequestGuard::new(Arc::clone(&self.tracker));
+        let is_test_request = take_catch_panic_marker(request.headers_mut());
+
+        TrackedFuture {
+            inner: CatchPanic::new(self.inner.call(request), self.tracker.is_catching_panics),
+            is_test_request,
+            maybe_request_guard: Some(request_guard),
+        }
+    }
+}
+
+pub struct TrackedFuture<F> {
+    inner: CatchPanic<F>,
+    is_test_request: bool,
+    maybe_request_guard: Option<RequestGuard>,
+}
+
+impl<F> Future for TrackedFuture<F>
+where
+    F: Future<Output = Result<Response, Infallible>>,
+{
+    type Output = Result<Response, Infallible>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Caught panics are returned as a response, rather than the connection being dropped.
+        // Only a `TestRequest` is given the panic message.
+        let is_test_request = self.is_test_request;
+        let result = ready!(Pin::new(&mut self.inner).poll(cx)).unwrap_or_else(|message| {
+            Ok(build_panic_response(
+                is_test_request.then_some(message.as_str()),
+            ))
+        });
+        let maybe_request_guard = self.maybe_request_guard.take();
+
+        Poll::Ready(result.map(|response| {
+            response.map(|body| {
+                Body::new(TrackedBody {
+                    inner: body,
+                    _maybe_request_guard: maybe_request_guard,
+                })
+            })
+        }))
+    }
+}
+
+/// Hyper drops the response body once it has all been written,
+/// which is before the client can finish reading it.
+struct TrackedBody {
+    inner: Body,
+    _maybe_request_guard: Option<RequestGuard>,
+}
+
+impl http_body::Body for TrackedBody {
+    type Data = Bytes;
+    type Error = axum::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        Pin::new(&mut self.inner).poll_frame(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod test_track_connection {
+    use super::*;
+    use axum::routing::get;
+    use axum::Router;
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn it_should_count_connections_until_all_clones_are_dropped() {
+        let tracker = Arc::new(ServeTracker::default());
+        let service = tracker.track_connection(Router::<()>::new());
+        let cloned_service = service.clone();
+        assert_eq!(tracker.open_connections(), 1);
+
+        drop(service);
+        assert_eq!(tracker.open_connections(), 1);
+
+        drop(cloned_service);
+        assert_eq!(tracker.open_connections(), 0);
+    }
+
+    #[tokio::test]
+    async fn it_should_count_requests_until_the_response_body_is_dropped() {
+        let tracker = Arc::new(ServeTracker::default());
+        let app = Router::new().route("/ping", get(|| async { "pong!" }));
+        let service = tracker.track_connection(app);
+
+        let request = Request::get("/ping").body(Body::empty()).unwrap();
+        let response = service.oneshot(request).await.unwrap();
+        assert_eq!(tracker.active_requests(), 1);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, "pong!");
+        assert_eq!(tracker.active_requests(), 0);
+    }
+}
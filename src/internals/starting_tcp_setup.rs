@@ -1,14 +1,32 @@
-use anyhow::Context;
-use anyhow::Result;
 use reserve_port::ReservedPort;
+use socket2::Domain;
+use socket2::Protocol;
+use socket2::Socket;
+use socket2::Type;
+use std::io::Result as IoResult;
 use std::net::IpAddr;
 use std::net::Ipv4Addr;
+use std::net::Ipv6Addr;
 use std::net::SocketAddr;
 use std::net::TcpListener as StdTcpListener;
+use std::ops::RangeInclusive;
 use tokio::net::TcpListener as TokioTcpListener;
 
+use crate::internals::SeededRng;
+use crate::Error;
+
 pub const DEFAULT_IP_ADDRESS: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
 
+/// Binding on this address accepts both IPv4 and IPv6 connections.
+pub const DUAL_STACK_IP_ADDRESS: IpAddr = IpAddr::V6(Ipv6Addr::UNSPECIFIED);
+
+/// The number of pending connections allowed, matching the standard library.
+const LISTEN_BACKLOG: i32 = 128;
+
+/// The dynamic port range, which seeded ports are picked from.
+const SEEDED_PORT_RANGE: RangeInclusive<u16> = 49152..=65535;
+const SEEDED_PORT_ATTEMPTS: usize = 100;
+
 pub struct StartingTcpSetup {
     pub maybe_reserved_port: Option<ReservedPort>,
     pub socket_addr: SocketAddr,
@@ -16,21 +34,31 @@ pub struct StartingTcpSetup {
 }
 
 impl StartingTcpSetup {
-    pub fn new(maybe_ip: Option<IpAddr>, maybe_port: Option<u16>) -> Result<Self> {
+    pub fn new(
+        maybe_ip: Option<IpAddr>,
+        maybe_port: Option<u16>,
+        maybe_port_seed: Option<u64>,
+    ) -> Result<Self, Error> {
         let ip = maybe_ip.unwrap_or(DEFAULT_IP_ADDRESS);
 
-        maybe_port
-            .map(|port| Self::new_with_port(ip, port))
-            .unwrap_or_else(|| Self::new_without_port(ip))
+        match (maybe_port, maybe_port_seed) {
+            (Some(port), _) => Self::new_with_port(ip, port),
+            (None, Some(port_seed)) => Self::new_with_seeded_port(ip, port_seed),
+            (None, None) => Self::new_without_port(ip),
+        }
     }
 
-    fn new_with_port(ip: IpAddr, port: u16) -> Result<Self> {
-        ReservedPort::reserve_port(port)?;
+    fn new_with_port(ip: IpAddr, port: u16) -> Result<Self, Error> {
+        ReservedPort::reserve_port(port).map_err(|error| Error::PortReserveFailed {
+            message: format!("port {port}, {error}"),
+        })?;
         let socket_addr = SocketAddr::new(ip, port);
-        let std_tcp_listener = StdTcpListener::bind(socket_addr)
-            .context("Failed to create TCPListener for TestServer")?;
-        std_tcp_listener.set_nonblocking(true)?;
-        let tokio_tcp_listener = TokioTcpListener::from_std(std_tcp_listener)?;
+        let std_tcp_listener =
+            bind_std_tcp_listener(socket_addr).map_err(|source| Error::PortBindFailed {
+                address: socket_addr,
+                source,
+            })?;
+        let tokio_tcp_listener = into_tokio_tcp_listener(socket_addr, std_tcp_listener)?;
 
         Ok(Self {
             maybe_reserved_port: None,
@@ -39,11 +67,49 @@ impl StartingTcpSetup {
         })
     }
 
-    fn new_without_port(ip: IpAddr) -> Result<Self> {
-        let (reserved_port, std_tcp_listener) = ReservedPort::random_with_tcp(ip)?;
+    fn new_with_seeded_port(ip: IpAddr, port_seed: u64) -> Result<Self, Error> {
+        let mut rng = SeededRng::new(port_seed);
+        let range_start = *SEEDED_PORT_RANGE.start() as u64;
+        let range_len = (*SEEDED_PORT_RANGE.end() - *SEEDED_PORT_RANGE.start()) as u64 + 1;
+
+        for _ in 0..SEEDED_PORT_ATTEMPTS {
+            let port = (range_start + rng.next_u64() % range_len) as u16;
+            let socket_addr = SocketAddr::new(ip, port);
+
+            // Ports in use are skipped, moving on to the next port from the seed.
+            let Ok(std_tcp_listener) = bind_std_tcp_listener(socket_addr) else {
+                continue;
+            };
+            ReservedPort::reserve_port(port).map_err(|error| Error::PortReserveFailed {
+                message: format!("port {port}, {error}"),
+            })?;
+            let tokio_tcp_listener = into_tokio_tcp_listener(socket_addr, std_tcp_listener)?;
+
+            return Ok(Self {
+                maybe_reserved_port: None,
+                socket_addr,
+                tcp_listener: tokio_tcp_listener,
+            });
+        }
+
+        Err(Error::PortReserveFailed {
+            message: format!(
+                "no free port found after {SEEDED_PORT_ATTEMPTS} attempts using seed {port_seed}"
+            ),
+        })
+    }
+
+    fn new_without_port(ip: IpAddr) -> Result<Self, Error> {
+        if ip == DUAL_STACK_IP_ADDRESS {
+            return Self::new_dual_stack_without_port();
+        }
+
+        let (reserved_port, std_tcp_listener) =
+            ReservedPort::random_with_tcp(ip).map_err(|error| Error::PortReserveFailed {
+                message: error.to_string(),
+            })?;
         let socket_addr = SocketAddr::new(ip, reserved_port.port());
-        std_tcp_listener.set_nonblocking(true)?;
-        let tokio_tcp_listener = TokioTcpListener::from_std(std_tcp_listener)?;
+        let tokio_tcp_listener = into_tokio_tcp_listener(socket_addr, std_tcp_listener)?;
 
         Ok(Self {
             maybe_reserved_port: Some(reserved_port),
@@ -51,12 +117,64 @@ impl StartingTcpSetup {
             tcp_listener: tokio_tcp_listener,
         })
     }
+
+    fn new_dual_stack_without_port() -> Result<Self, Error> {
+        let reserved_port = ReservedPort::random().map_err(|error| Error::PortReserveFailed {
+            message: error.to_string(),
+        })?;
+        let socket_addr = SocketAddr::new(DUAL_STACK_IP_ADDRESS, reserved_port.port());
+        let std_tcp_listener =
+            bind_std_tcp_listener(socket_addr).map_err(|source| Error::PortBindFailed {
+                address: socket_addr,
+                source,
+            })?;
+        let tokio_tcp_listener = into_tokio_tcp_listener(socket_addr, std_tcp_listener)?;
+
+        Ok(Self {
+            maybe_reserved_port: Some(reserved_port),
+            socket_addr,
+            tcp_listener: tokio_tcp_listener,
+        })
+    }
+}
+
+/// Binds a listener to the address given.
+///
+/// The dual stack address is bound with IPv4 connections explicitly allowed,
+/// as whether they are by default differs between platforms.
+fn bind_std_tcp_listener(socket_addr: SocketAddr) -> IoResult<StdTcpListener> {
+    if socket_addr.ip() != DUAL_STACK_IP_ADDRESS {
+        return StdTcpListener::bind(socket_addr);
+    }
+
+    let socket = Socket::new(Domain::IPV6, Type::STREAM, Some(Protocol::TCP))?;
+    socket.set_only_v6(false)?;
+    #[cfg(not(windows))]
+    socket.set_reuse_address(true)?;
+    socket.bind(&socket_addr.into())?;
+    socket.listen(LISTEN_BACKLOG)?;
+
+    Ok(socket.into())
+}
+
+fn into_tokio_tcp_listener(
+    socket_addr: SocketAddr,
+    std_tcp_listener: StdTcpListener,
+) -> Result<TokioTcpListener, Error> {
+    std_tcp_listener
+        .set_nonblocking(true)
+        .and_then(|_| TokioTcpListener::from_std(std_tcp_listener))
+        .map_err(|source| Error::PortBindFailed {
+            address: socket_addr,
+            source,
+        })
 }
 
 #[cfg(test)]
 mod test_new {
     use super::*;
     use regex::Regex;
+    use std::io::ErrorKind;
     use std::net::Ipv4Addr;
 
     #[tokio::test]
@@ -64,7 +182,7 @@ mod test_new {
         let ip = None;
         let port = None;
 
-        let setup = StartingTcpSetup::new(ip, port).unwrap();
+        let setup = StartingTcpSetup::new(ip, port, None).unwrap();
         let addr = format!("{}", setup.socket_addr);
 
         let regex = Regex::new("^127\\.0\\.0\\.1:[0-9]+$").unwrap();
@@ -77,7 +195,7 @@ mod test_new {
         let ip = Some(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)));
         let port = None;
 
-        let setup = StartingTcpSetup::new(ip, port).unwrap();
+        let setup = StartingTcpSetup::new(ip, port, None).unwrap();
         let addr = format!("{}", setup.socket_addr);
 
         let regex = Regex::new("^127\\.0\\.0\\.1:[0-9]+$").unwrap();
@@ -90,7 +208,7 @@ mod test_new {
         let ip = None;
         let port = Some(8123);
 
-        let setup = StartingTcpSetup::new(ip, port).unwrap();
+        let setup = StartingTcpSetup::new(ip, port, None).unwrap();
         let addr = format!("{}", setup.socket_addr);
 
         assert_eq!(addr, "127.0.0.1:8123");
@@ -101,9 +219,73 @@ mod test_new {
         let ip = Some(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)));
         let port = Some(8124);
 
-        let setup = StartingTcpSetup::new(ip, port).unwrap();
+        let setup = StartingTcpSetup::new(ip, port, None).unwrap();
         let addr = format!("{}", setup.socket_addr);
 
         assert_eq!(addr, "127.0.0.1:8124");
     }
+
+    #[tokio::test]
+    async fn it_should_error_with_port_bind_failed_when_port_in_use() {
+        let std_tcp_listener = StdTcpListener::bind("127.0.0.1:0").unwrap();
+        let port = std_tcp_listener.local_addr().unwrap().port();
+
+        let result = StartingTcpSetup::new(None, Some(port), None);
+        assert!(matches!(
+            result,
+            Err(Error::PortBindFailed { source, .. }) if source.kind() == ErrorKind::AddrInUse
+        ));
+    }
+}
+
+#[cfg(test)]
+mod test_new_with_seeded_port {
+    use super::*;
+
+    #[tokio::test]
+    async fn it_should_pick_a_port_within_the_seeded_range() {
+        let setup = StartingTcpSetup::new(None, None, Some(123)).unwrap();
+
+        assert!(SEEDED_PORT_RANGE.contains(&setup.socket_addr.port()));
+    }
+
+    #[tokio::test]
+    async fn it_should_pick_the_same_port_for_the_same_seed_when_free() {
+        let first_port = StartingTcpSetup::new(None, None, Some(456))
+            .unwrap()
+            .socket_addr
+            .port();
+
+        // The first setup is dropped, freeing the port for reuse.
+        let second_port = StartingTcpSetup::new(None, None, Some(456))
+            .unwrap()
+            .socket_addr
+            .port();
+
+        assert_eq!(first_port, second_port);
+    }
+
+    #[tokio::test]
+    async fn it_should_skip_ports_in_use() {
+        let first = StartingTcpSetup::new(None, None, Some(789)).unwrap();
+        let second = StartingTcpSetup::new(None, None, Some(789)).unwrap();
+
+        assert_ne!(first.socket_addr.port(), second.socket_addr.port());
+    }
+}
+
+#[cfg(test)]
+mod test_new_dual_stack {
+    use super::*;
+    use std::net::TcpStream;
+
+    #[tokio::test]
+    async fn it_should_accept_ipv4_and_ipv6_connections() {
+        let setup = StartingTcpSetup::new(Some(DUAL_STACK_IP_ADDRESS), None, None).unwrap();
+        let port = setup.socket_addr.port();
+
+        assert_eq!(setup.socket_addr.ip(), DUAL_STACK_IP_ADDRESS);
+        assert!(TcpStream::connect(("127.0.0.1", port)).is_ok());
+        assert!(TcpStream::connect(("::1", port)).is_ok());
+    }
 }
@@ -0,0 +1,82 @@
+/// Returns true if the text matches the wildcard pattern given.
+///
+/// A `*` in the pattern matches any number of characters (including none),
+/// and a `?` matches exactly one character. All other characters must match exactly.
+pub fn is_text_pattern_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let mut pattern_index = 0;
+    let mut text_index = 0;
+    let mut maybe_backtrack: Option<(usize, usize)> = None;
+
+    while text_index < text.len() {
+        match pattern.get(pattern_index) {
+            Some('*') => {
+                maybe_backtrack = Some((pattern_index, text_index));
+                pattern_index += 1;
+            }
+            Some('?') => {
+                pattern_index += 1;
+                text_index += 1;
+            }
+            Some(c) if *c == text[text_index] => {
+                pattern_index += 1;
+                text_index += 1;
+            }
+            _ => {
+                // Retry from the last `*`, having it match one more character.
+                let Some((star_index, star_text_index)) = maybe_backtrack else {
+                    return false;
+                };
+
+                maybe_backtrack = Some((star_index, star_text_index + 1));
+                pattern_index = star_index + 1;
+                text_index = star_text_index + 1;
+            }
+        }
+    }
+
+    pattern[pattern_index..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod test_is_text_pattern_match {
+    use super::*;
+
+    #[test]
+    fn it_should_match_exact_text() {
+        assert!(is_text_pattern_match("hello", "hello"));
+        assert!(!is_text_pattern_match("hello", "hello!"));
+    }
+
+    #[test]
+    fn it_should_match_wildcards() {
+        let pattern = "Hello, *! You have ? new messages";
+
+        assert!(is_text_pattern_match(
+            pattern,
+            "Hello, Joe! You have 3 new messages"
+        ));
+        assert!(is_text_pattern_match(
+            pattern,
+            "Hello, ! You have 3 new messages"
+        ));
+        assert!(!is_text_pattern_match(
+            pattern,
+            "Hello, Joe! You have 10 new messages"
+        ));
+    }
+
+    #[test]
+    fn it_should_backtrack_stars() {
+        assert!(is_text_pattern_match("*a*b", "xaxaxb"));
+        assert!(is_text_pattern_match("*", ""));
+        assert!(!is_text_pattern_match("*a*b", "xaxaxc"));
+    }
+
+    #[test]
+    fn it_should_match_unicode_characters_as_one() {
+        assert!(is_text_pattern_match("caf?", "café"));
+    }
+}
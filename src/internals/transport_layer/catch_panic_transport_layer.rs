@@ -0,0 +1,64 @@
+use axum::body::Body;
+use http::Request;
+use http::Response;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use url::Url;
+
+use crate::internals::build_panic_response;
+use crate::internals::take_catch_panic_marker;
+use crate::internals::CatchPanic;
+use crate::transport_layer::TransportLayer;
+use crate::transport_layer::TransportLayerType;
+use crate::Error;
+use crate::TransportDiagnostics;
+
+/// Wraps a mock transport layer, returning a `500` response for handlers which panic.
+///
+/// The HTTP transport catches panics within the server instead,
+/// as there the handler runs outside of the request being sent.
+#[derive(Debug)]
+pub struct CatchPanicTransportLayer {
+    inner: Arc<dyn TransportLayer>,
+}
+
+impl CatchPanicTransportLayer {
+    pub(crate) fn new(inner: Arc<dyn TransportLayer>) -> Self {
+        Self { inner }
+    }
+}
+
+impl TransportLayer for CatchPanicTransportLayer {
+    fn send<'a>(
+        &'a self,
+        mut request: Request<Body>,
+    ) -> Pin<Box<dyn 'a + Future<Output = Result<Response<Body>, Error>>>> {
+        Box::pin(async move {
+            let is_test_request = take_catch_panic_marker(request.headers_mut());
+
+            match CatchPanic::new(self.inner.send(request), true).await {
+                Ok(result) => result,
+                Err(message) => Ok(build_panic_response(
+                    is_test_request.then_some(message.as_str()),
+                )),
+            }
+        })
+    }
+
+    fn url(&self) -> Option<&Url> {
+        self.inner.url()
+    }
+
+    fn transport_layer_type(&self) -> TransportLayerType {
+        self.inner.transport_layer_type()
+    }
+
+    fn is_running(&self) -> bool {
+        self.inner.is_running()
+    }
+
+    fn transport_diagnostics(&self) -> TransportDiagnostics {
+        self.inner.transport_diagnostics()
+    }
+}
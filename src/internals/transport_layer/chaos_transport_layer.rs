@@ -0,0 +1,238 @@
+use anyhow::anyhow;
+use anyhow::Error as AnyhowError;
+use axum::body::Body;
+use http::Request;
+use http::Response;
+use http::StatusCode;
+use http_body_util::BodyExt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Mutex;
+use tokio::time::sleep;
+use url::Url;
+
+use crate::internals::SeededRng;
+use crate::transport_layer::TransportLayer;
+use crate::transport_layer::TransportLayerType;
+use crate::ChaosConfig;
+use crate::Error;
+use crate::TransportDiagnostics;
+
+/// Wraps another transport layer, injecting failures as described by the [`ChaosConfig`].
+#[derive(Debug)]
+pub struct ChaosTransportLayer {
+    inner: Arc<dyn TransportLayer>,
+    config: ChaosConfig,
+    rng: Mutex<SeededRng>,
+}
+
+enum ChaosOutcome {
+    DroppedConnection,
+    ServerError,
+    TruncatedBody,
+    Unchanged,
+}
+
+impl ChaosTransportLayer {
+    pub(crate) fn new(inner: Arc<dyn TransportLayer>, config: ChaosConfig) -> Self {
+        let rng = Mutex::new(SeededRng::new(config.seed));
+
+        Self { inner, config, rng }
+    }
+
+    fn next_outcome(&self) -> ChaosOutcome {
+        let mut rng = self
+            .rng
+            .lock()
+            .expect("Failed to lock random number generator, for chaos transport");
+
+        if rng.next_bool(self.config.dropped_connection_rate) {
+            ChaosOutcome::DroppedConnection
+        } else if rng.next_bool(self.config.server_error_rate) {
+            ChaosOutcome::ServerError
+        } else if rng.next_bool(self.config.truncated_body_rate) {
+            ChaosOutcome::TruncatedBody
+        } else {
+            ChaosOutcome::Unchanged
+        }
+    }
+}
+
+impl TransportLayer for ChaosTransportLayer {
+    fn send<'a>(
+        &'a self,
+        request: Request<Body>,
+    ) -> Pin<Box<dyn 'a + Future<Output = Result<Response<Body>, Error>>>> {
+        Box::pin(async move {
+            if let Some(latency) = self.config.latency {
+                sleep(latency).await;
+            }
+
+            match self.next_outcome() {
+                ChaosOutcome::DroppedConnection => Err(Error::Other(anyhow!(
+                    "Connection dropped by chaos, for request {} {}",
+                    request.method(),
+                    request.uri()
+                ))),
+                ChaosOutcome::ServerError => {
+                    let response = Response::builder()
+                        .status(StatusCode::INTERNAL_SERVER_ERROR)
+                        .body(Body::empty())
+                        .map_err(AnyhowError::from)?;
+
+                    Ok(response)
+                }
+                ChaosOutcome::TruncatedBody => {
+                    let response = self.inner.send(request).await?;
+                    let (parts, body) = response.into_parts();
+                    let mut body_bytes =
+                        body.collect().await.map_err(AnyhowError::from)?.to_bytes();
+                    body_bytes.truncate(body_bytes.len() / 2);
+
+                    Ok(Response::from_parts(parts, Body::from(body_bytes)))
+                }
+                ChaosOutcome::Unchanged => self.inner.send(request).await,
+            }
+        })
+    }
+
+    fn url(&self) -> Option<&Url> {
+        self.inner.url()
+    }
+
+    fn transport_layer_type(&self) -> TransportLayerType {
+        self.inner.transport_layer_type()
+    }
+
+    fn is_running(&self) -> bool {
+        self.inner.is_running()
+    }
+
+    fn transport_diagnostics(&self) -> TransportDiagnostics {
+        self.inner.transport_diagnostics()
+    }
+}
+
+#[cfg(test)]
+mod test_send {
+    use axum::routing::get;
+    use axum::Router;
+    use http::StatusCode;
+    use std::time::Duration;
+    use std::time::Instant;
+
+    use crate::ChaosConfig;
+    use crate::TestServer;
+
+    fn new_server(chaos: ChaosConfig) -> TestServer {
+        let app = Router::new().route("/text", get(|| async { "hello world!" }));
+
+        TestServer::builder().with_chaos(chaos).build(app).unwrap()
+    }
+
+    async fn collect_status_codes(server: &TestServer) -> Vec<StatusCode> {
+        let mut status_codes = Vec::new();
+        for _ in 0..20 {
+            let response = server.get("/text").await;
+            status_codes.push(response.status_code());
+        }
+
+        status_codes
+    }
+
+    #[tokio::test]
+    async fn it_should_not_change_responses_by_default() {
+        let server = new_server(ChaosConfig::default());
+
+        server.get("/text").await.assert_text("hello world!");
+    }
+
+    #[tokio::test]
+    async fn it_should_always_return_server_error_at_full_rate() {
+        let server = new_server(ChaosConfig {
+            server_error_rate: 1.0,
+            ..ChaosConfig::default()
+        });
+
+        server
+            .get("/text")
+            .expect_failure()
+            .await
+            .assert_status_internal_server_error();
+    }
+
+    #[tokio::test]
+    async fn it_should_return_the_same_failures_for_the_same_seed() {
+        let chaos = ChaosConfig {
+            seed: 42,
+            server_error_rate: 0.5,
+            ..ChaosConfig::default()
+        };
+
+        let first = collect_status_codes(&new_server(chaos.clone())).await;
+        let second = collect_status_codes(&new_server(chaos)).await;
+
+        assert_eq!(first, second);
+        assert!(first.contains(&StatusCode::OK));
+        assert!(first.contains(&StatusCode::INTERNAL_SERVER_ERROR));
+    }
+
+    #[tokio::test]
+    async fn it_should_truncate_bodies_at_full_rate() {
+        let server = new_server(ChaosConfig {
+            truncated_body_rate: 1.0,
+            ..ChaosConfig::default()
+        });
+
+        server.get("/text").await.assert_text("hello ");
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_fail_sending_when_connection_dropped() {
+        let server = new_server(ChaosConfig {
+            dropped_connection_rate: 1.0,
+            ..ChaosConfig::default()
+        });
+
+        server.get("/text").await;
+    }
+
+    #[tokio::test]
+    async fn it_should_return_error_from_try_send_when_connection_dropped() {
+        let server = new_server(ChaosConfig {
+            dropped_connection_rate: 1.0,
+            ..ChaosConfig::default()
+        });
+
+        let error = server.get("/text").try_send().await.unwrap_err();
+        assert!(format!("{error:#}").contains("Connection dropped by chaos"));
+    }
+
+    #[test]
+    fn it_should_fail_to_build_server_with_invalid_rate() {
+        let app = Router::new();
+        let result = TestServer::builder()
+            .with_chaos(ChaosConfig {
+                dropped_connection_rate: -0.5,
+                ..ChaosConfig::default()
+            })
+            .build(app);
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn it_should_add_latency() {
+        let server = new_server(ChaosConfig {
+            latency: Some(Duration::from_millis(50)),
+            ..ChaosConfig::default()
+        });
+
+        let start = Instant::now();
+        server.get("/text").await;
+
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+}
@@ -1,20 +1,46 @@
-use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::Error as AnyhowError;
 use axum::body::Body;
+use bytes::Bytes;
+use http::header;
+use http::HeaderValue;
 use http::Request;
 use http::Response;
+use http::Uri;
+use http::Version;
+use hyper::client::conn::http1::Builder as ClientBuilder;
 use hyper_util::client::legacy::Client;
+use hyper_util::rt::TokioIo;
 use reserve_port::ReservedPort;
 use std::future::Future;
+use std::io::Error as IoError;
+use std::io::ErrorKind;
+use std::io::Result as IoResult;
+use std::net::IpAddr;
+use std::net::Ipv4Addr;
+use std::net::Ipv6Addr;
+use std::net::SocketAddr;
 use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Mutex;
+use tokio::net::lookup_host;
+use tokio::net::TcpSocket;
+use tokio::net::TcpStream;
 use url::Url;
 
+use crate::internals::find_final_head;
+use crate::internals::CaptureRawResponseHead;
+use crate::internals::RawResponseHead;
+use crate::internals::ReceiveBufferSize;
+use crate::internals::RecordingStream;
 use crate::transport_layer::TransportLayer;
 use crate::transport_layer::TransportLayerType;
 use crate::util::ServeHandle;
+use crate::Error;
+use crate::TransportDiagnostics;
 
 #[derive(Debug)]
 pub struct HttpTransportLayer {
-    #[allow(dead_code)]
     serve_handle: ServeHandle,
 
     #[allow(dead_code)]
@@ -41,10 +67,36 @@ impl TransportLayer for HttpTransportLayer {
     fn send<'a>(
         &'a self,
         request: Request<Body>,
-    ) -> Pin<Box<dyn 'a + Future<Output = Result<Response<Body>>>>> {
+    ) -> Pin<Box<dyn 'a + Future<Output = Result<Response<Body>, Error>>>> {
         Box::pin(async {
-            let client = Client::builder(hyper_util::rt::TokioExecutor::new()).build_http();
-            let hyper_response = client.request(request).await?;
+            if !self.is_running() {
+                return Err(Error::TransportClosed);
+            }
+
+            // HTTP/2 requests are sent with prior knowledge,
+            // as the server is plain text, and so cannot negotiate it using ALPN.
+            let is_http2 = request.version() == Version::HTTP_2;
+            let is_capturing_raw_head = request
+                .extensions()
+                .get::<CaptureRawResponseHead>()
+                .is_some();
+            let maybe_receive_buffer_size = request
+                .extensions()
+                .get::<ReceiveBufferSize>()
+                .map(|ReceiveBufferSize(size)| *size);
+            if (is_capturing_raw_head || maybe_receive_buffer_size.is_some()) && !is_http2 {
+                return send_over_new_connection(
+                    request,
+                    is_capturing_raw_head,
+                    maybe_receive_buffer_size,
+                )
+                .await;
+            }
+
+            let client = Client::builder(hyper_util::rt::TokioExecutor::new())
+                .http2_only(is_http2)
+                .build_http();
+            let hyper_response = client.request(request).await.map_err(AnyhowError::from)?;
 
             let (parts, response_body) = hyper_response.into_parts();
             let returned_response: Response<Body> =
@@ -65,4 +117,152 @@ impl TransportLayer for HttpTransportLayer {
     fn is_running(&self) -> bool {
         !self.serve_handle.is_finished()
     }
+
+    fn transport_diagnostics(&self) -> TransportDiagnostics {
+        self.serve_handle.transport_diagnostics()
+    }
+}
+
+/// Sends the request over a new HTTP/1 connection.
+///
+/// When capturing, the raw head of the response is added to its extensions.
+/// When a receive buffer size is given, both the socket and the client
+/// read with a buffer of that size, so the server sees a slow consumer sooner.
+async fn send_over_new_connection(
+    mut request: Request<Body>,
+    is_capturing_raw_head: bool,
+    maybe_receive_buffer_size: Option<usize>,
+) -> Result<Response<Body>, Error> {
+    let authority = request.uri().authority().cloned().ok_or_else(|| {
+        anyhow!(
+            "Expected request to have a host, for request {}",
+            request.uri()
+        )
+    })?;
+    let port = authority.port_u16().unwrap_or(80);
+    let host = authority.host().trim_matches(['[', ']']);
+    let stream = match maybe_receive_buffer_size {
+        Some(receive_buffer_size) => connect_with_receive_buffer(host, port, receive_buffer_size)
+            .await
+            .map_err(AnyhowError::from)?,
+        None => TcpStream::connect((host, port))
+            .await
+            .map_err(AnyhowError::from)?,
+    };
+
+    let recorded = Arc::new(Mutex::new(Vec::new()));
+    let io = TokioIo::new(RecordingStream::new(stream, Arc::clone(&recorded)));
+    let (mut sender, connection) = ClientBuilder::new()
+        .read_buf_exact_size(maybe_receive_buffer_size)
+        .handshake(io)
+        .await
+        .map_err(AnyhowError::from)?;
+    tokio::spawn(connection.with_upgrades());
+
+    // The request is sent in origin form (just the path), as the legacy client does.
+    let path_and_query = request
+        .uri()
+        .path_and_query()
+        .map(|path_and_query| path_and_query.as_str())
+        .unwrap_or("/");
+    *request.uri_mut() = path_and_query.parse::<Uri>().map_err(AnyhowError::from)?;
+    if !request.headers().contains_key(header::HOST) {
+        let host = HeaderValue::from_str(authority.as_str()).map_err(AnyhowError::from)?;
+        request.headers_mut().insert(header::HOST, host);
+    }
+
+    let hyper_response = sender
+        .send_request(request)
+        .await
+        .map_err(AnyhowError::from)?;
+    let maybe_raw_head = {
+        let recorded = recorded.lock().expect("Failed to lock recorded bytes");
+        find_final_head(&recorded).map(Bytes::copy_from_slice)
+    };
+
+    let (mut parts, response_body) = hyper_response.into_parts();
+    if let Some(raw_head) = maybe_raw_head.filter(|_| is_capturing_raw_head) {
+        parts.extensions.insert(RawResponseHead(raw_head));
+    }
+
+    Ok(Response::from_parts(parts, Body::new(response_body)))
+}
+
+async fn connect_with_receive_buffer(
+    host: &str,
+    port: u16,
+    receive_buffer_size: usize,
+) -> IoResult<TcpStream> {
+    let socket_addr = lookup_host((host, port)).await?.next().ok_or_else(|| {
+        IoError::new(
+            ErrorKind::NotFound,
+            format!("No address found for host '{host}'"),
+        )
+    })?;
+    let socket = match socket_addr {
+        SocketAddr::V4(_) => TcpSocket::new_v4()?,
+        SocketAddr::V6(_) => TcpSocket::new_v6()?,
+    };
+
+    // The buffer size must be set before connecting, as it is used to negotiate the TCP window.
+    socket.set_recv_buffer_size(u32::try_from(receive_buffer_size).unwrap_or(u32::MAX))?;
+    socket.connect(socket_addr).await
+}
+
+/// Builds the url for a server running on the address given.
+///
+/// Servers bound to an unspecified address, such as `0.0.0.0` or `::`,
+/// are reached through the loopback address of the same family.
+/// IPv6 addresses are bracketed, such as `http://[::1]:8080`.
+pub fn build_server_url(socket_addr: SocketAddr) -> Result<Url, Error> {
+    let ip = match socket_addr.ip() {
+        IpAddr::V4(ip) if ip.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        IpAddr::V6(ip) if ip.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+        ip => ip,
+    };
+    let server_address = format!("http://{}", SocketAddr::new(ip, socket_addr.port()));
+
+    match server_address.parse() {
+        Ok(server_url) => Ok(server_url),
+        Err(error) => Err(Error::InvalidUrl {
+            url: server_address,
+            message: format!("{error}"),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod test_build_server_url {
+    use super::*;
+
+    #[test]
+    fn it_should_build_url_for_ipv4_address() {
+        let socket_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 8080);
+
+        let url = build_server_url(socket_addr).unwrap();
+        assert_eq!(url.as_str(), "http://127.0.0.1:8080/");
+    }
+
+    #[test]
+    fn it_should_bracket_ipv6_address() {
+        let socket_addr = SocketAddr::new(IpAddr::V6(Ipv6Addr::LOCALHOST), 8080);
+
+        let url = build_server_url(socket_addr).unwrap();
+        assert_eq!(url.as_str(), "http://[::1]:8080/");
+    }
+
+    #[test]
+    fn it_should_use_loopback_for_unspecified_addresses() {
+        let ipv4_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 8080);
+        let ipv6_addr = SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), 8080);
+
+        assert_eq!(
+            build_server_url(ipv4_addr).unwrap().as_str(),
+            "http://127.0.0.1:8080/"
+        );
+        assert_eq!(
+            build_server_url(ipv6_addr).unwrap().as_str(),
+            "http://[::1]:8080/"
+        );
+    }
 }
@@ -0,0 +1,191 @@
+use anyhow::anyhow;
+use anyhow::Error as AnyhowError;
+use axum::body::Body;
+use http::Request;
+use http::Response;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use url::Url;
+
+use crate::transport_layer::TransportLayer;
+use crate::transport_layer::TransportLayerType;
+use crate::Error;
+use crate::InterceptAction;
+use crate::RequestInterceptor;
+use crate::TransportDiagnostics;
+
+/// Wraps another transport layer, applying the [`RequestInterceptor`]s to each request sent.
+#[derive(Debug)]
+pub struct InterceptTransportLayer {
+    inner: Arc<dyn TransportLayer>,
+    interceptors: Vec<RequestInterceptor>,
+}
+
+impl InterceptTransportLayer {
+    pub(crate) fn new(
+        inner: Arc<dyn TransportLayer>,
+        interceptors: Vec<RequestInterceptor>,
+    ) -> Self {
+        Self {
+            inner,
+            interceptors,
+        }
+    }
+}
+
+impl TransportLayer for InterceptTransportLayer {
+    fn send<'a>(
+        &'a self,
+        mut request: Request<Body>,
+    ) -> Pin<Box<dyn 'a + Future<Output = Result<Response<Body>, Error>>>> {
+        Box::pin(async move {
+            for interceptor in &self.interceptors {
+                if !interceptor.matcher.matches(&request) {
+                    continue;
+                }
+
+                match &interceptor.action {
+                    InterceptAction::ConnectionError => {
+                        return Err(Error::Other(anyhow!(
+                            "Connection refused by interceptor, for request {} {}",
+                            request.method(),
+                            request.uri()
+                        )));
+                    }
+                    InterceptAction::Respond(status_code) => {
+                        let response = Response::builder()
+                            .status(*status_code)
+                            .body(Body::empty())
+                            .map_err(AnyhowError::from)?;
+
+                        return Ok(response);
+                    }
+                    InterceptAction::SetHeader(name, value) => {
+                        request.headers_mut().insert(name.clone(), value.clone());
+                    }
+                    InterceptAction::RemoveHeader(name) => {
+                        request.headers_mut().remove(name);
+                    }
+                }
+            }
+
+            self.inner.send(request).await
+        })
+    }
+
+    fn url(&self) -> Option<&Url> {
+        self.inner.url()
+    }
+
+    fn transport_layer_type(&self) -> TransportLayerType {
+        self.inner.transport_layer_type()
+    }
+
+    fn is_running(&self) -> bool {
+        self.inner.is_running()
+    }
+
+    fn transport_diagnostics(&self) -> TransportDiagnostics {
+        self.inner.transport_diagnostics()
+    }
+}
+
+#[cfg(test)]
+mod test_send {
+    use axum::routing::get;
+    use axum::Router;
+    use http::HeaderName;
+    use http::HeaderValue;
+    use http::Method;
+    use http::StatusCode;
+    use serde_json::Value;
+
+    use crate::InterceptAction;
+    use crate::RequestMatcher;
+    use crate::TestServer;
+
+    fn new_test_router() -> Router {
+        Router::new()
+            .route("/users", get(crate::routes::echo_headers_json))
+            .route(
+                "/payments",
+                get(|| async { "payments" }).post(|| async { "paid" }),
+            )
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_matching_requests_with_connection_error() {
+        let server = TestServer::builder()
+            .http_transport()
+            .intercept("/payments", InterceptAction::ConnectionError)
+            .build(new_test_router())
+            .unwrap();
+
+        let error = server.get("/payments").try_send().await.unwrap_err();
+        assert!(format!("{error:#}").contains("Connection refused by interceptor"));
+
+        server.get("/users").await.assert_status_ok();
+    }
+
+    #[tokio::test]
+    async fn it_should_respond_to_matching_requests() {
+        let server = TestServer::builder()
+            .intercept(
+                RequestMatcher::method_route(Method::POST, "/payments"),
+                InterceptAction::Respond(StatusCode::SERVICE_UNAVAILABLE),
+            )
+            .build(new_test_router())
+            .unwrap();
+
+        server
+            .post("/payments")
+            .await
+            .assert_status(StatusCode::SERVICE_UNAVAILABLE);
+        server.get("/payments").await.assert_text("payments");
+    }
+
+    #[tokio::test]
+    async fn it_should_rewrite_headers() {
+        let mut server = TestServer::builder()
+            .http_transport()
+            .intercept(
+                RequestMatcher::any(),
+                InterceptAction::SetHeader(
+                    HeaderName::from_static("x-tenant"),
+                    HeaderValue::from_static("intercepted"),
+                ),
+            )
+            .intercept(
+                "/users",
+                InterceptAction::RemoveHeader(HeaderName::from_static("x-remove-me")),
+            )
+            .build(new_test_router())
+            .unwrap();
+        server.add_header("x-tenant", "original");
+        server.add_header("x-remove-me", "value");
+
+        let headers = server.get("/users").await.json::<Value>();
+
+        assert_eq!(headers["x-tenant"], "intercepted");
+        assert_eq!(headers.get("x-remove-me"), None);
+    }
+
+    #[tokio::test]
+    async fn it_should_not_record_intercepted_responses_in_metrics() {
+        let server = TestServer::builder()
+            .record_metrics()
+            .intercept(
+                "/payments",
+                InterceptAction::Respond(StatusCode::BAD_GATEWAY),
+            )
+            .build(new_test_router())
+            .unwrap();
+
+        server.get("/payments").await;
+        server.get("/users").await;
+
+        assert_eq!(server.metrics().total_hits(), 1);
+        assert_eq!(server.metrics().path_hits("/payments"), 0);
+    }
+}
@@ -0,0 +1,100 @@
+use anyhow::Error as AnyhowError;
+use axum::body::Body;
+use http::Request;
+use http::Response;
+use http::StatusCode;
+use http_body_util::BodyExt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Mutex;
+use url::Url;
+
+use crate::transport_layer::TransportLayer;
+use crate::transport_layer::TransportLayerType;
+use crate::Error;
+use crate::ServerMetrics;
+use crate::TransportDiagnostics;
+
+/// Wraps another transport layer, recording each request that passes through it.
+#[derive(Debug)]
+pub struct MetricsTransportLayer {
+    inner: Arc<dyn TransportLayer>,
+    metrics: Arc<Mutex<ServerMetrics>>,
+}
+
+impl MetricsTransportLayer {
+    pub(crate) fn new(inner: Arc<dyn TransportLayer>, metrics: Arc<Mutex<ServerMetrics>>) -> Self {
+        Self { inner, metrics }
+    }
+}
+
+impl TransportLayer for MetricsTransportLayer {
+    fn send<'a>(
+        &'a self,
+        request: Request<Body>,
+    ) -> Pin<Box<dyn 'a + Future<Output = Result<Response<Body>, Error>>>> {
+        Box::pin(async move {
+            let path = request.uri().path().to_string();
+            let method = request.method().clone();
+            let path_and_query = request
+                .uri()
+                .path_and_query()
+                .map(ToString::to_string)
+                .unwrap_or_else(|| path.clone());
+            let (request_parts, request_body) = request.into_parts();
+            let request_bytes = request_body
+                .collect()
+                .await
+                .map_err(AnyhowError::from)?
+                .to_bytes();
+            let request_len = request_bytes.len();
+            let recorded_body = request_bytes.clone();
+            let request = Request::from_parts(request_parts, Body::from(request_bytes));
+
+            let response = self.inner.send(request).await?;
+            let status_code = response.status();
+
+            // Upgraded connections (i.e. WebSockets) must keep their body untouched.
+            let (response, response_len) = if status_code == StatusCode::SWITCHING_PROTOCOLS {
+                (response, 0)
+            } else {
+                let (response_parts, response_body) = response.into_parts();
+                let response_bytes = response_body
+                    .collect()
+                    .await
+                    .map_err(AnyhowError::from)?
+                    .to_bytes();
+                let response_len = response_bytes.len();
+                let response = Response::from_parts(response_parts, Body::from(response_bytes));
+
+                (response, response_len)
+            };
+
+            let mut metrics = self
+                .metrics
+                .lock()
+                .expect("Failed to lock metrics, for recording request");
+            metrics.record(&path, status_code, request_len, response_len);
+            metrics.record_request(&method, &path_and_query, &recorded_body);
+
+            Ok(response)
+        })
+    }
+
+    fn url(&self) -> Option<&Url> {
+        self.inner.url()
+    }
+
+    fn transport_layer_type(&self) -> TransportLayerType {
+        self.inner.transport_layer_type()
+    }
+
+    fn is_running(&self) -> bool {
+        self.inner.is_running()
+    }
+
+    fn transport_diagnostics(&self) -> TransportDiagnostics {
+        self.inner.transport_diagnostics()
+    }
+}
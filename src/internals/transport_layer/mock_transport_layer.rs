@@ -1,5 +1,4 @@
 use anyhow::Error as AnyhowError;
-use anyhow::Result;
 use axum::body::Body;
 use axum::response::Response as AxumResponse;
 use bytes::Bytes;
@@ -13,6 +12,7 @@ use tower::Service;
 
 use crate::transport_layer::TransportLayer;
 use crate::transport_layer::TransportLayerType;
+use crate::Error;
 
 pub struct MockTransportLayer<S> {
     service: S,
@@ -41,7 +41,7 @@ where
     fn send<'a>(
         &'a self,
         request: Request<Body>,
-    ) -> Pin<Box<dyn 'a + Future<Output = Result<Response<Body>>>>> {
+    ) -> Pin<Box<dyn 'a + Future<Output = Result<Response<Body>, Error>>>> {
         Box::pin(async {
             let body: Body = Bytes::new().into();
             let empty_request = Request::builder()
@@ -49,9 +49,13 @@ where
                 .expect("should build empty request");
 
             let service = self.service.clone();
-            let router = service.oneshot(empty_request).await?;
+            let router = service
+                .oneshot(empty_request)
+                .await
+                .map_err(AnyhowError::from)?;
+
+            let response = router.oneshot(request).await.map_err(AnyhowError::from)?;
 
-            let response = router.oneshot(request).await?;
             Ok(response)
         })
     }
@@ -3,3 +3,15 @@ pub use self::http_transport_layer::*;
 
 mod mock_transport_layer;
 pub use self::mock_transport_layer::*;
+
+mod chaos_transport_layer;
+pub use self::chaos_transport_layer::*;
+
+mod metrics_transport_layer;
+pub use self::metrics_transport_layer::*;
+
+mod intercept_transport_layer;
+pub use self::intercept_transport_layer::*;
+
+mod catch_panic_transport_layer;
+pub use self::catch_panic_transport_layer::*;
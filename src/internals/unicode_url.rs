@@ -0,0 +1,106 @@
+use std::borrow::Cow;
+use std::fmt::Write;
+use url::Host;
+use url::Url;
+
+/// Prepares a path or url with unicode characters for parsing as a [`http::Uri`],
+/// which only accepts ASCII.
+///
+/// Unicode hosts are converted to punycode,
+/// and all other non-ASCII characters are percent-encoded as UTF-8.
+/// This is the same encoding the `Url` type uses,
+/// so the path sent is the same on mock and HTTP transports.
+pub fn encode_unicode_url(path: &str) -> Cow<'_, str> {
+    if path.is_ascii() {
+        return Cow::Borrowed(path);
+    }
+
+    if path.contains("://") {
+        if let Ok(url) = Url::parse(path) {
+            return Cow::Owned(url.into());
+        }
+    }
+
+    Cow::Owned(percent_encode_non_ascii(path))
+}
+
+/// Converts a unicode host, which may have a port, to punycode.
+///
+/// Hosts which are not valid are returned unchanged.
+pub fn encode_unicode_host(host: &str) -> String {
+    if host.is_ascii() {
+        return host.to_string();
+    }
+
+    let (domain, maybe_port) = match host.rsplit_once(':') {
+        Some((domain, port)) if port.bytes().all(|byte| byte.is_ascii_digit()) => {
+            (domain, Some(port))
+        }
+        _ => (host, None),
+    };
+
+    match (Host::parse(domain), maybe_port) {
+        (Ok(domain), Some(port)) => format!("{domain}:{port}"),
+        (Ok(domain), None) => domain.to_string(),
+        (Err(_), _) => host.to_string(),
+    }
+}
+
+fn percent_encode_non_ascii(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+
+    for c in path.chars() {
+        if c.is_ascii() {
+            encoded.push(c);
+        } else {
+            let mut buffer = [0; 4];
+            for byte in c.encode_utf8(&mut buffer).bytes() {
+                let _ = write!(encoded, "%{byte:02X}");
+            }
+        }
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod test_encode_unicode_url {
+    use super::*;
+
+    #[test]
+    fn it_should_leave_ascii_paths_unchanged() {
+        assert_eq!(encode_unicode_url("/users?name=joe"), "/users?name=joe");
+    }
+
+    #[test]
+    fn it_should_percent_encode_unicode_paths() {
+        assert_eq!(encode_unicode_url("/café"), "/caf%C3%A9");
+        assert_eq!(
+            encode_unicode_url("/search?q=日本"),
+            "/search?q=%E6%97%A5%E6%9C%AC"
+        );
+    }
+
+    #[test]
+    fn it_should_punycode_unicode_hosts() {
+        assert_eq!(
+            encode_unicode_url("http://café.example/café"),
+            "http://xn--caf-dma.example/caf%C3%A9"
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_encode_unicode_host {
+    use super::*;
+
+    #[test]
+    fn it_should_punycode_hosts_with_ports() {
+        assert_eq!(encode_unicode_host("café.example"), "xn--caf-dma.example");
+        assert_eq!(
+            encode_unicode_host("café.example:8080"),
+            "xn--caf-dma.example:8080"
+        );
+        assert_eq!(encode_unicode_host("api.local"), "api.local");
+    }
+}
@@ -1,9 +1,13 @@
 use hyper::upgrade::OnUpgrade;
+use std::sync::Arc;
+use std::sync::Mutex;
 
 use crate::transport_layer::TransportLayerType;
+use crate::ServerSharedState;
 
 #[derive(Clone, Debug)]
 pub struct TestResponseWebSocket {
     pub maybe_on_upgrade: Option<OnUpgrade>,
     pub transport_type: TransportLayerType,
+    pub server_state: Arc<Mutex<ServerSharedState>>,
 }
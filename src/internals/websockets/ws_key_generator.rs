@@ -1,8 +1,13 @@
 use base64::engine::general_purpose::STANDARD;
 use base64::Engine;
-use uuid::Uuid;
+
+use crate::internals::SeededRng;
 
 /// Generates a random key for use, that is base 64 encoded for use over HTTP.
-pub fn generate_ws_key() -> String {
-    STANDARD.encode(Uuid::new_v4().as_bytes())
+pub fn generate_ws_key(rng: &mut SeededRng) -> String {
+    let mut key = [0_u8; 16];
+    key[..8].copy_from_slice(&rng.next_u64().to_be_bytes());
+    key[8..].copy_from_slice(&rng.next_u64().to_be_bytes());
+
+    STANDARD.encode(key)
 }
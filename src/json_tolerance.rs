@@ -0,0 +1,94 @@
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+
+/// The tolerance used when comparing numbers within Json,
+/// using [`TestResponse::assert_json_approx()`](crate::TestResponse::assert_json_approx()).
+///
+/// A plain `f64` can be used in place of this, which is treated as an absolute tolerance.
+///
+/// ```rust
+/// use axum_test::JsonTolerance;
+///
+/// // These are the same.
+/// let tolerance = JsonTolerance::Absolute(0.001);
+/// let tolerance: JsonTolerance = 0.001.into();
+///
+/// // Allows numbers to differ by up to 1%.
+/// let tolerance = JsonTolerance::Relative(0.01);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum JsonTolerance {
+    /// Numbers match if they differ by no more than this amount.
+    Absolute(f64),
+
+    /// Numbers match if they differ by no more than this fraction,
+    /// of the larger of the two numbers.
+    ///
+    /// i.e. `0.01` allows numbers to differ by 1%.
+    Relative(f64),
+}
+
+impl JsonTolerance {
+    /// Returns true if the two numbers are considered equal under this tolerance.
+    pub fn is_within(self, expected: f64, received: f64) -> bool {
+        if expected == received {
+            return true;
+        }
+
+        let difference = (expected - received).abs();
+        match self {
+            Self::Absolute(epsilon) => difference <= epsilon,
+            Self::Relative(epsilon) => {
+                let largest = expected.abs().max(received.abs());
+                difference <= largest * epsilon
+            }
+        }
+    }
+}
+
+impl From<f64> for JsonTolerance {
+    fn from(epsilon: f64) -> Self {
+        Self::Absolute(epsilon)
+    }
+}
+
+impl Display for JsonTolerance {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Absolute(epsilon) => write!(f, "absolute tolerance {epsilon}"),
+            Self::Relative(epsilon) => write!(f, "relative tolerance {epsilon}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_is_within {
+    use super::*;
+
+    #[test]
+    fn it_should_match_numbers_within_absolute_tolerance() {
+        let tolerance = JsonTolerance::Absolute(0.01);
+
+        assert!(tolerance.is_within(1.0, 1.005));
+        assert!(tolerance.is_within(1.005, 1.0));
+        assert!(!tolerance.is_within(1.0, 1.02));
+    }
+
+    #[test]
+    fn it_should_match_numbers_within_relative_tolerance() {
+        let tolerance = JsonTolerance::Relative(0.01);
+
+        assert!(tolerance.is_within(1000.0, 1009.0));
+        assert!(!tolerance.is_within(1000.0, 1020.0));
+        assert!(!tolerance.is_within(1.0, 1.02));
+    }
+
+    #[test]
+    fn it_should_match_exact_numbers_with_zero_tolerance() {
+        let tolerance = JsonTolerance::Relative(0.0);
+
+        assert!(tolerance.is_within(0.0, 0.0));
+        assert!(!tolerance.is_within(0.0, 0.1));
+    }
+}
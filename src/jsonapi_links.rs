@@ -0,0 +1,75 @@
+use serde_json::Map;
+use serde_json::Value;
+
+/// The pagination links from the top level of a [JSON:API](https://jsonapi.org) document.
+///
+/// This is returned from [`TestResponse::jsonapi_links()`](crate::TestResponse::jsonapi_links()).
+/// Links given as link objects are read from their `href` member.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JsonApiLinks {
+    /// The `self` link, for the current page.
+    pub self_link: Option<String>,
+
+    /// The `first` link, for the first page.
+    pub first: Option<String>,
+
+    /// The `last` link, for the last page.
+    pub last: Option<String>,
+
+    /// The `prev` link, for the previous page.
+    pub prev: Option<String>,
+
+    /// The `next` link, for the next page.
+    pub next: Option<String>,
+}
+
+impl JsonApiLinks {
+    pub(crate) fn from_links(maybe_links: Option<&Map<String, Value>>) -> Self {
+        let Some(links) = maybe_links else {
+            return Self::default();
+        };
+
+        Self {
+            self_link: read_link(links, "self"),
+            first: read_link(links, "first"),
+            last: read_link(links, "last"),
+            prev: read_link(links, "prev"),
+            next: read_link(links, "next"),
+        }
+    }
+}
+
+fn read_link(links: &Map<String, Value>, name: &str) -> Option<String> {
+    match links.get(name)? {
+        Value::String(href) => Some(href.clone()),
+        Value::Object(link) => link.get("href")?.as_str().map(ToString::to_string),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test_from_links {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn it_should_read_string_and_object_links() {
+        let links = json!({
+            "self": "/articles?page=2",
+            "next": { "href": "/articles?page=3" },
+            "prev": null,
+        });
+
+        let links = JsonApiLinks::from_links(links.as_object());
+
+        assert_eq!(links.self_link.as_deref(), Some("/articles?page=2"));
+        assert_eq!(links.next.as_deref(), Some("/articles?page=3"));
+        assert_eq!(links.prev, None);
+        assert_eq!(links.last, None);
+    }
+
+    #[test]
+    fn it_should_be_empty_without_links() {
+        assert_eq!(JsonApiLinks::from_links(None), JsonApiLinks::default());
+    }
+}
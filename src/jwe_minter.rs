@@ -0,0 +1,558 @@
+use aes_gcm::aead::Aead;
+use aes_gcm::aead::AeadCore;
+use aes_gcm::aead::KeyInit;
+use aes_gcm::aead::Nonce;
+use aes_gcm::aead::OsRng;
+use aes_gcm::aead::Payload;
+use aes_gcm::Aes128Gcm;
+use aes_gcm::Aes256Gcm;
+use aes_kw::KekAes128;
+use aes_kw::KekAes256;
+use anyhow::anyhow;
+use anyhow::Context;
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::json;
+use serde_json::Value;
+
+use crate::internals::decode_base64;
+use crate::internals::encode_base64_url;
+
+/// The length of the initialization vector used with AES GCM, in bytes.
+const GCM_IV_LENGTH: usize = 12;
+
+/// The length of the authentication tag produced by AES GCM, in bytes.
+const GCM_TAG_LENGTH: usize = 16;
+
+/// How the key encrypting the claims of a JWE is managed, which is set as its `alg` header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JweAlgorithm {
+    /// The key given encrypts the claims directly (`dir`).
+    ///
+    /// The key must be the length needed by the [`JweEncryption`] used.
+    Direct,
+
+    /// A random key encrypts the claims, which is wrapped using AES Key Wrap
+    /// with the 16 byte key given (`A128KW`).
+    A128Kw,
+
+    /// A random key encrypts the claims, which is wrapped using AES Key Wrap
+    /// with the 32 byte key given (`A256KW`).
+    A256Kw,
+}
+
+impl JweAlgorithm {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Direct => "dir",
+            Self::A128Kw => "A128KW",
+            Self::A256Kw => "A256KW",
+        }
+    }
+
+    fn key_length(self, encryption: JweEncryption) -> usize {
+        match self {
+            Self::Direct => encryption.key_length(),
+            Self::A128Kw => 16,
+            Self::A256Kw => 32,
+        }
+    }
+}
+
+/// How the claims of a JWE are encrypted, which is set as its `enc` header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JweEncryption {
+    /// AES GCM using a 16 byte key (`A128GCM`).
+    A128Gcm,
+
+    /// AES GCM using a 32 byte key (`A256GCM`).
+    A256Gcm,
+}
+
+impl JweEncryption {
+    fn name(self) -> &'static str {
+        match self {
+            Self::A128Gcm => "A128GCM",
+            Self::A256Gcm => "A256GCM",
+        }
+    }
+
+    fn key_length(self) -> usize {
+        match self {
+            Self::A128Gcm => 16,
+            Self::A256Gcm => 32,
+        }
+    }
+}
+
+/// Mints encrypted JWTs ([RFC 7516](https://www.rfc-editor.org/rfc/rfc7516) JWEs),
+/// for testing applications which accept encrypted bearer tokens.
+///
+/// Tokens use the compact serialization, with the claims given as the Json payload.
+/// The header holds the `alg` and `enc` used, a `typ` of `JWT`,
+/// and the key id if one is set using [`JweMinter::with_key_id()`].
+///
+/// Tokens minted can be read back using [`JweMinter::decrypt()`],
+/// such as for checking tokens returned by the application.
+///
+/// ```rust
+/// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+/// #
+/// use axum::Router;
+/// use axum::routing::get;
+/// use axum_test::JweAlgorithm;
+/// use axum_test::JweEncryption;
+/// use axum_test::JweMinter;
+/// use axum_test::TestServer;
+/// use serde_json::json;
+///
+/// let app = Router::new()
+///     .route(&"/me", get(|| async { "Joe" }));
+/// let server = TestServer::new(app)?;
+///
+/// let minter = JweMinter::new(JweAlgorithm::A256Kw, JweEncryption::A256Gcm, [7; 32])
+///     .with_key_id("test-key");
+/// let token = minter.mint(json!({ "sub": "user-1", "scope": "read" }));
+///
+/// server.get(&"/me")
+///     .authorization_bearer(&token)
+///     .await
+///     .assert_text("Joe");
+///
+/// let claims: serde_json::Value = minter.decrypt(&token)?;
+/// assert_eq!(claims["sub"], "user-1");
+/// #
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone)]
+pub struct JweMinter {
+    algorithm: JweAlgorithm,
+    encryption: JweEncryption,
+    key: Vec<u8>,
+    maybe_key_id: Option<String>,
+}
+
+impl JweMinter {
+    /// Creates a minter encrypting tokens with the key given.
+    ///
+    /// This will panic if the key is not the length needed by the algorithm and encryption given.
+    #[must_use]
+    #[track_caller]
+    pub fn new<K>(algorithm: JweAlgorithm, encryption: JweEncryption, key: K) -> Self
+    where
+        K: Into<Vec<u8>>,
+    {
+        let key = key.into();
+        let expected_length = algorithm.key_length(encryption);
+        if key.len() != expected_length {
+            panic!(
+                "Expected a {expected_length} byte key for JWE algorithm '{}' with encryption '{}', received {} bytes",
+                algorithm.name(),
+                encryption.name(),
+                key.len()
+            );
+        }
+
+        Self {
+            algorithm,
+            encryption,
+            key,
+            maybe_key_id: None,
+        }
+    }
+
+    /// Sets the `kid` header of tokens minted,
+    /// for applications which pick the decryption key by its id.
+    #[must_use]
+    pub fn with_key_id<K>(mut self, key_id: K) -> Self
+    where
+        K: Into<String>,
+    {
+        self.maybe_key_id = Some(key_id.into());
+        self
+    }
+
+    /// Returns a new token holding the claims given.
+    ///
+    /// Each token is encrypted with a new random initialization vector,
+    /// so minting the same claims twice returns different tokens.
+    ///
+    /// This will panic if the claims cannot be serialized to Json.
+    #[must_use]
+    pub fn mint<C>(&self, claims: C) -> String
+    where
+        C: Serialize,
+    {
+        let claims = serde_json::to_vec(&claims).expect("Failed to serialize JWE claims to Json");
+
+        let mut header = json!({
+            "alg": self.algorithm.name(),
+            "enc": self.encryption.name(),
+            "typ": "JWT",
+        });
+        if let Some(key_id) = &self.maybe_key_id {
+            header["kid"] = Value::String(key_id.clone());
+        }
+        let encoded_header = encode_base64_url(header.to_string().as_bytes());
+
+        let content_key = match self.algorithm {
+            JweAlgorithm::Direct => self.key.clone(),
+            JweAlgorithm::A128Kw | JweAlgorithm::A256Kw => match self.encryption {
+                JweEncryption::A128Gcm => Aes128Gcm::generate_key(OsRng).to_vec(),
+                JweEncryption::A256Gcm => Aes256Gcm::generate_key(OsRng).to_vec(),
+            },
+        };
+        let encrypted_key = self.wrap_key(&content_key);
+
+        let (iv, mut ciphertext) = match self.encryption {
+            JweEncryption::A128Gcm => {
+                encrypt_content::<Aes128Gcm>(&content_key, &claims, encoded_header.as_bytes())
+            }
+            JweEncryption::A256Gcm => {
+                encrypt_content::<Aes256Gcm>(&content_key, &claims, encoded_header.as_bytes())
+            }
+        };
+        let tag = ciphertext.split_off(ciphertext.len() - GCM_TAG_LENGTH);
+
+        format!(
+            "{encoded_header}.{}.{}.{}.{}",
+            encode_base64_url(&encrypted_key),
+            encode_base64_url(&iv),
+            encode_base64_url(&ciphertext),
+            encode_base64_url(&tag)
+        )
+    }
+
+    /// Decrypts a token encrypted with this minter's key, algorithm, and encryption,
+    /// and deserializes its claims.
+    ///
+    /// An error is returned if the token is not a JWE using them,
+    /// or if it was changed after it was encrypted.
+    pub fn decrypt<C>(&self, token: &str) -> Result<C>
+    where
+        C: DeserializeOwned,
+    {
+        let parts = token.split('.').collect::<Vec<_>>();
+        let [encoded_header, encrypted_key, iv, ciphertext, tag] = parts.as_slice() else {
+            return Err(anyhow!(
+                "Expected a JWE with five parts separated by '.', received {} parts",
+                parts.len()
+            ));
+        };
+
+        let header: Value = serde_json::from_slice(&decode_base64(encoded_header)?)
+            .context("Failed to parse JWE header as Json")?;
+        if header["alg"] != self.algorithm.name() || header["enc"] != self.encryption.name() {
+            return Err(anyhow!(
+                "Expected a JWE using alg '{}' and enc '{}', received header {header}",
+                self.algorithm.name(),
+                self.encryption.name()
+            ));
+        }
+
+        let content_key = self.unwrap_key(&decode_base64(encrypted_key)?)?;
+        let iv = decode_base64(iv)?;
+        if iv.len() != GCM_IV_LENGTH {
+            return Err(anyhow!(
+                "Expected a {GCM_IV_LENGTH} byte JWE initialization vector, received {} bytes",
+                iv.len()
+            ));
+        }
+        let mut ciphertext = decode_base64(ciphertext)?;
+        ciphertext.extend(decode_base64(tag)?);
+
+        let claims = match self.encryption {
+            JweEncryption::A128Gcm => decrypt_content::<Aes128Gcm>(
+                &content_key,
+                &iv,
+                &ciphertext,
+                encoded_header.as_bytes(),
+            ),
+            JweEncryption::A256Gcm => decrypt_content::<Aes256Gcm>(
+                &content_key,
+                &iv,
+                &ciphertext,
+                encoded_header.as_bytes(),
+            ),
+        }?;
+
+        serde_json::from_slice(&claims).context("Failed to deserialize JWE claims from Json")
+    }
+
+    fn wrap_key(&self, content_key: &[u8]) -> Vec<u8> {
+        let wrapped_key = match self.algorithm {
+            JweAlgorithm::Direct => return Vec::new(),
+            JweAlgorithm::A128Kw => KekAes128::try_from(self.key.as_slice())
+                .expect("Failed to create AES key wrap from JWE key")
+                .wrap_vec(content_key),
+            JweAlgorithm::A256Kw => KekAes256::try_from(self.key.as_slice())
+                .expect("Failed to create AES key wrap from JWE key")
+                .wrap_vec(content_key),
+        };
+
+        wrapped_key.expect("Failed to wrap JWE content encryption key")
+    }
+
+    fn unwrap_key(&self, encrypted_key: &[u8]) -> Result<Vec<u8>> {
+        let unwrapped_key = match self.algorithm {
+            JweAlgorithm::Direct => {
+                if !encrypted_key.is_empty() {
+                    return Err(anyhow!(
+                        "Expected an empty JWE encrypted key when using alg 'dir'"
+                    ));
+                }
+
+                return Ok(self.key.clone());
+            }
+            JweAlgorithm::A128Kw => KekAes128::try_from(self.key.as_slice())
+                .expect("Failed to create AES key wrap from JWE key")
+                .unwrap_vec(encrypted_key),
+            JweAlgorithm::A256Kw => KekAes256::try_from(self.key.as_slice())
+                .expect("Failed to create AES key wrap from JWE key")
+                .unwrap_vec(encrypted_key),
+        };
+
+        unwrapped_key.map_err(|_| {
+            anyhow!("Failed to unwrap JWE content encryption key, the key is wrong or the token was changed")
+        })
+    }
+}
+
+/// Encrypts the plaintext with a new random initialization vector,
+/// returning the vector used, and the ciphertext followed by its authentication tag.
+fn encrypt_content<A>(key: &[u8], plaintext: &[u8], aad: &[u8]) -> (Vec<u8>, Vec<u8>)
+where
+    A: Aead + AeadCore + KeyInit,
+{
+    let cipher = A::new_from_slice(key).expect("Failed to create JWE content cipher from key");
+    let iv = A::generate_nonce(OsRng);
+    let ciphertext = cipher
+        .encrypt(
+            &iv,
+            Payload {
+                msg: plaintext,
+                aad,
+            },
+        )
+        .expect("Failed to encrypt JWE claims");
+
+    (iv.to_vec(), ciphertext)
+}
+
+fn decrypt_content<A>(key: &[u8], iv: &[u8], ciphertext: &[u8], aad: &[u8]) -> Result<Vec<u8>>
+where
+    A: Aead + AeadCore + KeyInit,
+{
+    let cipher = A::new_from_slice(key)
+        .map_err(|_| anyhow!("Failed to create JWE content cipher from key"))?;
+
+    cipher
+        .decrypt(
+            Nonce::<A>::from_slice(iv),
+            Payload {
+                msg: ciphertext,
+                aad,
+            },
+        )
+        .map_err(|_| anyhow!("Failed to decrypt JWE, the key is wrong or the token was changed"))
+}
+
+#[cfg(test)]
+mod test_mint {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const KEY_16: [u8; 16] = [1; 16];
+    const KEY_32: [u8; 32] = [2; 32];
+
+    fn read_header(token: &str) -> Value {
+        let encoded_header = token.split('.').next().unwrap();
+        serde_json::from_slice(&decode_base64(encoded_header).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn it_should_round_trip_claims_for_all_algorithms_and_encryptions() {
+        let minters = [
+            JweMinter::new(JweAlgorithm::Direct, JweEncryption::A128Gcm, KEY_16),
+            JweMinter::new(JweAlgorithm::Direct, JweEncryption::A256Gcm, KEY_32),
+            JweMinter::new(JweAlgorithm::A128Kw, JweEncryption::A128Gcm, KEY_16),
+            JweMinter::new(JweAlgorithm::A128Kw, JweEncryption::A256Gcm, KEY_16),
+            JweMinter::new(JweAlgorithm::A256Kw, JweEncryption::A128Gcm, KEY_32),
+            JweMinter::new(JweAlgorithm::A256Kw, JweEncryption::A256Gcm, KEY_32),
+        ];
+        let claims = json!({ "sub": "user-1", "scope": "read write" });
+
+        for minter in minters {
+            let token = minter.mint(&claims);
+
+            assert_eq!(token.split('.').count(), 5);
+            assert_eq!(minter.decrypt::<Value>(&token).unwrap(), claims);
+        }
+    }
+
+    #[test]
+    fn it_should_set_the_header() {
+        let minter = JweMinter::new(JweAlgorithm::A256Kw, JweEncryption::A256Gcm, KEY_32)
+            .with_key_id("test-key");
+
+        let token = minter.mint(json!({ "sub": "user-1" }));
+
+        assert_eq!(
+            read_header(&token),
+            json!({
+                "alg": "A256KW",
+                "enc": "A256GCM",
+                "typ": "JWT",
+                "kid": "test-key",
+            })
+        );
+    }
+
+    #[test]
+    fn it_should_leave_the_encrypted_key_empty_for_direct_encryption() {
+        let minter = JweMinter::new(JweAlgorithm::Direct, JweEncryption::A128Gcm, KEY_16);
+
+        let token = minter.mint(json!({ "sub": "user-1" }));
+
+        assert_eq!(token.split('.').nth(1), Some(""));
+    }
+
+    #[test]
+    fn it_should_mint_different_tokens_for_the_same_claims() {
+        let minter = JweMinter::new(JweAlgorithm::A128Kw, JweEncryption::A128Gcm, KEY_16);
+
+        let first = minter.mint(json!({ "sub": "user-1" }));
+        let second = minter.mint(json!({ "sub": "user-1" }));
+
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Expected a 32 byte key for JWE algorithm 'dir' with encryption 'A256GCM', received 16 bytes"
+    )]
+    fn it_should_panic_when_the_key_is_the_wrong_length() {
+        let _ = JweMinter::new(JweAlgorithm::Direct, JweEncryption::A256Gcm, KEY_16);
+    }
+}
+
+#[cfg(test)]
+mod test_decrypt {
+    use super::*;
+
+    fn new_minter(key: [u8; 32]) -> JweMinter {
+        JweMinter::new(JweAlgorithm::A256Kw, JweEncryption::A256Gcm, key)
+    }
+
+    #[test]
+    fn it_should_deserialize_claims_into_types() {
+        #[derive(serde::Deserialize)]
+        struct Claims {
+            sub: String,
+        }
+
+        let minter = new_minter([3; 32]);
+        let token = minter.mint(json!({ "sub": "user-1" }));
+
+        let claims: Claims = minter.decrypt(&token).unwrap();
+        assert_eq!(claims.sub, "user-1");
+    }
+
+    #[test]
+    fn it_should_fail_with_a_different_key() {
+        let token = new_minter([3; 32]).mint(json!({ "sub": "user-1" }));
+
+        let error = new_minter([4; 32]).decrypt::<Value>(&token).unwrap_err();
+
+        assert!(error
+            .to_string()
+            .contains("Failed to unwrap JWE content encryption key"));
+    }
+
+    #[test]
+    fn it_should_fail_when_the_token_is_changed() {
+        let minter = new_minter([3; 32]);
+        let token = minter.mint(json!({ "sub": "user-1" }));
+        let mut parts = token.split('.').map(str::to_string).collect::<Vec<_>>();
+        parts[3] = encode_base64_url(b"changed");
+
+        let error = minter.decrypt::<Value>(&parts.join(".")).unwrap_err();
+
+        assert!(error.to_string().contains("Failed to decrypt JWE"));
+    }
+
+    #[test]
+    fn it_should_fail_when_the_algorithm_differs() {
+        let token = JweMinter::new(JweAlgorithm::Direct, JweEncryption::A256Gcm, [3; 32])
+            .mint(json!({ "sub": "user-1" }));
+
+        let error = new_minter([3; 32]).decrypt::<Value>(&token).unwrap_err();
+
+        assert!(error
+            .to_string()
+            .contains("Expected a JWE using alg 'A256KW' and enc 'A256GCM'"));
+    }
+
+    #[test]
+    fn it_should_fail_for_tokens_which_are_not_jwes() {
+        let error = new_minter([3; 32])
+            .decrypt::<Value>("header.payload.signature")
+            .unwrap_err();
+
+        assert!(error
+            .to_string()
+            .contains("Expected a JWE with five parts separated by '.', received 3 parts"));
+    }
+}
+
+#[cfg(test)]
+mod test_bearer_tokens {
+    use super::*;
+    use crate::TestServer;
+    use axum::extract::State;
+    use axum::routing::get;
+    use axum::Router;
+    use http::header;
+    use http::HeaderMap;
+    use http::StatusCode;
+
+    async fn route_get_me(
+        State(minter): State<JweMinter>,
+        headers: HeaderMap,
+    ) -> Result<String, StatusCode> {
+        let token = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or(StatusCode::UNAUTHORIZED)?;
+        let claims: Value = minter
+            .decrypt(token)
+            .map_err(|_| StatusCode::UNAUTHORIZED)?;
+
+        Ok(claims["sub"].as_str().unwrap_or_default().to_string())
+    }
+
+    #[tokio::test]
+    async fn it_should_be_accepted_by_applications_sharing_the_key() {
+        let minter = JweMinter::new(JweAlgorithm::A128Kw, JweEncryption::A256Gcm, [5; 16]);
+        let app = Router::new()
+            .route("/me", get(route_get_me))
+            .with_state(minter.clone());
+        let server = TestServer::new(app).unwrap();
+
+        server
+            .get("/me")
+            .authorization_bearer(minter.mint(json!({ "sub": "user-1" })))
+            .await
+            .assert_text("user-1");
+
+        let other_minter = JweMinter::new(JweAlgorithm::A128Kw, JweEncryption::A256Gcm, [6; 16]);
+        server
+            .get("/me")
+            .authorization_bearer(other_minter.mint(json!({ "sub": "user-1" })))
+            .expect_failure()
+            .await
+            .assert_status_unauthorized();
+    }
+}
@@ -0,0 +1,204 @@
+use axum::extract::rejection::BytesRejection;
+use axum::extract::rejection::ExtensionRejection;
+use axum::extract::rejection::FailedToBufferBody;
+use axum::extract::rejection::FailedToDeserializeForm;
+use axum::extract::rejection::FailedToDeserializeFormBody;
+use axum::extract::rejection::FailedToDeserializePathParams;
+use axum::extract::rejection::FailedToDeserializeQueryString;
+use axum::extract::rejection::FailedToResolveHost;
+use axum::extract::rejection::FormRejection;
+use axum::extract::rejection::HostRejection;
+use axum::extract::rejection::InvalidFormContentType;
+use axum::extract::rejection::InvalidUtf8;
+use axum::extract::rejection::InvalidUtf8InPathParam;
+use axum::extract::rejection::JsonDataError;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::rejection::JsonSyntaxError;
+use axum::extract::rejection::LengthLimitError;
+use axum::extract::rejection::MatchedPathMissing;
+use axum::extract::rejection::MatchedPathRejection;
+use axum::extract::rejection::MissingExtension;
+use axum::extract::rejection::MissingJsonContentType;
+use axum::extract::rejection::MissingPathParams;
+use axum::extract::rejection::NestedPathRejection;
+use axum::extract::rejection::PathRejection;
+use axum::extract::rejection::QueryRejection;
+use axum::extract::rejection::RawFormRejection;
+use axum::extract::rejection::RawPathParamsRejection;
+use axum::extract::rejection::StringRejection;
+use axum::extract::rejection::UnknownBodyError;
+use http::StatusCode;
+
+/// A response axum sends when an extractor rejects a request,
+/// as the status code and the text the body starts with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RejectionResponse {
+    /// The status code axum responds with.
+    pub status: StatusCode,
+
+    /// The start of the body, before any details about the error.
+    pub body_prefix: &'static str,
+}
+
+/// An axum rejection type, that can be recognised from the response it produces.
+///
+/// This is implemented for the rejections in [`axum::extract::rejection`],
+/// for use with [`TestResponse::assert_is_rejection()`](crate::TestResponse::assert_is_rejection()).
+/// The composite rejections, such as [`JsonRejection`], match any of the rejections they hold.
+pub trait KnownRejection {
+    /// The responses axum can produce for this rejection.
+    fn known_responses() -> &'static [RejectionResponse];
+
+    /// Returns true if the status code and body were produced by this rejection.
+    fn is_response(status: StatusCode, body: &str) -> bool {
+        Self::known_responses()
+            .iter()
+            .any(|known| known.status == status && body.starts_with(known.body_prefix))
+    }
+}
+
+macro_rules! known_rejection {
+    ($rejection:ty => [$(($status:ident, $body_prefix:expr)),+ $(,)?]) => {
+        impl KnownRejection for $rejection {
+            fn known_responses() -> &'static [RejectionResponse] {
+                &[$(RejectionResponse {
+                    status: StatusCode::$status,
+                    body_prefix: $body_prefix,
+                }),+]
+            }
+        }
+    };
+}
+
+const JSON_DATA_ERROR: &str = "Failed to deserialize the JSON body into the target type";
+const JSON_SYNTAX_ERROR: &str = "Failed to parse the request body as JSON";
+const MISSING_JSON_CONTENT_TYPE: &str = "Expected request with `Content-Type: application/json`";
+const MISSING_EXTENSION: &str = "Missing request extension";
+const MISSING_PATH_PARAMS: &str = "No paths parameters found for matched route";
+const INVALID_FORM_CONTENT_TYPE: &str =
+    "Form requests must have `Content-Type: application/x-www-form-urlencoded`";
+const FAILED_TO_RESOLVE_HOST: &str = "No host found in request";
+const FAILED_TO_DESERIALIZE_FORM: &str = "Failed to deserialize form";
+const FAILED_TO_DESERIALIZE_FORM_BODY: &str = "Failed to deserialize form body";
+const FAILED_TO_DESERIALIZE_QUERY_STRING: &str = "Failed to deserialize query string";
+const INVALID_PATH_PARAMS: &str = "Invalid URL: ";
+const WRONG_NUMBER_OF_PATH_PARAMS: &str = "Wrong number of path arguments for `Path`";
+const UNSUPPORTED_PATH_TYPE: &str = "Unsupported type `";
+const INVALID_UTF8_IN_PATH_PARAM: &str = "Invalid UTF-8 in `";
+const MATCHED_PATH_MISSING: &str = "No matched path found";
+const NESTED_PATH_REJECTION: &str = "The matched route is not nested";
+const FAILED_TO_BUFFER_BODY: &str = "Failed to buffer the request body";
+const INVALID_UTF8: &str = "Request body didn't contain valid UTF-8";
+
+known_rejection!(JsonDataError => [(UNPROCESSABLE_ENTITY, JSON_DATA_ERROR)]);
+known_rejection!(JsonSyntaxError => [(BAD_REQUEST, JSON_SYNTAX_ERROR)]);
+known_rejection!(MissingJsonContentType => [(UNSUPPORTED_MEDIA_TYPE, MISSING_JSON_CONTENT_TYPE)]);
+known_rejection!(MissingExtension => [(INTERNAL_SERVER_ERROR, MISSING_EXTENSION)]);
+known_rejection!(MissingPathParams => [(INTERNAL_SERVER_ERROR, MISSING_PATH_PARAMS)]);
+known_rejection!(InvalidFormContentType => [(UNSUPPORTED_MEDIA_TYPE, INVALID_FORM_CONTENT_TYPE)]);
+known_rejection!(FailedToResolveHost => [(BAD_REQUEST, FAILED_TO_RESOLVE_HOST)]);
+known_rejection!(FailedToDeserializeForm => [(BAD_REQUEST, FAILED_TO_DESERIALIZE_FORM)]);
+known_rejection!(FailedToDeserializeFormBody => [(UNPROCESSABLE_ENTITY, FAILED_TO_DESERIALIZE_FORM_BODY)]);
+known_rejection!(FailedToDeserializeQueryString => [(BAD_REQUEST, FAILED_TO_DESERIALIZE_QUERY_STRING)]);
+known_rejection!(FailedToDeserializePathParams => [
+    (BAD_REQUEST, INVALID_PATH_PARAMS),
+    (INTERNAL_SERVER_ERROR, WRONG_NUMBER_OF_PATH_PARAMS),
+    (INTERNAL_SERVER_ERROR, UNSUPPORTED_PATH_TYPE),
+]);
+known_rejection!(InvalidUtf8InPathParam => [(BAD_REQUEST, INVALID_UTF8_IN_PATH_PARAM)]);
+known_rejection!(MatchedPathMissing => [(INTERNAL_SERVER_ERROR, MATCHED_PATH_MISSING)]);
+known_rejection!(NestedPathRejection => [(INTERNAL_SERVER_ERROR, NESTED_PATH_REJECTION)]);
+known_rejection!(LengthLimitError => [(PAYLOAD_TOO_LARGE, FAILED_TO_BUFFER_BODY)]);
+known_rejection!(UnknownBodyError => [(BAD_REQUEST, FAILED_TO_BUFFER_BODY)]);
+known_rejection!(InvalidUtf8 => [(BAD_REQUEST, INVALID_UTF8)]);
+
+known_rejection!(FailedToBufferBody => [
+    (PAYLOAD_TOO_LARGE, FAILED_TO_BUFFER_BODY),
+    (BAD_REQUEST, FAILED_TO_BUFFER_BODY),
+]);
+known_rejection!(BytesRejection => [
+    (PAYLOAD_TOO_LARGE, FAILED_TO_BUFFER_BODY),
+    (BAD_REQUEST, FAILED_TO_BUFFER_BODY),
+]);
+known_rejection!(StringRejection => [
+    (PAYLOAD_TOO_LARGE, FAILED_TO_BUFFER_BODY),
+    (BAD_REQUEST, FAILED_TO_BUFFER_BODY),
+    (BAD_REQUEST, INVALID_UTF8),
+]);
+known_rejection!(JsonRejection => [
+    (UNPROCESSABLE_ENTITY, JSON_DATA_ERROR),
+    (BAD_REQUEST, JSON_SYNTAX_ERROR),
+    (UNSUPPORTED_MEDIA_TYPE, MISSING_JSON_CONTENT_TYPE),
+    (PAYLOAD_TOO_LARGE, FAILED_TO_BUFFER_BODY),
+    (BAD_REQUEST, FAILED_TO_BUFFER_BODY),
+]);
+known_rejection!(FormRejection => [
+    (UNSUPPORTED_MEDIA_TYPE, INVALID_FORM_CONTENT_TYPE),
+    (BAD_REQUEST, FAILED_TO_DESERIALIZE_FORM),
+    (UNPROCESSABLE_ENTITY, FAILED_TO_DESERIALIZE_FORM_BODY),
+    (PAYLOAD_TOO_LARGE, FAILED_TO_BUFFER_BODY),
+    (BAD_REQUEST, FAILED_TO_BUFFER_BODY),
+]);
+known_rejection!(RawFormRejection => [
+    (UNSUPPORTED_MEDIA_TYPE, INVALID_FORM_CONTENT_TYPE),
+    (PAYLOAD_TOO_LARGE, FAILED_TO_BUFFER_BODY),
+    (BAD_REQUEST, FAILED_TO_BUFFER_BODY),
+]);
+known_rejection!(QueryRejection => [(BAD_REQUEST, FAILED_TO_DESERIALIZE_QUERY_STRING)]);
+known_rejection!(ExtensionRejection => [(INTERNAL_SERVER_ERROR, MISSING_EXTENSION)]);
+known_rejection!(PathRejection => [
+    (BAD_REQUEST, INVALID_PATH_PARAMS),
+    (INTERNAL_SERVER_ERROR, WRONG_NUMBER_OF_PATH_PARAMS),
+    (INTERNAL_SERVER_ERROR, UNSUPPORTED_PATH_TYPE),
+    (INTERNAL_SERVER_ERROR, MISSING_PATH_PARAMS),
+]);
+known_rejection!(RawPathParamsRejection => [
+    (BAD_REQUEST, INVALID_UTF8_IN_PATH_PARAM),
+    (INTERNAL_SERVER_ERROR, MISSING_PATH_PARAMS),
+]);
+known_rejection!(HostRejection => [(BAD_REQUEST, FAILED_TO_RESOLVE_HOST)]);
+known_rejection!(MatchedPathRejection => [(INTERNAL_SERVER_ERROR, MATCHED_PATH_MISSING)]);
+
+#[cfg(test)]
+mod test_is_response {
+    use super::*;
+
+    #[test]
+    fn it_should_match_json_data_error() {
+        let body = "Failed to deserialize the JSON body into the target type: missing field `name`";
+
+        assert!(JsonDataError::is_response(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            body
+        ));
+        assert!(JsonRejection::is_response(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            body
+        ));
+        assert!(!JsonSyntaxError::is_response(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            body
+        ));
+    }
+
+    #[test]
+    fn it_should_not_match_with_a_different_status_code() {
+        let body = "Failed to deserialize the JSON body into the target type: missing field `name`";
+
+        assert!(!JsonDataError::is_response(StatusCode::BAD_REQUEST, body));
+    }
+
+    #[test]
+    fn it_should_tell_form_errors_apart_by_status_code() {
+        let body = "Failed to deserialize form body: missing field `name`";
+
+        assert!(FailedToDeserializeFormBody::is_response(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            body
+        ));
+        assert!(!FailedToDeserializeForm::is_response(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            body
+        ));
+    }
+}
@@ -77,8 +77,13 @@
 
 pub(crate) mod internals;
 
+#[cfg(feature = "blocking")]
+pub mod blocking;
+
 pub mod multipart;
 
+pub mod routes;
+
 pub mod transport_layer;
 pub mod util;
 
@@ -88,6 +93,9 @@ pub use self::test_request::*;
 mod test_response;
 pub use self::test_response::*;
 
+mod test_response_stream;
+pub use self::test_response_stream::*;
+
 mod test_server_builder;
 pub use self::test_server_builder::*;
 
@@ -97,6 +105,9 @@ pub use self::test_server_config::*;
 mod test_server;
 pub use self::test_server::*;
 
+mod test_server_scope;
+pub use self::test_server_scope::*;
+
 #[cfg(feature = "ws")]
 mod test_web_socket;
 #[cfg(feature = "ws")]
@@ -107,6 +118,213 @@ pub use tokio_tungstenite::tungstenite::Message as WsMessage;
 mod transport;
 pub use self::transport::*;
 
+mod json_tolerance;
+pub use self::json_tolerance::*;
+
+mod inner_request;
+pub use self::inner_request::*;
+
+mod chaos_config;
+pub use self::chaos_config::*;
+
+mod csrf_config;
+pub use self::csrf_config::*;
+
+mod browser_profile;
+pub use self::browser_profile::*;
+
+#[cfg(feature = "html")]
+mod html_document;
+#[cfg(feature = "html")]
+pub use self::html_document::*;
+
+#[cfg(feature = "html")]
+mod html_form;
+#[cfg(feature = "html")]
+pub use self::html_form::*;
+
+#[cfg(feature = "archives")]
+mod zip_archive;
+#[cfg(feature = "archives")]
+pub use self::zip_archive::*;
+
+mod server_metrics;
+pub use self::server_metrics::*;
+
+mod recorded_exchange;
+pub use self::recorded_exchange::*;
+
+mod transport_diagnostics;
+pub use self::transport_diagnostics::*;
+
+mod test_guard;
+pub use self::test_guard::*;
+
+mod route_info;
+pub use self::route_info::*;
+
+mod recording_router;
+pub use self::recording_router::*;
+
+mod response_burst;
+pub use self::response_burst::*;
+
+mod response_pair;
+pub use self::response_pair::*;
+
+mod header_matrix;
+pub use self::header_matrix::*;
+
+mod link_entry;
+pub use self::link_entry::*;
+
+mod scenario;
+pub use self::scenario::*;
+
+mod error_body_schema;
+pub use self::error_body_schema::*;
+
+mod problem_details;
+pub use self::problem_details::*;
+
+mod assertion_bundle;
+pub use self::assertion_bundle::*;
+
+mod response_validator;
+pub use self::response_validator::*;
+
+mod request_interceptor;
+pub use self::request_interceptor::*;
+
+mod test_event_log;
+pub use self::test_event_log::*;
+
+mod instrumentation_probe;
+pub use self::instrumentation_probe::*;
+
+mod query_counter;
+pub use self::query_counter::*;
+
+mod file_kind;
+pub use self::file_kind::*;
+
+mod mock_introspection_endpoint;
+pub use self::mock_introspection_endpoint::*;
+
+#[cfg(feature = "jwe")]
+mod jwe_minter;
+#[cfg(feature = "jwe")]
+pub use self::jwe_minter::*;
+
+#[cfg(feature = "webhooks")]
+mod webhook_catcher;
+#[cfg(feature = "webhooks")]
+pub use self::webhook_catcher::*;
+
+#[cfg(feature = "mail")]
+mod mail_catcher;
+#[cfg(feature = "mail")]
+pub use self::mail_catcher::*;
+
+#[cfg(feature = "tus")]
+mod tus_upload;
+#[cfg(feature = "tus")]
+pub use self::tus_upload::*;
+
+#[cfg(feature = "matched-route")]
+mod matched_route_layer;
+#[cfg(feature = "matched-route")]
+pub use self::matched_route_layer::*;
+
+#[cfg(feature = "etag")]
+mod hash_algorithm;
+#[cfg(feature = "etag")]
+pub use self::hash_algorithm::*;
+
+#[cfg(feature = "jsonapi")]
+mod jsonapi_links;
+#[cfg(feature = "jsonapi")]
+pub use self::jsonapi_links::*;
+
+#[cfg(feature = "rejections")]
+mod known_rejection;
+#[cfg(feature = "rejections")]
+pub use self::known_rejection::*;
+
+#[cfg(feature = "reqwest")]
+mod reqwest_configurer;
+#[cfg(feature = "reqwest")]
+pub use self::reqwest_configurer::*;
+
+mod assertion_error;
+pub use self::assertion_error::*;
+
+mod error;
+pub use self::error::*;
+
+mod capabilities;
+pub use self::capabilities::*;
+
+#[cfg(feature = "macros")]
+#[doc(hidden)]
+pub mod test_harness;
+
+/// Marks an async function as a test, which is given a [`TestServer`].
+///
+/// The `TestServer` is built by calling the function given as `server`,
+/// and the test is run on a new tokio runtime.
+/// This removes the need to write `#[tokio::test]` and build the server at the start of every test.
+///
+/// ```rust
+/// use axum::Router;
+/// use axum::routing::get;
+/// use axum_test::TestServer;
+///
+/// fn new_test_server() -> TestServer {
+///     let app = Router::new()
+///         .route(&"/ping", get(|| async { "pong!" }));
+///
+///     TestServer::new(app).unwrap()
+/// }
+///
+/// #[axum_test::test(server = new_test_server)]
+/// async fn it_should_ping(server: TestServer) {
+///     server.get(&"/ping").await.assert_text("pong!");
+/// }
+/// ```
+///
+/// The server can be taken by value, by reference (`&TestServer`),
+/// or by mutable reference (`&mut TestServer`).
+///
+/// Optional `setup` and `teardown` hooks can also be given.
+/// These are async functions taking a `&TestServer`,
+/// run before and after the test. Teardown runs even if the test panics,
+/// which requires the test to take the server by reference.
+///
+/// ```rust
+/// # use axum::Router;
+/// # use axum_test::TestServer;
+/// #
+/// # fn new_test_server() -> TestServer {
+/// #     TestServer::new(Router::new()).unwrap()
+/// # }
+/// #
+/// async fn seed_users(server: &TestServer) {
+///     // ...
+/// }
+///
+/// async fn delete_users(server: &TestServer) {
+///     // ...
+/// }
+///
+/// #[axum_test::test(server = new_test_server, setup = seed_users, teardown = delete_users)]
+/// async fn it_should_list_users(server: &TestServer) {
+///     // ...
+/// }
+/// ```
+#[cfg(feature = "macros")]
+pub use axum_test_macros::test;
+
 pub use http;
 
 #[cfg(test)]
@@ -438,3 +656,68 @@ mod integrated_test_typed_routing_and_query {
             .assert_text("get 123, with-added-query");
     }
 }
+
+#[cfg(all(test, feature = "macros"))]
+extern crate self as axum_test;
+
+#[cfg(all(test, feature = "macros"))]
+mod integrated_test_macros {
+    use axum::routing::get;
+    use axum::Router;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+
+    use crate::TestServer;
+
+    static SETUP_CALLS: AtomicUsize = AtomicUsize::new(0);
+    static TEARDOWN_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new().route("/ping", get(|| async { "pong!" }));
+
+        TestServer::new(app).unwrap()
+    }
+
+    async fn count_setup(_server: &TestServer) {
+        SETUP_CALLS.fetch_add(1, Ordering::SeqCst);
+    }
+
+    async fn count_teardown(_server: &TestServer) {
+        TEARDOWN_CALLS.fetch_add(1, Ordering::SeqCst);
+    }
+
+    #[crate::test(server = new_test_server)]
+    async fn it_should_inject_server_by_value(server: TestServer) {
+        server.get("/ping").await.assert_text("pong!");
+    }
+
+    #[crate::test(server = new_test_server)]
+    async fn it_should_inject_server_by_mutable_reference(server: &mut TestServer) {
+        server.add_header("x-custom", "value");
+        server.get("/ping").await.assert_text("pong!");
+    }
+
+    #[crate::test(server = new_test_server)]
+    async fn it_should_support_returning_results(server: &TestServer) -> anyhow::Result<()> {
+        server.get("/ping").await.assert_text("pong!");
+        Ok(())
+    }
+
+    #[crate::test(server = new_test_server, setup = count_setup, teardown = count_teardown)]
+    async fn it_should_run_setup_and_teardown_hooks(server: &TestServer) {
+        server.get("/ping").await.assert_text("pong!");
+        assert!(SETUP_CALLS.load(Ordering::SeqCst) >= 1);
+    }
+
+    /// Panics with a different message to the test,
+    /// so the test only passes if the teardown ran after the test panicked.
+    async fn panic_in_teardown(_server: &TestServer) {
+        panic!("teardown ran");
+    }
+
+    #[crate::test(server = new_test_server, teardown = panic_in_teardown)]
+    #[should_panic(expected = "teardown ran")]
+    async fn it_should_run_teardown_when_test_panics(server: &TestServer) {
+        server.get("/ping").await.assert_text("not pong");
+    }
+}
@@ -0,0 +1,65 @@
+/// A single link from a `Link` header, as described in RFC 8288.
+///
+/// These are returned from [`TestResponse::links()`](crate::TestResponse::links()).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkEntry {
+    /// The target of the link, exactly as it appears between the `<` and `>`.
+    pub target: String,
+
+    /// The parameters of the link in the order they appear, such as `rel` and `as`.
+    ///
+    /// Names are lower case, and quotes around values are removed.
+    pub params: Vec<(String, String)>,
+}
+
+impl LinkEntry {
+    /// Returns true if the `rel` parameter contains the relation given.
+    ///
+    /// Relations are compared case insensitively,
+    /// and a `rel` can hold multiple relations separated by spaces (i.e. `rel="next last"`).
+    #[must_use]
+    pub fn has_rel(&self, rel: &str) -> bool {
+        self.params
+            .iter()
+            .filter(|(name, _)| name == "rel")
+            .flat_map(|(_, value)| value.split_whitespace())
+            .any(|value| value.eq_ignore_ascii_case(rel))
+    }
+
+    /// Returns the value of the first parameter with the name given, if there is one.
+    ///
+    /// Names are compared case insensitively.
+    #[must_use]
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(param_name, _)| param_name.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the `as` parameter, the type of content for `preload` links (i.e. `style` or `script`).
+    #[must_use]
+    pub fn as_type(&self) -> Option<&str> {
+        self.param("as")
+    }
+}
+
+#[cfg(test)]
+mod test_param {
+    use super::*;
+
+    #[test]
+    fn it_should_find_params_ignoring_case() {
+        let link = LinkEntry {
+            target: "/style.css".to_string(),
+            params: vec![
+                ("rel".to_string(), "preload".to_string()),
+                ("as".to_string(), "style".to_string()),
+            ],
+        };
+
+        assert_eq!(link.param("REL"), Some("preload"));
+        assert_eq!(link.as_type(), Some("style"));
+        assert_eq!(link.param("crossorigin"), None);
+    }
+}
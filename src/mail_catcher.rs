@@ -0,0 +1,715 @@
+use anyhow::Context;
+use bytes::Bytes;
+use mail_parser::MessageParser;
+use mail_parser::MimeHeaders;
+use std::net::SocketAddr;
+use std::net::TcpListener as StdTcpListener;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::io::AsyncBufReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::io::BufReader;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use tokio::time::timeout_at;
+use tokio::time::Instant;
+
+use crate::internals::check;
+use crate::internals::OrPanic;
+use crate::AssertionError;
+use crate::Error;
+
+/// A file attached to a [`CapturedEmail`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmailAttachment {
+    /// The file name of the attachment, if one was given.
+    pub filename: Option<String>,
+
+    /// The content type of the attachment, i.e. `text/csv`.
+    pub content_type: Option<String>,
+
+    /// The decoded contents of the attachment.
+    pub contents: Bytes,
+}
+
+/// An email received by a [`MailCatcher`].
+///
+/// The message is parsed when it is received,
+/// decoding the subject, bodies, and attachments.
+#[derive(Debug, Clone)]
+pub struct CapturedEmail {
+    envelope_from: String,
+    envelope_recipients: Vec<String>,
+    raw: Bytes,
+    subject: Option<String>,
+    from: Option<String>,
+    to: Vec<String>,
+    text_body: Option<String>,
+    html_body: Option<String>,
+    attachments: Vec<EmailAttachment>,
+}
+
+impl CapturedEmail {
+    fn parse(envelope_from: String, envelope_recipients: Vec<String>, raw: Bytes) -> Self {
+        let maybe_message = MessageParser::default().parse(raw.as_ref());
+        let Some(message) = maybe_message else {
+            return Self {
+                envelope_from,
+                envelope_recipients,
+                raw,
+                subject: None,
+                from: None,
+                to: Vec::new(),
+                text_body: None,
+                html_body: None,
+                attachments: Vec::new(),
+            };
+        };
+
+        let from = message
+            .from()
+            .and_then(|from| from.first())
+            .and_then(|from| from.address())
+            .map(ToString::to_string);
+        let to = message
+            .to()
+            .map(|to| {
+                to.iter()
+                    .filter_map(|to| to.address())
+                    .map(ToString::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let attachments = message
+            .attachments()
+            .map(|attachment| EmailAttachment {
+                filename: attachment.attachment_name().map(ToString::to_string),
+                content_type: attachment.content_type().map(|content_type| {
+                    match content_type.subtype() {
+                        Some(subtype) => format!("{}/{subtype}", content_type.ctype()),
+                        None => content_type.ctype().to_string(),
+                    }
+                }),
+                contents: Bytes::copy_from_slice(attachment.contents()),
+            })
+            .collect();
+
+        Self {
+            subject: message.subject().map(ToString::to_string),
+            from,
+            to,
+            text_body: message.body_text(0).map(|body| body.to_string()),
+            html_body: message.body_html(0).map(|body| body.to_string()),
+            attachments,
+            envelope_from,
+            envelope_recipients,
+            raw,
+        }
+    }
+
+    /// The sender given to the SMTP server, with `MAIL FROM`.
+    #[must_use]
+    pub fn envelope_from(&self) -> &str {
+        &self.envelope_from
+    }
+
+    /// The recipients given to the SMTP server, with `RCPT TO`.
+    /// This includes any `Bcc` recipients.
+    #[must_use]
+    pub fn envelope_recipients(&self) -> &[String] {
+        &self.envelope_recipients
+    }
+
+    /// The raw message, as sent to the SMTP server.
+    #[must_use]
+    pub fn raw(&self) -> &Bytes {
+        &self.raw
+    }
+
+    /// The decoded `Subject` header.
+    #[must_use]
+    pub fn subject(&self) -> Option<&str> {
+        self.subject.as_deref()
+    }
+
+    /// The address in the `From` header.
+    #[must_use]
+    pub fn from(&self) -> Option<&str> {
+        self.from.as_deref()
+    }
+
+    /// The addresses in the `To` header.
+    #[must_use]
+    pub fn to(&self) -> &[String] {
+        &self.to
+    }
+
+    /// The plain text body, if there is one.
+    ///
+    /// For messages with only a HTML body, this is the HTML converted to text.
+    #[must_use]
+    pub fn text_body(&self) -> Option<&str> {
+        self.text_body.as_deref()
+    }
+
+    /// The HTML body, if there is one.
+    ///
+    /// For messages with only a plain text body, this is the text converted to HTML.
+    #[must_use]
+    pub fn html_body(&self) -> Option<&str> {
+        self.html_body.as_deref()
+    }
+
+    /// The files attached to the email.
+    #[must_use]
+    pub fn attachments(&self) -> &[EmailAttachment] {
+        &self.attachments
+    }
+
+    /// Returns the attachment with the file name given, if there is one.
+    #[must_use]
+    pub fn maybe_attachment(&self, filename: &str) -> Option<&EmailAttachment> {
+        self.attachments
+            .iter()
+            .find(|attachment| attachment.filename.as_deref() == Some(filename))
+    }
+
+    /// Asserts the subject contains the text given.
+    #[track_caller]
+    pub fn assert_subject_contains(&self, expected_contents: &str) {
+        self.check_subject_contains(expected_contents).or_panic()
+    }
+
+    /// Checks the subject contains the text given.
+    ///
+    /// This is the non-panicking version of [`CapturedEmail::assert_subject_contains()`].
+    pub fn check_subject_contains(&self, expected_contents: &str) -> Result<(), AssertionError> {
+        let subject = self.subject().unwrap_or_default();
+
+        check(
+            subject.contains(expected_contents),
+            format_args!(
+                "Expected email subject to contain '{expected_contents}', received '{subject}'"
+            ),
+        )
+    }
+
+    /// Asserts the plain text body contains the text given.
+    #[track_caller]
+    pub fn assert_text_body_contains(&self, expected_contents: &str) {
+        self.check_text_body_contains(expected_contents).or_panic()
+    }
+
+    /// Checks the plain text body contains the text given.
+    ///
+    /// This is the non-panicking version of [`CapturedEmail::assert_text_body_contains()`].
+    pub fn check_text_body_contains(&self, expected_contents: &str) -> Result<(), AssertionError> {
+        let text_body = self.text_body().unwrap_or_default();
+
+        check(
+            text_body.contains(expected_contents),
+            format_args!(
+                "Expected email text body to contain '{expected_contents}', received '{text_body}'"
+            ),
+        )
+    }
+
+    /// Asserts the email was sent to the address given,
+    /// either in the envelope or the `To` header.
+    #[track_caller]
+    pub fn assert_recipient(&self, expected_address: &str) {
+        self.check_recipient(expected_address).or_panic()
+    }
+
+    /// Checks the email was sent to the address given.
+    ///
+    /// This is the non-panicking version of [`CapturedEmail::assert_recipient()`].
+    pub fn check_recipient(&self, expected_address: &str) -> Result<(), AssertionError> {
+        let is_recipient = self
+            .envelope_recipients
+            .iter()
+            .chain(&self.to)
+            .any(|address| address.eq_ignore_ascii_case(expected_address));
+
+        check(
+            is_recipient,
+            format_args!(
+                "Expected email to be sent to '{expected_address}', recipients were {:?}",
+                self.envelope_recipients
+            ),
+        )
+    }
+
+    /// Asserts the email has an attachment with the file name given.
+    #[track_caller]
+    pub fn assert_has_attachment(&self, filename: &str) {
+        self.check_has_attachment(filename).or_panic()
+    }
+
+    /// Checks the email has an attachment with the file name given.
+    ///
+    /// This is the non-panicking version of [`CapturedEmail::assert_has_attachment()`].
+    pub fn check_has_attachment(&self, filename: &str) -> Result<(), AssertionError> {
+        let filenames: Vec<&str> = self
+            .attachments
+            .iter()
+            .filter_map(|attachment| attachment.filename.as_deref())
+            .collect();
+
+        check(
+            filenames.contains(&filename),
+            format_args!(
+                "Expected email to have attachment '{filename}', found attachments {filenames:?}"
+            ),
+        )
+    }
+}
+
+#[derive(Debug, Default)]
+struct CatcherState {
+    emails: Mutex<Vec<CapturedEmail>>,
+    num_awaited: Mutex<usize>,
+    notify: Notify,
+}
+
+/// An in-process SMTP server, which records the emails sent to it.
+///
+/// Give the address from [`MailCatcher::address()`] to your application as its SMTP server,
+/// and then wait for emails to arrive using [`MailCatcher::await_email()`].
+///
+/// It accepts all mail, and does not support TLS or authentication,
+/// so your SMTP client needs to be configured to send without them.
+/// The server stops when the catcher is dropped.
+///
+/// ```rust
+/// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+/// #
+/// use axum_test::MailCatcher;
+/// use std::time::Duration;
+///
+/// let mail = MailCatcher::start()?;
+/// let smtp_address = mail.address();
+///
+/// // Configure your application to send email to `smtp_address`,
+/// // and do something which sends one ...
+///
+/// let email = mail.await_email(Duration::from_secs(5)).await;
+/// email.assert_recipient("joe@example.com");
+/// email.assert_subject_contains("Verify your email");
+/// #
+/// # Ok(()) }
+/// ```
+#[derive(Debug)]
+pub struct MailCatcher {
+    address: SocketAddr,
+    state: Arc<CatcherState>,
+    server_handle: JoinHandle<()>,
+}
+
+impl MailCatcher {
+    /// Starts the catcher on a random local port.
+    ///
+    /// This must be called from within a Tokio runtime.
+    pub fn start() -> Result<Self, Error> {
+        let listener =
+            StdTcpListener::bind("127.0.0.1:0").context("Failed to bind port for mail catcher")?;
+        listener
+            .set_nonblocking(true)
+            .context("Failed to set mail catcher port to non-blocking")?;
+        let address = listener
+            .local_addr()
+            .context("Failed to read address of mail catcher")?;
+        let listener = TcpListener::from_std(listener)
+            .context("Failed to start mail catcher, this must be called within a Tokio runtime")?;
+
+        let state = Arc::new(CatcherState::default());
+        let server_handle = tokio::spawn(run_smtp_server(listener, state.clone()));
+
+        Ok(Self {
+            address,
+            state,
+            server_handle,
+        })
+    }
+
+    /// The address of the SMTP server.
+    #[must_use]
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// The host of the SMTP server, for apps which take the host and port separately.
+    #[must_use]
+    pub fn host(&self) -> String {
+        self.address.ip().to_string()
+    }
+
+    /// The port of the SMTP server.
+    #[must_use]
+    pub fn port(&self) -> u16 {
+        self.address.port()
+    }
+
+    /// Returns all of the emails received so far, in the order they arrived.
+    #[must_use]
+    pub fn emails(&self) -> Vec<CapturedEmail> {
+        self.state
+            .emails
+            .lock()
+            .expect("Failed to lock captured emails, for reading emails")
+            .clone()
+    }
+
+    /// Removes all of the emails received so far.
+    pub fn clear(&self) {
+        self.state
+            .emails
+            .lock()
+            .expect("Failed to lock captured emails, for clearing emails")
+            .clear();
+        *self
+            .state
+            .num_awaited
+            .lock()
+            .expect("Failed to lock captured emails, for clearing emails") = 0;
+    }
+
+    /// Waits for the next email, returning it.
+    ///
+    /// Each call returns the email after the one returned before,
+    /// including emails which arrived before it was called.
+    ///
+    /// This will panic if no email arrives within the timeout.
+    pub async fn await_email(&self, timeout: Duration) -> CapturedEmail {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            // Created before checking, so emails arriving in between are not missed.
+            let notified = self.state.notify.notified();
+            if let Some(email) = self.take_next_email() {
+                return email;
+            }
+
+            if timeout_at(deadline, notified).await.is_err() {
+                let num_emails = self.emails().len();
+                panic!("Timed out after {timeout:?} waiting for an email, {num_emails} emails were received before");
+            }
+        }
+    }
+
+    fn take_next_email(&self) -> Option<CapturedEmail> {
+        let emails = self
+            .state
+            .emails
+            .lock()
+            .expect("Failed to lock captured emails, for awaiting email");
+        let mut num_awaited = self
+            .state
+            .num_awaited
+            .lock()
+            .expect("Failed to lock captured emails, for awaiting email");
+
+        let email = emails.get(*num_awaited)?.clone();
+        *num_awaited += 1;
+
+        Some(email)
+    }
+}
+
+impl Drop for MailCatcher {
+    fn drop(&mut self) {
+        self.server_handle.abort();
+    }
+}
+
+async fn run_smtp_server(listener: TcpListener, state: Arc<CatcherState>) {
+    while let Ok((stream, _)) = listener.accept().await {
+        tokio::spawn(run_smtp_session(stream, state.clone()));
+    }
+}
+
+/// Speaks just enough SMTP to receive mail from a client.
+/// Connection errors end the session quietly, as the client has gone.
+async fn run_smtp_session(stream: TcpStream, state: Arc<CatcherState>) {
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+    let mut envelope_from = String::new();
+    let mut envelope_recipients = Vec::new();
+    let mut line = Vec::new();
+
+    if writer
+        .write_all(b"220 axum-test mail catcher\r\n")
+        .await
+        .is_err()
+    {
+        return;
+    }
+
+    loop {
+        line.clear();
+        match reader.read_until(b'\n', &mut line).await {
+            Ok(0) | Err(_) => return,
+            Ok(_) => {}
+        }
+
+        let command = String::from_utf8_lossy(&line);
+        let command = command.trim_end();
+        let verb = command
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_ascii_uppercase();
+
+        let reply: &[u8] = match verb.as_str() {
+            "EHLO" => b"250-axum-test\r\n250-8BITMIME\r\n250 SMTPUTF8\r\n",
+            "HELO" | "NOOP" => b"250 OK\r\n",
+            "MAIL" => {
+                envelope_from = parse_smtp_address(command);
+                envelope_recipients.clear();
+                b"250 OK\r\n"
+            }
+            "RCPT" => {
+                envelope_recipients.push(parse_smtp_address(command));
+                b"250 OK\r\n"
+            }
+            "RSET" => {
+                envelope_from.clear();
+                envelope_recipients.clear();
+                b"250 OK\r\n"
+            }
+            "DATA" => {
+                if writer
+                    .write_all(b"354 End data with <CR><LF>.<CR><LF>\r\n")
+                    .await
+                    .is_err()
+                {
+                    return;
+                }
+                let Some(raw) = read_smtp_data(&mut reader).await else {
+                    return;
+                };
+
+                let email = CapturedEmail::parse(
+                    std::mem::take(&mut envelope_from),
+                    std::mem::take(&mut envelope_recipients),
+                    raw,
+                );
+                state
+                    .emails
+                    .lock()
+                    .expect("Failed to lock captured emails, for recording email")
+                    .push(email);
+                state.notify.notify_waiters();
+
+                b"250 OK\r\n"
+            }
+            "QUIT" => {
+                let _ = writer.write_all(b"221 Bye\r\n").await;
+                return;
+            }
+            _ => b"502 Command not implemented\r\n",
+        };
+
+        if writer.write_all(reply).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Reads the message sent after `DATA`, up to the line holding a single `.`,
+/// undoing the dot stuffing on lines starting with a `.`.
+async fn read_smtp_data<R>(reader: &mut R) -> Option<Bytes>
+where
+    R: AsyncBufReadExt + Unpin,
+{
+    let mut raw = Vec::new();
+    let mut line = Vec::new();
+
+    loop {
+        line.clear();
+        match reader.read_until(b'\n', &mut line).await {
+            Ok(0) | Err(_) => return None,
+            Ok(_) => {}
+        }
+
+        if line == b".\r\n" || line == b".\n" {
+            return Some(Bytes::from(raw));
+        }
+
+        let unstuffed = line.strip_prefix(b".").unwrap_or(&line);
+        raw.extend_from_slice(unstuffed);
+    }
+}
+
+/// Takes the address from a `MAIL FROM:<...>` or `RCPT TO:<...>` command.
+fn parse_smtp_address(command: &str) -> String {
+    let Some((_, argument)) = command.split_once(':') else {
+        return String::new();
+    };
+    let argument = argument.trim();
+
+    match argument.strip_prefix('<') {
+        Some(rest) => rest.split('>').next().unwrap_or_default().to_string(),
+        None => argument
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test_await_email {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+
+    const MESSAGE: &str = "From: App <app@example.com>\r\n\
+To: Joe <joe@example.com>\r\n\
+Subject: Verify your email\r\n\
+MIME-Version: 1.0\r\n\
+Content-Type: multipart/mixed; boundary=\"boundary\"\r\n\
+\r\n\
+--boundary\r\n\
+Content-Type: text/plain\r\n\
+\r\n\
+Click the link to verify.\r\n\
+.. and this line was dot stuffed.\r\n\
+--boundary\r\n\
+Content-Type: text/csv\r\n\
+Content-Disposition: attachment; filename=\"report.csv\"\r\n\
+Content-Transfer-Encoding: base64\r\n\
+\r\n\
+YSxiCjEsMgo=\r\n\
+--boundary--\r\n";
+
+    async fn send_email(address: SocketAddr, message: &str) {
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        let commands = [
+            "EHLO localhost\r\n",
+            "MAIL FROM:<app@example.com>\r\n",
+            "RCPT TO:<joe@example.com>\r\n",
+            "RCPT TO:<audit@example.com>\r\n",
+            "DATA\r\n",
+        ];
+        for command in commands {
+            stream.write_all(command.as_bytes()).await.unwrap();
+        }
+        stream.write_all(message.as_bytes()).await.unwrap();
+        stream.write_all(b".\r\nQUIT\r\n").await.unwrap();
+
+        let mut replies = String::new();
+        stream.read_to_string(&mut replies).await.unwrap();
+        assert!(replies.ends_with("221 Bye\r\n"), "{replies}");
+    }
+
+    #[tokio::test]
+    async fn it_should_capture_and_parse_emails() {
+        let mail = MailCatcher::start().unwrap();
+        send_email(mail.address(), MESSAGE).await;
+
+        let email = mail.await_email(Duration::from_secs(1)).await;
+
+        assert_eq!(email.envelope_from(), "app@example.com");
+        assert_eq!(
+            email.envelope_recipients(),
+            ["joe@example.com", "audit@example.com"]
+        );
+        assert_eq!(email.from(), Some("app@example.com"));
+        assert_eq!(email.to(), ["joe@example.com"]);
+        email.assert_subject_contains("Verify");
+        email.assert_recipient("audit@example.com");
+        email.assert_text_body_contains(". and this line was dot stuffed.");
+        email.assert_has_attachment("report.csv");
+
+        let attachment = email.maybe_attachment("report.csv").unwrap();
+        assert_eq!(attachment.content_type.as_deref(), Some("text/csv"));
+        assert_eq!(attachment.contents, "a,b\n1,2\n");
+    }
+
+    #[tokio::test]
+    async fn it_should_return_emails_in_order() {
+        let mail = MailCatcher::start().unwrap();
+        send_email(mail.address(), "Subject: First\r\n\r\nOne\r\n").await;
+        send_email(mail.address(), "Subject: Second\r\n\r\nTwo\r\n").await;
+
+        let first = mail.await_email(Duration::from_secs(1)).await;
+        let second = mail.await_email(Duration::from_secs(1)).await;
+
+        assert_eq!(first.subject(), Some("First"));
+        assert_eq!(second.subject(), Some("Second"));
+        assert_eq!(mail.emails().len(), 2);
+
+        mail.clear();
+        assert!(mail.emails().is_empty());
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "Timed out after 50ms waiting for an email")]
+    async fn it_should_panic_on_timeout() {
+        let mail = MailCatcher::start().unwrap();
+
+        let _ = mail.await_email(Duration::from_millis(50)).await;
+    }
+}
+
+#[cfg(test)]
+mod test_captured_email_assertions {
+    use super::*;
+
+    fn new_email() -> CapturedEmail {
+        let raw = "Subject: Welcome aboard\r\nTo: joe@example.com\r\n\r\nHello Joe\r\n";
+        CapturedEmail::parse(
+            "app@example.com".to_string(),
+            vec!["joe@example.com".to_string()],
+            Bytes::from_static(raw.as_bytes()),
+        )
+    }
+
+    #[test]
+    fn it_should_fail_on_different_subject() {
+        let error = new_email().check_subject_contains("Goodbye").unwrap_err();
+
+        assert!(error
+            .to_string()
+            .contains("Expected email subject to contain 'Goodbye', received 'Welcome aboard'"));
+    }
+
+    #[test]
+    fn it_should_fail_on_other_recipient() {
+        let error = new_email().check_recipient("sam@example.com").unwrap_err();
+
+        assert!(error
+            .to_string()
+            .contains("Expected email to be sent to 'sam@example.com'"));
+    }
+
+    #[test]
+    fn it_should_fail_on_missing_attachment() {
+        let error = new_email().check_has_attachment("report.csv").unwrap_err();
+
+        assert!(error
+            .to_string()
+            .contains("Expected email to have attachment 'report.csv', found attachments []"));
+    }
+}
+
+#[cfg(test)]
+mod test_parse_smtp_address {
+    use super::*;
+
+    #[test]
+    fn it_should_parse_addresses() {
+        assert_eq!(
+            parse_smtp_address("MAIL FROM:<app@example.com> SIZE=100"),
+            "app@example.com"
+        );
+        assert_eq!(
+            parse_smtp_address("RCPT TO: joe@example.com"),
+            "joe@example.com"
+        );
+        assert_eq!(parse_smtp_address("MAIL FROM:<>"), "");
+    }
+}
@@ -0,0 +1,105 @@
+use axum::extract::MatchedPath;
+use http::HeaderMap;
+use http::HeaderValue;
+use http::Request;
+use http::Response;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+use tower::Layer;
+use tower::Service;
+
+/// Carries the route pattern matched by Axum from the [`MatchedRouteLayer`] to the `TestRequest`,
+/// which removes it before the response is seen.
+const MATCHED_ROUTE_HEADER: &str = "x-axum-test-matched-route";
+
+/// A layer which records the route pattern Axum matched for each request,
+/// such as `/users/:id`.
+///
+/// Add it to your [`Router`](::axum::Router) using `Router::layer`,
+/// and the route is then available on the response using
+/// [`TestResponse::matched_route()`](crate::TestResponse::matched_route()).
+/// Requests handled by a fallback have no matched route.
+///
+/// This is for confirming a request reached the route intended,
+/// rather than a more general route or a fallback.
+///
+/// ```rust
+/// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+/// #
+/// use axum::Router;
+/// use axum::routing::get;
+/// use axum_test::MatchedRouteLayer;
+/// use axum_test::TestServer;
+///
+/// let app = Router::new()
+///     .route(&"/users/:id", get(|| async { "user" }))
+///     .route(&"/users/me", get(|| async { "me" }))
+///     .layer(MatchedRouteLayer);
+/// let server = TestServer::new(app)?;
+///
+/// server.get(&"/users/me")
+///     .await
+///     .assert_matched_route("/users/me");
+/// #
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MatchedRouteLayer;
+
+impl<S> Layer<S> for MatchedRouteLayer {
+    type Service = MatchedRouteService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MatchedRouteService { inner }
+    }
+}
+
+/// The service built by [`MatchedRouteLayer`].
+#[derive(Debug, Clone)]
+pub struct MatchedRouteService<S> {
+    inner: S,
+}
+
+impl<S, B, ResBody> Service<Request<B>> for MatchedRouteService<S>
+where
+    S: Service<Request<B>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+    S::Error: Send + 'static,
+    ResBody: Send + 'static,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<B>) -> Self::Future {
+        let maybe_header_value = request
+            .extensions()
+            .get::<MatchedPath>()
+            .and_then(|matched_path| HeaderValue::from_str(matched_path.as_str()).ok());
+        let future = self.inner.call(request);
+
+        Box::pin(async move {
+            let mut response = future.await?;
+            if let Some(header_value) = maybe_header_value {
+                response
+                    .headers_mut()
+                    .insert(MATCHED_ROUTE_HEADER, header_value);
+            }
+
+            Ok(response)
+        })
+    }
+}
+
+/// Removes the route added by the [`MatchedRouteLayer`] from the headers, returning it.
+pub(crate) fn take_matched_route(headers: &mut HeaderMap) -> Option<String> {
+    let header_value = headers.remove(MATCHED_ROUTE_HEADER)?;
+
+    header_value.to_str().ok().map(ToString::to_string)
+}
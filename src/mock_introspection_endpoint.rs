@@ -0,0 +1,219 @@
+use axum::extract::State;
+use axum::response::IntoResponse;
+use axum::response::Response;
+use axum::routing::post;
+use axum::Form;
+use axum::Json;
+use axum::Router;
+use http::StatusCode;
+use serde::Serialize;
+use serde_json::Map;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use crate::Error;
+use crate::TestServer;
+
+/// The path the [`MockIntrospectionEndpoint`] answers introspection requests on.
+pub const INTROSPECTION_PATH: &str = "/introspect";
+
+type TokenStore = Arc<Mutex<HashMap<String, Value>>>;
+
+/// A stand in for an OAuth 2.0 token introspection endpoint ([RFC 7662](https://www.rfc-editor.org/rfc/rfc7662)),
+/// for testing resource servers which validate opaque tokens.
+///
+/// Each token is configured with the claims returned for it.
+/// Unknown tokens are answered as inactive, with `{ "active": false }`,
+/// and requests missing a `token` are rejected with a `400 Bad Request`.
+///
+/// Tokens can be added and removed after the endpoint is running,
+/// as all clones share the same tokens.
+///
+/// ```rust
+/// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+/// #
+/// use axum_test::MockIntrospectionEndpoint;
+/// use serde_json::json;
+///
+/// let introspection = MockIntrospectionEndpoint::new();
+/// introspection.add_active_token("abc123", json!({ "sub": "user-1", "scope": "read" }));
+///
+/// let introspection_server = introspection.build_server()?;
+/// let introspection_url = introspection_server.server_url(&"/introspect")?;
+///
+/// // Configure your application to introspect tokens at `introspection_url` ...
+/// #
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MockIntrospectionEndpoint {
+    tokens: TokenStore,
+}
+
+impl MockIntrospectionEndpoint {
+    /// Creates an endpoint which knows no tokens.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a token which is answered as active, with the claims given.
+    ///
+    /// The claims must serialize to a Json object, and replace any set for the token before.
+    pub fn add_active_token<T, C>(&self, token: T, claims: C)
+    where
+        T: Into<String>,
+        C: Serialize,
+    {
+        let claims =
+            serde_json::to_value(claims).expect("Failed to serialize introspection claims to Json");
+        let Value::Object(mut claims) = claims else {
+            panic!("Expected introspection claims to be a Json object, received {claims}");
+        };
+        claims.insert("active".to_string(), Value::Bool(true));
+
+        self.insert_token(token.into(), Value::Object(claims));
+    }
+
+    /// Adds a token which is answered as inactive,
+    /// such as one which has expired or been revoked.
+    pub fn add_inactive_token<T>(&self, token: T)
+    where
+        T: Into<String>,
+    {
+        self.insert_token(token.into(), new_inactive_response());
+    }
+
+    /// Removes a token, so it is answered as unknown.
+    pub fn remove_token(&self, token: &str) {
+        self.tokens
+            .lock()
+            .expect("Failed to lock introspection tokens, for removing token")
+            .remove(token);
+    }
+
+    /// Returns the introspection response for the token given.
+    #[must_use]
+    pub fn introspect(&self, token: &str) -> Value {
+        self.tokens
+            .lock()
+            .expect("Failed to lock introspection tokens, for introspecting token")
+            .get(token)
+            .cloned()
+            .unwrap_or_else(new_inactive_response)
+    }
+
+    /// Returns a router answering introspection requests on [`INTROSPECTION_PATH`].
+    pub fn into_router(self) -> Router {
+        Router::new()
+            .route(INTROSPECTION_PATH, post(route_post_introspect))
+            .with_state(self)
+    }
+
+    /// Runs the endpoint on a real HTTP server, on a random local port.
+    ///
+    /// Use [`TestServer::server_url()`] with [`INTROSPECTION_PATH`]
+    /// to get the address for your application to call.
+    pub fn build_server(self) -> Result<TestServer, Error> {
+        TestServer::builder()
+            .http_transport()
+            .build(self.into_router())
+    }
+
+    fn insert_token(&self, token: String, response: Value) {
+        self.tokens
+            .lock()
+            .expect("Failed to lock introspection tokens, for adding token")
+            .insert(token, response);
+    }
+}
+
+fn new_inactive_response() -> Value {
+    let mut response = Map::new();
+    response.insert("active".to_string(), Value::Bool(false));
+    Value::Object(response)
+}
+
+async fn route_post_introspect(
+    State(endpoint): State<MockIntrospectionEndpoint>,
+    Form(params): Form<HashMap<String, String>>,
+) -> Response {
+    let Some(token) = params.get("token") else {
+        let error = serde_json::json!({ "error": "invalid_request" });
+        return (StatusCode::BAD_REQUEST, Json(error)).into_response();
+    };
+
+    Json(endpoint.introspect(token)).into_response()
+}
+
+#[cfg(test)]
+mod test_introspect {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn it_should_answer_active_tokens_with_claims() {
+        let endpoint = MockIntrospectionEndpoint::new();
+        endpoint.add_active_token("abc123", json!({ "sub": "user-1" }));
+
+        assert_eq!(
+            endpoint.introspect("abc123"),
+            json!({ "active": true, "sub": "user-1" })
+        );
+    }
+
+    #[test]
+    fn it_should_answer_inactive_and_unknown_tokens() {
+        let endpoint = MockIntrospectionEndpoint::new();
+        endpoint.add_active_token("abc123", json!({}));
+        endpoint.add_inactive_token("revoked");
+        endpoint.remove_token("abc123");
+
+        assert_eq!(endpoint.introspect("revoked"), json!({ "active": false }));
+        assert_eq!(endpoint.introspect("abc123"), json!({ "active": false }));
+        assert_eq!(endpoint.introspect("unknown"), json!({ "active": false }));
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected introspection claims to be a Json object")]
+    fn it_should_panic_on_non_object_claims() {
+        MockIntrospectionEndpoint::new().add_active_token("abc123", "user-1");
+    }
+}
+
+#[cfg(test)]
+mod test_build_server {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn it_should_answer_introspection_requests_over_http() {
+        let endpoint = MockIntrospectionEndpoint::new();
+        let server = endpoint.clone().build_server().unwrap();
+        assert!(server.server_address().is_some());
+
+        // Added after the server is running.
+        endpoint.add_active_token("abc123", json!({ "scope": "read" }));
+
+        server
+            .post(INTROSPECTION_PATH)
+            .form(&[("token", "abc123"), ("token_type_hint", "access_token")])
+            .await
+            .assert_json(&json!({ "active": true, "scope": "read" }));
+
+        server
+            .post(INTROSPECTION_PATH)
+            .form(&[("token", "other")])
+            .await
+            .assert_json(&json!({ "active": false }));
+
+        server
+            .post(INTROSPECTION_PATH)
+            .form(&[("token_type_hint", "access_token")])
+            .expect_failure()
+            .await
+            .assert_status_bad_request();
+    }
+}
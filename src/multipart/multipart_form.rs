@@ -1,13 +1,52 @@
+use anyhow::Context;
 use axum::body::Body as AxumBody;
+use bytes::Bytes;
+use http_body_util::BodyExt;
 use rust_multipart_rfc7578_2::client::multipart::Body as CommonMultipartBody;
+use rust_multipart_rfc7578_2::client::multipart::BoundaryGenerator;
 use rust_multipart_rfc7578_2::client::multipart::Form;
+use serde::Serialize;
+use std::cell::RefCell;
 use std::fmt::Display;
 use std::io::Cursor;
 
 use crate::multipart::Part;
 
+const CONTENT_TYPE_PREFIX: &str = "multipart/form-data; boundary=";
+
+/// The longest boundary allowed by [RFC 2046](https://www.rfc-editor.org/rfc/rfc2046#section-5.1.1).
+const MAX_BOUNDARY_LENGTH: usize = 70;
+
+thread_local! {
+    /// The boundary for the next `Form` built, as they only take a boundary from a `BoundaryGenerator`.
+    static NEXT_BOUNDARY: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Hands the boundary of a `MultipartForm` to the `Form` being built from it.
+struct NextBoundaryGenerator;
+
+impl BoundaryGenerator for NextBoundaryGenerator {
+    fn generate_boundary() -> String {
+        NEXT_BOUNDARY
+            .with(|next_boundary| next_boundary.borrow_mut().take())
+            .expect("Expected boundary to be set before building multipart form")
+    }
+}
+
+#[derive(Debug, Clone)]
+enum MultipartField {
+    Text { name: String, text: String },
+    Part { name: String, part: Part },
+}
+
+/// A multipart form to send, using [`TestRequest::multipart()`](crate::TestRequest::multipart()).
+///
+/// Parts are sent in the order they are added.
+/// The boundary between them is random, unless set using [`MultipartForm::with_boundary()`].
+#[derive(Debug, Clone)]
 pub struct MultipartForm {
-    inner: Form<'static>,
+    boundary: String,
+    fields: Vec<MultipartField>,
 }
 
 impl MultipartForm {
@@ -15,16 +54,108 @@ impl MultipartForm {
         Default::default()
     }
 
+    /// Sets the boundary placed between parts, replacing the randomly generated one.
+    ///
+    /// This makes the body sent byte for byte the same across test runs,
+    /// for comparing against snapshots, or computing a signature over it.
+    ///
+    /// The boundary must be 1 to 70 characters, using only those allowed by
+    /// [RFC 2046](https://www.rfc-editor.org/rfc/rfc2046#section-5.1.1).
+    /// This will panic if it is not.
+    ///
+    /// ```rust
+    /// use axum_test::multipart::MultipartForm;
+    ///
+    /// let form = MultipartForm::new()
+    ///     .with_boundary("X-TEST-BOUNDARY")
+    ///     .add_text("name", "Joe");
+    ///
+    /// assert_eq!(form.content_type(), "multipart/form-data; boundary=X-TEST-BOUNDARY");
+    /// ```
+    #[track_caller]
+    pub fn with_boundary<B>(mut self, boundary: B) -> Self
+    where
+        B: Display,
+    {
+        let boundary = boundary.to_string();
+        if !is_valid_boundary(&boundary) {
+            panic!("Invalid multipart boundary '{boundary}', it must be 1 to {MAX_BOUNDARY_LENGTH} characters of letters, digits, or '()+_,-./:=? (and not end with a space)");
+        }
+
+        self.boundary = boundary;
+        self
+    }
+
+    /// Returns the boundary placed between parts.
+    #[must_use]
+    pub fn boundary(&self) -> &str {
+        &self.boundary
+    }
+
     /// Creates a text part, and adds it to be sent.
     pub fn add_text<N, T>(mut self, name: N, text: T) -> Self
     where
         N: Display,
         T: ToString,
     {
-        self.inner.add_text(name, text.to_string());
+        self.fields.push(MultipartField::Text {
+            name: name.to_string(),
+            text: text.to_string(),
+        });
         self
     }
 
+    /// Creates a text part for each of the values given, all using the same name, and adds them to be sent.
+    ///
+    /// The name is used as is, so include any array notation the server expects, such as `tags[]`.
+    ///
+    /// ```rust
+    /// use axum_test::multipart::MultipartForm;
+    ///
+    /// let form = MultipartForm::new()
+    ///     .add_array("tags[]", ["rust", "axum"]);
+    /// ```
+    pub fn add_array<N, I, T>(mut self, name: N, values: I) -> Self
+    where
+        N: Display,
+        I: IntoIterator<Item = T>,
+        T: ToString,
+    {
+        let name = name.to_string();
+        for value in values {
+            self = self.add_text(&name, value);
+        }
+
+        self
+    }
+
+    /// Serializes the value given as Json, and adds it as a part with the content type `application/json`.
+    ///
+    /// This is for sending structured metadata alongside files.
+    ///
+    /// ```rust
+    /// use axum_test::multipart::MultipartForm;
+    /// use axum_test::multipart::Part;
+    /// use serde_json::json;
+    ///
+    /// let form = MultipartForm::new()
+    ///     .add_json("metadata", &json!({ "title": "Cat photo" }))
+    ///     .add_part("file", Part::bytes(b"...".as_slice()).file_name("cat.png"));
+    /// ```
+    #[track_caller]
+    pub fn add_json<N, J>(self, name: N, value: &J) -> Self
+    where
+        N: Display,
+        J: ?Sized + Serialize,
+    {
+        let json_bytes = serde_json::to_vec(value)
+            .with_context(|| format!("Failed to serialize multipart part '{name}' as Json"))
+            .unwrap();
+        let part = Part::bytes(json_bytes).mime_type(mime::APPLICATION_JSON);
+
+        self.add_part(name, part)
+    }
+
     /// Adds a new section to this multipart form to be sent.
     ///
     /// See [`Part`](crate::multipart::Part).
@@ -32,30 +163,224 @@ impl MultipartForm {
     where
         N: Display,
     {
-        let reader = Cursor::new(part.bytes);
-        self.inner
-            .add_reader_2(name, reader, part.file_name, Some(part.mime_type));
-
+        self.fields.push(MultipartField::Part {
+            name: name.to_string(),
+            part,
+        });
         self
     }
 
     /// Returns the content type this form will use when it is sent.
     pub fn content_type(&self) -> String {
-        self.inner.content_type()
+        format!("{CONTENT_TYPE_PREFIX}{}", self.boundary)
+    }
+
+    /// Returns the body this form will send, as bytes.
+    ///
+    /// This is for computing signatures over the body,
+    /// or comparing it against a snapshot, when used with [`MultipartForm::with_boundary()`].
+    pub async fn to_bytes(&self) -> Bytes {
+        let body: AxumBody = self.clone().into();
+
+        body.collect()
+            .await
+            .context("Failed to build multipart form body")
+            .unwrap()
+            .to_bytes()
+    }
+
+    fn into_form(self) -> Form<'static> {
+        NEXT_BOUNDARY.with(|next_boundary| *next_boundary.borrow_mut() = Some(self.boundary));
+        let mut form = Form::new::<NextBoundaryGenerator>();
+
+        for field in self.fields {
+            match field {
+                MultipartField::Text { name, text } => form.add_text(name, text),
+                MultipartField::Part { name, part } => {
+                    let reader = Cursor::new(part.bytes);
+                    form.add_reader_2(name, reader, part.file_name, Some(part.mime_type));
+                }
+            }
+        }
+
+        form
     }
 }
 
 impl Default for MultipartForm {
     fn default() -> Self {
+        let random_content_type = Form::default().content_type();
+        let boundary = random_content_type
+            .strip_prefix(CONTENT_TYPE_PREFIX)
+            .unwrap_or(&random_content_type)
+            .to_string();
+
         Self {
-            inner: Default::default(),
+            boundary,
+            fields: Vec::new(),
         }
     }
 }
 
 impl From<MultipartForm> for AxumBody {
     fn from(multipart: MultipartForm) -> Self {
-        let inner_body: CommonMultipartBody = multipart.inner.into();
+        let inner_body: CommonMultipartBody = multipart.into_form().into();
         AxumBody::from_stream(inner_body)
     }
 }
+
+fn is_valid_boundary(boundary: &str) -> bool {
+    let is_valid_char = |c: char| c.is_ascii_alphanumeric() || "'()+_,-./:=? ".contains(c);
+
+    !boundary.is_empty()
+        && boundary.len() <= MAX_BOUNDARY_LENGTH
+        && !boundary.ends_with(' ')
+        && boundary.chars().all(is_valid_char)
+}
+
+#[cfg(test)]
+mod test_with_boundary {
+    use super::*;
+
+    #[tokio::test]
+    async fn it_should_send_exact_bytes_with_boundary_given() {
+        let form = MultipartForm::new()
+            .with_boundary("X-TEST-BOUNDARY")
+            .add_text("name", "Joe")
+            .add_part(
+                "file",
+                Part::bytes(b"hello".as_slice())
+                    .file_name("hello.txt")
+                    .mime_type("text/plain"),
+            );
+
+        let bytes = form.to_bytes().await;
+
+        assert_eq!(
+            String::from_utf8_lossy(&bytes),
+            "--X-TEST-BOUNDARY\r\n\
+            content-type: text/plain\r\n\
+            content-disposition: form-data; name=\"name\"\r\n\
+            \r\n\
+            Joe\r\n\
+            --X-TEST-BOUNDARY\r\n\
+            content-type: text/plain\r\n\
+            content-disposition: form-data; name=\"file\"; filename=\"hello.txt\"\r\n\
+            \r\n\
+            hello\r\n\
+            --X-TEST-BOUNDARY--\r\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn it_should_send_the_same_bytes_every_time() {
+        let build_form = || {
+            MultipartForm::new()
+                .with_boundary("X-TEST-BOUNDARY")
+                .add_text("b", "2")
+                .add_text("a", "1")
+        };
+
+        assert_eq!(build_form().to_bytes().await, build_form().to_bytes().await);
+    }
+
+    #[test]
+    fn it_should_use_boundary_in_content_type() {
+        let form = MultipartForm::new().with_boundary("X-TEST-BOUNDARY");
+
+        assert_eq!(
+            form.content_type(),
+            "multipart/form-data; boundary=X-TEST-BOUNDARY"
+        );
+    }
+
+    #[test]
+    fn it_should_generate_a_random_boundary_by_default() {
+        let form = MultipartForm::new();
+
+        assert!(!form.boundary().is_empty());
+        assert_eq!(
+            form.content_type(),
+            format!("multipart/form-data; boundary={}", form.boundary())
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn it_should_panic_when_boundary_is_empty() {
+        let _ = MultipartForm::new().with_boundary("");
+    }
+
+    #[test]
+    #[should_panic]
+    fn it_should_panic_when_boundary_has_invalid_characters() {
+        let _ = MultipartForm::new().with_boundary("X-TEST\r\nBOUNDARY");
+    }
+}
+
+#[cfg(test)]
+mod test_add_array {
+    use super::*;
+
+    #[tokio::test]
+    async fn it_should_add_a_text_part_for_each_value() {
+        let form = MultipartForm::new()
+            .with_boundary("X-TEST-BOUNDARY")
+            .add_array("tags[]", ["a", "b"]);
+
+        let bytes = form.to_bytes().await;
+
+        assert_eq!(
+            String::from_utf8_lossy(&bytes),
+            "--X-TEST-BOUNDARY\r\n\
+            content-type: text/plain\r\n\
+            content-disposition: form-data; name=\"tags[]\"\r\n\
+            \r\n\
+            a\r\n\
+            --X-TEST-BOUNDARY\r\n\
+            content-type: text/plain\r\n\
+            content-disposition: form-data; name=\"tags[]\"\r\n\
+            \r\n\
+            b\r\n\
+            --X-TEST-BOUNDARY--\r\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn it_should_add_nothing_for_no_values() {
+        let form = MultipartForm::new()
+            .with_boundary("X-TEST-BOUNDARY")
+            .add_array("tags[]", Vec::<String>::new());
+
+        let expected = MultipartForm::new()
+            .with_boundary("X-TEST-BOUNDARY")
+            .to_bytes()
+            .await;
+        assert_eq!(form.to_bytes().await, expected);
+    }
+}
+
+#[cfg(test)]
+mod test_add_json {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn it_should_add_json_part_with_json_content_type() {
+        let form = MultipartForm::new()
+            .with_boundary("X-TEST-BOUNDARY")
+            .add_json("metadata", &json!({ "title": "Cat" }));
+
+        let bytes = form.to_bytes().await;
+
+        assert_eq!(
+            String::from_utf8_lossy(&bytes),
+            "--X-TEST-BOUNDARY\r\n\
+            content-type: application/json\r\n\
+            content-disposition: form-data; name=\"metadata\"\r\n\
+            \r\n\
+            {\"title\":\"Cat\"}\r\n\
+            --X-TEST-BOUNDARY--\r\n"
+        );
+    }
+}
@@ -9,6 +9,7 @@ use std::fmt::Display;
 /// Use [`Part::text()`](crate::multipart::Part::text()) and [`Part::bytes()`](crate::multipart::Part::bytes()) for creating new instances.
 /// Then attach them to a `MultipartForm` using [`MultipartForm::add_part()`](crate::multipart::MultipartForm::add_part()).
 ///
+#[derive(Debug, Clone)]
 pub struct Part {
     pub(crate) bytes: Bytes,
     pub(crate) file_name: Option<String>,
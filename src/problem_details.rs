@@ -0,0 +1,144 @@
+use anyhow::anyhow;
+use anyhow::Result;
+use serde_json::Map;
+use serde_json::Value;
+
+/// The default problem type, used when a problem has no `type` member.
+pub const ABOUT_BLANK_PROBLEM_TYPE: &str = "about:blank";
+
+/// An error response following [RFC 7807](https://www.rfc-editor.org/rfc/rfc7807),
+/// usually sent with the content type `application/problem+json`.
+///
+/// This is returned from [`TestResponse::problem_details()`](crate::TestResponse::problem_details()).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProblemDetails {
+    /// The URI identifying the type of problem,
+    /// which is `about:blank` when the body does not give one.
+    pub problem_type: String,
+
+    /// A short summary of the type of problem.
+    pub title: Option<String>,
+
+    /// The HTTP status code the server gave for this occurrence of the problem.
+    pub status: Option<u16>,
+
+    /// An explanation specific to this occurrence of the problem.
+    pub detail: Option<String>,
+
+    /// A URI identifying this occurrence of the problem.
+    pub instance: Option<String>,
+
+    /// All other members of the problem, such as `balance` in an out-of-credit problem.
+    pub extensions: Map<String, Value>,
+}
+
+impl ProblemDetails {
+    /// Reads the problem from a Json body.
+    ///
+    /// This returns an error if the body is not a Json object,
+    /// or the standard members have the wrong types.
+    pub fn from_json(body: Value) -> Result<Self> {
+        let Value::Object(mut extensions) = body else {
+            return Err(anyhow!(
+                "Expected problem details to be a Json object, received {body}"
+            ));
+        };
+
+        let problem_type = take_string(&mut extensions, "type")?
+            .unwrap_or_else(|| ABOUT_BLANK_PROBLEM_TYPE.to_string());
+        let title = take_string(&mut extensions, "title")?;
+        let detail = take_string(&mut extensions, "detail")?;
+        let instance = take_string(&mut extensions, "instance")?;
+        let status = match extensions.remove("status") {
+            None | Some(Value::Null) => None,
+            Some(status) => {
+                let maybe_status = status
+                    .as_u64()
+                    .and_then(|status| u16::try_from(status).ok());
+                let status = maybe_status.ok_or_else(|| {
+                    anyhow!(
+                        "Expected problem member 'status' to be a status code, received {status}"
+                    )
+                })?;
+
+                Some(status)
+            }
+        };
+
+        Ok(Self {
+            problem_type,
+            title,
+            status,
+            detail,
+            instance,
+            extensions,
+        })
+    }
+}
+
+fn take_string(members: &mut Map<String, Value>, name: &str) -> Result<Option<String>> {
+    match members.remove(name) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(value)) => Ok(Some(value)),
+        Some(value) => Err(anyhow!(
+            "Expected problem member '{name}' to be a string, received {value}"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test_from_json {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn it_should_read_standard_members_and_extensions() {
+        let problem = ProblemDetails::from_json(json!({
+            "type": "https://example.com/errors/out-of-credit",
+            "title": "You do not have enough credit.",
+            "status": 403,
+            "detail": "Your current balance is 30, but that costs 50.",
+            "instance": "/account/12345/msgs/abc",
+            "balance": 30,
+        }))
+        .unwrap();
+
+        assert_eq!(
+            problem.problem_type,
+            "https://example.com/errors/out-of-credit"
+        );
+        assert_eq!(
+            problem.title.as_deref(),
+            Some("You do not have enough credit.")
+        );
+        assert_eq!(problem.status, Some(403));
+        assert_eq!(problem.instance.as_deref(), Some("/account/12345/msgs/abc"));
+        assert_eq!(
+            problem.extensions,
+            json!({ "balance": 30 }).as_object().cloned().unwrap()
+        );
+    }
+
+    #[test]
+    fn it_should_default_type_to_about_blank() {
+        let problem = ProblemDetails::from_json(json!({ "status": 404 })).unwrap();
+
+        assert_eq!(problem.problem_type, ABOUT_BLANK_PROBLEM_TYPE);
+        assert_eq!(problem.title, None);
+    }
+
+    #[test]
+    fn it_should_reject_wrong_types() {
+        let error = ProblemDetails::from_json(json!({ "title": 123 })).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("Expected problem member 'title' to be a string, received 123"));
+
+        let error = ProblemDetails::from_json(json!({ "status": "403" })).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("Expected problem member 'status' to be a status code"));
+
+        assert!(ProblemDetails::from_json(json!([])).is_err());
+    }
+}
@@ -0,0 +1,165 @@
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use crate::internals::check;
+use crate::internals::OrPanic;
+use crate::AssertionError;
+use crate::InstrumentationProbe;
+
+/// An [`InstrumentationProbe`] counting the queries your application runs for each request.
+///
+/// The counter is given to your application, and incremented for each query.
+/// Such as from a handler, a database wrapper,
+/// or a `tracing` layer watching for query events from sqlx.
+/// The count for each request is read using `response.probe::<QueryCount>()`.
+///
+/// This is useful for catching N+1 query regressions in integration tests.
+///
+/// ```rust
+/// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+/// #
+/// use axum::Extension;
+/// use axum::Router;
+/// use axum::routing::get;
+/// use axum_test::QueryCount;
+/// use axum_test::QueryCounter;
+/// use axum_test::TestServer;
+///
+/// async fn route_get_users(Extension(queries): Extension<QueryCounter>) {
+///     queries.increment();
+/// }
+///
+/// let queries = QueryCounter::new();
+/// let app = Router::new()
+///     .route(&"/users", get(route_get_users))
+///     .layer(Extension(queries.clone()));
+///
+/// let server = TestServer::builder()
+///     .add_probe(queries)
+///     .build(app)?;
+///
+/// let response = server.get(&"/users").await;
+/// response.probe::<QueryCount>().assert_query_count_at_most(5);
+/// #
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct QueryCounter {
+    total: Arc<AtomicUsize>,
+    total_before_request: Arc<AtomicUsize>,
+}
+
+impl QueryCounter {
+    /// Creates a new counter, starting at zero.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a query being run.
+    pub fn increment(&self) {
+        self.total.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Returns the number of queries recorded, across all requests.
+    #[must_use]
+    pub fn total(&self) -> usize {
+        self.total.load(Ordering::SeqCst)
+    }
+}
+
+impl InstrumentationProbe for QueryCounter {
+    type Reading = QueryCount;
+
+    fn before_request(&self) {
+        self.total_before_request
+            .store(self.total(), Ordering::SeqCst);
+    }
+
+    fn after_request(&self) -> QueryCount {
+        let total_before_request = self.total_before_request.load(Ordering::SeqCst);
+
+        QueryCount {
+            count: self.total().saturating_sub(total_before_request),
+        }
+    }
+}
+
+/// The number of queries run for a request, as counted by a [`QueryCounter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueryCount {
+    count: usize,
+}
+
+impl QueryCount {
+    /// Returns the number of queries run.
+    #[must_use]
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Asserts exactly the number of queries given were run.
+    #[track_caller]
+    pub fn assert_query_count(&self, expected_count: usize) {
+        self.check_query_count(expected_count).or_panic()
+    }
+
+    /// Checks exactly the number of queries given were run.
+    ///
+    /// This is the non-panicking version of [`QueryCount::assert_query_count()`].
+    pub fn check_query_count(&self, expected_count: usize) -> Result<(), AssertionError> {
+        let count = self.count;
+
+        check(
+            count == expected_count,
+            format_args!("Expected {expected_count} queries to be run, received {count}"),
+        )
+    }
+
+    /// Asserts no more than the number of queries given were run.
+    #[track_caller]
+    pub fn assert_query_count_at_most(&self, max_count: usize) {
+        self.check_query_count_at_most(max_count).or_panic()
+    }
+
+    /// Checks no more than the number of queries given were run.
+    ///
+    /// This is the non-panicking version of [`QueryCount::assert_query_count_at_most()`].
+    pub fn check_query_count_at_most(&self, max_count: usize) -> Result<(), AssertionError> {
+        let count = self.count;
+
+        check(
+            count <= max_count,
+            format_args!("Expected at most {max_count} queries to be run, received {count}"),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test_query_count {
+    use super::*;
+
+    #[test]
+    fn it_should_count_queries_since_before_request() {
+        let queries = QueryCounter::new();
+        queries.increment();
+
+        queries.before_request();
+        queries.increment();
+        queries.increment();
+
+        let query_count = queries.after_request();
+        assert_eq!(query_count.count(), 2);
+        assert_eq!(queries.total(), 3);
+    }
+
+    #[test]
+    fn it_should_check_at_most() {
+        let query_count = QueryCount { count: 3 };
+
+        query_count.assert_query_count_at_most(3);
+        assert!(query_count.check_query_count_at_most(2).is_err());
+        assert!(query_count.check_query_count(2).is_err());
+    }
+}
@@ -0,0 +1,514 @@
+use bytes::Bytes;
+use http::header;
+use http::HeaderMap;
+use http::Method;
+use http::StatusCode;
+use serde_json::Value;
+use std::fmt::Write;
+
+use crate::internals::content_type_essence;
+use crate::internals::encode_hex;
+use crate::internals::DataMask;
+use crate::TestResponse;
+
+/// The value written in place of masked headers and Json values, in generated test code.
+const MASKED_PLACEHOLDER: &str = "<masked>";
+
+/// The comment written before Json which has had values masked, in generated test code.
+const MASKED_JSON_TODO: &str = "// TODO: replace the masked values in this Json";
+
+/// A request sent by a [`TestServer`](crate::TestServer), and the response it received.
+///
+/// These are recorded when the server is built with
+/// [`TestServerBuilder::record_exchanges()`](crate::TestServerBuilder::record_exchanges()),
+/// and are retrieved using [`TestServer::recorded_exchanges()`](crate::TestServer::recorded_exchanges()).
+#[derive(Debug, Clone)]
+pub struct RecordedExchange {
+    method: Method,
+    path_and_query: String,
+    request_headers: HeaderMap,
+    maybe_request_body: Option<Bytes>,
+    status_code: StatusCode,
+    maybe_response_content_type: Option<String>,
+    response_body: Bytes,
+    data_mask: DataMask,
+}
+
+impl RecordedExchange {
+    pub(crate) fn from_response(response: &TestResponse) -> Self {
+        let request_url = response.request_url();
+        let path_and_query = match request_url.query() {
+            Some(query) => format!("{}?{query}", request_url.path()),
+            None => request_url.path().to_string(),
+        };
+
+        Self {
+            method: response.request_method(),
+            path_and_query,
+            request_headers: response.request_headers().clone(),
+            maybe_request_body: response.request_body_bytes().cloned(),
+            status_code: response.status_code(),
+            maybe_response_content_type: response.maybe_content_type(),
+            response_body: response.as_bytes().clone(),
+            data_mask: response.data_mask().clone(),
+        }
+    }
+
+    /// The method of the request.
+    #[must_use]
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// The path of the request, including the query.
+    #[must_use]
+    pub fn path(&self) -> &str {
+        &self.path_and_query
+    }
+
+    /// The status code of the response.
+    #[must_use]
+    pub fn status_code(&self) -> StatusCode {
+        self.status_code
+    }
+
+    /// Returns Rust code sending this request, and asserting the response is the same,
+    /// for starting a regression test from the traffic seen.
+    ///
+    /// The code expects a `server` variable holding the [`TestServer`](crate::TestServer),
+    /// with `http::StatusCode` and `serde_json::json` imported.
+    ///
+    /// Headers and Json values masked on the `TestServer` are written as `"<masked>"`,
+    /// with a `// TODO` comment to replace them.
+    ///
+    /// ```text
+    /// let response = server
+    ///     .post("/todos")
+    ///     .json(&json!({
+    ///       "title": "Buy milk"
+    ///     }))
+    ///     .await;
+    /// response.assert_status(StatusCode::CREATED);
+    /// response.assert_json(&json!({
+    ///   "id": 1
+    /// }));
+    /// ```
+    #[must_use]
+    pub fn to_test_code(&self) -> String {
+        let mut code = "let response = server\n".to_string();
+        code.push_str(&format_request_method(&self.method, &self.path_and_query));
+
+        for (name, value) in &self.request_headers {
+            if *name == header::CONTENT_TYPE
+                || *name == header::CONTENT_LENGTH
+                || *name == header::HOST
+            {
+                continue;
+            }
+
+            if self.data_mask.is_masked_header(name) {
+                let _ = write!(
+                    code,
+                    "\n    .add_header({:?}, {MASKED_PLACEHOLDER:?}) // TODO: replace the masked value",
+                    name.as_str()
+                );
+            } else if let Ok(value) = value.to_str() {
+                let _ = write!(code, "\n    .add_header({:?}, {value:?})", name.as_str());
+            }
+        }
+
+        let maybe_request_content_type = self
+            .request_headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok());
+        code.push_str(&format_request_body(
+            self.maybe_request_body.as_ref(),
+            maybe_request_content_type,
+            &self.data_mask,
+        ));
+        code.push_str("\n    .await;\n");
+
+        let _ = writeln!(
+            code,
+            "response.assert_status({});",
+            format_status_code(self.status_code)
+        );
+        code.push_str(&format_response_body(
+            &self.response_body,
+            self.maybe_response_content_type.as_deref(),
+            &self.data_mask,
+        ));
+
+        code
+    }
+}
+
+/// The exchanges recorded by a [`TestServer`](crate::TestServer), in the order they were sent.
+///
+/// See [`RecordedExchange`] for more details.
+#[derive(Debug, Clone, Default)]
+pub struct RecordedExchanges {
+    exchanges: Vec<RecordedExchange>,
+}
+
+impl RecordedExchanges {
+    pub(crate) fn new(exchanges: Vec<RecordedExchange>) -> Self {
+        Self { exchanges }
+    }
+
+    /// The exchanges recorded, in the order they were sent.
+    #[must_use]
+    pub fn exchanges(&self) -> &[RecordedExchange] {
+        &self.exchanges
+    }
+
+    /// The number of exchanges recorded.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.exchanges.len()
+    }
+
+    /// Returns true if no exchanges have been recorded.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.exchanges.is_empty()
+    }
+
+    /// Returns Rust code for every exchange recorded, in order, separated by blank lines.
+    ///
+    /// See [`RecordedExchange::to_test_code()`] for more details.
+    #[must_use]
+    pub fn to_test_code(&self) -> String {
+        self.exchanges
+            .iter()
+            .map(RecordedExchange::to_test_code)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn format_request_method(method: &Method, path: &str) -> String {
+    match *method {
+        Method::GET => format!("    .get({path:?})"),
+        Method::POST => format!("    .post({path:?})"),
+        Method::PUT => format!("    .put({path:?})"),
+        Method::PATCH => format!("    .patch({path:?})"),
+        Method::DELETE => format!("    .delete({path:?})"),
+        Method::HEAD | Method::OPTIONS | Method::CONNECT | Method::TRACE => {
+            format!("    .method(http::Method::{method}, {path:?})")
+        }
+        _ => format!(
+            "    .method(http::Method::from_bytes(b{:?}).unwrap(), {path:?})",
+            method.as_str()
+        ),
+    }
+}
+
+fn format_request_body(
+    maybe_body: Option<&Bytes>,
+    maybe_content_type: Option<&str>,
+    data_mask: &DataMask,
+) -> String {
+    let Some(body) = maybe_body else {
+        return "\n    // The request body was over the recording limit, and so is missing."
+            .to_string();
+    };
+
+    let maybe_essence = maybe_content_type.map(content_type_essence);
+    let content_type_suffix = |default_content_type: &str| match maybe_content_type {
+        Some(content_type) if content_type == default_content_type => String::new(),
+        Some(content_type) => format!("\n    .content_type({content_type:?})"),
+        None => "\n    .no_content_type()".to_string(),
+    };
+
+    if body.is_empty() {
+        return maybe_content_type
+            .map(|content_type| format!("\n    .content_type({content_type:?})"))
+            .unwrap_or_default();
+    }
+
+    if maybe_essence.as_deref() == Some("application/json") {
+        if let Ok(json) = serde_json::from_slice::<Value>(body) {
+            let (json, is_masked) = mask_json(json, data_mask);
+            let todo = if is_masked {
+                format!("\n    {MASKED_JSON_TODO}")
+            } else {
+                String::new()
+            };
+            return format!(
+                "{todo}\n    .json(&json!({})){}",
+                indent(&format_json(&json), "    "),
+                content_type_suffix("application/json")
+            );
+        }
+    }
+
+    match std::str::from_utf8(body) {
+        Ok(text) => format!("\n    .text({text:?}){}", content_type_suffix("text/plain")),
+        Err(_) => {
+            let content_type = maybe_content_type
+                .map(|content_type| format!("\n    .content_type({content_type:?})"))
+                .unwrap_or_default();
+
+            format!("\n    .bytes_hex({:?}){content_type}", encode_hex(body))
+        }
+    }
+}
+
+fn format_response_body(
+    body: &Bytes,
+    maybe_content_type: Option<&str>,
+    data_mask: &DataMask,
+) -> String {
+    if body.is_empty() {
+        return String::new();
+    }
+
+    let maybe_essence = maybe_content_type.map(content_type_essence);
+    let is_json = maybe_essence
+        .as_deref()
+        .is_some_and(|essence| essence == "application/json" || essence.ends_with("+json"));
+    if is_json {
+        if let Ok(json) = serde_json::from_slice::<Value>(body) {
+            let (json, is_masked) = mask_json(json, data_mask);
+            let todo = if is_masked {
+                format!("{MASKED_JSON_TODO}\n")
+            } else {
+                String::new()
+            };
+            return format!(
+                "{todo}response.assert_json(&json!({}));\n",
+                format_json(&json)
+            );
+        }
+    }
+
+    match std::str::from_utf8(body) {
+        Ok(text) => format!("response.assert_text({text:?});\n"),
+        Err(_) => format!("response.assert_body_hex_eq({:?});\n", encode_hex(body)),
+    }
+}
+
+/// Returns the `StatusCode` constant for the status code given,
+/// or code building it from its number when it has no constant.
+fn format_status_code(status_code: StatusCode) -> String {
+    match status_code.canonical_reason() {
+        Some(reason) => {
+            let name = reason
+                .chars()
+                .filter_map(|c| match c {
+                    ' ' | '-' => Some('_'),
+                    c if c.is_ascii_alphanumeric() => Some(c.to_ascii_uppercase()),
+                    _ => None,
+                })
+                .collect::<String>();
+
+            format!("StatusCode::{name}")
+        }
+        None => format!("StatusCode::from_u16({}).unwrap()", status_code.as_u16()),
+    }
+}
+
+/// Replaces the values at the masked Json paths with a placeholder,
+/// returning if any values were masked.
+fn mask_json(json: Value, data_mask: &DataMask) -> (Value, bool) {
+    let mut masked_json = json.clone();
+    data_mask.mask_json_with(&mut masked_json, MASKED_PLACEHOLDER);
+    let is_masked = masked_json != json;
+
+    (masked_json, is_masked)
+}
+
+fn format_json(json: &Value) -> String {
+    serde_json::to_string_pretty(json).expect("Failed to reserialise serde_json::Value")
+}
+
+/// Indents every line after the first, so multi-line values line up with the code around them.
+fn indent(text: &str, indentation: &str) -> String {
+    text.replace('\n', &format!("\n{indentation}"))
+}
+
+#[cfg(test)]
+mod test_to_test_code {
+    use super::*;
+
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::routing::post;
+    use axum::Json;
+    use axum::Router;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .route(
+                "/todos",
+                post(|Json(todo): Json<Value>| async move {
+                    (
+                        StatusCode::CREATED,
+                        Json(json!({ "id": 1, "title": todo["title"] })),
+                    )
+                }),
+            )
+            .route("/ping", get(|| async { "pong!" }))
+            .route("/empty", get(|| async { StatusCode::NO_CONTENT }))
+            .route("/bytes", get(|| async { vec![0_u8, 255] }));
+
+        TestServer::builder().record_exchanges().build(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_generate_json_request_and_assertions() {
+        let server = new_test_server();
+
+        let response = server
+            .post("/todos")
+            .add_header("x-request-id", "abc123")
+            .json(&json!({ "title": "Buy milk" }))
+            .await;
+
+        let expected = r#"let response = server
+    .post("/todos")
+    .add_header("x-request-id", "abc123")
+    .json(&json!({
+      "title": "Buy milk"
+    }))
+    .await;
+response.assert_status(StatusCode::CREATED);
+response.assert_json(&json!({
+  "id": 1,
+  "title": "Buy milk"
+}));
+"#;
+        assert_eq!(response.to_test_code(), expected);
+    }
+
+    #[tokio::test]
+    async fn it_should_generate_text_with_query() {
+        let server = new_test_server();
+
+        let response = server.get("/ping").add_query_param("a", "b").await;
+
+        let expected = r#"let response = server
+    .get("/ping?a=b")
+    .await;
+response.assert_status(StatusCode::OK);
+response.assert_text("pong!");
+"#;
+        assert_eq!(response.to_test_code(), expected);
+    }
+
+    #[tokio::test]
+    async fn it_should_skip_empty_response_bodies() {
+        let server = new_test_server();
+
+        let response = server.get("/empty").await;
+
+        let expected = r#"let response = server
+    .get("/empty")
+    .await;
+response.assert_status(StatusCode::NO_CONTENT);
+"#;
+        assert_eq!(response.to_test_code(), expected);
+    }
+
+    #[tokio::test]
+    async fn it_should_generate_hex_for_binary_bodies() {
+        let server = new_test_server();
+
+        let response = server
+            .get("/bytes")
+            .bytes(Bytes::from_static(&[1, 254]))
+            .await;
+
+        let expected = r#"let response = server
+    .get("/bytes")
+    .bytes_hex("01fe")
+    .await;
+response.assert_status(StatusCode::OK);
+response.assert_body_hex_eq("00ff");
+"#;
+        assert_eq!(response.to_test_code(), expected);
+    }
+
+    #[tokio::test]
+    async fn it_should_generate_code_for_all_recorded_exchanges() {
+        let server = new_test_server();
+
+        server.get("/ping").await;
+        server.get("/empty").await;
+
+        let exchanges = server.recorded_exchanges();
+        assert_eq!(exchanges.len(), 2);
+
+        let expected = r#"let response = server
+    .get("/ping")
+    .await;
+response.assert_status(StatusCode::OK);
+response.assert_text("pong!");
+
+let response = server
+    .get("/empty")
+    .await;
+response.assert_status(StatusCode::NO_CONTENT);
+"#;
+        assert_eq!(exchanges.to_test_code(), expected);
+    }
+
+    #[tokio::test]
+    async fn it_should_write_placeholders_for_masked_headers_and_json() {
+        let app = Router::new().route(
+            "/login",
+            post(|Json(login): Json<Value>| async move {
+                Json(json!({ "user": login["user"], "token": "my-secret-token" }))
+            }),
+        );
+        let server = TestServer::builder()
+            .mask_header("x-api-key")
+            .mask_json_path("$.password")
+            .mask_json_path("$.token")
+            .build(app)
+            .unwrap();
+
+        let response = server
+            .post("/login")
+            .add_header("x-api-key", "my-secret-key")
+            .json(&json!({ "user": "joe", "password": "hunter2" }))
+            .await;
+
+        let expected = r#"let response = server
+    .post("/login")
+    .add_header("x-api-key", "<masked>") // TODO: replace the masked value
+    // TODO: replace the masked values in this Json
+    .json(&json!({
+      "password": "<masked>",
+      "user": "joe"
+    }))
+    .await;
+response.assert_status(StatusCode::OK);
+// TODO: replace the masked values in this Json
+response.assert_json(&json!({
+  "token": "<masked>",
+  "user": "joe"
+}));
+"#;
+        assert_eq!(response.to_test_code(), expected);
+    }
+
+    #[test]
+    fn it_should_name_status_codes_by_their_constant() {
+        assert_eq!(
+            format_status_code(StatusCode::IM_A_TEAPOT),
+            "StatusCode::IM_A_TEAPOT"
+        );
+        assert_eq!(
+            format_status_code(StatusCode::NON_AUTHORITATIVE_INFORMATION),
+            "StatusCode::NON_AUTHORITATIVE_INFORMATION"
+        );
+        assert_eq!(
+            format_status_code(StatusCode::from_u16(218).unwrap()),
+            "StatusCode::from_u16(218).unwrap()"
+        );
+    }
+}
@@ -0,0 +1,264 @@
+use axum::handler::Handler;
+use axum::routing::on;
+use axum::routing::MethodFilter;
+use axum::Router;
+use http::Method;
+
+use crate::RouteInfo;
+
+/// A [`Router`] which records each route added to it,
+/// so the [`TestServer`](crate::TestServer) can list them using [`TestServer::routes()`](crate::TestServer::routes()).
+///
+/// Each route is added to the `Router` and to the list in the same call,
+/// so the two cannot drift apart.
+/// Routes added to the inner `Router` directly, such as within [`RecordingRouter::map_router()`],
+/// are not recorded.
+///
+/// ```rust
+/// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+/// #
+/// use axum::http::Method;
+/// use axum_test::RecordingRouter;
+/// use axum_test::RouteInfo;
+/// use axum_test::TestServer;
+///
+/// let app = RecordingRouter::new()
+///     .route(Method::GET, "/users", || async { "all users" })
+///     .route(Method::POST, "/users", || async { "created" });
+/// let server = TestServer::new(app)?;
+///
+/// assert_eq!(server.routes(), vec![
+///     RouteInfo { method: Method::GET, path_pattern: "/users".to_string() },
+///     RouteInfo { method: Method::POST, path_pattern: "/users".to_string() },
+/// ]);
+/// #
+/// # Ok(()) }
+/// ```
+#[derive(Debug)]
+#[must_use]
+pub struct RecordingRouter<S = ()> {
+    router: Router<S>,
+    routes: Vec<RouteInfo>,
+}
+
+impl<S> RecordingRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    /// Creates a new `RecordingRouter`, with no routes.
+    pub fn new() -> Self {
+        Self {
+            router: Router::new(),
+            routes: Vec::new(),
+        }
+    }
+
+    /// Adds a handler for the method and path given, and records it as a route.
+    /// The path uses Axum's syntax (i.e. `/users/:id`).
+    ///
+    /// This will panic if the method cannot be routed by Axum, or if Axum rejects the route.
+    #[track_caller]
+    pub fn route<H, T>(self, method: Method, path_pattern: &str, handler: H) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static,
+    {
+        let method_filter = MethodFilter::try_from(method.clone())
+            .unwrap_or_else(|_| panic!("Method {method} cannot be routed by Axum"));
+
+        let mut routes = self.routes;
+        routes.push(RouteInfo {
+            method,
+            path_pattern: path_pattern.to_string(),
+        });
+
+        Self {
+            router: self.router.route(path_pattern, on(method_filter, handler)),
+            routes,
+        }
+    }
+
+    /// Merges the routes of the `RecordingRouter` given into this one.
+    ///
+    /// See [`Router::merge()`] for more details.
+    #[track_caller]
+    pub fn merge(self, other: RecordingRouter<S>) -> Self {
+        let mut routes = self.routes;
+        routes.extend(other.routes);
+
+        Self {
+            router: self.router.merge(other.router),
+            routes,
+        }
+    }
+
+    /// Nests the `RecordingRouter` given under the path given,
+    /// recording its routes with the path as a prefix.
+    ///
+    /// See [`Router::nest()`] for more details.
+    #[track_caller]
+    pub fn nest(self, path: &str, other: RecordingRouter<S>) -> Self {
+        let prefix = path.trim_end_matches('/');
+        let nested_routes = other.routes.into_iter().map(|route| RouteInfo {
+            path_pattern: match route.path_pattern.as_str() {
+                "/" => prefix.to_string(),
+                path_pattern => format!("{prefix}{path_pattern}"),
+            },
+            method: route.method,
+        });
+
+        let mut routes = self.routes;
+        routes.extend(nested_routes);
+
+        Self {
+            router: self.router.nest(path, other.router),
+            routes,
+        }
+    }
+
+    /// Changes the inner `Router`, keeping the routes recorded so far.
+    ///
+    /// This is for everything else a `Router` supports, such as layers and fallbacks.
+    /// Routes added to the `Router` here are not recorded.
+    pub fn map_router<S2, F>(self, map: F) -> RecordingRouter<S2>
+    where
+        F: FnOnce(Router<S>) -> Router<S2>,
+    {
+        RecordingRouter {
+            router: map(self.router),
+            routes: self.routes,
+        }
+    }
+
+    /// Provides the state for the router, keeping the routes recorded.
+    ///
+    /// See [`Router::with_state()`] for more details.
+    pub fn with_state<S2>(self, state: S) -> RecordingRouter<S2> {
+        RecordingRouter {
+            router: self.router.with_state(state),
+            routes: self.routes,
+        }
+    }
+
+    /// Returns the routes recorded, in the order they were added.
+    #[must_use]
+    pub fn routes(&self) -> &[RouteInfo] {
+        &self.routes
+    }
+
+    /// Returns the inner `Router`, dropping the routes recorded.
+    pub fn into_router(self) -> Router<S> {
+        self.router
+    }
+}
+
+impl<S> Default for RecordingRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test_route {
+    use super::*;
+
+    use crate::TestServer;
+
+    #[tokio::test]
+    async fn it_should_record_each_route_added() {
+        let app: RecordingRouter = RecordingRouter::new()
+            .route(Method::GET, "/users", || async { "all users" })
+            .route(Method::GET, "/users/:id", || async { "one user" })
+            .route(Method::DELETE, "/users/:id", || async { "deleted" });
+
+        assert_eq!(
+            app.routes(),
+            &[
+                RouteInfo {
+                    method: Method::GET,
+                    path_pattern: "/users".to_string(),
+                },
+                RouteInfo {
+                    method: Method::GET,
+                    path_pattern: "/users/:id".to_string(),
+                },
+                RouteInfo {
+                    method: Method::DELETE,
+                    path_pattern: "/users/:id".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn it_should_serve_each_route_added() {
+        let app = RecordingRouter::new()
+            .route(Method::GET, "/users/:id", || async { "one user" })
+            .route(Method::DELETE, "/users/:id", || async { "deleted" });
+        let server = TestServer::new(app).unwrap();
+
+        server.get("/users/1").await.assert_text("one user");
+        server.delete("/users/1").await.assert_text("deleted");
+        server
+            .post("/users/1")
+            .expect_failure()
+            .await
+            .assert_status(http::StatusCode::METHOD_NOT_ALLOWED);
+    }
+}
+
+#[cfg(test)]
+mod test_nest {
+    use super::*;
+
+    use crate::TestServer;
+
+    #[tokio::test]
+    async fn it_should_record_routes_with_the_prefix() {
+        let users = RecordingRouter::new()
+            .route(Method::GET, "/", || async { "all users" })
+            .route(Method::GET, "/:id", || async { "one user" });
+        let app = RecordingRouter::new()
+            .route(Method::GET, "/ping", || async { "pong!" })
+            .nest("/users", users);
+
+        let paths = app
+            .routes()
+            .iter()
+            .map(|route| route.path_pattern.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, vec!["/ping", "/users", "/users/:id"]);
+
+        let server = TestServer::new(app).unwrap();
+        server.get("/users").await.assert_text("all users");
+        server.get("/users/1").await.assert_text("one user");
+    }
+}
+
+#[cfg(test)]
+mod test_with_state {
+    use super::*;
+
+    use axum::extract::State;
+
+    use crate::TestServer;
+
+    #[tokio::test]
+    async fn it_should_keep_routes_when_adding_state() {
+        let app = RecordingRouter::new()
+            .route(
+                Method::GET,
+                "/count",
+                |State(count): State<u32>| async move { format!("count is {count}") },
+            )
+            .with_state::<()>(123);
+
+        assert_eq!(app.routes().len(), 1);
+
+        let server = TestServer::new(app).unwrap();
+        server.get("/count").await.assert_text("count is 123");
+    }
+}
@@ -0,0 +1,200 @@
+use axum::body::Body;
+use http::HeaderName;
+use http::HeaderValue;
+use http::Method;
+use http::Request;
+use http::StatusCode;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::sync::Arc;
+
+use crate::internals::is_route_match;
+
+type RequestMatcherFn = dyn Fn(&Request<Body>) -> bool + Send + Sync;
+
+/// Picks which requests a [`RequestInterceptor`] applies to.
+///
+/// This can be built from an Axum route pattern (such as `/users/:id`),
+/// either by calling [`RequestMatcher::route()`], or by converting from a `&str`.
+///
+/// ```rust
+/// use axum_test::RequestMatcher;
+/// use http::Method;
+///
+/// let users = RequestMatcher::route("/users/:id");
+/// let create_user = RequestMatcher::method_route(Method::POST, "/users");
+/// let has_auth = RequestMatcher::new(|request| request.headers().contains_key("authorization"));
+/// ```
+#[derive(Clone)]
+pub struct RequestMatcher {
+    matcher: Arc<RequestMatcherFn>,
+}
+
+impl RequestMatcher {
+    /// Builds a matcher from a closure,
+    /// which returns true for requests it matches.
+    pub fn new<F>(matcher: F) -> Self
+    where
+        F: Fn(&Request<Body>) -> bool + Send + Sync + 'static,
+    {
+        Self {
+            matcher: Arc::new(matcher),
+        }
+    }
+
+    /// Matches all requests.
+    pub fn any() -> Self {
+        Self::new(|_| true)
+    }
+
+    /// Matches requests to paths matching the Axum route pattern given, for any method.
+    pub fn route(route: &str) -> Self {
+        let route = route.to_string();
+        Self::new(move |request| is_route_match(&route, request.uri().path()))
+    }
+
+    /// Matches requests with the method given,
+    /// to paths matching the Axum route pattern given.
+    pub fn method_route(method: Method, route: &str) -> Self {
+        let route = route.to_string();
+        Self::new(move |request| {
+            request.method() == method && is_route_match(&route, request.uri().path())
+        })
+    }
+
+    /// Returns true if the request given is matched.
+    pub fn matches(&self, request: &Request<Body>) -> bool {
+        (self.matcher)(request)
+    }
+}
+
+impl From<&str> for RequestMatcher {
+    fn from(route: &str) -> Self {
+        Self::route(route)
+    }
+}
+
+impl Debug for RequestMatcher {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("RequestMatcher").finish_non_exhaustive()
+    }
+}
+
+/// Two matchers are equal when they share the same function,
+/// such as when one is cloned from the other.
+impl PartialEq for RequestMatcher {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.matcher, &other.matcher)
+    }
+}
+
+impl Eq for RequestMatcher {}
+
+/// What a [`RequestInterceptor`] does to the requests it matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InterceptAction {
+    /// Fails the request with a connection error, as though the server could not be reached.
+    /// The request does not reach your application.
+    ConnectionError,
+
+    /// Returns an empty response with the status code given.
+    /// The request does not reach your application.
+    Respond(StatusCode),
+
+    /// Sets a header on the request, replacing any existing values.
+    SetHeader(HeaderName, HeaderValue),
+
+    /// Removes a header from the request.
+    RemoveHeader(HeaderName),
+}
+
+/// Intercepts requests before they reach your application,
+/// to inject faults or rewrite them.
+///
+/// These are added using [`TestServerBuilder::intercept()`](crate::TestServerBuilder::intercept()).
+/// Interceptors run in the order they were added,
+/// and an interceptor which fails or responds to a request stops those after it.
+///
+/// Intercepted requests are changed on the way to the server,
+/// so they work with both mock and HTTP transports,
+/// and requests which do not reach your application are not recorded in the metrics.
+///
+/// ```rust
+/// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+/// #
+/// use axum::Router;
+/// use axum::routing::get;
+/// use axum_test::InterceptAction;
+/// use axum_test::TestServer;
+///
+/// let app = Router::new()
+///     .route(&"/payments", get(|| async { "payments" }));
+///
+/// let server = TestServer::builder()
+///     .http_transport()
+///     .intercept("/payments", InterceptAction::ConnectionError)
+///     .build(app)?;
+///
+/// let result = server.get(&"/payments").try_send().await;
+/// assert!(result.is_err());
+/// #
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestInterceptor {
+    /// The requests this applies to.
+    pub matcher: RequestMatcher,
+
+    /// What happens to the requests matched.
+    pub action: InterceptAction,
+}
+
+impl RequestInterceptor {
+    /// Creates an interceptor, applying the action to requests matched.
+    pub fn new<M>(matcher: M, action: InterceptAction) -> Self
+    where
+        M: Into<RequestMatcher>,
+    {
+        Self {
+            matcher: matcher.into(),
+            action,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_request_matcher {
+    use super::*;
+
+    fn new_request(method: Method, path: &str) -> Request<Body> {
+        Request::builder()
+            .method(method)
+            .uri(path)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[test]
+    fn it_should_match_routes_for_any_method() {
+        let matcher = RequestMatcher::from("/users/:id");
+
+        assert!(matcher.matches(&new_request(Method::GET, "/users/1")));
+        assert!(matcher.matches(&new_request(Method::DELETE, "/users/2?force=true")));
+        assert!(!matcher.matches(&new_request(Method::GET, "/users")));
+    }
+
+    #[test]
+    fn it_should_match_method_and_route() {
+        let matcher = RequestMatcher::method_route(Method::POST, "/users");
+
+        assert!(matcher.matches(&new_request(Method::POST, "/users")));
+        assert!(!matcher.matches(&new_request(Method::GET, "/users")));
+    }
+
+    #[test]
+    fn it_should_match_everything_for_any() {
+        assert!(RequestMatcher::any().matches(&new_request(Method::PUT, "/anything")));
+    }
+}
@@ -0,0 +1,60 @@
+use reqwest::ClientBuilder;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::sync::Arc;
+
+type ReqwestConfigurerFn = dyn Fn(ClientBuilder) -> ClientBuilder + Send + Sync;
+
+/// Changes the Reqwest client used by a [`TestServer`](crate::TestServer),
+/// such as to set a proxy, timeouts, or custom TLS settings.
+///
+/// These are added using [`TestServerBuilder::configure_reqwest()`](crate::TestServerBuilder::configure_reqwest()),
+/// and are applied in order, after the defaults for the client are set.
+/// The defaults are to not follow redirects,
+/// and to store cookies when the server saves cookies.
+///
+/// ```rust
+/// use axum_test::ReqwestConfigurer;
+/// use std::time::Duration;
+///
+/// let configurer = ReqwestConfigurer::new(|builder| builder.timeout(Duration::from_secs(5)));
+/// ```
+#[derive(Clone)]
+pub struct ReqwestConfigurer {
+    configurer: Arc<ReqwestConfigurerFn>,
+}
+
+impl ReqwestConfigurer {
+    /// Builds a configurer from a closure,
+    /// which takes the client builder and returns it with changes applied.
+    pub fn new<F>(configurer: F) -> Self
+    where
+        F: Fn(ClientBuilder) -> ClientBuilder + Send + Sync + 'static,
+    {
+        Self {
+            configurer: Arc::new(configurer),
+        }
+    }
+
+    /// Applies this configurer to the client builder given.
+    pub fn configure(&self, builder: ClientBuilder) -> ClientBuilder {
+        (self.configurer)(builder)
+    }
+}
+
+impl Debug for ReqwestConfigurer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("ReqwestConfigurer").finish_non_exhaustive()
+    }
+}
+
+/// Two configurers are equal when they share the same function,
+/// such as when one is cloned from the other.
+impl PartialEq for ReqwestConfigurer {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.configurer, &other.configurer)
+    }
+}
+
+impl Eq for ReqwestConfigurer {}
@@ -0,0 +1,160 @@
+use http::StatusCode;
+
+use crate::internals::check;
+use crate::internals::OrPanic;
+use crate::AssertionError;
+use crate::TestResponse;
+
+/// The responses from sending the same request many times at a fixed interval,
+/// returned from [`TestServer::burst()`](crate::TestServer::burst()).
+///
+/// This is for testing rate limiting, where later requests are expected to be rejected.
+#[derive(Debug, Clone)]
+pub struct ResponseBurst {
+    responses: Vec<TestResponse>,
+}
+
+impl ResponseBurst {
+    pub(crate) fn new(responses: Vec<TestResponse>) -> Self {
+        Self { responses }
+    }
+
+    /// Returns the responses, in the order the requests were sent.
+    #[must_use]
+    pub fn responses(&self) -> &[TestResponse] {
+        &self.responses
+    }
+
+    /// Consumes this, returning the responses in the order the requests were sent.
+    #[must_use]
+    pub fn into_responses(self) -> Vec<TestResponse> {
+        self.responses
+    }
+
+    /// Returns the status code of each response, in the order the requests were sent.
+    #[must_use]
+    pub fn statuses(&self) -> Vec<StatusCode> {
+        self.responses
+            .iter()
+            .map(TestResponse::status_code)
+            .collect()
+    }
+
+    /// Asserts at least one response in the burst has the status code given.
+    /// Such as `429 Too Many Requests`, once a rate limit has been hit.
+    #[track_caller]
+    pub fn assert_statuses_eventually_contain<S>(&self, status: S)
+    where
+        S: TryInto<StatusCode>,
+        S::Error: ::std::fmt::Debug,
+    {
+        self.check_statuses_eventually_contain(status).or_panic()
+    }
+
+    /// Checks at least one response in the burst has the status code given.
+    ///
+    /// This is the non-panicking version of [`ResponseBurst::assert_statuses_eventually_contain()`].
+    pub fn check_statuses_eventually_contain<S>(&self, status: S) -> Result<(), AssertionError>
+    where
+        S: TryInto<StatusCode>,
+        S::Error: ::std::fmt::Debug,
+    {
+        let status = status
+            .try_into()
+            .expect("Failed to convert to a valid status code");
+        let statuses = self.statuses();
+        let received = statuses
+            .iter()
+            .map(StatusCode::as_u16)
+            .map(|status| status.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        check(
+            statuses.contains(&status),
+            format_args!(
+                "Expected a response with status {status} within a burst of {} requests, received statuses [{received}]",
+                statuses.len()
+            ),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test_assert_statuses_eventually_contain {
+    use crate::TestServer;
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use axum::Router;
+    use std::sync::atomic::AtomicU32;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn new_test_server(limit: u32) -> TestServer {
+        let count = Arc::new(AtomicU32::new(0));
+        let app = Router::new().route(
+            "/limited",
+            get(move || async move {
+                if count.fetch_add(1, Ordering::SeqCst) < limit {
+                    StatusCode::OK
+                } else {
+                    StatusCode::TOO_MANY_REQUESTS
+                }
+            }),
+        );
+
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_return_statuses_in_order() {
+        let server = new_test_server(2);
+
+        let burst = server.burst("/limited", 4, Duration::ZERO).await;
+
+        assert_eq!(
+            burst.statuses(),
+            vec![
+                StatusCode::OK,
+                StatusCode::OK,
+                StatusCode::TOO_MANY_REQUESTS,
+                StatusCode::TOO_MANY_REQUESTS,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_when_status_is_found() {
+        let server = new_test_server(2);
+
+        server
+            .burst("/limited", 4, Duration::ZERO)
+            .await
+            .assert_statuses_eventually_contain(429);
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_status_is_not_found() {
+        let server = new_test_server(10);
+
+        server
+            .burst("/limited", 4, Duration::ZERO)
+            .await
+            .assert_statuses_eventually_contain(StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn it_should_wait_the_interval_between_requests() {
+        let server = new_test_server(10);
+        let started_at = tokio::time::Instant::now();
+
+        server
+            .burst("/limited", 4, Duration::from_secs(10))
+            .await
+            .assert_statuses_eventually_contain(StatusCode::OK);
+
+        assert_eq!(started_at.elapsed(), Duration::from_secs(30));
+    }
+}
@@ -0,0 +1,114 @@
+use http::header;
+use http::StatusCode;
+
+use crate::internals::check;
+use crate::internals::OrPanic;
+use crate::AssertionError;
+use crate::TestResponse;
+
+/// The responses from sending the same request twice,
+/// returned from [`TestServer::get_twice()`](crate::TestServer::get_twice()).
+///
+/// This is for testing caching, where the second response
+/// is expected to be served from a cache.
+#[derive(Debug, Clone)]
+pub struct ResponsePair {
+    first: TestResponse,
+    second: TestResponse,
+}
+
+impl ResponsePair {
+    pub(crate) fn new(first: TestResponse, second: TestResponse) -> Self {
+        Self { first, second }
+    }
+
+    /// Returns the response to the first request.
+    #[must_use]
+    pub fn first(&self) -> &TestResponse {
+        &self.first
+    }
+
+    /// Returns the response to the second request.
+    #[must_use]
+    pub fn second(&self) -> &TestResponse {
+        &self.second
+    }
+
+    /// Consumes this, returning the first and second responses.
+    #[must_use]
+    pub fn into_responses(self) -> (TestResponse, TestResponse) {
+        (self.first, self.second)
+    }
+
+    /// Asserts the second response was served from a cache.
+    ///
+    /// This passes if the second response is any of:
+    ///
+    ///  * A `304 Not Modified`, from revalidating the `ETag` or `Last-Modified` of the first.
+    ///  * Carrying an `Age` header, which caches add to stored responses.
+    ///  * Carrying an `X-Cache` or `X-Cache-Status` header containing `HIT`.
+    ///
+    /// For caches using other headers, use [`ResponsePair::assert_second_is_cache_hit_by_header()`].
+    #[track_caller]
+    pub fn assert_second_is_cache_hit(&self) {
+        self.check_second_is_cache_hit().or_panic()
+    }
+
+    /// Checks the second response was served from a cache.
+    ///
+    /// This is the non-panicking version of [`ResponsePair::assert_second_is_cache_hit()`].
+    pub fn check_second_is_cache_hit(&self) -> Result<(), AssertionError> {
+        let second = &self.second;
+        let is_not_modified = second.status_code() == StatusCode::NOT_MODIFIED;
+        let has_age = second.maybe_header(header::AGE).is_some();
+        let has_hit_header = ["x-cache", "x-cache-status"]
+            .into_iter()
+            .any(|header_name| is_header_containing(second, header_name, "HIT"));
+        let debug_request_format = second.debug_request_format();
+
+        check(
+            is_not_modified || has_age || has_hit_header,
+            format_args!(
+                "Expected second response to be a cache hit, received status {} without an Age or X-Cache HIT header, for request {debug_request_format}",
+                second.status_code()
+            ),
+        )
+    }
+
+    /// Asserts the second response was served from a cache,
+    /// by it carrying the header given containing the value given (ignoring case).
+    /// Such as `("cf-cache-status", "HIT")`.
+    #[track_caller]
+    pub fn assert_second_is_cache_hit_by_header(&self, header_name: &str, hit_value: &str) {
+        self.check_second_is_cache_hit_by_header(header_name, hit_value)
+            .or_panic()
+    }
+
+    /// Checks the second response was served from a cache,
+    /// by it carrying the header given containing the value given (ignoring case).
+    ///
+    /// This is the non-panicking version of [`ResponsePair::assert_second_is_cache_hit_by_header()`].
+    pub fn check_second_is_cache_hit_by_header(
+        &self,
+        header_name: &str,
+        hit_value: &str,
+    ) -> Result<(), AssertionError> {
+        let debug_request_format = self.second.debug_request_format();
+
+        check(
+            is_header_containing(&self.second, header_name, hit_value),
+            format_args!(
+                "Expected second response to be a cache hit, with header '{header_name}' containing '{hit_value}', for request {debug_request_format}"
+            ),
+        )
+    }
+}
+
+fn is_header_containing(response: &TestResponse, header_name: &str, value: &str) -> bool {
+    let value = value.to_ascii_lowercase();
+
+    response
+        .maybe_header(header_name)
+        .and_then(|header_value| header_value.to_str().ok().map(str::to_ascii_lowercase))
+        .is_some_and(|header_value| header_value.contains(&value))
+}
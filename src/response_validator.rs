@@ -0,0 +1,84 @@
+use anyhow::Context;
+use anyhow::Result;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::sync::Arc;
+
+use crate::ErrorBodySchema;
+use crate::TestResponse;
+
+type ResponseValidatorFn = dyn Fn(&TestResponse) -> Result<()> + Send + Sync;
+
+/// A check run against every response received by a [`TestServer`](crate::TestServer).
+///
+/// These are added using [`TestServerBuilder::add_response_validator()`](crate::TestServerBuilder::add_response_validator()),
+/// and are run after each request, before the status code is checked against the expected state.
+/// If a validator returns an error, then the request will panic.
+///
+/// This is useful for enforcing invariants across all endpoints,
+/// such as a correlation header always being present.
+///
+/// ```rust
+/// use anyhow::ensure;
+/// use axum_test::ResponseValidator;
+/// use axum_test::TestResponse;
+///
+/// let validator = ResponseValidator::new(|response: &TestResponse| {
+///     ensure!(response.maybe_header("x-correlation-id").is_some(), "missing correlation id");
+///     Ok(())
+/// });
+/// ```
+#[derive(Clone)]
+pub struct ResponseValidator {
+    validator: Arc<ResponseValidatorFn>,
+}
+
+impl ResponseValidator {
+    /// Builds a validator from a closure,
+    /// which returns an error if the response given is invalid.
+    pub fn new<F>(validator: F) -> Self
+    where
+        F: Fn(&TestResponse) -> Result<()> + Send + Sync + 'static,
+    {
+        Self {
+            validator: Arc::new(validator),
+        }
+    }
+
+    /// Runs this validator against the response given.
+    pub fn validate(&self, response: &TestResponse) -> Result<()> {
+        (self.validator)(response)
+    }
+}
+
+impl From<ErrorBodySchema> for ResponseValidator {
+    fn from(schema: ErrorBodySchema) -> Self {
+        Self::new(move |response| {
+            let status_code = response.status_code();
+            if status_code.is_client_error() || status_code.is_server_error() {
+                schema
+                    .validate(response.as_bytes())
+                    .context("Error body did not match schema")?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+impl Debug for ResponseValidator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("ResponseValidator").finish_non_exhaustive()
+    }
+}
+
+/// Two validators are equal when they share the same function,
+/// such as when one is cloned from the other.
+impl PartialEq for ResponseValidator {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.validator, &other.validator)
+    }
+}
+
+impl Eq for ResponseValidator {}
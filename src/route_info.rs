@@ -0,0 +1,61 @@
+use http::Method;
+
+/// A route of the application a [`TestServer`](crate::TestServer) is running.
+///
+/// These are recorded by a [`RecordingRouter`](crate::RecordingRouter), or given by hand using [`TestServerBuilder::route()`](crate::TestServerBuilder::route()),
+/// and returned from [`TestServer::routes()`](crate::TestServer::routes()),
+/// for driving table tests over every route in an application.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteInfo {
+    /// The method the route was registered for.
+    pub method: Method,
+
+    /// The path the route was registered with, using Axum's syntax (i.e. `/users/:id`).
+    pub path_pattern: String,
+}
+
+impl RouteInfo {
+    /// Returns a path which matches this route,
+    /// with each `:param` and `*wildcard` replaced with `1`.
+    ///
+    /// i.e. `/users/:id/posts/:post_id` becomes `/users/1/posts/1`.
+    #[must_use]
+    pub fn example_path(&self) -> String {
+        self.path_pattern
+            .split('/')
+            .map(|segment| {
+                if segment.starts_with(':') || segment.starts_with('*') {
+                    "1"
+                } else {
+                    segment
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+#[cfg(test)]
+mod test_example_path {
+    use super::*;
+
+    #[test]
+    fn it_should_replace_params_and_wildcards() {
+        let route = RouteInfo {
+            method: Method::GET,
+            path_pattern: "/users/:id/files/*path".to_string(),
+        };
+
+        assert_eq!(route.example_path(), "/users/1/files/1");
+    }
+
+    #[test]
+    fn it_should_keep_static_paths() {
+        let route = RouteInfo {
+            method: Method::GET,
+            path_pattern: "/users".to_string(),
+        };
+
+        assert_eq!(route.example_path(), "/users");
+    }
+}
@@ -0,0 +1,44 @@
+use axum::routing::any;
+use axum::routing::MethodRouter;
+use std::time::Duration;
+use tokio::time::sleep;
+
+/// A route which waits for the duration given, before responding with `200 OK`.
+///
+/// This responds to all HTTP methods.
+///
+/// ```rust
+/// use axum::Router;
+/// use axum_test::routes::delay;
+/// use std::time::Duration;
+///
+/// let app: Router = Router::new()
+///     .route(&"/slow", delay(Duration::from_millis(500)));
+/// ```
+pub fn delay<S>(duration: Duration) -> MethodRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    any(move || async move {
+        sleep(duration).await;
+    })
+}
+
+#[cfg(test)]
+mod test_delay {
+    use super::*;
+    use crate::TestServer;
+    use axum::Router;
+    use std::time::Instant;
+
+    #[tokio::test]
+    async fn it_should_wait_before_responding() {
+        let app = Router::new().route("/slow", delay(Duration::from_millis(100)));
+        let server = TestServer::new(app).unwrap();
+
+        let start = Instant::now();
+        server.get("/slow").await.assert_status_ok();
+
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+}
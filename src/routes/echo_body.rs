@@ -0,0 +1,36 @@
+use axum::response::IntoResponse;
+use bytes::Bytes;
+use http::header;
+use http::HeaderMap;
+
+/// A handler which responds with the body it received.
+///
+/// The `Content-Type` of the request is also returned, if one was sent.
+pub async fn echo_body(headers: HeaderMap, body: Bytes) -> impl IntoResponse {
+    let mut response_headers = HeaderMap::new();
+    if let Some(content_type) = headers.get(header::CONTENT_TYPE) {
+        response_headers.insert(header::CONTENT_TYPE, content_type.clone());
+    }
+
+    (response_headers, body)
+}
+
+#[cfg(test)]
+mod test_echo_body {
+    use super::*;
+    use crate::TestServer;
+    use axum::routing::post;
+    use axum::Router;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn it_should_return_body_and_content_type_sent() {
+        let app = Router::new().route("/echo", post(echo_body));
+        let server = TestServer::new(app).unwrap();
+
+        let response = server.post("/echo").json(&json!({ "name": "Joe" })).await;
+
+        response.assert_json(&json!({ "name": "Joe" }));
+        response.assert_header(header::CONTENT_TYPE, "application/json");
+    }
+}
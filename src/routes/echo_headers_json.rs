@@ -0,0 +1,50 @@
+use axum::Json;
+use http::HeaderMap;
+use serde_json::Map;
+use serde_json::Value;
+
+/// A handler which responds with the headers it received, as a Json object.
+///
+/// Each header name maps to it's value as a string.
+/// Where a header was received multiple times, the values are joined with `", "`.
+pub async fn echo_headers_json(headers: HeaderMap) -> Json<Value> {
+    let mut headers_json = Map::new();
+
+    for name in headers.keys() {
+        let values: Vec<String> = headers
+            .get_all(name)
+            .iter()
+            .map(|value| String::from_utf8_lossy(value.as_bytes()).to_string())
+            .collect();
+
+        headers_json.insert(name.to_string(), Value::String(values.join(", ")));
+    }
+
+    Json(Value::Object(headers_json))
+}
+
+#[cfg(test)]
+mod test_echo_headers_json {
+    use super::*;
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn it_should_return_headers_sent_as_json() {
+        let app = Router::new().route("/headers", get(echo_headers_json));
+        let server = TestServer::new(app).unwrap();
+
+        server
+            .get("/headers")
+            .add_header("x-custom", "first")
+            .add_header("x-custom", "second")
+            .add_header("x-other", "value")
+            .await
+            .assert_json(&json!({
+                "x-custom": "first, second",
+                "x-other": "value",
+            }));
+    }
+}
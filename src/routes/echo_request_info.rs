@@ -0,0 +1,76 @@
+use axum::extract::ConnectInfo;
+use axum::Json;
+use http::Method;
+use http::Uri;
+use http::Version;
+use serde_json::json;
+use serde_json::Value;
+use std::net::SocketAddr;
+
+/// A handler which responds with details of the request it received, as Json.
+///
+/// This includes the `method`, `uri`, and HTTP `version`.
+/// The `peer_addr` is included when the service is run with connect info
+/// (see [`axum::Router::into_make_service_with_connect_info`]), otherwise it is `null`.
+///
+/// ```json
+/// {
+///     "method": "GET",
+///     "uri": "/info?filter=enabled",
+///     "version": "HTTP/1.1",
+///     "peer_addr": "127.0.0.1:1234"
+/// }
+/// ```
+pub async fn echo_request_info(
+    method: Method,
+    uri: Uri,
+    version: Version,
+    maybe_connect_info: Option<ConnectInfo<SocketAddr>>,
+) -> Json<Value> {
+    let peer_addr = maybe_connect_info.map(|ConnectInfo(addr)| addr.to_string());
+
+    Json(json!({
+        "method": method.as_str(),
+        "uri": uri.to_string(),
+        "version": format!("{version:?}"),
+        "peer_addr": peer_addr,
+    }))
+}
+
+#[cfg(test)]
+mod test_echo_request_info {
+    use super::*;
+    use crate::TestServer;
+    use axum::routing::any;
+    use axum::Router;
+
+    #[tokio::test]
+    async fn it_should_return_request_info() {
+        let app = Router::new().route("/info", any(echo_request_info));
+        let server = TestServer::new(app).unwrap();
+
+        server
+            .put("/info")
+            .add_query_param("filter", "enabled")
+            .await
+            .assert_json(&json!({
+                "method": "PUT",
+                "uri": "http://localhost/info?filter=enabled",
+                "version": "HTTP/1.1",
+                "peer_addr": null,
+            }));
+    }
+
+    #[tokio::test]
+    async fn it_should_return_peer_addr_when_using_connect_info() {
+        let app = Router::new()
+            .route("/info", any(echo_request_info))
+            .into_make_service_with_connect_info::<SocketAddr>();
+        let server = TestServer::new(app).unwrap();
+
+        let info = server.get("/info").await.json::<Value>();
+        let peer_addr = info["peer_addr"].as_str().unwrap();
+
+        assert!(peer_addr.starts_with("127.0.0.1:"));
+    }
+}
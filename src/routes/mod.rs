@@ -0,0 +1,53 @@
+//!
+//! Ready made handlers, for mounting onto a [`Router`](::axum::Router) when testing.
+//!
+//! These are useful when testing middleware and client behaviour,
+//! where what the handler does matters less than what it received.
+//!
+//! ```rust
+//! # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+//! #
+//! use axum::Router;
+//! use axum::routing::get;
+//! use axum::routing::post;
+//! use axum_test::TestServer;
+//! use axum_test::routes::echo_body;
+//! use axum_test::routes::echo_headers_json;
+//! use axum_test::routes::status;
+//! use http::StatusCode;
+//!
+//! let app = Router::new()
+//!     .route(&"/echo", post(echo_body))
+//!     .route(&"/headers", get(echo_headers_json))
+//!     .route(&"/teapot", status(StatusCode::IM_A_TEAPOT));
+//!
+//! let server = TestServer::new(app)?;
+//!
+//! server.post(&"/echo")
+//!     .text("hello!")
+//!     .await
+//!     .assert_text("hello!");
+//! #
+//! # Ok(()) }
+//! ```
+//!
+
+mod echo_body;
+pub use self::echo_body::*;
+
+mod echo_headers_json;
+pub use self::echo_headers_json::*;
+
+mod echo_request_info;
+pub use self::echo_request_info::*;
+
+#[cfg(feature = "multipart-echo")]
+mod multipart_echo;
+#[cfg(feature = "multipart-echo")]
+pub use self::multipart_echo::*;
+
+mod delay;
+pub use self::delay::*;
+
+mod status;
+pub use self::status::*;
@@ -0,0 +1,229 @@
+use crate::internals::check;
+use crate::internals::OrPanic;
+use crate::AssertionError;
+use axum::extract::multipart::MultipartError;
+use axum::extract::Multipart;
+use axum::Json;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+
+/// A description of one part received by [`multipart_echo`].
+///
+/// This is read back from the response using
+/// [`TestResponse::multipart_echo_parts()`](crate::TestResponse::multipart_echo_parts())
+/// and [`TestResponse::assert_multipart_part()`](crate::TestResponse::assert_multipart_part()).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultipartEchoPart {
+    /// The name of the form field.
+    pub name: String,
+
+    /// The file name of the part, if it was sent with one.
+    pub filename: Option<String>,
+
+    /// The content type of the part, if it was sent with one.
+    pub content_type: Option<String>,
+
+    /// The size of the part's contents, in bytes.
+    pub size: usize,
+
+    /// The SHA-256 of the part's contents, as lowercase hex.
+    pub sha256: String,
+}
+
+impl MultipartEchoPart {
+    /// Asserts the contents of this part match the bytes given,
+    /// by comparing their size and SHA-256.
+    #[track_caller]
+    pub fn assert_bytes<B>(&self, expected_bytes: B)
+    where
+        B: AsRef<[u8]>,
+    {
+        self.check_bytes(expected_bytes).or_panic()
+    }
+
+    /// Checks the contents of this part match the bytes given.
+    ///
+    /// This is the non-panicking version of [`MultipartEchoPart::assert_bytes()`].
+    pub fn check_bytes<B>(&self, expected_bytes: B) -> Result<(), AssertionError>
+    where
+        B: AsRef<[u8]>,
+    {
+        let name = &self.name;
+        let expected_bytes = expected_bytes.as_ref();
+        let expected_size = expected_bytes.len();
+        let received_size = self.size;
+
+        check(
+            expected_size == received_size && sha256_hex(expected_bytes) == self.sha256,
+            format_args!("Expected multipart part '{name}' to match the {expected_size} bytes given, received {received_size} bytes with a different SHA-256"),
+        )
+    }
+}
+
+/// A handler which reads a multipart form,
+/// and responds with a description of each part as a Json array.
+///
+/// The contents of the parts are not returned.
+/// Each part is described by it's name, file name, content type, size, and SHA-256,
+/// which can be asserted using [`TestResponse::assert_multipart_part()`](crate::TestResponse::assert_multipart_part()).
+///
+/// ```json
+/// [
+///     {
+///         "name": "file",
+///         "filename": "report.csv",
+///         "content_type": "text/csv",
+///         "size": 5,
+///         "sha256": "205830ca5b23bbe39ab510cfddc1dff2d9842e38b5fa7b7c48cd4ca7e44f92a1"
+///     }
+/// ]
+/// ```
+///
+/// ```rust
+/// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+/// #
+/// use axum::Router;
+/// use axum::routing::post;
+/// use axum_test::TestServer;
+/// use axum_test::multipart::MultipartForm;
+/// use axum_test::multipart::Part;
+/// use axum_test::routes::multipart_echo;
+///
+/// let app = Router::new().route(&"/upload", post(multipart_echo));
+/// let server = TestServer::new(app)?;
+///
+/// let form = MultipartForm::new()
+///     .add_part("file", Part::bytes(b"a,b,c".as_slice()).file_name("report.csv"));
+///
+/// server.post(&"/upload")
+///     .multipart(form)
+///     .await
+///     .assert_multipart_part("file", |part| {
+///         assert_eq!(part.filename.as_deref(), Some("report.csv"));
+///         part.assert_bytes(b"a,b,c");
+///     });
+/// #
+/// # Ok(()) }
+/// ```
+pub async fn multipart_echo(
+    mut multipart: Multipart,
+) -> Result<Json<Vec<MultipartEchoPart>>, MultipartError> {
+    let mut parts = vec![];
+
+    while let Some(field) = multipart.next_field().await? {
+        let name = field.name().unwrap_or_default().to_string();
+        let filename = field.file_name().map(ToString::to_string);
+        let content_type = field.content_type().map(ToString::to_string);
+        let data = field.bytes().await?;
+
+        parts.push(MultipartEchoPart {
+            name,
+            filename,
+            content_type,
+            size: data.len(),
+            sha256: sha256_hex(&data),
+        });
+    }
+
+    Ok(Json(parts))
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+#[cfg(test)]
+mod test_multipart_echo {
+    use super::*;
+    use crate::multipart::MultipartForm;
+    use crate::multipart::Part;
+    use crate::TestServer;
+    use axum::routing::post;
+    use axum::Router;
+
+    #[tokio::test]
+    async fn it_should_describe_each_part_received() {
+        let app = Router::new().route("/upload", post(multipart_echo));
+        let server = TestServer::new(app).unwrap();
+
+        let form = MultipartForm::new().add_text("title", "hello").add_part(
+            "file",
+            Part::bytes(b"a,b,c".as_slice())
+                .file_name("report.csv")
+                .mime_type("text/csv"),
+        );
+
+        let parts = server
+            .post("/upload")
+            .multipart(form)
+            .await
+            .multipart_echo_parts();
+
+        assert_eq!(
+            parts,
+            vec![
+                MultipartEchoPart {
+                    name: "title".to_string(),
+                    filename: None,
+                    content_type: Some("text/plain".to_string()),
+                    size: 5,
+                    sha256: sha256_hex(b"hello"),
+                },
+                MultipartEchoPart {
+                    name: "file".to_string(),
+                    filename: Some("report.csv".to_string()),
+                    content_type: Some("text/csv".to_string()),
+                    size: 5,
+                    sha256: sha256_hex(b"a,b,c"),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn it_should_reject_requests_which_are_not_multipart() {
+        let app = Router::new().route("/upload", post(multipart_echo));
+        let server = TestServer::new(app).unwrap();
+
+        server
+            .post("/upload")
+            .text("not multipart")
+            .expect_failure()
+            .await
+            .assert_status_bad_request();
+    }
+}
+
+#[cfg(test)]
+mod test_assert_bytes {
+    use super::*;
+
+    fn new_part(bytes: &[u8]) -> MultipartEchoPart {
+        MultipartEchoPart {
+            name: "file".to_string(),
+            filename: None,
+            content_type: None,
+            size: bytes.len(),
+            sha256: sha256_hex(bytes),
+        }
+    }
+
+    #[test]
+    fn it_should_pass_for_matching_bytes() {
+        new_part(b"hello").assert_bytes(b"hello");
+    }
+
+    #[test]
+    #[should_panic]
+    fn it_should_panic_for_different_bytes_of_the_same_size() {
+        new_part(b"hello").assert_bytes(b"world");
+    }
+
+    #[test]
+    #[should_panic]
+    fn it_should_panic_for_different_sizes() {
+        new_part(b"hello").assert_bytes(b"hello!");
+    }
+}
@@ -0,0 +1,41 @@
+use axum::routing::any;
+use axum::routing::MethodRouter;
+use http::StatusCode;
+
+/// A route which always responds with the status code given,
+/// and an empty body.
+///
+/// This responds to all HTTP methods.
+///
+/// ```rust
+/// use axum::Router;
+/// use axum_test::routes::status;
+/// use http::StatusCode;
+///
+/// let app: Router = Router::new()
+///     .route(&"/unavailable", status(StatusCode::SERVICE_UNAVAILABLE));
+/// ```
+pub fn status<S>(status_code: StatusCode) -> MethodRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    any(move || async move { status_code })
+}
+
+#[cfg(test)]
+mod test_status {
+    use super::*;
+    use crate::TestServer;
+    use axum::Router;
+
+    #[tokio::test]
+    async fn it_should_respond_with_status_given() {
+        let app = Router::new().route("/teapot", status(StatusCode::IM_A_TEAPOT));
+        let server = TestServer::new(app).unwrap();
+
+        server
+            .post("/teapot")
+            .await
+            .assert_status(StatusCode::IM_A_TEAPOT);
+    }
+}
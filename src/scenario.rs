@@ -0,0 +1,422 @@
+use anyhow::anyhow;
+use anyhow::Context;
+use anyhow::Result;
+use http::HeaderName;
+use http::HeaderValue;
+use http::Method;
+use http::StatusCode;
+use serde_json::Map;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::read_to_string;
+use std::path::Path;
+
+use crate::internals::check;
+use crate::internals::read_insomnia_export;
+use crate::internals::read_postman_collection;
+use crate::internals::ExpectedState;
+use crate::AssertionError;
+use crate::TestServer;
+
+/// A sequence of requests, and the responses expected for them,
+/// which can be written as a Json or Yaml file and run with
+/// [`TestServer::assert_scenario()`](crate::TestServer::assert_scenario()).
+///
+/// This allows API tests to be written without writing Rust.
+///
+/// Each step has a `method` and `path`, and optionally `headers`,
+/// and a body as either `json` or `text`.
+/// The response is checked against the `expect` member, which can hold
+/// a `status` code and a `json_contains` value (see [`TestResponse::assert_json_contains()`](crate::TestResponse::assert_json_contains())).
+///
+/// ```yaml
+/// steps:
+///   - name: create a user
+///     method: POST
+///     path: /users
+///     headers:
+///       x-api-key: my-key
+///     json:
+///       name: Joe
+///     expect:
+///       status: 201
+///       json_contains:
+///         name: Joe
+///
+///   - name: list users
+///     method: GET
+///     path: /users
+///     expect:
+///       status: 200
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scenario {
+    steps: Vec<ScenarioStep>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ScenarioStep {
+    pub(crate) name: String,
+    pub(crate) method: Method,
+    pub(crate) path: String,
+    pub(crate) headers: Vec<(HeaderName, HeaderValue)>,
+    pub(crate) body: ScenarioBody,
+    pub(crate) expected_status: Option<StatusCode>,
+    pub(crate) expected_json_contains: Option<Value>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ScenarioBody {
+    None,
+    Json(Value),
+    Text(String),
+}
+
+impl Scenario {
+    /// Reads a scenario from a Json file.
+    pub fn from_json_file<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path_ref = path.as_ref();
+        let scenario = read_json_file(path_ref)?;
+
+        Self::try_from(scenario)
+            .with_context(|| format!("Failed to read scenario '{}'", path_ref.display()))
+    }
+
+    /// Reads a scenario from a Yaml file.
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml_file<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path_ref = path.as_ref();
+        let contents = read_to_string(path_ref)
+            .with_context(|| format!("Failed to read from file '{}'", path_ref.display()))?;
+        let scenario = serde_yaml::from_str::<Value>(&contents).with_context(|| {
+            format!(
+                "Failed to deserialize file '{}' as Yaml",
+                path_ref.display()
+            )
+        })?;
+
+        Self::try_from(scenario)
+            .with_context(|| format!("Failed to read scenario '{}'", path_ref.display()))
+    }
+
+    /// Reads the requests of a Postman collection (v2.1) as a scenario,
+    /// including the requests inside folders.
+    ///
+    /// `{{variables}}` are replaced using the variables given,
+    /// falling back to the variables of the collection.
+    /// Basic test scripts are turned into expectations, such as
+    /// `pm.response.to.have.status(201)` and `pm.expect(jsonData.name).to.eql("Joe")`.
+    /// Other statements in the scripts are ignored.
+    ///
+    /// Only `raw` request bodies are supported.
+    pub fn from_postman_collection(
+        collection: &Value,
+        variables: &HashMap<String, String>,
+    ) -> Result<Self> {
+        let steps = read_postman_collection(collection, variables)?;
+        Ok(Self { steps })
+    }
+
+    /// Reads a Postman collection (v2.1) file as a scenario.
+    ///
+    /// See [`Scenario::from_postman_collection()`] for details.
+    pub fn from_postman_collection_file<P>(
+        path: P,
+        variables: &HashMap<String, String>,
+    ) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path_ref = path.as_ref();
+        let collection = read_json_file(path_ref)?;
+
+        Self::from_postman_collection(&collection, variables)
+            .with_context(|| format!("Failed to read Postman collection '{}'", path_ref.display()))
+    }
+
+    /// Reads the requests of an Insomnia export (format 4) as a scenario.
+    ///
+    /// `{{ _.variables }}` are replaced using the variables given,
+    /// falling back to the environments in the export.
+    /// Insomnia exports do not hold assertions, so any response is accepted.
+    pub fn from_insomnia_export(
+        export: &Value,
+        variables: &HashMap<String, String>,
+    ) -> Result<Self> {
+        let steps = read_insomnia_export(export, variables)?;
+        Ok(Self { steps })
+    }
+
+    /// Reads an Insomnia export (format 4) Json file as a scenario.
+    ///
+    /// See [`Scenario::from_insomnia_export()`] for details.
+    pub fn from_insomnia_export_file<P>(
+        path: P,
+        variables: &HashMap<String, String>,
+    ) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path_ref = path.as_ref();
+        let export = read_json_file(path_ref)?;
+
+        Self::from_insomnia_export(&export, variables)
+            .with_context(|| format!("Failed to read Insomnia export '{}'", path_ref.display()))
+    }
+
+    /// Returns the number of steps in this scenario.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Returns true if this scenario has no steps.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Runs every step against the server given, in order.
+    ///
+    /// Steps carry on running after one fails,
+    /// and all failures are returned together, labelled by their step.
+    pub(crate) async fn run(&self, server: &TestServer) -> Result<(), AssertionError> {
+        let mut failures = Vec::new();
+
+        for (i, step) in self.steps.iter().enumerate() {
+            if let Err(error) = step.run(server).await {
+                failures.push(format!("step {} '{}' failed: {error}", i + 1, step.name));
+            }
+        }
+
+        check(
+            failures.is_empty(),
+            format_args!(
+                "Scenario failed {} of {} steps:\n\n{}",
+                failures.len(),
+                self.steps.len(),
+                failures.join("\n\n")
+            ),
+        )
+    }
+}
+
+impl TryFrom<Value> for Scenario {
+    type Error = anyhow::Error;
+
+    fn try_from(scenario: Value) -> Result<Self> {
+        let steps = scenario
+            .get("steps")
+            .and_then(Value::as_array)
+            .context("Expected scenario to have a 'steps' array")?
+            .iter()
+            .enumerate()
+            .map(|(i, step)| {
+                ScenarioStep::try_from_value(i, step)
+                    .with_context(|| format!("Failed to read scenario step {}", i + 1))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { steps })
+    }
+}
+
+impl ScenarioStep {
+    fn try_from_value(index: usize, step: &Value) -> Result<Self> {
+        let step = step
+            .as_object()
+            .ok_or_else(|| anyhow!("Expected step to be an object, received {step}"))?;
+        check_known_keys(
+            step,
+            &[
+                "name", "method", "path", "headers", "json", "text", "expect",
+            ],
+        )?;
+
+        let method_str = read_str(step, "method")?.context("Expected step to have a 'method'")?;
+        let method = Method::from_bytes(method_str.to_ascii_uppercase().as_bytes())
+            .with_context(|| format!("Unknown method '{method_str}'"))?;
+        let path = read_str(step, "path")?
+            .context("Expected step to have a 'path'")?
+            .to_string();
+        let name = read_str(step, "name")?
+            .map(ToString::to_string)
+            .unwrap_or_else(|| format!("{method} {path} (step {})", index + 1));
+
+        let headers = match step.get("headers") {
+            None => Vec::new(),
+            Some(Value::Object(headers)) => headers
+                .iter()
+                .map(|(name, value)| {
+                    let value = value.as_str().with_context(|| {
+                        format!("Expected header '{name}' to be a string, received {value}")
+                    })?;
+                    let header_name = HeaderName::from_bytes(name.as_bytes())
+                        .with_context(|| format!("Invalid header name '{name}'"))?;
+                    let header_value = HeaderValue::from_str(value)
+                        .with_context(|| format!("Invalid value for header '{name}'"))?;
+
+                    Ok((header_name, header_value))
+                })
+                .collect::<Result<Vec<_>>>()?,
+            Some(headers) => {
+                return Err(anyhow!(
+                    "Expected 'headers' to be an object, received {headers}"
+                ))
+            }
+        };
+
+        let body = match (step.get("json"), read_str(step, "text")?) {
+            (Some(_), Some(_)) => {
+                return Err(anyhow!("Expected step to have 'json' or 'text', not both"))
+            }
+            (Some(json), None) => ScenarioBody::Json(json.clone()),
+            (None, Some(text)) => ScenarioBody::Text(text.to_string()),
+            (None, None) => ScenarioBody::None,
+        };
+
+        let (expected_status, expected_json_contains) = match step.get("expect") {
+            None => (None, None),
+            Some(Value::Object(expect)) => {
+                check_known_keys(expect, &["status", "json_contains"])?;
+
+                let expected_status = expect
+                    .get("status")
+                    .map(|status| {
+                        status
+                            .as_u64()
+                            .and_then(|status| u16::try_from(status).ok())
+                            .and_then(|status| StatusCode::from_u16(status).ok())
+                            .with_context(|| {
+                                format!("Expected 'status' to be a status code, received {status}")
+                            })
+                    })
+                    .transpose()?;
+
+                (expected_status, expect.get("json_contains").cloned())
+            }
+            Some(expect) => {
+                return Err(anyhow!(
+                    "Expected 'expect' to be an object, received {expect}"
+                ))
+            }
+        };
+
+        Ok(Self {
+            name,
+            method,
+            path,
+            headers,
+            body,
+            expected_status,
+            expected_json_contains,
+        })
+    }
+
+    async fn run(&self, server: &TestServer) -> Result<(), AssertionError> {
+        let mut request = server
+            .method(self.method.clone(), &self.path)
+            .expect_state(ExpectedState::None);
+        for (name, value) in &self.headers {
+            request = request.add_header(name.clone(), value.clone());
+        }
+        request = match &self.body {
+            ScenarioBody::None => request,
+            ScenarioBody::Json(json) => request.json(json),
+            ScenarioBody::Text(text) => request.text(text),
+        };
+
+        let response = request.try_send().await?;
+        if let Some(expected_status) = self.expected_status {
+            response.check_status(expected_status)?;
+        }
+        if let Some(expected_json_contains) = &self.expected_json_contains {
+            response.check_json_contains(expected_json_contains)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn read_json_file(path: &Path) -> Result<Value> {
+    let contents = read_to_string(path)
+        .with_context(|| format!("Failed to read from file '{}'", path.display()))?;
+
+    serde_json::from_str::<Value>(&contents)
+        .with_context(|| format!("Failed to deserialize file '{}' as Json", path.display()))
+}
+
+fn read_str<'a>(members: &'a Map<String, Value>, name: &str) -> Result<Option<&'a str>> {
+    match members.get(name) {
+        None => Ok(None),
+        Some(Value::String(value)) => Ok(Some(value)),
+        Some(value) => Err(anyhow!(
+            "Expected '{name}' to be a string, received {value}"
+        )),
+    }
+}
+
+fn check_known_keys(members: &Map<String, Value>, known_keys: &[&str]) -> Result<()> {
+    match members
+        .keys()
+        .find(|key| !known_keys.contains(&key.as_str()))
+    {
+        None => Ok(()),
+        Some(key) => Err(anyhow!(
+            "Unknown key '{key}', expected one of {known_keys:?}"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test_try_from {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn it_should_read_steps() {
+        let scenario = Scenario::try_from(json!({
+            "steps": [
+                {
+                    "name": "create user",
+                    "method": "post",
+                    "path": "/users",
+                    "headers": { "x-api-key": "my-key" },
+                    "json": { "name": "Joe" },
+                    "expect": { "status": 201, "json_contains": { "name": "Joe" } },
+                },
+                { "method": "GET", "path": "/users" },
+            ],
+        }))
+        .unwrap();
+
+        assert_eq!(scenario.len(), 2);
+        assert_eq!(scenario.steps[0].method, Method::POST);
+        assert_eq!(scenario.steps[0].expected_status, Some(StatusCode::CREATED));
+        assert_eq!(scenario.steps[1].name, "GET /users (step 2)");
+        assert_eq!(scenario.steps[1].body, ScenarioBody::None);
+    }
+
+    #[test]
+    fn it_should_reject_unknown_keys() {
+        let error = Scenario::try_from(json!({
+            "steps": [{ "method": "GET", "path": "/", "expect": { "stauts": 200 } }],
+        }))
+        .unwrap_err();
+
+        assert!(format!("{error:#}").contains("Unknown key 'stauts'"));
+    }
+
+    #[test]
+    fn it_should_reject_steps_without_a_path() {
+        let error = Scenario::try_from(json!({ "steps": [{ "method": "GET" }] })).unwrap_err();
+
+        assert!(format!("{error:#}").contains("Expected step to have a 'path'"));
+    }
+}
@@ -0,0 +1,300 @@
+use http::Method;
+use http::StatusCode;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::hash::Hash;
+use std::hash::Hasher;
+
+use crate::internals::check;
+use crate::internals::is_route_match;
+use crate::internals::OrPanic;
+use crate::AssertionError;
+
+/// Counts of the requests that reached the application behind a [`TestServer`](crate::TestServer).
+///
+/// These are only recorded when the server is built with
+/// [`TestServerBuilder::record_metrics()`](crate::TestServerBuilder::record_metrics()),
+/// and are retrieved using [`TestServer::metrics()`](crate::TestServer::metrics()).
+///
+/// Requests are counted by their path, excluding the query.
+/// These can be looked up by the exact path using [`ServerMetrics::path_hits()`],
+/// or by an Axum route pattern (such as `/users/:id`) using [`ServerMetrics::route_hits()`].
+///
+/// ```rust
+/// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+/// #
+/// use axum::Router;
+/// use axum::routing::get;
+/// use axum_test::TestServer;
+///
+/// let app = Router::new()
+///     .route("/login", get(|| async { "logged in" }));
+///
+/// let server = TestServer::builder()
+///     .record_metrics()
+///     .build(app)?;
+///
+/// server.get("/login").await;
+///
+/// let metrics = server.metrics();
+/// assert_eq!(metrics.path_hits("/login"), 1);
+/// assert_eq!(metrics.status_hits(200), 1);
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ServerMetrics {
+    hits_by_path: BTreeMap<String, usize>,
+    hits_by_status: BTreeMap<StatusCode, usize>,
+    bytes_sent: usize,
+    bytes_received: usize,
+    hits_by_request: BTreeMap<(String, String, u64), usize>,
+}
+
+impl ServerMetrics {
+    pub(crate) fn record(
+        &mut self,
+        path: &str,
+        status_code: StatusCode,
+        request_bytes: usize,
+        response_bytes: usize,
+    ) {
+        *self.hits_by_path.entry(path.to_string()).or_default() += 1;
+        *self.hits_by_status.entry(status_code).or_default() += 1;
+        self.bytes_sent += request_bytes;
+        self.bytes_received += response_bytes;
+    }
+
+    /// Records the request sent, for finding requests sent more than once.
+    ///
+    /// The body is stored as a hash, to avoid holding onto large bodies.
+    pub(crate) fn record_request(&mut self, method: &Method, path_and_query: &str, body: &[u8]) {
+        let mut hasher = DefaultHasher::new();
+        body.hash(&mut hasher);
+        let key = (
+            method.to_string(),
+            path_and_query.to_string(),
+            hasher.finish(),
+        );
+
+        *self.hits_by_request.entry(key).or_default() += 1;
+    }
+
+    /// The total number of requests that reached the application.
+    #[must_use]
+    pub fn total_hits(&self) -> usize {
+        self.hits_by_path.values().sum()
+    }
+
+    /// The number of requests made to exactly the path given.
+    #[must_use]
+    pub fn path_hits(&self, path: &str) -> usize {
+        self.hits_by_path.get(path).copied().unwrap_or_default()
+    }
+
+    /// The number of requests made to paths matching the Axum route pattern given.
+    ///
+    /// i.e. `route_hits("/users/:id")` counts requests to both `/users/1` and `/users/2`.
+    ///
+    /// Patterns are matched against the paths requested, not the route Axum picked.
+    /// So requests to `/users/me` are also counted, even if it has its own route.
+    #[must_use]
+    pub fn route_hits(&self, route: &str) -> usize {
+        self.hits_by_path
+            .iter()
+            .filter(|(path, _)| is_route_match(route, path))
+            .map(|(_, hits)| hits)
+            .sum()
+    }
+
+    /// The number of requests made to each path.
+    #[must_use]
+    pub fn hits_by_path(&self) -> &BTreeMap<String, usize> {
+        &self.hits_by_path
+    }
+
+    /// The number of responses returned with the status code given.
+    #[must_use]
+    pub fn status_hits<S>(&self, status_code: S) -> usize
+    where
+        S: TryInto<StatusCode>,
+        S::Error: std::fmt::Debug,
+    {
+        let status_code = status_code
+            .try_into()
+            .expect("Failed to build StatusCode from status given");
+
+        self.hits_by_status
+            .get(&status_code)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The number of responses returned with each status code.
+    #[must_use]
+    pub fn hits_by_status(&self) -> &BTreeMap<StatusCode, usize> {
+        &self.hits_by_status
+    }
+
+    /// The total number of bytes sent in request bodies.
+    #[must_use]
+    pub fn bytes_sent(&self) -> usize {
+        self.bytes_sent
+    }
+
+    /// The total number of bytes received in response bodies.
+    #[must_use]
+    pub fn bytes_received(&self) -> usize {
+        self.bytes_received
+    }
+
+    /// Returns the requests which were sent more than once,
+    /// with the same method, path, query, and body.
+    ///
+    /// This is for catching accidental double submits, such as from retry logic in a client.
+    #[must_use]
+    pub fn duplicate_requests(&self) -> Vec<DuplicateRequest> {
+        self.hits_by_request
+            .iter()
+            .filter(|(_, hits)| **hits > 1)
+            .map(|((method, path, _), hits)| DuplicateRequest {
+                method: method.parse().expect("Failed to parse recorded method"),
+                path: path.clone(),
+                hits: *hits,
+            })
+            .collect()
+    }
+
+    /// Asserts no request was sent more than once,
+    /// with the same method, path, query, and body.
+    #[track_caller]
+    pub fn assert_no_duplicate_requests(&self) {
+        self.check_no_duplicate_requests().or_panic()
+    }
+
+    /// Checks no request was sent more than once,
+    /// with the same method, path, query, and body.
+    ///
+    /// This is the non-panicking version of [`ServerMetrics::assert_no_duplicate_requests()`].
+    pub fn check_no_duplicate_requests(&self) -> Result<(), AssertionError> {
+        let duplicates = self.duplicate_requests();
+        let duplicates_list = duplicates
+            .iter()
+            .map(|duplicate| format!("    {duplicate}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        check(
+            duplicates.is_empty(),
+            format_args!("Expected no duplicate requests, found,\n{duplicates_list}"),
+        )
+    }
+}
+
+/// A request sent more than once with the same method, path, query, and body,
+/// returned from [`ServerMetrics::duplicate_requests()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateRequest {
+    /// The method of the request.
+    pub method: Method,
+
+    /// The path of the request, including the query.
+    pub path: String,
+
+    /// The number of times the request was sent.
+    pub hits: usize,
+}
+
+impl Display for DuplicateRequest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{} {} sent {} times", self.method, self.path, self.hits)
+    }
+}
+
+#[cfg(test)]
+mod test_record {
+    use super::*;
+
+    #[test]
+    fn it_should_count_hits_by_path_and_status() {
+        let mut metrics = ServerMetrics::default();
+        metrics.record("/login", StatusCode::OK, 10, 20);
+        metrics.record("/login", StatusCode::UNAUTHORIZED, 5, 0);
+        metrics.record("/logout", StatusCode::OK, 0, 3);
+
+        assert_eq!(metrics.total_hits(), 3);
+        assert_eq!(metrics.path_hits("/login"), 2);
+        assert_eq!(metrics.path_hits("/logout"), 1);
+        assert_eq!(metrics.path_hits("/unknown"), 0);
+        assert_eq!(metrics.status_hits(StatusCode::OK), 2);
+        assert_eq!(metrics.status_hits(401), 1);
+        assert_eq!(metrics.bytes_sent(), 15);
+        assert_eq!(metrics.bytes_received(), 23);
+    }
+}
+
+#[cfg(test)]
+mod test_route_hits {
+    use super::*;
+
+    #[test]
+    fn it_should_count_hits_across_paths_matching_route() {
+        let mut metrics = ServerMetrics::default();
+        metrics.record("/users/1", StatusCode::OK, 0, 0);
+        metrics.record("/users/2", StatusCode::OK, 0, 0);
+        metrics.record("/users/2", StatusCode::OK, 0, 0);
+        metrics.record("/users/2/posts", StatusCode::OK, 0, 0);
+
+        assert_eq!(metrics.route_hits("/users/:id"), 3);
+        assert_eq!(metrics.route_hits("/users/:id/posts"), 1);
+        assert_eq!(metrics.route_hits("/users/*rest"), 4);
+        assert_eq!(metrics.path_hits("/users/2"), 2);
+    }
+}
+
+#[cfg(test)]
+mod test_assert_no_duplicate_requests {
+    use super::*;
+
+    #[test]
+    fn it_should_pass_when_requests_differ() {
+        let mut metrics = ServerMetrics::default();
+        metrics.record_request(&Method::POST, "/orders", b"{\"id\":1}");
+        metrics.record_request(&Method::POST, "/orders", b"{\"id\":2}");
+        metrics.record_request(&Method::PUT, "/orders", b"{\"id\":1}");
+        metrics.record_request(&Method::POST, "/orders?page=2", b"{\"id\":1}");
+
+        metrics.assert_no_duplicate_requests();
+    }
+
+    #[test]
+    fn it_should_find_requests_sent_more_than_once() {
+        let mut metrics = ServerMetrics::default();
+        metrics.record_request(&Method::POST, "/orders", b"{\"id\":1}");
+        metrics.record_request(&Method::POST, "/orders", b"{\"id\":1}");
+        metrics.record_request(&Method::GET, "/orders", b"");
+
+        assert_eq!(
+            metrics.duplicate_requests(),
+            vec![DuplicateRequest {
+                method: Method::POST,
+                path: "/orders".to_string(),
+                hits: 2,
+            }]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "POST /orders sent 2 times")]
+    fn it_should_panic_when_requests_are_duplicated() {
+        let mut metrics = ServerMetrics::default();
+        metrics.record_request(&Method::POST, "/orders", b"{\"id\":1}");
+        metrics.record_request(&Method::POST, "/orders", b"{\"id\":1}");
+
+        metrics.assert_no_duplicate_requests();
+    }
+}
@@ -0,0 +1,189 @@
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use crate::internals::OrPanic;
+use crate::AssertionError;
+
+/// A shared log of named events, for checking the order things happen inside your application.
+///
+/// The log is given to your application as an [`Extension`](::axum::Extension),
+/// and handlers push events to it as they run.
+/// The same log is given to the [`TestServer`](crate::TestServer) using
+/// [`TestServerBuilder::event_log()`](crate::TestServerBuilder::event_log()),
+/// and read back in tests using [`TestServer::events()`](crate::TestServer::events()).
+///
+/// ```rust
+/// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+/// #
+/// use axum::Extension;
+/// use axum::Router;
+/// use axum::routing::post;
+/// use axum_test::TestEventLog;
+/// use axum_test::TestServer;
+///
+/// async fn route_create_user(Extension(events): Extension<TestEventLog>) {
+///     events.push("auth.check");
+///     events.push("db.write");
+///     events.push("email.queued");
+/// }
+///
+/// let events = TestEventLog::new();
+/// let app = Router::new()
+///     .route(&"/users", post(route_create_user))
+///     .layer(Extension(events.clone()));
+///
+/// let server = TestServer::builder()
+///     .event_log(events)
+///     .build(app)?;
+///
+/// server.post(&"/users").await;
+///
+/// server.events().assert_order(["auth.check", "db.write", "email.queued"]);
+/// #
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TestEventLog {
+    events: Arc<Mutex<Vec<String>>>,
+}
+
+/// Two event logs are equal when they share the same events,
+/// such as when one is cloned from the other.
+impl PartialEq for TestEventLog {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.events, &other.events)
+    }
+}
+
+impl Eq for TestEventLog {}
+
+impl TestEventLog {
+    /// Creates a new empty event log.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records an event, after all of those recorded so far.
+    pub fn push<E>(&self, event: E)
+    where
+        E: Into<String>,
+    {
+        self.events
+            .lock()
+            .expect("Failed to lock event log, for pushing event")
+            .push(event.into());
+    }
+
+    /// Returns all of the events recorded, in the order they were pushed.
+    #[must_use]
+    pub fn events(&self) -> Vec<String> {
+        self.events
+            .lock()
+            .expect("Failed to lock event log, for reading events")
+            .clone()
+    }
+
+    /// Removes all of the events recorded.
+    pub fn clear(&self) {
+        self.events
+            .lock()
+            .expect("Failed to lock event log, for clearing events")
+            .clear();
+    }
+
+    /// Asserts the events given were recorded, in that order.
+    ///
+    /// Other events may be recorded before, between, or after them.
+    #[track_caller]
+    pub fn assert_order<I>(&self, expected_events: I)
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        self.check_order(expected_events).or_panic()
+    }
+
+    /// Checks the events given were recorded, in that order.
+    ///
+    /// This is the non-panicking version of [`TestEventLog::assert_order()`].
+    pub fn check_order<I>(&self, expected_events: I) -> Result<(), AssertionError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let events = self.events();
+        let mut remaining = events.as_slice();
+        let mut maybe_previous: Option<String> = None;
+
+        for expected_event in expected_events {
+            let expected_event = expected_event.as_ref();
+            let maybe_position = remaining.iter().position(|event| event == expected_event);
+
+            let Some(position) = maybe_position else {
+                let is_recorded = events.iter().any(|event| event == expected_event);
+                let message = match (is_recorded, &maybe_previous) {
+                    (true, Some(previous)) => format!("Expected event '{expected_event}' to be recorded after '{previous}', recorded events were {events:?}"),
+                    _ => format!("Expected event '{expected_event}' to be recorded, it was not found, recorded events were {events:?}"),
+                };
+
+                return Err(AssertionError::new(message));
+            };
+
+            remaining = &remaining[position + 1..];
+            maybe_previous = Some(expected_event.to_string());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_check_order {
+    use super::*;
+
+    fn new_event_log() -> TestEventLog {
+        let events = TestEventLog::new();
+        events.push("auth.check");
+        events.push("db.read");
+        events.push("db.write");
+        events.push("email.queued");
+        events
+    }
+
+    #[test]
+    fn it_should_pass_when_events_are_in_order() {
+        let events = new_event_log();
+
+        events.assert_order(["auth.check", "db.write", "email.queued"]);
+        events.assert_order(Vec::<String>::new());
+    }
+
+    #[test]
+    fn it_should_fail_when_events_are_out_of_order() {
+        let events = new_event_log();
+        let error = events.check_order(["db.write", "auth.check"]).unwrap_err();
+
+        assert!(error
+            .to_string()
+            .contains("Expected event 'auth.check' to be recorded after 'db.write'"));
+    }
+
+    #[test]
+    fn it_should_fail_when_event_is_missing() {
+        let events = new_event_log();
+        let error = events.check_order(["auth.check", "sms.sent"]).unwrap_err();
+
+        assert!(error
+            .to_string()
+            .contains("Expected event 'sms.sent' to be recorded, it was not found"));
+    }
+
+    #[test]
+    fn it_should_clear_events() {
+        let events = new_event_log();
+        events.clear();
+
+        assert!(events.events().is_empty());
+    }
+}
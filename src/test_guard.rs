@@ -0,0 +1,224 @@
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::sync::mpsc::channel;
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use crate::ServerSharedState;
+
+/// Prints the requests still in flight, and WebSockets still open, on a [`TestServer`](crate::TestServer)
+/// when a test panics or takes too long.
+///
+/// This is created using [`TestServer::guard()`](crate::TestServer::guard()),
+/// and should be held for the length of the test.
+/// When it is dropped during a panic, the diagnostics are printed to stderr.
+///
+/// A deadline can be added using [`TestGuard::with_deadline()`].
+/// If the guard is still alive when the deadline passes, the diagnostics are also printed.
+/// This is checked on a separate thread, so it works even when the test is stuck.
+/// It is useful for tests which hang under a test runner timeout, such as with `cargo nextest`,
+/// where otherwise nothing says which request stalled.
+///
+/// ```rust
+/// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+/// #
+/// use axum::Router;
+/// use axum::routing::get;
+/// use axum_test::TestServer;
+/// use std::time::Duration;
+///
+/// let app = Router::new()
+///     .route("/ping", get(|| async { "pong!" }));
+/// let server = TestServer::new(app)?;
+/// let _guard = server.guard().with_deadline(Duration::from_secs(30));
+///
+/// server.get("/ping").await;
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub struct TestGuard {
+    server_state: Arc<Mutex<ServerSharedState>>,
+    maybe_deadline_sender: Option<Sender<()>>,
+}
+
+impl TestGuard {
+    pub(crate) fn new(server_state: Arc<Mutex<ServerSharedState>>) -> Self {
+        Self {
+            server_state,
+            maybe_deadline_sender: None,
+        }
+    }
+
+    /// Prints the diagnostics if this guard is still alive once the time given has passed.
+    ///
+    /// Calling this again replaces the previous deadline.
+    #[must_use]
+    pub fn with_deadline(mut self, deadline: Duration) -> Self {
+        // Dropping the sender wakes the thread, and so stops the deadline.
+        let (sender, receiver) = channel::<()>();
+        let server_state = self.server_state.clone();
+
+        thread::spawn(move || {
+            if receiver.recv_timeout(deadline) == Err(RecvTimeoutError::Timeout) {
+                eprintln!(
+                    "{}",
+                    format_report(
+                        &server_state,
+                        &format!("TestGuard deadline of {deadline:?} has passed")
+                    )
+                );
+            }
+        });
+
+        self.maybe_deadline_sender = Some(sender);
+        self
+    }
+
+    /// Returns a description of the requests currently in flight,
+    /// and WebSockets currently open, on the server.
+    ///
+    /// This is what is printed on a panic, or when the deadline passes.
+    #[must_use]
+    pub fn report(&self) -> String {
+        format_report(&self.server_state, "TestGuard report")
+    }
+}
+
+impl Drop for TestGuard {
+    fn drop(&mut self) {
+        self.maybe_deadline_sender = None;
+
+        if thread::panicking() {
+            eprintln!(
+                "{}",
+                format_report(&self.server_state, "TestGuard dropped during a panic")
+            );
+        }
+    }
+}
+
+impl Debug for TestGuard {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("TestGuard")
+            .field("has_deadline", &self.maybe_deadline_sender.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+fn format_report(server_state: &Arc<Mutex<ServerSharedState>>, title: &str) -> String {
+    match ServerSharedState::in_flight_report(server_state) {
+        Ok(report) => format!("{title}\n{report}"),
+        Err(err) => format!("{title}\nFailed to read in flight requests, {err:?}"),
+    }
+}
+
+#[cfg(test)]
+mod test_report {
+    use axum::routing::get;
+    use axum::Router;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::Notify;
+    use tokio::time::sleep;
+    use tokio::time::timeout;
+
+    use crate::TestServer;
+
+    #[tokio::test]
+    async fn it_should_report_nothing_when_idle() {
+        let app = Router::new().route("/ping", get(|| async { "pong!" }));
+        let server = TestServer::new(app).unwrap();
+        let guard = server.guard();
+
+        server.get("/ping").await;
+
+        assert_eq!(
+            guard.report(),
+            "TestGuard report\nRequests in flight: none\nWebSockets open: none\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn it_should_report_requests_in_flight() {
+        let release = Arc::new(Notify::new());
+        let handler_release = release.clone();
+        let app = Router::new().route(
+            "/slow",
+            get(move || async move {
+                handler_release.notified().await;
+                "done"
+            }),
+        );
+        let server = TestServer::new(app).unwrap();
+        let guard = server.guard();
+
+        let (response, report) = tokio::join!(server.get("/slow"), async {
+            sleep(Duration::from_millis(50)).await;
+            let report = guard.report();
+            release.notify_one();
+            report
+        });
+
+        assert!(report.contains("Requests in flight: 1\n    GET http://localhost/slow, for "));
+        response.assert_text("done");
+        assert!(guard.report().contains("Requests in flight: none"));
+    }
+
+    #[tokio::test]
+    async fn it_should_stop_tracking_cancelled_requests() {
+        let app = Router::new().route(
+            "/slow",
+            get(|| async {
+                sleep(Duration::from_secs(60)).await;
+                "done"
+            }),
+        );
+        let server = TestServer::new(app).unwrap();
+        let guard = server.guard();
+
+        let result = timeout(Duration::from_millis(50), server.get("/slow")).await;
+        assert!(result.is_err());
+
+        assert!(guard.report().contains("Requests in flight: none"));
+    }
+}
+
+#[cfg(feature = "ws")]
+#[cfg(test)]
+mod test_report_websockets {
+    use axum::extract::ws::WebSocketUpgrade;
+    use axum::response::Response;
+    use axum::routing::get;
+    use axum::Router;
+
+    use crate::TestServer;
+
+    async fn route_get_websocket(ws: WebSocketUpgrade) -> Response {
+        ws.on_upgrade(|mut socket| async move { while socket.recv().await.is_some() {} })
+    }
+
+    #[tokio::test]
+    async fn it_should_report_open_websockets() {
+        let app = Router::new().route("/ws", get(route_get_websocket));
+        let server = TestServer::builder().http_transport().build(app).unwrap();
+        let guard = server.guard();
+
+        let websocket = server.get_websocket("/ws").await.into_websocket().await;
+        assert!(guard
+            .report()
+            .contains("WebSockets open: 1\n    GET http://"));
+
+        let (sender, receiver) = websocket.split();
+        drop(sender);
+        assert!(guard.report().contains("WebSockets open: 1\n"));
+
+        drop(receiver);
+        assert!(guard.report().contains("WebSockets open: none"));
+    }
+}
@@ -0,0 +1,70 @@
+//!
+//! Support used by the code generated by the [`test`](crate::test) macro.
+//! This is not intended to be used directly.
+//!
+
+use std::any::Any;
+use std::future::Future;
+use std::panic::catch_unwind as std_catch_unwind;
+use std::panic::resume_unwind as std_resume_unwind;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+
+type PanicPayload = Box<dyn Any + Send + 'static>;
+
+/// Runs the future given to completion, on a new single threaded runtime.
+pub fn block_on<F>(future: F) -> F::Output
+where
+    F: Future,
+{
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("Failed to build tokio runtime, for test")
+        .block_on(future)
+}
+
+/// Runs the future given, catching any panic so teardown can still take place.
+pub async fn catch_unwind<F>(future: F) -> Result<F::Output, PanicPayload>
+where
+    F: Future,
+{
+    CatchUnwind {
+        future: Box::pin(future),
+    }
+    .await
+}
+
+/// Continues a panic caught by [`catch_unwind`], or returns the output.
+pub fn resume_unwind<T>(result: Result<T, PanicPayload>) -> T {
+    match result {
+        Ok(output) => output,
+        Err(panic) => std_resume_unwind(panic),
+    }
+}
+
+struct CatchUnwind<F>
+where
+    F: Future,
+{
+    future: Pin<Box<F>>,
+}
+
+impl<F> Future for CatchUnwind<F>
+where
+    F: Future,
+{
+    type Output = Result<F::Output, PanicPayload>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let future = self.future.as_mut();
+
+        match std_catch_unwind(AssertUnwindSafe(|| future.poll(cx))) {
+            Ok(Poll::Pending) => Poll::Pending,
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Err(panic) => Poll::Ready(Err(panic)),
+        }
+    }
+}
@@ -9,11 +9,11 @@ use cookie::time::OffsetDateTime;
 use cookie::Cookie;
 use cookie::CookieJar;
 use http::header;
-use http::header::SET_COOKIE;
 use http::HeaderName;
 use http::HeaderValue;
 use http::Method;
 use http::Request;
+use http::Version;
 use http_body_util::BodyExt;
 use serde::Serialize;
 use std::fmt::Debug;
@@ -26,15 +26,49 @@ use std::io::BufReader;
 use std::path::Path;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::time::Instant;
 use url::Url;
 
+use crate::internals::add_catch_panic_marker;
+use crate::internals::build_odata_query_param;
+use crate::internals::content_type_essence;
+use crate::internals::decode_base64;
+use crate::internals::decode_hex;
+use crate::internals::format_http_date;
+#[cfg(all(
+    feature = "dyn-features",
+    not(all(
+        feature = "yaml",
+        feature = "msgpack",
+        feature = "ws",
+        feature = "typed-routing"
+    ))
+))]
+use crate::internals::missing_feature_error;
+use crate::internals::parse_curl_command;
+use crate::internals::BodyFraming;
+use crate::internals::CaptureRawResponseHead;
+use crate::internals::ExpectWith;
 use crate::internals::ExpectedState;
+use crate::internals::InFlightKind;
 use crate::internals::QueryParamsStore;
+use crate::internals::ReceiveBufferSize;
+use crate::internals::RecordedRequest;
+use crate::internals::ReplayableRequest;
 use crate::internals::RequestPathFormatter;
+use crate::internals::DEFAULT_CHUNK_SIZE;
 use crate::multipart::MultipartForm;
 use crate::transport_layer::TransportLayer;
+use crate::BrowserProfile;
+use crate::Error;
 use crate::ServerSharedState;
 use crate::TestResponse;
+use crate::TestResponseStream;
+use crate::TestServer;
+use crate::IDEMPOTENCY_KEY_HEADER;
+
+mod pending_response;
+pub(crate) use self::pending_response::*;
 
 mod test_request_config;
 pub(crate) use self::test_request_config::*;
@@ -91,7 +125,7 @@ pub(crate) use self::test_request_config::*;
 /// for reuse on any future requests.
 ///
 /// This behaviour is **off** by default, and can be changed for all `TestRequests`
-/// when building the `TestServer`. By building it with a `TestServerConfig` where `save_cookies` is set to true.
+/// when building the `TestServer`. By building it with a `TestServerConfig` where `cookie_config` saves cookies.
 ///
 /// ## Expecting Failure and Success
 ///
@@ -113,9 +147,13 @@ pub struct TestRequest {
     config: TestRequestConfig,
 
     server_state: Arc<Mutex<ServerSharedState>>,
-    transport: Arc<Box<dyn TransportLayer>>,
+    transport: Arc<dyn TransportLayer>,
 
     body: Option<Body>,
+    body_framing: BodyFraming,
+    is_body_forbidden: bool,
+    maybe_receive_buffer_size: Option<usize>,
+    maybe_missing_feature: Option<Error>,
 
     expected_state: ExpectedState,
 }
@@ -123,7 +161,7 @@ pub struct TestRequest {
 impl TestRequest {
     pub(crate) fn new(
         server_state: Arc<Mutex<ServerSharedState>>,
-        transport: Arc<Box<dyn TransportLayer>>,
+        transport: Arc<dyn TransportLayer>,
         config: TestRequestConfig,
     ) -> Self {
         let expected_state = config.expected_state;
@@ -133,12 +171,89 @@ impl TestRequest {
             server_state,
             transport,
             body: None,
+            body_framing: BodyFraming::default(),
+            is_body_forbidden: false,
+            maybe_receive_buffer_size: None,
+            maybe_missing_feature: None,
             expected_state,
         }
     }
 
+    /// Marks the request as using a method which needs a feature axum-test was built without.
+    /// The error is returned when the request is sent.
+    #[cfg(all(
+        feature = "dyn-features",
+        not(all(
+            feature = "yaml",
+            feature = "msgpack",
+            feature = "ws",
+            feature = "typed-routing"
+        ))
+    ))]
+    pub(crate) fn with_missing_feature(mut self, method: &str, feature: &str) -> Self {
+        if self.maybe_missing_feature.is_none() {
+            self.maybe_missing_feature = Some(missing_feature_error(method, feature));
+        }
+
+        self
+    }
+
+    /// Builds a request against the server given from a `curl` command,
+    /// such as one copied from a bug report.
+    ///
+    /// The method (`-X`), headers (`-H`), body (`-d`, `--data-raw`, `--json`, etc),
+    /// basic auth (`-u`), cookies (`-b`), and `-G` are understood.
+    /// The scheme and host of the url are dropped, so the request is sent to the server given.
+    /// Options which only change how curl runs (i.e. `-s`, `-v`, `-L`) are ignored.
+    ///
+    /// This will panic if the command cannot be parsed, or uses an option which is not supported,
+    /// such as reading the data from a file with `-d @file`.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Json;
+    /// use axum::Router;
+    /// use axum::routing::post;
+    /// use axum_test::TestRequest;
+    /// use axum_test::TestServer;
+    /// use serde_json::Value;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/users", post(|Json(user): Json<Value>| async move { Json(user) }));
+    /// let server = TestServer::new(app)?;
+    ///
+    /// let curl = r#"curl -X POST -H 'content-type: application/json' -d '{"name":"Joe"}' http://localhost:3000/users"#;
+    /// TestRequest::from_curl_str(&server, curl)
+    ///     .await
+    ///     .assert_json_contains(&serde_json::json!({ "name": "Joe" }));
+    /// #
+    /// # Ok(()) }
+    /// ```
+    #[track_caller]
+    pub fn from_curl_str(server: &TestServer, curl: &str) -> Self {
+        let command = parse_curl_command(curl)
+            .with_context(|| format!("Failed to parse curl command '{curl}'"))
+            .unwrap();
+
+        let mut request = server.method(command.method, &command.path);
+        for (name, value) in command.headers {
+            request = if name.eq_ignore_ascii_case("content-type") {
+                request.content_type(&value)
+            } else {
+                request.add_header(name, value)
+            };
+        }
+
+        match command.maybe_body {
+            Some(body) => request.bytes(body.into()),
+            None => request,
+        }
+    }
+
     /// Set the body of the request to send up data as Json,
     /// and changes the content type to `application/json`.
+    #[track_caller]
     pub fn json<J>(self, body: &J) -> Self
     where
         J: ?Sized + Serialize,
@@ -151,6 +266,7 @@ impl TestRequest {
     }
 
     /// Sends a payload as a Json request, with the contents coming from a file.
+    #[track_caller]
     pub fn json_from_file<P>(self, path: P) -> Self
     where
         P: AsRef<Path>,
@@ -175,57 +291,98 @@ impl TestRequest {
 
     /// Set the body of the request to send up data as Yaml,
     /// and changes the content type to `application/yaml`.
-    #[cfg(feature = "yaml")]
+    ///
+    /// When built with `dyn-features`, without the `yaml` feature,
+    /// sending the request returns an [`Error::MissingFeature`](crate::Error::MissingFeature).
+    #[cfg(any(feature = "yaml", feature = "dyn-features"))]
+    #[track_caller]
     pub fn yaml<Y>(self, body: &Y) -> Self
     where
         Y: ?Sized + Serialize,
     {
-        let body = serde_yaml::to_string(body).expect("It should serialize the content into Yaml");
+        #[cfg(not(feature = "yaml"))]
+        {
+            let _ = body;
+            self.with_missing_feature("TestRequest::yaml", "yaml")
+        }
 
-        self.bytes(body.into_bytes().into())
-            .content_type("application/yaml")
+        #[cfg(feature = "yaml")]
+        {
+            let body =
+                serde_yaml::to_string(body).expect("It should serialize the content into Yaml");
+
+            self.bytes(body.into_bytes().into())
+                .content_type("application/yaml")
+        }
     }
 
     /// Sends a payload as a Yaml request, with the contents coming from a file.
-    #[cfg(feature = "yaml")]
+    ///
+    /// When built with `dyn-features`, without the `yaml` feature,
+    /// sending the request returns an [`Error::MissingFeature`](crate::Error::MissingFeature).
+    #[cfg(any(feature = "yaml", feature = "dyn-features"))]
+    #[track_caller]
     pub fn yaml_from_file<P>(self, path: P) -> Self
     where
         P: AsRef<Path>,
     {
-        let path_ref = path.as_ref();
-        let file = File::open(path_ref)
-            .with_context(|| format!("Failed to read from file '{}'", path_ref.display()))
-            .unwrap();
-
-        let reader = BufReader::new(file);
-        let payload = serde_yaml::from_reader::<_, serde_yaml::Value>(reader)
-            .with_context(|| {
-                format!(
-                    "Failed to deserialize file '{}' as Yaml",
-                    path_ref.display()
-                )
-            })
-            .unwrap();
+        #[cfg(not(feature = "yaml"))]
+        {
+            let _ = path;
+            self.with_missing_feature("TestRequest::yaml_from_file", "yaml")
+        }
 
-        self.yaml(&payload)
+        #[cfg(feature = "yaml")]
+        {
+            let path_ref = path.as_ref();
+            let file = File::open(path_ref)
+                .with_context(|| format!("Failed to read from file '{}'", path_ref.display()))
+                .unwrap();
+
+            let reader = BufReader::new(file);
+            let payload = serde_yaml::from_reader::<_, serde_yaml::Value>(reader)
+                .with_context(|| {
+                    format!(
+                        "Failed to deserialize file '{}' as Yaml",
+                        path_ref.display()
+                    )
+                })
+                .unwrap();
+
+            self.yaml(&payload)
+        }
     }
 
     /// Set the body of the request to send up data as MsgPack,
     /// and changes the content type to `application/msgpack`.
-    #[cfg(feature = "msgpack")]
+    ///
+    /// When built with `dyn-features`, without the `msgpack` feature,
+    /// sending the request returns an [`Error::MissingFeature`](crate::Error::MissingFeature).
+    #[cfg(any(feature = "msgpack", feature = "dyn-features"))]
+    #[track_caller]
     pub fn msgpack<M>(self, body: &M) -> Self
     where
         M: ?Sized + Serialize,
     {
-        let body_bytes =
-            ::rmp_serde::to_vec(body).expect("It should serialize the content into MsgPack");
+        #[cfg(not(feature = "msgpack"))]
+        {
+            let _ = body;
+            self.with_missing_feature("TestRequest::msgpack", "msgpack")
+        }
 
-        self.bytes(body_bytes.into())
-            .content_type("application/msgpack")
+        #[cfg(feature = "msgpack")]
+        {
+            let body_bytes =
+                ::rmp_serde::to_vec(body).expect("It should serialize the content into MsgPack");
+
+            self.bytes(body_bytes.into())
+                .content_type("application/msgpack")
+        }
     }
 
     /// Sets the body of the request, with the content type
     /// of 'application/x-www-form-urlencoded'.
+    #[track_caller]
     pub fn form<F>(self, body: &F) -> Self
     where
         F: ?Sized + Serialize,
@@ -237,6 +394,49 @@ impl TestRequest {
             .content_type(mime::APPLICATION_WWW_FORM_URLENCODED.essence_str())
     }
 
+    /// Sets the body of the request, encoded using the content type given,
+    /// and sends it with that content type.
+    ///
+    /// Encoders registered using [`TestServer::register_encoder()`](crate::TestServer::register_encoder())
+    /// are used first. Otherwise Json (including `+json` types), Yaml, MsgPack,
+    /// and form bodies are encoded in the same way as the methods for those formats.
+    ///
+    /// If there is no encoder for the content type, or encoding fails, then this will panic.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Json;
+    /// use axum::Router;
+    /// use axum::routing::post;
+    /// use axum_test::TestServer;
+    /// use serde_json::json;
+    /// use serde_json::Value;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/todo", post(|Json(todo): Json<Value>| async move { Json(todo) }));
+    /// let server = TestServer::new(app)?;
+    ///
+    /// server.post(&"/todo")
+    ///     .body_as("application/json", &json!({ "description": "buy milk" }))
+    ///     .await
+    ///     .assert_json(&json!({ "description": "buy milk" }));
+    /// #
+    /// # Ok(()) }
+    /// ```
+    #[track_caller]
+    pub fn body_as<B>(self, content_type: &str, body: &B) -> Self
+    where
+        B: ?Sized + Serialize,
+    {
+        let body_bytes = self
+            .encode_body(content_type, body)
+            .with_context(|| format!("Failed to encode body as '{content_type}'"))
+            .unwrap();
+
+        self.bytes(body_bytes.into()).content_type(content_type)
+    }
+
     /// For sending multipart forms.
     /// The payload is built using [`MultipartForm`](crate::multipart::MultipartForm) and [`Part`](crate::multipart::Part).
     ///
@@ -313,6 +513,7 @@ impl TestRequest {
     }
 
     /// Sends a payload as plain text, with the contents coming from a file.
+    #[track_caller]
     pub fn text_from_file<P>(self, path: P) -> Self
     where
         P: AsRef<Path>,
@@ -335,9 +536,79 @@ impl TestRequest {
         self
     }
 
+    /// Decodes the base64 given, and sets the raw bytes as the body of the request.
+    ///
+    /// Either the standard or URL safe alphabet is accepted,
+    /// padding is optional, and whitespace is ignored.
+    /// The content type is left unchanged.
+    ///
+    /// If the text given is not valid base64, then this will panic.
+    #[track_caller]
+    pub fn bytes_base64<S>(self, body_base64: S) -> Self
+    where
+        S: AsRef<str>,
+    {
+        let payload = decode_base64(body_base64.as_ref())
+            .context("Failed to decode request body from base64")
+            .unwrap();
+
+        self.bytes(payload.into())
+    }
+
+    /// Decodes the hex given, and sets the raw bytes as the body of the request.
+    ///
+    /// The content type is left unchanged.
+    ///
+    /// If the text given is not valid hex, then this will panic.
+    #[track_caller]
+    pub fn bytes_hex<S>(self, body_hex: S) -> Self
+    where
+        S: AsRef<str>,
+    {
+        let payload = decode_hex(body_hex.as_ref())
+            .context("Failed to decode request body from hex")
+            .unwrap();
+
+        self.bytes(payload.into())
+    }
+
+    /// Sends the body using chunked transfer encoding, split into chunks of 8 KiB,
+    /// instead of declaring its length up front.
+    ///
+    /// This is for testing how your application handles streamed request bodies,
+    /// and is best used with the real HTTP transport,
+    /// where the chunks are framed on the wire.
+    ///
+    /// Use [`TestRequest::chunk_size()`] to change the size of the chunks.
+    pub fn chunked(self) -> Self {
+        self.chunk_size(DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Sends the body using chunked transfer encoding, split into chunks of the size given in bytes.
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.body_framing.maybe_chunk_size = Some(chunk_size);
+        self
+    }
+
+    /// Sends a `Content-Length` header with the length given,
+    /// regardless of the size of the body actually sent.
+    ///
+    /// This is for negative tests of how your application handles malformed framing.
+    /// With the mock transport the whole body is still given to your application.
+    ///
+    /// Over the real HTTP transport, a body longer than the length declared is cut short.
+    /// A body shorter than the length declared leaves the server waiting for the rest,
+    /// so the request only completes if your application responds without reading it all
+    /// (such as from a read timeout).
+    pub fn content_length_mismatch(mut self, declared_length: u64) -> Self {
+        self.body_framing.maybe_declared_content_length = Some(declared_length);
+        self
+    }
+
     /// Reads the contents of the file as raw bytes, and sends it within the request.
     ///
     /// The content type is left unchanged, and no parsing of the file is done.
+    #[track_caller]
     pub fn bytes_from_file<P>(self, path: P) -> Self
     where
         P: AsRef<Path>,
@@ -356,6 +627,35 @@ impl TestRequest {
         self
     }
 
+    /// Sends this request without a `Content-Type` header,
+    /// removing any default content type from the `TestServer`.
+    ///
+    /// This is for testing endpoints which reject requests carrying a content type.
+    ///
+    /// Setting a body, such as with [`TestRequest::json()`], sets the content type again.
+    /// So call this after setting the body.
+    pub fn no_content_type(mut self) -> Self {
+        self.config.content_type = None;
+        self.config
+            .headers
+            .retain(|(header_name, _)| header_name != header::CONTENT_TYPE);
+        self
+    }
+
+    /// Sends this request without a body,
+    /// removing any body and `Content-Length` header set so far.
+    ///
+    /// If a body is set after calling this, then sending the request will fail.
+    pub fn no_body(mut self) -> Self {
+        self.body = None;
+        self.body_framing = BodyFraming::default();
+        self.is_body_forbidden = true;
+        self.config.headers.retain(|(header_name, _)| {
+            header_name != header::CONTENT_LENGTH && header_name != header::TRANSFER_ENCODING
+        });
+        self
+    }
+
     /// Adds a Cookie to be sent with this request.
     pub fn add_cookie(mut self, cookie: Cookie<'_>) -> Self {
         self.config.cookies.add(cookie.into_owned());
@@ -480,6 +780,7 @@ impl TestRequest {
     /// # Ok(()) }
     /// ```
     ///
+    #[track_caller]
     pub fn add_query_params<V>(mut self, query_params: V) -> Self
     where
         V: Serialize,
@@ -528,6 +829,61 @@ impl TestRequest {
         self
     }
 
+    /// Adds an OData `$filter` query option, such as `age gt 20`.
+    ///
+    /// The expression is percent encoded, including any spaces and quotes,
+    /// and the `$` of the option name is left as is.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum_test::TestServer;
+    ///
+    /// let app = Router::new();
+    /// let server = TestServer::new(app)?;
+    ///
+    /// // Sends `/people?$filter=name%20eq%20%27O%27%27Brien%27&$orderby=age%20desc&$top=10`
+    /// let response = server.get(&"/people")
+    ///     .odata_filter("name eq 'O''Brien'")
+    ///     .odata_orderby("age desc")
+    ///     .odata_top(10)
+    ///     .await;
+    /// #
+    /// # Ok(()) }
+    /// ```
+    pub fn odata_filter(self, filter: &str) -> Self {
+        self.add_raw_query_param(&build_odata_query_param("filter", filter))
+    }
+
+    /// Adds an OData `$orderby` query option, such as `name desc`.
+    pub fn odata_orderby(self, orderby: &str) -> Self {
+        self.add_raw_query_param(&build_odata_query_param("orderby", orderby))
+    }
+
+    /// Adds an OData `$select` query option, such as `name,age`.
+    pub fn odata_select(self, select: &str) -> Self {
+        self.add_raw_query_param(&build_odata_query_param("select", select))
+    }
+
+    /// Adds an OData `$top` query option, limiting the number of results.
+    pub fn odata_top(self, top: usize) -> Self {
+        self.add_raw_query_param(&build_odata_query_param("top", &top.to_string()))
+    }
+
+    /// Adds an OData `$skip` query option, skipping over the results given.
+    pub fn odata_skip(self, skip: usize) -> Self {
+        self.add_raw_query_param(&build_odata_query_param("skip", &skip.to_string()))
+    }
+
+    /// Adds the OData `$count=true` query option,
+    /// asking for the total number of results in `@odata.count`.
+    ///
+    /// See [`TestResponse::odata_count()`](crate::TestResponse::odata_count()) for reading it.
+    pub fn odata_count(self) -> Self {
+        self.add_raw_query_param(&build_odata_query_param("count", "true"))
+    }
+
     /// Clears all query params set,
     /// including any that came from the [`TestServer`](crate::TestServer).
     pub fn clear_query_params(mut self) -> Self {
@@ -535,6 +891,44 @@ impl TestRequest {
         self
     }
 
+    /// Replaces all query params set with those given,
+    /// including any that came from the [`TestServer`](crate::TestServer).
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum_test::TestServer;
+    ///
+    /// let app = Router::new();
+    /// let mut server = TestServer::new(app)?;
+    /// server.add_query_param("page", 1);
+    /// server.add_query_param("sort", "name");
+    ///
+    /// // Sends only `?page=5`.
+    /// let response = server.get(&"/users")
+    ///     .set_query_params(&[("page", 5)])
+    ///     .await;
+    /// #
+    /// # Ok(()) }
+    /// ```
+    pub fn set_query_params<V>(self, query_params: V) -> Self
+    where
+        V: Serialize,
+    {
+        self.clear_query_params().add_query_params(query_params)
+    }
+
+    /// Removes all query params with the key given,
+    /// including any that came from the [`TestServer`](crate::TestServer).
+    ///
+    /// This is useful for overriding a single query param set on the server,
+    /// by removing it, and then adding a new value.
+    pub fn remove_query_param(mut self, key: &str) -> Self {
+        self.config.query_params.remove(key);
+        self
+    }
+
     /// Adds a header to be sent with this request.
     ///
     /// ```rust
@@ -554,6 +948,7 @@ impl TestRequest {
     /// #
     /// # Ok(()) }
     /// ```
+    #[track_caller]
     pub fn add_header<N, V>(mut self, name: N, value: V) -> Self
     where
         N: TryInto<HeaderName>,
@@ -561,12 +956,18 @@ impl TestRequest {
         V: TryInto<HeaderValue>,
         V::Error: Debug,
     {
-        let header_name: HeaderName = name
-            .try_into()
-            .expect("Failed to convert header name to HeaderName");
-        let header_value: HeaderValue = value
-            .try_into()
-            .expect("Failed to convert header vlue to HeaderValue");
+        let header_name: HeaderName = name.try_into().expect_with(|| {
+            format!(
+                "Failed to convert header name to HeaderName, for request {}",
+                self.debug_request_format()
+            )
+        });
+        let header_value: HeaderValue = value.try_into().expect_with(|| {
+            format!(
+                "Failed to convert header value to HeaderValue, for request {}",
+                self.debug_request_format()
+            )
+        });
 
         self.config.headers.push((header_name, header_value));
         self
@@ -574,12 +975,18 @@ impl TestRequest {
 
     /// Adds an 'AUTHORIZATION' HTTP header to the request,
     /// with no internal formatting of what is given.
+    #[track_caller]
     pub fn authorization<T>(self, authorization_header: T) -> Self
     where
         T: AsRef<str>,
     {
         let authorization_header_value = HeaderValue::from_str(authorization_header.as_ref())
-            .expect("Cannot build Authorization HeaderValue from token");
+            .expect_with(|| {
+                format!(
+                    "Cannot build Authorization HeaderValue from token, for request {}",
+                    self.debug_request_format()
+                )
+            });
 
         self.add_header(header::AUTHORIZATION, authorization_header_value)
     }
@@ -594,56 +1001,296 @@ impl TestRequest {
         self.authorization(authorization_bearer_header_str)
     }
 
-    /// Clears all headers set.
-    pub fn clear_headers(mut self) -> Self {
-        self.config.headers = vec![];
-        self
+    /// Adds an 'ACCEPT' HTTP header to the request,
+    /// for the mime type given (i.e. `application/json`).
+    #[track_caller]
+    pub fn accept<T>(self, mime: T) -> Self
+    where
+        T: AsRef<str>,
+    {
+        let accept_header_value = HeaderValue::from_str(mime.as_ref()).expect_with(|| {
+            format!(
+                "Cannot build Accept HeaderValue from mime, for request {}",
+                self.debug_request_format()
+            )
+        });
+
+        self.add_header(header::ACCEPT, accept_header_value)
     }
 
-    /// Sets the scheme to use when making the request. i.e. http or https.
-    /// The default scheme is 'http'.
+    /// Adds an 'ACCEPT-LANGUAGE' HTTP header to the request,
+    /// for the language given (i.e. `de-DE`).
+    #[track_caller]
+    pub fn accept_language<T>(self, language: T) -> Self
+    where
+        T: AsRef<str>,
+    {
+        let accept_language_header_value =
+            HeaderValue::from_str(language.as_ref()).expect_with(|| {
+                format!(
+                    "Cannot build Accept-Language HeaderValue from language, for request {}",
+                    self.debug_request_format()
+                )
+            });
+
+        self.add_header(header::ACCEPT_LANGUAGE, accept_language_header_value)
+    }
+
+    /// Adds an 'ACCEPT-CHARSET' HTTP header to the request,
+    /// for the charset given (i.e. `utf-8`).
+    #[track_caller]
+    pub fn accept_charset<T>(self, charset: T) -> Self
+    where
+        T: AsRef<str>,
+    {
+        let accept_charset_header_value =
+            HeaderValue::from_str(charset.as_ref()).expect_with(|| {
+                format!(
+                    "Cannot build Accept-Charset HeaderValue from charset, for request {}",
+                    self.debug_request_format()
+                )
+            });
+
+        self.add_header(header::ACCEPT_CHARSET, accept_charset_header_value)
+    }
+
+    /// Sets the 'USER-AGENT' HTTP header of the request,
+    /// replacing any `User-Agent` header already set.
+    #[track_caller]
+    pub fn user_agent<T>(mut self, user_agent: T) -> Self
+    where
+        T: AsRef<str>,
+    {
+        let user_agent_header_value =
+            HeaderValue::from_str(user_agent.as_ref()).expect_with(|| {
+                format!(
+                    "Cannot build User-Agent HeaderValue from user agent, for request {}",
+                    self.debug_request_format()
+                )
+            });
+
+        self.config
+            .headers
+            .retain(|(header_name, _)| header_name != header::USER_AGENT);
+        self.add_header(header::USER_AGENT, user_agent_header_value)
+    }
+
+    /// Sets the headers sent by a real client, such as a browser or search engine crawler.
+    /// See [`BrowserProfile`](crate::BrowserProfile) for the profiles available.
+    ///
+    /// Headers in the profile replace any of the same name already set.
     ///
     /// ```rust
     /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
     /// #
     /// use axum::Router;
+    /// use axum_test::BrowserProfile;
     /// use axum_test::TestServer;
     ///
     /// let app = Router::new();
     /// let server = TestServer::new(app)?;
     ///
     /// let response = server
-    ///     .get(&"/my-end-point")
-    ///     .scheme(&"https")
+    ///     .get(&"/")
+    ///     .browser_profile(BrowserProfile::chrome())
     ///     .await;
     /// #
     /// # Ok(()) }
     /// ```
-    ///
-    pub fn scheme(mut self, scheme: &str) -> Self {
-        self.config
-            .full_request_url
-            .set_scheme(scheme)
-            .map_err(|_| anyhow!("Scheme '{scheme}' cannot be set to request"))
-            .unwrap();
+    pub fn browser_profile(mut self, profile: BrowserProfile) -> Self {
+        for (profile_header_name, _) in profile.headers() {
+            self.config
+                .headers
+                .retain(|(header_name, _)| header_name != profile_header_name);
+        }
+
+        self.config.headers.extend_from_slice(profile.headers());
         self
     }
 
-    /// Marks that this request is expected to always return a HTTP
-    /// status code within the 2xx range (200 to 299).
-    ///
-    /// If a code _outside_ of that range is returned,
-    /// then this will panic.
+    /// Sets the 'DATE' HTTP header of the request to the date given,
+    /// replacing any `Date` header already set.
     ///
-    /// ```rust
-    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
-    /// #
-    /// use axum::Json;
-    /// use axum::Router;
-    /// use axum::routing::put;
-    /// use serde_json::json;
+    /// If the server has a clock skew set, then it is added to this date.
+    pub fn date(mut self, date: OffsetDateTime) -> Self {
+        let date_header_value = self.build_date_header_value(date);
+
+        self.config
+            .headers
+            .retain(|(header_name, _)| header_name != header::DATE);
+        self.add_header(header::DATE, date_header_value)
+    }
+
+    /// Adds an 'IF-MODIFIED-SINCE' HTTP header to the request, for the date given.
     ///
-    /// use axum_test::TestServer;
+    /// If the server has a clock skew set, then it is added to this date.
+    pub fn if_modified_since(self, date: OffsetDateTime) -> Self {
+        let if_modified_since_header_value = self.build_date_header_value(date);
+
+        self.add_header(header::IF_MODIFIED_SINCE, if_modified_since_header_value)
+    }
+
+    fn build_date_header_value(&self, date: OffsetDateTime) -> HeaderValue {
+        let skewed_date = match self.config.clock_skew {
+            Some(clock_skew) => date + clock_skew,
+            None => date,
+        };
+
+        HeaderValue::from_str(&format_http_date(skewed_date))
+            .expect("Cannot build HeaderValue from date")
+    }
+
+    /// Sets the 'IDEMPOTENCY-KEY' HTTP header of the request to the key given,
+    /// replacing any idempotency key already set.
+    ///
+    /// The key is available on the response using [`TestResponse::idempotency_key()`](crate::TestResponse::idempotency_key()).
+    #[track_caller]
+    pub fn idempotency_key<K>(mut self, key: K) -> Self
+    where
+        K: AsRef<str>,
+    {
+        let idempotency_key_header_value = HeaderValue::from_str(key.as_ref()).expect_with(|| {
+            format!(
+                "Cannot build Idempotency-Key HeaderValue from key, for request {}",
+                self.debug_request_format()
+            )
+        });
+
+        self.config
+            .headers
+            .retain(|(header_name, _)| header_name != IDEMPOTENCY_KEY_HEADER);
+        self.add_header(IDEMPOTENCY_KEY_HEADER, idempotency_key_header_value)
+    }
+
+    /// Sets the 'IDEMPOTENCY-KEY' HTTP header of the request to a newly generated UUID.
+    ///
+    /// The key is available on the response using [`TestResponse::idempotency_key()`](crate::TestResponse::idempotency_key()).
+    #[track_caller]
+    pub fn random_idempotency_key(self) -> Self {
+        let key = self
+            .config
+            .rng
+            .lock()
+            .expect("Failed to lock random number generator, for idempotency key")
+            .next_uuid_v4();
+
+        self.idempotency_key(key)
+    }
+
+    /// Clears all headers set.
+    pub fn clear_headers(mut self) -> Self {
+        self.config.headers = vec![];
+        self
+    }
+
+    /// Sends this request without the CSRF token,
+    /// when the `TestServer` has [CSRF handling enabled](crate::TestServer::enable_csrf()).
+    ///
+    /// This is for testing requests missing the token are rejected.
+    pub fn without_csrf(mut self) -> Self {
+        self.config.maybe_csrf_token = None;
+        self
+    }
+
+    /// Sets the scheme to use when making the request. i.e. http or https.
+    /// The default scheme is 'http'.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum_test::TestServer;
+    ///
+    /// let app = Router::new();
+    /// let server = TestServer::new(app)?;
+    ///
+    /// let response = server
+    ///     .get(&"/my-end-point")
+    ///     .scheme(&"https")
+    ///     .await;
+    /// #
+    /// # Ok(()) }
+    /// ```
+    ///
+    #[track_caller]
+    pub fn scheme(mut self, scheme: &str) -> Self {
+        self.config
+            .full_request_url
+            .set_scheme(scheme)
+            .expect_with(|| {
+                format!(
+                    "Scheme '{scheme}' cannot be set, for request {}",
+                    self.debug_request_format()
+                )
+            });
+        self
+    }
+
+    /// Sets the HTTP version to send this request with.
+    ///
+    /// When using the HTTP transport, requests set to `Version::HTTP_2`
+    /// are sent over HTTP/2 with prior knowledge (without upgrading from HTTP/1.1).
+    /// With the mock transport, the version is passed to your application as is.
+    ///
+    /// **Defaults** to `Version::HTTP_11`.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum::routing::get;
+    /// use axum_test::TestServer;
+    /// use http::Version;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/ping", get(|| async { "pong!" }));
+    /// let server = TestServer::builder()
+    ///     .http_transport()
+    ///     .build(app)?;
+    ///
+    /// server
+    ///     .get(&"/ping")
+    ///     .http_version(Version::HTTP_2)
+    ///     .await
+    ///     .assert_http_version(Version::HTTP_2);
+    /// #
+    /// # Ok(()) }
+    /// ```
+    pub fn http_version(mut self, version: Version) -> Self {
+        self.config.version = version;
+        self
+    }
+
+    /// Connects with a receive buffer of the size given, in bytes,
+    /// for testing how your application handles slow consumers.
+    ///
+    /// The buffer is set on the socket, and on the client reading from it.
+    /// This is best used with [`TestRequest::stream()`], where pausing the stream
+    /// fills the small buffer quickly, and so the server has to handle the backpressure.
+    ///
+    /// The socket buffer is only set by the HTTP transport, for HTTP/1 requests.
+    /// The operating system may round the size up to its own minimum.
+    /// With any transport, it also sizes the chunks [`TestResponseStream`] holds before pushing back.
+    pub fn receive_buffer_size(mut self, receive_buffer_size: usize) -> Self {
+        self.maybe_receive_buffer_size = Some(receive_buffer_size);
+        self
+    }
+
+    /// Marks that this request is expected to always return a HTTP
+    /// status code within the 2xx range (200 to 299).
+    ///
+    /// If a code _outside_ of that range is returned,
+    /// then this will panic.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Json;
+    /// use axum::Router;
+    /// use axum::routing::put;
+    /// use serde_json::json;
+    ///
+    /// use axum_test::TestServer;
     ///
     /// let app = Router::new()
     ///     .route(&"/todo", put(|| async { unimplemented!() }));
@@ -676,22 +1323,166 @@ impl TestRequest {
         self.expect_state(ExpectedState::Failure)
     }
 
-    fn expect_state(mut self, expected_state: ExpectedState) -> Self {
+    /// Marks that this WebSocket request is expected to have the upgrade rejected,
+    /// with the server returning a status code other than `101 Switching Protocols`.
+    ///
+    /// If the server switches protocols, then this will panic.
+    /// The status code and body of the rejection can then be asserted on the response.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum::extract::WebSocketUpgrade;
+    /// use axum::response::IntoResponse;
+    /// use axum::routing::get;
+    /// use axum_test::TestServer;
+    /// use http::HeaderMap;
+    /// use http::StatusCode;
+    ///
+    /// async fn route_get_websocket(headers: HeaderMap, ws: WebSocketUpgrade) -> impl IntoResponse {
+    ///     if !headers.contains_key("authorization") {
+    ///         return (StatusCode::UNAUTHORIZED, "login required").into_response();
+    ///     }
+    ///
+    ///     ws.on_upgrade(|_socket| async {})
+    /// }
+    ///
+    /// let app = Router::new().route(&"/ws", get(route_get_websocket));
+    /// let server = TestServer::builder()
+    ///     .http_transport()
+    ///     .build(app)?;
+    ///
+    /// let response = server
+    ///     .get_websocket(&"/ws")
+    ///     .expect_upgrade_rejected()
+    ///     .await;
+    ///
+    /// response.assert_status_unauthorized();
+    /// response.assert_text("login required");
+    /// #
+    /// # Ok(()) }
+    /// ```
+    #[cfg(feature = "ws")]
+    pub fn expect_upgrade_rejected(self) -> Self {
+        self.expect_state(ExpectedState::UpgradeRejected)
+    }
+
+    /// Marks that this request is expected to return a `Content-Type` matching the one given.
+    /// It is checked when the response is received, panicking if it does not match.
+    ///
+    /// See [`TestResponse::assert_content_type()`](crate::TestResponse::assert_content_type())
+    /// for how content types are matched.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Json;
+    /// use axum::Router;
+    /// use axum::routing::get;
+    /// use axum_test::TestServer;
+    /// use serde_json::json;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/user", get(|| async { Json(json!({ "name": "Joe" })) }));
+    /// let server = TestServer::new(app)?;
+    ///
+    /// server.get(&"/user")
+    ///     .expect_content_type("application/json")
+    ///     .await;
+    /// #
+    /// # Ok(()) }
+    /// ```
+    pub fn expect_content_type(mut self, content_type: &str) -> Self {
+        self.config.expected_content_type = Some(content_type.to_string());
+        self
+    }
+
+    /// Turns off checking the `Content-Type` of the response,
+    /// for servers built with [`TestServerBuilder::expect_content_type()`](crate::TestServerBuilder::expect_content_type()).
+    pub fn expect_any_content_type(mut self) -> Self {
+        self.config.expected_content_type = None;
+        self
+    }
+
+    pub(crate) fn expect_state(mut self, expected_state: ExpectedState) -> Self {
         self.expected_state = expected_state;
         self
     }
 
+    /// Sends the request, returning an error if it could not be sent,
+    /// instead of panicking.
+    ///
+    /// This is useful for requests which are expected to fail at the transport,
+    /// such as connections dropped by a [`ChaosConfig`](crate::ChaosConfig).
+    /// Assertions on the response (such as [`TestRequest::expect_success()`]) will still panic.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum::routing::get;
+    /// use axum_test::ChaosConfig;
+    /// use axum_test::TestServer;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/todo", get(|| async { "buy milk" }));
+    /// let server = TestServer::builder()
+    ///     .with_chaos(ChaosConfig {
+    ///         dropped_connection_rate: 1.0,
+    ///         ..ChaosConfig::default()
+    ///     })
+    ///     .build(app)?;
+    ///
+    /// let result = server.get(&"/todo").try_send().await;
+    /// assert!(result.is_err());
+    /// #
+    /// # Ok(()) }
+    /// ```
+    pub async fn try_send(self) -> Result<TestResponse> {
+        self.send().await.context("Sending request failed")
+    }
+
     async fn send(self) -> Result<TestResponse> {
+        let (request, mut pending) = self.prepare().await?;
+
+        let http_response = pending.send(request).await?;
+        let (parts, response_body) = pending.receive_head(http_response)?;
+        let response_bytes = pending.read_body(response_body).await?;
+        pending.log_verbose(&parts, Some(response_bytes.len()));
+
+        pending.into_test_response(parts, response_bytes)
+    }
+
+    /// Builds the request to send, and the [`PendingResponse`] for handling what comes back.
+    ///
+    /// This is shared by [`TestRequest::send()`] and [`TestRequest::try_stream()`],
+    /// so both send the request in the same way.
+    async fn prepare(mut self) -> Result<(Request<Body>, PendingResponse)> {
+        if let Some(missing_feature) = self.maybe_missing_feature {
+            return Err(missing_feature.into());
+        }
+
         let debug_request_format = self.debug_request_format().to_string();
+        let in_flight = ServerSharedState::start_in_flight(
+            &self.server_state,
+            InFlightKind::Request,
+            debug_request_format.clone(),
+        )?;
+        self.config.add_csrf_token();
 
         let method = self.config.method;
-        let expected_state = self.expected_state;
-        let save_cookies = self.config.is_saving_cookies;
+        let max_recorded_request_body = self.config.max_recorded_request_body;
+
+        if let Some(readiness_check) = &self.config.maybe_readiness_check {
+            readiness_check.wait_until_ready().await?;
+        }
+
         let body = self.body.unwrap_or(Body::empty());
         let url =
             Self::build_url_query_params(self.config.full_request_url, &self.config.query_params);
 
-        let request = Self::build_request(
+        let mut request = Self::build_request(
             method.clone(),
             &url,
             body,
@@ -700,48 +1491,137 @@ impl TestRequest {
             self.config.headers,
             &debug_request_format,
         )?;
-
-        #[allow(unused_mut)] // Allowed for the `ws` use immediately after.
-        let mut http_response = self.transport.send(request).await?;
-
-        #[cfg(feature = "ws")]
-        let websockets = {
-            let maybe_on_upgrade = http_response
+        *request.version_mut() = self.config.version;
+
+        // A copy of what was sent is kept on the response,
+        // and requests with an idempotency key keep all of it, so they can be replayed.
+        let (request_parts, request_body) = request.into_parts();
+        let request_body = request_body.collect().await?.to_bytes();
+        if self.is_body_forbidden && !request_body.is_empty() {
+            return Err(anyhow!(
+                "Expected request to have no body, as `no_body()` was called, received {} bytes, for request {debug_request_format}",
+                request_body.len()
+            ));
+        }
+        let request_headers = request_parts.headers.clone();
+        let maybe_replayable_request =
+            request_headers
+                .contains_key(IDEMPOTENCY_KEY_HEADER)
+                .then(|| ReplayableRequest {
+                    headers: request_headers.clone(),
+                    body: request_body.clone(),
+                });
+        let recorded_request = RecordedRequest {
+            headers: request_headers,
+            maybe_body: (request_body.len() <= max_recorded_request_body)
+                .then(|| request_body.clone()),
+        };
+        let request = Request::from_parts(request_parts, Body::from(request_body));
+
+        let mut request = if self.body_framing == BodyFraming::default() {
+            request
+        } else {
+            self.body_framing.apply(request).await.with_context(|| {
+                format!("Failed to frame request body, for request {debug_request_format}")
+            })?
+        };
+        if self.config.is_capturing_raw_head {
+            request.extensions_mut().insert(CaptureRawResponseHead);
+        }
+        if let Some(receive_buffer_size) = self.maybe_receive_buffer_size {
+            request
                 .extensions_mut()
-                .remove::<hyper::upgrade::OnUpgrade>();
-            let transport_type = self.transport.transport_layer_type();
+                .insert(ReceiveBufferSize(receive_buffer_size));
+        }
+        if self.config.is_catching_panics {
+            add_catch_panic_marker(request.headers_mut());
+        }
 
-            crate::internals::TestResponseWebSocket {
-                maybe_on_upgrade,
-                transport_type,
-            }
+        let pending = PendingResponse {
+            debug_request_format,
+            server_state: self.server_state,
+            transport: self.transport,
+            maybe_in_flight: Some(in_flight),
+            method,
+            url,
+            maybe_replayable_request,
+            recorded_request,
+            expected_state: self.expected_state,
+            is_saving_cookies: self.config.is_saving_cookies,
+            response_validators: self.config.response_validators,
+            probes: self.config.probes,
+            data_mask: self.config.data_mask,
+            response_decoders: self.config.response_decoders,
+            is_verbose: self.config.is_verbose,
+            maybe_expected_content_type: self.config.expected_content_type,
+            max_buffered_body: self.config.max_buffered_body,
+            maybe_recorded_exchanges: self.config.maybe_recorded_exchanges,
+            started_at: Instant::now(),
+            received: ReceivedHead::default(),
         };
 
-        let (parts, response_body) = http_response.into_parts();
-        let response_bytes = response_body.collect().await?.to_bytes();
+        Ok((request, pending))
+    }
 
-        if save_cookies {
-            let cookie_headers = parts.headers.get_all(SET_COOKIE).into_iter();
-            ServerSharedState::add_cookies_by_header(&self.server_state, cookie_headers)?;
-        }
+    /// Sends the request, returning once the response head has been received,
+    /// with the body read as it arrives.
+    ///
+    /// The body can be paused and resumed, to test how your application handles slow consumers.
+    /// See [`TestResponseStream`] for more details.
+    ///
+    /// The status code and content type are checked once the head has been received.
+    /// Response validators are run, and the exchange is recorded,
+    /// when the whole body is read with [`TestResponseStream::read_to_end()`].
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum::routing::get;
+    /// use axum_test::TestServer;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/events", get(|| async { "data: hello\n\n" }));
+    /// let server = TestServer::builder()
+    ///     .http_transport()
+    ///     .build(app)?;
+    ///
+    /// let stream = server
+    ///     .get(&"/events")
+    ///     .receive_buffer_size(1024)
+    ///     .stream()
+    ///     .await;
+    ///
+    /// assert_eq!(stream.read_to_end().await, "data: hello\n\n");
+    /// #
+    /// # Ok(()) }
+    /// ```
+    pub async fn stream(self) -> TestResponseStream {
+        self.try_stream().await.unwrap()
+    }
 
-        let test_response = TestResponse::new(
-            method,
-            url,
-            parts,
-            response_bytes,
-            #[cfg(feature = "ws")]
-            websockets,
-        );
+    /// Sends the request, returning a [`TestResponseStream`] once the response head has been received,
+    /// or an error if the request could not be sent.
+    ///
+    /// See [`TestRequest::stream()`] for more details.
+    pub async fn try_stream(self) -> Result<TestResponseStream> {
+        let maybe_receive_buffer_size = self.maybe_receive_buffer_size;
+        let (request, mut pending) = self.prepare().await?;
 
-        // Assert if ok or not.
-        match expected_state {
-            ExpectedState::Success => test_response.assert_status_success(),
-            ExpectedState::Failure => test_response.assert_status_failure(),
-            ExpectedState::None => {}
-        }
+        let http_response = pending
+            .send(request)
+            .await
+            .context("Sending request failed")?;
+        let (parts, response_body) = pending.receive_head(http_response)?;
+        pending.log_verbose(&parts, None);
+        pending.assert_head(&parts);
 
-        Ok(test_response)
+        Ok(TestResponseStream::new(
+            pending,
+            parts,
+            response_body,
+            maybe_receive_buffer_size,
+        ))
     }
 
     fn build_url_query_params(mut url: Url, query_params: &QueryParamsStore) -> Url {
@@ -799,6 +1679,36 @@ impl TestRequest {
         Ok(request)
     }
 
+    fn encode_body<B>(&self, content_type: &str, body: &B) -> Result<Vec<u8>>
+    where
+        B: ?Sized + Serialize,
+    {
+        if let Some(encoder) = self.config.request_encoders.find(content_type) {
+            let body_value = serde_json::to_value(body)?;
+            return encoder(&body_value);
+        }
+
+        let essence = content_type_essence(content_type);
+        match essence.as_str() {
+            "application/json" => Ok(serde_json::to_vec(body)?),
+            essence if essence.ends_with("+json") => Ok(serde_json::to_vec(body)?),
+            #[cfg(feature = "yaml")]
+            "application/yaml" | "application/x-yaml" | "text/yaml" => {
+                Ok(serde_yaml::to_string(body)?.into_bytes())
+            }
+            #[cfg(feature = "msgpack")]
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => {
+                Ok(::rmp_serde::to_vec(body)?)
+            }
+            "application/x-www-form-urlencoded" => {
+                Ok(serde_urlencoded::to_string(body)?.into_bytes())
+            }
+            _ => Err(anyhow!(
+                "no encoder registered for Content-Type '{content_type}'"
+            )),
+        }
+    }
+
     fn debug_request_format(&self) -> RequestPathFormatter<'_> {
         RequestPathFormatter::new(
             &self.config.method,
@@ -811,7 +1721,8 @@ impl TestRequest {
 impl TryFrom<TestRequest> for Request<Body> {
     type Error = AnyhowError;
 
-    fn try_from(test_request: TestRequest) -> Result<Request<Body>> {
+    fn try_from(mut test_request: TestRequest) -> Result<Request<Body>> {
+        test_request.config.add_csrf_token();
         let debug_request_format = test_request.debug_request_format().to_string();
         let url = TestRequest::build_url_query_params(
             test_request.config.full_request_url,
@@ -819,7 +1730,7 @@ impl TryFrom<TestRequest> for Request<Body> {
         );
         let body = test_request.body.unwrap_or(Body::empty());
 
-        TestRequest::build_request(
+        let mut request = TestRequest::build_request(
             test_request.config.method,
             &url,
             body,
@@ -827,7 +1738,10 @@ impl TryFrom<TestRequest> for Request<Body> {
             test_request.config.cookies,
             test_request.config.headers,
             &debug_request_format,
-        )
+        )?;
+        *request.version_mut() = test_request.config.version;
+
+        Ok(request)
     }
 }
 
@@ -836,7 +1750,7 @@ impl IntoFuture for TestRequest {
     type IntoFuture = AutoFuture<TestResponse>;
 
     fn into_future(self) -> Self::IntoFuture {
-        AutoFuture::new(async { self.send().await.context("Sending request failed").unwrap() })
+        AutoFuture::new(async { self.try_send().await.unwrap() })
     }
 }
 
@@ -908,17 +1822,112 @@ mod test_content_type {
         // Build an application with a route.
         let app = Router::new().route("/content_type", get(get_content_type));
 
-        // Run the server.
+        // Run the server.
+        let server = TestServer::new(app).expect("Should create test server");
+
+        // Get the request.
+        let text = server
+            .get(&"/content_type")
+            .content_type(&"application/custom")
+            .await
+            .text();
+
+        assert_eq!(text, "application/custom");
+    }
+}
+
+#[cfg(test)]
+mod test_no_content_type {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+    use http::header::CONTENT_TYPE;
+    use http::HeaderMap;
+
+    async fn get_content_type(headers: HeaderMap) -> String {
+        headers
+            .get(CONTENT_TYPE)
+            .map(|h| h.to_str().unwrap().to_string())
+            .unwrap_or_else(|| "none".to_string())
+    }
+
+    #[tokio::test]
+    async fn it_should_remove_server_default_content_type() {
+        let app = Router::new().route("/content_type", get(get_content_type));
+        let server = TestServer::builder()
+            .default_content_type("application/json")
+            .build(app)
+            .expect("Should create test server");
+
+        server
+            .get("/content_type")
+            .no_content_type()
+            .await
+            .assert_text("none");
+    }
+
+    #[tokio::test]
+    async fn it_should_remove_content_type_set_on_body() {
+        let app = Router::new().route("/content_type", get(get_content_type));
+        let server = TestServer::new(app).expect("Should create test server");
+
+        server
+            .get("/content_type")
+            .text("hello")
+            .no_content_type()
+            .await
+            .assert_text("none");
+    }
+}
+
+#[cfg(test)]
+mod test_no_body {
+    use crate::TestServer;
+    use axum::body::Bytes;
+    use axum::routing::post;
+    use axum::Router;
+    use http::header::CONTENT_LENGTH;
+    use http::HeaderMap;
+
+    async fn post_body_info(headers: HeaderMap, body: Bytes) -> String {
+        let content_length = headers
+            .get(CONTENT_LENGTH)
+            .map(|h| h.to_str().unwrap().to_string())
+            .unwrap_or_else(|| "none".to_string());
+
+        format!("{content_length} {}", body.len())
+    }
+
+    #[tokio::test]
+    async fn it_should_remove_body_and_content_length() {
+        let app = Router::new().route("/body", post(post_body_info));
+        let server = TestServer::builder()
+            .http_transport()
+            .build(app)
+            .expect("Should create test server");
+
+        server
+            .post("/body")
+            .text("hello")
+            .add_header(CONTENT_LENGTH, 5)
+            .no_body()
+            .await
+            .assert_text("none 0");
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_when_body_set_after_no_body() {
+        let app = Router::new().route("/body", post(post_body_info));
         let server = TestServer::new(app).expect("Should create test server");
 
-        // Get the request.
-        let text = server
-            .get(&"/content_type")
-            .content_type(&"application/custom")
-            .await
-            .text();
+        let result = server
+            .post("/body")
+            .no_body()
+            .text("hello")
+            .try_send()
+            .await;
 
-        assert_eq!(text, "application/custom");
+        assert!(result.is_err());
     }
 }
 
@@ -1404,6 +2413,129 @@ mod test_form {
     }
 }
 
+#[cfg(test)]
+mod test_body_as {
+    use crate::TestServer;
+    use axum::routing::post;
+    use axum::Router;
+    use http::header;
+    use http::HeaderMap;
+    use serde::Serialize;
+    use serde_json::json;
+    use serde_json::Value;
+
+    #[derive(Serialize)]
+    struct User {
+        name: String,
+        age: u32,
+    }
+
+    async fn route_post_echo(headers: HeaderMap, body: String) -> String {
+        let content_type = headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+
+        format!("{content_type}\n{body}")
+    }
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new().route("/echo", post(route_post_echo));
+        let mut server = TestServer::new(app).unwrap();
+        server.register_encoder("application/vnd.my-user", |user: &Value| {
+            let name = user["name"].as_str().unwrap_or_default();
+            Ok(format!("{name}:{}", user["age"]).into_bytes())
+        });
+
+        server
+    }
+
+    fn new_user() -> User {
+        User {
+            name: "Joe".to_string(),
+            age: 42,
+        }
+    }
+
+    #[tokio::test]
+    async fn it_should_encode_using_registered_encoder() {
+        let server = new_test_server();
+
+        server
+            .post("/echo")
+            .body_as("application/vnd.my-user; version=2", &new_user())
+            .await
+            .assert_text("application/vnd.my-user; version=2\nJoe:42");
+    }
+
+    #[tokio::test]
+    async fn it_should_encode_json() {
+        let server = new_test_server();
+
+        server
+            .post("/echo")
+            .body_as("application/json", &new_user())
+            .await
+            .assert_text("application/json\n{\"name\":\"Joe\",\"age\":42}");
+    }
+
+    #[tokio::test]
+    async fn it_should_encode_json_suffix_content_types() {
+        let server = new_test_server();
+
+        server
+            .post("/echo")
+            .body_as("application/vnd.api+json", &json!({ "data": null }))
+            .await
+            .assert_text("application/vnd.api+json\n{\"data\":null}");
+    }
+
+    #[tokio::test]
+    async fn it_should_encode_form() {
+        let server = new_test_server();
+
+        server
+            .post("/echo")
+            .body_as("application/x-www-form-urlencoded", &new_user())
+            .await
+            .assert_text("application/x-www-form-urlencoded\nname=Joe&age=42");
+    }
+
+    #[cfg(feature = "yaml")]
+    #[tokio::test]
+    async fn it_should_encode_yaml() {
+        let server = new_test_server();
+
+        server
+            .post("/echo")
+            .body_as("application/yaml", &new_user())
+            .await
+            .assert_text("application/yaml\nname: Joe\nage: 42\n");
+    }
+
+    #[tokio::test]
+    async fn it_should_prefer_registered_encoder_over_built_in_encoders() {
+        let mut server = new_test_server();
+        server.register_encoder("application/json", |_| Ok(b"custom".to_vec()));
+
+        server
+            .post("/echo")
+            .body_as("application/json", &new_user())
+            .await
+            .assert_text("application/json\ncustom");
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_no_encoder_is_registered() {
+        let server = new_test_server();
+
+        let _ = server
+            .post("/echo")
+            .body_as("application/vnd.unknown", &new_user());
+    }
+}
+
 #[cfg(test)]
 mod test_bytes {
     use crate::TestServer;
@@ -1540,6 +2672,64 @@ mod test_bytes_from_file {
     }
 }
 
+#[cfg(test)]
+mod test_bytes_base64 {
+    use crate::TestServer;
+    use axum::routing::post;
+    use axum::Router;
+    use bytes::Bytes;
+
+    #[tokio::test]
+    async fn it_should_send_the_decoded_bytes() {
+        let app = Router::new().route("/echo", post(|body: Bytes| async move { body }));
+        let server = TestServer::new(app).unwrap();
+
+        server
+            .post("/echo")
+            .bytes_base64("3q2+7w==")
+            .await
+            .assert_body_hex_eq("deadbeef");
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_for_invalid_base64() {
+        let app = Router::new().route("/echo", post(|body: Bytes| async move { body }));
+        let server = TestServer::new(app).unwrap();
+
+        let _ = server.post("/echo").bytes_base64("not base64!");
+    }
+}
+
+#[cfg(test)]
+mod test_bytes_hex {
+    use crate::TestServer;
+    use axum::routing::post;
+    use axum::Router;
+    use bytes::Bytes;
+
+    #[tokio::test]
+    async fn it_should_send_the_decoded_bytes() {
+        let app = Router::new().route("/echo", post(|body: Bytes| async move { body }));
+        let server = TestServer::new(app).unwrap();
+
+        server
+            .post("/echo")
+            .bytes_hex("DEADBEEF")
+            .await
+            .assert_body_base64_eq("3q2+7w==");
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_for_invalid_hex() {
+        let app = Router::new().route("/echo", post(|body: Bytes| async move { body }));
+        let server = TestServer::new(app).unwrap();
+
+        let _ = server.post("/echo").bytes_hex("xyz");
+    }
+}
+
 #[cfg(test)]
 mod test_text {
     use crate::TestServer;
@@ -1800,75 +2990,258 @@ mod test_expect_success {
         let mut server = TestServer::new(app).expect("Should create test server");
         server.expect_failure();
 
-        // Get the request.
-        server.get(&"/ping").expect_success().await;
+        // Get the request.
+        server.get(&"/ping").expect_success().await;
+    }
+}
+
+#[cfg(test)]
+mod test_expect_failure {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+    use http::StatusCode;
+
+    #[tokio::test]
+    async fn it_should_not_panic_if_expect_failure_on_404() {
+        // Build an application with a route.
+        let app = Router::new();
+
+        // Run the server.
+        let server = TestServer::new(app).expect("Should create test server");
+
+        // Get the request.
+        server.get(&"/some_unknown_route").expect_failure().await;
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_if_success_is_returned() {
+        async fn get_ping() -> &'static str {
+            "pong!"
+        }
+
+        // Build an application with a route.
+        let app = Router::new().route("/ping", get(get_ping));
+
+        // Run the server.
+        let server = TestServer::new(app).expect("Should create test server");
+
+        // Get the request.
+        server.get(&"/ping").expect_failure().await;
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_on_other_2xx_status_code() {
+        async fn get_accepted() -> StatusCode {
+            StatusCode::ACCEPTED
+        }
+
+        // Build an application with a route.
+        let app = Router::new().route("/accepted", get(get_accepted));
+
+        // Run the server.
+        let server = TestServer::new(app).expect("Should create test server");
+
+        // Get the request.
+        server.get(&"/accepted").expect_failure().await;
+    }
+
+    #[tokio::test]
+    async fn it_should_should_override_what_test_server_has_set() {
+        // Build an application with a route.
+        let app = Router::new();
+
+        // Run the server.
+        let mut server = TestServer::new(app).expect("Should create test server");
+        server.expect_success();
+
+        // Get the request.
+        server.get(&"/some_unknown_route").expect_failure().await;
+    }
+}
+
+#[cfg(feature = "ws")]
+#[cfg(test)]
+mod test_expect_upgrade_rejected {
+    use crate::TestServer;
+    use axum::extract::WebSocketUpgrade;
+    use axum::response::IntoResponse;
+    use axum::response::Response;
+    use axum::routing::get;
+    use axum::Router;
+    use http::HeaderMap;
+    use http::StatusCode;
+
+    async fn route_get_websocket(headers: HeaderMap, ws: WebSocketUpgrade) -> Response {
+        if !headers.contains_key("authorization") {
+            return (StatusCode::UNAUTHORIZED, "login required").into_response();
+        }
+
+        ws.on_upgrade(|_socket| async {})
+    }
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new().route("/ws", get(route_get_websocket));
+
+        TestServer::builder().http_transport().build(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_return_the_rejection_when_upgrade_is_rejected() {
+        let server = new_test_server();
+
+        let response = server.get_websocket("/ws").expect_upgrade_rejected().await;
+
+        response.assert_status_unauthorized();
+        response.assert_text("login required");
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_upgrade_is_accepted() {
+        let server = new_test_server();
+
+        server
+            .get_websocket("/ws")
+            .authorization("secret")
+            .expect_upgrade_rejected()
+            .await;
+    }
+
+    #[tokio::test]
+    async fn it_should_override_expect_success_by_default() {
+        let app = Router::new().route("/ws", get(route_get_websocket));
+        let server = TestServer::builder()
+            .http_transport()
+            .expect_success_by_default()
+            .build(app)
+            .unwrap();
+
+        server
+            .get_websocket("/ws")
+            .expect_upgrade_rejected()
+            .await
+            .assert_status_unauthorized();
+    }
+}
+
+#[cfg(test)]
+mod test_expect_content_type {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Json;
+    use axum::Router;
+    use serde_json::json;
+
+    fn new_test_router() -> Router {
+        Router::new()
+            .route("/json", get(|| async { Json(json!({ "name": "Joe" })) }))
+            .route("/text", get(|| async { "hello" }))
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_when_content_type_matches() {
+        let server = TestServer::new(new_test_router()).unwrap();
+
+        server
+            .get("/json")
+            .expect_content_type("application/json")
+            .await;
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_content_type_differs() {
+        let server = TestServer::new(new_test_router()).unwrap();
+
+        server
+            .get("/text")
+            .expect_content_type("application/json")
+            .await;
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_server_default_differs() {
+        let server = TestServer::builder()
+            .expect_content_type("application/json")
+            .build(new_test_router())
+            .unwrap();
+
+        server.get("/text").await;
+    }
+
+    #[tokio::test]
+    async fn it_should_override_server_default() {
+        let server = TestServer::builder()
+            .expect_content_type("application/json")
+            .build(new_test_router())
+            .unwrap();
+
+        server.get("/json").await;
+        server.get("/text").expect_content_type("text/plain").await;
+        server.get("/text").expect_any_content_type().await;
     }
 }
 
 #[cfg(test)]
-mod test_expect_failure {
+mod test_max_buffered_body {
+    use crate::Error;
     use crate::TestServer;
+    use axum::body::Body;
     use axum::routing::get;
     use axum::Router;
-    use http::StatusCode;
-
-    #[tokio::test]
-    async fn it_should_not_panic_if_expect_failure_on_404() {
-        // Build an application with a route.
-        let app = Router::new();
-
-        // Run the server.
-        let server = TestServer::new(app).expect("Should create test server");
+    use bytes::Bytes;
+    use futures_util::stream;
+    use std::convert::Infallible;
 
-        // Get the request.
-        server.get(&"/some_unknown_route").expect_failure().await;
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .route("/small", get(|| async { "small" }))
+            .route("/large", get(|| async { "x".repeat(2048) }))
+            .route(
+                "/endless",
+                get(|| async {
+                    Body::from_stream(stream::repeat_with(|| {
+                        Ok::<_, Infallible>(Bytes::from_static(b"endless"))
+                    }))
+                }),
+            );
+
+        TestServer::builder()
+            .max_buffered_body(1024)
+            .build(app)
+            .unwrap()
     }
 
     #[tokio::test]
-    #[should_panic]
-    async fn it_should_panic_if_success_is_returned() {
-        async fn get_ping() -> &'static str {
-            "pong!"
-        }
-
-        // Build an application with a route.
-        let app = Router::new().route("/ping", get(get_ping));
-
-        // Run the server.
-        let server = TestServer::new(app).expect("Should create test server");
+    async fn it_should_read_bodies_within_the_limit() {
+        let server = new_test_server();
 
-        // Get the request.
-        server.get(&"/ping").expect_failure().await;
+        server.get("/small").await.assert_text("small");
     }
 
     #[tokio::test]
-    #[should_panic]
-    async fn it_should_panic_on_other_2xx_status_code() {
-        async fn get_accepted() -> StatusCode {
-            StatusCode::ACCEPTED
-        }
-
-        // Build an application with a route.
-        let app = Router::new().route("/accepted", get(get_accepted));
+    async fn it_should_fail_bodies_over_the_limit() {
+        let server = new_test_server();
 
-        // Run the server.
-        let server = TestServer::new(app).expect("Should create test server");
+        let error = server.get("/large").try_send().await.unwrap_err();
 
-        // Get the request.
-        server.get(&"/accepted").expect_failure().await;
+        assert!(matches!(
+            error.downcast_ref::<Error>(),
+            Some(Error::BodyTooLarge { limit: 1024 })
+        ));
     }
 
     #[tokio::test]
-    async fn it_should_should_override_what_test_server_has_set() {
-        // Build an application with a route.
-        let app = Router::new();
+    async fn it_should_stop_reading_endless_bodies() {
+        let server = new_test_server();
 
-        // Run the server.
-        let mut server = TestServer::new(app).expect("Should create test server");
-        server.expect_success();
+        let error = server.get("/endless").try_send().await.unwrap_err();
 
-        // Get the request.
-        server.get(&"/some_unknown_route").expect_failure().await;
+        assert!(format!("{error:#}").contains("maximum buffered size of 1024 bytes"));
     }
 }
 
@@ -2264,84 +3637,366 @@ mod test_clear_cookies {
         // Check it comes back.
         let response_text = server.get(&"/cookie").clear_cookies().await.text();
 
-        assert_eq!(response_text, "cookie-not-found");
+        assert_eq!(response_text, "cookie-not-found");
+    }
+
+    #[tokio::test]
+    async fn it_should_clear_cookies_added_to_test_server() {
+        let app = Router::new()
+            .route("/cookie", put(put_cookie))
+            .route("/cookie", get(get_cookie));
+        let mut server = TestServer::new(app).expect("Should create test server");
+
+        let cookie = Cookie::new(TEST_COOKIE_NAME, "my-custom-cookie");
+        server.add_cookie(cookie);
+
+        // Check it comes back.
+        let response_text = server.get(&"/cookie").clear_cookies().await.text();
+
+        assert_eq!(response_text, "cookie-not-found");
+    }
+}
+
+#[cfg(test)]
+mod test_add_header {
+    use super::*;
+    use crate::TestServer;
+    use axum::async_trait;
+    use axum::extract::FromRequestParts;
+    use axum::routing::get;
+    use axum::Router;
+    use http::request::Parts;
+    use http::HeaderName;
+    use http::HeaderValue;
+    use hyper::StatusCode;
+    use std::marker::Sync;
+
+    const TEST_HEADER_NAME: &'static str = &"test-header";
+    const TEST_HEADER_CONTENT: &'static str = &"Test header content";
+
+    struct TestHeader(Vec<u8>);
+
+    #[async_trait]
+    impl<S: Sync> FromRequestParts<S> for TestHeader {
+        type Rejection = (StatusCode, &'static str);
+
+        async fn from_request_parts(
+            parts: &mut Parts,
+            _state: &S,
+        ) -> Result<TestHeader, Self::Rejection> {
+            parts
+                .headers
+                .get(HeaderName::from_static(TEST_HEADER_NAME))
+                .map(|v| TestHeader(v.as_bytes().to_vec()))
+                .ok_or((StatusCode::BAD_REQUEST, "Missing test header"))
+        }
+    }
+
+    async fn ping_header(TestHeader(header): TestHeader) -> Vec<u8> {
+        header
+    }
+
+    #[tokio::test]
+    async fn it_should_send_header_added_to_request() {
+        // Build an application with a route.
+        let app = Router::new().route("/header", get(ping_header));
+
+        // Run the server.
+        let server = TestServer::new(app).expect("Should create test server");
+
+        // Send a request with the header
+        let response = server
+            .get(&"/header")
+            .add_header(
+                HeaderName::from_static(TEST_HEADER_NAME),
+                HeaderValue::from_static(TEST_HEADER_CONTENT),
+            )
+            .await;
+
+        // Check it sent back the right text
+        response.assert_text(TEST_HEADER_CONTENT)
+    }
+
+    #[tokio::test]
+    #[should_panic(
+        expected = "Failed to convert header name to HeaderName, for request GET http://localhost/header"
+    )]
+    async fn it_should_panic_with_request_when_header_name_is_invalid() {
+        let server = TestServer::new(Router::new()).expect("Should create test server");
+
+        let _ = server.get("/header").add_header("invalid header", "value");
+    }
+}
+
+#[cfg(test)]
+mod test_accept {
+    use crate::routes::echo_headers_json;
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+    use serde_json::json;
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new().route("/headers", get(echo_headers_json));
+
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_send_accept_header() {
+        let server = new_test_server();
+
+        server
+            .get("/headers")
+            .accept("application/json")
+            .await
+            .assert_json_contains(&json!({ "accept": "application/json" }));
+    }
+
+    #[tokio::test]
+    async fn it_should_send_accept_language_header() {
+        let server = new_test_server();
+
+        server
+            .get("/headers")
+            .accept_language("de-DE")
+            .await
+            .assert_json_contains(&json!({ "accept-language": "de-DE" }));
+    }
+
+    #[tokio::test]
+    async fn it_should_send_accept_charset_header() {
+        let server = new_test_server();
+
+        server
+            .get("/headers")
+            .accept_charset("utf-8")
+            .await
+            .assert_json_contains(&json!({ "accept-charset": "utf-8" }));
+    }
+}
+
+#[cfg(test)]
+mod test_user_agent {
+    use crate::routes::echo_headers_json;
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+    use serde_json::json;
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new().route("/headers", get(echo_headers_json));
+
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_send_user_agent_header() {
+        let server = new_test_server();
+
+        server
+            .get("/headers")
+            .user_agent("my-test-client/1.0")
+            .await
+            .assert_json_contains(&json!({ "user-agent": "my-test-client/1.0" }));
+    }
+
+    #[tokio::test]
+    async fn it_should_replace_user_agent_already_set() {
+        let server = new_test_server();
+
+        server
+            .get("/headers")
+            .user_agent("first")
+            .user_agent("second")
+            .await
+            .assert_json_contains(&json!({ "user-agent": "second" }));
+    }
+}
+
+#[cfg(test)]
+mod test_browser_profile {
+    use crate::routes::echo_headers_json;
+    use crate::BrowserProfile;
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+    use serde_json::json;
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new().route("/headers", get(echo_headers_json));
+
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_send_chrome_headers() {
+        let server = new_test_server();
+
+        server
+            .get("/headers")
+            .browser_profile(BrowserProfile::chrome())
+            .await
+            .assert_json_contains(&json!({
+                "accept-language": "en-US,en;q=0.9",
+                "sec-ch-ua-mobile": "?0",
+                "sec-ch-ua-platform": "\"Windows\"",
+            }));
+    }
+
+    #[tokio::test]
+    async fn it_should_send_curl_headers() {
+        let server = new_test_server();
+
+        server
+            .get("/headers")
+            .browser_profile(BrowserProfile::curl())
+            .await
+            .assert_json_contains(&json!({
+                "user-agent": "curl/8.5.0",
+                "accept": "*/*",
+            }));
+    }
+
+    #[tokio::test]
+    async fn it_should_replace_headers_already_set() {
+        let server = new_test_server();
+
+        server
+            .get("/headers")
+            .user_agent("my-test-client/1.0")
+            .accept("application/json")
+            .browser_profile(BrowserProfile::googlebot())
+            .await
+            .assert_json_contains(&json!({
+                "user-agent": "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)",
+                "accept": "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8",
+            }));
+    }
+}
+
+#[cfg(test)]
+mod test_date {
+    use crate::routes::echo_headers_json;
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+    use cookie::time::Duration as TimeDuration;
+    use cookie::time::OffsetDateTime;
+    use serde_json::json;
+
+    fn new_app() -> Router {
+        Router::new().route("/headers", get(echo_headers_json))
+    }
+
+    #[tokio::test]
+    async fn it_should_send_date_header() {
+        let server = TestServer::new(new_app()).unwrap();
+
+        server
+            .get("/headers")
+            .date(OffsetDateTime::from_unix_timestamp(1704164645).unwrap())
+            .await
+            .assert_json_contains(&json!({ "date": "Tue, 02 Jan 2024 03:04:05 GMT" }));
+    }
+
+    #[tokio::test]
+    async fn it_should_not_send_date_header_by_default() {
+        let server = TestServer::new(new_app()).unwrap();
+
+        let headers = server.get("/headers").await.json::<serde_json::Value>();
+        assert_eq!(headers.get("date"), None);
+    }
+
+    #[tokio::test]
+    async fn it_should_add_clock_skew_to_dates_given() {
+        let server = TestServer::builder()
+            .with_clock_skew(TimeDuration::hours(-1))
+            .build(new_app())
+            .unwrap();
+
+        server
+            .get("/headers")
+            .date(OffsetDateTime::from_unix_timestamp(1704164645).unwrap())
+            .if_modified_since(OffsetDateTime::from_unix_timestamp(1704078245).unwrap())
+            .await
+            .assert_json_contains(&json!({
+                "date": "Tue, 02 Jan 2024 02:04:05 GMT",
+                "if-modified-since": "Mon, 01 Jan 2024 02:04:05 GMT",
+            }));
     }
 
     #[tokio::test]
-    async fn it_should_clear_cookies_added_to_test_server() {
-        let app = Router::new()
-            .route("/cookie", put(put_cookie))
-            .route("/cookie", get(get_cookie));
-        let mut server = TestServer::new(app).expect("Should create test server");
-
-        let cookie = Cookie::new(TEST_COOKIE_NAME, "my-custom-cookie");
-        server.add_cookie(cookie);
+    async fn it_should_send_skewed_date_header_when_clock_skew_set() {
+        let server = TestServer::builder()
+            .with_clock_skew(TimeDuration::days(-2))
+            .build(new_app())
+            .unwrap();
 
-        // Check it comes back.
-        let response_text = server.get(&"/cookie").clear_cookies().await.text();
+        let headers = server.get("/headers").await.json::<serde_json::Value>();
+        let date = crate::internals::parse_http_date(headers["date"].as_str().unwrap()).unwrap();
+        let expected = OffsetDateTime::now_utc() - TimeDuration::days(2);
 
-        assert_eq!(response_text, "cookie-not-found");
+        assert!((date - expected).abs() < TimeDuration::minutes(1));
     }
 }
 
 #[cfg(test)]
-mod test_add_header {
-    use super::*;
+mod test_idempotency_key {
+    use crate::routes::echo_headers_json;
     use crate::TestServer;
-    use axum::async_trait;
-    use axum::extract::FromRequestParts;
     use axum::routing::get;
     use axum::Router;
-    use http::request::Parts;
-    use http::HeaderName;
-    use http::HeaderValue;
-    use hyper::StatusCode;
-    use std::marker::Sync;
+    use serde_json::json;
 
-    const TEST_HEADER_NAME: &'static str = &"test-header";
-    const TEST_HEADER_CONTENT: &'static str = &"Test header content";
+    fn new_test_server() -> TestServer {
+        let app = Router::new().route("/headers", get(echo_headers_json));
 
-    struct TestHeader(Vec<u8>);
+        TestServer::new(app).unwrap()
+    }
 
-    #[async_trait]
-    impl<S: Sync> FromRequestParts<S> for TestHeader {
-        type Rejection = (StatusCode, &'static str);
+    #[tokio::test]
+    async fn it_should_send_idempotency_key_given() {
+        let server = new_test_server();
 
-        async fn from_request_parts(
-            parts: &mut Parts,
-            _state: &S,
-        ) -> Result<TestHeader, Self::Rejection> {
-            parts
-                .headers
-                .get(HeaderName::from_static(TEST_HEADER_NAME))
-                .map(|v| TestHeader(v.as_bytes().to_vec()))
-                .ok_or((StatusCode::BAD_REQUEST, "Missing test header"))
-        }
-    }
+        let response = server.get("/headers").idempotency_key("abc-123").await;
 
-    async fn ping_header(TestHeader(header): TestHeader) -> Vec<u8> {
-        header
+        response.assert_json_contains(&json!({ "idempotency-key": "abc-123" }));
+        assert_eq!(response.idempotency_key(), "abc-123");
     }
 
     #[tokio::test]
-    async fn it_should_send_header_added_to_request() {
-        // Build an application with a route.
-        let app = Router::new().route("/header", get(ping_header));
-
-        // Run the server.
-        let server = TestServer::new(app).expect("Should create test server");
+    async fn it_should_replace_idempotency_key_already_set() {
+        let server = new_test_server();
 
-        // Send a request with the header
         let response = server
-            .get(&"/header")
-            .add_header(
-                HeaderName::from_static(TEST_HEADER_NAME),
-                HeaderValue::from_static(TEST_HEADER_CONTENT),
-            )
+            .get("/headers")
+            .idempotency_key("first")
+            .idempotency_key("second")
             .await;
 
-        // Check it sent back the right text
-        response.assert_text(TEST_HEADER_CONTENT)
+        response.assert_json_contains(&json!({ "idempotency-key": "second" }));
+    }
+
+    #[tokio::test]
+    async fn it_should_send_random_idempotency_key() {
+        let server = new_test_server();
+
+        let first = server.get("/headers").random_idempotency_key().await;
+        let second = server.get("/headers").random_idempotency_key().await;
+
+        first.assert_json_contains(&json!({ "idempotency-key": first.idempotency_key() }));
+        assert_eq!(first.idempotency_key().len(), 36);
+        assert_ne!(first.idempotency_key(), second.idempotency_key());
+    }
+
+    #[tokio::test]
+    async fn it_should_have_no_idempotency_key_by_default() {
+        let server = new_test_server();
+
+        let response = server.get("/headers").await;
+
+        assert_eq!(response.maybe_idempotency_key(), None);
     }
 }
 
@@ -2748,6 +4403,146 @@ mod test_add_raw_query_param {
     }
 }
 
+#[cfg(test)]
+mod test_odata {
+    use crate::TestServer;
+    use axum::extract::Query;
+    use axum::extract::RawQuery;
+    use axum::routing::get;
+    use axum::Router;
+    use std::collections::HashMap;
+
+    async fn get_raw_query(RawQuery(query): RawQuery) -> String {
+        query.unwrap_or_default()
+    }
+
+    async fn get_decoded_query(Query(params): Query<HashMap<String, String>>) -> String {
+        format!(
+            "{} | {} | {} | {} | {}",
+            params["$filter"], params["$select"], params["$skip"], params["$top"], params["$count"]
+        )
+    }
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new().route("/people", get(get_raw_query));
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_send_encoded_odata_query_options() {
+        let server = new_test_server();
+
+        server
+            .get("/people")
+            .odata_filter("name eq 'O''Brien'")
+            .odata_orderby("age desc")
+            .odata_top(10)
+            .await
+            .assert_text("$filter=name%20eq%20%27O%27%27Brien%27&$orderby=age%20desc&$top=10");
+    }
+
+    #[tokio::test]
+    async fn it_should_decode_on_the_server_as_the_original_values() {
+        let app = Router::new().route("/people", get(get_decoded_query));
+        let server = TestServer::new(app).unwrap();
+
+        server
+            .get("/people")
+            .odata_filter("city eq 'Zürich' and age gt 20")
+            .odata_select("name,age")
+            .odata_skip(20)
+            .odata_top(5)
+            .odata_count()
+            .await
+            .assert_text("city eq 'Zürich' and age gt 20 | name,age | 20 | 5 | true");
+    }
+}
+
+#[cfg(test)]
+mod test_from_curl_str {
+    use crate::TestRequest;
+    use crate::TestServer;
+    use axum::extract::Request;
+    use axum::routing::any;
+    use axum::Json;
+    use axum::Router;
+    use http_body_util::BodyExt;
+    use serde_json::json;
+    use serde_json::Value;
+
+    async fn route_echo(request: Request) -> Json<Value> {
+        let (parts, body) = request.into_parts();
+        let header_text = |name: &str| {
+            parts
+                .headers
+                .get(name)
+                .map(|value| value.to_str().unwrap().to_string())
+        };
+        let body_bytes = body.collect().await.unwrap().to_bytes();
+
+        Json(json!({
+            "method": parts.method.as_str(),
+            "uri": parts.uri.to_string(),
+            "content_type": header_text("content-type"),
+            "api_key": header_text("x-api-key"),
+            "cookie": header_text("cookie"),
+            "body": String::from_utf8_lossy(&body_bytes),
+        }))
+    }
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new().route("/users", any(route_echo));
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_send_json_curl_commands() {
+        let server = new_test_server();
+        let curl = r#"curl -X POST 'https://api.example.com/users' \
+            -H 'Content-Type: application/json' \
+            -H 'x-api-key: abc123' \
+            --data-raw '{"name":"Joe"}'"#;
+
+        let echo = TestRequest::from_curl_str(&server, curl)
+            .await
+            .json::<Value>();
+
+        assert_eq!(
+            echo,
+            json!({
+                "method": "POST",
+                "uri": "http://localhost/users",
+                "content_type": "application/json",
+                "api_key": "abc123",
+                "cookie": null,
+                "body": r#"{"name":"Joe"}"#,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn it_should_send_form_data_and_cookies() {
+        let server = new_test_server();
+
+        let echo = TestRequest::from_curl_str(&server, "curl -b 'session=abc' -d name=Joe /users")
+            .await
+            .json::<Value>();
+
+        assert_eq!(echo["method"], "POST");
+        assert_eq!(echo["content_type"], "application/x-www-form-urlencoded");
+        assert_eq!(echo["cookie"], "session=abc");
+        assert_eq!(echo["body"], "name=Joe");
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "Failed to parse curl command")]
+    async fn it_should_panic_on_unsupported_options() {
+        let server = new_test_server();
+
+        let _ = TestRequest::from_curl_str(&server, "curl -F file=@photo.png /users");
+    }
+}
+
 #[cfg(test)]
 mod test_add_query_param {
     use crate::TestServer;
@@ -2878,6 +4673,58 @@ mod test_clear_query_params {
     }
 }
 
+#[cfg(test)]
+mod test_set_query_params {
+    use crate::TestServer;
+    use axum::extract::RawQuery;
+    use axum::routing::get;
+    use axum::Router;
+
+    async fn get_raw_query(RawQuery(query): RawQuery) -> String {
+        query.unwrap_or_default()
+    }
+
+    #[tokio::test]
+    async fn it_should_replace_server_and_request_params() {
+        let app = Router::new().route("/query", get(get_raw_query));
+        let mut server = TestServer::new(app).unwrap();
+        server.add_query_param("page", 1);
+
+        server
+            .get("/query")
+            .add_query_param("sort", "name")
+            .set_query_params([("page", 5)])
+            .await
+            .assert_text("page=5");
+    }
+}
+
+#[cfg(test)]
+mod test_remove_query_param {
+    use crate::TestServer;
+    use axum::extract::RawQuery;
+    use axum::routing::get;
+    use axum::Router;
+
+    async fn get_raw_query(RawQuery(query): RawQuery) -> String {
+        query.unwrap_or_default()
+    }
+
+    #[tokio::test]
+    async fn it_should_override_single_server_param() {
+        let app = Router::new().route("/query", get(get_raw_query));
+        let mut server = TestServer::new(app).unwrap();
+        server.add_query_params([("page", "1"), ("sort", "name")]);
+
+        server
+            .get("/query")
+            .remove_query_param("page")
+            .add_query_param("page", 2)
+            .await
+            .assert_text("sort=name&page=2");
+    }
+}
+
 #[cfg(test)]
 mod test_scheme {
     use crate::TestServer;
@@ -3075,3 +4922,167 @@ mod test_multipart {
             .assert_json(&vec!["file is 6 bytes, text/plain".to_string()]);
     }
 }
+
+#[cfg(test)]
+mod test_chunked {
+    use crate::TestServer;
+    use axum::extract::Request;
+    use axum::routing::post;
+    use axum::Json;
+    use axum::Router;
+    use http::header;
+    use http_body_util::BodyExt;
+    use serde_json::json;
+    use serde_json::Value;
+
+    async fn route_post_framing(request: Request) -> Json<Value> {
+        let (parts, mut body) = request.into_parts();
+        let header_text = |name| {
+            parts
+                .headers
+                .get(name)
+                .map(|value: &http::HeaderValue| value.to_str().unwrap().to_string())
+        };
+
+        let mut num_frames = 0;
+        let mut body_bytes = Vec::new();
+        while let Some(frame) = body.frame().await {
+            if let Ok(data) = frame.unwrap().into_data() {
+                num_frames += 1;
+                body_bytes.extend_from_slice(&data);
+            }
+        }
+
+        Json(json!({
+            "transfer_encoding": header_text(header::TRANSFER_ENCODING),
+            "content_length": header_text(header::CONTENT_LENGTH),
+            "num_frames": num_frames,
+            "body": String::from_utf8(body_bytes).unwrap(),
+        }))
+    }
+
+    fn new_test_router() -> Router {
+        Router::new().route("/framing", post(route_post_framing))
+    }
+
+    #[tokio::test]
+    async fn it_should_send_chunks_with_mock_transport() {
+        let server = TestServer::new(new_test_router()).unwrap();
+
+        let framing = server
+            .post("/framing")
+            .text("0123456789")
+            .chunk_size(4)
+            .await
+            .json::<Value>();
+
+        assert_eq!(framing["transfer_encoding"], "chunked");
+        assert_eq!(framing["content_length"], Value::Null);
+        assert_eq!(framing["num_frames"], 3);
+        assert_eq!(framing["body"], "0123456789");
+    }
+
+    #[tokio::test]
+    async fn it_should_send_chunked_with_http_transport() {
+        let server = TestServer::builder()
+            .http_transport()
+            .build(new_test_router())
+            .unwrap();
+
+        let framing = server
+            .post("/framing")
+            .text("0123456789")
+            .chunked()
+            .await
+            .json::<Value>();
+
+        assert_eq!(framing["transfer_encoding"], "chunked");
+        assert_eq!(framing["content_length"], Value::Null);
+        assert_eq!(framing["body"], "0123456789");
+    }
+
+    #[tokio::test]
+    async fn it_should_send_declared_content_length_with_mock_transport() {
+        let server = TestServer::new(new_test_router()).unwrap();
+
+        let framing = server
+            .post("/framing")
+            .text("0123456789")
+            .content_length_mismatch(100)
+            .await
+            .json::<Value>();
+
+        assert_eq!(framing["content_length"], "100");
+        assert_eq!(framing["body"], "0123456789");
+    }
+
+    #[tokio::test]
+    async fn it_should_cut_long_bodies_with_http_transport() {
+        let server = TestServer::builder()
+            .http_transport()
+            .build(new_test_router())
+            .unwrap();
+
+        let framing = server
+            .post("/framing")
+            .text("0123456789")
+            .content_length_mismatch(4)
+            .await
+            .json::<Value>();
+
+        assert_eq!(framing["content_length"], "4");
+        assert_eq!(framing["body"], "0123");
+    }
+}
+
+#[cfg(test)]
+mod test_receive_buffer_size {
+    use axum::routing::get;
+    use axum::Router;
+
+    use crate::TestServer;
+
+    const BODY_SIZE: usize = 256 * 1024;
+
+    fn new_app() -> Router {
+        Router::new().route("/large", get(|| async { "a".repeat(BODY_SIZE) }))
+    }
+
+    #[tokio::test]
+    async fn it_should_receive_the_whole_body_with_http_transport() {
+        let server = TestServer::builder()
+            .http_transport()
+            .build(new_app())
+            .unwrap();
+
+        let response = server.get("/large").receive_buffer_size(1024).await;
+
+        assert_eq!(response.as_bytes().len(), BODY_SIZE);
+        assert_eq!(response.maybe_raw_head(), None);
+    }
+
+    #[tokio::test]
+    async fn it_should_be_ignored_with_mock_transport() {
+        let server = TestServer::builder()
+            .mock_transport()
+            .build(new_app())
+            .unwrap();
+
+        let response = server.get("/large").receive_buffer_size(1024).await;
+
+        assert_eq!(response.as_bytes().len(), BODY_SIZE);
+    }
+
+    #[tokio::test]
+    async fn it_should_still_capture_the_raw_head() {
+        let server = TestServer::builder()
+            .http_transport()
+            .capture_raw_head()
+            .build(new_app())
+            .unwrap();
+
+        let response = server.get("/large").receive_buffer_size(1024).await;
+
+        assert!(response.raw_head().starts_with("HTTP/1.1 200 OK"));
+    }
+}
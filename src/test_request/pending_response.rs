@@ -0,0 +1,271 @@
+use anyhow::anyhow;
+use anyhow::Context;
+use anyhow::Error as AnyhowError;
+use anyhow::Result;
+use axum::body::Body;
+use bytes::Bytes;
+use http::header;
+use http::header::SET_COOKIE;
+use http::response::Parts;
+use http::Method;
+use http::Request;
+use http::Response;
+use http_body_util::BodyExt;
+use http_body_util::LengthLimitError;
+use http_body_util::Limited;
+use mime::Mime;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Instant;
+use url::Url;
+
+use crate::internals::format_request_log_line;
+use crate::internals::take_panic_message;
+use crate::internals::DataMask;
+use crate::internals::ExpectWith;
+use crate::internals::ExpectedState;
+use crate::internals::InFlightHandle;
+use crate::internals::ProbeReading;
+use crate::internals::RawResponseHead;
+use crate::internals::RecordedRequest;
+use crate::internals::ReplayableRequest;
+use crate::internals::ResponseDecoders;
+use crate::internals::StatusCodeFormatter;
+use crate::test_response::is_content_type_match;
+use crate::transport_layer::TransportLayer;
+use crate::Error;
+use crate::ProbeHandle;
+use crate::RecordedExchange;
+use crate::ResponseValidator;
+use crate::ServerSharedState;
+use crate::TestResponse;
+
+/// A request which has been built and is ready to send,
+/// holding everything needed to handle its response.
+///
+/// This is shared by sending and streaming requests,
+/// so both handle the request and its response in the same way.
+#[derive(Debug)]
+pub struct PendingResponse {
+    pub debug_request_format: String,
+    pub server_state: Arc<Mutex<ServerSharedState>>,
+    pub transport: Arc<dyn TransportLayer>,
+    pub maybe_in_flight: Option<InFlightHandle>,
+    pub method: Method,
+    pub url: Url,
+    pub maybe_replayable_request: Option<ReplayableRequest>,
+    pub recorded_request: RecordedRequest,
+    pub expected_state: ExpectedState,
+    pub is_saving_cookies: bool,
+    pub response_validators: Vec<ResponseValidator>,
+    pub probes: Vec<ProbeHandle>,
+    pub data_mask: Arc<DataMask>,
+    pub response_decoders: Arc<ResponseDecoders>,
+    pub is_verbose: bool,
+    pub maybe_expected_content_type: Option<String>,
+    pub max_buffered_body: Option<usize>,
+    pub maybe_recorded_exchanges: Option<Arc<Mutex<Vec<RecordedExchange>>>>,
+    pub started_at: Instant,
+
+    pub received: ReceivedHead,
+}
+
+/// What is taken from the head of the response, for building the `TestResponse`.
+#[derive(Debug, Default)]
+pub struct ReceivedHead {
+    probe_readings: Vec<ProbeReading>,
+    maybe_panic_message: Option<String>,
+    maybe_raw_head: Option<String>,
+    #[cfg(feature = "matched-route")]
+    maybe_matched_route: Option<String>,
+    #[cfg(feature = "ws")]
+    maybe_websockets: Option<crate::internals::TestResponseWebSocket>,
+}
+
+impl PendingResponse {
+    /// Sends the request given, returning the response once its head has been received.
+    pub async fn send(&mut self, request: Request<Body>) -> Result<Response<Body>> {
+        for probe in &self.probes {
+            probe.before_request();
+        }
+
+        self.started_at = Instant::now();
+        Ok(self.transport.send(request).await?)
+    }
+
+    /// Takes what the crate needs from the head of the response,
+    /// and updates the cookies and CSRF token held by the server.
+    pub fn receive_head(&mut self, http_response: Response<Body>) -> Result<(Parts, Body)> {
+        let (mut parts, response_body) = http_response.into_parts();
+
+        #[cfg(feature = "ws")]
+        {
+            self.received.maybe_websockets = Some(crate::internals::TestResponseWebSocket {
+                maybe_on_upgrade: parts.extensions.remove::<hyper::upgrade::OnUpgrade>(),
+                transport_type: self.transport.transport_layer_type(),
+                server_state: self.server_state.clone(),
+            });
+        }
+
+        self.received.maybe_panic_message = take_panic_message(&mut parts.headers);
+        #[cfg(feature = "matched-route")]
+        {
+            self.received.maybe_matched_route = crate::take_matched_route(&mut parts.headers);
+        }
+        self.received.maybe_raw_head = parts
+            .extensions
+            .remove::<RawResponseHead>()
+            .map(|RawResponseHead(raw_head)| String::from_utf8_lossy(&raw_head).to_string());
+        self.received.probe_readings = self.probes.iter().map(ProbeHandle::after_request).collect();
+
+        ServerSharedState::update_csrf_token(&self.server_state, &parts.headers)?;
+
+        let is_authenticating = ServerSharedState::is_authenticating(&self.server_state)?;
+        if self.is_saving_cookies || is_authenticating {
+            let cookie_headers = parts.headers.get_all(SET_COOKIE).into_iter();
+            ServerSharedState::add_cookies_by_header(&self.server_state, cookie_headers)?;
+        }
+
+        Ok((parts, response_body))
+    }
+
+    /// Reads the whole of the response body, up to the `max_buffered_body` limit if there is one.
+    pub async fn read_body(&self, response_body: Body) -> Result<Bytes> {
+        let debug_request_format = &self.debug_request_format;
+
+        let response_bytes = match self.max_buffered_body {
+            Some(limit) => Limited::new(response_body, limit)
+                .collect()
+                .await
+                .map_err(|error| {
+                    if error.is::<LengthLimitError>() {
+                        AnyhowError::from(Error::BodyTooLarge { limit })
+                    } else {
+                        anyhow!(error)
+                    }
+                })
+                .with_context(|| {
+                    format!("Reading response body, for request {debug_request_format}")
+                })?
+                .to_bytes(),
+            None => response_body.collect().await?.to_bytes(),
+        };
+
+        Ok(response_bytes)
+    }
+
+    /// Prints the request and response, when verbose logging is turned on.
+    ///
+    /// The body size is `None` for streamed responses, where it is not yet known.
+    pub fn log_verbose(&self, parts: &Parts, maybe_body_size: Option<usize>) {
+        if self.is_verbose {
+            let log_line = format_request_log_line(
+                &self.method,
+                &self.url,
+                parts.status,
+                self.started_at.elapsed(),
+                maybe_body_size,
+            );
+            eprintln!("{log_line}");
+        }
+    }
+
+    /// Asserts the status code and content type are as expected, using only the head of the response.
+    ///
+    /// This is for streamed responses, where the body is not held.
+    pub fn assert_head(&self, parts: &Parts) {
+        let debug_request_format = &self.debug_request_format;
+        let is_success = parts.status.is_success();
+
+        match self.expected_state {
+            ExpectedState::Success if !is_success => {
+                panic!(
+                    "Expect status code within 2xx range, received {}, for request {debug_request_format}",
+                    StatusCodeFormatter(parts.status)
+                );
+            }
+            ExpectedState::Failure if is_success => {
+                panic!(
+                    "Expect status code outside 2xx range, received {}, for request {debug_request_format}",
+                    StatusCodeFormatter(parts.status)
+                );
+            }
+            _ => {}
+        }
+
+        if let Some(expected_content_type) = &self.maybe_expected_content_type {
+            let expected_mime = expected_content_type
+                .parse::<Mime>()
+                .expect_with(|| {
+                    format!("Failed to parse expected content type '{expected_content_type}', for request {debug_request_format}")
+                });
+            let maybe_content_type = parts
+                .headers
+                .get(header::CONTENT_TYPE)
+                .and_then(|content_type| content_type.to_str().ok());
+            let is_match = maybe_content_type
+                .is_some_and(|content_type| is_content_type_match(content_type, &expected_mime));
+
+            if !is_match {
+                panic!(
+                    "Expected content type '{expected_content_type}', received {maybe_content_type:?}, for request {debug_request_format}"
+                );
+            }
+        }
+    }
+
+    /// Builds the `TestResponse` from the whole of the response,
+    /// recording it and running the response validators and expectations on it.
+    pub fn into_test_response(self, parts: Parts, response_bytes: Bytes) -> Result<TestResponse> {
+        let debug_request_format = self.debug_request_format;
+        let received = self.received;
+
+        let test_response = TestResponse::new(
+            self.method,
+            self.url,
+            self.maybe_replayable_request,
+            self.recorded_request,
+            parts,
+            response_bytes,
+            #[cfg(feature = "ws")]
+            received
+                .maybe_websockets
+                .expect("The head of the response should be received before the response is built"),
+        )
+        .with_probe_readings(received.probe_readings)
+        .with_data_mask(self.data_mask)
+        .with_response_decoders(self.response_decoders)
+        .with_panic_message(received.maybe_panic_message)
+        .with_raw_head(received.maybe_raw_head);
+        #[cfg(feature = "matched-route")]
+        let test_response = test_response.with_matched_route(received.maybe_matched_route);
+
+        if let Some(recorded_exchanges) = &self.maybe_recorded_exchanges {
+            recorded_exchanges
+                .lock()
+                .map_err(|err| anyhow!("Failed to lock recorded exchanges, {err:?}"))?
+                .push(RecordedExchange::from_response(&test_response));
+        }
+
+        for response_validator in &self.response_validators {
+            if let Err(error) = response_validator.validate(&test_response) {
+                panic!("Response validator failed, {error:#}, for request {debug_request_format}");
+            }
+        }
+
+        // Assert if ok or not.
+        match self.expected_state {
+            ExpectedState::Success => test_response.assert_status_success(),
+            ExpectedState::Failure => test_response.assert_status_failure(),
+            #[cfg(feature = "ws")]
+            ExpectedState::UpgradeRejected => test_response.assert_upgrade_rejected(),
+            ExpectedState::None => {}
+        }
+
+        if let Some(expected_content_type) = &self.maybe_expected_content_type {
+            test_response.assert_content_type(expected_content_type);
+        }
+
+        Ok(test_response)
+    }
+}
@@ -1,21 +1,62 @@
+use cookie::time::Duration as TimeDuration;
 use cookie::CookieJar;
 use http::HeaderName;
 use http::HeaderValue;
 use http::Method;
+use http::Version;
+use std::sync::Arc;
+use std::sync::Mutex;
 use url::Url;
 
+use crate::internals::CsrfToken;
+use crate::internals::DataMask;
 use crate::internals::ExpectedState;
 use crate::internals::QueryParamsStore;
+use crate::internals::ReadinessCheck;
+use crate::internals::RequestEncoders;
+use crate::internals::ResponseDecoders;
+use crate::internals::SeededRng;
+use crate::ProbeHandle;
+use crate::RecordedExchange;
+use crate::ResponseValidator;
 
 #[derive(Debug, Clone)]
 pub struct TestRequestConfig {
     pub is_saving_cookies: bool,
     pub expected_state: ExpectedState,
     pub content_type: Option<String>,
+    pub expected_content_type: Option<String>,
     pub full_request_url: Url,
     pub method: Method,
+    pub version: Version,
+    pub response_validators: Vec<ResponseValidator>,
+    pub probes: Vec<ProbeHandle>,
+    pub data_mask: Arc<DataMask>,
+    pub response_decoders: Arc<ResponseDecoders>,
+    pub request_encoders: Arc<RequestEncoders>,
+    pub clock_skew: Option<TimeDuration>,
+    pub rng: Arc<Mutex<SeededRng>>,
+    pub maybe_readiness_check: Option<Arc<ReadinessCheck>>,
+    pub maybe_csrf_token: Option<CsrfToken>,
+    pub is_verbose: bool,
+    pub max_buffered_body: Option<usize>,
+    pub max_recorded_request_body: usize,
+    pub is_catching_panics: bool,
+    pub is_capturing_raw_head: bool,
+    pub maybe_recorded_exchanges: Option<Arc<Mutex<Vec<RecordedExchange>>>>,
 
     pub cookies: CookieJar,
     pub query_params: QueryParamsStore,
     pub headers: Vec<(HeaderName, HeaderValue)>,
 }
+
+impl TestRequestConfig {
+    /// Moves the CSRF token, if there is one, into the cookies and headers to send.
+    pub fn add_csrf_token(&mut self) {
+        if let Some(csrf_token) = self.maybe_csrf_token.take() {
+            self.cookies.add(csrf_token.cookie);
+            self.headers
+                .push((csrf_token.header_name, csrf_token.header_value));
+        }
+    }
+}
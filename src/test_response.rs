@@ -1,13 +1,67 @@
+use crate::internals::check;
+use crate::internals::check_eq;
+use crate::internals::check_serialized_eq;
+use crate::internals::check_text_eq;
+use crate::internals::content_type_essence;
+use crate::internals::decode_base64;
+use crate::internals::decode_hex;
+use crate::internals::encode_base64;
+use crate::internals::encode_hex;
+use crate::internals::find_hal_link;
+use crate::internals::find_json_approx_mismatch;
+use crate::internals::format_canonical_json;
 use crate::internals::format_status_code_range;
-use crate::internals::DebugResponseBody;
+use crate::internals::guess_mime_from_extension;
+use crate::internals::is_text_pattern_match;
+#[cfg(all(
+    feature = "dyn-features",
+    not(all(feature = "yaml", feature = "msgpack", feature = "ws"))
+))]
+use crate::internals::missing_feature_error;
+use crate::internals::parse_http_date;
+use crate::internals::parse_link_header;
+use crate::internals::resolve_relative_url;
+use crate::internals::DataMask;
+use crate::internals::DebugResponseContent;
+use crate::internals::ExpectWith;
+use crate::internals::FullResponseBody;
+use crate::internals::OrPanic;
+use crate::internals::ProbeReading;
+use crate::internals::RecordedRequest;
+use crate::internals::ReplayableRequest;
 use crate::internals::RequestPathFormatter;
+use crate::internals::ResponseDecoders;
 use crate::internals::StatusCodeFormatter;
 use crate::internals::TryIntoRangeBounds;
+#[cfg(feature = "multipart-echo")]
+use crate::routes::MultipartEchoPart;
+use crate::AssertionBundle;
+use crate::AssertionError;
+use crate::FileKind;
+#[cfg(feature = "html")]
+use crate::HtmlDocument;
+#[cfg(feature = "jsonapi")]
+use crate::JsonApiLinks;
+use crate::JsonTolerance;
+#[cfg(feature = "rejections")]
+use crate::KnownRejection;
+use crate::LinkEntry;
+use crate::ProblemDetails;
+use crate::RecordedExchange;
+use crate::TestRequest;
+use crate::TestServer;
+#[cfg(feature = "archives")]
+use crate::ZipArchive;
 use anyhow::Context;
-use assert_json_diff::assert_json_include;
+use assert_json_diff::assert_json_matches_no_panic;
+use assert_json_diff::CompareMode;
+use assert_json_diff::Config as JsonDiffConfig;
 use bytes::Bytes;
+use cookie::time::OffsetDateTime;
 use cookie::Cookie;
 use cookie::CookieJar;
+use encoding_rs::Encoding;
+use http::header;
 use http::header::HeaderName;
 use http::header::SET_COOKIE;
 use http::response::Parts;
@@ -15,6 +69,10 @@ use http::HeaderMap;
 use http::HeaderValue;
 use http::Method;
 use http::StatusCode;
+use http::Version;
+use mime::Mime;
+#[cfg(feature = "regex")]
+use regex::Regex;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use serde_json::Value;
@@ -27,14 +85,20 @@ use std::io::BufReader;
 use std::ops::RangeBounds;
 use url::Url;
 
-#[cfg(feature = "pretty-assertions")]
-use pretty_assertions::{assert_eq, assert_ne};
+#[cfg(feature = "etag")]
+use crate::HashAlgorithm;
 
 #[cfg(feature = "ws")]
 use crate::internals::TestResponseWebSocket;
 #[cfg(feature = "ws")]
 use crate::TestWebSocket;
 use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+const DEPRECATION_HEADER: &str = "deprecation";
+const SUNSET_HEADER: &str = "sunset";
+pub(crate) const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
 
 ///
 /// The `TestResponse` is the result of a request created using a [`TestServer`](crate::TestServer).
@@ -138,9 +202,20 @@ pub struct TestResponse {
 
     /// This is the actual url that was used for the request.
     full_request_url: Url,
+    maybe_replayable_request: Option<ReplayableRequest>,
+    recorded_request: RecordedRequest,
     headers: HeaderMap<HeaderValue>,
     status_code: StatusCode,
+    version: Version,
     response_body: Bytes,
+    probe_readings: Vec<ProbeReading>,
+    data_mask: Arc<DataMask>,
+    response_decoders: Arc<ResponseDecoders>,
+    maybe_panic_message: Option<String>,
+    maybe_raw_head: Option<String>,
+
+    #[cfg(feature = "matched-route")]
+    maybe_matched_route: Option<String>,
 
     #[cfg(feature = "ws")]
     websockets: TestResponseWebSocket,
@@ -150,6 +225,8 @@ impl TestResponse {
     pub(crate) fn new(
         method: Method,
         full_request_url: Url,
+        maybe_replayable_request: Option<ReplayableRequest>,
+        recorded_request: RecordedRequest,
         parts: Parts,
         response_body: Bytes,
 
@@ -158,16 +235,131 @@ impl TestResponse {
         Self {
             method,
             full_request_url,
+            maybe_replayable_request,
+            recorded_request,
             headers: parts.headers,
             status_code: parts.status,
+            version: parts.version,
             response_body,
+            probe_readings: Vec::new(),
+            data_mask: Arc::default(),
+            response_decoders: Arc::default(),
+            maybe_panic_message: None,
+            maybe_raw_head: None,
+
+            #[cfg(feature = "matched-route")]
+            maybe_matched_route: None,
 
             #[cfg(feature = "ws")]
             websockets,
         }
     }
 
-    /// Returns the underlying response, extracted as a UTF-8 string.
+    /// Asserts the path sent for the request, after encoding, matches the one given.
+    ///
+    /// Unicode characters in paths are percent-encoded as UTF-8,
+    /// so a request to `/café` is sent as `/caf%C3%A9`.
+    #[track_caller]
+    pub fn assert_request_path_encoded(&self, expected_path: &str) {
+        self.check_request_path_encoded(expected_path).or_panic()
+    }
+
+    /// Checks the path sent for the request, after encoding, matches the one given.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_request_path_encoded()`].
+    pub fn check_request_path_encoded(&self, expected_path: &str) -> Result<(), AssertionError> {
+        let debug_request_format = self.debug_request_format();
+
+        check_eq(
+            expected_path,
+            self.full_request_url.path(),
+            format_args!(
+                "Expected encoded request path to match, for request {debug_request_format}"
+            ),
+        )
+    }
+
+    pub(crate) fn with_data_mask(mut self, data_mask: Arc<DataMask>) -> Self {
+        self.data_mask = data_mask;
+        self
+    }
+
+    pub(crate) fn data_mask(&self) -> &DataMask {
+        &self.data_mask
+    }
+
+    pub(crate) fn with_response_decoders(
+        mut self,
+        response_decoders: Arc<ResponseDecoders>,
+    ) -> Self {
+        self.response_decoders = response_decoders;
+        self
+    }
+
+    pub(crate) fn with_panic_message(mut self, maybe_panic_message: Option<String>) -> Self {
+        self.maybe_panic_message = maybe_panic_message;
+        self
+    }
+
+    pub(crate) fn with_raw_head(mut self, maybe_raw_head: Option<String>) -> Self {
+        self.maybe_raw_head = maybe_raw_head;
+        self
+    }
+
+    #[cfg(feature = "matched-route")]
+    pub(crate) fn with_matched_route(mut self, maybe_matched_route: Option<String>) -> Self {
+        self.maybe_matched_route = maybe_matched_route;
+        self
+    }
+
+    pub(crate) fn with_probe_readings(mut self, probe_readings: Vec<ProbeReading>) -> Self {
+        self.probe_readings = probe_readings;
+        self
+    }
+
+    /// Returns the reading of the type given, taken by an
+    /// [`InstrumentationProbe`](crate::InstrumentationProbe) while this request ran.
+    /// Such as `response.probe::<QueryCount>()`.
+    ///
+    /// This will panic if no probe took a reading of that type.
+    #[must_use]
+    #[track_caller]
+    pub fn probe<R>(&self) -> R
+    where
+        R: Clone + 'static,
+    {
+        let debug_request_format = self.debug_request_format();
+
+        self.maybe_probe::<R>()
+            .with_context(|| {
+                format!(
+                    "No probe reading of type {} was taken, for request {debug_request_format}",
+                    ::std::any::type_name::<R>()
+                )
+            })
+            .unwrap()
+    }
+
+    /// Returns the reading of the type given, taken by an
+    /// [`InstrumentationProbe`](crate::InstrumentationProbe) while this request ran,
+    /// or `None` if no probe took a reading of that type.
+    #[must_use]
+    pub fn maybe_probe<R>(&self) -> Option<R>
+    where
+        R: Clone + 'static,
+    {
+        self.probe_readings
+            .iter()
+            .find_map(|probe_reading| probe_reading.downcast::<R>())
+    }
+
+    /// Returns the underlying response, extracted as a string.
+    ///
+    /// The body is decoded using the `charset` given in the `Content-Type` header,
+    /// such as `ISO-8859-1` or `UTF-16`. When no charset is present,
+    /// or it is not recognised, the body is decoded as UTF-8.
+    ///
+    /// Invalid bytes are replaced with the unicode replacement character.
     ///
     /// # Example
     ///
@@ -202,7 +394,49 @@ impl TestResponse {
     /// ```
     #[must_use]
     pub fn text(&self) -> String {
-        String::from_utf8_lossy(self.as_bytes()).to_string()
+        let maybe_encoding = self
+            .maybe_charset()
+            .and_then(|charset| Encoding::for_label(charset.as_bytes()));
+
+        match maybe_encoding {
+            Some(encoding) => decode_text(self.as_bytes(), encoding),
+            None => String::from_utf8_lossy(self.as_bytes()).to_string(),
+        }
+    }
+
+    /// Returns the underlying response, decoded as a string using the encoding given.
+    /// This ignores any `charset` set in the `Content-Type` header.
+    ///
+    /// Encodings are given by their label, i.e. `"utf-8"`, `"iso-8859-1"`, or `"utf-16le"`.
+    /// If the label is not recognised, then this will panic.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum::routing::get;
+    /// use axum_test::TestServer;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/legacy-feed", get(|| async { vec![0x63, 0x61, 0x66, 0xE9] }));
+    /// let server = TestServer::new(app)?;
+    ///
+    /// let text = server.get(&"/legacy-feed")
+    ///     .await
+    ///     .text_with_encoding("iso-8859-1");
+    ///
+    /// assert_eq!(text, "café");
+    /// #
+    /// # Ok(()) }
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn text_with_encoding(&self, encoding: &str) -> String {
+        let encoding = Encoding::for_label(encoding.as_bytes())
+            .with_context(|| format!("Unknown text encoding '{encoding}'"))
+            .unwrap();
+
+        decode_text(self.as_bytes(), encoding)
     }
 
     /// Deserializes the response, as Json, into the type given.
@@ -246,17 +480,44 @@ impl TestResponse {
     /// # }
     /// ```
     #[must_use]
+    #[track_caller]
     pub fn json<T>(&self) -> T
     where
         T: DeserializeOwned,
     {
-        serde_json::from_slice::<T>(self.as_bytes())
-            .with_context(|| {
-                let debug_request_format = self.debug_request_format();
+        self.try_json::<T>().unwrap()
+    }
 
-                format!("Deserializing response from Json, for request {debug_request_format}")
-            })
-            .unwrap()
+    /// Returns the Json response in the canonical form of
+    /// [RFC 8785](https://www.rfc-editor.org/rfc/rfc8785) (JCS).
+    ///
+    /// Keys are sorted, whitespace is removed, and numbers are normalised,
+    /// giving the exact bytes a server would sign or hash.
+    ///
+    /// If the response is not Json, then this will panic.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum::routing::get;
+    /// use axum_test::TestServer;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/todo", get(|| async { r#"{ "title": "buy milk", "done": false }"# }));
+    ///
+    /// let server = TestServer::new(app)?;
+    /// let canonical = server.get(&"/todo").await.canonical_json();
+    ///
+    /// assert_eq!(canonical, r#"{"done":false,"title":"buy milk"}"#);
+    /// #
+    /// # Ok(()) }
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn canonical_json(&self) -> String {
+        let value = self.try_json::<Value>().unwrap();
+        format_canonical_json(&value)
     }
 
     /// Deserializes the response, as Yaml, into the type given.
@@ -299,19 +560,14 @@ impl TestResponse {
     /// # Ok(())
     /// # }
     /// ```
-    #[cfg(feature = "yaml")]
+    #[cfg(any(feature = "yaml", feature = "dyn-features"))]
     #[must_use]
+    #[track_caller]
     pub fn yaml<T>(&self) -> T
     where
         T: DeserializeOwned,
     {
-        serde_yaml::from_slice::<T>(self.as_bytes())
-            .with_context(|| {
-                let debug_request_format = self.debug_request_format();
-
-                format!("Deserializing response from YAML, for request {debug_request_format}")
-            })
-            .unwrap()
+        self.try_yaml::<T>().unwrap()
     }
 
     /// Deserializes the response, as MsgPack, into the type given.
@@ -354,19 +610,14 @@ impl TestResponse {
     /// # Ok(())
     /// # }
     /// ```
-    #[cfg(feature = "msgpack")]
+    #[cfg(any(feature = "msgpack", feature = "dyn-features"))]
     #[must_use]
+    #[track_caller]
     pub fn msgpack<T>(&self) -> T
     where
         T: DeserializeOwned,
     {
-        rmp_serde::from_slice::<T>(self.as_bytes())
-            .with_context(|| {
-                let debug_request_format = self.debug_request_format();
-
-                format!("Deserializing response from MsgPack, for request {debug_request_format}")
-            })
-            .unwrap()
+        self.try_msgpack::<T>().unwrap()
     }
 
     /// Deserializes the response, as an urlencoded Form, into the type given.
@@ -410,2337 +661,8693 @@ impl TestResponse {
     /// # }
     /// ```
     #[must_use]
+    #[track_caller]
     pub fn form<T>(&self) -> T
     where
         T: DeserializeOwned,
     {
-        serde_urlencoded::from_bytes::<T>(self.as_bytes())
-            .with_context(|| {
-                let debug_request_format = self.debug_request_format();
-
-                format!("Deserializing response from Form, for request {debug_request_format}")
-            })
-            .unwrap()
+        self.try_form::<T>().unwrap()
     }
 
-    /// Returns the raw underlying response as `Bytes`.
+    /// Deserializes the response into the type given,
+    /// using a decoder picked by the `Content-Type` of the response.
+    ///
+    /// Decoders registered using [`TestServer::register_decoder()`](crate::TestServer::register_decoder())
+    /// are used first. Otherwise Json (including `+json` types), Yaml, MsgPack,
+    /// and form responses are decoded using the methods for those formats.
+    ///
+    /// If there is no decoder for the content type, or decoding fails, then this will panic.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Json;
+    /// use axum::Router;
+    /// use axum::routing::get;
+    /// use axum_test::TestServer;
+    /// use serde_json::json;
+    /// use serde_json::Value;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/todo", get(|| async { Json(json!({ "description": "buy milk" })) }));
+    /// let server = TestServer::new(app)?;
+    ///
+    /// let todo = server.get(&"/todo").await.decode::<Value>();
+    /// assert_eq!(todo, json!({ "description": "buy milk" }));
+    /// #
+    /// # Ok(()) }
+    /// ```
     #[must_use]
-    pub fn as_bytes(&self) -> &Bytes {
-        &self.response_body
+    #[track_caller]
+    pub fn decode<T>(&self) -> T
+    where
+        T: DeserializeOwned,
+    {
+        self.try_decode::<T>().unwrap()
     }
 
-    /// Consumes this returning the underlying `Bytes`
-    /// in the response.
+    /// Parses the response as HTML, for querying and asserting using CSS selectors.
+    ///
+    /// This is useful for testing server rendered pages,
+    /// such as those built using Askama or Maud.
+    /// The parsing is lenient, and never fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum::response::Html;
+    /// use axum::routing::get;
+    ///
+    /// use axum_test::TestServer;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/login", get(|| async {
+    ///         Html(r#"<h1>Welcome</h1><form id="login"></form>"#)
+    ///     }));
+    ///
+    /// let server = TestServer::new(app)?;
+    /// let html = server.get(&"/login").await.html();
+    ///
+    /// html.assert_selector_exists("form#login");
+    /// html.assert_selector_text("h1", "Welcome");
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "html")]
     #[must_use]
-    pub fn into_bytes(self) -> Bytes {
-        self.response_body
+    pub fn html(&self) -> HtmlDocument {
+        HtmlDocument::new(
+            &self.text(),
+            self.full_request_url.clone(),
+            self.debug_request_format().to_string(),
+        )
     }
 
-    /// The status_code of the response.
+    /// Reads the response body as a zip archive,
+    /// for inspecting and asserting on the files inside.
+    ///
+    /// This will panic if the body is not a valid zip archive.
+    ///
+    /// See [`ZipArchive`] for the assertions available.
+    #[cfg(feature = "archives")]
     #[must_use]
-    pub fn status_code(&self) -> StatusCode {
-        self.status_code
+    #[track_caller]
+    pub fn zip(&self) -> ZipArchive {
+        ZipArchive::new(
+            self.response_body.clone(),
+            self.debug_request_format().to_string(),
+        )
+        .unwrap()
     }
 
-    /// The Method used to produce this response.
+    /// Reads the response body as [RFC 7807](https://www.rfc-editor.org/rfc/rfc7807) problem details,
+    /// as sent with the content type `application/problem+json`.
+    ///
+    /// This will panic if the body is not a Json object,
+    /// or the standard members have the wrong types.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Json;
+    /// use axum::Router;
+    /// use axum::http::StatusCode;
+    /// use axum::routing::post;
+    /// use axum_test::TestServer;
+    /// use serde_json::json;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/messages", post(|| async {
+    ///         (StatusCode::FORBIDDEN, Json(json!({
+    ///             "type": "https://example.com/errors/out-of-credit",
+    ///             "title": "You do not have enough credit.",
+    ///             "status": 403,
+    ///             "balance": 30,
+    ///         })))
+    ///     }));
+    /// let server = TestServer::new(app)?;
+    ///
+    /// let response = server.post(&"/messages").expect_failure().await;
+    /// response.assert_problem_type("https://example.com/errors/out-of-credit");
+    /// response.assert_problem_status_matches();
+    ///
+    /// let problem = response.problem_details();
+    /// assert_eq!(problem.extensions["balance"], 30);
+    /// #
+    /// # Ok(()) }
+    /// ```
+    #[track_caller]
     #[must_use]
-    pub fn request_method(&self) -> Method {
-        self.method.clone()
+    pub fn problem_details(&self) -> ProblemDetails {
+        self.try_problem_details().unwrap()
     }
 
-    /// The full URL that was used to produce this response.
-    #[must_use]
-    pub fn request_url(&self) -> Url {
-        self.full_request_url.clone()
+    /// Asserts the problem details `type` member is the URI given.
+    ///
+    /// Problems without a `type` are treated as `about:blank`.
+    #[track_caller]
+    pub fn assert_problem_type(&self, expected_type: &str) {
+        self.check_problem_type(expected_type).or_panic()
     }
 
-    /// Finds a header with the given name.
-    /// If there are multiple headers with the same name,
-    /// then only the first [`HeaderValue`](::http::HeaderValue) will be returned.
+    /// Checks the problem details `type` member is the URI given.
     ///
-    /// `None` is returned when no header was found.
-    #[must_use]
-    pub fn maybe_header<N>(&self, name: N) -> Option<HeaderValue>
-    where
-        N: TryInto<HeaderName>,
-        N::Error: Debug,
-    {
-        let header_name = name
-            .try_into()
-            .expect("Failed to build HeaderName from name given");
-        self.headers.get(header_name).map(|h| h.to_owned())
+    /// This is the non-panicking version of [`TestResponse::assert_problem_type()`].
+    pub fn check_problem_type(&self, expected_type: &str) -> Result<(), AssertionError> {
+        let problem = self.try_problem_details()?;
+        let debug_request_format = self.debug_request_format();
+
+        check_eq(
+            expected_type,
+            problem.problem_type.as_str(),
+            format_args!("Expected problem type to match, for request {debug_request_format}"),
+        )
     }
 
-    /// Returns the headers returned from the response.
-    #[must_use]
-    pub fn headers(&self) -> &HeaderMap<HeaderValue> {
-        &self.headers
+    /// Asserts the problem details `title` member is the text given.
+    #[track_caller]
+    pub fn assert_problem_title(&self, expected_title: &str) {
+        self.check_problem_title(expected_title).or_panic()
     }
 
-    #[must_use]
-    pub fn maybe_content_type(&self) -> Option<String> {
-        self.headers.get(http::header::CONTENT_TYPE).map(|header| {
-            header
-                .to_str()
-                .with_context(|| {
-                    format!("Failed to decode header CONTENT_TYPE, received '{header:?}'")
-                })
-                .unwrap()
-                .to_string()
-        })
+    /// Checks the problem details `title` member is the text given.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_problem_title()`].
+    pub fn check_problem_title(&self, expected_title: &str) -> Result<(), AssertionError> {
+        let problem = self.try_problem_details()?;
+        let debug_request_format = self.debug_request_format();
+
+        check_eq(
+            &Some(expected_title),
+            &problem.title.as_deref(),
+            format_args!("Expected problem title to match, for request {debug_request_format}"),
+        )
     }
 
-    #[must_use]
-    pub fn content_type(&self) -> String {
-        self.maybe_content_type()
-            .expect("CONTENT_TYPE not found in response header")
+    /// Asserts the problem details `status` member is present,
+    /// and matches the status code of the response.
+    #[track_caller]
+    pub fn assert_problem_status_matches(&self) {
+        self.check_problem_status_matches().or_panic()
     }
 
-    /// Finds a header with the given name.
-    /// If there are multiple headers with the same name,
-    /// then only the first will be returned.
+    /// Checks the problem details `status` member is present,
+    /// and matches the status code of the response.
     ///
-    /// If no header is found, then this will panic.
-    #[must_use]
-    pub fn header<N>(&self, name: N) -> HeaderValue
-    where
-        N: TryInto<HeaderName> + Display + Clone,
-        N::Error: Debug,
-    {
-        let debug_header = name.clone();
-        let header_name = name
-            .try_into()
-            .expect("Failed to build HeaderName from name given, '{debug_header}'");
-        self.headers
-            .get(header_name)
-            .map(|h| h.to_owned())
-            .with_context(|| {
-                let debug_request_format = self.debug_request_format();
+    /// This is the non-panicking version of [`TestResponse::assert_problem_status_matches()`].
+    pub fn check_problem_status_matches(&self) -> Result<(), AssertionError> {
+        let problem = self.try_problem_details()?;
+        let debug_request_format = self.debug_request_format();
+        let status_code = self.status_code.as_u16();
 
-                format!("Cannot find header {debug_header}, for request {debug_request_format}",)
-            })
-            .unwrap()
+        check_eq(
+            &Some(status_code),
+            &problem.status,
+            format_args!("Expected problem status to match the response status code, for request {debug_request_format}"),
+        )
     }
 
-    /// Iterates over all of the headers contained in the response.
-    pub fn iter_headers(&self) -> impl Iterator<Item = (&'_ HeaderName, &'_ HeaderValue)> {
-        self.headers.iter()
-    }
+    fn try_problem_details(&self) -> anyhow::Result<ProblemDetails> {
+        let debug_request_format = self.debug_request_format();
+        let body = serde_json::from_slice::<serde_json::Value>(&self.response_body)
+            .with_context(|| {
+                format!("Failed to read problem details, body is not Json, for request {debug_request_format}")
+            })?;
 
-    /// Iterates over all of the headers for a specific name, contained in the response.
-    pub fn iter_headers_by_name<N>(&self, name: N) -> impl Iterator<Item = &'_ HeaderValue>
-    where
-        N: TryInto<HeaderName>,
-        N::Error: Debug,
-    {
-        let header_name = name
-            .try_into()
-            .expect("Failed to build HeaderName from name given");
-        self.headers.get_all(header_name).iter()
+        ProblemDetails::from_json(body).with_context(|| {
+            format!("Failed to read problem details, for request {debug_request_format}")
+        })
     }
 
+    /// Deserializes the primary `data` of a [JSON:API](https://jsonapi.org) document,
+    /// into the type given.
+    ///
+    /// This will panic if the body is not a JSON:API document,
+    /// or the `data` cannot be deserialized.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Json;
+    /// use axum::Router;
+    /// use axum::routing::get;
+    /// use axum_test::TestServer;
+    /// use serde_json::json;
+    /// use serde_json::Value;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/articles/1", get(|| async {
+    ///         Json(json!({
+    ///             "data": {
+    ///                 "type": "articles",
+    ///                 "id": "1",
+    ///                 "attributes": { "title": "Rails is Omakase" },
+    ///                 "relationships": {
+    ///                     "author": { "data": { "type": "people", "id": "9" } },
+    ///                 },
+    ///             },
+    ///             "included": [
+    ///                 { "type": "people", "id": "9", "attributes": { "name": "dgeb" } },
+    ///             ],
+    ///         }))
+    ///     }));
+    /// let server = TestServer::new(app)?;
+    ///
+    /// let response = server.get(&"/articles/1").await;
+    /// response.assert_jsonapi_relationship_exists("author");
+    /// response.assert_jsonapi_included_count(1);
+    ///
+    /// let article = response.jsonapi_data::<Value>();
+    /// assert_eq!(article["attributes"]["title"], "Rails is Omakase");
+    /// #
+    /// # Ok(()) }
+    /// ```
+    #[cfg(feature = "jsonapi")]
     #[must_use]
-    pub fn contains_header<N>(&self, name: N) -> bool
+    #[track_caller]
+    pub fn jsonapi_data<T>(&self) -> T
     where
-        N: TryInto<HeaderName>,
-        N::Error: Debug,
+        T: DeserializeOwned,
     {
-        let header_name = name
-            .try_into()
-            .expect("Failed to build HeaderName from name given");
-        self.headers.contains_key(header_name)
+        self.try_jsonapi_data::<T>().unwrap()
     }
 
-    /// Asserts the header named is present in the response.
+    /// Returns the top level pagination links of a [JSON:API](https://jsonapi.org) document.
     ///
-    /// If the header is not present, then the assertion fails.
+    /// Links which are missing are returned as `None`.
+    /// This will panic if the body is not a JSON:API document.
+    #[cfg(feature = "jsonapi")]
+    #[must_use]
     #[track_caller]
-    pub fn assert_contains_header<N>(&self, name: N)
-    where
-        N: TryInto<HeaderName> + Display + Clone,
-        N::Error: Debug,
-    {
-        let debug_header_name = name.clone();
-        let debug_request_format = self.debug_request_format();
-        let has_header = self.contains_header(name);
-
-        assert!(has_header, "Expected header '{debug_header_name}' to be present in response, header was not found, for request {debug_request_format}");
+    pub fn jsonapi_links(&self) -> JsonApiLinks {
+        self.try_jsonapi_links().unwrap()
     }
 
+    /// Asserts the primary `data` of a JSON:API document has the relationship given.
+    #[cfg(feature = "jsonapi")]
     #[track_caller]
-    pub fn assert_header<N, V>(&self, name: N, value: V)
-    where
-        N: TryInto<HeaderName> + Display + Clone,
-        N::Error: Debug,
-        V: TryInto<HeaderValue>,
-        V::Error: Debug,
-    {
-        let debug_header_name = name.clone();
-        let header_name = name
-            .try_into()
-            .expect("Failed to build HeaderName from name given");
-        let expected_header_value = value
-            .try_into()
-            .expect("Could not turn given value into HeaderValue");
+    pub fn assert_jsonapi_relationship_exists(&self, relationship: &str) {
+        self.check_jsonapi_relationship_exists(relationship)
+            .or_panic()
+    }
+
+    /// Checks the primary `data` of a JSON:API document has the relationship given.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_jsonapi_relationship_exists()`].
+    #[cfg(feature = "jsonapi")]
+    pub fn check_jsonapi_relationship_exists(
+        &self,
+        relationship: &str,
+    ) -> Result<(), AssertionError> {
+        let document = self.try_jsonapi_document()?;
         let debug_request_format = self.debug_request_format();
-        let maybe_found_header_value = self.maybe_header(header_name);
+        let relationships = document
+            .get("data")
+            .and_then(|data| data.get("relationships"))
+            .and_then(Value::as_object);
+        let names = relationships
+            .map(|relationships| relationships.keys().cloned().collect::<Vec<_>>())
+            .unwrap_or_default();
 
-        match maybe_found_header_value {
-            None => {
-                panic!("Expected header '{debug_header_name}' to be present in response, header was not found, for request {debug_request_format}")
-            }
-            Some(found_header_value) => {
-                assert_eq!(expected_header_value, found_header_value,)
-            }
-        }
+        check(
+            names.iter().any(|name| name == relationship),
+            format_args!("Expected JSON:API data to have relationship '{relationship}', received relationships {names:?}, for request {debug_request_format}"),
+        )
     }
 
-    /// Finds a [`Cookie`] with the given name.
-    /// If there are multiple matching cookies,
-    /// then only the first will be returned.
+    /// Asserts the number of resources in the `included` member of a JSON:API document.
     ///
-    /// `None` is returned if no Cookie is found.
-    #[must_use]
-    pub fn maybe_cookie(&self, cookie_name: &str) -> Option<Cookie<'static>> {
-        for cookie in self.iter_cookies() {
-            if cookie.name() == cookie_name {
-                return Some(cookie.into_owned());
+    /// Documents without an `included` member are treated as including nothing.
+    #[cfg(feature = "jsonapi")]
+    #[track_caller]
+    pub fn assert_jsonapi_included_count(&self, expected_count: usize) {
+        self.check_jsonapi_included_count(expected_count).or_panic()
+    }
+
+    /// Checks the number of resources in the `included` member of a JSON:API document.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_jsonapi_included_count()`].
+    #[cfg(feature = "jsonapi")]
+    pub fn check_jsonapi_included_count(
+        &self,
+        expected_count: usize,
+    ) -> Result<(), AssertionError> {
+        let document = self.try_jsonapi_document()?;
+        let debug_request_format = self.debug_request_format();
+        let received_count = match document.get("included") {
+            None | Some(Value::Null) => 0,
+            Some(Value::Array(included)) => included.len(),
+            Some(included) => {
+                return Err(AssertionError::new(format!(
+                    "Expected JSON:API included to be an array, received {included}, for request {debug_request_format}"
+                )))
             }
-        }
+        };
 
-        None
+        check_eq(
+            &expected_count,
+            &received_count,
+            format_args!(
+                "Expected JSON:API included count to match, for request {debug_request_format}"
+            ),
+        )
     }
 
-    /// Finds a [`Cookie`](::cookie::Cookie) with the given name.
-    /// If there are multiple matching cookies,
-    /// then only the first will be returned.
+    /// Asserts the JSON:API document has a `next` link, for fetching another page.
+    #[cfg(feature = "jsonapi")]
+    #[track_caller]
+    pub fn assert_jsonapi_has_next_page(&self) {
+        self.check_jsonapi_has_next_page().or_panic()
+    }
+
+    /// Checks the JSON:API document has a `next` link, for fetching another page.
     ///
-    /// If no `Cookie` is found, then this will panic.
-    #[must_use]
-    pub fn cookie(&self, cookie_name: &str) -> Cookie<'static> {
-        self.maybe_cookie(cookie_name)
-            .with_context(|| {
-                let debug_request_format = self.debug_request_format();
+    /// This is the non-panicking version of [`TestResponse::assert_jsonapi_has_next_page()`].
+    #[cfg(feature = "jsonapi")]
+    pub fn check_jsonapi_has_next_page(&self) -> Result<(), AssertionError> {
+        let links = self.try_jsonapi_links()?;
+        let debug_request_format = self.debug_request_format();
 
-                format!("Cannot find cookie {cookie_name}, for request {debug_request_format}")
-            })
-            .unwrap()
+        check(
+            links.next.is_some(),
+            format_args!("Expected JSON:API document to have a next link, received none, for request {debug_request_format}"),
+        )
     }
 
-    /// Returns all of the cookies contained in the response,
-    /// within a [`CookieJar`](::cookie::CookieJar) object.
+    /// Asserts the JSON:API document has no `next` link, as it is the last page.
+    #[cfg(feature = "jsonapi")]
+    #[track_caller]
+    pub fn assert_jsonapi_is_last_page(&self) {
+        self.check_jsonapi_is_last_page().or_panic()
+    }
+
+    /// Checks the JSON:API document has no `next` link, as it is the last page.
     ///
-    /// See the `cookie` crate for details.
-    #[must_use]
-    pub fn cookies(&self) -> CookieJar {
-        let mut cookies = CookieJar::new();
+    /// This is the non-panicking version of [`TestResponse::assert_jsonapi_is_last_page()`].
+    #[cfg(feature = "jsonapi")]
+    pub fn check_jsonapi_is_last_page(&self) -> Result<(), AssertionError> {
+        let links = self.try_jsonapi_links()?;
+        let debug_request_format = self.debug_request_format();
 
-        for cookie in self.iter_cookies() {
-            cookies.add(cookie.into_owned());
+        match links.next {
+            None => Ok(()),
+            Some(next) => Err(AssertionError::new(format!(
+                "Expected JSON:API document to be the last page, received next link '{next}', for request {debug_request_format}"
+            ))),
         }
+    }
 
-        cookies
+    #[cfg(feature = "jsonapi")]
+    fn try_jsonapi_document(&self) -> anyhow::Result<serde_json::Map<String, Value>> {
+        let debug_request_format = self.debug_request_format();
+        let body = serde_json::from_slice::<Value>(&self.response_body).with_context(|| {
+            format!("Failed to read JSON:API document, body is not Json, for request {debug_request_format}")
+        })?;
+
+        match body {
+            Value::Object(document) => Ok(document),
+            body => Err(anyhow::anyhow!(
+                "Expected JSON:API document to be a Json object, received {body}, for request {debug_request_format}"
+            )),
+        }
     }
 
-    /// Iterate over all of the cookies in the response.
-    pub fn iter_cookies(&self) -> impl Iterator<Item = Cookie<'_>> {
-        self.iter_headers_by_name(SET_COOKIE).map(|header| {
-            let header_str = header
-                .to_str()
-                .with_context(|| {
-                    let debug_request_format = self.debug_request_format();
+    #[cfg(feature = "jsonapi")]
+    fn try_jsonapi_data<T>(&self) -> anyhow::Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let mut document = self.try_jsonapi_document()?;
+        let debug_request_format = self.debug_request_format();
+        let data = document.remove("data").with_context(|| {
+            format!("Expected JSON:API document to have data, for request {debug_request_format}")
+        })?;
 
-                    format!(
-                        "Reading header 'Set-Cookie' as string, for request {debug_request_format}",
-                    )
-                })
-                .unwrap();
+        serde_json::from_value::<T>(data).with_context(|| {
+            format!("Deserializing JSON:API data, for request {debug_request_format}")
+        })
+    }
 
-            Cookie::parse(header_str)
-                .with_context(|| {
-                    let debug_request_format = self.debug_request_format();
+    #[cfg(feature = "jsonapi")]
+    fn try_jsonapi_links(&self) -> anyhow::Result<JsonApiLinks> {
+        let document = self.try_jsonapi_document()?;
+        let links = document.get("links").and_then(Value::as_object);
 
-                    format!("Parsing 'Set-Cookie' header, for request {debug_request_format}",)
-                })
-                .unwrap()
-        })
+        Ok(JsonApiLinks::from_links(links))
     }
 
-    /// Consumes the request, turning it into a `TestWebSocket`.
-    /// If this cannot be done, then the response will panic.
-    ///
-    /// *Note*, this requires the server to be running on a real HTTP
-    /// port. Either using a randomly assigned port, or a specified one.
-    /// See the [`TestServerConfig::transport`](crate::TestServerConfig::transport) for more details.
+    /// Asserts the response was produced by the axum rejection given,
+    /// such as `response.assert_is_rejection::<JsonRejection>()`.
     ///
-    /// # Example
+    /// This matches on the status code and the start of the body axum sends for that rejection,
+    /// so tests of invalid input do not need to match the full body text.
     ///
     /// ```rust
     /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
     /// #
+    /// use axum::extract::rejection::JsonRejection;
+    /// use axum::routing::post;
+    /// use axum::Json;
     /// use axum::Router;
     /// use axum_test::TestServer;
+    /// use serde_json::json;
+    /// use serde_json::Value;
     ///
-    /// let app = Router::new();
-    /// let server = TestServer::builder()
-    ///     .http_transport()
-    ///     .build(app)?;
-    ///
-    /// let mut websocket = server
-    ///     .get_websocket(&"/my-web-socket-end-point")
-    ///     .await
-    ///     .into_websocket()
-    ///     .await;
+    /// let app = Router::new().route("/users", post(|Json(user): Json<Value>| async move { Json(user) }));
+    /// let server = TestServer::new(app)?;
     ///
-    /// websocket.send_text("Hello!").await;
+    /// let response = server.post("/users").text("not json").await;
+    /// response.assert_is_rejection::<JsonRejection>();
     /// #
-    /// # Ok(()) }
+    /// # Ok(())
+    /// # }
     /// ```
-    ///
-    #[cfg(feature = "ws")]
-    #[must_use]
-    pub async fn into_websocket(self) -> TestWebSocket {
-        use crate::transport_layer::TransportLayerType;
-
-        // Using the mock approach will just fail.
-        if self.websockets.transport_type != TransportLayerType::Http {
-            unimplemented!("WebSocket requires a HTTP based transport layer, see `TestServerConfig::transport`");
-        }
-
-        let debug_request_format = self.debug_request_format().to_string();
-
-        let on_upgrade = self.websockets.maybe_on_upgrade.with_context(|| {
-            format!("Expected WebSocket upgrade to be found, it is None, for request {debug_request_format}")
-        })
-        .unwrap();
-
-        let upgraded = on_upgrade
-            .await
-            .with_context(|| {
-                format!("Failed to upgrade connection for, for request {debug_request_format}")
-            })
-            .unwrap();
-
-        TestWebSocket::new(upgraded).await
-    }
-
-    /// This performs an assertion comparing the whole body of the response,
-    /// against the text provided.
-    #[track_caller]
-    pub fn assert_text<C>(&self, expected: C)
-    where
-        C: AsRef<str>,
-    {
-        let expected_contents = expected.as_ref();
-        assert_eq!(expected_contents, &self.text());
-    }
-
-    /// This asserts if the text given is contained, somewhere, within the response.
+    #[cfg(feature = "rejections")]
     #[track_caller]
-    pub fn assert_text_contains<C>(&self, expected: C)
+    pub fn assert_is_rejection<R>(&self)
     where
-        C: AsRef<str>,
+        R: KnownRejection,
     {
-        let expected_contents = expected.as_ref();
-        let received = self.text();
-        let is_contained = received.contains(expected_contents);
-
-        assert!(
-            is_contained,
-            "Failed to find '{expected_contents}', received '{received}'"
-        );
+        self.check_is_rejection::<R>().or_panic()
     }
 
-    /// Asserts the response from the server matches the contents of the file.
-    #[track_caller]
-    pub fn assert_text_from_file<P>(&self, path: P)
+    /// Checks the response was produced by the axum rejection given.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_is_rejection()`].
+    #[cfg(feature = "rejections")]
+    pub fn check_is_rejection<R>(&self) -> Result<(), AssertionError>
     where
-        P: AsRef<Path>,
+        R: KnownRejection,
     {
-        let path_ref = path.as_ref();
-        let expected = read_to_string(path_ref)
-            .with_context(|| format!("Failed to read from file '{}'", path_ref.display()))
-            .unwrap();
+        let body = String::from_utf8_lossy(&self.response_body);
+        let debug_request_format = self.debug_request_format();
+        let rejection_name = ::std::any::type_name::<R>();
+        let received_debug = StatusCodeFormatter(self.status_code);
 
-        self.assert_text(expected);
+        check(
+            R::is_response(self.status_code, &body),
+            format_args!(
+                "Expected response to be rejection {rejection_name}, received {received_debug} with body '{body}', for request {debug_request_format}"
+            ),
+        )
     }
 
-    /// Deserializes the contents of the request as Json,
-    /// and asserts it matches the value given.
+    /// Reads the parts described by a [`multipart_echo`](crate::routes::multipart_echo) handler.
     ///
-    /// If `other` does not match, or the response is not Json,
-    /// then this will panic.
+    /// This will panic if the response is not a list of parts.
+    #[cfg(feature = "multipart-echo")]
+    #[must_use]
     #[track_caller]
-    pub fn assert_json<T>(&self, expected: &T)
-    where
-        T: DeserializeOwned + PartialEq<T> + Debug,
-    {
-        assert_eq!(*expected, self.json::<T>());
+    pub fn multipart_echo_parts(&self) -> Vec<MultipartEchoPart> {
+        self.try_json::<Vec<MultipartEchoPart>>().unwrap()
     }
 
-    /// Asserts the content is within the json returned.
-    /// This is useful for when servers return times and IDs that you
-    /// wish to ignore.
+    /// Asserts a [`multipart_echo`](crate::routes::multipart_echo) handler received a part with the name given,
+    /// and then calls the function given with that part to assert on.
+    ///
+    /// Where multiple parts share the name, the first is given.
     ///
     /// ```rust
     /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
     /// #
     /// use axum::Router;
-    /// use axum::extract::Json;
-    /// use axum::routing::get;
+    /// use axum::routing::post;
     /// use axum_test::TestServer;
-    /// use serde_json::json;
-    /// use std::time::Instant;
+    /// use axum_test::multipart::MultipartForm;
+    /// use axum_test::multipart::Part;
+    /// use axum_test::routes::multipart_echo;
     ///
-    /// let app = Router::new()
-    ///     .route(&"/user", get(|| async {
-    ///         let id = Instant::now().elapsed().as_millis();
+    /// let app = Router::new().route(&"/upload", post(multipart_echo));
+    /// let server = TestServer::new(app)?;
     ///
-    ///         Json(json!({
-    ///            "id": id,
-    ///            "name": "Joe",
-    ///            "age": 20,
-    ///        }))
-    ///     }));
-    /// let server = TestServer::new(app)?;
+    /// let form = MultipartForm::new()
+    ///     .add_part("avatar", Part::bytes(b"png!".as_slice()).mime_type("image/png"));
     ///
-    /// // Checks the response contains _only_ the values listed here,
-    /// // and ignores the rest.
-    /// server.get(&"/user")
+    /// server.post(&"/upload")
+    ///     .multipart(form)
     ///     .await
-    ///     .assert_json_contains(&json!({
-    ///         "name": "Joe",
-    ///         "age": 20,
-    ///     }));
+    ///     .assert_multipart_part("avatar", |part| {
+    ///         assert_eq!(part.content_type.as_deref(), Some("image/png"));
+    ///         assert_eq!(part.size, 4);
+    ///     });
     /// #
     /// # Ok(()) }
     /// ```
+    #[cfg(feature = "multipart-echo")]
     #[track_caller]
-    pub fn assert_json_contains<T>(&self, expected: &T)
+    pub fn assert_multipart_part<N, F>(&self, name: N, assert_part: F)
     where
-        T: Serialize,
+        N: AsRef<str>,
+        F: FnOnce(&MultipartEchoPart),
     {
-        let received = self.json::<Value>();
-        assert_json_include!(actual: received, expected: expected);
+        self.check_multipart_part(name, assert_part).or_panic()
     }
 
-    /// Read json file from given path and assert it with json response.
+    /// Checks a [`multipart_echo`](crate::routes::multipart_echo) handler received a part with the name given,
+    /// and then calls the function given with that part.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_multipart_part()`].
+    #[cfg(feature = "multipart-echo")]
+    pub fn check_multipart_part<N, F>(&self, name: N, assert_part: F) -> Result<(), AssertionError>
+    where
+        N: AsRef<str>,
+        F: FnOnce(&MultipartEchoPart),
+    {
+        let name = name.as_ref();
+        let debug_request_format = self.debug_request_format();
+        let parts = self.try_json::<Vec<MultipartEchoPart>>()?;
+        let received_names: Vec<&str> = parts.iter().map(|part| part.name.as_str()).collect();
+        let part = parts
+            .iter()
+            .find(|part| part.name == name)
+            .with_context(|| {
+                format!("Expected multipart part '{name}' to have been received, received parts {received_names:?}, for request {debug_request_format}")
+            })?;
+
+        assert_part(part);
+
+        Ok(())
+    }
+
+    /// Returns the raw underlying response as `Bytes`.
+    #[must_use]
+    pub fn as_bytes(&self) -> &Bytes {
+        &self.response_body
+    }
+
+    /// Consumes this returning the underlying `Bytes`
+    /// in the response.
+    #[must_use]
+    pub fn into_bytes(self) -> Bytes {
+        self.response_body
+    }
+
+    /// Decodes the body of the response from base64, returning the raw bytes.
+    ///
+    /// Either the standard or URL safe alphabet is accepted,
+    /// padding is optional, and whitespace is ignored.
+    ///
+    /// If the body is not valid base64, then this will panic.
     ///
     /// ```rust
     /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
     /// #
-    /// use axum::Json;
+    /// use axum::Router;
     /// use axum::routing::get;
-    /// use axum::routing::Router;
     /// use axum_test::TestServer;
-    /// use serde_json::json;
     ///
     /// let app = Router::new()
-    ///     .route(&"/json", get(|| async {
-    ///         Json(json!({
-    ///             "name": "Joe",
-    ///             "age": 20,
-    ///         }))
-    ///     }));
+    ///     .route(&"/key", get(|| async { "3q2+7w==" }));
     ///
-    /// let server = TestServer::new(app).unwrap();
-    /// server
-    ///     .get(&"/json")
-    ///     .await
-    ///     .assert_json_from_file("files/example.json");
+    /// let server = TestServer::new(app)?;
+    /// let key = server.get(&"/key").await.bytes_from_base64();
+    ///
+    /// assert_eq!(key.as_ref(), &[0xde, 0xad, 0xbe, 0xef]);
     /// #
     /// # Ok(()) }
     /// ```
-    ///
+    #[must_use]
     #[track_caller]
-    pub fn assert_json_from_file<P>(&self, path: P)
-    where
-        P: AsRef<Path>,
-    {
-        let path_ref = path.as_ref();
-        let file = File::open(path_ref)
-            .with_context(|| format!("Failed to read from file '{}'", path_ref.display()))
-            .unwrap();
+    pub fn bytes_from_base64(&self) -> Bytes {
+        let debug_request_format = self.debug_request_format();
 
-        let reader = BufReader::new(file);
-        let expected = serde_json::from_reader::<_, serde_json::Value>(reader)
+        decode_base64(&self.text())
             .with_context(|| {
-                format!(
-                    "Failed to deserialize file '{}' as json",
-                    path_ref.display()
-                )
+                format!("Decoding response from base64, for request {debug_request_format}")
             })
-            .unwrap();
-
-        self.assert_json(&expected);
+            .map(Bytes::from)
+            .unwrap()
     }
 
-    /// Deserializes the contents of the request as Yaml,
-    /// and asserts it matches the value given.
+    /// Decodes the body of the response from hex, returning the raw bytes.
     ///
-    /// If `other` does not match, or the response is not Yaml,
-    /// then this will panic.
-    #[cfg(feature = "yaml")]
-    #[track_caller]
-    pub fn assert_yaml<T>(&self, other: &T)
-    where
-        T: DeserializeOwned + PartialEq<T> + Debug,
-    {
-        assert_eq!(*other, self.yaml::<T>());
-    }
-
-    /// Read yaml file from given path and assert it with yaml response.
-    #[cfg(feature = "yaml")]
+    /// Upper and lower case are accepted, and whitespace is ignored.
+    ///
+    /// If the body is not valid hex, then this will panic.
+    #[must_use]
     #[track_caller]
-    pub fn assert_yaml_from_file<P>(&self, path: P)
-    where
-        P: AsRef<Path>,
-    {
-        let path_ref = path.as_ref();
-        let file = File::open(path_ref)
-            .with_context(|| format!("Failed to read from file '{}'", path_ref.display()))
-            .unwrap();
+    pub fn bytes_from_hex(&self) -> Bytes {
+        let debug_request_format = self.debug_request_format();
 
-        let reader = BufReader::new(file);
-        let expected = serde_yaml::from_reader::<_, serde_yaml::Value>(reader)
+        decode_hex(&self.text())
             .with_context(|| {
-                format!(
-                    "Failed to deserialize file '{}' as yaml",
-                    path_ref.display()
-                )
+                format!("Decoding response from hex, for request {debug_request_format}")
             })
-            .unwrap();
+            .map(Bytes::from)
+            .unwrap()
+    }
 
-        self.assert_yaml(&expected);
+    /// The status_code of the response.
+    #[must_use]
+    pub fn status_code(&self) -> StatusCode {
+        self.status_code
     }
 
-    /// Deserializes the contents of the request as MsgPack,
-    /// and asserts it matches the value given.
+    /// The HTTP version the response was served with.
+    #[must_use]
+    pub fn http_version(&self) -> Version {
+        self.version
+    }
+
+    /// Returns true if the handler panicked, and this response was returned in it's place.
     ///
-    /// If `other` does not match, or the response is not MsgPack,
-    /// then this will panic.
-    #[cfg(feature = "msgpack")]
-    #[track_caller]
-    pub fn assert_msgpack<T>(&self, other: &T)
-    where
-        T: DeserializeOwned + PartialEq<T> + Debug,
-    {
-        assert_eq!(*other, self.msgpack::<T>());
+    /// This requires the `TestServer` to be built with
+    /// [`TestServerBuilder::catch_panics()`](crate::TestServerBuilder::catch_panics()),
+    /// as otherwise the request fails instead.
+    #[must_use]
+    pub fn was_panic(&self) -> bool {
+        self.maybe_panic_message.is_some()
     }
 
-    /// Deserializes the contents of the request as an url encoded form,
-    /// and asserts it matches the value given.
+    /// Returns the message the handler panicked with, if it panicked.
     ///
-    /// If `other` does not match, or the response cannot be deserialized,
-    /// then this will panic.
-    #[track_caller]
-    pub fn assert_form<T>(&self, other: &T)
-    where
-        T: DeserializeOwned + PartialEq<T> + Debug,
-    {
-        assert_eq!(*other, self.form::<T>());
+    /// This requires the `TestServer` to be built with
+    /// [`TestServerBuilder::catch_panics()`](crate::TestServerBuilder::catch_panics()).
+    #[must_use]
+    pub fn panic_message(&self) -> Option<&str> {
+        self.maybe_panic_message.as_deref()
     }
 
-    /// Assert the response status code matches the one given.
+    /// Asserts the handler panicked, with a message containing the text given.
+    ///
+    /// This requires the `TestServer` to be built with
+    /// [`TestServerBuilder::catch_panics()`](crate::TestServerBuilder::catch_panics()).
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum::routing::get;
+    /// use axum_test::TestServer;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/todo", get(|| async {
+    ///         let maybe_todo: Option<&str> = None;
+    ///         maybe_todo.expect("todo should exist")
+    ///     }));
+    ///
+    /// let server = TestServer::builder()
+    ///     .catch_panics()
+    ///     .build(app)?;
+    ///
+    /// server.get(&"/todo")
+    ///     .await
+    ///     .assert_handler_panicked_with("todo should exist");
+    /// #
+    /// # Ok(()) }
+    /// ```
     #[track_caller]
-    pub fn assert_status(&self, expected_status_code: StatusCode) {
-        let received_debug = StatusCodeFormatter(self.status_code);
-        let expected_debug = StatusCodeFormatter(expected_status_code);
-        let debug_request_format = self.debug_request_format();
-        let debug_body = DebugResponseBody(self);
-
-        assert_eq!(
-            expected_status_code, self.status_code,
-            "Expected status code to be {expected_debug}, received {received_debug}, for request {debug_request_format}, with body {debug_body}"
-        );
+    pub fn assert_handler_panicked_with(&self, expected_message: &str) {
+        self.check_handler_panicked_with(expected_message)
+            .or_panic()
     }
 
-    /// Assert the response status code does **not** match the one given.
-    #[track_caller]
-    pub fn assert_not_status(&self, expected_status_code: StatusCode) {
-        let received_debug = StatusCodeFormatter(self.status_code);
-        let expected_debug = StatusCodeFormatter(expected_status_code);
+    /// Checks the handler panicked, with a message containing the text given.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_handler_panicked_with()`].
+    pub fn check_handler_panicked_with(
+        &self,
+        expected_message: &str,
+    ) -> Result<(), AssertionError> {
         let debug_request_format = self.debug_request_format();
-        let debug_body = DebugResponseBody(self);
 
-        assert_ne!(
-            expected_status_code,
-            self.status_code,
-            "Expected status code to not be {expected_debug}, received {received_debug}, for request {debug_request_format}, with body {debug_body}"
-        );
+        match self.panic_message() {
+            None => Err(AssertionError::new(format!(
+                "Expected handler to panic with '{expected_message}', it did not panic, for request {debug_request_format}"
+            ))),
+            Some(received_message) => check(
+                received_message.contains(expected_message),
+                format_args!("Expected handler to panic with '{expected_message}', received '{received_message}', for request {debug_request_format}"),
+            ),
+        }
     }
 
-    /// Assert that the status code is **within** the 2xx range.
-    /// i.e. The range from 200-299.
+    /// Returns the raw head of the response, exactly as it was sent over the connection.
+    /// This is the status line and headers, ending with the blank line before the body.
+    ///
+    /// Unlike [`TestResponse::headers()`], this keeps the original casing and formatting of the headers.
+    ///
+    /// This requires the `TestServer` to be built with
+    /// [`TestServerBuilder::capture_raw_head()`](crate::TestServerBuilder::capture_raw_head()),
+    /// and to use the HTTP transport. This will panic if the raw head was not captured.
+    #[must_use]
     #[track_caller]
-    pub fn assert_status_success(&self) {
-        let status_code = self.status_code.as_u16();
-        let received_debug = StatusCodeFormatter(self.status_code);
+    pub fn raw_head(&self) -> &str {
         let debug_request_format = self.debug_request_format();
-        let debug_body = DebugResponseBody(self);
 
-        assert!(
-            200 <= status_code && status_code <= 299,
-            "Expect status code within 2xx range, received {received_debug}, for request {debug_request_format}, with body {debug_body}"
-        );
+        self.maybe_raw_head()
+            .with_context(|| {
+                format!("Expected raw response head, it was not captured (this requires `capture_raw_head()` and the HTTP transport), for request {debug_request_format}")
+            })
+            .unwrap()
     }
 
-    /// Assert that the status code is **outside** the 2xx range.
-    /// i.e. A status code less than 200, or 300 or more.
-    #[track_caller]
-    pub fn assert_status_failure(&self) {
-        let status_code = self.status_code.as_u16();
-        let received_debug = StatusCodeFormatter(self.status_code);
-        let debug_request_format = self.debug_request_format();
-        let debug_body = DebugResponseBody(self);
-
-        assert!(
-            status_code < 200 || 299 < status_code,
-            "Expect status code outside 2xx range, received {received_debug}, for request {debug_request_format}, with body {debug_body}"
-        );
+    /// Returns the raw head of the response, if it was captured.
+    ///
+    /// See [`TestResponse::raw_head()`] for details.
+    #[must_use]
+    pub fn maybe_raw_head(&self) -> Option<&str> {
+        self.maybe_raw_head.as_deref()
     }
 
-    /// Assert the status code is within the range given.
+    /// Asserts the raw head of the response contains the text given,
+    /// for checking the exact casing and formatting of headers sent over the wire.
+    ///
+    /// See [`TestResponse::raw_head()`] for the requirements to capture the raw head.
     ///
     /// ```rust
     /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
     /// #
-    /// use axum::Json;
+    /// use axum::Router;
     /// use axum::routing::get;
-    /// use axum::routing::Router;
     /// use axum_test::TestServer;
-    /// use http::StatusCode;
     ///
     /// let app = Router::new()
-    ///     .route(&"/json", get(|| async {
-    ///         StatusCode::OK
-    ///     }));
-    /// let server = TestServer::new(app).unwrap();
-    ///
-    /// // Within success statuses
-    /// server
-    ///     .get(&"/json")
-    ///     .await
-    ///     .assert_status_in_range(200..=299);
+    ///     .route(&"/todo", get(|| async { ([("x-todo-count", "3")], "todos") }));
     ///
-    /// // Outside success
-    /// server
-    ///     .get(&"/json")
-    ///     .await
-    ///     .assert_status_in_range(300..);
+    /// let server = TestServer::builder()
+    ///     .http_transport()
+    ///     .capture_raw_head()
+    ///     .build(app)?;
     ///
-    /// // Before server error
-    /// server
-    ///     .get(&"/json")
+    /// server.get(&"/todo")
     ///     .await
-    ///     .assert_status_in_range(..StatusCode::INTERNAL_SERVER_ERROR);
+    ///     .assert_raw_head_contains("\r\nx-todo-count: 3\r\n");
     /// #
     /// # Ok(()) }
     /// ```
-    pub fn assert_status_in_range<R, S>(&self, expected_status_range: R)
-    where
-        R: RangeBounds<S> + TryIntoRangeBounds<StatusCode> + Debug,
-        S: TryInto<StatusCode>,
-    {
-        let range = TryIntoRangeBounds::<StatusCode>::try_into_range_bounds(expected_status_range)
-            .expect("Failed to convert status code");
+    #[track_caller]
+    pub fn assert_raw_head_contains(&self, expected: &str) {
+        self.check_raw_head_contains(expected).or_panic()
+    }
 
-        let status_code = self.status_code();
-        let is_in_range = range.contains(&status_code);
+    /// Checks the raw head of the response contains the text given.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_raw_head_contains()`].
+    pub fn check_raw_head_contains(&self, expected: &str) -> Result<(), AssertionError> {
         let debug_request_format = self.debug_request_format();
-        let debug_body = DebugResponseBody(self);
+        let raw_head = self.maybe_raw_head().ok_or_else(|| {
+            AssertionError::new(format!(
+                "Expected raw response head, it was not captured (this requires `capture_raw_head()` and the HTTP transport), for request {debug_request_format}"
+            ))
+        })?;
 
-        assert!(
-            is_in_range,
-            "Expected status to be in range {}, received {status_code}, for request {debug_request_format}, with body {debug_body}",
-            format_status_code_range(range)
-        );
+        check(
+            raw_head.contains(expected),
+            format_args!(
+                "Expected raw response head to contain {expected:?}, received {raw_head:?}, for request {debug_request_format}"
+            ),
+        )
     }
 
-    /// Assert the status code is not within the range given.
+    /// Returns the route pattern Axum matched for the request, such as `/users/:id`.
+    ///
+    /// This requires the [`MatchedRouteLayer`](crate::MatchedRouteLayer) to be added to the `Router`.
+    /// This will panic if no route was matched, such as when the request was handled by a fallback.
+    #[cfg(feature = "matched-route")]
+    #[must_use]
+    #[track_caller]
+    pub fn matched_route(&self) -> &str {
+        let debug_request_format = self.debug_request_format();
+
+        self.maybe_matched_route()
+            .with_context(|| {
+                format!("Expected matched route, none was found (this requires the `MatchedRouteLayer`), for request {debug_request_format}")
+            })
+            .unwrap()
+    }
+
+    /// Returns the route pattern Axum matched for the request, if there was one.
+    ///
+    /// See [`TestResponse::matched_route()`] for details.
+    #[cfg(feature = "matched-route")]
+    #[must_use]
+    pub fn maybe_matched_route(&self) -> Option<&str> {
+        self.maybe_matched_route.as_deref()
+    }
+
+    /// Asserts the request was handled by the route pattern given, such as `/users/:id`.
+    ///
+    /// This requires the [`MatchedRouteLayer`](crate::MatchedRouteLayer) to be added to the `Router`.
     ///
     /// ```rust
     /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
     /// #
-    /// use axum::Json;
+    /// use axum::Router;
     /// use axum::routing::get;
-    /// use axum::routing::Router;
+    /// use axum_test::MatchedRouteLayer;
     /// use axum_test::TestServer;
-    /// use http::StatusCode;
     ///
     /// let app = Router::new()
-    ///     .route(&"/json", get(|| async {
-    ///         StatusCode::NOT_FOUND
-    ///     }));
-    /// let server = TestServer::new(app).unwrap();
+    ///     .route(&"/users/:id", get(|| async { "user" }))
+    ///     .fallback(|| async { "fallback" })
+    ///     .layer(MatchedRouteLayer);
+    /// let server = TestServer::new(app)?;
     ///
-    /// // Is not success
-    /// server
-    ///     .get(&"/json")
+    /// server.get(&"/users/123")
     ///     .await
-    ///     .assert_status_not_in_range(200..=299);
+    ///     .assert_matched_route("/users/:id");
+    /// #
+    /// # Ok(()) }
+    /// ```
+    #[cfg(feature = "matched-route")]
+    #[track_caller]
+    pub fn assert_matched_route(&self, expected_route: &str) {
+        self.check_matched_route(expected_route).or_panic()
+    }
+
+    /// Checks the request was handled by the route pattern given.
     ///
-    /// // 300 or higher
-    /// server
-    ///     .get(&"/json")
+    /// This is the non-panicking version of [`TestResponse::assert_matched_route()`].
+    #[cfg(feature = "matched-route")]
+    pub fn check_matched_route(&self, expected_route: &str) -> Result<(), AssertionError> {
+        let debug_request_format = self.debug_request_format();
+        let matched_route = self.maybe_matched_route().ok_or_else(|| {
+            AssertionError::new(format!(
+                "Expected matched route '{expected_route}', no route was matched (the request may have reached a fallback, or the `MatchedRouteLayer` is missing), for request {debug_request_format}"
+            ))
+        })?;
+
+        check_eq(
+            expected_route,
+            matched_route,
+            format_args!("Expected matched route to match, for request {debug_request_format}"),
+        )
+    }
+
+    /// Asserts the `ETag` header is the hash of the response body,
+    /// using the algorithm given.
+    ///
+    /// The hash can be encoded as hex (in either case) or base64 (standard or URL safe),
+    /// and the `ETag` can be weak or strong.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum::routing::get;
+    /// use axum_test::HashAlgorithm;
+    /// use axum_test::TestServer;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/abc", get(|| async {
+    ///         ([("etag", "\"ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad\"")], "abc")
+    ///     }));
+    /// let server = TestServer::new(app)?;
+    ///
+    /// server.get(&"/abc")
     ///     .await
-    ///     .assert_status_not_in_range(300..);
+    ///     .assert_etag_matches_body_hash(HashAlgorithm::Sha256);
+    /// #
+    /// # Ok(()) }
+    /// ```
+    #[cfg(feature = "etag")]
+    #[track_caller]
+    pub fn assert_etag_matches_body_hash(&self, algorithm: HashAlgorithm) {
+        self.check_etag_matches_body_hash(algorithm).or_panic()
+    }
+
+    /// Checks the `ETag` header is the hash of the response body,
+    /// using the algorithm given.
     ///
-    /// // After server error
-    /// server
-    ///     .get(&"/json")
+    /// This is the non-panicking version of [`TestResponse::assert_etag_matches_body_hash()`].
+    #[cfg(feature = "etag")]
+    pub fn check_etag_matches_body_hash(
+        &self,
+        algorithm: HashAlgorithm,
+    ) -> Result<(), AssertionError> {
+        let debug_request_format = self.debug_request_format();
+        let etag = self.etag_for_check()?;
+        let opaque_tag = etag_opaque_tag(&etag);
+        let body_hash = algorithm.digest(&self.response_body);
+
+        let is_hex_match = decode_hex(opaque_tag).is_ok_and(|decoded| decoded == body_hash);
+        let is_base64_match = decode_base64(opaque_tag).is_ok_and(|decoded| decoded == body_hash);
+
+        check(
+            is_hex_match || is_base64_match,
+            format_args!(
+                "Expected ETag to be the {algorithm} hash of the body, {}, received ETag {etag}, for request {debug_request_format}",
+                encode_hex(&body_hash)
+            ),
+        )
+    }
+
+    /// Asserts the `ETag` header is a weak validator, i.e. `W/"abc123"`.
+    #[cfg(feature = "etag")]
+    #[track_caller]
+    pub fn assert_weak_etag(&self) {
+        self.check_weak_etag().or_panic()
+    }
+
+    /// Checks the `ETag` header is a weak validator.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_weak_etag()`].
+    #[cfg(feature = "etag")]
+    pub fn check_weak_etag(&self) -> Result<(), AssertionError> {
+        let debug_request_format = self.debug_request_format();
+        let etag = self.etag_for_check()?;
+
+        check(
+            etag.starts_with("W/\""),
+            format_args!("Expected weak ETag, received {etag}, for request {debug_request_format}"),
+        )
+    }
+
+    #[cfg(feature = "etag")]
+    fn etag_for_check(&self) -> Result<String, AssertionError> {
+        let debug_request_format = self.debug_request_format();
+
+        self.headers
+            .get(header::ETAG)
+            .and_then(|etag| etag.to_str().ok())
+            .map(ToString::to_string)
+            .ok_or_else(|| {
+                AssertionError::new(format!(
+                    "Expected ETag header, none was found, for request {debug_request_format}"
+                ))
+            })
+    }
+
+    /// The Method used to produce this response.
+    #[must_use]
+    pub fn request_method(&self) -> Method {
+        self.method.clone()
+    }
+
+    /// The full URL that was used to produce this response.
+    #[must_use]
+    pub fn request_url(&self) -> Url {
+        self.full_request_url.clone()
+    }
+
+    /// The headers sent with the request that produced this response.
+    ///
+    /// These are the headers as built by the `TestRequest`,
+    /// including cookies and content type, but not those added by the transport (such as `Host`).
+    #[must_use]
+    pub fn request_headers(&self) -> &HeaderMap<HeaderValue> {
+        &self.recorded_request.headers
+    }
+
+    /// The body sent with the request that produced this response.
+    ///
+    /// `None` is returned if the body was larger than
+    /// [`TestServerConfig::max_recorded_request_body`](crate::TestServerConfig::max_recorded_request_body),
+    /// and so no copy was kept.
+    #[must_use]
+    pub fn request_body_bytes(&self) -> Option<&Bytes> {
+        self.recorded_request.maybe_body.as_ref()
+    }
+
+    /// Returns Rust code which sends the same request,
+    /// and asserts this response is received, for starting a regression test.
+    ///
+    /// See [`RecordedExchange::to_test_code()`](crate::RecordedExchange::to_test_code()) for more details.
+    #[must_use]
+    pub fn to_test_code(&self) -> String {
+        RecordedExchange::from_response(self).to_test_code()
+    }
+
+    /// The headers and body that were sent with the request,
+    /// if it was sent with an `Idempotency-Key`.
+    pub(crate) fn maybe_replayable_request(&self) -> Option<&ReplayableRequest> {
+        self.maybe_replayable_request.as_ref()
+    }
+
+    /// Returns the `Idempotency-Key` header sent with the request,
+    /// including keys generated by [`TestRequest::random_idempotency_key()`](crate::TestRequest::random_idempotency_key()).
+    ///
+    /// `None` is returned if the request had no idempotency key.
+    #[must_use]
+    pub fn maybe_idempotency_key(&self) -> Option<String> {
+        self.maybe_replayable_request
+            .as_ref()?
+            .headers
+            .get(IDEMPOTENCY_KEY_HEADER)
+            .and_then(|key| key.to_str().ok())
+            .map(ToString::to_string)
+    }
+
+    /// Returns the `Idempotency-Key` header sent with the request,
+    /// including keys generated by [`TestRequest::random_idempotency_key()`](crate::TestRequest::random_idempotency_key()).
+    ///
+    /// This will panic if the request had no idempotency key.
+    #[must_use]
+    #[track_caller]
+    pub fn idempotency_key(&self) -> String {
+        let debug_request_format = self.debug_request_format();
+
+        self.maybe_idempotency_key()
+            .with_context(|| format!("Expected request to have an Idempotency-Key header, for request {debug_request_format}"))
+            .unwrap()
+    }
+
+    /// Returns the target of the first link in the `Link` headers with the relation given.
+    ///
+    /// i.e. `link_header("next")` returns `/page/2`, for the header `Link: </page/2>; rel="next"`.
+    ///
+    /// The target is returned exactly as it appears in the header.
+    /// `None` is returned when there is no link with that relation.
+    #[must_use]
+    pub fn link_header(&self, rel: &str) -> Option<String> {
+        self.links()
+            .into_iter()
+            .find(|link| link.has_rel(rel))
+            .map(|link| link.target)
+    }
+
+    /// Returns every link in the `Link` headers, in the order they appear.
+    ///
+    /// Malformed links are skipped.
+    ///
+    /// Links sent on a `103 Early Hints` response are not included,
+    /// as informational responses are not exposed by the transports.
+    #[must_use]
+    pub fn links(&self) -> Vec<LinkEntry> {
+        self.headers
+            .get_all(header::LINK)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(parse_link_header)
+            .collect()
+    }
+
+    /// Asserts the `Link` headers include a `preload` of the target given,
+    /// with the `as` type given (i.e. `style`, `script`, or `font`).
+    ///
+    /// The target is compared exactly as it appears in the header.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum::http::header;
+    /// use axum::routing::get;
+    /// use axum_test::TestServer;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/", get(|| async {
+    ///         ([(header::LINK, r#"</style.css>; rel=preload; as=style"#)], "home")
+    ///     }));
+    /// let server = TestServer::new(app)?;
+    ///
+    /// server.get(&"/")
     ///     .await
-    ///     .assert_status_not_in_range(..StatusCode::INTERNAL_SERVER_ERROR);
+    ///     .assert_preload("/style.css", "style");
     /// #
     /// # Ok(()) }
     /// ```
-    pub fn assert_status_not_in_range<R, S>(&self, expected_status_range: R)
+    #[track_caller]
+    pub fn assert_preload(&self, target: &str, as_type: &str) {
+        self.check_preload(target, as_type).or_panic()
+    }
+
+    /// Checks the `Link` headers include a `preload` of the target given,
+    /// with the `as` type given.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_preload()`].
+    pub fn check_preload(&self, target: &str, as_type: &str) -> Result<(), AssertionError> {
+        let debug_request_format = self.debug_request_format();
+        let link = self.check_link_with_rel("preload", target)?;
+        let received_as_type = link.as_type();
+
+        check(
+            received_as_type.is_some_and(|received| received.eq_ignore_ascii_case(as_type)),
+            format_args!(
+                "Expected preload link '{target}' to have as={as_type}, received {received_as_type:?}, for request {debug_request_format}"
+            ),
+        )
+    }
+
+    /// Asserts the `Link` headers include a `prefetch` of the target given.
+    ///
+    /// The target is compared exactly as it appears in the header.
+    #[track_caller]
+    pub fn assert_prefetch(&self, target: &str) {
+        self.check_prefetch(target).or_panic()
+    }
+
+    /// Checks the `Link` headers include a `prefetch` of the target given.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_prefetch()`].
+    pub fn check_prefetch(&self, target: &str) -> Result<(), AssertionError> {
+        self.check_link_with_rel("prefetch", target).map(|_| ())
+    }
+
+    fn check_link_with_rel(&self, rel: &str, target: &str) -> Result<LinkEntry, AssertionError> {
+        let debug_request_format = self.debug_request_format();
+
+        self.links()
+            .into_iter()
+            .find(|link| link.has_rel(rel) && link.target == target)
+            .ok_or_else(|| {
+                AssertionError::new(format!(
+                    "Expected {rel} link '{target}', none found, for request {debug_request_format}"
+                ))
+            })
+    }
+
+    /// Builds a request to follow a link in this response, sent to the server given.
+    ///
+    /// The link is found by looking for:
+    ///
+    ///  * a `Link` header with the relation given (see [`TestResponse::link_header()`]),
+    ///  * then, with the `html` feature, an `<a>` or `<link>` element with that `rel`,
+    ///  * then, with the `html` feature, the first element matching it as a CSS selector which has a `href`.
+    ///
+    /// Relative links are resolved against the url of this request.
+    ///
+    /// This will panic if no link is found.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum::http::header;
+    /// use axum::routing::get;
+    /// use axum_test::TestServer;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/page/1", get(|| async { ([(header::LINK, r#"</page/2>; rel="next""#)], "one") }))
+    ///     .route(&"/page/2", get(|| async { "two" }));
+    /// let server = TestServer::new(app)?;
+    ///
+    /// let response = server.get(&"/page/1").await;
+    /// response.follow_link(&server, "next")
+    ///     .await
+    ///     .assert_text("two");
+    /// #
+    /// # Ok(()) }
+    /// ```
+    #[track_caller]
+    pub fn follow_link(&self, server: &TestServer, rel_or_selector: &str) -> TestRequest {
+        let maybe_target = self.link_header(rel_or_selector);
+
+        #[cfg(feature = "html")]
+        let maybe_target = maybe_target.or_else(|| self.html().find_link(rel_or_selector));
+
+        let debug_request_format = self.debug_request_format();
+        let target = maybe_target
+            .with_context(|| {
+                format!("Expected a link matching '{rel_or_selector}', none found, for request {debug_request_format}")
+            })
+            .unwrap();
+
+        let path = resolve_relative_url(&self.full_request_url, &target);
+        server.get(&path)
+    }
+
+    /// Returns the `href` of the first link with the relation given,
+    /// from the `_links` of a [HAL](https://datatracker.ietf.org/doc/html/draft-kelly-json-hal) response.
+    ///
+    /// The link is returned exactly as it appears in the body.
+    /// `None` is returned when there is no link with that relation,
+    /// and this will panic if the body is not Json.
+    #[must_use]
+    pub fn maybe_hal_link(&self, rel: &str) -> Option<String> {
+        let document = self.json::<Value>();
+        find_hal_link(&document, rel)
+    }
+
+    /// Asserts the HAL response has a link with the relation given,
+    /// and its `href` is the one expected.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Json;
+    /// use axum::Router;
+    /// use axum::routing::get;
+    /// use axum_test::TestServer;
+    /// use serde_json::json;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/users/1", get(|| async {
+    ///         Json(json!({
+    ///             "name": "Joe",
+    ///             "_links": {
+    ///                 "self": { "href": "/users/1" },
+    ///             },
+    ///         }))
+    ///     }));
+    /// let server = TestServer::new(app)?;
+    ///
+    /// server.get(&"/users/1")
+    ///     .await
+    ///     .assert_hal_link("self", "/users/1");
+    /// #
+    /// # Ok(()) }
+    /// ```
+    #[track_caller]
+    pub fn assert_hal_link(&self, rel: &str, expected_href: &str) {
+        self.check_hal_link(rel, expected_href).or_panic()
+    }
+
+    /// Checks the HAL response has a link with the relation given,
+    /// and its `href` is the one expected.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_hal_link()`].
+    pub fn check_hal_link(&self, rel: &str, expected_href: &str) -> Result<(), AssertionError> {
+        let document = self.try_json::<Value>()?;
+        let debug_request_format = self.debug_request_format();
+        let href = find_hal_link(&document, rel).ok_or_else(|| {
+            AssertionError::new(format!(
+                "Expected HAL link '{rel}', none found, for request {debug_request_format}"
+            ))
+        })?;
+
+        if expected_href == href {
+            return Ok(());
+        }
+
+        let masked_document = self.data_mask().masked_json(&document);
+        let masked_href = find_hal_link(&masked_document, rel).unwrap_or_default();
+        check_text_eq(
+            expected_href,
+            masked_href.as_str(),
+            self.data_mask(),
+            format_args!("Expected HAL link '{rel}' to match, for request {debug_request_format}"),
+        )
+    }
+
+    /// Builds a request to follow a link in the `_links` of this HAL response,
+    /// sent to the server given.
+    ///
+    /// Relative links are resolved against the url of this request.
+    ///
+    /// This will panic if no link is found.
+    #[track_caller]
+    pub fn follow_hal_link(&self, server: &TestServer, rel: &str) -> TestRequest {
+        let debug_request_format = self.debug_request_format();
+        let target = self
+            .maybe_hal_link(rel)
+            .with_context(|| {
+                format!("Expected HAL link '{rel}', none found, for request {debug_request_format}")
+            })
+            .unwrap();
+
+        let path = resolve_relative_url(&self.full_request_url, &target);
+        server.get(&path)
+    }
+
+    /// Deserializes a resource from the `_embedded` member of a HAL response,
+    /// into the type given.
+    ///
+    /// Relations holding several resources can be read into a `Vec`.
+    /// This will panic if the relation is missing, or cannot be deserialized.
+    #[must_use]
+    #[track_caller]
+    pub fn hal_embedded<T>(&self, rel: &str) -> T
     where
-        R: RangeBounds<S> + TryIntoRangeBounds<StatusCode> + Debug,
-        S: TryInto<StatusCode>,
+        T: DeserializeOwned,
     {
-        let range = TryIntoRangeBounds::<StatusCode>::try_into_range_bounds(expected_status_range)
-            .expect("Failed to convert status code");
-
-        let status_code = self.status_code();
-        let is_not_in_range = !range.contains(&status_code);
         let debug_request_format = self.debug_request_format();
-        let debug_body = DebugResponseBody(self);
+        let mut document = self.json::<Value>();
+        let embedded = document
+            .get_mut("_embedded")
+            .and_then(|embedded| embedded.get_mut(rel))
+            .map(Value::take)
+            .with_context(|| {
+                format!("Expected HAL embedded resource '{rel}', none found, for request {debug_request_format}")
+            })
+            .unwrap();
+
+        serde_json::from_value::<T>(embedded)
+            .with_context(|| {
+                format!("Deserializing HAL embedded resource '{rel}', for request {debug_request_format}")
+            })
+            .unwrap()
+    }
+
+    /// Returns the total number of results from the `@odata.count` of an OData response.
+    ///
+    /// This is only included when the request asked for it,
+    /// such as with [`TestRequest::odata_count()`](crate::TestRequest::odata_count()).
+    /// `None` is returned when it is missing, and this will panic if the body is not Json.
+    #[must_use]
+    pub fn maybe_odata_count(&self) -> Option<u64> {
+        self.json::<Value>().get("@odata.count")?.as_u64()
+    }
+
+    /// Returns the total number of results from the `@odata.count` of an OData response.
+    ///
+    /// This will panic if it is missing.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Json;
+    /// use axum::Router;
+    /// use axum::routing::get;
+    /// use axum_test::TestServer;
+    /// use serde_json::json;
+    /// use serde_json::Value;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/people", get(|| async {
+    ///         Json(json!({
+    ///             "@odata.count": 25,
+    ///             "value": [{ "name": "Joe" }],
+    ///         }))
+    ///     }));
+    /// let server = TestServer::new(app)?;
+    ///
+    /// let response = server.get(&"/people")
+    ///     .odata_count()
+    ///     .odata_top(1)
+    ///     .await;
+    ///
+    /// assert_eq!(response.odata_count(), 25);
+    /// assert_eq!(response.odata_value::<Vec<Value>>().len(), 1);
+    /// #
+    /// # Ok(()) }
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn odata_count(&self) -> u64 {
+        let debug_request_format = self.debug_request_format();
+
+        self.maybe_odata_count()
+            .with_context(|| {
+                format!("Expected OData response to have '@odata.count', none found, for request {debug_request_format}")
+            })
+            .unwrap()
+    }
+
+    /// Asserts the `@odata.count` of an OData response is the number given.
+    #[track_caller]
+    pub fn assert_odata_count(&self, expected_count: u64) {
+        self.check_odata_count(expected_count).or_panic()
+    }
+
+    /// Checks the `@odata.count` of an OData response is the number given.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_odata_count()`].
+    pub fn check_odata_count(&self, expected_count: u64) -> Result<(), AssertionError> {
+        let document = self.try_json::<Value>()?;
+        let debug_request_format = self.debug_request_format();
+        let received_count = document.get("@odata.count").and_then(Value::as_u64);
+
+        check_eq(
+            &Some(expected_count),
+            &received_count,
+            format_args!("Expected '@odata.count' to match, for request {debug_request_format}"),
+        )
+    }
+
+    /// Deserializes the `value` of an OData collection response, into the type given.
+    ///
+    /// This will panic if it is missing, or cannot be deserialized.
+    #[must_use]
+    #[track_caller]
+    pub fn odata_value<T>(&self) -> T
+    where
+        T: DeserializeOwned,
+    {
+        let debug_request_format = self.debug_request_format();
+        let mut document = self.json::<Value>();
+        let value = document
+            .get_mut("value")
+            .map(Value::take)
+            .with_context(|| {
+                format!("Expected OData response to have 'value', none found, for request {debug_request_format}")
+            })
+            .unwrap();
+
+        serde_json::from_value::<T>(value)
+            .with_context(|| {
+                format!("Deserializing OData value, for request {debug_request_format}")
+            })
+            .unwrap()
+    }
+
+    /// Finds a header with the given name.
+    /// If there are multiple headers with the same name,
+    /// then only the first [`HeaderValue`](::http::HeaderValue) will be returned.
+    ///
+    /// `None` is returned when no header was found.
+    #[must_use]
+    #[track_caller]
+    pub fn maybe_header<N>(&self, name: N) -> Option<HeaderValue>
+    where
+        N: TryInto<HeaderName>,
+        N::Error: Debug,
+    {
+        let header_name = name.try_into().expect_with(|| {
+            format!(
+                "Failed to build HeaderName from name given, for request {}",
+                self.debug_request_format()
+            )
+        });
+        self.headers.get(header_name).map(|h| h.to_owned())
+    }
+
+    /// Returns the headers returned from the response.
+    #[must_use]
+    pub fn headers(&self) -> &HeaderMap<HeaderValue> {
+        &self.headers
+    }
+
+    #[must_use]
+    #[track_caller]
+    pub fn maybe_content_type(&self) -> Option<String> {
+        self.headers.get(http::header::CONTENT_TYPE).map(|header| {
+            header
+                .to_str()
+                .with_context(|| {
+                    format!("Failed to decode header CONTENT_TYPE, received '{header:?}'")
+                })
+                .unwrap()
+                .to_string()
+        })
+    }
+
+    #[must_use]
+    #[track_caller]
+    pub fn content_type(&self) -> String {
+        self.maybe_content_type()
+            .expect("CONTENT_TYPE not found in response header")
+    }
+
+    /// Returns the `charset` parameter from the `Content-Type` header, if one is present.
+    ///
+    /// i.e. `Content-Type: text/html; charset=ISO-8859-1` will return `ISO-8859-1`.
+    #[must_use]
+    pub fn maybe_charset(&self) -> Option<String> {
+        let content_type = self.maybe_content_type()?;
+        let mime = content_type.parse::<Mime>().ok()?;
+
+        mime.get_param(mime::CHARSET)
+            .map(|charset| charset.as_str().to_string())
+    }
+
+    /// Asserts the `Content-Type` header matches the content type given.
+    ///
+    /// Parameters are only compared when given,
+    /// so `application/json` matches `application/json; charset=utf-8`.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Json;
+    /// use axum::Router;
+    /// use axum::routing::get;
+    /// use axum_test::TestServer;
+    /// use serde_json::json;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/user", get(|| async { Json(json!({ "name": "Joe" })) }));
+    /// let server = TestServer::new(app)?;
+    ///
+    /// server.get(&"/user").await.assert_content_type("application/json");
+    /// #
+    /// # Ok(()) }
+    /// ```
+    #[track_caller]
+    pub fn assert_content_type(&self, expected_content_type: &str) {
+        self.check_content_type(expected_content_type).or_panic()
+    }
+
+    /// Checks the `Content-Type` header matches the content type given.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_content_type()`].
+    pub fn check_content_type(&self, expected_content_type: &str) -> Result<(), AssertionError> {
+        let debug_request_format = self.debug_request_format();
+        let expected_mime = expected_content_type
+            .parse::<Mime>()
+            .with_context(|| {
+                format!("Failed to parse expected content type '{expected_content_type}', for request {debug_request_format}")
+            })?;
+        let content_type = self.maybe_content_type().with_context(|| {
+            format!("Expected content type '{expected_content_type}', header was not found, for request {debug_request_format}")
+        })?;
+
+        let is_match = is_content_type_match(&content_type, &expected_mime);
+
+        check(
+            is_match,
+            format_args!("Expected content type '{expected_content_type}', received '{content_type}', for request {debug_request_format}"),
+        )
+    }
+
+    /// Finds a header with the given name.
+    /// If there are multiple headers with the same name,
+    /// then only the first will be returned.
+    ///
+    /// If no header is found, then this will panic.
+    #[must_use]
+    #[track_caller]
+    pub fn header<N>(&self, name: N) -> HeaderValue
+    where
+        N: TryInto<HeaderName> + Display + Clone,
+        N::Error: Debug,
+    {
+        let debug_header = name.clone();
+        let header_name = name.try_into().expect_with(|| {
+            format!(
+                "Failed to build HeaderName from name given, '{debug_header}', for request {}",
+                self.debug_request_format()
+            )
+        });
+        self.headers
+            .get(header_name)
+            .map(|h| h.to_owned())
+            .with_context(|| {
+                let debug_request_format = self.debug_request_format();
+
+                format!("Cannot find header {debug_header}, for request {debug_request_format}",)
+            })
+            .unwrap()
+    }
+
+    /// Iterates over all of the headers contained in the response.
+    pub fn iter_headers(&self) -> impl Iterator<Item = (&'_ HeaderName, &'_ HeaderValue)> {
+        self.headers.iter()
+    }
+
+    /// Iterates over all of the headers for a specific name, contained in the response.
+    #[track_caller]
+    pub fn iter_headers_by_name<N>(&self, name: N) -> impl Iterator<Item = &'_ HeaderValue>
+    where
+        N: TryInto<HeaderName>,
+        N::Error: Debug,
+    {
+        let header_name = name.try_into().expect_with(|| {
+            format!(
+                "Failed to build HeaderName from name given, for request {}",
+                self.debug_request_format()
+            )
+        });
+        self.headers.get_all(header_name).iter()
+    }
+
+    /// Returns all of the headers in the response, including duplicates.
+    ///
+    /// Where a header appears multiple times, each value is included,
+    /// in the order they were received.
+    ///
+    /// The order is only kept between values of the same header name.
+    /// Headers are stored in a [`HeaderMap`](http::HeaderMap),
+    /// which does not keep the order across different names,
+    /// so the order of headers with different names is arbitrary.
+    #[must_use]
+    pub fn headers_in_order(&self) -> Vec<(HeaderName, HeaderValue)> {
+        self.headers
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect()
+    }
+
+    #[must_use]
+    #[track_caller]
+    pub fn contains_header<N>(&self, name: N) -> bool
+    where
+        N: TryInto<HeaderName>,
+        N::Error: Debug,
+    {
+        let header_name = name.try_into().expect_with(|| {
+            format!(
+                "Failed to build HeaderName from name given, for request {}",
+                self.debug_request_format()
+            )
+        });
+        self.headers.contains_key(header_name)
+    }
+
+    /// Asserts the header named is present in the response.
+    ///
+    /// If the header is not present, then the assertion fails.
+    #[track_caller]
+    pub fn assert_contains_header<N>(&self, name: N)
+    where
+        N: TryInto<HeaderName> + Display + Clone,
+        N::Error: Debug,
+    {
+        self.check_contains_header(name).or_panic()
+    }
+
+    /// Checks the header named is present in the response.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_contains_header()`].
+    pub fn check_contains_header<N>(&self, name: N) -> Result<(), AssertionError>
+    where
+        N: TryInto<HeaderName> + Display + Clone,
+        N::Error: Debug,
+    {
+        let debug_header_name = name.clone();
+        let debug_request_format = self.debug_request_format();
+        let has_header = self.contains_header(name);
+
+        check(has_header, format_args!("Expected header '{debug_header_name}' to be present in response, header was not found, for request {debug_request_format}"))
+    }
+
+    #[track_caller]
+    pub fn assert_header<N, V>(&self, name: N, value: V)
+    where
+        N: TryInto<HeaderName> + Display + Clone,
+        N::Error: Debug,
+        V: TryInto<HeaderValue>,
+        V::Error: Debug,
+    {
+        self.check_header(name, value).or_panic()
+    }
+
+    /// Checks the header named is present in the response,
+    /// and matches the value given.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_header()`].
+    #[track_caller]
+    pub fn check_header<N, V>(&self, name: N, value: V) -> Result<(), AssertionError>
+    where
+        N: TryInto<HeaderName> + Display + Clone,
+        N::Error: Debug,
+        V: TryInto<HeaderValue>,
+        V::Error: Debug,
+    {
+        let debug_header_name = name.clone();
+        let header_name = name.try_into().expect_with(|| {
+            format!(
+                "Failed to build HeaderName from name given, for request {}",
+                self.debug_request_format()
+            )
+        });
+        let expected_header_value = value
+            .try_into()
+            .expect("Could not turn given value into HeaderValue");
+        let debug_request_format = self.debug_request_format();
+        let is_masked = self.data_mask.is_masked_header(&header_name);
+        let found_header_value = self
+            .maybe_header(header_name)
+            .with_context(|| {
+                format!("Expected header '{debug_header_name}' to be present in response, header was not found, for request {debug_request_format}")
+            })?;
+
+        if is_masked {
+            return check(
+                expected_header_value == found_header_value,
+                format_args!("Expected header '{debug_header_name}' to match, for request {debug_request_format}, the values are not shown as the header is masked"),
+            );
+        }
+
+        check_eq(
+            &expected_header_value,
+            &found_header_value,
+            format_args!("Expected header '{debug_header_name}' to match, for request {debug_request_format}"),
+        )
+    }
+
+    /// Asserts the header named appears exactly `expected_count` times in the response.
+    ///
+    /// This is useful for headers which are sent multiple times, such as `Set-Cookie`.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum::response::AppendHeaders;
+    /// use axum::routing::get;
+    /// use axum_test::TestServer;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/login", get(|| async {
+    ///         AppendHeaders([("set-cookie", "session=abc"), ("set-cookie", "theme=dark")])
+    ///     }));
+    /// let server = TestServer::new(app)?;
+    ///
+    /// server.get(&"/login")
+    ///     .await
+    ///     .assert_header_count("set-cookie", 2);
+    /// #
+    /// # Ok(()) }
+    /// ```
+    #[track_caller]
+    pub fn assert_header_count<N>(&self, name: N, expected_count: usize)
+    where
+        N: TryInto<HeaderName> + Display + Clone,
+        N::Error: Debug,
+    {
+        self.check_header_count(name, expected_count).or_panic()
+    }
+
+    /// Checks the header named appears exactly `expected_count` times in the response.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_header_count()`].
+    pub fn check_header_count<N>(
+        &self,
+        name: N,
+        expected_count: usize,
+    ) -> Result<(), AssertionError>
+    where
+        N: TryInto<HeaderName> + Display + Clone,
+        N::Error: Debug,
+    {
+        let debug_header_name = name.clone();
+        let debug_request_format = self.debug_request_format();
+        let received_count = self.iter_headers_by_name(name).count();
+
+        check(
+            expected_count == received_count,
+            format_args!("Expected header '{debug_header_name}' to appear {expected_count} times, received {received_count}, for request {debug_request_format}"),
+        )
+    }
+
+    /// Asserts the values of the header named match those given,
+    /// in the same order, and with no other values present.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum::response::AppendHeaders;
+    /// use axum::routing::get;
+    /// use axum_test::TestServer;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/chain", get(|| async {
+    ///         AppendHeaders([("x-chain", "a"), ("x-chain", "b")])
+    ///     }));
+    /// let server = TestServer::new(app)?;
+    ///
+    /// server.get(&"/chain")
+    ///     .await
+    ///     .assert_header_values_in_order("x-chain", ["a", "b"]);
+    /// #
+    /// # Ok(()) }
+    /// ```
+    #[track_caller]
+    pub fn assert_header_values_in_order<N, I, V>(&self, name: N, expected_values: I)
+    where
+        N: TryInto<HeaderName> + Display + Clone,
+        N::Error: Debug,
+        I: IntoIterator<Item = V>,
+        V: TryInto<HeaderValue>,
+        V::Error: Debug,
+    {
+        self.check_header_values_in_order(name, expected_values)
+            .or_panic()
+    }
+
+    /// Checks the values of the header named match those given,
+    /// in the same order, and with no other values present.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_header_values_in_order()`].
+    #[track_caller]
+    pub fn check_header_values_in_order<N, I, V>(
+        &self,
+        name: N,
+        expected_values: I,
+    ) -> Result<(), AssertionError>
+    where
+        N: TryInto<HeaderName> + Display + Clone,
+        N::Error: Debug,
+        I: IntoIterator<Item = V>,
+        V: TryInto<HeaderValue>,
+        V::Error: Debug,
+    {
+        let debug_header_name = name.clone();
+        let debug_request_format = self.debug_request_format();
+        let expected_values: Vec<HeaderValue> = expected_values
+            .into_iter()
+            .map(|value| {
+                value
+                    .try_into()
+                    .expect("Could not turn given value into HeaderValue")
+            })
+            .collect();
+        let header_name: HeaderName = name.try_into().expect_with(|| {
+            format!(
+                "Failed to build HeaderName from name given, for request {debug_request_format}"
+            )
+        });
+        let is_masked = self.data_mask.is_masked_header(&header_name);
+        let received_values: Vec<HeaderValue> =
+            self.iter_headers_by_name(header_name).cloned().collect();
+
+        if is_masked {
+            return check(
+                expected_values == received_values,
+                format_args!("Expected header '{debug_header_name}' values to match in order, for request {debug_request_format}, the values are not shown as the header is masked"),
+            );
+        }
+
+        check_eq(
+            &expected_values,
+            &received_values,
+            format_args!("Expected header '{debug_header_name}' values to match in order, for request {debug_request_format}"),
+        )
+    }
+
+    /// Asserts the `Content-Language` header includes the language given.
+    ///
+    /// The header may list multiple languages, separated by commas,
+    /// and languages are compared case insensitively.
+    #[track_caller]
+    pub fn assert_content_language<L>(&self, expected_language: L)
+    where
+        L: AsRef<str>,
+    {
+        self.check_content_language(expected_language).or_panic()
+    }
+
+    /// Checks the `Content-Language` header includes the language given.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_content_language()`].
+    pub fn check_content_language<L>(&self, expected_language: L) -> Result<(), AssertionError>
+    where
+        L: AsRef<str>,
+    {
+        let expected_language = expected_language.as_ref();
+        let debug_request_format = self.debug_request_format();
+        let content_language = self
+            .maybe_header(header::CONTENT_LANGUAGE)
+            .with_context(|| {
+                format!("Expected header 'content-language' to be present in response, header was not found, for request {debug_request_format}")
+            })?;
+        let content_language = content_language.to_str().unwrap_or_default();
+        let has_language = content_language
+            .split(',')
+            .any(|language| language.trim().eq_ignore_ascii_case(expected_language));
+
+        check(
+            has_language,
+            format_args!("Expected content language '{expected_language}', received '{content_language}', for request {debug_request_format}"),
+        )
+    }
+
+    /// Asserts the `Vary` headers include the header name given.
+    ///
+    /// Names are compared case insensitively, across all `Vary` headers.
+    /// A `Vary: *` header matches any name.
+    #[track_caller]
+    pub fn assert_vary_contains<N>(&self, expected_name: N)
+    where
+        N: AsRef<str>,
+    {
+        self.check_vary_contains(expected_name).or_panic()
+    }
+
+    /// Checks the `Vary` headers include the header name given.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_vary_contains()`].
+    pub fn check_vary_contains<N>(&self, expected_name: N) -> Result<(), AssertionError>
+    where
+        N: AsRef<str>,
+    {
+        let expected_name = expected_name.as_ref();
+        let debug_request_format = self.debug_request_format();
+        let vary_names = self.header_list_values(header::VARY);
+        let has_name = vary_names
+            .iter()
+            .any(|name| *name == "*" || name.eq_ignore_ascii_case(expected_name));
+
+        check(
+            has_name,
+            format_args!("Expected Vary header to contain '{expected_name}', received {vary_names:?}, for request {debug_request_format}"),
+        )
+    }
+
+    /// Asserts the header named contains the value given,
+    /// within its comma separated list of values.
+    ///
+    /// Values are trimmed and compared case insensitively,
+    /// across all headers with this name.
+    /// This is for list-valued headers such as `Vary`, `Allow`, or `Accept-Ranges`,
+    /// where the order and spacing of the values is not significant.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum::response::AppendHeaders;
+    /// use axum::routing::get;
+    /// use axum_test::TestServer;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/cors", get(|| async {
+    ///         AppendHeaders([("vary", "Accept-Encoding,  Origin")])
+    ///     }));
+    /// let server = TestServer::new(app)?;
+    ///
+    /// server.get(&"/cors")
+    ///     .await
+    ///     .assert_header_contains_value("vary", "origin");
+    /// #
+    /// # Ok(()) }
+    /// ```
+    #[track_caller]
+    pub fn assert_header_contains_value<N, V>(&self, name: N, expected_value: V)
+    where
+        N: TryInto<HeaderName> + Display + Clone,
+        N::Error: Debug,
+        V: AsRef<str>,
+    {
+        self.check_header_contains_value(name, expected_value)
+            .or_panic()
+    }
+
+    /// Checks the header named contains the value given,
+    /// within its comma separated list of values.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_header_contains_value()`].
+    pub fn check_header_contains_value<N, V>(
+        &self,
+        name: N,
+        expected_value: V,
+    ) -> Result<(), AssertionError>
+    where
+        N: TryInto<HeaderName> + Display + Clone,
+        N::Error: Debug,
+        V: AsRef<str>,
+    {
+        let expected_value = expected_value.as_ref().trim();
+        let debug_header_name = name.clone();
+        let debug_request_format = self.debug_request_format();
+        let header_name: HeaderName = name.try_into().expect_with(|| {
+            format!(
+                "Failed to build HeaderName from name given, for request {debug_request_format}"
+            )
+        });
+        let is_masked = self.data_mask.is_masked_header(&header_name);
+        let received_values = self.header_list_values(header_name);
+        let has_value = received_values
+            .iter()
+            .any(|value| value.eq_ignore_ascii_case(expected_value));
+
+        if is_masked {
+            return check(
+                has_value,
+                format_args!("Expected header '{debug_header_name}' to contain value '{expected_value}', for request {debug_request_format}, the values are not shown as the header is masked"),
+            );
+        }
+
+        check(
+            has_value,
+            format_args!("Expected header '{debug_header_name}' to contain value '{expected_value}', received {received_values:?}, for request {debug_request_format}"),
+        )
+    }
+
+    /// Asserts the `Allow` headers include each of the methods given,
+    /// in any order.
+    ///
+    /// Other methods may also be listed. For example axum includes `HEAD`
+    /// for any route with a `GET` handler.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum::routing::get;
+    /// use axum_test::TestServer;
+    /// use http::Method;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/todo", get(|| async { "ok" }).post(|| async { "ok" }));
+    /// let server = TestServer::new(app)?;
+    ///
+    /// server.delete(&"/todo")
+    ///     .expect_failure()
+    ///     .await
+    ///     .assert_allow_methods([Method::GET, Method::POST]);
+    /// #
+    /// # Ok(()) }
+    /// ```
+    #[track_caller]
+    pub fn assert_allow_methods<I>(&self, expected_methods: I)
+    where
+        I: IntoIterator<Item = Method>,
+    {
+        self.check_allow_methods(expected_methods).or_panic()
+    }
+
+    /// Checks the `Allow` headers include each of the methods given,
+    /// in any order.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_allow_methods()`].
+    pub fn check_allow_methods<I>(&self, expected_methods: I) -> Result<(), AssertionError>
+    where
+        I: IntoIterator<Item = Method>,
+    {
+        let debug_request_format = self.debug_request_format();
+        let received_methods = self.header_list_values(header::ALLOW);
+        let missing_methods: Vec<Method> = expected_methods
+            .into_iter()
+            .filter(|method| {
+                !received_methods
+                    .iter()
+                    .any(|received| received.eq_ignore_ascii_case(method.as_str()))
+            })
+            .collect();
+
+        check(
+            missing_methods.is_empty(),
+            format_args!("Expected Allow header to include methods {missing_methods:?}, received {received_methods:?}, for request {debug_request_format}"),
+        )
+    }
+
+    /// Asserts the `Accept-Ranges` header includes `bytes`,
+    /// meaning the server supports byte range requests.
+    #[track_caller]
+    pub fn assert_accept_ranges_bytes(&self) {
+        self.check_accept_ranges_bytes().or_panic()
+    }
+
+    /// Checks the `Accept-Ranges` header includes `bytes`.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_accept_ranges_bytes()`].
+    pub fn check_accept_ranges_bytes(&self) -> Result<(), AssertionError> {
+        self.check_header_contains_value(header::ACCEPT_RANGES, "bytes")
+    }
+
+    /// Asserts the `Date` header is within the tolerance given of the current time.
+    #[track_caller]
+    pub fn assert_date_within(&self, tolerance: Duration) {
+        self.check_date_within(tolerance).or_panic()
+    }
+
+    /// Checks the `Date` header is within the tolerance given of the current time.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_date_within()`].
+    pub fn check_date_within(&self, tolerance: Duration) -> Result<(), AssertionError> {
+        let debug_request_format = self.debug_request_format();
+        let date_header = self
+            .maybe_header(header::DATE)
+            .with_context(|| {
+                format!("Expected header 'date' to be present in response, header was not found, for request {debug_request_format}")
+            })?;
+        let raw_date = date_header.to_str().with_context(|| {
+            format!("Failed to read Date header, for request {debug_request_format}")
+        })?;
+        let date = parse_http_date(raw_date)
+            .with_context(|| format!("for request {debug_request_format}"))?;
+
+        let now = OffsetDateTime::now_utc();
+        let difference = (now - date).unsigned_abs();
+        check(
+            difference <= tolerance,
+            format_args!("Expected Date header to be within {tolerance:?} of now ({now}), received '{raw_date}', for request {debug_request_format}"),
+        )
+    }
+
+    /// Asserts the `Content-Type` matches the mime type guessed from the extension
+    /// at the end of the request path, i.e. `text/css` for `/assets/app.css`.
+    ///
+    /// This is for testing static files are served with the right content type.
+    /// Only the common static asset types are recognised.
+    #[track_caller]
+    pub fn assert_mime_guessed_from_extension(&self) {
+        self.check_mime_guessed_from_extension().or_panic()
+    }
+
+    /// Checks the `Content-Type` matches the mime type guessed from the extension of the request path.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_mime_guessed_from_extension()`].
+    pub fn check_mime_guessed_from_extension(&self) -> Result<(), AssertionError> {
+        let debug_request_format = self.debug_request_format();
+        let path = self.full_request_url.path();
+        let expected_mime = guess_mime_from_extension(path).with_context(|| {
+            format!("Failed to guess mime type from the extension of path '{path}', for request {debug_request_format}")
+        })?;
+
+        self.check_content_type(expected_mime)
+    }
+
+    /// Asserts the `Last-Modified` header is present, and holds a valid HTTP date.
+    #[track_caller]
+    pub fn assert_last_modified_present(&self) {
+        self.check_last_modified_present().or_panic()
+    }
+
+    /// Checks the `Last-Modified` header is present, and holds a valid HTTP date.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_last_modified_present()`].
+    pub fn check_last_modified_present(&self) -> Result<(), AssertionError> {
+        let debug_request_format = self.debug_request_format();
+        let last_modified_header = self
+            .maybe_header(header::LAST_MODIFIED)
+            .with_context(|| {
+                format!("Expected header 'last-modified' to be present in response, header was not found, for request {debug_request_format}")
+            })?;
+        let raw_last_modified = last_modified_header.to_str().with_context(|| {
+            format!("Failed to read Last-Modified header, for request {debug_request_format}")
+        })?;
+        parse_http_date(raw_last_modified).with_context(|| {
+            format!("Failed to parse Last-Modified header, for request {debug_request_format}")
+        })?;
+
+        Ok(())
+    }
+
+    /// Returns true if the response carries a `Deprecation` or `Sunset` header,
+    /// marking the endpoint as deprecated.
+    #[must_use]
+    pub fn is_deprecated(&self) -> bool {
+        self.contains_header(DEPRECATION_HEADER) || self.contains_header(SUNSET_HEADER)
+    }
+
+    /// Asserts the response does not carry a `Deprecation` or `Sunset` header.
+    ///
+    /// This is useful for catching requests to endpoints which are being phased out.
+    #[track_caller]
+    pub fn assert_not_deprecated(&self) {
+        self.check_not_deprecated().or_panic()
+    }
+
+    /// Checks the response does not carry a `Deprecation` or `Sunset` header.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_not_deprecated()`].
+    pub fn check_not_deprecated(&self) -> Result<(), AssertionError> {
+        let debug_request_format = self.debug_request_format();
+
+        if let Some(deprecation) = self.maybe_header(DEPRECATION_HEADER) {
+            return Err(AssertionError::new(format!("Expected response to not be deprecated, found Deprecation header {deprecation:?}, for request {debug_request_format}")));
+        }
+
+        if let Some(sunset) = self.maybe_header(SUNSET_HEADER) {
+            return Err(AssertionError::new(format!("Expected response to not be deprecated, found Sunset header {sunset:?}, for request {debug_request_format}")));
+        }
+
+        Ok(())
+    }
+
+    /// Finds a [`Cookie`] with the given name.
+    /// If there are multiple matching cookies,
+    /// then only the first will be returned.
+    ///
+    /// `None` is returned if no Cookie is found.
+    #[must_use]
+    pub fn maybe_cookie(&self, cookie_name: &str) -> Option<Cookie<'static>> {
+        for cookie in self.iter_cookies() {
+            if cookie.name() == cookie_name {
+                return Some(cookie.into_owned());
+            }
+        }
+
+        None
+    }
+
+    /// Finds a [`Cookie`](::cookie::Cookie) with the given name.
+    /// If there are multiple matching cookies,
+    /// then only the first will be returned.
+    ///
+    /// If no `Cookie` is found, then this will panic.
+    #[must_use]
+    #[track_caller]
+    pub fn cookie(&self, cookie_name: &str) -> Cookie<'static> {
+        self.maybe_cookie(cookie_name)
+            .with_context(|| {
+                let debug_request_format = self.debug_request_format();
+
+                format!("Cannot find cookie {cookie_name}, for request {debug_request_format}")
+            })
+            .unwrap()
+    }
+
+    /// Returns all of the cookies contained in the response,
+    /// within a [`CookieJar`](::cookie::CookieJar) object.
+    ///
+    /// See the `cookie` crate for details.
+    #[must_use]
+    pub fn cookies(&self) -> CookieJar {
+        let mut cookies = CookieJar::new();
+
+        for cookie in self.iter_cookies() {
+            cookies.add(cookie.into_owned());
+        }
+
+        cookies
+    }
+
+    /// Iterate over all of the cookies in the response.
+    #[track_caller]
+    pub fn iter_cookies(&self) -> impl Iterator<Item = Cookie<'_>> {
+        self.iter_headers_by_name(SET_COOKIE).map(|header| {
+            let header_str = header
+                .to_str()
+                .with_context(|| {
+                    let debug_request_format = self.debug_request_format();
+
+                    format!(
+                        "Reading header 'Set-Cookie' as string, for request {debug_request_format}",
+                    )
+                })
+                .unwrap();
+
+            Cookie::parse(header_str)
+                .with_context(|| {
+                    let debug_request_format = self.debug_request_format();
+
+                    format!("Parsing 'Set-Cookie' header, for request {debug_request_format}",)
+                })
+                .unwrap()
+        })
+    }
+
+    /// Consumes the request, turning it into a `TestWebSocket`.
+    /// If this cannot be done, then the response will panic.
+    ///
+    /// *Note*, this requires the server to be running on a real HTTP
+    /// port. Either using a randomly assigned port, or a specified one.
+    /// See the [`TestServerConfig::transport`](crate::TestServerConfig::transport) for more details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum_test::TestServer;
+    ///
+    /// let app = Router::new();
+    /// let server = TestServer::builder()
+    ///     .http_transport()
+    ///     .build(app)?;
+    ///
+    /// let mut websocket = server
+    ///     .get_websocket(&"/my-web-socket-end-point")
+    ///     .await
+    ///     .into_websocket()
+    ///     .await;
+    ///
+    /// websocket.send_text("Hello!").await;
+    /// #
+    /// # Ok(()) }
+    /// ```
+    ///
+    #[cfg(feature = "ws")]
+    #[must_use]
+    pub async fn into_websocket(self) -> TestWebSocket {
+        use crate::transport_layer::TransportLayerType;
+
+        // Using the mock approach will just fail.
+        if self.websockets.transport_type != TransportLayerType::Http {
+            unimplemented!("WebSocket requires a HTTP based transport layer, see `TestServerConfig::transport`");
+        }
+
+        self.check_upgrade_accepted().or_panic();
+
+        let debug_request_format = self.debug_request_format().to_string();
+
+        let on_upgrade = self.websockets.maybe_on_upgrade.with_context(|| {
+            format!("Expected WebSocket upgrade to be found, it is None, for request {debug_request_format}")
+        })
+        .unwrap();
+
+        let upgraded = on_upgrade
+            .await
+            .with_context(|| {
+                format!("Failed to upgrade connection for, for request {debug_request_format}")
+            })
+            .unwrap();
+
+        let in_flight = crate::ServerSharedState::start_in_flight(
+            &self.websockets.server_state,
+            crate::internals::InFlightKind::WebSocket,
+            debug_request_format,
+        )
+        .unwrap();
+
+        TestWebSocket::new(upgraded, self.websockets.server_state, in_flight).await
+    }
+
+    /// Stands in for `into_websocket` when built with `dyn-features`, without the `ws` feature.
+    ///
+    /// This always returns an [`Error::MissingFeature`](crate::Error::MissingFeature),
+    /// as the WebSocket types are not available.
+    #[cfg(all(feature = "dyn-features", not(feature = "ws")))]
+    pub async fn into_websocket(self) -> Result<std::convert::Infallible, crate::Error> {
+        Err(missing_feature_error("TestResponse::into_websocket", "ws"))
+    }
+
+    /// Asserts the server rejected the WebSocket upgrade,
+    /// by responding with a status code other than `101 Switching Protocols`.
+    ///
+    /// See [`TestRequest::expect_upgrade_rejected()`](crate::TestRequest::expect_upgrade_rejected())
+    /// for checking this when the request is sent.
+    #[cfg(feature = "ws")]
+    #[track_caller]
+    pub fn assert_upgrade_rejected(&self) {
+        self.check_upgrade_rejected().or_panic()
+    }
+
+    /// Checks the server rejected the WebSocket upgrade.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_upgrade_rejected()`].
+    #[cfg(feature = "ws")]
+    pub fn check_upgrade_rejected(&self) -> Result<(), AssertionError> {
+        let debug_request_format = self.debug_request_format();
+
+        check(
+            self.status_code != StatusCode::SWITCHING_PROTOCOLS,
+            format_args!("Expected WebSocket upgrade to be rejected, server switched protocols, for request {debug_request_format}"),
+        )
+    }
+
+    #[cfg(feature = "ws")]
+    fn check_upgrade_accepted(&self) -> Result<(), AssertionError> {
+        let debug_request_format = self.debug_request_format();
+        let received_debug = StatusCodeFormatter(self.status_code);
+        let body = String::from_utf8_lossy(&self.response_body);
+
+        check(
+            self.status_code == StatusCode::SWITCHING_PROTOCOLS,
+            format_args!("Expected WebSocket upgrade, server rejected it with {received_debug} and body '{body}', for request {debug_request_format}"),
+        )
+    }
+
+    /// Asserts the `charset` in the `Content-Type` header matches the one given.
+    /// The comparison is case insensitive.
+    ///
+    /// If there is no `charset` in the response, then this will panic.
+    #[track_caller]
+    pub fn assert_charset<C>(&self, expected: C)
+    where
+        C: AsRef<str>,
+    {
+        self.check_charset(expected).or_panic()
+    }
+
+    /// Checks the `charset` in the `Content-Type` header matches the one given.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_charset()`].
+    pub fn check_charset<C>(&self, expected: C) -> Result<(), AssertionError>
+    where
+        C: AsRef<str>,
+    {
+        let expected_charset = expected.as_ref();
+        let debug_request_format = self.debug_request_format();
+
+        match self.maybe_charset() {
+            None => Err(AssertionError::new(format!(
+                "Expected charset '{expected_charset}', no charset was found in the Content-Type, for request {debug_request_format}"
+            ))),
+            Some(received_charset) => check(
+                received_charset.eq_ignore_ascii_case(expected_charset),
+                format_args!("Expected charset '{expected_charset}', received '{received_charset}', for request {debug_request_format}"),
+            ),
+        }
+    }
+
+    /// This performs an assertion comparing the whole body of the response,
+    /// against the text provided.
+    #[track_caller]
+    pub fn assert_text<C>(&self, expected: C)
+    where
+        C: AsRef<str>,
+    {
+        self.check_text(expected).or_panic()
+    }
+
+    /// Checks the whole body of the response matches the text provided.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_text()`].
+    pub fn check_text<C>(&self, expected: C) -> Result<(), AssertionError>
+    where
+        C: AsRef<str>,
+    {
+        let expected_contents = expected.as_ref();
+        let debug_request_format = self.debug_request_format();
+
+        check_text_eq(
+            expected_contents,
+            self.text().as_str(),
+            self.data_mask(),
+            format_args!("Expected text to match, for request {debug_request_format}"),
+        )
+    }
+
+    /// Asserts the raw bytes of the response body match the bytes given as base64.
+    ///
+    /// This is useful for binary responses, such as images or signatures,
+    /// where writing the expected bytes inline would be unwieldy.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum::routing::get;
+    /// use axum_test::TestServer;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/pixel", get(|| async { vec![0x47_u8, 0x49, 0x46, 0x38] }));
+    ///
+    /// let server = TestServer::new(app)?;
+    /// server.get(&"/pixel")
+    ///     .await
+    ///     .assert_body_base64_eq("R0lGOA==");
+    /// #
+    /// # Ok(()) }
+    /// ```
+    #[track_caller]
+    pub fn assert_body_base64_eq<C>(&self, expected_base64: C)
+    where
+        C: AsRef<str>,
+    {
+        self.check_body_base64_eq(expected_base64).or_panic()
+    }
+
+    /// Checks the raw bytes of the response body match the bytes given as base64.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_body_base64_eq()`].
+    pub fn check_body_base64_eq<C>(&self, expected_base64: C) -> Result<(), AssertionError>
+    where
+        C: AsRef<str>,
+    {
+        let expected_base64 = expected_base64.as_ref();
+        let debug_request_format = self.debug_request_format();
+        let expected_bytes = decode_base64(expected_base64).map_err(|err| {
+            AssertionError::new(format!(
+                "{err}, the expected body must be base64, for request {debug_request_format}"
+            ))
+        })?;
+        let received_base64 = encode_base64(self.as_bytes());
+
+        check(
+            expected_bytes == self.as_bytes().as_ref(),
+            format_args!("Expected body to match base64 '{expected_base64}', received '{received_base64}', for request {debug_request_format}"),
+        )
+    }
+
+    /// Asserts the raw bytes of the response body match the bytes given as hex.
+    #[track_caller]
+    pub fn assert_body_hex_eq<C>(&self, expected_hex: C)
+    where
+        C: AsRef<str>,
+    {
+        self.check_body_hex_eq(expected_hex).or_panic()
+    }
+
+    /// Checks the raw bytes of the response body match the bytes given as hex.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_body_hex_eq()`].
+    pub fn check_body_hex_eq<C>(&self, expected_hex: C) -> Result<(), AssertionError>
+    where
+        C: AsRef<str>,
+    {
+        let expected_hex = expected_hex.as_ref();
+        let debug_request_format = self.debug_request_format();
+        let expected_bytes = decode_hex(expected_hex).map_err(|err| {
+            AssertionError::new(format!(
+                "{err}, the expected body must be hex, for request {debug_request_format}"
+            ))
+        })?;
+        let received_hex = encode_hex(self.as_bytes());
+
+        check(
+            expected_bytes == self.as_bytes().as_ref(),
+            format_args!("Expected body to match hex '{expected_hex}', received '{received_hex}', for request {debug_request_format}"),
+        )
+    }
+
+    /// This asserts if the text given is contained, somewhere, within the response.
+    #[track_caller]
+    pub fn assert_text_contains<C>(&self, expected: C)
+    where
+        C: AsRef<str>,
+    {
+        self.check_text_contains(expected).or_panic()
+    }
+
+    /// Checks the text given is contained, somewhere, within the response.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_text_contains()`].
+    pub fn check_text_contains<C>(&self, expected: C) -> Result<(), AssertionError>
+    where
+        C: AsRef<str>,
+    {
+        let expected_contents = expected.as_ref();
+        let received = self.text();
+        let is_contained = received.contains(expected_contents);
+
+        check(
+            is_contained,
+            format_args!("Failed to find '{expected_contents}', received '{received}'"),
+        )
+    }
+
+    /// Asserts the whole body of the response matches the wildcard pattern given.
+    ///
+    /// A `*` matches any number of characters (including none),
+    /// and a `?` matches exactly one character.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum::routing::get;
+    /// use axum_test::TestServer;
+    ///
+    /// let app = Router::new()
+    ///     .route("/inbox", get(|| async { "Hello, Joe! You have 3 new messages" }));
+    /// let server = TestServer::new(app)?;
+    ///
+    /// server.get("/inbox")
+    ///     .await
+    ///     .assert_text_matches_pattern("Hello, *! You have ? new messages");
+    /// #
+    /// # Ok(()) }
+    /// ```
+    #[track_caller]
+    pub fn assert_text_matches_pattern(&self, pattern: &str) {
+        self.check_text_matches_pattern(pattern).or_panic()
+    }
+
+    /// Checks the whole body of the response matches the wildcard pattern given.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_text_matches_pattern()`].
+    pub fn check_text_matches_pattern(&self, pattern: &str) -> Result<(), AssertionError> {
+        let received = self.text();
+        let debug_request_format = self.debug_request_format();
+
+        check(
+            is_text_pattern_match(pattern, &received),
+            format_args!("Expected text to match pattern '{pattern}', received '{received}', for request {debug_request_format}"),
+        )
+    }
+
+    /// Asserts the body of the response matches the regular expression given.
+    ///
+    /// The expression is not anchored, so use `^` and `$` to match the whole body.
+    /// This will panic if the expression is invalid.
+    #[cfg(feature = "regex")]
+    #[track_caller]
+    pub fn assert_text_matches_regex(&self, regex: &str) {
+        self.check_text_matches_regex(regex).or_panic()
+    }
+
+    /// Checks the body of the response matches the regular expression given.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_text_matches_regex()`].
+    #[cfg(feature = "regex")]
+    pub fn check_text_matches_regex(&self, regex: &str) -> Result<(), AssertionError> {
+        let compiled =
+            Regex::new(regex).with_context(|| format!("Failed to build regex from '{regex}'"))?;
+        let received = self.text();
+        let debug_request_format = self.debug_request_format();
+
+        check(
+            compiled.is_match(&received),
+            format_args!("Expected text to match regex '{regex}', received '{received}', for request {debug_request_format}"),
+        )
+    }
+
+    /// Returns the capture groups from matching the regular expression given against the body.
+    /// The first capture group is the first item, and groups which did not take part are empty.
+    ///
+    /// This will panic if the expression is invalid, or does not match.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum::routing::post;
+    /// use axum_test::TestServer;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/users", post(|| async { "created user-42 at /users/42" }));
+    /// let server = TestServer::new(app)?;
+    ///
+    /// let captures = server.post(&"/users")
+    ///     .await
+    ///     .text_captures(r"^created (user-\d+) at (\S+)$");
+    ///
+    /// assert_eq!(captures, vec!["user-42", "/users/42"]);
+    /// #
+    /// # Ok(()) }
+    /// ```
+    #[cfg(feature = "regex")]
+    #[must_use]
+    #[track_caller]
+    pub fn text_captures(&self, regex: &str) -> Vec<String> {
+        let compiled = Regex::new(regex)
+            .with_context(|| format!("Failed to build regex from '{regex}'"))
+            .unwrap();
+        let received = self.text();
+        let debug_request_format = self.debug_request_format();
+
+        let captures = compiled.captures(&received).unwrap_or_else(|| {
+            panic!("Expected text to match regex '{regex}', received '{received}', for request {debug_request_format}")
+        });
+
+        captures
+            .iter()
+            .skip(1)
+            .map(|capture| capture.map_or_else(String::new, |capture| capture.as_str().to_string()))
+            .collect()
+    }
+
+    /// Asserts the response body starts with the bytes given.
+    #[track_caller]
+    pub fn assert_body_starts_with<B>(&self, expected_prefix: B)
+    where
+        B: AsRef<[u8]>,
+    {
+        self.check_body_starts_with(expected_prefix).or_panic()
+    }
+
+    /// Checks the response body starts with the bytes given.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_body_starts_with()`].
+    pub fn check_body_starts_with<B>(&self, expected_prefix: B) -> Result<(), AssertionError>
+    where
+        B: AsRef<[u8]>,
+    {
+        let expected_prefix = expected_prefix.as_ref();
+        let received = self.as_bytes();
+        let received_prefix = &received[..received.len().min(expected_prefix.len())];
+        let debug_request_format = self.debug_request_format();
+
+        check(
+            received.starts_with(expected_prefix),
+            format_args!("Expected body to start with {:?}, received {:?}, for request {debug_request_format}", Bytes::copy_from_slice(expected_prefix), Bytes::copy_from_slice(received_prefix)),
+        )
+    }
+
+    /// Asserts the response body ends with the bytes given.
+    #[track_caller]
+    pub fn assert_body_ends_with<B>(&self, expected_suffix: B)
+    where
+        B: AsRef<[u8]>,
+    {
+        self.check_body_ends_with(expected_suffix).or_panic()
+    }
+
+    /// Checks the response body ends with the bytes given.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_body_ends_with()`].
+    pub fn check_body_ends_with<B>(&self, expected_suffix: B) -> Result<(), AssertionError>
+    where
+        B: AsRef<[u8]>,
+    {
+        let expected_suffix = expected_suffix.as_ref();
+        let received = self.as_bytes();
+        let received_suffix = &received[received.len().saturating_sub(expected_suffix.len())..];
+        let debug_request_format = self.debug_request_format();
+
+        check(
+            received.ends_with(expected_suffix),
+            format_args!(
+                "Expected body to end with {:?}, received {:?}, for request {debug_request_format}",
+                Bytes::copy_from_slice(expected_suffix),
+                Bytes::copy_from_slice(received_suffix)
+            ),
+        )
+    }
+
+    /// Asserts the response body starts with the signature for the kind of file given.
+    ///
+    /// This is a cheap sanity check for binary endpoints,
+    /// and does not validate the rest of the file.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum::routing::get;
+    /// use axum_test::FileKind;
+    /// use axum_test::TestServer;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/report.pdf", get(|| async { &b"%PDF-1.7\n..."[..] }));
+    /// let server = TestServer::new(app)?;
+    ///
+    /// server.get(&"/report.pdf")
+    ///     .await
+    ///     .assert_body_magic(FileKind::Pdf);
+    /// #
+    /// # Ok(()) }
+    /// ```
+    #[track_caller]
+    pub fn assert_body_magic(&self, expected_kind: FileKind) {
+        self.check_body_magic(expected_kind).or_panic()
+    }
+
+    /// Checks the response body starts with the signature for the kind of file given.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_body_magic()`].
+    pub fn check_body_magic(&self, expected_kind: FileKind) -> Result<(), AssertionError> {
+        let received = self.as_bytes();
+        let debug_request_format = self.debug_request_format();
+
+        if expected_kind.is_match(received) {
+            return Ok(());
+        }
+
+        let message = match FileKind::detect(received) {
+            Some(received_kind) => format!("Expected body to be a {expected_kind} file, received a {received_kind} file, for request {debug_request_format}"),
+            None => format!("Expected body to be a {expected_kind} file, received unrecognised bytes, for request {debug_request_format}"),
+        };
+
+        Err(AssertionError::new(message))
+    }
+
+    /// Asserts the response from the server matches the contents of the file.
+    #[track_caller]
+    pub fn assert_text_from_file<P>(&self, path: P)
+    where
+        P: AsRef<Path>,
+    {
+        self.check_text_from_file(path).or_panic()
+    }
+
+    /// Checks the response from the server matches the contents of the file.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_text_from_file()`].
+    pub fn check_text_from_file<P>(&self, path: P) -> Result<(), AssertionError>
+    where
+        P: AsRef<Path>,
+    {
+        let path_ref = path.as_ref();
+        let expected = read_to_string(path_ref)
+            .with_context(|| format!("Failed to read from file '{}'", path_ref.display()))?;
+
+        self.check_text(expected)
+    }
+
+    /// Deserializes the contents of the request as Json,
+    /// and asserts it matches the value given.
+    ///
+    /// If `other` does not match, or the response is not Json,
+    /// then this will panic.
+    #[track_caller]
+    pub fn assert_json<T>(&self, expected: &T)
+    where
+        T: DeserializeOwned + PartialEq<T> + Serialize,
+    {
+        self.check_json(expected).or_panic()
+    }
+
+    /// Deserializes the contents of the request as Json,
+    /// and checks it matches the value given.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_json()`].
+    pub fn check_json<T>(&self, expected: &T) -> Result<(), AssertionError>
+    where
+        T: DeserializeOwned + PartialEq<T> + Serialize,
+    {
+        let received = self.try_json::<T>()?;
+        let debug_request_format = self.debug_request_format();
+
+        check_serialized_eq(
+            expected,
+            &received,
+            self.data_mask(),
+            format_args!("Expected Json to match, for request {debug_request_format}"),
+        )
+    }
+
+    /// Asserts the response body is byte for byte the canonical Json of the value given,
+    /// as defined by [RFC 8785](https://www.rfc-editor.org/rfc/rfc8785) (JCS).
+    ///
+    /// This is useful for testing signed payloads, where the server must
+    /// send the exact bytes that were signed.
+    /// Use [`TestResponse::canonical_json()`] when the server does not send canonical Json.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum::routing::get;
+    /// use axum_test::TestServer;
+    /// use serde_json::json;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/payload", get(|| async { r#"{"amount":4.5,"currency":"GBP"}"# }));
+    ///
+    /// let server = TestServer::new(app)?;
+    /// server.get(&"/payload")
+    ///     .await
+    ///     .assert_canonical_json_eq(&json!({
+    ///         "currency": "GBP",
+    ///         "amount": 4.50,
+    ///     }));
+    /// #
+    /// # Ok(()) }
+    /// ```
+    #[track_caller]
+    pub fn assert_canonical_json_eq<T>(&self, expected: &T)
+    where
+        T: Serialize,
+    {
+        self.check_canonical_json_eq(expected).or_panic()
+    }
+
+    /// Checks the response body is byte for byte the canonical Json of the value given.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_canonical_json_eq()`].
+    pub fn check_canonical_json_eq<T>(&self, expected: &T) -> Result<(), AssertionError>
+    where
+        T: Serialize,
+    {
+        let debug_request_format = self.debug_request_format();
+        let expected_value = serde_json::to_value(expected).map_err(|err| {
+            AssertionError::new(format!(
+                "Failed to serialize expected value to Json, {err}, for request {debug_request_format}"
+            ))
+        })?;
+        let expected_canonical = format_canonical_json(&expected_value);
+        let received = self.text();
+
+        check_text_eq(
+            expected_canonical.as_str(),
+            received.as_str(),
+            self.data_mask(),
+            format_args!("Expected response to be the canonical Json given, for request {debug_request_format}"),
+        )
+    }
+
+    /// Asserts the content is within the json returned.
+    /// This is useful for when servers return times and IDs that you
+    /// wish to ignore.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum::extract::Json;
+    /// use axum::routing::get;
+    /// use axum_test::TestServer;
+    /// use serde_json::json;
+    /// use std::time::Instant;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/user", get(|| async {
+    ///         let id = Instant::now().elapsed().as_millis();
+    ///
+    ///         Json(json!({
+    ///            "id": id,
+    ///            "name": "Joe",
+    ///            "age": 20,
+    ///        }))
+    ///     }));
+    /// let server = TestServer::new(app)?;
+    ///
+    /// // Checks the response contains _only_ the values listed here,
+    /// // and ignores the rest.
+    /// server.get(&"/user")
+    ///     .await
+    ///     .assert_json_contains(&json!({
+    ///         "name": "Joe",
+    ///         "age": 20,
+    ///     }));
+    /// #
+    /// # Ok(()) }
+    /// ```
+    #[track_caller]
+    pub fn assert_json_contains<T>(&self, expected: &T)
+    where
+        T: Serialize,
+    {
+        self.check_json_contains(expected).or_panic()
+    }
+
+    /// Checks the content is within the json returned.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_json_contains()`].
+    pub fn check_json_contains<T>(&self, expected: &T) -> Result<(), AssertionError>
+    where
+        T: Serialize,
+    {
+        let received = self.try_json::<Value>()?;
+        let debug_request_format = self.debug_request_format();
+
+        let diff_config = JsonDiffConfig::new(CompareMode::Inclusive);
+        if assert_json_matches_no_panic(&received, expected, diff_config.clone()).is_ok() {
+            return Ok(());
+        }
+
+        let data_mask = self.data_mask();
+        let masked_expected = serde_json::to_value(expected)
+            .map(|expected| data_mask.masked_json(&expected))
+            .map_err(|err| {
+                AssertionError::new(format!(
+                    "Failed to serialize expected value to Json, {err}, for request {debug_request_format}"
+                ))
+            })?;
+        let masked_received = data_mask.masked_json(&received);
+
+        match assert_json_matches_no_panic(&masked_received, &masked_expected, diff_config) {
+            Err(diff) => Err(AssertionError::new(format!(
+                "Expected Json to contain the value given, for request {debug_request_format}\n\n{diff}"
+            ))),
+            Ok(()) => Err(AssertionError::new(format!(
+                "Expected Json to contain the value given, for request {debug_request_format}, the differences are within masked data"
+            ))),
+        }
+    }
+
+    /// Asserts the Json returned matches the value given,
+    /// with numbers allowed to differ within the tolerance given.
+    ///
+    /// This is useful for floating point values, such as prices or coordinates,
+    /// where the exact representation can differ.
+    ///
+    /// The tolerance can be a [`JsonTolerance`](crate::JsonTolerance),
+    /// or a `f64` for an absolute tolerance.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum::extract::Json;
+    /// use axum::routing::get;
+    /// use axum_test::JsonTolerance;
+    /// use axum_test::TestServer;
+    /// use serde_json::json;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/location", get(|| async {
+    ///         Json(json!({
+    ///            "lat": 51.50740001,
+    ///            "lng": -0.12779999,
+    ///        }))
+    ///     }));
+    /// let server = TestServer::new(app)?;
+    ///
+    /// let response = server.get(&"/location").await;
+    /// response.assert_json_approx(&json!({
+    ///     "lat": 51.5074,
+    ///     "lng": -0.1278,
+    /// }), 0.0001);
+    /// response.assert_json_approx(&json!({
+    ///     "lat": 51.5074,
+    ///     "lng": -0.1278,
+    /// }), JsonTolerance::Relative(0.001));
+    /// #
+    /// # Ok(()) }
+    /// ```
+    #[track_caller]
+    pub fn assert_json_approx<T, E>(&self, expected: &T, tolerance: E)
+    where
+        T: Serialize,
+        E: Into<JsonTolerance>,
+    {
+        self.check_json_approx(expected, tolerance).or_panic()
+    }
+
+    /// Checks the Json returned matches the value given,
+    /// with numbers allowed to differ within the tolerance given.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_json_approx()`].
+    #[track_caller]
+    pub fn check_json_approx<T, E>(&self, expected: &T, tolerance: E) -> Result<(), AssertionError>
+    where
+        T: Serialize,
+        E: Into<JsonTolerance>,
+    {
+        let tolerance = tolerance.into();
+        let expected_value = serde_json::to_value(expected)
+            .expect("It should serialize the expected value into Json");
+        let received = self.try_json::<Value>()?;
+
+        if find_json_approx_mismatch(&expected_value, &received, tolerance).is_some() {
+            let debug_request_format = self.debug_request_format();
+            let data_mask = self.data_mask();
+            let mismatch = find_json_approx_mismatch(
+                &data_mask.masked_json(&expected_value),
+                &data_mask.masked_json(&received),
+                tolerance,
+            )
+            .unwrap_or_else(|| "the differences are within masked data".to_string());
+
+            return Err(AssertionError::new(format!(
+                "Expected Json to match within {tolerance}, {mismatch}, for request {debug_request_format}"
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Read json file from given path and assert it with json response.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Json;
+    /// use axum::routing::get;
+    /// use axum::routing::Router;
+    /// use axum_test::TestServer;
+    /// use serde_json::json;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/json", get(|| async {
+    ///         Json(json!({
+    ///             "name": "Joe",
+    ///             "age": 20,
+    ///         }))
+    ///     }));
+    ///
+    /// let server = TestServer::new(app).unwrap();
+    /// server
+    ///     .get(&"/json")
+    ///     .await
+    ///     .assert_json_from_file("files/example.json");
+    /// #
+    /// # Ok(()) }
+    /// ```
+    ///
+    #[track_caller]
+    pub fn assert_json_from_file<P>(&self, path: P)
+    where
+        P: AsRef<Path>,
+    {
+        self.check_json_from_file(path).or_panic()
+    }
+
+    /// Read json file from given path and check it matches the json response.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_json_from_file()`].
+    pub fn check_json_from_file<P>(&self, path: P) -> Result<(), AssertionError>
+    where
+        P: AsRef<Path>,
+    {
+        let path_ref = path.as_ref();
+        let file = File::open(path_ref)
+            .with_context(|| format!("Failed to read from file '{}'", path_ref.display()))?;
+
+        let reader = BufReader::new(file);
+        let expected =
+            serde_json::from_reader::<_, serde_json::Value>(reader).with_context(|| {
+                format!(
+                    "Failed to deserialize file '{}' as json",
+                    path_ref.display()
+                )
+            })?;
+
+        self.check_json(&expected)
+    }
+
+    /// Deserializes the contents of the request as Yaml,
+    /// and asserts it matches the value given.
+    ///
+    /// If `other` does not match, or the response is not Yaml,
+    /// then this will panic.
+    #[cfg(any(feature = "yaml", feature = "dyn-features"))]
+    #[track_caller]
+    pub fn assert_yaml<T>(&self, other: &T)
+    where
+        T: DeserializeOwned + PartialEq<T> + Serialize,
+    {
+        self.check_yaml(other).or_panic()
+    }
+
+    /// Deserializes the contents of the request as Yaml,
+    /// and checks it matches the value given.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_yaml()`].
+    #[cfg(any(feature = "yaml", feature = "dyn-features"))]
+    pub fn check_yaml<T>(&self, other: &T) -> Result<(), AssertionError>
+    where
+        T: DeserializeOwned + PartialEq<T> + Serialize,
+    {
+        let received = self.try_yaml::<T>()?;
+        let debug_request_format = self.debug_request_format();
+
+        check_serialized_eq(
+            other,
+            &received,
+            self.data_mask(),
+            format_args!("Expected Yaml to match, for request {debug_request_format}"),
+        )
+    }
+
+    /// Read yaml file from given path and assert it with yaml response.
+    #[cfg(any(feature = "yaml", feature = "dyn-features"))]
+    #[track_caller]
+    pub fn assert_yaml_from_file<P>(&self, path: P)
+    where
+        P: AsRef<Path>,
+    {
+        self.check_yaml_from_file(path).or_panic()
+    }
+
+    /// Read yaml file from given path and check it matches the yaml response.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_yaml_from_file()`].
+    #[cfg(any(feature = "yaml", feature = "dyn-features"))]
+    pub fn check_yaml_from_file<P>(&self, path: P) -> Result<(), AssertionError>
+    where
+        P: AsRef<Path>,
+    {
+        #[cfg(not(feature = "yaml"))]
+        {
+            let _ = path;
+            Err(anyhow::Error::from(missing_feature_error(
+                "TestResponse::check_yaml_from_file",
+                "yaml",
+            ))
+            .into())
+        }
+
+        #[cfg(feature = "yaml")]
+        {
+            let path_ref = path.as_ref();
+            let file = File::open(path_ref)
+                .with_context(|| format!("Failed to read from file '{}'", path_ref.display()))?;
+
+            let reader = BufReader::new(file);
+            let expected =
+                serde_yaml::from_reader::<_, serde_yaml::Value>(reader).with_context(|| {
+                    format!(
+                        "Failed to deserialize file '{}' as yaml",
+                        path_ref.display()
+                    )
+                })?;
+
+            self.check_yaml(&expected)
+        }
+    }
+
+    /// Deserializes the contents of the request as MsgPack,
+    /// and asserts it matches the value given.
+    ///
+    /// If `other` does not match, or the response is not MsgPack,
+    /// then this will panic.
+    #[cfg(any(feature = "msgpack", feature = "dyn-features"))]
+    #[track_caller]
+    pub fn assert_msgpack<T>(&self, other: &T)
+    where
+        T: DeserializeOwned + PartialEq<T> + Serialize,
+    {
+        self.check_msgpack(other).or_panic()
+    }
+
+    /// Deserializes the contents of the request as MsgPack,
+    /// and checks it matches the value given.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_msgpack()`].
+    #[cfg(any(feature = "msgpack", feature = "dyn-features"))]
+    pub fn check_msgpack<T>(&self, other: &T) -> Result<(), AssertionError>
+    where
+        T: DeserializeOwned + PartialEq<T> + Serialize,
+    {
+        let received = self.try_msgpack::<T>()?;
+        let debug_request_format = self.debug_request_format();
+
+        check_serialized_eq(
+            other,
+            &received,
+            self.data_mask(),
+            format_args!("Expected MsgPack to match, for request {debug_request_format}"),
+        )
+    }
+
+    /// Deserializes the contents of the request as an url encoded form,
+    /// and asserts it matches the value given.
+    ///
+    /// If `other` does not match, or the response cannot be deserialized,
+    /// then this will panic.
+    #[track_caller]
+    pub fn assert_form<T>(&self, other: &T)
+    where
+        T: DeserializeOwned + PartialEq<T> + Serialize,
+    {
+        self.check_form(other).or_panic()
+    }
+
+    /// Deserializes the contents of the request as an url encoded form,
+    /// and checks it matches the value given.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_form()`].
+    pub fn check_form<T>(&self, other: &T) -> Result<(), AssertionError>
+    where
+        T: DeserializeOwned + PartialEq<T> + Serialize,
+    {
+        let received = self.try_form::<T>()?;
+        let debug_request_format = self.debug_request_format();
+
+        check_serialized_eq(
+            other,
+            &received,
+            self.data_mask(),
+            format_args!("Expected Form to match, for request {debug_request_format}"),
+        )
+    }
+
+    /// Asserts this response passes every check in the [`AssertionBundle`](crate::AssertionBundle) given.
+    ///
+    /// All checks are run, and the failure lists every check which did not pass.
+    #[track_caller]
+    pub fn assert_bundle(&self, bundle: &AssertionBundle) {
+        self.check_bundle(bundle).or_panic()
+    }
+
+    /// Checks this response passes every check in the [`AssertionBundle`](crate::AssertionBundle) given.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_bundle()`].
+    pub fn check_bundle(&self, bundle: &AssertionBundle) -> Result<(), AssertionError> {
+        bundle.check(self)
+    }
+
+    /// Asserts this response has the same status code and body as the response given.
+    ///
+    /// This is useful for checking a request replayed using
+    /// [`TestServer::replay()`](crate::TestServer::replay()) returns the same result as the original.
+    #[track_caller]
+    pub fn assert_same_response(&self, other: &TestResponse) {
+        self.check_same_response(other).or_panic()
+    }
+
+    /// Checks this response has the same status code and body as the response given.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_same_response()`].
+    pub fn check_same_response(&self, other: &TestResponse) -> Result<(), AssertionError> {
+        let debug_request_format = self.debug_request_format();
+        let expected_debug = StatusCodeFormatter(other.status_code);
+        let received_debug = StatusCodeFormatter(self.status_code);
+        let debug_content = DebugResponseContent(self);
+
+        check(
+            other.status_code == self.status_code,
+            format_args!("Expected status code to match the original {expected_debug}, received {received_debug}, for request {debug_request_format}, with {debug_content}"),
+        )?;
+
+        if other.as_bytes() == self.as_bytes() {
+            return Ok(());
+        }
+
+        check_text_eq(
+            &other.text(),
+            &self.text(),
+            self.data_mask(),
+            format_args!("Expected body to match the original, for request {debug_request_format}"),
+        )
+    }
+
+    /// Assert the response was served with the HTTP version given.
+    ///
+    /// The mock transport always reports the version set by your application,
+    /// so use a real HTTP transport to test which version is served over the wire.
+    #[track_caller]
+    pub fn assert_http_version(&self, expected_version: Version) {
+        self.check_http_version(expected_version).or_panic()
+    }
+
+    /// Checks the response was served with the HTTP version given.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_http_version()`].
+    pub fn check_http_version(&self, expected_version: Version) -> Result<(), AssertionError> {
+        let debug_request_format = self.debug_request_format();
+
+        check(
+            expected_version == self.version,
+            format_args!(
+                "Expected HTTP version to be {expected_version:?}, received {:?}, for request {debug_request_format}",
+                self.version
+            ),
+        )
+    }
+
+    /// Assert the response status code matches the one given.
+    #[track_caller]
+    pub fn assert_status(&self, expected_status_code: StatusCode) {
+        self.check_status(expected_status_code).or_panic()
+    }
+
+    /// Checks the response status code matches the one given.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_status()`].
+    pub fn check_status(&self, expected_status_code: StatusCode) -> Result<(), AssertionError> {
+        let received_debug = StatusCodeFormatter(self.status_code);
+        let expected_debug = StatusCodeFormatter(expected_status_code);
+        let debug_request_format = self.debug_request_format();
+        let debug_content = DebugResponseContent(self);
+
+        check(
+            expected_status_code == self.status_code,
+            format_args!("Expected status code to be {expected_debug}, received {received_debug}, for request {debug_request_format}, with {debug_content}"),
+        )
+    }
+
+    /// Assert the response status code matches the one given,
+    /// and prints the whole response body to stderr if it does not.
+    ///
+    /// The failure message of [`TestResponse::assert_status()`] cuts off long bodies.
+    /// This is for when the detail needed is further down the body, such as in a long stack trace.
+    #[track_caller]
+    pub fn assert_status_else_print_body(&self, expected_status_code: StatusCode) {
+        self.or_panic_printing_body(self.check_status(expected_status_code))
+    }
+
+    #[track_caller]
+    fn or_panic_printing_body(&self, result: Result<(), AssertionError>) {
+        if result.is_err() {
+            eprintln!(
+                "Response body, for request {}:\n{}",
+                self.debug_request_format(),
+                FullResponseBody(self)
+            );
+        }
+
+        result.or_panic()
+    }
+
+    /// Assert the response status code does **not** match the one given.
+    #[track_caller]
+    pub fn assert_not_status(&self, expected_status_code: StatusCode) {
+        self.check_not_status(expected_status_code).or_panic()
+    }
+
+    /// Checks the response status code does **not** match the one given.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_not_status()`].
+    pub fn check_not_status(&self, expected_status_code: StatusCode) -> Result<(), AssertionError> {
+        let received_debug = StatusCodeFormatter(self.status_code);
+        let expected_debug = StatusCodeFormatter(expected_status_code);
+        let debug_request_format = self.debug_request_format();
+        let debug_content = DebugResponseContent(self);
+
+        check(
+            expected_status_code != self.status_code,
+            format_args!("Expected status code to not be {expected_debug}, received {received_debug}, for request {debug_request_format}, with {debug_content}"),
+        )
+    }
+
+    /// Assert that the status code is **within** the 2xx range.
+    /// i.e. The range from 200-299.
+    #[track_caller]
+    pub fn assert_status_success(&self) {
+        self.check_status_success().or_panic()
+    }
+
+    /// Checks that the status code is **within** the 2xx range.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_status_success()`].
+    pub fn check_status_success(&self) -> Result<(), AssertionError> {
+        let status_code = self.status_code.as_u16();
+        let received_debug = StatusCodeFormatter(self.status_code);
+        let debug_request_format = self.debug_request_format();
+        let debug_content = DebugResponseContent(self);
+
+        check(
+            200 <= status_code && status_code <= 299,
+            format_args!("Expect status code within 2xx range, received {received_debug}, for request {debug_request_format}, with {debug_content}"),
+        )
+    }
+
+    /// Assert that the status code is **within** the 2xx range,
+    /// and prints the whole response body to stderr if it is not.
+    ///
+    /// See [`TestResponse::assert_status_else_print_body()`] for more details.
+    #[track_caller]
+    pub fn assert_status_success_else_print_body(&self) {
+        self.or_panic_printing_body(self.check_status_success())
+    }
+
+    /// Assert that the status code is **outside** the 2xx range.
+    /// i.e. A status code less than 200, or 300 or more.
+    #[track_caller]
+    pub fn assert_status_failure(&self) {
+        self.check_status_failure().or_panic()
+    }
+
+    /// Checks that the status code is **outside** the 2xx range.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_status_failure()`].
+    pub fn check_status_failure(&self) -> Result<(), AssertionError> {
+        let status_code = self.status_code.as_u16();
+        let received_debug = StatusCodeFormatter(self.status_code);
+        let debug_request_format = self.debug_request_format();
+        let debug_content = DebugResponseContent(self);
+
+        check(
+            status_code < 200 || 299 < status_code,
+            format_args!("Expect status code outside 2xx range, received {received_debug}, for request {debug_request_format}, with {debug_content}"),
+        )
+    }
+
+    /// Assert the status code is within the range given.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Json;
+    /// use axum::routing::get;
+    /// use axum::routing::Router;
+    /// use axum_test::TestServer;
+    /// use http::StatusCode;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/json", get(|| async {
+    ///         StatusCode::OK
+    ///     }));
+    /// let server = TestServer::new(app).unwrap();
+    ///
+    /// // Within success statuses
+    /// server
+    ///     .get(&"/json")
+    ///     .await
+    ///     .assert_status_in_range(200..=299);
+    ///
+    /// // Outside success
+    /// server
+    ///     .get(&"/json")
+    ///     .await
+    ///     .assert_status_in_range(300..);
+    ///
+    /// // Before server error
+    /// server
+    ///     .get(&"/json")
+    ///     .await
+    ///     .assert_status_in_range(..StatusCode::INTERNAL_SERVER_ERROR);
+    /// #
+    /// # Ok(()) }
+    /// ```
+    #[track_caller]
+    pub fn assert_status_in_range<R, S>(&self, expected_status_range: R)
+    where
+        R: RangeBounds<S> + TryIntoRangeBounds<StatusCode> + Debug,
+        S: TryInto<StatusCode>,
+    {
+        self.check_status_in_range(expected_status_range).or_panic()
+    }
+
+    /// Checks the status code is within the range given.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_status_in_range()`].
+    #[track_caller]
+    pub fn check_status_in_range<R, S>(
+        &self,
+        expected_status_range: R,
+    ) -> Result<(), AssertionError>
+    where
+        R: RangeBounds<S> + TryIntoRangeBounds<StatusCode> + Debug,
+        S: TryInto<StatusCode>,
+    {
+        let range = TryIntoRangeBounds::<StatusCode>::try_into_range_bounds(expected_status_range)
+            .expect("Failed to convert status code");
+
+        let status_code = self.status_code();
+        let is_in_range = range.contains(&status_code);
+        let received_debug = StatusCodeFormatter(status_code);
+        let debug_request_format = self.debug_request_format();
+        let debug_content = DebugResponseContent(self);
+
+        check(
+            is_in_range,
+            format_args!(
+                "Expected status to be in range {}, received {received_debug}, for request {debug_request_format}, with {debug_content}",
+                format_status_code_range(range)
+            ),
+        )
+    }
+
+    /// Assert the status code is not within the range given.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Json;
+    /// use axum::routing::get;
+    /// use axum::routing::Router;
+    /// use axum_test::TestServer;
+    /// use http::StatusCode;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/json", get(|| async {
+    ///         StatusCode::NOT_FOUND
+    ///     }));
+    /// let server = TestServer::new(app).unwrap();
+    ///
+    /// // Is not success
+    /// server
+    ///     .get(&"/json")
+    ///     .await
+    ///     .assert_status_not_in_range(200..=299);
+    ///
+    /// // 300 or higher
+    /// server
+    ///     .get(&"/json")
+    ///     .await
+    ///     .assert_status_not_in_range(300..);
+    ///
+    /// // After server error
+    /// server
+    ///     .get(&"/json")
+    ///     .await
+    ///     .assert_status_not_in_range(..StatusCode::INTERNAL_SERVER_ERROR);
+    /// #
+    /// # Ok(()) }
+    /// ```
+    #[track_caller]
+    pub fn assert_status_not_in_range<R, S>(&self, expected_status_range: R)
+    where
+        R: RangeBounds<S> + TryIntoRangeBounds<StatusCode> + Debug,
+        S: TryInto<StatusCode>,
+    {
+        self.check_status_not_in_range(expected_status_range)
+            .or_panic()
+    }
+
+    /// Checks the status code is not within the range given.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_status_not_in_range()`].
+    #[track_caller]
+    pub fn check_status_not_in_range<R, S>(
+        &self,
+        expected_status_range: R,
+    ) -> Result<(), AssertionError>
+    where
+        R: RangeBounds<S> + TryIntoRangeBounds<StatusCode> + Debug,
+        S: TryInto<StatusCode>,
+    {
+        let range = TryIntoRangeBounds::<StatusCode>::try_into_range_bounds(expected_status_range)
+            .expect("Failed to convert status code");
+
+        let status_code = self.status_code();
+        let is_not_in_range = !range.contains(&status_code);
+        let received_debug = StatusCodeFormatter(status_code);
+        let debug_request_format = self.debug_request_format();
+        let debug_content = DebugResponseContent(self);
+
+        check(
+            is_not_in_range,
+            format_args!(
+                "Expected status is not in range {}, received {received_debug}, for request {debug_request_format}, with {debug_content}",
+                format_status_code_range(range)
+            ),
+        )
+    }
+
+    /// Assert the response status code is 200.
+    #[track_caller]
+    pub fn assert_status_ok(&self) {
+        self.check_status_ok().or_panic()
+    }
+
+    /// Checks the response status code is 200.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_status_ok()`].
+    pub fn check_status_ok(&self) -> Result<(), AssertionError> {
+        self.check_status(StatusCode::OK)
+    }
+
+    /// Assert the response status code is 200,
+    /// and prints the whole response body to stderr if it is not.
+    ///
+    /// See [`TestResponse::assert_status_else_print_body()`] for more details.
+    #[track_caller]
+    pub fn assert_status_ok_else_print_body(&self) {
+        self.or_panic_printing_body(self.check_status_ok())
+    }
+
+    /// Assert the response status code is **not** 200.
+    #[track_caller]
+    pub fn assert_status_not_ok(&self) {
+        self.check_status_not_ok().or_panic()
+    }
+
+    /// Checks the response status code is **not** 200.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_status_not_ok()`].
+    pub fn check_status_not_ok(&self) -> Result<(), AssertionError> {
+        self.check_not_status(StatusCode::OK)
+    }
+
+    /// Assert the response status code is 303.
+    #[track_caller]
+    pub fn assert_status_see_other(&self) {
+        self.check_status_see_other().or_panic()
+    }
+
+    /// Checks the response status code is 303.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_status_see_other()`].
+    pub fn check_status_see_other(&self) -> Result<(), AssertionError> {
+        self.check_status(StatusCode::SEE_OTHER)
+    }
+
+    /// Assert the response status code is 400.
+    #[track_caller]
+    pub fn assert_status_bad_request(&self) {
+        self.check_status_bad_request().or_panic()
+    }
+
+    /// Checks the response status code is 400.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_status_bad_request()`].
+    pub fn check_status_bad_request(&self) -> Result<(), AssertionError> {
+        self.check_status(StatusCode::BAD_REQUEST)
+    }
+
+    /// Assert the response status code is 404.
+    #[track_caller]
+    pub fn assert_status_not_found(&self) {
+        self.check_status_not_found().or_panic()
+    }
+
+    /// Checks the response status code is 404.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_status_not_found()`].
+    pub fn check_status_not_found(&self) -> Result<(), AssertionError> {
+        self.check_status(StatusCode::NOT_FOUND)
+    }
+
+    /// Assert the response status code is 401.
+    #[track_caller]
+    pub fn assert_status_unauthorized(&self) {
+        self.check_status_unauthorized().or_panic()
+    }
+
+    /// Checks the response status code is 401.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_status_unauthorized()`].
+    pub fn check_status_unauthorized(&self) -> Result<(), AssertionError> {
+        self.check_status(StatusCode::UNAUTHORIZED)
+    }
+
+    /// Assert the response status code is 403.
+    #[track_caller]
+    pub fn assert_status_forbidden(&self) {
+        self.check_status_forbidden().or_panic()
+    }
+
+    /// Checks the response status code is 403.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_status_forbidden()`].
+    pub fn check_status_forbidden(&self) -> Result<(), AssertionError> {
+        self.check_status(StatusCode::FORBIDDEN)
+    }
+
+    /// Assert the response status code is 409.
+    #[track_caller]
+    pub fn assert_status_conflict(&self) {
+        self.check_status_conflict().or_panic()
+    }
+
+    /// Checks the response status code is 409.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_status_conflict()`].
+    pub fn check_status_conflict(&self) -> Result<(), AssertionError> {
+        self.check_status(StatusCode::CONFLICT)
+    }
+
+    /// Assert the response status code is 413.
+    ///
+    /// The payload is too large.
+    #[track_caller]
+    pub fn assert_status_payload_too_large(&self) {
+        self.check_status_payload_too_large().or_panic()
+    }
+
+    /// Checks the response status code is 413.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_status_payload_too_large()`].
+    pub fn check_status_payload_too_large(&self) -> Result<(), AssertionError> {
+        self.check_status(StatusCode::PAYLOAD_TOO_LARGE)
+    }
+
+    /// Assert the response status code is 422.
+    #[track_caller]
+    pub fn assert_status_unprocessable_entity(&self) {
+        self.check_status_unprocessable_entity().or_panic()
+    }
+
+    /// Checks the response status code is 422.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_status_unprocessable_entity()`].
+    pub fn check_status_unprocessable_entity(&self) -> Result<(), AssertionError> {
+        self.check_status(StatusCode::UNPROCESSABLE_ENTITY)
+    }
+
+    /// Assert the response status code is 429.
+    #[track_caller]
+    pub fn assert_status_too_many_requests(&self) {
+        self.check_status_too_many_requests().or_panic()
+    }
+
+    /// Checks the response status code is 429.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_status_too_many_requests()`].
+    pub fn check_status_too_many_requests(&self) -> Result<(), AssertionError> {
+        self.check_status(StatusCode::TOO_MANY_REQUESTS)
+    }
+
+    /// Assert the response status code is 101.
+    ///
+    /// This type of code is used in Web Socket connection when
+    /// first request.
+    #[track_caller]
+    pub fn assert_status_switching_protocols(&self) {
+        self.check_status_switching_protocols().or_panic()
+    }
+
+    /// Checks the response status code is 101.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_status_switching_protocols()`].
+    pub fn check_status_switching_protocols(&self) -> Result<(), AssertionError> {
+        self.check_status(StatusCode::SWITCHING_PROTOCOLS)
+    }
+
+    /// Assert the response status code is 500.
+    #[track_caller]
+    pub fn assert_status_internal_server_error(&self) {
+        self.check_status_internal_server_error().or_panic()
+    }
+
+    /// Checks the response status code is 500.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_status_internal_server_error()`].
+    pub fn check_status_internal_server_error(&self) -> Result<(), AssertionError> {
+        self.check_status(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    /// Assert the response status code is 503.
+    #[track_caller]
+    pub fn assert_status_service_unavailable(&self) {
+        self.check_status_service_unavailable().or_panic()
+    }
+
+    /// Checks the response status code is 503.
+    ///
+    /// This is the non-panicking version of [`TestResponse::assert_status_service_unavailable()`].
+    pub fn check_status_service_unavailable(&self) -> Result<(), AssertionError> {
+        self.check_status(StatusCode::SERVICE_UNAVAILABLE)
+    }
+
+    fn try_json<T>(&self) -> anyhow::Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        serde_json::from_slice::<T>(self.as_bytes()).with_context(|| {
+            let debug_request_format = self.debug_request_format();
+
+            format!("Deserializing response from Json, for request {debug_request_format}")
+        })
+    }
+
+    #[cfg(any(feature = "yaml", feature = "dyn-features"))]
+    fn try_yaml<T>(&self) -> anyhow::Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        #[cfg(not(feature = "yaml"))]
+        {
+            Err(missing_feature_error("TestResponse::yaml", "yaml").into())
+        }
+
+        #[cfg(feature = "yaml")]
+        {
+            serde_yaml::from_slice::<T>(self.as_bytes()).with_context(|| {
+                let debug_request_format = self.debug_request_format();
+
+                format!("Deserializing response from YAML, for request {debug_request_format}")
+            })
+        }
+    }
+
+    #[cfg(any(feature = "msgpack", feature = "dyn-features"))]
+    fn try_msgpack<T>(&self) -> anyhow::Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        #[cfg(not(feature = "msgpack"))]
+        {
+            Err(missing_feature_error("TestResponse::msgpack", "msgpack").into())
+        }
+
+        #[cfg(feature = "msgpack")]
+        {
+            rmp_serde::from_slice::<T>(self.as_bytes()).with_context(|| {
+                let debug_request_format = self.debug_request_format();
+
+                format!("Deserializing response from MsgPack, for request {debug_request_format}")
+            })
+        }
+    }
+
+    fn try_form<T>(&self) -> anyhow::Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        serde_urlencoded::from_bytes::<T>(self.as_bytes()).with_context(|| {
+            let debug_request_format = self.debug_request_format();
+
+            format!("Deserializing response from Form, for request {debug_request_format}")
+        })
+    }
+
+    fn try_decode<T>(&self) -> anyhow::Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let debug_request_format = self.debug_request_format();
+        let content_type = self.maybe_content_type().with_context(|| {
+            format!(
+                "Decoding response, no Content-Type was found, for request {debug_request_format}"
+            )
+        })?;
+
+        if let Some(decoder) = self.response_decoders.find(&content_type) {
+            let value = decoder(self.as_bytes()).with_context(|| {
+                format!(
+                    "Decoding response from '{content_type}', for request {debug_request_format}"
+                )
+            })?;
+
+            return serde_json::from_value::<T>(value).with_context(|| {
+                format!("Deserializing response decoded from '{content_type}', for request {debug_request_format}")
+            });
+        }
+
+        let essence = content_type_essence(&content_type);
+        match essence.as_str() {
+            "application/json" => self.try_json::<T>(),
+            essence if essence.ends_with("+json") => self.try_json::<T>(),
+            #[cfg(any(feature = "yaml", feature = "dyn-features"))]
+            "application/yaml" | "application/x-yaml" | "text/yaml" => self.try_yaml::<T>(),
+            #[cfg(any(feature = "msgpack", feature = "dyn-features"))]
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => {
+                self.try_msgpack::<T>()
+            }
+            "application/x-www-form-urlencoded" => self.try_form::<T>(),
+            _ => Err(anyhow::anyhow!(
+                "Decoding response, no decoder registered for Content-Type '{content_type}', for request {debug_request_format}"
+            )),
+        }
+    }
+
+    pub(crate) fn debug_request_format(&self) -> RequestPathFormatter<'_> {
+        RequestPathFormatter::new(&self.method, self.full_request_url.as_str(), None)
+            .with_recorded_request(&self.recorded_request, &self.data_mask)
+    }
+
+    /// Returns the comma separated values of all headers with this name, trimmed,
+    /// with empty entries skipped.
+    fn header_list_values<N>(&self, name: N) -> Vec<&str>
+    where
+        N: TryInto<HeaderName>,
+        N::Error: Debug,
+    {
+        self.iter_headers_by_name(name)
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .collect()
+    }
+}
+
+/// Returns true if the content type has the same essence as the mime given,
+/// and includes all of its parameters.
+pub(crate) fn is_content_type_match(content_type: &str, expected_mime: &Mime) -> bool {
+    content_type.parse::<Mime>().is_ok_and(|mime| {
+        mime.essence_str() == expected_mime.essence_str()
+            && expected_mime
+                .params()
+                .all(|(name, value)| mime.get_param(name) == Some(value))
+    })
+}
+
+fn decode_text(bytes: &[u8], encoding: &'static Encoding) -> String {
+    let (text, _, _) = encoding.decode(bytes);
+    text.into_owned()
+}
+
+/// Returns the tag inside of an `ETag`, without the weak prefix or quotes.
+#[cfg(feature = "etag")]
+fn etag_opaque_tag(etag: &str) -> &str {
+    let strong_etag = etag.strip_prefix("W/").unwrap_or(etag);
+
+    strong_etag
+        .strip_prefix('"')
+        .and_then(|tag| tag.strip_suffix('"'))
+        .unwrap_or(strong_etag)
+}
+
+impl From<TestResponse> for Bytes {
+    fn from(response: TestResponse) -> Self {
+        response.into_bytes()
+    }
+}
+
+#[cfg(test)]
+mod test_assert_header {
+    use crate::TestServer;
+    use axum::http::HeaderMap;
+    use axum::routing::get;
+    use axum::Router;
+
+    async fn route_get_header() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-my-custom-header", "content".parse().unwrap());
+        headers
+    }
+
+    #[tokio::test]
+    async fn it_should_not_panic_if_contains_header_and_content_matches() {
+        let router = Router::new().route(&"/header", get(route_get_header));
+
+        let server = TestServer::new(router).unwrap();
+
+        server
+            .get(&"/header")
+            .await
+            .assert_header("x-my-custom-header", "content");
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_if_contains_header_and_content_does_not_match() {
+        let router = Router::new().route(&"/header", get(route_get_header));
+
+        let server = TestServer::new(router).unwrap();
+
+        server
+            .get(&"/header")
+            .await
+            .assert_header("x-my-custom-header", "different-content");
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_if_not_contains_header() {
+        let router = Router::new().route(&"/header", get(route_get_header));
+
+        let server = TestServer::new(router).unwrap();
+
+        server
+            .get(&"/header")
+            .await
+            .assert_header("x-custom-header-not-found", "content");
+    }
+}
+
+#[cfg(test)]
+mod test_headers_in_order {
+    use crate::TestServer;
+    use axum::response::AppendHeaders;
+    use axum::routing::get;
+    use axum::Router;
+    use http::HeaderValue;
+
+    #[tokio::test]
+    async fn it_should_return_duplicate_headers_in_order() {
+        let app = Router::new().route(
+            "/chain",
+            get(|| async { AppendHeaders([("x-chain", "a"), ("x-other", "c"), ("x-chain", "b")]) }),
+        );
+        let server = TestServer::new(app).unwrap();
+
+        let headers = server.get("/chain").await.headers_in_order();
+        let chain_values: Vec<&HeaderValue> = headers
+            .iter()
+            .filter(|(name, _)| name == "x-chain")
+            .map(|(_, value)| value)
+            .collect();
+
+        assert_eq!(chain_values, vec!["a", "b"]);
+        assert!(headers
+            .iter()
+            .any(|(name, value)| name == "x-other" && value == "c"));
+    }
+}
+
+#[cfg(test)]
+mod test_assert_header_count {
+    use crate::TestServer;
+    use axum::response::AppendHeaders;
+    use axum::routing::get;
+    use axum::Router;
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new().route(
+            "/cookies",
+            get(|| async {
+                AppendHeaders([
+                    ("set-cookie", "first=1"),
+                    ("set-cookie", "second=2"),
+                    ("set-cookie", "third=3"),
+                ])
+            }),
+        );
+
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_when_count_matches() {
+        let server = new_test_server();
+
+        let response = server.get("/cookies").await;
+        response.assert_header_count("set-cookie", 3);
+        response.assert_header_count("x-not-present", 0);
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_count_does_not_match() {
+        let server = new_test_server();
+
+        server
+            .get("/cookies")
+            .await
+            .assert_header_count("set-cookie", 2);
+    }
+}
+
+#[cfg(test)]
+mod test_assert_header_values_in_order {
+    use crate::TestServer;
+    use axum::response::AppendHeaders;
+    use axum::routing::get;
+    use axum::Router;
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new().route(
+            "/chain",
+            get(|| async { AppendHeaders([("x-chain", "a"), ("x-chain", "b")]) }),
+        );
+
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_when_values_match_in_order() {
+        let server = new_test_server();
+
+        server
+            .get("/chain")
+            .await
+            .assert_header_values_in_order("x-chain", ["a", "b"]);
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_values_are_out_of_order() {
+        let server = new_test_server();
+
+        server
+            .get("/chain")
+            .await
+            .assert_header_values_in_order("x-chain", ["b", "a"]);
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_values_are_missing() {
+        let server = new_test_server();
+
+        server
+            .get("/chain")
+            .await
+            .assert_header_values_in_order("x-chain", ["a"]);
+    }
+}
+
+#[cfg(test)]
+mod test_assert_content_language {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .route(
+                "/german",
+                get(|| async { ([("content-language", "de, en-GB")], "hallo") }),
+            )
+            .route("/none", get(|| async { "hello" }));
+
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_when_language_is_listed() {
+        let server = new_test_server();
+
+        let response = server.get("/german").await;
+        response.assert_content_language("de");
+        response.assert_content_language("EN-gb");
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_language_is_not_listed() {
+        let server = new_test_server();
+
+        server.get("/german").await.assert_content_language("fr");
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_header_is_missing() {
+        let server = new_test_server();
+
+        server.get("/none").await.assert_content_language("en");
+    }
+}
+
+#[cfg(test)]
+mod test_assert_vary_contains {
+    use crate::TestServer;
+    use axum::response::AppendHeaders;
+    use axum::routing::get;
+    use axum::Router;
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .route(
+                "/vary",
+                get(|| async {
+                    AppendHeaders([("vary", "Accept-Language, Accept"), ("vary", "cookie")])
+                }),
+            )
+            .route(
+                "/vary-all",
+                get(|| async { AppendHeaders([("vary", "*")]) }),
+            );
+
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_when_name_is_in_any_vary_header() {
+        let server = new_test_server();
+
+        let response = server.get("/vary").await;
+        response.assert_vary_contains("accept-language");
+        response.assert_vary_contains("Cookie");
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_for_any_name_when_vary_is_wildcard() {
+        let server = new_test_server();
+
+        server
+            .get("/vary-all")
+            .await
+            .assert_vary_contains("accept-encoding");
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_name_is_missing() {
+        let server = new_test_server();
+
+        server
+            .get("/vary")
+            .await
+            .assert_vary_contains("accept-encoding");
+    }
+}
+
+#[cfg(test)]
+mod test_assert_header_contains_value {
+    use crate::TestServer;
+    use axum::response::AppendHeaders;
+    use axum::routing::get;
+    use axum::Router;
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new().route(
+            "/list",
+            get(|| async {
+                AppendHeaders([
+                    ("vary", "Accept-Encoding,Origin ,  Cookie"),
+                    ("vary", "accept-language"),
+                ])
+            }),
+        );
+
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_regardless_of_order_spacing_and_case() {
+        let server = new_test_server();
+
+        let response = server.get("/list").await;
+        response.assert_header_contains_value("vary", "origin");
+        response.assert_header_contains_value("vary", "cookie");
+        response.assert_header_contains_value("vary", "Accept-Language");
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_value_is_missing() {
+        let server = new_test_server();
+
+        server
+            .get("/list")
+            .await
+            .assert_header_contains_value("vary", "user-agent");
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_value_only_partially_matches() {
+        let server = new_test_server();
+
+        server
+            .get("/list")
+            .await
+            .assert_header_contains_value("vary", "accept");
+    }
+
+    #[tokio::test]
+    async fn it_should_error_when_header_is_missing() {
+        let server = new_test_server();
+
+        let result = server
+            .get("/list")
+            .await
+            .check_header_contains_value("allow", "GET");
+        assert!(result.is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_assert_allow_methods {
+    use crate::TestServer;
+    use axum::response::AppendHeaders;
+    use axum::routing::get;
+    use axum::Router;
+    use http::Method;
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .route("/todo", get(|| async { "ok" }).post(|| async { "ok" }))
+            .route(
+                "/options",
+                get(|| async { AppendHeaders([("allow", "post,  GET"), ("allow", "OPTIONS")]) }),
+            );
+
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_for_axum_method_not_allowed_responses() {
+        let server = new_test_server();
+
+        server
+            .delete("/todo")
+            .expect_failure()
+            .await
+            .assert_allow_methods([Method::POST, Method::GET]);
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_across_multiple_headers_in_any_case() {
+        let server = new_test_server();
+
+        server.get("/options").await.assert_allow_methods([
+            Method::OPTIONS,
+            Method::GET,
+            Method::POST,
+        ]);
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_a_method_is_missing() {
+        let server = new_test_server();
+
+        server
+            .get("/options")
+            .await
+            .assert_allow_methods([Method::GET, Method::DELETE]);
+    }
+}
+
+#[cfg(test)]
+mod test_assert_accept_ranges_bytes {
+    use crate::TestServer;
+    use axum::response::AppendHeaders;
+    use axum::routing::get;
+    use axum::Router;
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .route(
+                "/bytes",
+                get(|| async { AppendHeaders([("accept-ranges", " Bytes ")]) }),
+            )
+            .route(
+                "/none",
+                get(|| async { AppendHeaders([("accept-ranges", "none")]) }),
+            );
+
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_when_bytes_are_accepted() {
+        let server = new_test_server();
+
+        server.get("/bytes").await.assert_accept_ranges_bytes();
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_ranges_are_not_accepted() {
+        let server = new_test_server();
+
+        server.get("/none").await.assert_accept_ranges_bytes();
+    }
+}
+
+#[cfg(test)]
+mod test_assert_date_within {
+    use crate::internals::format_http_date;
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+    use cookie::time::Duration as TimeDuration;
+    use cookie::time::OffsetDateTime;
+    use std::time::Duration;
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .route(
+                "/now",
+                get(|| async { [("date", format_http_date(OffsetDateTime::now_utc()))] }),
+            )
+            .route(
+                "/stale",
+                get(|| async {
+                    let date = OffsetDateTime::now_utc() - TimeDuration::hours(1);
+                    [("date", format_http_date(date))]
+                }),
+            )
+            .route("/none", get(|| async { "no date" }));
+
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_when_date_is_within_tolerance() {
+        let server = new_test_server();
+
+        server
+            .get("/now")
+            .await
+            .assert_date_within(Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_date_is_outside_tolerance() {
+        let server = new_test_server();
+
+        server
+            .get("/stale")
+            .await
+            .assert_date_within(Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_date_is_missing() {
+        let server = new_test_server();
+
+        server
+            .get("/none")
+            .await
+            .assert_date_within(Duration::from_secs(5));
+    }
+}
+
+#[cfg(test)]
+mod test_assert_mime_guessed_from_extension {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+    use tower_http::services::ServeDir;
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .nest_service("/static", ServeDir::new("files/static"))
+            .route("/wrong.css", get(|| async { "body {}" }))
+            .route("/no-extension", get(|| async { "hello" }));
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_for_matching_content_type() {
+        let server = new_test_server();
+
+        server
+            .get("/static/app.js")
+            .await
+            .assert_mime_guessed_from_extension();
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_for_different_content_type() {
+        let server = new_test_server();
+        let error = server
+            .get("/wrong.css")
+            .await
+            .check_mime_guessed_from_extension()
+            .unwrap_err();
+
+        assert!(error
+            .to_string()
+            .contains("Expected content type 'text/css', received 'text/plain; charset=utf-8'"));
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_without_extension() {
+        let server = new_test_server();
+        let error = server
+            .get("/no-extension")
+            .await
+            .check_mime_guessed_from_extension()
+            .unwrap_err();
+
+        assert!(error
+            .to_string()
+            .contains("Failed to guess mime type from the extension of path '/no-extension'"));
+    }
+}
+
+#[cfg(test)]
+mod test_assert_last_modified_present {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+    use tower_http::services::ServeDir;
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .nest_service("/static", ServeDir::new("files/static"))
+            .route(
+                "/invalid",
+                get(|| async { ([("last-modified", "yesterday")], "hello") }),
+            )
+            .route("/dynamic", get(|| async { "hello" }));
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_for_static_files() {
+        let server = new_test_server();
+
+        server
+            .get("/static/app.js")
+            .await
+            .assert_last_modified_present();
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_when_missing() {
+        let server = new_test_server();
+        let error = server
+            .get("/dynamic")
+            .await
+            .check_last_modified_present()
+            .unwrap_err();
+
+        assert!(error
+            .to_string()
+            .contains("Expected header 'last-modified' to be present in response"));
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_when_invalid() {
+        let server = new_test_server();
+        let error = server
+            .get("/invalid")
+            .await
+            .check_last_modified_present()
+            .unwrap_err();
+
+        assert!(error
+            .to_string()
+            .contains("Failed to parse Last-Modified header"));
+    }
+}
+
+#[cfg(test)]
+mod test_assert_not_deprecated {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .route("/current", get(|| async { "current" }))
+            .route(
+                "/deprecated",
+                get(|| async { ([("deprecation", "true")], "deprecated") }),
+            )
+            .route(
+                "/sunset",
+                get(|| async { ([("sunset", "Wed, 11 Nov 2026 23:59:59 GMT")], "sunset") }),
+            );
+
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_when_not_deprecated() {
+        let server = new_test_server();
+
+        server.get("/current").await.assert_not_deprecated();
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_deprecation_header_present() {
+        let server = new_test_server();
+
+        server.get("/deprecated").await.assert_not_deprecated();
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_sunset_header_present() {
+        let server = new_test_server();
+
+        server.get("/sunset").await.assert_not_deprecated();
+    }
+}
+
+#[cfg(test)]
+mod test_assert_contains_header {
+    use crate::TestServer;
+    use axum::http::HeaderMap;
+    use axum::routing::get;
+    use axum::Router;
+
+    async fn route_get_header() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-my-custom-header", "content".parse().unwrap());
+        headers
+    }
+
+    #[tokio::test]
+    async fn it_should_not_panic_if_contains_header() {
+        let router = Router::new().route(&"/header", get(route_get_header));
+
+        let server = TestServer::new(router).unwrap();
+
+        server
+            .get(&"/header")
+            .await
+            .assert_contains_header("x-my-custom-header");
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_if_not_contains_header() {
+        let router = Router::new().route(&"/header", get(route_get_header));
+
+        let server = TestServer::new(router).unwrap();
+
+        server
+            .get(&"/header")
+            .await
+            .assert_contains_header("x-custom-header-not-found");
+    }
+}
+
+#[cfg(test)]
+mod test_assert_success {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+    use http::StatusCode;
+
+    pub async fn route_get_pass() -> StatusCode {
+        StatusCode::OK
+    }
+
+    pub async fn route_get_fail() -> StatusCode {
+        StatusCode::SERVICE_UNAVAILABLE
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_when_200() {
+        let router = Router::new()
+            .route(&"/pass", get(route_get_pass))
+            .route(&"/fail", get(route_get_fail));
+
+        let server = TestServer::new(router).unwrap();
+
+        let response = server.get(&"/pass").await;
+
+        response.assert_status_success()
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_not_200() {
+        let router = Router::new()
+            .route(&"/pass", get(route_get_pass))
+            .route(&"/fail", get(route_get_fail));
+
+        let server = TestServer::new(router).unwrap();
+
+        let response = server.get(&"/fail").expect_failure().await;
+
+        response.assert_status_success()
+    }
+}
+
+#[cfg(test)]
+mod test_assert_failure {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+    use http::StatusCode;
+
+    pub async fn route_get_pass() -> StatusCode {
+        StatusCode::OK
+    }
+
+    pub async fn route_get_fail() -> StatusCode {
+        StatusCode::SERVICE_UNAVAILABLE
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_when_not_200() {
+        let router = Router::new()
+            .route(&"/pass", get(route_get_pass))
+            .route(&"/fail", get(route_get_fail));
+
+        let server = TestServer::new(router).unwrap();
+        let response = server.get(&"/fail").expect_failure().await;
+
+        response.assert_status_failure()
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_200() {
+        let router = Router::new()
+            .route(&"/pass", get(route_get_pass))
+            .route(&"/fail", get(route_get_fail));
+
+        let server = TestServer::new(router).unwrap();
+        let response = server.get(&"/pass").await;
+
+        response.assert_status_failure()
+    }
+}
+
+#[cfg(test)]
+mod test_assert_http_version {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+    use http::Version;
+
+    fn new_app() -> Router {
+        Router::new().route("/ping", get(|| async { "pong!" }))
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_for_http_1_1_over_mock_transport() {
+        let server = TestServer::new(new_app()).unwrap();
+
+        let response = server.get("/ping").await;
+        assert_eq!(response.http_version(), Version::HTTP_11);
+        response.assert_http_version(Version::HTTP_11);
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_for_http_1_1_over_http_transport() {
+        let server = TestServer::builder()
+            .http_transport()
+            .build(new_app())
+            .unwrap();
+
+        server
+            .get("/ping")
+            .await
+            .assert_http_version(Version::HTTP_11);
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_for_http_2_over_http_transport() {
+        let server = TestServer::builder()
+            .http_transport()
+            .build(new_app())
+            .unwrap();
+
+        let response = server.get("/ping").http_version(Version::HTTP_2).await;
+
+        response.assert_text("pong!");
+        response.assert_http_version(Version::HTTP_2);
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_version_differs() {
+        let server = TestServer::new(new_app()).unwrap();
+
+        server
+            .get("/ping")
+            .await
+            .assert_http_version(Version::HTTP_2);
+    }
+}
+
+#[cfg(test)]
+mod test_assert_handler_panicked_with {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+    use http::StatusCode;
+
+    async fn route_get_panic() -> &'static str {
+        let code = 123;
+        panic!("oh no, failed with {code}")
+    }
+
+    fn new_app() -> Router {
+        Router::new()
+            .route("/ping", get(|| async { "pong!" }))
+            .route("/panic", get(route_get_panic))
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_when_handler_panics_over_mock_transport() {
+        let server = TestServer::builder()
+            .catch_panics()
+            .build(new_app())
+            .unwrap();
+
+        let response = server.get("/panic").await;
+        response.assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(response.was_panic());
+        assert_eq!(response.panic_message(), Some("oh no, failed with 123"));
+        response.assert_handler_panicked_with("failed with 123");
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_when_handler_panics_over_http_transport() {
+        let server = TestServer::builder()
+            .http_transport()
+            .catch_panics()
+            .build(new_app())
+            .unwrap();
+
+        let response = server.get("/panic").await;
+        response.assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+        response.assert_handler_panicked_with("failed with 123");
+
+        // The server should continue serving requests.
+        server.get("/ping").await.assert_text("pong!");
+    }
+
+    #[tokio::test]
+    async fn it_should_not_expose_the_panic_header() {
+        let server = TestServer::builder()
+            .catch_panics()
+            .build(new_app())
+            .unwrap();
+
+        let response = server.get("/panic").await;
+        assert!(response.maybe_header("x-axum-test-panic").is_none());
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_fail_when_handler_does_not_panic() {
+        let server = TestServer::builder()
+            .catch_panics()
+            .build(new_app())
+            .unwrap();
+
+        let response = server.get("/ping").await;
+        assert!(!response.was_panic());
+        response.assert_handler_panicked_with("oh no");
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_fail_when_panic_message_does_not_match() {
+        let server = TestServer::builder()
+            .catch_panics()
+            .build(new_app())
+            .unwrap();
+
+        server
+            .get("/panic")
+            .await
+            .assert_handler_panicked_with("something else");
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "oh no, failed with 123")]
+    async fn it_should_not_catch_panics_over_mock_transport_when_not_set() {
+        let server = TestServer::new(new_app()).unwrap();
+
+        let _ = server.get("/panic").try_send().await;
+    }
+
+    #[tokio::test]
+    async fn it_should_drop_the_connection_over_http_transport_when_not_set() {
+        let server = TestServer::builder()
+            .http_transport()
+            .build(new_app())
+            .unwrap();
+
+        let result = server.get("/panic").try_send().await;
+        assert!(result.is_err());
+    }
+}
+
+#[cfg(feature = "reqwest")]
+#[cfg(test)]
+mod test_assert_handler_panicked_with_reqwest {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+    use http::StatusCode;
+
+    async fn route_get_panic() -> &'static str {
+        panic!("oh no")
+    }
+
+    #[tokio::test]
+    async fn it_should_not_send_the_panic_message_to_other_clients() {
+        let app = Router::new().route("/panic", get(route_get_panic));
+        let server = TestServer::builder()
+            .http_transport()
+            .catch_panics()
+            .build(app)
+            .unwrap();
+
+        let response = server.reqwest_get("/panic").send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(response.headers().get("x-axum-test-panic").is_none());
+    }
+}
+
+#[cfg(test)]
+mod test_raw_head {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+
+    fn new_app() -> Router {
+        Router::new().route(
+            "/todo",
+            get(|| async { ([("x-todo-count", "3")], "todos") }),
+        )
+    }
+
+    #[tokio::test]
+    async fn it_should_capture_raw_head_over_http_transport() {
+        let server = TestServer::builder()
+            .http_transport()
+            .capture_raw_head()
+            .build(new_app())
+            .unwrap();
+
+        let response = server.get("/todo").await;
+        let raw_head = response.raw_head();
+
+        assert!(raw_head.starts_with("HTTP/1.1 200 OK\r\n"), "{raw_head}");
+        assert!(raw_head.ends_with("\r\n\r\n"), "{raw_head}");
+        response.assert_raw_head_contains("\r\nx-todo-count: 3\r\n");
+        response.assert_text("todos");
+    }
+
+    #[tokio::test]
+    async fn it_should_not_capture_raw_head_when_not_set() {
+        let server = TestServer::builder()
+            .http_transport()
+            .build(new_app())
+            .unwrap();
+
+        let response = server.get("/todo").await;
+        assert_eq!(response.maybe_raw_head(), None);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "Expected raw response head, it was not captured")]
+    async fn it_should_panic_when_using_mock_transport() {
+        let server = TestServer::builder()
+            .mock_transport()
+            .capture_raw_head()
+            .build(new_app())
+            .unwrap();
+
+        let _ = server.get("/todo").await.raw_head();
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_when_raw_head_does_not_contain_text() {
+        let server = TestServer::builder()
+            .http_transport()
+            .capture_raw_head()
+            .build(new_app())
+            .unwrap();
+
+        let response = server.get("/todo").await;
+        assert!(response.check_raw_head_contains("X-Todo-Count:").is_err());
+    }
+}
+
+#[cfg(feature = "etag")]
+#[cfg(test)]
+mod test_assert_etag_matches_body_hash {
+    use crate::HashAlgorithm;
+    use crate::TestServer;
+    use axum::http::header;
+    use axum::routing::get;
+    use axum::Router;
+
+    const ABC_SHA256_HEX_ETAG: &str =
+        "\"ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad\"";
+    const ABC_SHA256_WEAK_UPPERCASE_HEX_ETAG: &str =
+        "W/\"BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD\"";
+    const ABC_SHA256_BASE64_ETAG: &str = "\"ungWv48Bz+pBQUDeXa4iI7ADYaOWF3qctBD/YfIAFa0=\"";
+
+    fn new_test_server(etag: &'static str) -> TestServer {
+        let app = Router::new().route(
+            "/abc",
+            get(move || async move { ([(header::ETAG, etag)], "abc") }),
+        );
+
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_for_hex_hash() {
+        let server = new_test_server(ABC_SHA256_HEX_ETAG);
+
+        server
+            .get("/abc")
+            .await
+            .assert_etag_matches_body_hash(HashAlgorithm::Sha256);
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_for_weak_uppercase_hex_hash() {
+        let server = new_test_server(ABC_SHA256_WEAK_UPPERCASE_HEX_ETAG);
+
+        server
+            .get("/abc")
+            .await
+            .assert_etag_matches_body_hash(HashAlgorithm::Sha256);
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_for_base64_hash() {
+        let server = new_test_server(ABC_SHA256_BASE64_ETAG);
+
+        server
+            .get("/abc")
+            .await
+            .assert_etag_matches_body_hash(HashAlgorithm::Sha256);
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_for_different_algorithm() {
+        let server = new_test_server(ABC_SHA256_HEX_ETAG);
+
+        server
+            .get("/abc")
+            .await
+            .assert_etag_matches_body_hash(HashAlgorithm::Sha1);
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_when_etag_is_missing() {
+        let app = Router::new().route("/abc", get(|| async { "abc" }));
+        let server = TestServer::new(app).unwrap();
+
+        let response = server.get("/abc").await;
+
+        assert!(response
+            .check_etag_matches_body_hash(HashAlgorithm::Sha256)
+            .is_err());
+    }
+}
+
+#[cfg(feature = "etag")]
+#[cfg(test)]
+mod test_assert_weak_etag {
+    use crate::TestServer;
+    use axum::http::header;
+    use axum::routing::get;
+    use axum::Router;
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .route(
+                "/weak",
+                get(|| async { ([(header::ETAG, "W/\"v1\"")], "weak") }),
+            )
+            .route(
+                "/strong",
+                get(|| async { ([(header::ETAG, "\"v1\"")], "strong") }),
+            );
+
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_for_weak_etag() {
+        let server = new_test_server();
+
+        server.get("/weak").await.assert_weak_etag();
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_for_strong_etag() {
+        let server = new_test_server();
+
+        server.get("/strong").await.assert_weak_etag();
+    }
+}
+
+#[cfg(feature = "matched-route")]
+#[cfg(test)]
+mod test_assert_matched_route {
+    use crate::MatchedRouteLayer;
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+
+    fn new_test_server() -> TestServer {
+        let users = Router::new().route("/:id/posts", get(|| async { "posts" }));
+        let app = Router::new()
+            .route("/users/:id", get(|| async { "user" }))
+            .route("/users/me", get(|| async { "me" }))
+            .nest("/users", users)
+            .fallback(|| async { "fallback" })
+            .layer(MatchedRouteLayer);
+
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_when_route_matches() {
+        let server = new_test_server();
+
+        server
+            .get("/users/123")
+            .await
+            .assert_matched_route("/users/:id");
+        server
+            .get("/users/me")
+            .await
+            .assert_matched_route("/users/me");
+    }
+
+    #[tokio::test]
+    async fn it_should_include_the_prefix_of_nested_routes() {
+        let server = new_test_server();
+
+        server
+            .get("/users/123/posts")
+            .await
+            .assert_matched_route("/users/:id/posts");
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_route_differs() {
+        let server = new_test_server();
+
+        server
+            .get("/users/me")
+            .await
+            .assert_matched_route("/users/:id");
+    }
+
+    #[tokio::test]
+    async fn it_should_have_no_matched_route_for_fallback() {
+        let server = new_test_server();
+
+        let response = server.get("/unknown").await;
+
+        assert_eq!(response.maybe_matched_route(), None);
+        assert!(response.check_matched_route("/unknown").is_err());
+    }
+
+    #[tokio::test]
+    async fn it_should_remove_the_internal_header() {
+        let server = new_test_server();
+
+        let response = server.get("/users/123").await;
+
+        assert!(response.maybe_header("x-axum-test-matched-route").is_none());
+    }
+
+    #[tokio::test]
+    async fn it_should_find_matched_route_over_http_transport() {
+        let app = Router::new()
+            .route("/users/:id", get(|| async { "user" }))
+            .layer(MatchedRouteLayer);
+        let server = TestServer::builder().http_transport().build(app).unwrap();
+
+        let response = server.get("/users/123").await;
+
+        assert_eq!(response.matched_route(), "/users/:id");
+    }
+}
+
+#[cfg(test)]
+mod test_assert_status {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+    use http::StatusCode;
+
+    pub async fn route_get_ok() -> StatusCode {
+        StatusCode::OK
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_if_given_right_status_code() {
+        let router = Router::new().route(&"/ok", get(route_get_ok));
+        let server = TestServer::new(router).unwrap();
+
+        server.get(&"/ok").await.assert_status(StatusCode::OK);
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_status_code_does_not_match() {
+        let router = Router::new().route(&"/ok", get(route_get_ok));
+        let server = TestServer::new(router).unwrap();
+
+        server.get(&"/ok").await.assert_status(StatusCode::ACCEPTED);
+    }
+}
+
+#[cfg(test)]
+mod test_assert_not_status {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+    use http::StatusCode;
+
+    pub async fn route_get_ok() -> StatusCode {
+        StatusCode::OK
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_if_status_code_does_not_match() {
+        let router = Router::new().route(&"/ok", get(route_get_ok));
+        let server = TestServer::new(router).unwrap();
+
+        server
+            .get(&"/ok")
+            .await
+            .assert_not_status(StatusCode::ACCEPTED);
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_if_status_code_matches() {
+        let router = Router::new().route(&"/ok", get(route_get_ok));
+        let server = TestServer::new(router).unwrap();
+
+        server.get(&"/ok").await.assert_not_status(StatusCode::OK);
+    }
+}
+
+#[cfg(test)]
+mod test_assert_status_in_range {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::routing::Router;
+    use http::StatusCode;
+    use std::ops::RangeFull;
+
+    #[tokio::test]
+    async fn it_should_be_true_when_within_int_range() {
+        let app = Router::new().route(
+            &"/status",
+            get(|| async { StatusCode::NON_AUTHORITATIVE_INFORMATION }),
+        );
+
+        TestServer::new(app)
+            .unwrap()
+            .get(&"/status")
+            .await
+            .assert_status_in_range(200..299);
+    }
+
+    #[tokio::test]
+    async fn it_should_be_true_when_within_status_code_range() {
+        let app = Router::new().route(
+            &"/status",
+            get(|| async { StatusCode::NON_AUTHORITATIVE_INFORMATION }),
+        );
+
+        TestServer::new(app)
+            .unwrap()
+            .get(&"/status")
+            .await
+            .assert_status_in_range(StatusCode::OK..StatusCode::IM_USED);
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_be_false_when_outside_int_range() {
+        let app = Router::new().route(
+            &"/status",
+            get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
+        );
+
+        TestServer::new(app)
+            .unwrap()
+            .get(&"/status")
+            .await
+            .assert_status_in_range(200..299);
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_be_false_when_outside_status_code_range() {
+        let app = Router::new().route(
+            &"/status",
+            get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
+        );
+
+        TestServer::new(app)
+            .unwrap()
+            .get(&"/status")
+            .await
+            .assert_status_in_range(StatusCode::OK..StatusCode::IM_USED);
+    }
+
+    #[tokio::test]
+    async fn it_should_be_true_when_within_inclusive_range() {
+        let app = Router::new().route(
+            &"/status",
+            get(|| async { StatusCode::NON_AUTHORITATIVE_INFORMATION }),
+        );
+
+        TestServer::new(app)
+            .unwrap()
+            .get(&"/status")
+            .await
+            .assert_status_in_range(200..=299);
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_be_false_when_outside_inclusive_range() {
+        let app = Router::new().route(
+            &"/status",
+            get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
+        );
+
+        TestServer::new(app)
+            .unwrap()
+            .get(&"/status")
+            .await
+            .assert_status_in_range(200..=299);
+    }
+
+    #[tokio::test]
+    async fn it_should_be_true_when_within_to_range() {
+        let app = Router::new().route(
+            &"/status",
+            get(|| async { StatusCode::NON_AUTHORITATIVE_INFORMATION }),
+        );
+
+        TestServer::new(app)
+            .unwrap()
+            .get(&"/status")
+            .await
+            .assert_status_in_range(..299);
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_be_false_when_outside_to_range() {
+        let app = Router::new().route(
+            &"/status",
+            get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
+        );
+
+        TestServer::new(app)
+            .unwrap()
+            .get(&"/status")
+            .await
+            .assert_status_in_range(..299);
+    }
+
+    #[tokio::test]
+    async fn it_should_be_true_when_within_to_inclusive_range() {
+        let app = Router::new().route(
+            &"/status",
+            get(|| async { StatusCode::NON_AUTHORITATIVE_INFORMATION }),
+        );
+
+        TestServer::new(app)
+            .unwrap()
+            .get(&"/status")
+            .await
+            .assert_status_in_range(..=299);
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_be_false_when_outside_to_inclusive_range() {
+        let app = Router::new().route(
+            &"/status",
+            get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
+        );
+
+        TestServer::new(app)
+            .unwrap()
+            .get(&"/status")
+            .await
+            .assert_status_in_range(..=299);
+    }
+
+    #[tokio::test]
+    async fn it_should_be_true_when_within_from_range() {
+        let app = Router::new().route(
+            &"/status",
+            get(|| async { StatusCode::NON_AUTHORITATIVE_INFORMATION }),
+        );
+
+        TestServer::new(app)
+            .unwrap()
+            .get(&"/status")
+            .await
+            .assert_status_in_range(200..);
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_be_false_when_outside_from_range() {
+        let app = Router::new().route(
+            &"/status",
+            get(|| async { StatusCode::NON_AUTHORITATIVE_INFORMATION }),
+        );
+
+        TestServer::new(app)
+            .unwrap()
+            .get(&"/status")
+            .await
+            .assert_status_in_range(500..);
+    }
+
+    #[tokio::test]
+    async fn it_should_be_true_for_rull_range() {
+        let app = Router::new().route(
+            &"/status",
+            get(|| async { StatusCode::NON_AUTHORITATIVE_INFORMATION }),
+        );
+
+        TestServer::new(app)
+            .unwrap()
+            .get(&"/status")
+            .await
+            .assert_status_in_range::<RangeFull, StatusCode>(..);
+    }
+}
+
+#[cfg(test)]
+mod test_assert_status_not_in_range {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::routing::Router;
+    use http::StatusCode;
+    use std::ops::RangeFull;
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_be_false_when_within_int_range() {
+        let app = Router::new().route(
+            &"/status",
+            get(|| async { StatusCode::NON_AUTHORITATIVE_INFORMATION }),
+        );
+
+        TestServer::new(app)
+            .unwrap()
+            .get(&"/status")
+            .await
+            .assert_status_not_in_range(200..299);
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_be_false_when_within_status_code_range() {
+        let app = Router::new().route(
+            &"/status",
+            get(|| async { StatusCode::NON_AUTHORITATIVE_INFORMATION }),
+        );
+
+        TestServer::new(app)
+            .unwrap()
+            .get(&"/status")
+            .await
+            .assert_status_not_in_range(StatusCode::OK..StatusCode::IM_USED);
+    }
+
+    #[tokio::test]
+    async fn it_should_be_true_when_outside_int_range() {
+        let app = Router::new().route(
+            &"/status",
+            get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
+        );
+
+        TestServer::new(app)
+            .unwrap()
+            .get(&"/status")
+            .await
+            .assert_status_not_in_range(200..299);
+    }
+
+    #[tokio::test]
+    async fn it_should_be_true_when_outside_status_code_range() {
+        let app = Router::new().route(
+            &"/status",
+            get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
+        );
+
+        TestServer::new(app)
+            .unwrap()
+            .get(&"/status")
+            .await
+            .assert_status_not_in_range(StatusCode::OK..StatusCode::IM_USED);
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_be_false_when_within_inclusive_range() {
+        let app = Router::new().route(
+            &"/status",
+            get(|| async { StatusCode::NON_AUTHORITATIVE_INFORMATION }),
+        );
+
+        TestServer::new(app)
+            .unwrap()
+            .get(&"/status")
+            .await
+            .assert_status_not_in_range(200..=299);
+    }
+
+    #[tokio::test]
+    async fn it_should_be_true_when_outside_inclusive_range() {
+        let app = Router::new().route(
+            &"/status",
+            get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
+        );
+
+        TestServer::new(app)
+            .unwrap()
+            .get(&"/status")
+            .await
+            .assert_status_not_in_range(200..=299);
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_be_false_when_within_to_range() {
+        let app = Router::new().route(
+            &"/status",
+            get(|| async { StatusCode::NON_AUTHORITATIVE_INFORMATION }),
+        );
+
+        TestServer::new(app)
+            .unwrap()
+            .get(&"/status")
+            .await
+            .assert_status_not_in_range(..299);
+    }
+
+    #[tokio::test]
+    async fn it_should_be_true_when_outside_to_range() {
+        let app = Router::new().route(
+            &"/status",
+            get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
+        );
+
+        TestServer::new(app)
+            .unwrap()
+            .get(&"/status")
+            .await
+            .assert_status_not_in_range(..299);
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_be_false_when_within_to_inclusive_range() {
+        let app = Router::new().route(
+            &"/status",
+            get(|| async { StatusCode::NON_AUTHORITATIVE_INFORMATION }),
+        );
+
+        TestServer::new(app)
+            .unwrap()
+            .get(&"/status")
+            .await
+            .assert_status_not_in_range(..=299);
+    }
+
+    #[tokio::test]
+    async fn it_should_be_true_when_outside_to_inclusive_range() {
+        let app = Router::new().route(
+            &"/status",
+            get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
+        );
+
+        TestServer::new(app)
+            .unwrap()
+            .get(&"/status")
+            .await
+            .assert_status_not_in_range(..=299);
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_be_false_when_within_from_range() {
+        let app = Router::new().route(
+            &"/status",
+            get(|| async { StatusCode::NON_AUTHORITATIVE_INFORMATION }),
+        );
+
+        TestServer::new(app)
+            .unwrap()
+            .get(&"/status")
+            .await
+            .assert_status_not_in_range(200..);
+    }
+
+    #[tokio::test]
+    async fn it_should_be_true_when_outside_from_range() {
+        let app = Router::new().route(
+            &"/status",
+            get(|| async { StatusCode::NON_AUTHORITATIVE_INFORMATION }),
+        );
+
+        TestServer::new(app)
+            .unwrap()
+            .get(&"/status")
+            .await
+            .assert_status_not_in_range(500..);
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_be_false_for_rull_range() {
+        let app = Router::new().route(
+            &"/status",
+            get(|| async { StatusCode::NON_AUTHORITATIVE_INFORMATION }),
+        );
+
+        TestServer::new(app)
+            .unwrap()
+            .get(&"/status")
+            .await
+            .assert_status_not_in_range::<RangeFull, StatusCode>(..);
+    }
+}
+
+#[cfg(test)]
+mod test_into_bytes {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Json;
+    use axum::Router;
+    use serde_json::json;
+    use serde_json::Value;
+
+    async fn route_get_json() -> Json<Value> {
+        Json(json!({
+            "message": "it works?"
+        }))
+    }
+
+    #[tokio::test]
+    async fn it_should_deserialize_into_json() {
+        let app = Router::new().route(&"/json", get(route_get_json));
+
+        let server = TestServer::new(app).unwrap();
+
+        let bytes = server.get(&"/json").await.into_bytes();
+        let text = String::from_utf8_lossy(&bytes);
+
+        assert_eq!(text, r#"{"message":"it works?"}"#);
+    }
+}
+
+#[cfg(test)]
+mod test_request_body_bytes {
+    use crate::TestServer;
+    use axum::routing::post;
+    use axum::Router;
+    use serde_json::json;
+
+    fn new_test_router() -> Router {
+        Router::new().route("/users", post(|| async { "created" }))
+    }
+
+    #[tokio::test]
+    async fn it_should_keep_request_body_and_headers() {
+        let server = TestServer::new(new_test_router()).unwrap();
+
+        let response = server
+            .post("/users")
+            .add_header("x-trace-id", "abc123")
+            .json(&json!({ "name": "Joe" }))
+            .await;
+
+        assert_eq!(response.request_body_bytes().unwrap(), r#"{"name":"Joe"}"#);
+        assert_eq!(response.request_headers()["x-trace-id"], "abc123");
+        assert_eq!(
+            response.request_headers()["content-type"],
+            "application/json"
+        );
+    }
+
+    #[tokio::test]
+    async fn it_should_not_keep_bodies_over_the_limit() {
+        let server = TestServer::builder()
+            .max_recorded_request_body(4)
+            .build(new_test_router())
+            .unwrap();
+
+        let small_response = server.post("/users").text("Joe").await;
+        let large_response = server.post("/users").text("Joe Bloggs").await;
+
+        assert_eq!(small_response.request_body_bytes().unwrap(), "Joe");
+        assert_eq!(large_response.request_body_bytes(), None);
+        assert_eq!(
+            large_response.request_headers()["content-type"],
+            "text/plain"
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_content_type {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Json;
+    use axum::Router;
+    use serde::Deserialize;
+    use serde::Serialize;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct ExampleResponse {
+        name: String,
+        age: u32,
+    }
+
+    #[tokio::test]
+    async fn it_should_retrieve_json_content_type_for_json() {
+        let app = Router::new().route(
+            &"/json",
+            get(|| async {
+                Json(ExampleResponse {
+                    name: "Joe".to_string(),
+                    age: 20,
+                })
+            }),
+        );
+
+        let server = TestServer::new(app).unwrap();
+
+        let content_type = server.get(&"/json").await.content_type();
+        assert_eq!(content_type, "application/json");
+    }
+
+    #[cfg(feature = "yaml")]
+    #[tokio::test]
+    async fn it_should_retrieve_yaml_content_type_for_yaml() {
+        use axum_yaml::Yaml;
+
+        let app = Router::new().route(
+            &"/yaml",
+            get(|| async {
+                Yaml(ExampleResponse {
+                    name: "Joe".to_string(),
+                    age: 20,
+                })
+            }),
+        );
+
+        let server = TestServer::new(app).unwrap();
+
+        let content_type = server.get(&"/yaml").await.content_type();
+        assert_eq!(content_type, "application/yaml");
+    }
+}
+
+#[cfg(test)]
+mod test_assert_content_type {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+    use http::header;
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .route(
+                "/json",
+                get(|| async {
+                    (
+                        [(header::CONTENT_TYPE, "application/json; charset=utf-8")],
+                        "{}",
+                    )
+                }),
+            )
+            .route("/text", get(|| async { "hello" }))
+            .route("/empty", get(|| async {}));
+
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_when_content_type_matches() {
+        let server = new_test_server();
+        let response = server.get("/json").await;
+
+        response.assert_content_type("application/json");
+        response.assert_content_type("application/json; charset=utf-8");
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_content_type_differs() {
+        let server = new_test_server();
+
+        server
+            .get("/text")
+            .await
+            .assert_content_type("application/json");
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_parameters_differ() {
+        let server = new_test_server();
+
+        server
+            .get("/json")
+            .await
+            .assert_content_type("application/json; charset=latin1");
+    }
+
+    #[tokio::test]
+    async fn it_should_return_error_when_content_type_is_missing() {
+        let server = new_test_server();
+        let error = server
+            .get("/empty")
+            .await
+            .check_content_type("text/plain")
+            .unwrap_err();
+
+        assert!(error.to_string().contains("header was not found"));
+    }
+}
+
+#[cfg(test)]
+mod test_json {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Json;
+    use axum::Router;
+    use serde::Deserialize;
+    use serde::Serialize;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct ExampleResponse {
+        name: String,
+        age: u32,
+    }
+
+    async fn route_get_json() -> Json<ExampleResponse> {
+        Json(ExampleResponse {
+            name: "Joe".to_string(),
+            age: 20,
+        })
+    }
+
+    #[tokio::test]
+    async fn it_should_deserialize_into_json() {
+        let app = Router::new().route(&"/json", get(route_get_json));
+
+        let server = TestServer::new(app).unwrap();
+
+        let response = server.get(&"/json").await.json::<ExampleResponse>();
+
+        assert_eq!(
+            response,
+            ExampleResponse {
+                name: "Joe".to_string(),
+                age: 20,
+            }
+        );
+    }
+}
+
+#[cfg(feature = "yaml")]
+#[cfg(test)]
+mod test_yaml {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+    use axum_yaml::Yaml;
+    use serde::Deserialize;
+    use serde::Serialize;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct ExampleResponse {
+        name: String,
+        age: u32,
+    }
+
+    async fn route_get_yaml() -> Yaml<ExampleResponse> {
+        Yaml(ExampleResponse {
+            name: "Joe".to_string(),
+            age: 20,
+        })
+    }
+
+    #[tokio::test]
+    async fn it_should_deserialize_into_yaml() {
+        let app = Router::new().route(&"/yaml", get(route_get_yaml));
+
+        let server = TestServer::new(app).unwrap();
+
+        let response = server.get(&"/yaml").await.yaml::<ExampleResponse>();
+
+        assert_eq!(
+            response,
+            ExampleResponse {
+                name: "Joe".to_string(),
+                age: 20,
+            }
+        );
+    }
+}
+
+#[cfg(all(feature = "dyn-features", not(feature = "yaml")))]
+#[cfg(test)]
+mod test_yaml_without_feature {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+
+    #[tokio::test]
+    async fn it_should_describe_the_missing_feature() {
+        let app = Router::new().route(&"/text", get(|| async { "name: Joe" }));
+        let server = TestServer::new(app).unwrap();
+
+        let error = server
+            .get(&"/text")
+            .await
+            .check_yaml(&"name: Joe".to_string())
+            .unwrap_err();
+
+        assert!(error.to_string().contains(
+            "`TestResponse::yaml` requires axum-test to be built with the `yaml` feature"
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_should_return_missing_feature_when_trying_to_send_yaml() {
+        let app = Router::new().route(&"/text", get(|| async { "" }));
+        let server = TestServer::new(app).unwrap();
+
+        let error = server
+            .get(&"/text")
+            .yaml(&"name: Joe")
+            .try_send()
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref::<crate::Error>(),
+            Some(crate::Error::MissingFeature { feature, .. }) if feature == "yaml"
+        ));
+    }
+
+    #[tokio::test]
+    #[should_panic(
+        expected = "`TestRequest::yaml` requires axum-test to be built with the `yaml` feature"
+    )]
+    async fn it_should_panic_when_sending_yaml() {
+        let app = Router::new().route(&"/text", get(|| async { "" }));
+        let server = TestServer::new(app).unwrap();
+
+        let _ = server.get(&"/text").yaml(&"name: Joe").await;
+    }
+}
+
+#[cfg(feature = "html")]
+#[cfg(test)]
+mod test_html {
+    use crate::TestServer;
+    use axum::response::Html;
+    use axum::routing::get;
+    use axum::Router;
+
+    #[tokio::test]
+    async fn it_should_parse_html_response() {
+        let app = Router::new().route(
+            "/",
+            get(|| async { Html("<main><h1>Welcome</h1><a href='/next'>Next</a></main>") }),
+        );
+        let server = TestServer::new(app).unwrap();
+
+        let html = server.get("/").await.html();
+
+        html.assert_selector_text("main > h1", "Welcome");
+        assert_eq!(html.select_attr("a", "href"), Some("/next".to_string()));
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "for request GET http://localhost/")]
+    async fn it_should_include_request_in_failures() {
+        let app = Router::new().route("/", get(|| async { Html("<h1>Welcome</h1>") }));
+        let server = TestServer::new(app).unwrap();
+
+        server.get("/").await.html().assert_selector_exists("h2");
+    }
+}
+
+#[cfg(feature = "archives")]
+#[cfg(test)]
+mod test_zip {
+    use crate::new_zip_bytes;
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+
+    #[tokio::test]
+    async fn it_should_read_zip_response() {
+        let app = Router::new().route(
+            "/export",
+            get(|| async { new_zip_bytes(&[("report.csv", b"a,b\n1,2\n")]) }),
+        );
+        let server = TestServer::new(app).unwrap();
+
+        let archive = server.get("/export").await.zip();
+
+        archive.assert_file_count(1);
+        archive.assert_contains_file("report.csv");
+        assert_eq!(archive.file_bytes("report.csv"), "a,b\n1,2\n");
+    }
+
+    #[tokio::test]
+    #[should_panic(
+        expected = "Failed to read response as a zip archive, for request GET http://localhost/text"
+    )]
+    async fn it_should_panic_on_non_zip_response() {
+        let app = Router::new().route("/text", get(|| async { "hello" }));
+        let server = TestServer::new(app).unwrap();
+
+        let _ = server.get("/text").await.zip();
+    }
+}
+
+#[cfg(feature = "msgpack")]
+#[cfg(test)]
+mod test_msgpack {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+    use axum_msgpack::MsgPack;
+    use serde::Deserialize;
+    use serde::Serialize;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct ExampleResponse {
+        name: String,
+        age: u32,
+    }
+
+    async fn route_get_msgpack() -> MsgPack<ExampleResponse> {
+        MsgPack(ExampleResponse {
+            name: "Joe".to_string(),
+            age: 20,
+        })
+    }
+
+    #[tokio::test]
+    async fn it_should_deserialize_into_msgpack() {
+        let app = Router::new().route(&"/msgpack", get(route_get_msgpack));
+
+        let server = TestServer::new(app).unwrap();
+
+        let response = server.get(&"/msgpack").await.msgpack::<ExampleResponse>();
+
+        assert_eq!(
+            response,
+            ExampleResponse {
+                name: "Joe".to_string(),
+                age: 20,
+            }
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_form {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Form;
+    use axum::Router;
+    use serde::Deserialize;
+    use serde::Serialize;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct ExampleResponse {
+        name: String,
+        age: u32,
+    }
+
+    async fn route_get_form() -> Form<ExampleResponse> {
+        Form(ExampleResponse {
+            name: "Joe".to_string(),
+            age: 20,
+        })
+    }
+
+    #[tokio::test]
+    async fn it_should_deserialize_into_form() {
+        let app = Router::new().route(&"/form", get(route_get_form));
+
+        let server = TestServer::new(app).unwrap();
+
+        let response = server.get(&"/form").await.form::<ExampleResponse>();
+
+        assert_eq!(
+            response,
+            ExampleResponse {
+                name: "Joe".to_string(),
+                age: 20,
+            }
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_decode {
+    use crate::TestServer;
+    use anyhow::anyhow;
+    use axum::routing::get;
+    use axum::Form;
+    use axum::Json;
+    use axum::Router;
+    use serde::Deserialize;
+    use serde::Serialize;
+    use serde_json::json;
+    use serde_json::Value;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct User {
+        name: String,
+        age: u32,
+    }
+
+    fn decode_user(body: &[u8]) -> anyhow::Result<Value> {
+        let body = std::str::from_utf8(body)?;
+        let (name, age) = body.split_once(':').ok_or_else(|| anyhow!("missing ':'"))?;
+
+        Ok(json!({ "name": name, "age": age.parse::<u32>()? }))
+    }
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .route(
+                "/vendor",
+                get(|| async {
+                    (
+                        [("content-type", "Application/Vnd.My-User; charset=utf-8")],
+                        "Joe:42",
+                    )
+                }),
+            )
+            .route(
+                "/vendor-invalid",
+                get(|| async { ([("content-type", "application/vnd.my-user")], "Joe") }),
+            )
+            .route(
+                "/json",
+                get(|| async { Json(json!({ "name": "Joe", "age": 42 })) }),
+            )
+            .route(
+                "/problem",
+                get(|| async {
+                    (
+                        [("content-type", "application/problem+json")],
+                        r#"{ "name": "Joe", "age": 42 }"#,
+                    )
+                }),
+            )
+            .route(
+                "/form",
+                get(|| async {
+                    Form(User {
+                        name: "Joe".to_string(),
+                        age: 42,
+                    })
+                }),
+            )
+            .route(
+                "/unknown",
+                get(|| async { ([("content-type", "application/vnd.unknown")], "?") }),
+            );
+
+        let mut server = TestServer::new(app).unwrap();
+        server.register_decoder("application/vnd.my-user", decode_user);
+        server
+    }
+
+    fn expected_user() -> User {
+        User {
+            name: "Joe".to_string(),
+            age: 42,
+        }
+    }
+
+    #[tokio::test]
+    async fn it_should_decode_using_registered_decoder() {
+        let server = new_test_server();
+
+        let user = server.get("/vendor").await.decode::<User>();
+        assert_eq!(user, expected_user());
+    }
+
+    #[tokio::test]
+    async fn it_should_decode_json() {
+        let server = new_test_server();
+
+        let user = server.get("/json").await.decode::<User>();
+        assert_eq!(user, expected_user());
+    }
+
+    #[tokio::test]
+    async fn it_should_decode_json_suffix_content_types() {
+        let server = new_test_server();
+
+        let user = server.get("/problem").await.decode::<User>();
+        assert_eq!(user, expected_user());
+    }
+
+    #[cfg(feature = "yaml")]
+    #[tokio::test]
+    async fn it_should_decode_yaml() {
+        let app = Router::new().route(
+            "/yaml",
+            get(|| async {
+                (
+                    [("content-type", "application/yaml")],
+                    "name: Joe\nage: 42\n",
+                )
+            }),
+        );
+        let server = TestServer::new(app).unwrap();
+
+        let user = server.get("/yaml").await.decode::<User>();
+        assert_eq!(user, expected_user());
+    }
+
+    #[tokio::test]
+    async fn it_should_decode_form() {
+        let server = new_test_server();
+
+        let user = server.get("/form").await.decode::<User>();
+        assert_eq!(user, expected_user());
+    }
+
+    #[tokio::test]
+    async fn it_should_prefer_registered_decoder_over_built_in_decoders() {
+        let mut server = new_test_server();
+        server.register_decoder("application/json", |_| {
+            Ok(json!({ "name": "Custom", "age": 1 }))
+        });
+
+        let user = server.get("/json").await.decode::<User>();
+        assert_eq!(user.name, "Custom");
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_no_decoder_is_registered() {
+        let server = new_test_server();
+
+        let _ = server.get("/unknown").await.decode::<Value>();
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_decoder_fails() {
+        let server = new_test_server();
+
+        let _ = server.get("/vendor-invalid").await.decode::<User>();
+    }
+}
+
+#[cfg(test)]
+mod test_from {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+    use bytes::Bytes;
+
+    #[tokio::test]
+    async fn it_should_turn_into_response_bytes() {
+        let app = Router::new().route(&"/text", get(|| async { "This is some example text" }));
+        let server = TestServer::new(app).unwrap();
+
+        let response = server.get(&"/text").await;
+        let bytes: Bytes = response.into();
+        let text = String::from_utf8_lossy(&bytes);
+        assert_eq!(text, "This is some example text");
+    }
+}
+
+#[cfg(test)]
+mod test_assert_text {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+
+    fn new_test_server() -> TestServer {
+        async fn route_get_text() -> &'static str {
+            "This is some example text"
+        }
+
+        let app = Router::new().route(&"/text", get(route_get_text));
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_match_whole_text() {
+        let server = new_test_server();
+
+        server
+            .get(&"/text")
+            .await
+            .assert_text("This is some example text");
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_not_match_partial_text() {
+        let server = new_test_server();
+
+        server.get(&"/text").await.assert_text("some example");
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_not_match_different_text() {
+        let server = new_test_server();
+
+        server.get(&"/text").await.assert_text("🦊");
+    }
+}
+
+#[cfg(test)]
+mod test_bytes_from_base64 {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+
+    #[tokio::test]
+    async fn it_should_decode_the_body() {
+        let app = Router::new().route("/key", get(|| async { "3q2+7w==\n" }));
+        let server = TestServer::new(app).unwrap();
+
+        let bytes = server.get("/key").await.bytes_from_base64();
+
+        assert_eq!(bytes.as_ref(), &[0xde, 0xad, 0xbe, 0xef]);
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_if_the_body_is_not_base64() {
+        let app = Router::new().route("/key", get(|| async { "not base64!" }));
+        let server = TestServer::new(app).unwrap();
+
+        let _ = server.get("/key").await.bytes_from_base64();
+    }
+}
+
+#[cfg(test)]
+mod test_bytes_from_hex {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+
+    #[tokio::test]
+    async fn it_should_decode_the_body() {
+        let app = Router::new().route("/key", get(|| async { "DEADbeef" }));
+        let server = TestServer::new(app).unwrap();
+
+        let bytes = server.get("/key").await.bytes_from_hex();
+
+        assert_eq!(bytes.as_ref(), &[0xde, 0xad, 0xbe, 0xef]);
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_if_the_body_is_not_hex() {
+        let app = Router::new().route("/key", get(|| async { "xyz" }));
+        let server = TestServer::new(app).unwrap();
+
+        let _ = server.get("/key").await.bytes_from_hex();
+    }
+}
+
+#[cfg(test)]
+mod test_assert_body_base64_eq {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+
+    fn new_server() -> TestServer {
+        let app = Router::new().route("/pixel", get(|| async { vec![0x47_u8, 0x49, 0x46, 0x38] }));
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_for_matching_bytes() {
+        new_server()
+            .get("/pixel")
+            .await
+            .assert_body_base64_eq("R0lGOA==");
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_for_different_bytes() {
+        new_server()
+            .get("/pixel")
+            .await
+            .assert_body_base64_eq("R0lGOQ==");
+    }
+
+    #[tokio::test]
+    async fn it_should_error_for_invalid_expected_base64() {
+        let result = new_server()
+            .get("/pixel")
+            .await
+            .check_body_base64_eq("not base64!");
+
+        assert!(result.is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_assert_body_hex_eq {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+
+    fn new_server() -> TestServer {
+        let app = Router::new().route("/pixel", get(|| async { vec![0x47_u8, 0x49, 0x46, 0x38] }));
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_for_matching_bytes() {
+        new_server()
+            .get("/pixel")
+            .await
+            .assert_body_hex_eq("47494638");
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_for_different_bytes() {
+        new_server()
+            .get("/pixel")
+            .await
+            .assert_body_hex_eq("47494639");
+    }
+}
+
+#[cfg(test)]
+mod test_assert_text_contains {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+
+    fn new_test_server() -> TestServer {
+        async fn route_get_text() -> &'static str {
+            "This is some example text"
+        }
+
+        let app = Router::new().route(&"/text", get(route_get_text));
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_match_whole_text() {
+        let server = new_test_server();
+
+        server
+            .get(&"/text")
+            .await
+            .assert_text_contains("This is some example text");
+    }
+
+    #[tokio::test]
+    async fn it_should_match_partial_text() {
+        let server = new_test_server();
+
+        server
+            .get(&"/text")
+            .await
+            .assert_text_contains("some example");
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_not_match_different_text() {
+        let server = new_test_server();
+
+        server.get(&"/text").await.assert_text_contains("🦊");
+    }
+}
+
+#[cfg(test)]
+mod test_assert_text_matches_pattern {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
 
-        assert!(
-            is_not_in_range,
-            "Expected status is not in range {}, received {status_code}, for request {debug_request_format}, with body {debug_body}",
-            format_status_code_range(range)
+    fn new_test_server() -> TestServer {
+        let app = Router::new().route(
+            "/inbox",
+            get(|| async { "Hello, Joe! You have 3 new messages" }),
         );
+        TestServer::new(app).unwrap()
     }
 
-    /// Assert the response status code is 200.
-    #[track_caller]
-    pub fn assert_status_ok(&self) {
-        self.assert_status(StatusCode::OK)
-    }
+    #[tokio::test]
+    async fn it_should_match_pattern() {
+        let server = new_test_server();
 
-    /// Assert the response status code is **not** 200.
-    #[track_caller]
-    pub fn assert_status_not_ok(&self) {
-        self.assert_not_status(StatusCode::OK)
+        server
+            .get("/inbox")
+            .await
+            .assert_text_matches_pattern("Hello, *! You have ? new messages");
     }
 
-    /// Assert the response status code is 303.
-    #[track_caller]
-    pub fn assert_status_see_other(&self) {
-        self.assert_status(StatusCode::SEE_OTHER)
-    }
+    #[tokio::test]
+    async fn it_should_fail_on_different_text() {
+        let server = new_test_server();
+        let error = server
+            .get("/inbox")
+            .await
+            .check_text_matches_pattern("Hello, *! You have ? old messages")
+            .unwrap_err();
 
-    /// Assert the response status code is 400.
-    #[track_caller]
-    pub fn assert_status_bad_request(&self) {
-        self.assert_status(StatusCode::BAD_REQUEST)
+        assert!(error
+            .to_string()
+            .contains("Expected text to match pattern 'Hello, *! You have ? old messages'"));
     }
+}
 
-    /// Assert the response status code is 404.
-    #[track_caller]
-    pub fn assert_status_not_found(&self) {
-        self.assert_status(StatusCode::NOT_FOUND)
-    }
+#[cfg(feature = "regex")]
+#[cfg(test)]
+mod test_assert_text_matches_regex {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
 
-    /// Assert the response status code is 401.
-    #[track_caller]
-    pub fn assert_status_unauthorized(&self) {
-        self.assert_status(StatusCode::UNAUTHORIZED)
+    fn new_test_server() -> TestServer {
+        let app = Router::new().route("/user", get(|| async { "user-123" }));
+        TestServer::new(app).unwrap()
     }
 
-    /// Assert the response status code is 403.
-    #[track_caller]
-    pub fn assert_status_forbidden(&self) {
-        self.assert_status(StatusCode::FORBIDDEN)
+    #[tokio::test]
+    async fn it_should_match_regex() {
+        let server = new_test_server();
+
+        server
+            .get("/user")
+            .await
+            .assert_text_matches_regex(r"^user-\d+$");
     }
 
-    /// Assert the response status code is 409.
-    pub fn assert_status_conflict(&self) {
-        self.assert_status(StatusCode::CONFLICT)
+    #[tokio::test]
+    async fn it_should_fail_on_non_matching_regex() {
+        let server = new_test_server();
+        let error = server
+            .get("/user")
+            .await
+            .check_text_matches_regex(r"^admin-\d+$")
+            .unwrap_err();
+
+        assert!(error
+            .to_string()
+            .contains(r"Expected text to match regex '^admin-\d+$', received 'user-123'"));
     }
 
-    /// Assert the response status code is 413.
-    ///
-    /// The payload is too large.
-    #[track_caller]
-    pub fn assert_status_payload_too_large(&self) {
-        self.assert_status(StatusCode::PAYLOAD_TOO_LARGE)
+    #[tokio::test]
+    async fn it_should_fail_on_invalid_regex() {
+        let server = new_test_server();
+        let error = server
+            .get("/user")
+            .await
+            .check_text_matches_regex(r"^user-(\d+$")
+            .unwrap_err();
+
+        assert!(error.to_string().contains("Failed to build regex"));
     }
+}
 
-    /// Assert the response status code is 422.
-    #[track_caller]
-    pub fn assert_status_unprocessable_entity(&self) {
-        self.assert_status(StatusCode::UNPROCESSABLE_ENTITY)
+#[cfg(feature = "regex")]
+#[cfg(test)]
+mod test_text_captures {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new().route("/user", get(|| async { "user-123 joined" }));
+        TestServer::new(app).unwrap()
     }
 
-    /// Assert the response status code is 429.
-    #[track_caller]
-    pub fn assert_status_too_many_requests(&self) {
-        self.assert_status(StatusCode::TOO_MANY_REQUESTS)
+    #[tokio::test]
+    async fn it_should_return_capture_groups() {
+        let server = new_test_server();
+        let captures = server
+            .get("/user")
+            .await
+            .text_captures(r"^user-(\d+) (left)?(joined)?$");
+
+        assert_eq!(captures, vec!["123", "", "joined"]);
     }
 
-    /// Assert the response status code is 101.
-    ///
-    /// This type of code is used in Web Socket connection when
-    /// first request.
-    #[track_caller]
-    pub fn assert_status_switching_protocols(&self) {
-        self.assert_status(StatusCode::SWITCHING_PROTOCOLS)
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_not_matching() {
+        let server = new_test_server();
+        let _ = server.get("/user").await.text_captures(r"^admin-(\d+)$");
     }
+}
 
-    /// Assert the response status code is 500.
-    #[track_caller]
-    pub fn assert_status_internal_server_error(&self) {
-        self.assert_status(StatusCode::INTERNAL_SERVER_ERROR)
+#[cfg(test)]
+mod test_assert_body_starts_with {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new().route("/bytes", get(|| async { &b"\x01\x02\x03\x04"[..] }));
+        TestServer::new(app).unwrap()
     }
 
-    /// Assert the response status code is 503.
-    #[track_caller]
-    pub fn assert_status_service_unavailable(&self) {
-        self.assert_status(StatusCode::SERVICE_UNAVAILABLE)
+    #[tokio::test]
+    async fn it_should_pass_on_matching_prefix() {
+        let server = new_test_server();
+
+        server
+            .get("/bytes")
+            .await
+            .assert_body_starts_with(b"\x01\x02");
     }
 
-    fn debug_request_format(&self) -> RequestPathFormatter<'_> {
-        RequestPathFormatter::new(&self.method, self.full_request_url.as_str(), None)
+    #[tokio::test]
+    async fn it_should_fail_on_different_prefix() {
+        let server = new_test_server();
+        let error = server
+            .get("/bytes")
+            .await
+            .check_body_starts_with(b"\x02")
+            .unwrap_err();
+
+        assert!(error
+            .to_string()
+            .contains(r#"Expected body to start with b"\x02", received b"\x01""#));
     }
-}
 
-impl From<TestResponse> for Bytes {
-    fn from(response: TestResponse) -> Self {
-        response.into_bytes()
+    #[tokio::test]
+    async fn it_should_fail_when_prefix_is_longer_than_body() {
+        let server = new_test_server();
+        let result = server
+            .get("/bytes")
+            .await
+            .check_body_starts_with(b"\x01\x02\x03\x04\x05");
+
+        assert!(result.is_err());
     }
 }
 
 #[cfg(test)]
-mod test_assert_header {
+mod test_assert_body_ends_with {
     use crate::TestServer;
-    use axum::http::HeaderMap;
     use axum::routing::get;
     use axum::Router;
 
-    async fn route_get_header() -> HeaderMap {
-        let mut headers = HeaderMap::new();
-        headers.insert("x-my-custom-header", "content".parse().unwrap());
-        headers
+    fn new_test_server() -> TestServer {
+        let app = Router::new().route("/bytes", get(|| async { &b"\x01\x02\x03\x04"[..] }));
+        TestServer::new(app).unwrap()
     }
 
     #[tokio::test]
-    async fn it_should_not_panic_if_contains_header_and_content_matches() {
-        let router = Router::new().route(&"/header", get(route_get_header));
-
-        let server = TestServer::new(router).unwrap();
+    async fn it_should_pass_on_matching_suffix() {
+        let server = new_test_server();
 
         server
-            .get(&"/header")
+            .get("/bytes")
             .await
-            .assert_header("x-my-custom-header", "content");
+            .assert_body_ends_with(b"\x03\x04");
+        server.get("/bytes").await.assert_body_ends_with(b"");
     }
 
     #[tokio::test]
-    #[should_panic]
-    async fn it_should_panic_if_contains_header_and_content_does_not_match() {
-        let router = Router::new().route(&"/header", get(route_get_header));
+    async fn it_should_fail_on_different_suffix() {
+        let server = new_test_server();
+        let error = server
+            .get("/bytes")
+            .await
+            .check_body_ends_with(b"\x03")
+            .unwrap_err();
 
-        let server = TestServer::new(router).unwrap();
+        assert!(error
+            .to_string()
+            .contains(r#"Expected body to end with b"\x03", received b"\x04""#));
+    }
+}
+
+#[cfg(test)]
+mod test_assert_body_magic {
+    use crate::FileKind;
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .route("/image.png", get(|| async { &b"\x89PNG\r\n\x1a\n..."[..] }))
+            .route("/text", get(|| async { "hello" }));
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_on_matching_file_kind() {
+        let server = new_test_server();
 
         server
-            .get(&"/header")
+            .get("/image.png")
             .await
-            .assert_header("x-my-custom-header", "different-content");
+            .assert_body_magic(FileKind::Png);
     }
 
     #[tokio::test]
-    #[should_panic]
-    async fn it_should_panic_if_not_contains_header() {
-        let router = Router::new().route(&"/header", get(route_get_header));
+    async fn it_should_fail_on_different_file_kind() {
+        let server = new_test_server();
+        let error = server
+            .get("/image.png")
+            .await
+            .check_body_magic(FileKind::Zip)
+            .unwrap_err();
 
-        let server = TestServer::new(router).unwrap();
+        assert!(error
+            .to_string()
+            .contains("Expected body to be a zip file, received a png file"));
+    }
 
-        server
-            .get(&"/header")
+    #[tokio::test]
+    async fn it_should_fail_on_unrecognised_bytes() {
+        let server = new_test_server();
+        let error = server
+            .get("/text")
             .await
-            .assert_header("x-custom-header-not-found", "content");
+            .check_body_magic(FileKind::Pdf)
+            .unwrap_err();
+
+        assert!(error
+            .to_string()
+            .contains("Expected body to be a pdf file, received unrecognised bytes"));
     }
 }
 
 #[cfg(test)]
-mod test_assert_contains_header {
+mod test_assert_text_from_file {
     use crate::TestServer;
-    use axum::http::HeaderMap;
     use axum::routing::get;
-    use axum::Router;
-
-    async fn route_get_header() -> HeaderMap {
-        let mut headers = HeaderMap::new();
-        headers.insert("x-my-custom-header", "content".parse().unwrap());
-        headers
-    }
+    use axum::routing::Router;
 
     #[tokio::test]
-    async fn it_should_not_panic_if_contains_header() {
-        let router = Router::new().route(&"/header", get(route_get_header));
-
-        let server = TestServer::new(router).unwrap();
+    async fn it_should_match_from_file() {
+        let app = Router::new().route(&"/text", get(|| async { "hello!" }));
+        let server = TestServer::new(app).unwrap();
 
         server
-            .get(&"/header")
+            .get(&"/text")
             .await
-            .assert_contains_header("x-my-custom-header");
+            .assert_text_from_file("files/example.txt");
     }
 
     #[tokio::test]
     #[should_panic]
-    async fn it_should_panic_if_not_contains_header() {
-        let router = Router::new().route(&"/header", get(route_get_header));
-
-        let server = TestServer::new(router).unwrap();
+    async fn it_should_panic_when_not_match_the_file() {
+        let app = Router::new().route(&"/text", get(|| async { "🦊" }));
+        let server = TestServer::new(app).unwrap();
 
         server
-            .get(&"/header")
+            .get(&"/text")
             .await
-            .assert_contains_header("x-custom-header-not-found");
+            .assert_text_from_file("files/example.txt");
     }
 }
 
 #[cfg(test)]
-mod test_assert_success {
+mod test_assert_json {
     use crate::TestServer;
     use axum::routing::get;
+    use axum::Form;
+    use axum::Json;
     use axum::Router;
-    use http::StatusCode;
+    use serde::Deserialize;
+    use serde::Serialize;
 
-    pub async fn route_get_pass() -> StatusCode {
-        StatusCode::OK
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct ExampleResponse {
+        name: String,
+        age: u32,
     }
 
-    pub async fn route_get_fail() -> StatusCode {
-        StatusCode::SERVICE_UNAVAILABLE
+    async fn route_get_form() -> Form<ExampleResponse> {
+        Form(ExampleResponse {
+            name: "Joe".to_string(),
+            age: 20,
+        })
+    }
+
+    async fn route_get_json() -> Json<ExampleResponse> {
+        Json(ExampleResponse {
+            name: "Joe".to_string(),
+            age: 20,
+        })
     }
 
     #[tokio::test]
-    async fn it_should_pass_when_200() {
-        let router = Router::new()
-            .route(&"/pass", get(route_get_pass))
-            .route(&"/fail", get(route_get_fail));
+    async fn it_should_match_json_returned() {
+        let app = Router::new().route(&"/json", get(route_get_json));
 
-        let server = TestServer::new(router).unwrap();
+        let server = TestServer::new(app).unwrap();
 
-        let response = server.get(&"/pass").await;
+        server.get(&"/json").await.assert_json(&ExampleResponse {
+            name: "Joe".to_string(),
+            age: 20,
+        });
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_if_response_is_different() {
+        let app = Router::new().route(&"/json", get(route_get_json));
+
+        let server = TestServer::new(app).unwrap();
 
-        response.assert_status_success()
+        server.get(&"/json").await.assert_json(&ExampleResponse {
+            name: "Julia".to_string(),
+            age: 25,
+        });
     }
 
     #[tokio::test]
     #[should_panic]
-    async fn it_should_panic_when_not_200() {
-        let router = Router::new()
-            .route(&"/pass", get(route_get_pass))
-            .route(&"/fail", get(route_get_fail));
-
-        let server = TestServer::new(router).unwrap();
+    async fn it_should_panic_if_response_is_form() {
+        let app = Router::new().route(&"/form", get(route_get_form));
 
-        let response = server.get(&"/fail").expect_failure().await;
+        let server = TestServer::new(app).unwrap();
 
-        response.assert_status_success()
+        server.get(&"/form").await.assert_json(&ExampleResponse {
+            name: "Joe".to_string(),
+            age: 20,
+        });
     }
 }
 
 #[cfg(test)]
-mod test_assert_failure {
+mod test_canonical_json {
     use crate::TestServer;
     use axum::routing::get;
     use axum::Router;
-    use http::StatusCode;
-
-    pub async fn route_get_pass() -> StatusCode {
-        StatusCode::OK
-    }
-
-    pub async fn route_get_fail() -> StatusCode {
-        StatusCode::SERVICE_UNAVAILABLE
-    }
 
     #[tokio::test]
-    async fn it_should_pass_when_not_200() {
-        let router = Router::new()
-            .route(&"/pass", get(route_get_pass))
-            .route(&"/fail", get(route_get_fail));
+    async fn it_should_return_the_canonical_form_of_the_json_returned() {
+        let app = Router::new().route(
+            "/json",
+            get(|| async { r#"{ "b": [1.0, 2e2], "a": { "y": "A", "x": null } }"# }),
+        );
+        let server = TestServer::new(app).unwrap();
 
-        let server = TestServer::new(router).unwrap();
-        let response = server.get(&"/fail").expect_failure().await;
+        let canonical = server.get("/json").await.canonical_json();
 
-        response.assert_status_failure()
+        assert_eq!(canonical, r#"{"a":{"x":null,"y":"A"},"b":[1,200]}"#);
     }
 
     #[tokio::test]
     #[should_panic]
-    async fn it_should_panic_when_200() {
-        let router = Router::new()
-            .route(&"/pass", get(route_get_pass))
-            .route(&"/fail", get(route_get_fail));
-
-        let server = TestServer::new(router).unwrap();
-        let response = server.get(&"/pass").await;
+    async fn it_should_panic_if_the_response_is_not_json() {
+        let app = Router::new().route("/text", get(|| async { "not json" }));
+        let server = TestServer::new(app).unwrap();
 
-        response.assert_status_failure()
+        let _ = server.get("/text").await.canonical_json();
     }
 }
 
 #[cfg(test)]
-mod test_assert_status {
+mod test_assert_canonical_json_eq {
     use crate::TestServer;
     use axum::routing::get;
+    use axum::Json;
     use axum::Router;
-    use http::StatusCode;
+    use serde_json::json;
 
-    pub async fn route_get_ok() -> StatusCode {
-        StatusCode::OK
+    #[tokio::test]
+    async fn it_should_pass_when_the_body_is_the_canonical_json() {
+        let app = Router::new().route("/json", get(|| async { r#"{"a":1,"b":"two"}"# }));
+        let server = TestServer::new(app).unwrap();
+
+        server
+            .get("/json")
+            .await
+            .assert_canonical_json_eq(&json!({ "b": "two", "a": 1.0 }));
     }
 
     #[tokio::test]
-    async fn it_should_pass_if_given_right_status_code() {
-        let router = Router::new().route(&"/ok", get(route_get_ok));
-        let server = TestServer::new(router).unwrap();
+    #[should_panic]
+    async fn it_should_panic_when_the_keys_are_not_sorted() {
+        let app = Router::new().route("/json", get(|| async { r#"{"b":"two","a":1}"# }));
+        let server = TestServer::new(app).unwrap();
 
-        server.get(&"/ok").await.assert_status(StatusCode::OK);
+        server
+            .get("/json")
+            .await
+            .assert_canonical_json_eq(&json!({ "a": 1, "b": "two" }));
     }
 
     #[tokio::test]
     #[should_panic]
-    async fn it_should_panic_when_status_code_does_not_match() {
-        let router = Router::new().route(&"/ok", get(route_get_ok));
-        let server = TestServer::new(router).unwrap();
+    async fn it_should_panic_when_the_body_has_whitespace() {
+        let app = Router::new().route("/json", get(|| async { r#"{"a": 1}"# }));
+        let server = TestServer::new(app).unwrap();
 
-        server.get(&"/ok").await.assert_status(StatusCode::ACCEPTED);
+        server
+            .get("/json")
+            .await
+            .assert_canonical_json_eq(&json!({ "a": 1 }));
+    }
+
+    #[tokio::test]
+    async fn it_should_not_panic_on_check_for_different_json() {
+        let app = Router::new().route("/json", get(|| async { Json(json!({ "a": 1 })) }));
+        let server = TestServer::new(app).unwrap();
+
+        let result = server
+            .get("/json")
+            .await
+            .check_canonical_json_eq(&json!({ "a": 2 }));
+
+        assert!(result.is_err());
     }
 }
 
 #[cfg(test)]
-mod test_assert_not_status {
+mod test_assert_json_contains {
     use crate::TestServer;
     use axum::routing::get;
+    use axum::Form;
+    use axum::Json;
     use axum::Router;
-    use http::StatusCode;
+    use serde::Deserialize;
+    use serde::Serialize;
+    use serde_json::json;
+    use std::time::Instant;
 
-    pub async fn route_get_ok() -> StatusCode {
-        StatusCode::OK
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct ExampleResponse {
+        time: u64,
+        name: String,
+        age: u32,
+    }
+
+    async fn route_get_form() -> Form<ExampleResponse> {
+        Form(ExampleResponse {
+            time: Instant::now().elapsed().as_millis() as u64,
+            name: "Joe".to_string(),
+            age: 20,
+        })
+    }
+
+    async fn route_get_json() -> Json<ExampleResponse> {
+        Json(ExampleResponse {
+            time: Instant::now().elapsed().as_millis() as u64,
+            name: "Joe".to_string(),
+            age: 20,
+        })
     }
 
     #[tokio::test]
-    async fn it_should_pass_if_status_code_does_not_match() {
-        let router = Router::new().route(&"/ok", get(route_get_ok));
-        let server = TestServer::new(router).unwrap();
+    async fn it_should_match_subset_of_json_returned() {
+        let app = Router::new().route(&"/json", get(route_get_json));
+        let server = TestServer::new(app).unwrap();
+
+        server.get(&"/json").await.assert_json_contains(&json!({
+            "name": "Joe",
+            "age": 20,
+        }));
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_if_response_is_different() {
+        let app = Router::new().route(&"/json", get(route_get_json));
+        let server = TestServer::new(app).unwrap();
 
         server
-            .get(&"/ok")
+            .get(&"/json")
             .await
-            .assert_not_status(StatusCode::ACCEPTED);
+            .assert_json_contains(&ExampleResponse {
+                time: 1234,
+                name: "Julia".to_string(),
+                age: 25,
+            });
     }
 
     #[tokio::test]
     #[should_panic]
-    async fn it_should_panic_if_status_code_matches() {
-        let router = Router::new().route(&"/ok", get(route_get_ok));
-        let server = TestServer::new(router).unwrap();
+    async fn it_should_panic_if_response_is_form() {
+        let app = Router::new().route(&"/form", get(route_get_form));
+        let server = TestServer::new(app).unwrap();
 
-        server.get(&"/ok").await.assert_not_status(StatusCode::OK);
+        server.get(&"/form").await.assert_json_contains(&json!({
+            "name": "Joe",
+            "age": 20,
+        }));
     }
 }
 
 #[cfg(test)]
-mod test_assert_status_in_range {
+mod test_assert_json_approx {
+    use crate::JsonTolerance;
     use crate::TestServer;
     use axum::routing::get;
-    use axum::routing::Router;
-    use http::StatusCode;
-    use std::ops::RangeFull;
+    use axum::Json;
+    use axum::Router;
+    use serde_json::json;
 
-    #[tokio::test]
-    async fn it_should_be_true_when_within_int_range() {
+    fn new_test_server() -> TestServer {
         let app = Router::new().route(
-            &"/status",
-            get(|| async { StatusCode::NON_AUTHORITATIVE_INFORMATION }),
+            "/price",
+            get(|| async {
+                Json(json!({
+                    "name": "apples",
+                    "prices": [1.1000000001, 2.2],
+                }))
+            }),
         );
 
-        TestServer::new(app)
-            .unwrap()
-            .get(&"/status")
-            .await
-            .assert_status_in_range(200..299);
+        TestServer::new(app).unwrap()
     }
 
     #[tokio::test]
-    async fn it_should_be_true_when_within_status_code_range() {
-        let app = Router::new().route(
-            &"/status",
-            get(|| async { StatusCode::NON_AUTHORITATIVE_INFORMATION }),
-        );
+    async fn it_should_match_numbers_within_absolute_tolerance() {
+        let server = new_test_server();
 
-        TestServer::new(app)
-            .unwrap()
-            .get(&"/status")
-            .await
-            .assert_status_in_range(StatusCode::OK..StatusCode::IM_USED);
+        server.get("/price").await.assert_json_approx(
+            &json!({
+                "name": "apples",
+                "prices": [1.1, 2.2],
+            }),
+            0.001,
+        );
     }
 
     #[tokio::test]
-    #[should_panic]
-    async fn it_should_be_false_when_outside_int_range() {
-        let app = Router::new().route(
-            &"/status",
-            get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
-        );
+    async fn it_should_match_numbers_within_relative_tolerance() {
+        let server = new_test_server();
 
-        TestServer::new(app)
-            .unwrap()
-            .get(&"/status")
-            .await
-            .assert_status_in_range(200..299);
+        server.get("/price").await.assert_json_approx(
+            &json!({
+                "name": "apples",
+                "prices": [1.1, 2.21],
+            }),
+            JsonTolerance::Relative(0.01),
+        );
     }
 
     #[tokio::test]
     #[should_panic]
-    async fn it_should_be_false_when_outside_status_code_range() {
-        let app = Router::new().route(
-            &"/status",
-            get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
-        );
+    async fn it_should_panic_if_numbers_outside_tolerance() {
+        let server = new_test_server();
 
-        TestServer::new(app)
-            .unwrap()
-            .get(&"/status")
-            .await
-            .assert_status_in_range(StatusCode::OK..StatusCode::IM_USED);
+        server.get("/price").await.assert_json_approx(
+            &json!({
+                "name": "apples",
+                "prices": [1.2, 2.2],
+            }),
+            0.001,
+        );
     }
 
     #[tokio::test]
-    async fn it_should_be_true_when_within_inclusive_range() {
-        let app = Router::new().route(
-            &"/status",
-            get(|| async { StatusCode::NON_AUTHORITATIVE_INFORMATION }),
-        );
+    #[should_panic]
+    async fn it_should_panic_if_other_fields_differ() {
+        let server = new_test_server();
 
-        TestServer::new(app)
-            .unwrap()
-            .get(&"/status")
-            .await
-            .assert_status_in_range(200..=299);
+        server.get("/price").await.assert_json_approx(
+            &json!({
+                "name": "oranges",
+                "prices": [1.1, 2.2],
+            }),
+            0.001,
+        );
     }
+}
+
+#[cfg(test)]
+mod test_assert_json_from_file {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::routing::Router;
+    use axum::Form;
+    use axum::Json;
+    use serde::Deserialize;
+    use serde::Serialize;
+    use serde_json::json;
 
     #[tokio::test]
-    #[should_panic]
-    async fn it_should_be_false_when_outside_inclusive_range() {
+    async fn it_should_match_json_from_file() {
         let app = Router::new().route(
-            &"/status",
-            get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
+            &"/json",
+            get(|| async {
+                Json(json!(
+                    {
+                        "name": "Joe",
+                        "age": 20,
+                    }
+                ))
+            }),
         );
+        let server = TestServer::new(app).unwrap();
 
-        TestServer::new(app)
-            .unwrap()
-            .get(&"/status")
+        server
+            .get(&"/json")
             .await
-            .assert_status_in_range(200..=299);
+            .assert_json_from_file("files/example.json");
     }
 
     #[tokio::test]
-    async fn it_should_be_true_when_within_to_range() {
+    #[should_panic]
+    async fn it_should_panic_when_not_match_the_file() {
         let app = Router::new().route(
-            &"/status",
-            get(|| async { StatusCode::NON_AUTHORITATIVE_INFORMATION }),
+            &"/json",
+            get(|| async {
+                Json(json!(
+                    {
+                        "name": "Julia",
+                        "age": 25,
+                    }
+                ))
+            }),
         );
+        let server = TestServer::new(app).unwrap();
 
-        TestServer::new(app)
-            .unwrap()
-            .get(&"/status")
+        server
+            .get(&"/json")
             .await
-            .assert_status_in_range(..299);
+            .assert_json_from_file("files/example.json");
     }
 
     #[tokio::test]
     #[should_panic]
-    async fn it_should_be_false_when_outside_to_range() {
+    async fn it_should_panic_when_content_type_does_not_match() {
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct ExampleResponse {
+            name: String,
+            age: u32,
+        }
+
         let app = Router::new().route(
-            &"/status",
-            get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
+            &"/form",
+            get(|| async {
+                Form(ExampleResponse {
+                    name: "Joe".to_string(),
+                    age: 20,
+                })
+            }),
         );
+        let server = TestServer::new(app).unwrap();
 
-        TestServer::new(app)
-            .unwrap()
-            .get(&"/status")
+        server
+            .get(&"/form")
             .await
-            .assert_status_in_range(..299);
+            .assert_json_from_file("files/example.json");
     }
+}
 
-    #[tokio::test]
-    async fn it_should_be_true_when_within_to_inclusive_range() {
-        let app = Router::new().route(
-            &"/status",
-            get(|| async { StatusCode::NON_AUTHORITATIVE_INFORMATION }),
-        );
+#[cfg(feature = "yaml")]
+#[cfg(test)]
+mod test_assert_yaml {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Form;
+    use axum::Router;
+    use axum_yaml::Yaml;
+    use serde::Deserialize;
+    use serde::Serialize;
 
-        TestServer::new(app)
-            .unwrap()
-            .get(&"/status")
-            .await
-            .assert_status_in_range(..=299);
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct ExampleResponse {
+        name: String,
+        age: u32,
     }
 
-    #[tokio::test]
-    #[should_panic]
-    async fn it_should_be_false_when_outside_to_inclusive_range() {
-        let app = Router::new().route(
-            &"/status",
-            get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
-        );
+    async fn route_get_form() -> Form<ExampleResponse> {
+        Form(ExampleResponse {
+            name: "Joe".to_string(),
+            age: 20,
+        })
+    }
 
-        TestServer::new(app)
-            .unwrap()
-            .get(&"/status")
-            .await
-            .assert_status_in_range(..=299);
+    async fn route_get_yaml() -> Yaml<ExampleResponse> {
+        Yaml(ExampleResponse {
+            name: "Joe".to_string(),
+            age: 20,
+        })
     }
 
     #[tokio::test]
-    async fn it_should_be_true_when_within_from_range() {
-        let app = Router::new().route(
-            &"/status",
-            get(|| async { StatusCode::NON_AUTHORITATIVE_INFORMATION }),
-        );
+    async fn it_should_match_yaml_returned() {
+        let app = Router::new().route(&"/yaml", get(route_get_yaml));
 
-        TestServer::new(app)
-            .unwrap()
-            .get(&"/status")
-            .await
-            .assert_status_in_range(200..);
+        let server = TestServer::new(app).unwrap();
+
+        server.get(&"/yaml").await.assert_yaml(&ExampleResponse {
+            name: "Joe".to_string(),
+            age: 20,
+        });
     }
 
     #[tokio::test]
     #[should_panic]
-    async fn it_should_be_false_when_outside_from_range() {
-        let app = Router::new().route(
-            &"/status",
-            get(|| async { StatusCode::NON_AUTHORITATIVE_INFORMATION }),
-        );
+    async fn it_should_panic_if_response_is_different() {
+        let app = Router::new().route(&"/yaml", get(route_get_yaml));
 
-        TestServer::new(app)
-            .unwrap()
-            .get(&"/status")
-            .await
-            .assert_status_in_range(500..);
+        let server = TestServer::new(app).unwrap();
+
+        server.get(&"/yaml").await.assert_yaml(&ExampleResponse {
+            name: "Julia".to_string(),
+            age: 25,
+        });
     }
 
     #[tokio::test]
-    async fn it_should_be_true_for_rull_range() {
-        let app = Router::new().route(
-            &"/status",
-            get(|| async { StatusCode::NON_AUTHORITATIVE_INFORMATION }),
-        );
+    #[should_panic]
+    async fn it_should_panic_if_response_is_form() {
+        let app = Router::new().route(&"/form", get(route_get_form));
 
-        TestServer::new(app)
-            .unwrap()
-            .get(&"/status")
-            .await
-            .assert_status_in_range::<RangeFull, StatusCode>(..);
+        let server = TestServer::new(app).unwrap();
+
+        server.get(&"/form").await.assert_yaml(&ExampleResponse {
+            name: "Joe".to_string(),
+            age: 20,
+        });
     }
 }
 
+#[cfg(feature = "yaml")]
 #[cfg(test)]
-mod test_assert_status_not_in_range {
+mod test_assert_yaml_from_file {
     use crate::TestServer;
     use axum::routing::get;
     use axum::routing::Router;
-    use http::StatusCode;
-    use std::ops::RangeFull;
+    use axum::Form;
+    use axum_yaml::Yaml;
+    use serde::Deserialize;
+    use serde::Serialize;
+    use serde_json::json;
 
     #[tokio::test]
-    #[should_panic]
-    async fn it_should_be_false_when_within_int_range() {
+    async fn it_should_match_yaml_from_file() {
         let app = Router::new().route(
-            &"/status",
-            get(|| async { StatusCode::NON_AUTHORITATIVE_INFORMATION }),
+            &"/yaml",
+            get(|| async {
+                Yaml(json!(
+                    {
+                        "name": "Joe",
+                        "age": 20,
+                    }
+                ))
+            }),
         );
+        let server = TestServer::new(app).unwrap();
 
-        TestServer::new(app)
-            .unwrap()
-            .get(&"/status")
+        server
+            .get(&"/yaml")
             .await
-            .assert_status_not_in_range(200..299);
+            .assert_yaml_from_file("files/example.yaml");
     }
 
     #[tokio::test]
     #[should_panic]
-    async fn it_should_be_false_when_within_status_code_range() {
+    async fn it_should_panic_when_not_match_the_file() {
         let app = Router::new().route(
-            &"/status",
-            get(|| async { StatusCode::NON_AUTHORITATIVE_INFORMATION }),
+            &"/yaml",
+            get(|| async {
+                Yaml(json!(
+                    {
+                        "name": "Julia",
+                        "age": 25,
+                    }
+                ))
+            }),
         );
+        let server = TestServer::new(app).unwrap();
 
-        TestServer::new(app)
-            .unwrap()
-            .get(&"/status")
+        server
+            .get(&"/yaml")
             .await
-            .assert_status_not_in_range(StatusCode::OK..StatusCode::IM_USED);
+            .assert_yaml_from_file("files/example.yaml");
     }
 
     #[tokio::test]
-    async fn it_should_be_true_when_outside_int_range() {
+    #[should_panic]
+    async fn it_should_panic_when_content_type_does_not_match() {
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct ExampleResponse {
+            name: String,
+            age: u32,
+        }
+
         let app = Router::new().route(
-            &"/status",
-            get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
+            &"/form",
+            get(|| async {
+                Form(ExampleResponse {
+                    name: "Joe".to_string(),
+                    age: 20,
+                })
+            }),
         );
+        let server = TestServer::new(app).unwrap();
 
-        TestServer::new(app)
-            .unwrap()
-            .get(&"/status")
+        server
+            .get(&"/form")
             .await
-            .assert_status_not_in_range(200..299);
+            .assert_yaml_from_file("files/example.yaml");
+    }
+}
+
+#[cfg(test)]
+mod test_assert_form {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Form;
+    use axum::Json;
+    use axum::Router;
+    use serde::Deserialize;
+    use serde::Serialize;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct ExampleResponse {
+        name: String,
+        age: u32,
+    }
+
+    async fn route_get_form() -> Form<ExampleResponse> {
+        Form(ExampleResponse {
+            name: "Joe".to_string(),
+            age: 20,
+        })
     }
 
-    #[tokio::test]
-    async fn it_should_be_true_when_outside_status_code_range() {
-        let app = Router::new().route(
-            &"/status",
-            get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
-        );
-
-        TestServer::new(app)
-            .unwrap()
-            .get(&"/status")
-            .await
-            .assert_status_not_in_range(StatusCode::OK..StatusCode::IM_USED);
+    async fn route_get_json() -> Json<ExampleResponse> {
+        Json(ExampleResponse {
+            name: "Joe".to_string(),
+            age: 20,
+        })
     }
 
     #[tokio::test]
-    #[should_panic]
-    async fn it_should_be_false_when_within_inclusive_range() {
-        let app = Router::new().route(
-            &"/status",
-            get(|| async { StatusCode::NON_AUTHORITATIVE_INFORMATION }),
-        );
+    async fn it_should_match_form_returned() {
+        let app = Router::new().route(&"/form", get(route_get_form));
 
-        TestServer::new(app)
-            .unwrap()
-            .get(&"/status")
-            .await
-            .assert_status_not_in_range(200..=299);
+        let server = TestServer::new(app).unwrap();
+
+        server.get(&"/form").await.assert_form(&ExampleResponse {
+            name: "Joe".to_string(),
+            age: 20,
+        });
     }
 
     #[tokio::test]
-    async fn it_should_be_true_when_outside_inclusive_range() {
-        let app = Router::new().route(
-            &"/status",
-            get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
-        );
+    #[should_panic]
+    async fn it_should_panic_if_response_is_different() {
+        let app = Router::new().route(&"/form", get(route_get_form));
 
-        TestServer::new(app)
-            .unwrap()
-            .get(&"/status")
-            .await
-            .assert_status_not_in_range(200..=299);
+        let server = TestServer::new(app).unwrap();
+
+        server.get(&"/form").await.assert_form(&ExampleResponse {
+            name: "Julia".to_string(),
+            age: 25,
+        });
     }
 
     #[tokio::test]
     #[should_panic]
-    async fn it_should_be_false_when_within_to_range() {
-        let app = Router::new().route(
-            &"/status",
-            get(|| async { StatusCode::NON_AUTHORITATIVE_INFORMATION }),
-        );
+    async fn it_should_panic_if_response_is_json() {
+        let app = Router::new().route(&"/json", get(route_get_json));
 
-        TestServer::new(app)
-            .unwrap()
-            .get(&"/status")
-            .await
-            .assert_status_not_in_range(..299);
+        let server = TestServer::new(app).unwrap();
+
+        server.get(&"/json").await.assert_form(&ExampleResponse {
+            name: "Joe".to_string(),
+            age: 20,
+        });
     }
+}
+
+#[cfg(test)]
+mod test_text {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
 
     #[tokio::test]
-    async fn it_should_be_true_when_outside_to_range() {
-        let app = Router::new().route(
-            &"/status",
-            get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
-        );
+    async fn it_should_deserialize_into_text() {
+        async fn route_get_text() -> String {
+            "hello!".to_string()
+        }
 
-        TestServer::new(app)
-            .unwrap()
-            .get(&"/status")
-            .await
-            .assert_status_not_in_range(..299);
-    }
+        let app = Router::new().route(&"/text", get(route_get_text));
 
-    #[tokio::test]
-    #[should_panic]
-    async fn it_should_be_false_when_within_to_inclusive_range() {
-        let app = Router::new().route(
-            &"/status",
-            get(|| async { StatusCode::NON_AUTHORITATIVE_INFORMATION }),
-        );
+        let server = TestServer::new(app).unwrap();
 
-        TestServer::new(app)
-            .unwrap()
-            .get(&"/status")
-            .await
-            .assert_status_not_in_range(..=299);
+        let response = server.get(&"/text").await.text();
+
+        assert_eq!(response, "hello!");
     }
+}
 
-    #[tokio::test]
-    async fn it_should_be_true_when_outside_to_inclusive_range() {
-        let app = Router::new().route(
-            &"/status",
-            get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
-        );
+#[cfg(test)]
+mod test_text_with_encoding {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+    use http::header;
 
-        TestServer::new(app)
-            .unwrap()
-            .get(&"/status")
-            .await
-            .assert_status_not_in_range(..=299);
-    }
+    const LATIN_1_BYTES: [u8; 4] = [0x63, 0x61, 0x66, 0xE9];
+    const UTF_16_LE_BYTES: [u8; 8] = [0x63, 0x00, 0x61, 0x00, 0x66, 0x00, 0xE9, 0x00];
 
     #[tokio::test]
-    #[should_panic]
-    async fn it_should_be_false_when_within_from_range() {
-        let app = Router::new().route(
-            &"/status",
-            get(|| async { StatusCode::NON_AUTHORITATIVE_INFORMATION }),
-        );
+    async fn it_should_decode_using_charset_from_content_type() {
+        let app = Router::new()
+            .route(
+                "/latin-1",
+                get(|| async {
+                    (
+                        [(header::CONTENT_TYPE, "text/plain; charset=ISO-8859-1")],
+                        LATIN_1_BYTES.to_vec(),
+                    )
+                }),
+            )
+            .route(
+                "/utf-16",
+                get(|| async {
+                    (
+                        [(header::CONTENT_TYPE, "text/plain; charset=utf-16le")],
+                        UTF_16_LE_BYTES.to_vec(),
+                    )
+                }),
+            );
+        let server = TestServer::new(app).unwrap();
 
-        TestServer::new(app)
-            .unwrap()
-            .get(&"/status")
-            .await
-            .assert_status_not_in_range(200..);
+        server.get("/latin-1").await.assert_text("café");
+        server.get("/utf-16").await.assert_text("café");
     }
 
     #[tokio::test]
-    async fn it_should_be_true_when_outside_from_range() {
-        let app = Router::new().route(
-            &"/status",
-            get(|| async { StatusCode::NON_AUTHORITATIVE_INFORMATION }),
-        );
+    async fn it_should_decode_using_encoding_given() {
+        let app = Router::new().route("/latin-1", get(|| async { LATIN_1_BYTES.to_vec() }));
+        let server = TestServer::new(app).unwrap();
 
-        TestServer::new(app)
-            .unwrap()
-            .get(&"/status")
-            .await
-            .assert_status_not_in_range(500..);
+        let response = server.get("/latin-1").await;
+
+        assert_eq!(response.text(), "caf\u{FFFD}");
+        assert_eq!(response.text_with_encoding("iso-8859-1"), "café");
     }
 
     #[tokio::test]
     #[should_panic]
-    async fn it_should_be_false_for_rull_range() {
-        let app = Router::new().route(
-            &"/status",
-            get(|| async { StatusCode::NON_AUTHORITATIVE_INFORMATION }),
-        );
+    async fn it_should_panic_if_encoding_is_unknown() {
+        let app = Router::new().route("/text", get(|| async { "hello!" }));
+        let server = TestServer::new(app).unwrap();
 
-        TestServer::new(app)
-            .unwrap()
-            .get(&"/status")
-            .await
-            .assert_status_not_in_range::<RangeFull, StatusCode>(..);
+        let _ = server.get("/text").await.text_with_encoding("🦊");
     }
 }
 
 #[cfg(test)]
-mod test_into_bytes {
+mod test_assert_charset {
     use crate::TestServer;
     use axum::routing::get;
-    use axum::Json;
     use axum::Router;
-    use serde_json::json;
-    use serde_json::Value;
+    use http::header;
 
-    async fn route_get_json() -> Json<Value> {
-        Json(json!({
-            "message": "it works?"
-        }))
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .route(
+                "/html",
+                get(|| async { ([(header::CONTENT_TYPE, "text/html; charset=UTF-8")], "") }),
+            )
+            .route(
+                "/bytes",
+                get(|| async { ([(header::CONTENT_TYPE, "application/octet-stream")], "") }),
+            );
+
+        TestServer::new(app).unwrap()
     }
 
     #[tokio::test]
-    async fn it_should_deserialize_into_json() {
-        let app = Router::new().route(&"/json", get(route_get_json));
+    async fn it_should_match_charset_ignoring_case() {
+        let server = new_test_server();
 
-        let server = TestServer::new(app).unwrap();
+        server.get("/html").await.assert_charset("utf-8");
+    }
 
-        let bytes = server.get(&"/json").await.into_bytes();
-        let text = String::from_utf8_lossy(&bytes);
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_if_charset_differs() {
+        let server = new_test_server();
 
-        assert_eq!(text, r#"{"message":"it works?"}"#);
+        server.get("/html").await.assert_charset("iso-8859-1");
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_if_charset_missing() {
+        let server = new_test_server();
+
+        server.get("/bytes").await.assert_charset("utf-8");
     }
 }
 
+#[cfg(feature = "ws")]
 #[cfg(test)]
-mod test_content_type {
+mod test_into_websocket {
     use crate::TestServer;
+    use http::StatusCode;
+
+    use axum::extract::ws::WebSocket;
+    use axum::extract::WebSocketUpgrade;
+    use axum::response::Response;
     use axum::routing::get;
-    use axum::Json;
     use axum::Router;
-    use serde::Deserialize;
-    use serde::Serialize;
 
-    #[derive(Serialize, Deserialize, PartialEq, Debug)]
-    struct ExampleResponse {
-        name: String,
-        age: u32,
+    fn new_test_router() -> Router {
+        pub async fn route_get_websocket(ws: WebSocketUpgrade) -> Response {
+            async fn handle_ping_pong(mut socket: WebSocket) {
+                while let Some(_) = socket.recv().await {
+                    // do nothing
+                }
+            }
+
+            ws.on_upgrade(move |socket| handle_ping_pong(socket))
+        }
+
+        let app = Router::new().route(&"/ws", get(route_get_websocket));
+
+        app
     }
 
     #[tokio::test]
-    async fn it_should_retrieve_json_content_type_for_json() {
-        let app = Router::new().route(
-            &"/json",
-            get(|| async {
-                Json(ExampleResponse {
-                    name: "Joe".to_string(),
-                    age: 20,
-                })
-            }),
-        );
+    async fn it_should_upgrade_on_http_transport() {
+        let router = new_test_router();
+        let server = TestServer::builder()
+            .http_transport()
+            .build(router)
+            .unwrap();
 
-        let server = TestServer::new(app).unwrap();
+        let _ = server.get_websocket(&"/ws").await.into_websocket().await;
 
-        let content_type = server.get(&"/json").await.content_type();
-        assert_eq!(content_type, "application/json");
+        assert!(true);
     }
 
-    #[cfg(feature = "yaml")]
     #[tokio::test]
-    async fn it_should_retrieve_yaml_content_type_for_yaml() {
-        use axum_yaml::Yaml;
+    #[should_panic]
+    async fn it_should_fail_to_upgrade_on_mock_transport() {
+        let router = new_test_router();
+        let server = TestServer::builder()
+            .mock_transport()
+            .build(router)
+            .unwrap();
+
+        let _ = server.get_websocket(&"/ws").await.into_websocket().await;
+    }
 
+    #[tokio::test]
+    #[should_panic(
+        expected = "server rejected it with 401 (Unauthorized) and body 'login required'"
+    )]
+    async fn it_should_panic_with_the_rejection_when_upgrade_is_rejected() {
         let app = Router::new().route(
-            &"/yaml",
-            get(|| async {
-                Yaml(ExampleResponse {
-                    name: "Joe".to_string(),
-                    age: 20,
-                })
-            }),
+            "/ws",
+            get(|| async { (StatusCode::UNAUTHORIZED, "login required") }),
         );
+        let server = TestServer::builder().http_transport().build(app).unwrap();
 
-        let server = TestServer::new(app).unwrap();
-
-        let content_type = server.get(&"/yaml").await.content_type();
-        assert_eq!(content_type, "application/yaml");
+        let _ = server.get_websocket("/ws").await.into_websocket().await;
     }
 }
 
 #[cfg(test)]
-mod test_json {
+mod test_check_status_ok {
     use crate::TestServer;
     use axum::routing::get;
-    use axum::Json;
     use axum::Router;
-    use serde::Deserialize;
-    use serde::Serialize;
+    use http::StatusCode;
 
-    #[derive(Serialize, Deserialize, PartialEq, Debug)]
-    struct ExampleResponse {
-        name: String,
-        age: u32,
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .route("/ok", get(|| async { StatusCode::OK }))
+            .route("/not-found", get(|| async { StatusCode::NOT_FOUND }));
+
+        TestServer::new(app).unwrap()
     }
 
-    async fn route_get_json() -> Json<ExampleResponse> {
-        Json(ExampleResponse {
-            name: "Joe".to_string(),
-            age: 20,
-        })
+    #[tokio::test]
+    async fn it_should_return_ok_for_200() {
+        let server = new_test_server();
+
+        let result = server.get("/ok").await.check_status_ok();
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
-    async fn it_should_deserialize_into_json() {
-        let app = Router::new().route(&"/json", get(route_get_json));
+    async fn it_should_return_error_for_404() {
+        let server = new_test_server();
 
-        let server = TestServer::new(app).unwrap();
+        let error = server
+            .get("/not-found")
+            .await
+            .check_status_ok()
+            .unwrap_err();
 
-        let response = server.get(&"/json").await.json::<ExampleResponse>();
+        assert!(error
+            .message()
+            .starts_with("Expected status code to be 200 (OK), received 404 (Not Found)"));
+    }
 
-        assert_eq!(
-            response,
-            ExampleResponse {
-                name: "Joe".to_string(),
-                age: 20,
-            }
+    #[tokio::test]
+    async fn it_should_include_content_type_and_body_in_error() {
+        let app = Router::new().route(
+            "/missing",
+            get(|| async { (StatusCode::NOT_FOUND, "no such todo") }),
         );
+        let server = TestServer::new(app).unwrap();
+
+        let error = server.get("/missing").await.check_status_ok().unwrap_err();
+
+        assert!(error
+            .message()
+            .ends_with("with content type 'text/plain; charset=utf-8', and body 'no such todo'"));
     }
 }
 
-#[cfg(feature = "yaml")]
 #[cfg(test)]
-mod test_yaml {
+mod test_assert_status_ok_else_print_body {
     use crate::TestServer;
     use axum::routing::get;
     use axum::Router;
-    use axum_yaml::Yaml;
-    use serde::Deserialize;
-    use serde::Serialize;
+    use http::StatusCode;
 
-    #[derive(Serialize, Deserialize, PartialEq, Debug)]
-    struct ExampleResponse {
-        name: String,
-        age: u32,
-    }
+    fn new_test_server() -> TestServer {
+        let app = Router::new().route("/ok", get(|| async { "ok" })).route(
+            "/error",
+            get(|| async { (StatusCode::INTERNAL_SERVER_ERROR, "stack trace") }),
+        );
 
-    async fn route_get_yaml() -> Yaml<ExampleResponse> {
-        Yaml(ExampleResponse {
-            name: "Joe".to_string(),
-            age: 20,
-        })
+        TestServer::new(app).unwrap()
     }
 
     #[tokio::test]
-    async fn it_should_deserialize_into_yaml() {
-        let app = Router::new().route(&"/yaml", get(route_get_yaml));
+    async fn it_should_pass_for_200() {
+        let server = new_test_server();
 
-        let server = TestServer::new(app).unwrap();
+        server.get("/ok").await.assert_status_ok_else_print_body();
+    }
 
-        let response = server.get(&"/yaml").await.yaml::<ExampleResponse>();
+    #[tokio::test]
+    #[should_panic(expected = "Expected status code to be 200 (OK), received 500")]
+    async fn it_should_panic_for_500() {
+        let server = new_test_server();
 
-        assert_eq!(
-            response,
-            ExampleResponse {
-                name: "Joe".to_string(),
-                age: 20,
-            }
-        );
+        server
+            .get("/error")
+            .expect_failure()
+            .await
+            .assert_status_ok_else_print_body();
     }
 }
 
-#[cfg(feature = "msgpack")]
 #[cfg(test)]
-mod test_msgpack {
+mod test_check_json {
+    use crate::AssertionError;
+    use crate::TestResponse;
     use crate::TestServer;
     use axum::routing::get;
     use axum::Router;
-    use axum_msgpack::MsgPack;
-    use serde::Deserialize;
-    use serde::Serialize;
+    use serde_json::json;
+    use serde_json::Value;
 
-    #[derive(Serialize, Deserialize, PartialEq, Debug)]
-    struct ExampleResponse {
-        name: String,
-        age: u32,
+    fn check_is_joe(response: &TestResponse) -> Result<(), AssertionError> {
+        response.check_status_ok()?;
+        response.check_json_contains(&json!({ "name": "Joe" }))?;
+
+        Ok(())
     }
 
-    async fn route_get_msgpack() -> MsgPack<ExampleResponse> {
-        MsgPack(ExampleResponse {
-            name: "Joe".to_string(),
-            age: 20,
-        })
+    #[tokio::test]
+    async fn it_should_compose_checks_with_question_mark() {
+        let app = Router::new().route(
+            "/json",
+            get(|| async { axum::Json(json!({ "name": "Joe", "age": 20 })) }),
+        );
+        let server = TestServer::new(app).unwrap();
+
+        let response = server.get("/json").await;
+        check_is_joe(&response).unwrap();
     }
 
     #[tokio::test]
-    async fn it_should_deserialize_into_msgpack() {
-        let app = Router::new().route(&"/msgpack", get(route_get_msgpack));
+    async fn it_should_return_error_when_json_differs() {
+        let app = Router::new().route(
+            "/json",
+            get(|| async { axum::Json(json!({ "name": "Julia" })) }),
+        );
+        let server = TestServer::new(app).unwrap();
+
+        let response = server.get("/json").await;
+        let error = check_is_joe(&response).unwrap_err();
 
+        assert!(error
+            .message()
+            .starts_with("Expected Json to contain the value given"));
+    }
+
+    #[tokio::test]
+    async fn it_should_return_error_when_body_is_not_json() {
+        let app = Router::new().route("/text", get(|| async { "not json" }));
         let server = TestServer::new(app).unwrap();
 
-        let response = server.get(&"/msgpack").await.msgpack::<ExampleResponse>();
+        let error = server
+            .get("/text")
+            .await
+            .check_json(&Value::Null)
+            .unwrap_err();
 
-        assert_eq!(
-            response,
-            ExampleResponse {
-                name: "Joe".to_string(),
-                age: 20,
-            }
-        );
+        assert!(error.message().starts_with(
+            "Deserializing response from Json, for request GET http://localhost/text"
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_should_collect_multiple_failures() {
+        let app = Router::new().route("/text", get(|| async { "hello" }));
+        let server = TestServer::new(app).unwrap();
+
+        let response = server.get("/text").await;
+        let errors: Vec<AssertionError> = [
+            response.check_status_not_found(),
+            response.check_text("hello"),
+            response.check_text_contains("goodbye"),
+        ]
+        .into_iter()
+        .filter_map(Result::err)
+        .collect();
+
+        assert_eq!(errors.len(), 2);
     }
 }
 
 #[cfg(test)]
-mod test_form {
+mod test_assert_same_response {
     use crate::TestServer;
     use axum::routing::get;
-    use axum::Form;
     use axum::Router;
-    use serde::Deserialize;
-    use serde::Serialize;
+    use http::StatusCode;
 
-    #[derive(Serialize, Deserialize, PartialEq, Debug)]
-    struct ExampleResponse {
-        name: String,
-        age: u32,
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .route("/hello", get(|| async { "hello" }))
+            .route("/hello-again", get(|| async { "hello" }))
+            .route("/goodbye", get(|| async { "goodbye" }))
+            .route(
+                "/missing",
+                get(|| async { (StatusCode::NOT_FOUND, "hello") }),
+            );
+
+        TestServer::new(app).unwrap()
     }
 
-    async fn route_get_form() -> Form<ExampleResponse> {
-        Form(ExampleResponse {
-            name: "Joe".to_string(),
-            age: 20,
-        })
+    #[tokio::test]
+    async fn it_should_pass_for_same_status_and_body() {
+        let server = new_test_server();
+
+        let first = server.get("/hello").await;
+        let second = server.get("/hello-again").await;
+
+        second.assert_same_response(&first);
     }
 
     #[tokio::test]
-    async fn it_should_deserialize_into_form() {
-        let app = Router::new().route(&"/form", get(route_get_form));
+    async fn it_should_fail_for_different_body() {
+        let server = new_test_server();
 
-        let server = TestServer::new(app).unwrap();
+        let first = server.get("/hello").await;
+        let second = server.get("/goodbye").await;
 
-        let response = server.get(&"/form").await.form::<ExampleResponse>();
+        assert!(second.check_same_response(&first).is_err());
+    }
 
-        assert_eq!(
-            response,
-            ExampleResponse {
-                name: "Joe".to_string(),
-                age: 20,
-            }
-        );
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_for_different_status() {
+        let server = new_test_server();
+
+        let first = server.get("/hello").await;
+        let second = server.get("/missing").await;
+
+        second.assert_same_response(&first);
     }
 }
 
 #[cfg(test)]
-mod test_from {
+mod test_link_header {
     use crate::TestServer;
+    use axum::response::AppendHeaders;
     use axum::routing::get;
     use axum::Router;
-    use bytes::Bytes;
+    use http::header;
 
     #[tokio::test]
-    async fn it_should_turn_into_response_bytes() {
-        let app = Router::new().route(&"/text", get(|| async { "This is some example text" }));
+    async fn it_should_return_link_with_rel() {
+        let app = Router::new().route(
+            "/",
+            get(|| async {
+                (
+                    AppendHeaders([
+                        (header::LINK, r#"</page/1>; rel="prev""#),
+                        (
+                            header::LINK,
+                            r#"</page/3>; rel="next", </page/9>; rel="last""#,
+                        ),
+                    ]),
+                    "",
+                )
+            }),
+        );
         let server = TestServer::new(app).unwrap();
 
-        let response = server.get(&"/text").await;
-        let bytes: Bytes = response.into();
-        let text = String::from_utf8_lossy(&bytes);
-        assert_eq!(text, "This is some example text");
+        let response = server.get("/").await;
+
+        assert_eq!(response.link_header("prev"), Some("/page/1".to_string()));
+        assert_eq!(response.link_header("next"), Some("/page/3".to_string()));
+        assert_eq!(response.link_header("LAST"), Some("/page/9".to_string()));
+        assert_eq!(response.link_header("first"), None);
     }
 }
 
 #[cfg(test)]
-mod test_assert_text {
+mod test_assert_preload {
     use crate::TestServer;
     use axum::routing::get;
     use axum::Router;
+    use http::header;
 
     fn new_test_server() -> TestServer {
-        async fn route_get_text() -> &'static str {
-            "This is some example text"
-        }
+        let app = Router::new().route(
+            "/",
+            get(|| async {
+                (
+                    [(
+                        header::LINK,
+                        r#"</style.css>; rel=preload; as=style, </app.js>; rel="preload modulepreload"; as="script", </next>; rel=prefetch"#,
+                    )],
+                    "",
+                )
+            }),
+        );
 
-        let app = Router::new().route(&"/text", get(route_get_text));
         TestServer::new(app).unwrap()
     }
 
     #[tokio::test]
-    async fn it_should_match_whole_text() {
+    async fn it_should_pass_for_preloads_with_matching_as_type() {
         let server = new_test_server();
 
-        server
-            .get(&"/text")
-            .await
-            .assert_text("This is some example text");
+        let response = server.get("/").await;
+        response.assert_preload("/style.css", "style");
+        response.assert_preload("/app.js", "SCRIPT");
+        response.assert_prefetch("/next");
+        assert_eq!(response.links().len(), 3);
     }
 
     #[tokio::test]
-    #[should_panic]
-    async fn it_should_not_match_partial_text() {
+    #[should_panic(expected = "Expected preload link '/style.css' to have as=script")]
+    async fn it_should_panic_when_as_type_differs() {
         let server = new_test_server();
 
-        server.get(&"/text").await.assert_text("some example");
+        server.get("/").await.assert_preload("/style.css", "script");
     }
 
     #[tokio::test]
-    #[should_panic]
-    async fn it_should_not_match_different_text() {
+    #[should_panic(expected = "Expected preload link '/next', none found")]
+    async fn it_should_panic_when_link_is_not_a_preload() {
         let server = new_test_server();
 
-        server.get(&"/text").await.assert_text("🦊");
+        server.get("/").await.assert_preload("/next", "document");
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_prefetch_when_missing() {
+        let server = new_test_server();
+
+        let response = server.get("/").await;
+        assert!(response.check_prefetch("/style.css").is_err());
     }
 }
 
 #[cfg(test)]
-mod test_assert_text_contains {
+mod test_follow_link {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+    use http::header;
+
+    #[tokio::test]
+    async fn it_should_follow_relative_link_header() {
+        let app = Router::new()
+            .route(
+                "/users/page/1",
+                get(|| async { ([(header::LINK, r#"<2?size=10>; rel="next""#)], "one") }),
+            )
+            .route("/users/page/2", get(|| async { "two" }));
+        let server = TestServer::new(app).unwrap();
+
+        let response = server.get("/users/page/1").await;
+
+        response
+            .follow_link(&server, "next")
+            .await
+            .assert_text("two");
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "Expected a link matching 'next', none found")]
+    async fn it_should_panic_when_there_is_no_link() {
+        let app = Router::new().route("/", get(|| async { "" }));
+        let server = TestServer::new(app).unwrap();
+
+        let response = server.get("/").await;
+        let _ = response.follow_link(&server, "next");
+    }
+
+    #[cfg(feature = "html")]
+    #[tokio::test]
+    async fn it_should_follow_html_links_by_rel_and_selector() {
+        use axum::response::Html;
+
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async {
+                    Html(r#"<a rel="next" href="/two">Two</a><a class="about" href="about">About</a>"#)
+                }),
+            )
+            .route("/two", get(|| async { "two" }))
+            .route("/about", get(|| async { "about" }));
+        let server = TestServer::new(app).unwrap();
+
+        let response = server.get("/").await;
+
+        response
+            .follow_link(&server, "next")
+            .await
+            .assert_text("two");
+        response
+            .follow_link(&server, "a.about")
+            .await
+            .assert_text("about");
+    }
+}
+
+#[cfg(test)]
+mod test_hal {
     use crate::TestServer;
     use axum::routing::get;
+    use axum::Json;
     use axum::Router;
+    use serde_json::json;
+    use serde_json::Value;
 
     fn new_test_server() -> TestServer {
-        async fn route_get_text() -> &'static str {
-            "This is some example text"
-        }
+        let app = Router::new()
+            .route(
+                "/users/page/1",
+                get(|| async {
+                    Json(json!({
+                        "_links": {
+                            "self": { "href": "/users/page/1" },
+                            "next": { "href": "2" },
+                        },
+                        "_embedded": {
+                            "users": [{ "name": "Joe" }, { "name": "Kate" }],
+                        },
+                    }))
+                }),
+            )
+            .route(
+                "/users/page/2",
+                get(|| async {
+                    Json(json!({
+                        "_links": {
+                            "self": { "href": "/users/page/2" },
+                        },
+                    }))
+                }),
+            );
 
-        let app = Router::new().route(&"/text", get(route_get_text));
         TestServer::new(app).unwrap()
     }
 
     #[tokio::test]
-    async fn it_should_match_whole_text() {
+    async fn it_should_assert_hal_links() {
         let server = new_test_server();
 
-        server
-            .get(&"/text")
-            .await
-            .assert_text_contains("This is some example text");
+        let response = server.get("/users/page/1").await;
+        response.assert_hal_link("self", "/users/page/1");
+        assert!(response.check_hal_link("self", "/users/page/2").is_err());
+        assert!(response.check_hal_link("prev", "/users/page/0").is_err());
+        assert_eq!(response.maybe_hal_link("prev"), None);
     }
 
     #[tokio::test]
-    async fn it_should_match_partial_text() {
+    async fn it_should_follow_relative_hal_links() {
         let server = new_test_server();
 
-        server
-            .get(&"/text")
+        let response = server.get("/users/page/1").await;
+
+        response
+            .follow_hal_link(&server, "next")
             .await
-            .assert_text_contains("some example");
+            .assert_hal_link("self", "/users/page/2");
     }
 
     #[tokio::test]
-    #[should_panic]
-    async fn it_should_not_match_different_text() {
+    #[should_panic(expected = "Expected HAL link 'next', none found")]
+    async fn it_should_panic_following_missing_hal_links() {
         let server = new_test_server();
 
-        server.get(&"/text").await.assert_text_contains("🦊");
+        let response = server.get("/users/page/2").await;
+        let _ = response.follow_hal_link(&server, "next");
     }
-}
-
-#[cfg(test)]
-mod test_assert_text_from_file {
-    use crate::TestServer;
-    use axum::routing::get;
-    use axum::routing::Router;
 
     #[tokio::test]
-    async fn it_should_match_from_file() {
-        let app = Router::new().route(&"/text", get(|| async { "hello!" }));
-        let server = TestServer::new(app).unwrap();
+    async fn it_should_extract_embedded_resources() {
+        let server = new_test_server();
 
-        server
-            .get(&"/text")
+        let users = server
+            .get("/users/page/1")
             .await
-            .assert_text_from_file("files/example.txt");
+            .hal_embedded::<Vec<Value>>("users");
+
+        assert_eq!(
+            users,
+            vec![json!({ "name": "Joe" }), json!({ "name": "Kate" })]
+        );
     }
 
     #[tokio::test]
-    #[should_panic]
-    async fn it_should_panic_when_not_match_the_file() {
-        let app = Router::new().route(&"/text", get(|| async { "🦊" }));
-        let server = TestServer::new(app).unwrap();
+    #[should_panic(expected = "Expected HAL embedded resource 'users', none found")]
+    async fn it_should_panic_when_embedded_resource_is_missing() {
+        let server = new_test_server();
 
-        server
-            .get(&"/text")
+        let _ = server
+            .get("/users/page/2")
             .await
-            .assert_text_from_file("files/example.txt");
+            .hal_embedded::<Vec<Value>>("users");
     }
 }
 
 #[cfg(test)]
-mod test_assert_json {
+mod test_odata {
     use crate::TestServer;
     use axum::routing::get;
-    use axum::Form;
     use axum::Json;
     use axum::Router;
-    use serde::Deserialize;
-    use serde::Serialize;
-
-    #[derive(Serialize, Deserialize, PartialEq, Debug)]
-    struct ExampleResponse {
-        name: String,
-        age: u32,
-    }
+    use serde_json::json;
+    use serde_json::Value;
 
-    async fn route_get_form() -> Form<ExampleResponse> {
-        Form(ExampleResponse {
-            name: "Joe".to_string(),
-            age: 20,
-        })
-    }
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .route(
+                "/people",
+                get(|| async {
+                    Json(json!({
+                        "@odata.context": "$metadata#People",
+                        "@odata.count": 25,
+                        "value": [{ "name": "Joe" }, { "name": "Kate" }],
+                    }))
+                }),
+            )
+            .route("/uncounted", get(|| async { Json(json!({ "value": [] })) }));
 
-    async fn route_get_json() -> Json<ExampleResponse> {
-        Json(ExampleResponse {
-            name: "Joe".to_string(),
-            age: 20,
-        })
+        TestServer::new(app).unwrap()
     }
 
     #[tokio::test]
-    async fn it_should_match_json_returned() {
-        let app = Router::new().route(&"/json", get(route_get_json));
-
-        let server = TestServer::new(app).unwrap();
+    async fn it_should_read_odata_count_and_value() {
+        let server = new_test_server();
 
-        server.get(&"/json").await.assert_json(&ExampleResponse {
-            name: "Joe".to_string(),
-            age: 20,
-        });
+        let response = server.get("/people").await;
+        assert_eq!(response.odata_count(), 25);
+        assert_eq!(
+            response.odata_value::<Vec<Value>>(),
+            vec![json!({ "name": "Joe" }), json!({ "name": "Kate" })]
+        );
     }
 
     #[tokio::test]
-    #[should_panic]
-    async fn it_should_panic_if_response_is_different() {
-        let app = Router::new().route(&"/json", get(route_get_json));
+    async fn it_should_assert_odata_count() {
+        let server = new_test_server();
 
-        let server = TestServer::new(app).unwrap();
+        let response = server.get("/people").await;
+        response.assert_odata_count(25);
+        assert!(response.check_odata_count(2).is_err());
 
-        server.get(&"/json").await.assert_json(&ExampleResponse {
-            name: "Julia".to_string(),
-            age: 25,
-        });
+        let response = server.get("/uncounted").await;
+        assert_eq!(response.maybe_odata_count(), None);
+        assert!(response.check_odata_count(0).is_err());
     }
 
     #[tokio::test]
-    #[should_panic]
-    async fn it_should_panic_if_response_is_form() {
-        let app = Router::new().route(&"/form", get(route_get_form));
-
-        let server = TestServer::new(app).unwrap();
+    #[should_panic(expected = "Expected OData response to have '@odata.count', none found")]
+    async fn it_should_panic_when_odata_count_is_missing() {
+        let server = new_test_server();
 
-        server.get(&"/form").await.assert_json(&ExampleResponse {
-            name: "Joe".to_string(),
-            age: 20,
-        });
+        let _ = server.get("/uncounted").await.odata_count();
     }
 }
 
 #[cfg(test)]
-mod test_assert_json_contains {
+mod test_problem_details {
     use crate::TestServer;
+    use axum::http::StatusCode;
     use axum::routing::get;
-    use axum::Form;
     use axum::Json;
     use axum::Router;
-    use serde::Deserialize;
-    use serde::Serialize;
     use serde_json::json;
-    use std::time::Instant;
 
-    #[derive(Serialize, Deserialize, PartialEq, Debug)]
-    struct ExampleResponse {
-        time: u64,
-        name: String,
-        age: u32,
-    }
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .route(
+                "/out-of-credit",
+                get(|| async {
+                    (
+                        StatusCode::FORBIDDEN,
+                        Json(json!({
+                            "type": "https://example.com/errors/out-of-credit",
+                            "title": "You do not have enough credit.",
+                            "status": 403,
+                            "balance": 30,
+                        })),
+                    )
+                }),
+            )
+            .route(
+                "/wrong-status",
+                get(|| async { (StatusCode::NOT_FOUND, Json(json!({ "status": 500 }))) }),
+            )
+            .route("/text", get(|| async { "not a problem" }));
 
-    async fn route_get_form() -> Form<ExampleResponse> {
-        Form(ExampleResponse {
-            time: Instant::now().elapsed().as_millis() as u64,
-            name: "Joe".to_string(),
-            age: 20,
-        })
+        TestServer::new(app).unwrap()
     }
 
-    async fn route_get_json() -> Json<ExampleResponse> {
-        Json(ExampleResponse {
-            time: Instant::now().elapsed().as_millis() as u64,
-            name: "Joe".to_string(),
-            age: 20,
-        })
+    #[tokio::test]
+    async fn it_should_read_problem_details() {
+        let server = new_test_server();
+
+        let problem = server
+            .get("/out-of-credit")
+            .expect_failure()
+            .await
+            .problem_details();
+
+        assert_eq!(problem.status, Some(403));
+        assert_eq!(problem.extensions["balance"], 30);
     }
 
     #[tokio::test]
-    async fn it_should_match_subset_of_json_returned() {
-        let app = Router::new().route(&"/json", get(route_get_json));
-        let server = TestServer::new(app).unwrap();
+    async fn it_should_pass_problem_assertions() {
+        let server = new_test_server();
 
-        server.get(&"/json").await.assert_json_contains(&json!({
-            "name": "Joe",
-            "age": 20,
-        }));
+        let response = server.get("/out-of-credit").expect_failure().await;
+        response.assert_problem_type("https://example.com/errors/out-of-credit");
+        response.assert_problem_title("You do not have enough credit.");
+        response.assert_problem_status_matches();
     }
 
     #[tokio::test]
-    #[should_panic]
-    async fn it_should_panic_if_response_is_different() {
-        let app = Router::new().route(&"/json", get(route_get_json));
-        let server = TestServer::new(app).unwrap();
+    async fn it_should_default_problem_type_to_about_blank() {
+        let server = new_test_server();
 
         server
-            .get(&"/json")
+            .get("/wrong-status")
+            .expect_failure()
             .await
-            .assert_json_contains(&ExampleResponse {
-                time: 1234,
-                name: "Julia".to_string(),
-                age: 25,
-            });
+            .assert_problem_type("about:blank");
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_when_problem_status_differs() {
+        let server = new_test_server();
+
+        let response = server.get("/wrong-status").expect_failure().await;
+        assert!(response.check_problem_status_matches().is_err());
+        assert!(response.check_problem_title("Not Found").is_err());
     }
 
     #[tokio::test]
     #[should_panic]
-    async fn it_should_panic_if_response_is_form() {
-        let app = Router::new().route(&"/form", get(route_get_form));
-        let server = TestServer::new(app).unwrap();
+    async fn it_should_panic_when_body_is_not_json() {
+        let server = new_test_server();
 
-        server.get(&"/form").await.assert_json_contains(&json!({
-            "name": "Joe",
-            "age": 20,
-        }));
+        let _ = server.get("/text").await.problem_details();
     }
 }
 
 #[cfg(test)]
-mod test_assert_json_from_file {
+mod test_probe {
+    use crate::QueryCount;
+    use crate::QueryCounter;
     use crate::TestServer;
+    use axum::extract::Path;
     use axum::routing::get;
-    use axum::routing::Router;
-    use axum::Form;
-    use axum::Json;
-    use serde::Deserialize;
-    use serde::Serialize;
-    use serde_json::json;
+    use axum::Extension;
+    use axum::Router;
+
+    async fn get_users(Extension(queries): Extension<QueryCounter>, Path(count): Path<usize>) {
+        for _ in 0..count {
+            queries.increment();
+        }
+    }
+
+    fn new_test_server() -> TestServer {
+        let queries = QueryCounter::new();
+        let app = Router::new()
+            .route("/users/:count", get(get_users))
+            .layer(Extension(queries.clone()));
+
+        TestServer::builder().add_probe(queries).build(app).unwrap()
+    }
 
     #[tokio::test]
-    async fn it_should_match_json_from_file() {
-        let app = Router::new().route(
-            &"/json",
-            get(|| async {
-                Json(json!(
-                    {
-                        "name": "Joe",
-                        "age": 20,
-                    }
-                ))
-            }),
-        );
-        let server = TestServer::new(app).unwrap();
+    async fn it_should_return_reading_for_each_request() {
+        let server = new_test_server();
 
-        server
-            .get(&"/json")
-            .await
-            .assert_json_from_file("files/example.json");
+        let response = server.get("/users/3").await;
+        response.probe::<QueryCount>().assert_query_count(3);
+
+        let response = server.get("/users/1").await;
+        response.probe::<QueryCount>().assert_query_count_at_most(1);
     }
 
     #[tokio::test]
-    #[should_panic]
-    async fn it_should_panic_when_not_match_the_file() {
-        let app = Router::new().route(
-            &"/json",
-            get(|| async {
-                Json(json!(
-                    {
-                        "name": "Julia",
-                        "age": 25,
-                    }
-                ))
-            }),
-        );
-        let server = TestServer::new(app).unwrap();
+    async fn it_should_return_none_without_a_probe() {
+        let server = TestServer::new(Router::new()).unwrap();
 
-        server
-            .get(&"/json")
-            .await
-            .assert_json_from_file("files/example.json");
+        let response = server.get("/users/3").expect_failure().await;
+        assert_eq!(response.maybe_probe::<QueryCount>(), None);
     }
 
     #[tokio::test]
     #[should_panic]
-    async fn it_should_panic_when_content_type_does_not_match() {
-        #[derive(Serialize, Deserialize, PartialEq, Debug)]
-        struct ExampleResponse {
-            name: String,
-            age: u32,
-        }
-
-        let app = Router::new().route(
-            &"/form",
-            get(|| async {
-                Form(ExampleResponse {
-                    name: "Joe".to_string(),
-                    age: 20,
-                })
-            }),
-        );
-        let server = TestServer::new(app).unwrap();
+    async fn it_should_panic_when_over_the_limit() {
+        let server = new_test_server();
 
-        server
-            .get(&"/form")
-            .await
-            .assert_json_from_file("files/example.json");
+        let response = server.get("/users/6").await;
+        response.probe::<QueryCount>().assert_query_count_at_most(5);
     }
 }
 
-#[cfg(feature = "yaml")]
 #[cfg(test)]
-mod test_assert_yaml {
+mod test_data_mask {
     use crate::TestServer;
     use axum::routing::get;
-    use axum::Form;
+    use axum::routing::post;
+    use axum::Json;
     use axum::Router;
-    use axum_yaml::Yaml;
-    use serde::Deserialize;
-    use serde::Serialize;
+    use http::StatusCode;
+    use serde_json::json;
+    use serde_json::Value;
+    use std::panic::catch_unwind;
+    use std::panic::AssertUnwindSafe;
 
-    #[derive(Serialize, Deserialize, PartialEq, Debug)]
-    struct ExampleResponse {
-        name: String,
-        age: u32,
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .route(
+                "/login",
+                post(|| async {
+                    (
+                        StatusCode::BAD_REQUEST,
+                        Json(json!({ "user": "joe", "password": "hunter2" })),
+                    )
+                }),
+            )
+            .route("/key", get(|| async { [("x-api-key", "my-secret-key")] }));
+
+        TestServer::builder()
+            .mask_header("x-api-key")
+            .mask_json_path("$.password")
+            .build(app)
+            .unwrap()
     }
 
-    async fn route_get_form() -> Form<ExampleResponse> {
-        Form(ExampleResponse {
-            name: "Joe".to_string(),
-            age: 20,
-        })
+    #[tokio::test]
+    async fn it_should_mask_headers_and_json_in_messages() {
+        let server = new_test_server();
+
+        let response = server
+            .post("/login")
+            .add_header("x-api-key", "my-secret-key")
+            .authorization_bearer("my-secret-token")
+            .expect_failure()
+            .await;
+        let message = response.check_status_ok().unwrap_err().to_string();
+
+        assert!(message.contains("x-api-key: ***"));
+        assert!(message.contains("authorization: ***"));
+        assert!(message.contains(r#""password": "***""#));
+        assert!(message.contains(r#""user": "joe""#));
+        assert!(!message.contains("my-secret"));
+        assert!(!message.contains("hunter2"));
+    }
+
+    #[tokio::test]
+    async fn it_should_mask_json_in_failed_assert_json() {
+        let server = new_test_server();
+
+        let response = server.post("/login").expect_failure().await;
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            response.assert_json(&json!({ "user": "bob", "password": "hunter3" }));
+        }));
+        let panic = result.unwrap_err();
+        let message = panic.downcast_ref::<String>().unwrap();
+
+        assert!(message.contains("Expected Json to match"));
+        assert!(message.contains("***"));
+        assert!(!message.contains("hunter2"));
+        assert!(!message.contains("hunter3"));
     }
 
-    async fn route_get_yaml() -> Yaml<ExampleResponse> {
-        Yaml(ExampleResponse {
-            name: "Joe".to_string(),
-            age: 20,
-        })
+    #[tokio::test]
+    async fn it_should_not_show_values_when_only_masked_json_differs() {
+        let server = new_test_server();
+
+        let response = server.post("/login").expect_failure().await;
+        let message = response
+            .check_json(&json!({ "user": "joe", "password": "hunter3" }))
+            .unwrap_err()
+            .to_string();
+
+        assert!(message.contains("the differences are within masked data"));
+        assert!(!message.contains("hunter2"));
+        assert!(!message.contains("hunter3"));
     }
 
     #[tokio::test]
-    async fn it_should_match_yaml_returned() {
-        let app = Router::new().route(&"/yaml", get(route_get_yaml));
+    async fn it_should_mask_json_in_failed_text_and_json_contains_checks() {
+        let server = new_test_server();
 
-        let server = TestServer::new(app).unwrap();
+        let response = server.post("/login").expect_failure().await;
+        let text_message = response.check_text("blah").unwrap_err().to_string();
+        let contains_message = response
+            .check_json_contains(&json!({ "user": "bob" }))
+            .unwrap_err()
+            .to_string();
 
-        server.get(&"/yaml").await.assert_yaml(&ExampleResponse {
-            name: "Joe".to_string(),
-            age: 20,
-        });
+        assert!(text_message.contains("***"));
+        assert!(!text_message.contains("hunter2"));
+        assert!(contains_message.contains("bob"));
+        assert!(!contains_message.contains("hunter2"));
     }
 
     #[tokio::test]
-    #[should_panic]
-    async fn it_should_panic_if_response_is_different() {
-        let app = Router::new().route(&"/yaml", get(route_get_yaml));
+    async fn it_should_mask_values_in_failed_header_checks() {
+        let server = new_test_server();
 
-        let server = TestServer::new(app).unwrap();
+        let response = server.get("/key").await;
+        let header_message = response
+            .check_header("x-api-key", "wrong-key")
+            .unwrap_err()
+            .to_string();
+        let contains_message = response
+            .check_header_contains_value("x-api-key", "wrong-key")
+            .unwrap_err()
+            .to_string();
 
-        server.get(&"/yaml").await.assert_yaml(&ExampleResponse {
-            name: "Julia".to_string(),
-            age: 25,
-        });
+        assert!(!header_message.contains("my-secret-key"));
+        assert!(!header_message.contains("wrong-key"));
+        assert!(!contains_message.contains("my-secret-key"));
+        response.assert_header("x-api-key", "my-secret-key");
     }
 
     #[tokio::test]
-    #[should_panic]
-    async fn it_should_panic_if_response_is_form() {
-        let app = Router::new().route(&"/form", get(route_get_form));
+    async fn it_should_leave_the_body_unmasked_for_reading() {
+        let server = new_test_server();
 
-        let server = TestServer::new(app).unwrap();
+        let response = server.post("/login").expect_failure().await;
+        let body = response.json::<Value>();
 
-        server.get(&"/form").await.assert_yaml(&ExampleResponse {
-            name: "Joe".to_string(),
-            age: 20,
-        });
+        assert_eq!(body["password"], "hunter2");
+    }
+
+    #[test]
+    fn it_should_fail_to_build_with_invalid_json_path() {
+        let result = TestServer::builder()
+            .mask_json_path("password")
+            .build(Router::new());
+
+        assert!(result.is_err());
     }
 }
 
-#[cfg(feature = "yaml")]
+#[cfg(feature = "jsonapi")]
 #[cfg(test)]
-mod test_assert_yaml_from_file {
+mod test_jsonapi {
     use crate::TestServer;
     use axum::routing::get;
-    use axum::routing::Router;
-    use axum::Form;
-    use axum_yaml::Yaml;
-    use serde::Deserialize;
-    use serde::Serialize;
+    use axum::Json;
+    use axum::Router;
     use serde_json::json;
+    use serde_json::Value;
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .route(
+                "/articles",
+                get(|| async {
+                    Json(json!({
+                        "data": [{
+                            "type": "articles",
+                            "id": "1",
+                            "attributes": { "title": "JSON:API paints my bikeshed!" },
+                            "relationships": {
+                                "author": { "data": { "type": "people", "id": "9" } },
+                            },
+                        }],
+                        "included": [
+                            { "type": "people", "id": "9" },
+                            { "type": "comments", "id": "5" },
+                            { "type": "comments", "id": "12" },
+                        ],
+                        "links": {
+                            "self": "/articles?page=1",
+                            "next": { "href": "/articles?page=2" },
+                            "last": "/articles?page=3",
+                        },
+                    }))
+                }),
+            )
+            .route(
+                "/articles/1",
+                get(|| async {
+                    Json(json!({
+                        "data": {
+                            "type": "articles",
+                            "id": "1",
+                            "relationships": {
+                                "author": { "data": { "type": "people", "id": "9" } },
+                            },
+                        },
+                        "links": { "self": "/articles/1" },
+                    }))
+                }),
+            );
+
+        TestServer::new(app).unwrap()
+    }
 
     #[tokio::test]
-    async fn it_should_match_yaml_from_file() {
-        let app = Router::new().route(
-            &"/yaml",
-            get(|| async {
-                Yaml(json!(
-                    {
-                        "name": "Joe",
-                        "age": 20,
-                    }
-                ))
-            }),
+    async fn it_should_deserialize_data() {
+        let server = new_test_server();
+
+        let articles = server.get("/articles").await.jsonapi_data::<Vec<Value>>();
+
+        assert_eq!(articles.len(), 1);
+        assert_eq!(
+            articles[0]["attributes"]["title"],
+            "JSON:API paints my bikeshed!"
         );
-        let server = TestServer::new(app).unwrap();
+    }
 
-        server
-            .get(&"/yaml")
-            .await
-            .assert_yaml_from_file("files/example.yaml");
+    #[tokio::test]
+    async fn it_should_assert_relationship_exists() {
+        let server = new_test_server();
+
+        let response = server.get("/articles/1").await;
+        response.assert_jsonapi_relationship_exists("author");
+        assert!(response
+            .check_jsonapi_relationship_exists("comments")
+            .is_err());
     }
 
     #[tokio::test]
-    #[should_panic]
-    async fn it_should_panic_when_not_match_the_file() {
-        let app = Router::new().route(
-            &"/yaml",
-            get(|| async {
-                Yaml(json!(
-                    {
-                        "name": "Julia",
-                        "age": 25,
-                    }
-                ))
-            }),
-        );
-        let server = TestServer::new(app).unwrap();
+    async fn it_should_assert_included_count() {
+        let server = new_test_server();
 
         server
-            .get(&"/yaml")
+            .get("/articles")
             .await
-            .assert_yaml_from_file("files/example.yaml");
+            .assert_jsonapi_included_count(3);
+        server
+            .get("/articles/1")
+            .await
+            .assert_jsonapi_included_count(0);
+
+        let response = server.get("/articles").await;
+        assert!(response.check_jsonapi_included_count(2).is_err());
     }
 
     #[tokio::test]
-    #[should_panic]
-    async fn it_should_panic_when_content_type_does_not_match() {
-        #[derive(Serialize, Deserialize, PartialEq, Debug)]
-        struct ExampleResponse {
-            name: String,
-            age: u32,
-        }
+    async fn it_should_read_pagination_links() {
+        let server = new_test_server();
 
-        let app = Router::new().route(
-            &"/form",
-            get(|| async {
-                Form(ExampleResponse {
-                    name: "Joe".to_string(),
-                    age: 20,
-                })
-            }),
-        );
-        let server = TestServer::new(app).unwrap();
+        let response = server.get("/articles").await;
+        let links = response.jsonapi_links();
+        assert_eq!(links.next.as_deref(), Some("/articles?page=2"));
+        assert_eq!(links.last.as_deref(), Some("/articles?page=3"));
+        assert_eq!(links.prev, None);
 
-        server
-            .get(&"/form")
-            .await
-            .assert_yaml_from_file("files/example.yaml");
+        response.assert_jsonapi_has_next_page();
+        assert!(response.check_jsonapi_is_last_page().is_err());
+    }
+
+    #[tokio::test]
+    async fn it_should_assert_last_page() {
+        let server = new_test_server();
+
+        let response = server.get("/articles/1").await;
+        response.assert_jsonapi_is_last_page();
+        assert!(response.check_jsonapi_has_next_page().is_err());
     }
 }
 
+#[cfg(feature = "rejections")]
 #[cfg(test)]
-mod test_assert_form {
+mod test_is_rejection {
     use crate::TestServer;
+    use axum::extract::rejection::FailedToDeserializeQueryString;
+    use axum::extract::rejection::JsonDataError;
+    use axum::extract::rejection::JsonRejection;
+    use axum::extract::rejection::JsonSyntaxError;
+    use axum::extract::rejection::MissingJsonContentType;
+    use axum::extract::rejection::PathRejection;
+    use axum::extract::rejection::QueryRejection;
+    use axum::extract::Path;
+    use axum::extract::Query;
     use axum::routing::get;
-    use axum::Form;
+    use axum::routing::post;
     use axum::Json;
     use axum::Router;
     use serde::Deserialize;
-    use serde::Serialize;
+    use serde_json::json;
 
-    #[derive(Serialize, Deserialize, PartialEq, Debug)]
-    struct ExampleResponse {
+    #[derive(Deserialize)]
+    struct User {
         name: String,
-        age: u32,
     }
 
-    async fn route_get_form() -> Form<ExampleResponse> {
-        Form(ExampleResponse {
-            name: "Joe".to_string(),
-            age: 20,
-        })
+    #[derive(Deserialize)]
+    struct Pagination {
+        page: u32,
     }
 
-    async fn route_get_json() -> Json<ExampleResponse> {
-        Json(ExampleResponse {
-            name: "Joe".to_string(),
-            age: 20,
-        })
+    async fn post_user(Json(user): Json<User>) -> String {
+        user.name
     }
 
-    #[tokio::test]
-    async fn it_should_match_form_returned() {
-        let app = Router::new().route(&"/form", get(route_get_form));
+    async fn get_user(Path(id): Path<u32>) -> String {
+        format!("user {id}")
+    }
 
-        let server = TestServer::new(app).unwrap();
+    async fn get_users(Query(pagination): Query<Pagination>) -> String {
+        format!("page {}", pagination.page)
+    }
 
-        server.get(&"/form").await.assert_form(&ExampleResponse {
-            name: "Joe".to_string(),
-            age: 20,
-        });
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .route("/users", post(post_user).get(get_users))
+            .route("/users/:id", get(get_user));
+
+        TestServer::new(app).unwrap()
     }
 
     #[tokio::test]
-    #[should_panic]
-    async fn it_should_panic_if_response_is_different() {
-        let app = Router::new().route(&"/form", get(route_get_form));
-
-        let server = TestServer::new(app).unwrap();
+    async fn it_should_assert_json_data_error() {
+        let server = new_test_server();
 
-        server.get(&"/form").await.assert_form(&ExampleResponse {
-            name: "Julia".to_string(),
-            age: 25,
-        });
+        let response = server.post("/users").json(&json!({ "age": 30 })).await;
+        response.assert_is_rejection::<JsonDataError>();
+        response.assert_is_rejection::<JsonRejection>();
+        assert!(response.check_is_rejection::<JsonSyntaxError>().is_err());
     }
 
     #[tokio::test]
-    #[should_panic]
-    async fn it_should_panic_if_response_is_json() {
-        let app = Router::new().route(&"/json", get(route_get_json));
-
-        let server = TestServer::new(app).unwrap();
+    async fn it_should_assert_json_syntax_error() {
+        let server = new_test_server();
 
-        server.get(&"/json").await.assert_form(&ExampleResponse {
-            name: "Joe".to_string(),
-            age: 20,
-        });
+        let response = server
+            .post("/users")
+            .bytes("{ not json".into())
+            .content_type("application/json")
+            .await;
+        response.assert_is_rejection::<JsonSyntaxError>();
+        assert!(response.check_is_rejection::<JsonDataError>().is_err());
     }
-}
 
-#[cfg(test)]
-mod test_text {
-    use crate::TestServer;
-    use axum::routing::get;
-    use axum::Router;
+    #[tokio::test]
+    async fn it_should_assert_missing_json_content_type() {
+        let server = new_test_server();
+
+        let response = server.post("/users").text(r#"{ "name": "Joe" }"#).await;
+        response.assert_is_rejection::<MissingJsonContentType>();
+        response.assert_is_rejection::<JsonRejection>();
+    }
 
     #[tokio::test]
-    async fn it_should_deserialize_into_text() {
-        async fn route_get_text() -> String {
-            "hello!".to_string()
-        }
+    async fn it_should_assert_path_and_query_rejections() {
+        let server = new_test_server();
 
-        let app = Router::new().route(&"/text", get(route_get_text));
+        let response = server.get("/users/abc").await;
+        response.assert_is_rejection::<PathRejection>();
 
-        let server = TestServer::new(app).unwrap();
+        let response = server.get("/users").add_query_param("page", "first").await;
+        response.assert_is_rejection::<FailedToDeserializeQueryString>();
+        response.assert_is_rejection::<QueryRejection>();
+    }
 
-        let response = server.get(&"/text").await.text();
+    #[tokio::test]
+    async fn it_should_fail_when_not_a_rejection() {
+        let server = new_test_server();
 
-        assert_eq!(response, "hello!");
+        let response = server.post("/users").json(&json!({ "name": "Joe" })).await;
+        assert!(response.check_is_rejection::<JsonRejection>().is_err());
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_a_different_rejection() {
+        let server = new_test_server();
+
+        let response = server.get("/users/abc").await;
+        response.assert_is_rejection::<JsonRejection>();
     }
 }
 
-#[cfg(feature = "ws")]
+#[cfg(feature = "multipart-echo")]
 #[cfg(test)]
-mod test_into_websocket {
+mod test_assert_multipart_part {
+    use crate::multipart::MultipartForm;
+    use crate::multipart::Part;
+    use crate::routes::multipart_echo;
     use crate::TestServer;
-
-    use axum::extract::ws::WebSocket;
-    use axum::extract::WebSocketUpgrade;
-    use axum::response::Response;
-    use axum::routing::get;
+    use axum::routing::post;
     use axum::Router;
 
-    fn new_test_router() -> Router {
-        pub async fn route_get_websocket(ws: WebSocketUpgrade) -> Response {
-            async fn handle_ping_pong(mut socket: WebSocket) {
-                while let Some(_) = socket.recv().await {
-                    // do nothing
-                }
-            }
+    fn new_test_server() -> TestServer {
+        let app = Router::new().route("/upload", post(multipart_echo));
+        TestServer::new(app).unwrap()
+    }
 
-            ws.on_upgrade(move |socket| handle_ping_pong(socket))
-        }
+    fn new_form() -> MultipartForm {
+        MultipartForm::new().add_text("title", "hello").add_part(
+            "file",
+            Part::bytes(b"a,b,c".as_slice())
+                .file_name("report.csv")
+                .mime_type("text/csv"),
+        )
+    }
 
-        let app = Router::new().route(&"/ws", get(route_get_websocket));
+    #[tokio::test]
+    async fn it_should_give_the_part_named() {
+        let server = new_test_server();
 
-        app
+        server
+            .post("/upload")
+            .multipart(new_form())
+            .await
+            .assert_multipart_part("file", |part| {
+                assert_eq!(part.filename.as_deref(), Some("report.csv"));
+                assert_eq!(part.content_type.as_deref(), Some("text/csv"));
+                part.assert_bytes(b"a,b,c");
+            });
     }
 
     #[tokio::test]
-    async fn it_should_upgrade_on_http_transport() {
-        let router = new_test_router();
-        let server = TestServer::builder()
-            .http_transport()
-            .build(router)
-            .unwrap();
-
-        let _ = server.get_websocket(&"/ws").await.into_websocket().await;
+    #[should_panic]
+    async fn it_should_panic_when_part_is_missing() {
+        let server = new_test_server();
 
-        assert!(true);
+        server
+            .post("/upload")
+            .multipart(new_form())
+            .await
+            .assert_multipart_part("avatar", |_| {});
     }
 
     #[tokio::test]
     #[should_panic]
-    async fn it_should_fail_to_upgrade_on_mock_transport() {
-        let router = new_test_router();
-        let server = TestServer::builder()
-            .mock_transport()
-            .build(router)
-            .unwrap();
+    async fn it_should_panic_when_the_assertion_given_fails() {
+        let server = new_test_server();
 
-        let _ = server.get_websocket(&"/ws").await.into_websocket().await;
+        server
+            .post("/upload")
+            .multipart(new_form())
+            .await
+            .assert_multipart_part("title", |part| part.assert_bytes(b"goodbye"));
+    }
+
+    #[tokio::test]
+    async fn it_should_error_when_response_is_not_from_multipart_echo() {
+        let app = Router::new().route("/upload", post(|| async { "ok" }));
+        let server = TestServer::new(app).unwrap();
+
+        let result = server
+            .post("/upload")
+            .await
+            .check_multipart_part("file", |_| {});
+        assert!(result.is_err());
     }
 }
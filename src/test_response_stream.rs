@@ -0,0 +1,572 @@
+use axum::body::Body;
+use bytes::Bytes;
+use bytes::BytesMut;
+use http::response::Parts;
+use http::HeaderMap;
+use http::HeaderName;
+use http::HeaderValue;
+use http::StatusCode;
+use http_body_util::BodyExt;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use tokio::sync::mpsc::channel;
+use tokio::sync::mpsc::Receiver;
+use tokio::sync::mpsc::Sender;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+use crate::internals::ExpectWith;
+use crate::internals::InFlightHandle;
+use crate::test_request::PendingResponse;
+
+/// The number of chunks held, waiting to be taken, when no receive buffer size is set.
+const DEFAULT_BUFFERED_CHUNKS: usize = 16;
+
+/// The chunk size assumed when working out how many chunks fit in the receive buffer size.
+const ESTIMATED_CHUNK_SIZE: usize = 8 * 1024;
+
+/// A response whose body is read as it arrives, instead of being buffered in full,
+/// returned from [`TestRequest::stream()`](crate::TestRequest::stream()).
+///
+/// This is for testing responses which never end, or are very large,
+/// such as server sent events. The body is read in the background,
+/// and can be paused with [`TestResponseStream::pause()`] to act as a slow consumer.
+/// Whilst paused nothing more is read, and so the server is left to handle the backpressure.
+///
+/// Chunks read, but not yet taken, are held in a bounded buffer.
+/// Once it is full, nothing more is read until chunks are taken,
+/// so a slow consumer also pushes back on the server.
+/// The buffer is sized from [`TestRequest::receive_buffer_size()`](crate::TestRequest::receive_buffer_size()),
+/// at roughly one chunk per 8 KiB, and holds 16 chunks when that is not set.
+///
+/// ```rust
+/// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+/// #
+/// use axum::Router;
+/// use axum::routing::get;
+/// use axum_test::TestServer;
+///
+/// let app = Router::new()
+///     .route(&"/events", get(|| async { "data: hello\n\n" }));
+/// let server = TestServer::new(app)?;
+///
+/// let mut stream = server.get(&"/events").stream().await;
+/// let chunk = stream.next_chunk().await;
+///
+/// assert_eq!(chunk.as_deref(), Some(b"data: hello\n\n".as_slice()));
+/// #
+/// # Ok(()) }
+/// ```
+#[derive(Debug)]
+pub struct TestResponseStream {
+    debug_request_format: String,
+    parts: Parts,
+    /// Held until the body is read in full, to build the `TestResponse` for validators and recording.
+    maybe_pending: Option<PendingResponse>,
+    has_taken_chunks: bool,
+    is_paused: watch::Sender<bool>,
+    chunks: Receiver<Result<Bytes, String>>,
+    received_bytes: Arc<AtomicUsize>,
+    reader: JoinHandle<()>,
+}
+
+impl TestResponseStream {
+    pub(crate) fn new(
+        mut pending: PendingResponse,
+        parts: Parts,
+        body: Body,
+        maybe_receive_buffer_size: Option<usize>,
+    ) -> Self {
+        let debug_request_format = pending.debug_request_format.clone();
+        let maybe_in_flight = pending.maybe_in_flight.take();
+        let buffered_chunks = maybe_receive_buffer_size
+            .map(|receive_buffer_size| receive_buffer_size.div_ceil(ESTIMATED_CHUNK_SIZE).max(1))
+            .unwrap_or(DEFAULT_BUFFERED_CHUNKS);
+        let (is_paused, is_paused_receiver) = watch::channel(false);
+        let (chunks_sender, chunks) = channel(buffered_chunks);
+        let received_bytes = Arc::new(AtomicUsize::new(0));
+        let reader = tokio::spawn(read_body(
+            body,
+            is_paused_receiver,
+            chunks_sender,
+            Arc::clone(&received_bytes),
+            maybe_in_flight,
+        ));
+
+        Self {
+            debug_request_format,
+            parts,
+            maybe_pending: Some(pending),
+            has_taken_chunks: false,
+            is_paused,
+            chunks,
+            received_bytes,
+            reader,
+        }
+    }
+
+    /// The status code of the response.
+    #[must_use]
+    pub fn status_code(&self) -> StatusCode {
+        self.parts.status
+    }
+
+    /// The headers of the response.
+    #[must_use]
+    pub fn headers(&self) -> &HeaderMap {
+        &self.parts.headers
+    }
+
+    /// Finds the header with the name given, if it was returned.
+    #[must_use]
+    pub fn maybe_header<N>(&self, name: N) -> Option<&HeaderValue>
+    where
+        N: TryInto<HeaderName>,
+        N::Error: ::std::fmt::Debug,
+    {
+        let header_name = name
+            .try_into()
+            .expect("Failed to convert header name to HeaderName");
+
+        self.parts.headers.get(header_name)
+    }
+
+    /// Stops reading the body, leaving what is left with the server.
+    ///
+    /// A chunk already being read when this is called is still received.
+    pub fn pause(&self) {
+        self.is_paused.send_replace(true);
+    }
+
+    /// Carries on reading the body, after a call to [`TestResponseStream::pause()`].
+    pub fn resume(&self) {
+        self.is_paused.send_replace(false);
+    }
+
+    /// Returns true if reading the body is paused.
+    #[must_use]
+    pub fn is_paused(&self) -> bool {
+        *self.is_paused.borrow()
+    }
+
+    /// The number of bytes of the body read so far,
+    /// including those not yet taken with [`TestResponseStream::next_chunk()`].
+    #[must_use]
+    pub fn received_bytes(&self) -> usize {
+        self.received_bytes.load(Ordering::SeqCst)
+    }
+
+    /// Waits for the next chunk of the body, returning `None` once the body has ended.
+    ///
+    /// This also returns `None` if the stream is paused with no chunks left to take,
+    /// as nothing more arrives until it is resumed.
+    pub async fn next_chunk(&mut self) -> Option<Bytes> {
+        if self.is_paused() && self.chunks.is_empty() {
+            return None;
+        }
+
+        match self.chunks.recv().await? {
+            Ok(chunk) => {
+                self.has_taken_chunks = true;
+                Some(chunk)
+            }
+            Err(error) => panic!(
+                "Failed to read response body, {error}, for request {}",
+                self.debug_request_format
+            ),
+        }
+    }
+
+    /// Resumes reading, and waits for the rest of the body.
+    /// Returning every chunk not yet taken, joined together.
+    ///
+    /// If no chunks were taken before, this holds the whole body.
+    /// So the response validators are run on it, and the exchange is recorded,
+    /// in the same way as for [`TestRequest::send()`](crate::TestRequest).
+    pub async fn read_to_end(mut self) -> Bytes {
+        let has_taken_chunks = self.has_taken_chunks;
+        self.resume();
+
+        let mut body = BytesMut::new();
+        while let Some(chunk) = self.next_chunk().await {
+            body.extend_from_slice(&chunk);
+        }
+        let body = body.freeze();
+
+        if !has_taken_chunks {
+            if let Some(pending) = self.maybe_pending.take() {
+                let debug_request_format = self.debug_request_format.clone();
+                pending
+                    .into_test_response(self.parts.clone(), body.clone())
+                    .expect_with(|| {
+                        format!("Failed to build response, for request {debug_request_format}")
+                    });
+            }
+        }
+
+        body
+    }
+}
+
+impl Drop for TestResponseStream {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+async fn read_body(
+    mut body: Body,
+    mut is_paused: watch::Receiver<bool>,
+    chunks: Sender<Result<Bytes, String>>,
+    received_bytes: Arc<AtomicUsize>,
+    _maybe_in_flight: Option<InFlightHandle>,
+) {
+    loop {
+        if is_paused.wait_for(|is_paused| !is_paused).await.is_err() {
+            return;
+        }
+
+        let chunk = match body.frame().await {
+            None => return,
+            Some(Err(error)) => Err(format!("{error:#}")),
+            Some(Ok(frame)) => match frame.into_data() {
+                Ok(data) => Ok(data),
+                // Trailers are skipped.
+                Err(_) => continue,
+            },
+        };
+
+        let is_error = chunk.is_err();
+        if let Ok(data) = &chunk {
+            received_bytes.fetch_add(data.len(), Ordering::SeqCst);
+        }
+        if chunks.send(chunk).await.is_err() || is_error {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_next_chunk {
+    use axum::body::Body;
+    use axum::routing::get;
+    use axum::Router;
+    use bytes::Bytes;
+    use futures_util::stream::iter;
+    use std::convert::Infallible;
+
+    use crate::TestServer;
+
+    fn new_app() -> Router {
+        Router::new().route(
+            "/stream",
+            get(|| async {
+                let chunks = ["first", "second", "third"]
+                    .map(|chunk| Ok::<_, Infallible>(Bytes::from_static(chunk.as_bytes())));
+
+                Body::from_stream(iter(chunks))
+            }),
+        )
+    }
+
+    #[tokio::test]
+    async fn it_should_return_chunks_in_order_with_mock_transport() {
+        let server = TestServer::builder()
+            .mock_transport()
+            .build(new_app())
+            .unwrap();
+
+        let mut stream = server.get("/stream").stream().await;
+
+        assert_eq!(
+            stream.next_chunk().await.as_deref(),
+            Some(b"first".as_slice())
+        );
+        assert_eq!(
+            stream.next_chunk().await.as_deref(),
+            Some(b"second".as_slice())
+        );
+        assert_eq!(
+            stream.next_chunk().await.as_deref(),
+            Some(b"third".as_slice())
+        );
+        assert_eq!(stream.next_chunk().await, None);
+    }
+
+    #[tokio::test]
+    async fn it_should_return_the_whole_body_with_http_transport() {
+        let server = TestServer::builder()
+            .http_transport()
+            .build(new_app())
+            .unwrap();
+
+        let stream = server.get("/stream").stream().await;
+
+        assert_eq!(stream.read_to_end().await, "firstsecondthird");
+    }
+}
+
+#[cfg(test)]
+mod test_status_code {
+    use axum::routing::get;
+    use axum::Router;
+    use http::StatusCode;
+
+    use crate::TestServer;
+
+    fn new_app() -> Router {
+        Router::new().route(
+            "/teapot",
+            get(|| async { (StatusCode::IM_A_TEAPOT, [("x-kind", "teapot")], "short") }),
+        )
+    }
+
+    #[tokio::test]
+    async fn it_should_return_the_status_code_and_headers() {
+        let server = TestServer::new(new_app()).unwrap();
+
+        let stream = server.get("/teapot").stream().await;
+
+        assert_eq!(stream.status_code(), StatusCode::IM_A_TEAPOT);
+        assert_eq!(stream.maybe_header("x-kind").unwrap(), "teapot");
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "Expect status code within 2xx range, received 418")]
+    async fn it_should_panic_when_expecting_success() {
+        let server = TestServer::new(new_app()).unwrap();
+
+        server.get("/teapot").expect_success().stream().await;
+    }
+}
+
+#[cfg(test)]
+mod test_read_to_end {
+    use anyhow::anyhow;
+    use axum::routing::get;
+    use axum::Router;
+
+    use crate::TestServer;
+
+    fn new_app() -> Router {
+        Router::new().route("/text", get(|| async { "hello!" }))
+    }
+
+    #[tokio::test]
+    async fn it_should_record_the_exchange() {
+        let server = TestServer::builder()
+            .record_exchanges()
+            .build(new_app())
+            .unwrap();
+
+        let body = server.get("/text").stream().await.read_to_end().await;
+
+        assert_eq!(body, "hello!");
+        assert_eq!(server.recorded_exchanges().len(), 1);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "Response validator failed, rejected")]
+    async fn it_should_run_response_validators() {
+        let server = TestServer::builder()
+            .add_response_validator(|_| Err(anyhow!("rejected")))
+            .build(new_app())
+            .unwrap();
+
+        server.get("/text").stream().await.read_to_end().await;
+    }
+
+    #[tokio::test]
+    async fn it_should_not_run_response_validators_after_chunks_are_taken() {
+        let server = TestServer::builder()
+            .add_response_validator(|_| Err(anyhow!("rejected")))
+            .build(new_app())
+            .unwrap();
+
+        let mut stream = server.get("/text").stream().await;
+        let chunk = stream.next_chunk().await;
+
+        assert_eq!(chunk.as_deref(), Some(b"hello!".as_slice()));
+        assert_eq!(stream.read_to_end().await, "");
+    }
+}
+
+#[cfg(test)]
+mod test_expect_content_type {
+    use axum::routing::get;
+    use axum::Router;
+
+    use crate::TestServer;
+
+    #[tokio::test]
+    #[should_panic(expected = "Expected content type 'application/json'")]
+    async fn it_should_panic_when_content_type_does_not_match() {
+        let app = Router::new().route("/text", get(|| async { "hello!" }));
+        let server = TestServer::new(app).unwrap();
+
+        server
+            .get("/text")
+            .expect_content_type("application/json")
+            .stream()
+            .await;
+    }
+}
+
+#[cfg(test)]
+mod test_pause {
+    use axum::body::Body;
+    use axum::routing::get;
+    use axum::Router;
+    use bytes::Bytes;
+    use futures_util::stream::unfold;
+    use std::convert::Infallible;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::mpsc::channel;
+    use tokio::time::sleep;
+
+    use crate::TestServer;
+
+    const CHUNK_SIZE: usize = 16 * 1024;
+    const CHUNK_COUNT: usize = 1024;
+
+    /// Streams the chunks from a bounded channel, like the channels feeding SSE endpoints,
+    /// counting each chunk once the channel accepts it.
+    fn new_app(produced: Arc<AtomicUsize>) -> Router {
+        Router::new().route(
+            "/stream",
+            get(move || async move {
+                let (sender, receiver) = channel::<Bytes>(1);
+                tokio::spawn(async move {
+                    for _ in 0..CHUNK_COUNT {
+                        if sender
+                            .send(Bytes::from(vec![b'a'; CHUNK_SIZE]))
+                            .await
+                            .is_err()
+                        {
+                            return;
+                        }
+                        produced.fetch_add(1, Ordering::SeqCst);
+                    }
+                });
+
+                Body::from_stream(unfold(receiver, |mut receiver| async move {
+                    let chunk = receiver.recv().await?;
+                    Some((Ok::<_, Infallible>(chunk), receiver))
+                }))
+            }),
+        )
+    }
+
+    /// Returns the number of chunks produced, once the server has stopped producing more.
+    async fn wait_for_producing_to_stop(produced: &AtomicUsize) -> usize {
+        let mut last_produced = produced.load(Ordering::SeqCst);
+        loop {
+            sleep(Duration::from_millis(100)).await;
+
+            let next_produced = produced.load(Ordering::SeqCst);
+            if next_produced == last_produced {
+                return next_produced;
+            }
+            last_produced = next_produced;
+        }
+    }
+
+    #[tokio::test]
+    async fn it_should_stop_the_server_producing_when_paused_with_mock_transport() {
+        let produced = Arc::new(AtomicUsize::new(0));
+        let server = TestServer::builder()
+            .mock_transport()
+            .build(new_app(produced.clone()))
+            .unwrap();
+
+        let stream = server.get("/stream").stream().await;
+        stream.pause();
+
+        let produced_whilst_paused = wait_for_producing_to_stop(&produced).await;
+        assert!(produced_whilst_paused < CHUNK_COUNT);
+        assert!(stream.received_bytes() < CHUNK_COUNT * CHUNK_SIZE);
+
+        let body = stream.read_to_end().await;
+        assert_eq!(body.len(), CHUNK_COUNT * CHUNK_SIZE);
+    }
+
+    #[tokio::test]
+    async fn it_should_stop_the_server_producing_when_paused_with_http_transport() {
+        let produced = Arc::new(AtomicUsize::new(0));
+        let server = TestServer::builder()
+            .http_transport()
+            .build(new_app(produced.clone()))
+            .unwrap();
+
+        let stream = server
+            .get("/stream")
+            .receive_buffer_size(4096)
+            .stream()
+            .await;
+        stream.pause();
+
+        let produced_whilst_paused = wait_for_producing_to_stop(&produced).await;
+        assert!(produced_whilst_paused < CHUNK_COUNT);
+        assert!(stream.received_bytes() < CHUNK_COUNT * CHUNK_SIZE);
+
+        let body = stream.read_to_end().await;
+        assert_eq!(body.len(), CHUNK_COUNT * CHUNK_SIZE);
+    }
+
+    #[tokio::test]
+    async fn it_should_carry_on_reading_when_resumed() {
+        let produced = Arc::new(AtomicUsize::new(0));
+        let server = TestServer::new(new_app(produced.clone())).unwrap();
+
+        let mut stream = server.get("/stream").stream().await;
+        stream.pause();
+        assert!(stream.is_paused());
+        wait_for_producing_to_stop(&produced).await;
+
+        stream.resume();
+        assert!(!stream.is_paused());
+
+        let chunk = stream.next_chunk().await.unwrap();
+        assert_eq!(chunk.len(), CHUNK_SIZE);
+    }
+
+    #[tokio::test]
+    async fn it_should_return_none_when_paused_with_no_chunks_left() {
+        let produced = Arc::new(AtomicUsize::new(0));
+        let server = TestServer::new(new_app(produced.clone())).unwrap();
+
+        let mut stream = server.get("/stream").stream().await;
+        stream.pause();
+        wait_for_producing_to_stop(&produced).await;
+
+        // A chunk being read when paused can still arrive after the first drain.
+        while stream.chunks.try_recv().is_ok() {}
+        sleep(Duration::from_millis(100)).await;
+        while stream.chunks.try_recv().is_ok() {}
+
+        assert_eq!(stream.next_chunk().await, None);
+
+        stream.resume();
+        let chunk = stream.next_chunk().await.unwrap();
+        assert_eq!(chunk.len(), CHUNK_SIZE);
+    }
+
+    #[tokio::test]
+    async fn it_should_stop_reading_when_the_buffer_is_full() {
+        let produced = Arc::new(AtomicUsize::new(0));
+        let server = TestServer::new(new_app(produced.clone())).unwrap();
+
+        let stream = server.get("/stream").stream().await;
+        wait_for_producing_to_stop(&produced).await;
+
+        assert!(!stream.is_paused());
+        assert!(stream.received_bytes() < CHUNK_COUNT * CHUNK_SIZE);
+
+        let body = stream.read_to_end().await;
+        assert_eq!(body.len(), CHUNK_COUNT * CHUNK_SIZE);
+    }
+}
@@ -1,45 +1,125 @@
 use anyhow::anyhow;
 use anyhow::Context;
 use anyhow::Result;
+use axum::body::to_bytes;
+use axum::extract::Request as AxumRequest;
+use axum::handler::Handler;
+use axum::middleware::from_fn;
+use axum::middleware::Next;
+use axum::response::IntoResponse;
+use axum::routing::Route;
+use axum::Router;
+use cookie::time::Duration as TimeDuration;
+use cookie::time::OffsetDateTime;
 use cookie::Cookie;
 use cookie::CookieJar;
+use http::header;
 use http::HeaderName;
 use http::HeaderValue;
 use http::Method;
+use http::StatusCode;
 use http::Uri;
+use http::Version;
 use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::convert::Infallible;
 use std::fmt::Debug;
+use std::future::Future;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::thread;
+use std::time::Duration as StdDuration;
+use tokio::time::sleep_until;
+use tokio::time::Instant;
+use tower::Layer;
+use tower::Service;
 use url::Url;
 
 #[cfg(feature = "typed-routing")]
 use axum_extra::routing::TypedPath;
 
 #[cfg(feature = "reqwest")]
-use crate::transport_layer::TransportLayerType;
+use crate::ReqwestConfigurer;
+#[cfg(feature = "tus")]
+use crate::TusUpload;
+#[cfg(feature = "tus")]
+use bytes::Bytes;
 #[cfg(feature = "reqwest")]
 use reqwest::Client;
 #[cfg(feature = "reqwest")]
 use reqwest::RequestBuilder;
 
+use crate::internals::check;
+use crate::internals::encode_unicode_host;
+use crate::internals::encode_unicode_url;
+use crate::internals::filter_cookies_for_host;
+use crate::internals::format_http_date;
+use crate::internals::is_verbose_env_enabled;
+use crate::internals::join_all;
+use crate::internals::CatchPanicTransportLayer;
+use crate::internals::ChaosTransportLayer;
+use crate::internals::CsrfState;
+use crate::internals::DataMask;
+use crate::internals::EnvVarsGuard;
 use crate::internals::ExpectedState;
+use crate::internals::InterceptTransportLayer;
+use crate::internals::MetricsTransportLayer;
+use crate::internals::OrPanic;
 use crate::internals::QueryParamsStore;
+use crate::internals::ReadinessCheck;
+use crate::internals::RequestEncoders;
 use crate::internals::RequestPathFormatter;
+use crate::internals::ResponseDecoders;
+use crate::internals::SeededRng;
 use crate::transport_layer::IntoTransportLayer;
 use crate::transport_layer::TransportLayer;
 use crate::transport_layer::TransportLayerBuilder;
+use crate::transport_layer::TransportLayerType;
+use crate::AssertionError;
+use crate::CsrfConfig;
+use crate::Error;
+use crate::HeaderMatrix;
+use crate::InnerRequest;
+use crate::ProbeHandle;
+use crate::RecordedExchange;
+use crate::RecordedExchanges;
+use crate::ResponseBurst;
+use crate::ResponsePair;
+use crate::ResponseValidator;
+use crate::RouteInfo;
+use crate::Scenario;
+use crate::ServerMetrics;
+use crate::TestEventLog;
+use crate::TestGuard;
 use crate::TestRequest;
 use crate::TestRequestConfig;
+use crate::TestResponse;
 use crate::TestServerBuilder;
 use crate::TestServerConfig;
+use crate::TestServerScope;
 use crate::Transport;
+use crate::TransportDiagnostics;
 
 mod server_shared_state;
 pub(crate) use self::server_shared_state::*;
 
 const DEFAULT_URL_ADDRESS: &str = "http://localhost";
 
+/// The methods sent by [`TestServer::probe_unsupported_methods()`].
+///
+/// `CONNECT` is left out, as it is for proxies rather than resources.
+const PROBED_METHODS: [Method; 8] = [
+    Method::GET,
+    Method::HEAD,
+    Method::POST,
+    Method::PUT,
+    Method::PATCH,
+    Method::DELETE,
+    Method::OPTIONS,
+    Method::TRACE,
+];
+
 ///
 /// The `TestServer` runs your Axum application,
 /// allowing you to make HTTP requests against it.
@@ -141,14 +221,43 @@ const DEFAULT_URL_ADDRESS: &str = "http://localhost";
 #[derive(Debug)]
 pub struct TestServer {
     state: Arc<Mutex<ServerSharedState>>,
-    transport: Arc<Box<dyn TransportLayer>>,
+    transport: Arc<dyn TransportLayer>,
     save_cookies: bool,
+    cookie_domain: Option<String>,
     expected_state: ExpectedState,
     default_content_type: Option<String>,
+    expected_content_type: Option<String>,
     is_http_path_restricted: bool,
+    maybe_inner_requests: Option<Arc<Mutex<Vec<InnerRequest>>>>,
+    maybe_metrics: Option<Arc<Mutex<ServerMetrics>>>,
+    maybe_recorded_exchanges: Option<Arc<Mutex<Vec<RecordedExchange>>>>,
+    maybe_event_log: Option<TestEventLog>,
+    response_validators: Vec<ResponseValidator>,
+    probes: Vec<ProbeHandle>,
+    data_mask: Arc<DataMask>,
+    response_decoders: Arc<ResponseDecoders>,
+    request_encoders: Arc<RequestEncoders>,
+    // Only held, to restore the environment variables when dropped.
+    _env_vars_guard: Option<EnvVarsGuard>,
+    maybe_deprecated_requests: Option<Arc<Mutex<Vec<String>>>>,
+    is_failing_on_deprecated: bool,
+    is_failing_on_leaked_connections: bool,
+    clock_skew: Option<TimeDuration>,
+    seed: u64,
+    rng: Arc<Mutex<SeededRng>>,
+    maybe_readiness_check: Option<Arc<ReadinessCheck>>,
+    host_aliases: Vec<String>,
+    is_verbose: bool,
+    max_buffered_body: Option<usize>,
+    max_recorded_request_body: usize,
+    is_catching_panics: bool,
+    is_capturing_raw_head: bool,
+    routes: Vec<RouteInfo>,
 
     #[cfg(feature = "reqwest")]
     maybe_reqwest_client: Option<Client>,
+    #[cfg(feature = "reqwest")]
+    reqwest_configurers: Vec<ReqwestConfigurer>,
 }
 
 impl TestServer {
@@ -187,8 +296,10 @@ impl TestServer {
     ///  - [`axum::serve::Serve`]
     ///  - [`axum::serve::WithGracefulShutdown`]
     ///  - [`shuttle_axum::ShuttleAxum`]
+    ///  - a closure returning a [`axum::Router`], which is called to build a new app for each request
+    ///  - a `Result` holding any of the above, where an `Err` is returned from here
     ///
-    pub fn new<A>(app: A) -> Result<Self>
+    pub fn new<A>(app: A) -> Result<Self, Error>
     where
         A: IntoTransportLayer,
     {
@@ -201,1611 +312,6211 @@ impl TestServer {
     ///
     /// This can take a [`crate::TestServerConfig`] or a [`crate::TestServerBuilder`].
     /// See those for more information on configuration settings.
-    pub fn new_with_config<A, C>(app: A, config: C) -> Result<Self>
+    #[track_caller]
+    pub fn new_with_config<A, C>(app: A, config: C) -> Result<Self, Error>
     where
         A: IntoTransportLayer,
         C: Into<TestServerConfig>,
     {
         let config = config.into();
+        let transport_config = config.resolved_transport_config();
+        let cookie_config = config.resolved_cookie_config();
+        let expectation_config = config.resolved_expectation_config();
+        let is_saving_cookies = cookie_config.save_cookies.unwrap_or(false);
+        let mut routes = app.routes().unwrap_or_default();
+        routes.extend(config.routes);
+
+        let env_vars_guard = (!config.env_vars.is_empty())
+            .then(|| EnvVarsGuard::new(&config.env_vars))
+            .transpose()?;
+
         let mut shared_state = ServerSharedState::new();
-        if let Some(scheme) = config.default_scheme {
+        if let Some(scheme) = transport_config.default_scheme {
             shared_state.set_scheme_unlocked(scheme);
         }
 
         let shared_state_mutex = Mutex::new(shared_state);
         let state = Arc::new(shared_state_mutex);
 
-        let transport = match config.transport {
-            None => {
-                let builder = TransportLayerBuilder::new(None, None);
-                let transport = app.into_default_transport(builder)?;
-                Arc::new(transport)
-            }
+        // The chaos seed is used when no seed is given,
+        // so the seed reported always reproduces the same failures.
+        let seed = config
+            .seed
+            .or_else(|| config.chaos.as_ref().map(|chaos| chaos.seed))
+            .unwrap_or_else(|| SeededRng::from_time().next_u64());
+
+        let is_catching_panics = config.catch_panics;
+        let new_transport_builder = |ip, port| {
+            TransportLayerBuilder::new(ip, port)
+                .with_port_seed(config.seed)
+                .with_catch_panics(is_catching_panics)
+        };
+        let transport = match transport_config.transport {
+            None => app.into_default_transport(new_transport_builder(None, None))?,
             Some(Transport::HttpRandomPort) => {
-                let builder = TransportLayerBuilder::new(None, None);
-                let transport = app.into_http_transport_layer(builder)?;
-                Arc::new(transport)
+                app.into_http_transport_layer(new_transport_builder(None, None))?
             }
             Some(Transport::HttpIpPort { ip, port }) => {
-                let builder = TransportLayerBuilder::new(ip, port);
-                let transport = app.into_http_transport_layer(builder)?;
-                Arc::new(transport)
+                app.into_http_transport_layer(new_transport_builder(ip, port))?
+            }
+            Some(Transport::MockHttp) => app.into_mock_transport_layer()?,
+        };
+
+        // The readiness check polls the app directly, skipping metrics and chaos.
+        let base_transport: Arc<dyn TransportLayer> = Arc::from(transport);
+
+        // The HTTP transport catches panics within the server, as the handler runs there.
+        let is_mock_transport = base_transport.transport_layer_type() == TransportLayerType::Mock;
+        let transport: Arc<dyn TransportLayer> = if is_catching_panics && is_mock_transport {
+            Arc::new(CatchPanicTransportLayer::new(base_transport.clone()))
+        } else {
+            base_transport.clone()
+        };
+
+        let maybe_metrics = config
+            .record_metrics
+            .then(|| Arc::new(Mutex::new(ServerMetrics::default())));
+        let transport: Arc<dyn TransportLayer> = match &maybe_metrics {
+            Some(metrics) => Arc::new(MetricsTransportLayer::new(transport, metrics.clone())),
+            None => transport,
+        };
+
+        // Interceptors sit outside of metrics, so short-circuited requests are not recorded.
+        let transport: Arc<dyn TransportLayer> = if config.interceptors.is_empty() {
+            transport
+        } else {
+            Arc::new(InterceptTransportLayer::new(transport, config.interceptors))
+        };
+
+        let transport: Arc<dyn TransportLayer> = match config.chaos {
+            Some(mut chaos) => {
+                chaos.validate()?;
+                chaos.seed = seed;
+
+                Arc::new(ChaosTransportLayer::new(transport, chaos))
             }
-            Some(Transport::MockHttp) => {
-                let transport = app.into_mock_transport_layer()?;
-                Arc::new(transport)
+            None => transport,
+        };
+
+        let data_mask = Arc::new(DataMask::new(
+            &config.masked_headers,
+            &config.masked_json_paths,
+        )?);
+
+        let mut response_validators = Vec::new();
+        if let Some(error_body_schema) = config.error_body_schema {
+            response_validators.push(error_body_schema.into());
+        }
+        response_validators.extend(config.response_validators);
+
+        let maybe_deprecated_requests = (config.warn_on_deprecated || config.fail_on_deprecated)
+            .then(|| Arc::new(Mutex::new(Vec::new())));
+        if let Some(deprecated_requests) = maybe_deprecated_requests.clone() {
+            response_validators.push(ResponseValidator::new(move |response| {
+                if response.is_deprecated() {
+                    deprecated_requests
+                        .lock()
+                        .expect("Failed to lock deprecated requests, for recording request")
+                        .push(format!(
+                            "{} {}",
+                            response.request_method(),
+                            response.request_url()
+                        ));
+                }
+
+                Ok(())
+            }));
+        }
+
+        let maybe_readiness_check = match config.wait_until_ready {
+            None => None,
+            Some((path, timeout)) => {
+                let url = transport
+                    .url()
+                    .cloned()
+                    .unwrap_or_else(|| DEFAULT_URL_ADDRESS.parse().unwrap());
+                let ready_url = build_url(url, &path, &mut QueryParamsStore::new(), false)?;
+
+                Some(Arc::new(ReadinessCheck::new(
+                    base_transport,
+                    ready_url,
+                    timeout,
+                )))
             }
         };
 
-        let expected_state = match config.expect_success_by_default {
+        let expected_state = match expectation_config
+            .expect_success_by_default
+            .unwrap_or(false)
+        {
             true => ExpectedState::Success,
             false => ExpectedState::None,
         };
 
         #[cfg(feature = "reqwest")]
-        let maybe_reqwest_client = match transport.transport_layer_type() {
-            TransportLayerType::Http => {
-                let reqwest_client = reqwest::Client::builder()
-                    .redirect(reqwest::redirect::Policy::none())
-                    .cookie_store(config.save_cookies)
-                    .build()
-                    .expect("Failed to build Reqwest Client");
-
-                Some(reqwest_client)
-            }
-            TransportLayerType::Mock => None,
-        };
+        let maybe_reqwest_client = build_reqwest_client(
+            transport.transport_layer_type(),
+            is_saving_cookies,
+            &config.reqwest_configurers,
+        )?;
 
         Ok(Self {
             state,
             transport,
-            save_cookies: config.save_cookies,
+            save_cookies: is_saving_cookies,
+            cookie_domain: cookie_config.cookie_domain,
             expected_state,
             default_content_type: config.default_content_type,
-            is_http_path_restricted: config.restrict_requests_with_http_schema,
+            expected_content_type: expectation_config.expected_content_type,
+            is_http_path_restricted: transport_config
+                .restrict_requests_with_http_schema
+                .unwrap_or(false),
+            maybe_inner_requests: None,
+            maybe_metrics,
+            maybe_recorded_exchanges: config
+                .record_exchanges
+                .then(|| Arc::new(Mutex::new(Vec::new()))),
+            maybe_event_log: config.event_log,
+            response_validators,
+            probes: config.probes,
+            data_mask,
+            response_decoders: Arc::default(),
+            request_encoders: Arc::default(),
+            _env_vars_guard: env_vars_guard,
+            maybe_deprecated_requests,
+            is_failing_on_deprecated: config.fail_on_deprecated,
+            is_failing_on_leaked_connections: config.fail_on_leaked_connections,
+            clock_skew: config.clock_skew,
+            seed,
+            rng: Arc::new(Mutex::new(SeededRng::new(seed))),
+            maybe_readiness_check,
+            host_aliases: Vec::new(),
+            is_verbose: config.verbose || is_verbose_env_enabled(),
+            max_buffered_body: config.max_buffered_body,
+            max_recorded_request_body: config.max_recorded_request_body,
+            is_catching_panics,
+            is_capturing_raw_head: config.capture_raw_head,
+            routes,
 
             #[cfg(feature = "reqwest")]
             maybe_reqwest_client,
+            #[cfg(feature = "reqwest")]
+            reqwest_configurers: config.reqwest_configurers,
         })
     }
 
-    /// Creates a HTTP GET request to the path.
-    pub fn get(&self, path: &str) -> TestRequest {
-        self.method(Method::GET, path)
-    }
-
-    /// Creates a HTTP POST request to the given path.
-    pub fn post(&self, path: &str) -> TestRequest {
-        self.method(Method::POST, path)
-    }
-
-    /// Creates a HTTP PATCH request to the path.
-    pub fn patch(&self, path: &str) -> TestRequest {
-        self.method(Method::PATCH, path)
-    }
-
-    /// Creates a HTTP PUT request to the path.
-    pub fn put(&self, path: &str) -> TestRequest {
-        self.method(Method::PUT, path)
-    }
+    /// Creates a `TestServer` for testing a single [`tower::Layer`] in isolation.
+    ///
+    /// All requests are sent through the layer given, to the `inner_handler`.
+    /// The requests received by the inner handler are recorded,
+    /// allowing you to assert what the layer changed on the way in
+    /// (see [`TestServer::last_inner_request()`](crate::TestServer::last_inner_request())),
+    /// and on the way out (by asserting on the [`TestResponse`](crate::TestResponse) as usual).
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::http::HeaderValue;
+    /// use axum_test::TestServer;
+    /// use tower_http::set_header::SetRequestHeaderLayer;
+    ///
+    /// let layer = SetRequestHeaderLayer::overriding(
+    ///     http::header::USER_AGENT,
+    ///     HeaderValue::from_static("my-layer"),
+    /// );
+    /// let server = TestServer::for_layer(layer, || async { "inner" })?;
+    ///
+    /// server.get(&"/anything")
+    ///     .await
+    ///     .assert_text("inner");
+    ///
+    /// server
+    ///     .last_inner_request()
+    ///     .assert_header("user-agent", "my-layer");
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[track_caller]
+    pub fn for_layer<L, H, T>(layer: L, inner_handler: H) -> Result<Self, Error>
+    where
+        L: Layer<Route> + Clone + Send + 'static,
+        L::Service: Service<AxumRequest> + Clone + Send + 'static,
+        <L::Service as Service<AxumRequest>>::Response: IntoResponse + 'static,
+        <L::Service as Service<AxumRequest>>::Error: Into<Infallible> + 'static,
+        <L::Service as Service<AxumRequest>>::Future: Send + 'static,
+        H: Handler<T, ()>,
+        T: 'static,
+    {
+        let inner_requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded_inner_requests = inner_requests.clone();
+        let record_inner_request = from_fn(move |request: AxumRequest, next: Next| {
+            let recorded_inner_requests = recorded_inner_requests.clone();
+
+            async move {
+                let (parts, body) = request.into_parts();
+                let body_bytes = to_bytes(body, usize::MAX)
+                    .await
+                    .expect("Failed to read request body received by inner handler");
+
+                let inner_request = InnerRequest::new(
+                    parts.method.clone(),
+                    parts.uri.clone(),
+                    parts.headers.clone(),
+                    body_bytes.clone(),
+                );
+                recorded_inner_requests
+                    .lock()
+                    .expect("Failed to lock inner requests, for recording inner request")
+                    .push(inner_request);
+
+                let request = AxumRequest::from_parts(parts, body_bytes.into());
+                next.run(request).await
+            }
+        });
 
-    /// Creates a HTTP DELETE request to the path.
-    pub fn delete(&self, path: &str) -> TestRequest {
-        self.method(Method::DELETE, path)
-    }
+        let app = Router::new()
+            .fallback(inner_handler)
+            .layer(record_inner_request)
+            .layer(layer);
 
-    /// Creates a HTTP request, to the method and path provided.
-    pub fn method(&self, method: Method, path: &str) -> TestRequest {
-        let maybe_config = self.build_test_request_config(method.clone(), path);
-        let config = maybe_config
-            .with_context(|| format!("Failed to build, for request {method} {path}"))
-            .unwrap();
+        let mut server = Self::new(app)?;
+        server.maybe_inner_requests = Some(inner_requests);
 
-        TestRequest::new(self.state.clone(), self.transport.clone(), config)
+        Ok(server)
     }
 
-    #[cfg(feature = "reqwest")]
-    fn reqwest_client(&self) -> &Client {
-        self.maybe_reqwest_client
+    /// Returns all of the requests received by the inner handler,
+    /// in the order they were received.
+    ///
+    /// This will panic if the `TestServer` was not built using [`TestServer::for_layer()`](crate::TestServer::for_layer()).
+    #[must_use]
+    #[track_caller]
+    pub fn inner_requests(&self) -> Vec<InnerRequest> {
+        self.maybe_inner_requests
             .as_ref()
-            .expect("Reqwest client is not available, TestServer must be build with HTTP transport for Reqwest to be available")
+            .expect("Inner requests are only recorded when the TestServer is built using `TestServer::for_layer`")
+            .lock()
+            .expect("Failed to lock inner requests, for reading inner requests")
+            .clone()
     }
 
-    #[cfg(feature = "reqwest")]
-    pub fn reqwest_get(&self, path: &str) -> RequestBuilder {
-        self.reqwest_method(Method::GET, path)
+    /// Returns the most recent request received by the inner handler.
+    ///
+    /// This will panic if no request has reached the inner handler,
+    /// or if the `TestServer` was not built using [`TestServer::for_layer()`](crate::TestServer::for_layer()).
+    #[must_use]
+    #[track_caller]
+    pub fn last_inner_request(&self) -> InnerRequest {
+        self.inner_requests()
+            .pop()
+            .expect("No request has reached the inner handler")
+    }
+
+    /// Returns the seed used for all randomness within this `TestServer`.
+    /// This includes generated idempotency keys, WebSocket keys, and the chaos layer.
+    ///
+    /// When no seed was given, this is the seed from the [`ChaosConfig`](crate::ChaosConfig) if set,
+    /// or otherwise picked at random.
+    ///
+    /// Building a new server using [`TestServerBuilder::with_seed()`](crate::TestServerBuilder::with_seed())
+    /// and this seed will reproduce the same values.
+    /// The seed is also printed when a test panics.
+    #[must_use]
+    pub fn seed(&self) -> u64 {
+        self.seed
     }
 
-    #[cfg(feature = "reqwest")]
-    pub fn reqwest_post(&self, path: &str) -> RequestBuilder {
-        self.reqwest_method(Method::POST, path)
-    }
+    /// Waits for the server to become ready, using the path and timeout given to
+    /// [`TestServerBuilder::wait_until_ready()`](crate::TestServerBuilder::wait_until_ready()).
+    ///
+    /// Requests made using the `TestServer` already wait for this before they are sent.
+    /// Requests made using Reqwest do not, and so this should be called first.
+    ///
+    /// The server is only polled once. Later calls return immediately.
+    /// This does nothing if the `TestServer` was built without a readiness check.
+    pub async fn wait_until_ready(&self) -> Result<()> {
+        if let Some(readiness_check) = &self.maybe_readiness_check {
+            readiness_check.wait_until_ready().await?;
+        }
 
-    #[cfg(feature = "reqwest")]
-    pub fn reqwest_put(&self, path: &str) -> RequestBuilder {
-        self.reqwest_method(Method::PUT, path)
+        Ok(())
     }
 
-    #[cfg(feature = "reqwest")]
-    pub fn reqwest_patch(&self, path: &str) -> RequestBuilder {
-        self.reqwest_method(Method::PATCH, path)
+    /// Returns the metrics recorded on the requests that reached your application.
+    ///
+    /// This will panic if the `TestServer` was not built with
+    /// [`TestServerBuilder::record_metrics()`](crate::TestServerBuilder::record_metrics()).
+    #[must_use]
+    #[track_caller]
+    pub fn metrics(&self) -> ServerMetrics {
+        self.maybe_metrics
+            .as_ref()
+            .expect("Metrics are only recorded when the TestServer is built using `TestServerBuilder::record_metrics`")
+            .lock()
+            .expect("Failed to lock metrics, for reading metrics")
+            .clone()
     }
 
-    #[cfg(feature = "reqwest")]
-    pub fn reqwest_delete(&self, path: &str) -> RequestBuilder {
-        self.reqwest_method(Method::DELETE, path)
-    }
+    /// Returns every request sent, and the response received, in the order they were sent.
+    ///
+    /// Calling [`RecordedExchanges::to_test_code()`](crate::RecordedExchanges::to_test_code())
+    /// turns these into axum-test code, for starting regression tests from real traffic.
+    ///
+    /// This will panic if the `TestServer` was not built with
+    /// [`TestServerBuilder::record_exchanges()`](crate::TestServerBuilder::record_exchanges()).
+    #[must_use]
+    #[track_caller]
+    pub fn recorded_exchanges(&self) -> RecordedExchanges {
+        let exchanges = self
+            .maybe_recorded_exchanges
+            .as_ref()
+            .expect("Exchanges are only recorded when the TestServer is built using `TestServerBuilder::record_exchanges`")
+            .lock()
+            .expect("Failed to lock recorded exchanges, for reading recorded exchanges")
+            .clone();
 
-    #[cfg(feature = "reqwest")]
-    pub fn reqwest_head(&self, path: &str) -> RequestBuilder {
-        self.reqwest_method(Method::HEAD, path)
+        RecordedExchanges::new(exchanges)
     }
 
-    /// Creates a HTTP request, using Reqwest, using the method + path described.
-    /// This expects a relative url to the `TestServer`.
+    /// Asserts no request was sent more than once, with the same method, path, query, and body.
+    ///
+    /// This is for catching accidental double submits, such as from retry logic in a client.
+    ///
+    /// This will panic if the `TestServer` was not built with
+    /// [`TestServerBuilder::record_metrics()`](crate::TestServerBuilder::record_metrics()).
     ///
     /// ```rust
     /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
     /// #
-    /// use axum::Router;
+    /// # use axum::Router;
     /// use axum_test::TestServer;
     ///
-    /// let my_app = Router::new();
+    /// # let app = Router::new();
     /// let server = TestServer::builder()
-    ///     .http_transport() // Important, must be HTTP!
-    ///     .build(my_app)?;
+    ///     .record_metrics()
+    ///     .build(app)?;
     ///
-    /// // Build your request
-    /// let request = server.get(&"/user")
-    ///     .add_header("x-custom-header", "example.com")
-    ///     .content_type("application/yaml");
+    /// server.post(&"/orders").text("one").await;
+    /// server.post(&"/orders").text("two").await;
     ///
-    /// // await request to execute
-    /// let response = request.await;
+    /// server.assert_no_duplicate_requests();
     /// #
     /// # Ok(()) }
     /// ```
-    #[cfg(feature = "reqwest")]
-    pub fn reqwest_method(&self, method: Method, path: &str) -> RequestBuilder {
-        let request_url = self
-            .server_url(path)
-            .expect("Failed to generate server url for request {method} {path}");
+    #[track_caller]
+    pub fn assert_no_duplicate_requests(&self) {
+        self.metrics().assert_no_duplicate_requests()
+    }
 
-        self.reqwest_client().request(method, request_url)
+    /// Checks no request was sent more than once, with the same method, path, query, and body.
+    ///
+    /// This is the non-panicking version of [`TestServer::assert_no_duplicate_requests()`].
+    pub fn check_no_duplicate_requests(&self) -> Result<(), AssertionError> {
+        self.metrics().check_no_duplicate_requests()
     }
 
-    /// Creates a request to the server, to start a Websocket connection,
-    /// on the path given.
+    /// Returns the log of events pushed by your application.
     ///
-    /// This is the requivalent of making a GET request to the endpoint,
-    /// and setting the various headers needed for making an upgrade request.
+    /// This will panic if the `TestServer` was not built with
+    /// [`TestServerBuilder::event_log()`](crate::TestServerBuilder::event_log()).
+    #[must_use]
+    #[track_caller]
+    pub fn events(&self) -> TestEventLog {
+        self.maybe_event_log
+            .clone()
+            .expect("Events are only available when the TestServer is built using `TestServerBuilder::event_log`")
+    }
+
+    /// Returns the requests which received a response carrying a `Deprecation` or `Sunset` header,
+    /// in the order they were made. Each is formatted as the method and the full url.
     ///
-    /// *Note*, this requires the server to be running on a real HTTP
-    /// port. Either using a randomly assigned port, or a specified one.
-    /// See the [`TestServerConfig::transport`](crate::TestServerConfig::transport) for more details.
+    /// This will panic if the `TestServer` was not built with
+    /// [`TestServerBuilder::warn_on_deprecated()`](crate::TestServerBuilder::warn_on_deprecated()),
+    /// or [`TestServerBuilder::fail_on_deprecated()`](crate::TestServerBuilder::fail_on_deprecated()).
+    #[must_use]
+    #[track_caller]
+    pub fn deprecated_requests(&self) -> Vec<String> {
+        self.maybe_deprecated_requests
+            .as_ref()
+            .expect("Deprecated requests are only collected when the TestServer is built using `TestServerBuilder::warn_on_deprecated` or `TestServerBuilder::fail_on_deprecated`")
+            .lock()
+            .expect("Failed to lock deprecated requests, for reading deprecated requests")
+            .clone()
+    }
+
+    /// Returns the routes of the application, with one [`RouteInfo`] for each method of each route.
     ///
-    /// # Example
+    /// These are the routes recorded by a [`RecordingRouter`](crate::RecordingRouter),
+    /// followed by any given by hand using [`TestServerBuilder::route()`](crate::TestServerBuilder::route()).
+    /// Axum has no public API for listing the routes of a plain `Router`,
+    /// so for those this only returns the routes given by hand, and is empty if none were.
+    ///
+    /// This is for driving table tests across every route, such as checking they all require auth.
     ///
     /// ```rust
     /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
     /// #
-    /// use axum::Router;
+    /// use axum::http::Method;
+    /// use axum::http::StatusCode;
+    /// use axum_test::RecordingRouter;
     /// use axum_test::TestServer;
     ///
-    /// let app = Router::new();
-    /// let server = TestServer::builder()
-    ///     .http_transport()
-    ///     .build(app)?;
-    ///
-    /// let mut websocket = server
-    ///     .get_websocket(&"/my-web-socket-end-point")
-    ///     .await
-    ///     .into_websocket()
-    ///     .await;
+    /// let app = RecordingRouter::new()
+    ///     .route(Method::GET, "/users", || async { StatusCode::UNAUTHORIZED })
+    ///     .route(Method::GET, "/users/:id", || async { StatusCode::UNAUTHORIZED });
+    /// let server = TestServer::new(app)?;
     ///
-    /// websocket.send_text("Hello!").await;
+    /// for route in server.routes() {
+    ///     server.method(route.method.clone(), &route.example_path())
+    ///         .expect_failure()
+    ///         .await
+    ///         .assert_status_unauthorized();
+    /// }
     /// #
     /// # Ok(()) }
     /// ```
-    ///
-    #[cfg(feature = "ws")]
-    pub fn get_websocket(&self, path: &str) -> TestRequest {
-        use http::header;
-
-        self.get(path)
-            .add_header(header::CONNECTION, "upgrade")
-            .add_header(header::UPGRADE, "websocket")
-            .add_header(header::SEC_WEBSOCKET_VERSION, "13")
-            .add_header(
-                header::SEC_WEBSOCKET_KEY,
-                crate::internals::generate_ws_key(),
-            )
+    #[must_use]
+    pub fn routes(&self) -> Vec<RouteInfo> {
+        self.routes.clone()
     }
 
-    /// Creates a HTTP GET request, using the typed path provided.
+    /// Asserts every route of the application requires credentials.
     ///
-    /// See [`axum-extra`](https://docs.rs/axum-extra) for full documentation on [`TypedPath`](axum_extra::routing::TypedPath).
+    /// Each route from [`TestServer::routes()`] is requested twice,
+    /// using [`RouteInfo::example_path()`](crate::RouteInfo::example_path()) as the path.
+    /// First with no credentials, where a `401 Unauthorized` or `403 Forbidden` is expected.
+    /// Then with the credentials added by the function given, where neither is expected.
     ///
-    /// # Example Test
+    /// **Only routes known to the `TestServer` are checked.**
+    /// Routes of a plain `Router` which were not given using
+    /// [`TestServerBuilder::route()`](crate::TestServerBuilder::route()),
+    /// or routes added outside of a [`RecordingRouter`](crate::RecordingRouter),
+    /// are silently skipped. Use a `RecordingRouter` so every route is checked.
     ///
-    /// Using a `TypedPath` you can write build and test a route like below:
+    /// Routes whose method and pattern are listed in `public_routes` (i.e. `(Method::GET, "/login")`) are skipped.
+    /// All routes failing are reported together,
+    /// and this fails if there are no routes to check.
     ///
     /// ```rust
     /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
     /// #
-    /// use axum::Json;
-    /// use axum::Router;
-    /// use axum::routing::get;
-    /// use axum_extra::routing::RouterExt;
-    /// use axum_extra::routing::TypedPath;
-    /// use serde::Deserialize;
-    /// use serde::Serialize;
-    ///
+    /// use axum::http::HeaderMap;
+    /// use axum::http::Method;
+    /// use axum::http::StatusCode;
+    /// use axum_test::RecordingRouter;
     /// use axum_test::TestServer;
     ///
-    /// #[derive(TypedPath, Deserialize)]
-    /// #[typed_path("/users/:user_id")]
-    /// struct UserPath {
-    ///     pub user_id: u32,
-    /// }
-    ///
-    /// // Build a typed route:
-    /// async fn route_get_user(UserPath { user_id }: UserPath) -> String {
-    ///     format!("hello user {user_id}")
+    /// async fn get_user(headers: HeaderMap) -> StatusCode {
+    ///     match headers.get("authorization") {
+    ///         Some(_) => StatusCode::OK,
+    ///         None => StatusCode::UNAUTHORIZED,
+    ///     }
     /// }
     ///
-    /// let app = Router::new()
-    ///     .typed_get(route_get_user);
-    ///
-    /// // Then test the route:
+    /// let app = RecordingRouter::new()
+    ///     .route(Method::GET, "/login", || async { "login" })
+    ///     .route(Method::GET, "/users/:id", get_user);
     /// let server = TestServer::new(app)?;
+    ///
     /// server
-    ///     .typed_get(&UserPath { user_id: 123 })
-    ///     .await
-    ///     .assert_text("hello user 123");
+    ///     .assert_all_routes_unauthorized_without(
+    ///         |request| request.authorization_bearer("my-token"),
+    ///         &[(Method::GET, "/login")],
+    ///     )
+    ///     .await;
     /// #
-    /// # Ok(())
-    /// # }
+    /// # Ok(()) }
     /// ```
-    ///
-    #[cfg(feature = "typed-routing")]
-    pub fn typed_get<P>(&self, path: &P) -> TestRequest
-    where
-        P: TypedPath,
+    pub async fn assert_all_routes_unauthorized_without<F>(
+        &self,
+        add_credentials: F,
+        public_routes: &[(Method, &str)],
+    ) where
+        F: Fn(TestRequest) -> TestRequest,
     {
-        self.typed_method(Method::GET, path)
+        self.check_all_routes_unauthorized_without(add_credentials, public_routes)
+            .await
+            .or_panic()
     }
 
-    /// Creates a HTTP POST request, using the typed path provided.
+    /// Checks every route of the application requires credentials.
     ///
-    /// See [`axum-extra`](https://docs.rs/axum-extra) for full documentation on [`TypedPath`](axum_extra::routing::TypedPath).
-    #[cfg(feature = "typed-routing")]
-    pub fn typed_post<P>(&self, path: &P) -> TestRequest
+    /// This is the non-panicking version of [`TestServer::assert_all_routes_unauthorized_without()`].
+    pub async fn check_all_routes_unauthorized_without<F>(
+        &self,
+        add_credentials: F,
+        public_routes: &[(Method, &str)],
+    ) -> Result<(), AssertionError>
     where
-        P: TypedPath,
+        F: Fn(TestRequest) -> TestRequest,
     {
-        self.typed_method(Method::POST, path)
-    }
+        let is_auth_failure = |status_code: StatusCode| {
+            status_code == StatusCode::UNAUTHORIZED || status_code == StatusCode::FORBIDDEN
+        };
+        let is_public = |route: &RouteInfo| {
+            public_routes.iter().any(|(method, path_pattern)| {
+                *method == route.method && *path_pattern == route.path_pattern
+            })
+        };
 
-    /// Creates a HTTP PATCH request, using the typed path provided.
-    ///
-    /// See [`axum-extra`](https://docs.rs/axum-extra) for full documentation on [`TypedPath`](axum_extra::routing::TypedPath).
-    #[cfg(feature = "typed-routing")]
-    pub fn typed_patch<P>(&self, path: &P) -> TestRequest
-    where
-        P: TypedPath,
-    {
-        self.typed_method(Method::PATCH, path)
+        check(
+            !self.routes.is_empty(),
+            "No routes to check, use a RecordingRouter or TestServerBuilder::route so the TestServer knows the routes of the application",
+        )?;
+
+        let mut failures = Vec::new();
+        for route in self.routes.iter().cloned() {
+            if is_public(&route) {
+                continue;
+            }
+
+            let path = route.example_path();
+            let RouteInfo {
+                method,
+                path_pattern,
+            } = route;
+
+            let status_code = self
+                .method(method.clone(), &path)
+                .expect_state(ExpectedState::None)
+                .try_send()
+                .await?
+                .status_code();
+            if !is_auth_failure(status_code) {
+                failures.push(format!(
+                    "{method} {path_pattern} returned {status_code} without credentials"
+                ));
+                continue;
+            }
+
+            let request = self
+                .method(method.clone(), &path)
+                .expect_state(ExpectedState::None);
+            let status_code = add_credentials(request).try_send().await?.status_code();
+            if is_auth_failure(status_code) {
+                failures.push(format!(
+                    "{method} {path_pattern} returned {status_code} with credentials"
+                ));
+            }
+        }
+
+        check(
+            failures.is_empty(),
+            format_args!(
+                "Expected all routes to return 401 or 403 without credentials, and neither with them. Routes failing:\n    {}",
+                failures.join("\n    ")
+            ),
+        )
     }
 
-    /// Creates a HTTP PUT request, using the typed path provided.
+    /// Runs the steps of the scenario given against this server, in order,
+    /// and asserts each response is what the step expects.
     ///
-    /// See [`axum-extra`](https://docs.rs/axum-extra) for full documentation on [`TypedPath`](axum_extra::routing::TypedPath).
-    #[cfg(feature = "typed-routing")]
-    pub fn typed_put<P>(&self, path: &P) -> TestRequest
-    where
-        P: TypedPath,
-    {
-        self.typed_method(Method::PUT, path)
-    }
-
-    /// Creates a HTTP DELETE request, using the typed path provided.
-    ///
-    /// See [`axum-extra`](https://docs.rs/axum-extra) for full documentation on [`TypedPath`](axum_extra::routing::TypedPath).
-    #[cfg(feature = "typed-routing")]
-    pub fn typed_delete<P>(&self, path: &P) -> TestRequest
-    where
-        P: TypedPath,
-    {
-        self.typed_method(Method::DELETE, path)
-    }
-
-    /// Creates a typed HTTP request, using the method provided.
-    ///
-    /// See [`axum-extra`](https://docs.rs/axum-extra) for full documentation on [`TypedPath`](axum_extra::routing::TypedPath).
-    #[cfg(feature = "typed-routing")]
-    pub fn typed_method<P>(&self, method: Method, path: &P) -> TestRequest
-    where
-        P: TypedPath,
-    {
-        self.method(method, &path.to_string())
-    }
-
-    /// Returns the local web address for the test server,
-    /// if an address is available.
-    ///
-    /// The address is available when running as a real web server,
-    /// by setting the [`TestServerConfig`](crate::TestServerConfig) `transport` field to `Transport::HttpRandomPort` or `Transport::HttpIpPort`.
-    ///
-    /// This will return `None` when there is mock HTTP transport (the default).
-    pub fn server_address(&self) -> Option<Url> {
-        self.url()
-    }
-
-    /// This turns a relative path, into an absolute path to the server.
-    /// i.e. A path like `/users/123` will become something like `http://127.0.0.1:1234/users/123`.
-    ///
-    /// The absolute address can be used to make requests to the running server,
-    /// using any appropriate client you wish.
-    ///
-    /// # Example
+    /// All steps are run, and every failing step is reported.
     ///
     /// ```rust
     /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
     /// #
     /// use axum::Router;
+    /// use axum::routing::get;
+    /// use axum_test::Scenario;
     /// use axum_test::TestServer;
+    /// use serde_json::json;
     ///
-    /// let app = Router::new();
-    /// let server = TestServer::builder()
-    ///         .http_transport()
-    ///         .build(app)?;
-    ///
-    /// let full_url = server.server_url(&"/users/123?filter=enabled")?;
+    /// let app = Router::new()
+    ///     .route(&"/ping", get(|| async { "pong!" }));
+    /// let server = TestServer::new(app)?;
     ///
-    /// // Prints something like ... http://127.0.0.1:1234/users/123?filter=enabled
-    /// println!("{full_url}");
+    /// let scenario = Scenario::try_from(json!({
+    ///     "steps": [
+    ///         { "method": "GET", "path": "/ping", "expect": { "status": 200 } },
+    ///     ],
+    /// }))?;
+    /// server.assert_scenario(&scenario).await;
     /// #
     /// # Ok(()) }
     /// ```
-    ///
-    /// This will return an error if you are using the mock transport.
-    /// Real HTTP transport is required to use this method (see [`TestServerConfig`](crate::TestServerConfig) `transport` field).
-    ///
-    /// It will also return an error if you provide an absolute path,
-    /// for example if you pass in `http://google.com`.
-    pub fn server_url(&self, path: &str) -> Result<Url> {
-        let path_uri = path.parse::<Uri>()?;
-        if is_absolute_uri(&path_uri) {
-            return Err(anyhow!(
-                "Absolute path provided for building server url, need to provide a relative uri"
-            ));
-        }
-
-        let server_url = self.url()
-            .ok_or_else(||
-                anyhow!(
-                    "No local address for server, need to run with HTTP transport to have a server address",
-                )
-            )?;
-
-        let server_locked = self.state.as_ref().lock().map_err(|err| {
-            anyhow!("Failed to lock InternalTestServer, for building server_url, received {err:?}",)
-        })?;
-        let mut query_params = server_locked.query_params().clone();
-        let mut full_server_url = build_url(
-            server_url,
-            path,
-            &mut query_params,
-            self.is_http_path_restricted,
-        )?;
-
-        // Ensure the query params are present
-        if query_params.has_content() {
-            full_server_url.set_query(Some(&query_params.to_string()));
-        }
-
-        Ok(full_server_url)
+    pub async fn assert_scenario(&self, scenario: &Scenario) {
+        self.check_scenario(scenario).await.or_panic()
     }
 
-    /// Adds a single cookie to be included on *all* future requests.
+    /// Runs the steps of the scenario given against this server, in order,
+    /// and checks each response is what the step expects.
     ///
-    /// If a cookie with the same name already exists,
-    /// then it will be replaced.
-    pub fn add_cookie(&mut self, cookie: Cookie) {
-        ServerSharedState::add_cookie(&self.state, cookie)
-            .context("Trying to call add_cookie")
-            .unwrap()
+    /// This is the non-panicking version of [`TestServer::assert_scenario()`].
+    pub async fn check_scenario(&self, scenario: &Scenario) -> Result<(), AssertionError> {
+        scenario.run(self).await
     }
 
-    /// Adds extra cookies to be used on *all* future requests.
+    /// Returns a guard which prints the requests in flight, and WebSockets open, on this server,
+    /// if the test panics or passes a deadline whilst it is held.
     ///
-    /// Any cookies which have the same name as the new cookies,
-    /// will get replaced.
-    pub fn add_cookies(&mut self, cookies: CookieJar) {
-        ServerSharedState::add_cookies(&self.state, cookies)
-            .context("Trying to call add_cookies")
-            .unwrap()
+    /// See [`TestGuard`] for more details.
+    #[must_use]
+    pub fn guard(&self) -> TestGuard {
+        TestGuard::new(self.state.clone())
     }
 
-    /// Clears all of the cookies stored internally.
-    pub fn clear_cookies(&mut self) {
-        ServerSharedState::clear_cookies(&self.state)
-            .context("Trying to call clear_cookies")
-            .unwrap()
+    /// Returns diagnostics on the connections, and tasks, of the transport behind this server.
+    ///
+    /// See [`TransportDiagnostics`] for what is reported.
+    #[must_use]
+    pub fn transport_diagnostics(&self) -> TransportDiagnostics {
+        self.transport.transport_diagnostics()
     }
 
-    /// Requests made using this `TestServer` will save their cookies for future requests to send.
+    /// Asserts no requests are still being served,
+    /// such as a streamed response which was never finished.
     ///
-    /// This behaviour is off by default.
-    pub fn save_cookies(&mut self) {
-        self.save_cookies = true;
+    /// Idle connections are closed in the background after their last response,
+    /// and so are not counted as leaked.
+    /// Use [`TestServerBuilder::fail_on_leaked_connections()`](crate::TestServerBuilder::fail_on_leaked_connections())
+    /// to check this when the server is dropped.
+    #[track_caller]
+    pub fn assert_no_leaked_connections(&self) {
+        let active_requests = self.transport_diagnostics().active_requests;
+
+        assert_eq!(
+            active_requests, 0,
+            "Expected no leaked connections, {active_requests} requests are still being served"
+        );
     }
 
-    /// Requests made using this `TestServer` will _not_ save their cookies for future requests to send up.
+    /// Asserts paths matching the Axum route pattern given (such as `/users/:id`),
+    /// were called the number of times expected.
     ///
-    /// This is the default behaviour.
-    pub fn do_not_save_cookies(&mut self) {
-        self.save_cookies = false;
+    /// See [`ServerMetrics::route_hits()`](crate::ServerMetrics::route_hits()) for how routes are matched.
+    ///
+    /// This requires the `TestServer` to be built with
+    /// [`TestServerBuilder::record_metrics()`](crate::TestServerBuilder::record_metrics()).
+    #[track_caller]
+    pub fn assert_route_called(&self, route: &str, expected_count: usize) {
+        let received_count = self.metrics().route_hits(route);
+
+        assert_eq!(
+            expected_count, received_count,
+            "Expected route '{route}' to be called {expected_count} times, it was called {received_count} times"
+        );
     }
 
-    /// Requests made using this `TestServer` will assert a HTTP status in the 2xx range will be returned, unless marked otherwise.
+    /// Asserts exactly the path given was called the number of times expected.
     ///
-    /// By default this behaviour is off.
-    pub fn expect_success(&mut self) {
-        self.expected_state = ExpectedState::Success;
+    /// This requires the `TestServer` to be built with
+    /// [`TestServerBuilder::record_metrics()`](crate::TestServerBuilder::record_metrics()).
+    #[track_caller]
+    pub fn assert_path_called(&self, path: &str, expected_count: usize) {
+        let received_count = self.metrics().path_hits(path);
+
+        assert_eq!(
+            expected_count, received_count,
+            "Expected path '{path}' to be called {expected_count} times, it was called {received_count} times"
+        );
     }
 
-    /// Requests made using this `TestServer` will assert a HTTP status is outside the 2xx range will be returned, unless marked otherwise.
-    ///
-    /// By default this behaviour is off.
-    pub fn expect_failure(&mut self) {
-        self.expected_state = ExpectedState::Failure;
+    /// Creates a HTTP GET request to the path.
+    pub fn get(&self, path: &str) -> TestRequest {
+        self.method(Method::GET, path)
     }
 
-    /// Adds a query parameter to be sent on *all* future requests.
-    pub fn add_query_param<V>(&mut self, key: &str, value: V)
-    where
-        V: Serialize,
-    {
-        ServerSharedState::add_query_param(&self.state, key, value)
-            .context("Trying to call add_query_param")
-            .unwrap()
+    /// Creates a HTTP POST request to the given path.
+    pub fn post(&self, path: &str) -> TestRequest {
+        self.method(Method::POST, path)
     }
 
-    /// Adds query parameters to be sent on *all* future requests.
-    pub fn add_query_params<V>(&mut self, query_params: V)
-    where
-        V: Serialize,
-    {
-        ServerSharedState::add_query_params(&self.state, query_params)
-            .context("Trying to call add_query_params")
-            .unwrap()
+    /// Creates a HTTP PATCH request to the path.
+    pub fn patch(&self, path: &str) -> TestRequest {
+        self.method(Method::PATCH, path)
     }
 
-    /// Adds a raw query param, with no urlencoding of any kind,
-    /// to be send on *all* future requests.
-    pub fn add_raw_query_param(&mut self, raw_query_param: &str) {
-        ServerSharedState::add_raw_query_param(&self.state, raw_query_param)
-            .context("Trying to call add_raw_query_param")
-            .unwrap()
+    /// Creates a HTTP PUT request to the path.
+    pub fn put(&self, path: &str) -> TestRequest {
+        self.method(Method::PUT, path)
     }
 
-    /// Clears all query params set.
-    pub fn clear_query_params(&mut self) {
-        ServerSharedState::clear_query_params(&self.state)
-            .context("Trying to call clear_query_params")
-            .unwrap()
+    /// Creates a HTTP DELETE request to the path.
+    pub fn delete(&self, path: &str) -> TestRequest {
+        self.method(Method::DELETE, path)
     }
 
-    /// Adds a header to be sent with all future requests built from this `TestServer`.
+    /// Creates a HTTP request, to the method and path provided.
+    ///
+    /// Paths may contain unicode, which is percent-encoded as UTF-8 before sending,
+    /// such as `/café` being sent as `/caf%C3%A9`.
+    /// Unicode hosts in absolute urls are converted to punycode,
+    /// such as `http://café.example` being sent to `http://xn--caf-dma.example`.
+    /// This is the same for the mock and HTTP transports.
+    #[track_caller]
+    pub fn method(&self, method: Method, path: &str) -> TestRequest {
+        let maybe_config = self.build_test_request_config(method.clone(), path);
+        let config = maybe_config
+            .with_context(|| format!("Failed to build, for request {method} {path}"))
+            .unwrap();
+
+        TestRequest::new(self.state.clone(), self.transport.clone(), config)
+    }
+
+    /// Sends all of the requests given concurrently,
+    /// returning their responses in the same order as the requests.
+    ///
+    /// Each request keeps its own expectations, such as [`TestRequest::expect_success()`],
+    /// and panics as it would when sent alone.
+    /// If a request fails to be sent, this panics with the position of the request.
     ///
     /// ```rust
     /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
     /// #
     /// use axum::Router;
+    /// use axum::routing::get;
     /// use axum_test::TestServer;
     ///
-    /// let app = Router::new();
-    /// let mut server = TestServer::new(app)?;
-    ///
-    /// server.add_header("x-custom-header", "custom-value");
-    /// server.add_header(http::header::CONTENT_LENGTH, 12345);
-    /// server.add_header(http::header::HOST, "example.com");
+    /// let app = Router::new()
+    ///     .route(&"/users", get(|| async { "users" }))
+    ///     .route(&"/posts", get(|| async { "posts" }));
+    /// let server = TestServer::new(app)?;
     ///
-    /// let response = server.get(&"/my-end-point")
+    /// let responses = server
+    ///     .batch([
+    ///         server.get(&"/users"),
+    ///         server.get(&"/posts").expect_success(),
+    ///     ])
     ///     .await;
+    ///
+    /// responses[0].assert_text("users");
+    /// responses[1].assert_text("posts");
     /// #
     /// # Ok(()) }
     /// ```
-    pub fn add_header<N, V>(&mut self, name: N, value: V)
+    pub async fn batch<I>(&self, requests: I) -> Vec<TestResponse>
     where
-        N: TryInto<HeaderName>,
-        N::Error: Debug,
-        V: TryInto<HeaderValue>,
-        V::Error: Debug,
+        I: IntoIterator<Item = TestRequest>,
     {
-        let header_name: HeaderName = name
-            .try_into()
-            .expect("Failed to convert header name to HeaderName");
-        let header_value: HeaderValue = value
-            .try_into()
-            .expect("Failed to convert header vlue to HeaderValue");
+        self.try_batch(requests)
+            .await
+            .into_iter()
+            .map(|result| result.unwrap_or_else(|error| panic!("{error:#}")))
+            .collect()
+    }
 
-        ServerSharedState::add_header(&self.state, header_name, header_value)
-            .context("Trying to call add_header")
-            .unwrap()
+    /// Sends all of the requests given concurrently,
+    /// returning the result of each in the same order as the requests.
+    ///
+    /// This is the same as [`TestServer::batch()`],
+    /// except requests failing to be sent are returned as errors, rather than panicking.
+    pub async fn try_batch<I>(&self, requests: I) -> Vec<Result<TestResponse>>
+    where
+        I: IntoIterator<Item = TestRequest>,
+    {
+        let sends = requests
+            .into_iter()
+            .enumerate()
+            .map(|(index, request)| async move {
+                request
+                    .try_send()
+                    .await
+                    .with_context(|| format!("Batch request at position {index} failed"))
+            })
+            .collect();
+
+        join_all(sends).await
     }
 
-    /// Clears all headers set so far.
-    pub fn clear_headers(&mut self) {
-        ServerSharedState::clear_headers(&self.state)
-            .context("Trying to call clear_headers")
-            .unwrap()
+    /// Creates a [`TestServerScope`], where all paths have the prefix given.
+    ///
+    /// This is for testing apps nested using [`Router::nest()`](::axum::Router::nest()),
+    /// such as under a version prefix like `/api/v1`.
+    /// Requests made through the scope share this server's cookies and headers.
+    pub fn scope(&self, prefix: &str) -> TestServerScope<'_> {
+        TestServerScope::new(self, prefix)
     }
 
-    /// Sets the scheme to use when making _all_ requests from the `TestServer`.
-    /// i.e. http or https.
+    /// Builds a request to send the request which produced the response given again,
+    /// with exactly the same method, url, headers (including the `Idempotency-Key`), and body.
     ///
-    /// The default scheme is 'http'.
+    /// Only requests sent with an idempotency key can be replayed,
+    /// such as those using [`TestRequest::random_idempotency_key()`](crate::TestRequest::random_idempotency_key()).
+    /// This will panic if the request had no idempotency key.
+    ///
+    /// Use [`TestResponse::assert_same_response()`](crate::TestResponse::assert_same_response())
+    /// to check the replayed response matches the original,
+    /// which is useful for testing idempotent endpoints.
     ///
     /// ```rust
     /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
     /// #
     /// use axum::Router;
+    /// use axum::routing::post;
     /// use axum_test::TestServer;
     ///
-    /// let app = Router::new();
-    /// let mut server = TestServer::new(app)?;
-    /// server
-    ///     .scheme(&"https");
+    /// let app = Router::new()
+    ///     .route(&"/payments", post(|| async { "payment made" }));
+    /// let server = TestServer::new(app)?;
     ///
-    /// let response = server
-    ///     .get(&"/my-end-point")
+    /// let response = server.post(&"/payments")
+    ///     .random_idempotency_key()
     ///     .await;
+    ///
+    /// server.replay(&response)
+    ///     .await
+    ///     .assert_same_response(&response);
     /// #
-    /// # Ok(()) }
+    /// # Ok(())
+    /// # }
     /// ```
+    #[track_caller]
+    pub fn replay(&self, response: &TestResponse) -> TestRequest {
+        let method = response.request_method();
+        let url = response.request_url();
+        let debug_request_format = RequestPathFormatter::new(&method, url.as_str(), None);
+        let replayable_request = response
+            .maybe_replayable_request()
+            .with_context(|| {
+                format!("Expected request to have an Idempotency-Key header to be replayed, for request {debug_request_format}")
+            })
+            .unwrap();
+
+        let mut config = self
+            .build_test_request_config(method.clone(), url.as_str())
+            .with_context(|| format!("Failed to build replay, for request {debug_request_format}"))
+            .unwrap();
+
+        config.full_request_url = url.clone();
+        config.query_params = QueryParamsStore::new();
+        config.cookies = CookieJar::new();
+        config.content_type = None;
+        config.maybe_csrf_token = None;
+        config.headers = replayable_request
+            .headers
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+
+        TestRequest::new(self.state.clone(), self.transport.clone(), config)
+            .bytes(replayable_request.body.clone())
+    }
+
+    /// Asserts the path given supports range requests, by requesting its first byte.
     ///
-    pub fn scheme(&mut self, scheme: &str) {
-        ServerSharedState::set_scheme(&self.state, scheme.to_string())
-            .context("Trying to call set_scheme")
-            .unwrap()
+    /// This sends a `GET` request with a `Range: bytes=0-0` header,
+    /// and expects a `206 Partial Content` response holding that one byte.
+    /// It is for testing static files, such as those served using `tower_http::services::ServeDir`.
+    pub async fn assert_supports_range_requests(&self, path: &str) {
+        self.check_supports_range_requests(path).await.or_panic()
     }
 
-    pub(crate) fn url(&self) -> Option<Url> {
-        self.transport.url().cloned()
+    /// Checks the path given supports range requests, by requesting its first byte.
+    ///
+    /// This is the non-panicking version of [`TestServer::assert_supports_range_requests()`].
+    pub async fn check_supports_range_requests(&self, path: &str) -> Result<(), AssertionError> {
+        let response = self
+            .get(path)
+            .expect_state(ExpectedState::None)
+            .add_header(header::RANGE, HeaderValue::from_static("bytes=0-0"))
+            .try_send()
+            .await?;
+        response.check_status(StatusCode::PARTIAL_CONTENT)?;
+
+        let request_url = response.request_url();
+        let debug_request_format =
+            RequestPathFormatter::new(&Method::GET, request_url.as_str(), None);
+        let content_range = response
+            .maybe_header(header::CONTENT_RANGE)
+            .and_then(|content_range| content_range.to_str().ok().map(ToString::to_string))
+            .unwrap_or_default();
+        check(
+            content_range.starts_with("bytes 0-0/"),
+            format_args!("Expected range response to have Content-Range 'bytes 0-0/...', received '{content_range}', for request {debug_request_format}"),
+        )?;
+
+        let body_size = response.as_bytes().len();
+        check(
+            body_size == 1,
+            format_args!("Expected range response to hold 1 byte, received {body_size} bytes, for request {debug_request_format}"),
+        )
     }
 
-    pub(crate) fn build_test_request_config(
+    /// Asserts the path given is served precompressed with the encoding given (such as `br` or `gzip`),
+    /// when the client accepts it.
+    ///
+    /// This sends a `GET` request with an `Accept-Encoding` header for the encoding,
+    /// and expects a successful response with a matching `Content-Encoding`.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum_test::TestServer;
+    /// use tower_http::services::ServeDir;
+    ///
+    /// let app = Router::new()
+    ///     .nest_service("/assets", ServeDir::new("assets").precompressed_br());
+    /// let server = TestServer::new(app)?;
+    ///
+    /// server.assert_serves_precompressed("/assets/app.js", "br").await;
+    /// #
+    /// # Ok(()) }
+    /// ```
+    pub async fn assert_serves_precompressed(&self, path: &str, encoding: &str) {
+        self.check_serves_precompressed(path, encoding)
+            .await
+            .or_panic()
+    }
+
+    /// Checks the path given is served precompressed with the encoding given.
+    ///
+    /// This is the non-panicking version of [`TestServer::assert_serves_precompressed()`].
+    pub async fn check_serves_precompressed(
         &self,
-        method: Method,
         path: &str,
-    ) -> Result<TestRequestConfig> {
-        let url = self
-            .url()
-            .unwrap_or_else(|| DEFAULT_URL_ADDRESS.parse().unwrap());
+        encoding: &str,
+    ) -> Result<(), AssertionError> {
+        let encoding_header = HeaderValue::from_str(encoding)
+            .with_context(|| format!("Failed to build Accept-Encoding header from '{encoding}'"))?;
+        let response = self
+            .get(path)
+            .expect_state(ExpectedState::None)
+            .add_header(header::ACCEPT_ENCODING, encoding_header.clone())
+            .try_send()
+            .await?;
+        response.check_status_success()?;
+        response.check_header(header::CONTENT_ENCODING, encoding_header)
+    }
+
+    /// Asserts the path given rejects the method given with a `405 Method Not Allowed`,
+    /// and the response has an `Allow` header which does not list that method.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum::http::Method;
+    /// use axum::routing::get;
+    /// use axum_test::TestServer;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/users", get(|| async { "users" }));
+    /// let server = TestServer::new(app)?;
+    ///
+    /// server.assert_method_not_allowed(&"/users", Method::DELETE).await;
+    /// #
+    /// # Ok(()) }
+    /// ```
+    pub async fn assert_method_not_allowed(&self, path: &str, method: Method) {
+        self.check_method_not_allowed(path, method).await.or_panic()
+    }
 
-        let server_locked = self.state.as_ref().lock().map_err(|err| {
-            anyhow!(
-                "Failed to lock InternalTestServer, for request {method} {path}, received {err:?}",
-            )
-        })?;
+    /// Checks the path given rejects the method given with a `405 Method Not Allowed`,
+    /// and the response has an `Allow` header which does not list that method.
+    ///
+    /// This is the non-panicking version of [`TestServer::assert_method_not_allowed()`].
+    pub async fn check_method_not_allowed(
+        &self,
+        path: &str,
+        method: Method,
+    ) -> Result<(), AssertionError> {
+        let response = self
+            .method(method.clone(), path)
+            .expect_state(ExpectedState::None)
+            .try_send()
+            .await?;
+        response.check_status(StatusCode::METHOD_NOT_ALLOWED)?;
+
+        let request_url = response.request_url();
+        let debug_request_format = RequestPathFormatter::new(&method, request_url.as_str(), None);
+        let allow = response
+            .maybe_header(header::ALLOW)
+            .and_then(|allow| allow.to_str().ok().map(ToString::to_string))
+            .ok_or_else(|| {
+                AssertionError::new(format!(
+                    "Expected 405 response to have an Allow header, none found, for request {debug_request_format}"
+                ))
+            })?;
 
-        let cookies = server_locked.cookies().clone();
-        let mut query_params = server_locked.query_params().clone();
-        let headers = server_locked.headers().clone();
-        let mut full_request_url =
-            build_url(url, path, &mut query_params, self.is_http_path_restricted)?;
+        let is_method_allowed = allow
+            .split(',')
+            .any(|allowed| allowed.trim().eq_ignore_ascii_case(method.as_str()));
+        check(
+            !is_method_allowed,
+            format_args!("Expected Allow header to not list {method}, received '{allow}', for request {debug_request_format}"),
+        )
+    }
 
-        if let Some(scheme) = server_locked.scheme() {
-            full_request_url.set_scheme(scheme).map_err(|_| {
-                let debug_request_format = RequestPathFormatter::new(&method, full_request_url.as_str(), Some(&query_params));
-                anyhow!("Scheme '{scheme}' from TestServer cannot be set to request {debug_request_format}")
-            })?;
+    /// Sends every standard method to the path given,
+    /// and returns the status code received for each one.
+    ///
+    /// The methods sent are `GET`, `HEAD`, `POST`, `PUT`, `PATCH`, `DELETE`, `OPTIONS`, and `TRACE`.
+    /// Requests are sent without a body, and the status codes are not asserted.
+    /// This is for checking the fallback behaviour of a resource in one go.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum::http::Method;
+    /// use axum::http::StatusCode;
+    /// use axum::routing::get;
+    /// use axum_test::TestServer;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/users", get(|| async { "users" }));
+    /// let server = TestServer::new(app)?;
+    ///
+    /// let statuses = server.probe_unsupported_methods(&"/users").await;
+    /// assert_eq!(statuses[&Method::GET], StatusCode::OK);
+    /// assert_eq!(statuses[&Method::DELETE], StatusCode::METHOD_NOT_ALLOWED);
+    /// #
+    /// # Ok(()) }
+    /// ```
+    pub async fn probe_unsupported_methods(&self, path: &str) -> HashMap<Method, StatusCode> {
+        let mut statuses = HashMap::with_capacity(PROBED_METHODS.len());
+
+        for method in PROBED_METHODS {
+            let status_code = self
+                .method(method.clone(), path)
+                .expect_state(ExpectedState::None)
+                .await
+                .status_code();
+            statuses.insert(method, status_code);
         }
 
-        ::std::mem::drop(server_locked);
+        statuses
+    }
 
-        Ok(TestRequestConfig {
-            is_saving_cookies: self.save_cookies,
-            expected_state: self.expected_state,
-            content_type: self.default_content_type.clone(),
-            method,
+    /// Sends a GET request to the path given twice, for testing caching.
+    ///
+    /// If the first response has an `ETag` or `Last-Modified` header,
+    /// then the second request sends it back as `If-None-Match` or `If-Modified-Since`.
+    /// The status codes are not asserted.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum::routing::get;
+    /// use axum_test::TestServer;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/users", get(|| async { ([("x-cache", "HIT")], "users") }));
+    /// let server = TestServer::new(app)?;
+    ///
+    /// server.get_twice(&"/users").await.assert_second_is_cache_hit();
+    /// #
+    /// # Ok(()) }
+    /// ```
+    pub async fn get_twice(&self, path: &str) -> ResponsePair {
+        let first = self.get(path).expect_state(ExpectedState::None).await;
 
-            full_request_url,
-            cookies,
-            query_params,
-            headers,
-        })
+        let mut second_request = self.get(path).expect_state(ExpectedState::None);
+        if let Some(etag) = first.maybe_header(header::ETAG) {
+            second_request = second_request.add_header(header::IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = first.maybe_header(header::LAST_MODIFIED) {
+            second_request = second_request.add_header(header::IF_MODIFIED_SINCE, last_modified);
+        }
+        let second = second_request.await;
+
+        ResponsePair::new(first, second)
     }
 
-    /// Returns true or false if the underlying service inside the `TestServer`
-    /// is still running. For many types of services this will always return `true`.
+    /// Sends a `GET` request to the path given `n` times, one every `interval`,
+    /// and returns all of the responses. The status codes are not asserted.
     ///
-    /// When a `TestServer` is built using [`axum::serve::WithGracefulShutdown`],
-    /// this will return false if the service has shutdown.
-    pub fn is_running(&self) -> bool {
-        self.transport.is_running()
+    /// This is for testing rate limiting.
+    /// Requests are sent at a fixed schedule from when the burst started,
+    /// using Tokio's clock. When time is paused in Tokio
+    /// (i.e. `#[tokio::test(start_paused = true)]`) the burst will not wait in real time.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// # use axum::Router;
+    /// use axum_test::TestServer;
+    /// use std::time::Duration;
+    ///
+    /// # let app = Router::new();
+    /// let server = TestServer::new(app)?;
+    ///
+    /// server
+    ///     .burst(&"/api/search", 20, Duration::from_millis(100))
+    ///     .await
+    ///     .assert_statuses_eventually_contain(429);
+    /// #
+    /// # Ok(()) }
+    /// ```
+    pub async fn burst(&self, path: &str, n: usize, interval: StdDuration) -> ResponseBurst {
+        let started_at = Instant::now();
+        let mut responses = Vec::with_capacity(n);
+
+        for i in 0..n {
+            if i > 0 {
+                sleep_until(started_at + interval * i as u32).await;
+            }
+
+            let response = self.get(path).expect_state(ExpectedState::None).await;
+            responses.push(response);
+        }
+
+        ResponseBurst::new(responses)
     }
-}
 
-fn build_url(
-    mut url: Url,
-    path: &str,
-    query_params: &mut QueryParamsStore,
-    is_http_restricted: bool,
-) -> Result<Url> {
-    let path_uri = path.parse::<Uri>()?;
+    /// Creates a [`HeaderMatrix`](crate::HeaderMatrix), for sending a `GET` request to the path given
+    /// with every combination of a set of header values.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// # use axum::Router;
+    /// use axum_test::TestServer;
+    ///
+    /// # let app = Router::new();
+    /// let server = TestServer::new(app)?;
+    ///
+    /// server
+    ///     .matrix(&"/greeting")
+    ///     .vary_header("accept", ["application/json", "text/html"])
+    ///     .vary_header("accept-language", ["en", "de"])
+    ///     .run(|response, _combination| {
+    ///         response.assert_status_ok();
+    ///     })
+    ///     .await;
+    /// #
+    /// # Ok(()) }
+    /// ```
+    pub fn matrix(&self, path: &str) -> HeaderMatrix<'_> {
+        HeaderMatrix::new(self, path)
+    }
 
-    // If there is a scheme, then this is an absolute path.
-    if let Some(scheme) = path_uri.scheme_str() {
-        if is_http_restricted {
-            if has_different_schema(&url, &path_uri) || has_different_authority(&url, &path_uri) {
-                return Err(anyhow!("Request disallowed for path '{path}', requests are only allowed to local server. Turn off 'restrict_requests_with_http_schema' to change this."));
-            }
-        } else {
-            url.set_scheme(scheme)
-                .map_err(|_| anyhow!("Failed to set scheme for request, with path '{path}'"))?;
+    /// Creates an upload using the [tus resumable upload protocol](https://tus.io/protocols/resumable-upload),
+    /// which sends the bytes given to the path in chunks when awaited.
+    ///
+    /// See [`TusUpload`](crate::TusUpload) for details.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// # use axum::Router;
+    /// use axum_test::TestServer;
+    ///
+    /// # let app = Router::new();
+    /// let server = TestServer::new(app)?;
+    ///
+    /// server
+    ///     .tus_upload(&"/files", b"hello world".to_vec())
+    ///     .chunk_size(4)
+    ///     .with_checksums()
+    ///     .await
+    ///     .assert_complete();
+    /// #
+    /// # Ok(()) }
+    /// ```
+    #[cfg(feature = "tus")]
+    pub fn tus_upload<B>(&self, path: &str, bytes: B) -> TusUpload<'_>
+    where
+        B: Into<Bytes>,
+    {
+        TusUpload::new(self, path, bytes.into())
+    }
+
+    /// Returns the Reqwest client used by [`TestServer::reqwest_get()`](crate::TestServer::reqwest_get())
+    /// and the other Reqwest methods.
+    ///
+    /// This can be changed using [`TestServerBuilder::configure_reqwest()`](crate::TestServerBuilder::configure_reqwest()).
+    ///
+    /// This will panic if the server is not running with HTTP transport.
+    #[cfg(feature = "reqwest")]
+    #[track_caller]
+    pub fn reqwest_client(&self) -> &Client {
+        self.maybe_reqwest_client
+            .as_ref()
+            .expect("Reqwest client is not available, TestServer must be build with HTTP transport for Reqwest to be available")
+    }
+
+    #[cfg(feature = "reqwest")]
+    pub fn reqwest_get(&self, path: &str) -> RequestBuilder {
+        self.reqwest_method(Method::GET, path)
+    }
+
+    #[cfg(feature = "reqwest")]
+    pub fn reqwest_post(&self, path: &str) -> RequestBuilder {
+        self.reqwest_method(Method::POST, path)
+    }
+
+    #[cfg(feature = "reqwest")]
+    pub fn reqwest_put(&self, path: &str) -> RequestBuilder {
+        self.reqwest_method(Method::PUT, path)
+    }
+
+    #[cfg(feature = "reqwest")]
+    pub fn reqwest_patch(&self, path: &str) -> RequestBuilder {
+        self.reqwest_method(Method::PATCH, path)
+    }
+
+    #[cfg(feature = "reqwest")]
+    pub fn reqwest_delete(&self, path: &str) -> RequestBuilder {
+        self.reqwest_method(Method::DELETE, path)
+    }
+
+    #[cfg(feature = "reqwest")]
+    pub fn reqwest_head(&self, path: &str) -> RequestBuilder {
+        self.reqwest_method(Method::HEAD, path)
+    }
+
+    /// Creates a HTTP request, using Reqwest, using the method + path described.
+    /// This expects a relative url to the `TestServer`.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum_test::TestServer;
+    ///
+    /// let my_app = Router::new();
+    /// let server = TestServer::builder()
+    ///     .http_transport() // Important, must be HTTP!
+    ///     .build(my_app)?;
+    ///
+    /// // Build your request
+    /// let request = server.get(&"/user")
+    ///     .add_header("x-custom-header", "example.com")
+    ///     .content_type("application/yaml");
+    ///
+    /// // await request to execute
+    /// let response = request.await;
+    /// #
+    /// # Ok(()) }
+    /// ```
+    #[cfg(feature = "reqwest")]
+    #[track_caller]
+    pub fn reqwest_method(&self, method: Method, path: &str) -> RequestBuilder {
+        let request_url = self
+            .server_url(path)
+            .expect("Failed to generate server url for request {method} {path}");
+
+        self.reqwest_client().request(method, request_url)
+    }
+
+    /// Stands in for the Reqwest methods when built with `dyn-features`, without the `reqwest` feature.
+    ///
+    /// This always returns an [`Error::MissingFeature`](crate::Error::MissingFeature),
+    /// as the Reqwest types are not available.
+    #[cfg(all(feature = "dyn-features", not(feature = "reqwest")))]
+    pub fn reqwest_client(&self) -> Result<Infallible, Error> {
+        Err(crate::internals::missing_feature_error(
+            "TestServer::reqwest_client",
+            "reqwest",
+        ))
+    }
+
+    #[cfg(all(feature = "dyn-features", not(feature = "reqwest")))]
+    pub fn reqwest_get(&self, path: &str) -> Result<Infallible, Error> {
+        self.reqwest_method(Method::GET, path)
+    }
+
+    #[cfg(all(feature = "dyn-features", not(feature = "reqwest")))]
+    pub fn reqwest_post(&self, path: &str) -> Result<Infallible, Error> {
+        self.reqwest_method(Method::POST, path)
+    }
+
+    #[cfg(all(feature = "dyn-features", not(feature = "reqwest")))]
+    pub fn reqwest_put(&self, path: &str) -> Result<Infallible, Error> {
+        self.reqwest_method(Method::PUT, path)
+    }
+
+    #[cfg(all(feature = "dyn-features", not(feature = "reqwest")))]
+    pub fn reqwest_patch(&self, path: &str) -> Result<Infallible, Error> {
+        self.reqwest_method(Method::PATCH, path)
+    }
+
+    #[cfg(all(feature = "dyn-features", not(feature = "reqwest")))]
+    pub fn reqwest_delete(&self, path: &str) -> Result<Infallible, Error> {
+        self.reqwest_method(Method::DELETE, path)
+    }
+
+    #[cfg(all(feature = "dyn-features", not(feature = "reqwest")))]
+    pub fn reqwest_head(&self, path: &str) -> Result<Infallible, Error> {
+        self.reqwest_method(Method::HEAD, path)
+    }
+
+    /// Stands in for `reqwest_method` when built with `dyn-features`, without the `reqwest` feature.
+    ///
+    /// This always returns an [`Error::MissingFeature`](crate::Error::MissingFeature),
+    /// as the Reqwest types are not available.
+    #[cfg(all(feature = "dyn-features", not(feature = "reqwest")))]
+    pub fn reqwest_method(&self, method: Method, path: &str) -> Result<Infallible, Error> {
+        let _ = (method, path);
+
+        Err(crate::internals::missing_feature_error(
+            "TestServer::reqwest_method",
+            "reqwest",
+        ))
+    }
+
+    /// Creates a request to the server, to start a Websocket connection,
+    /// on the path given.
+    ///
+    /// This is the requivalent of making a GET request to the endpoint,
+    /// and setting the various headers needed for making an upgrade request.
+    ///
+    /// *Note*, this requires the server to be running on a real HTTP
+    /// port. Either using a randomly assigned port, or a specified one.
+    /// See the [`TestServerConfig::transport`](crate::TestServerConfig::transport) for more details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum_test::TestServer;
+    ///
+    /// let app = Router::new();
+    /// let server = TestServer::builder()
+    ///     .http_transport()
+    ///     .build(app)?;
+    ///
+    /// let mut websocket = server
+    ///     .get_websocket(&"/my-web-socket-end-point")
+    ///     .await
+    ///     .into_websocket()
+    ///     .await;
+    ///
+    /// websocket.send_text("Hello!").await;
+    /// #
+    /// # Ok(()) }
+    /// ```
+    ///
+    #[cfg(feature = "ws")]
+    pub fn get_websocket(&self, path: &str) -> TestRequest {
+        use http::header;
+
+        self.get(path)
+            .add_header(header::CONNECTION, "upgrade")
+            .add_header(header::UPGRADE, "websocket")
+            .add_header(header::SEC_WEBSOCKET_VERSION, "13")
+            .add_header(header::SEC_WEBSOCKET_KEY, self.generate_ws_key())
+    }
+
+    /// Stands in for `get_websocket` when built with `dyn-features`, without the `ws` feature.
+    ///
+    /// Sending the request returns an [`Error::MissingFeature`](crate::Error::MissingFeature).
+    #[cfg(all(feature = "dyn-features", not(feature = "ws")))]
+    pub fn get_websocket(&self, path: &str) -> TestRequest {
+        self.get(path)
+            .with_missing_feature("TestServer::get_websocket", "ws")
+    }
+
+    #[cfg(feature = "ws")]
+    fn generate_ws_key(&self) -> String {
+        let mut rng = self
+            .rng
+            .lock()
+            .expect("Failed to lock random number generator, for WebSocket key");
+
+        crate::internals::generate_ws_key(&mut rng)
+    }
+
+    /// Creates a HTTP GET request, using the typed path provided.
+    ///
+    /// See [`axum-extra`](https://docs.rs/axum-extra) for full documentation on [`TypedPath`](axum_extra::routing::TypedPath).
+    ///
+    /// # Example Test
+    ///
+    /// Using a `TypedPath` you can write build and test a route like below:
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Json;
+    /// use axum::Router;
+    /// use axum::routing::get;
+    /// use axum_extra::routing::RouterExt;
+    /// use axum_extra::routing::TypedPath;
+    /// use serde::Deserialize;
+    /// use serde::Serialize;
+    ///
+    /// use axum_test::TestServer;
+    ///
+    /// #[derive(TypedPath, Deserialize)]
+    /// #[typed_path("/users/:user_id")]
+    /// struct UserPath {
+    ///     pub user_id: u32,
+    /// }
+    ///
+    /// // Build a typed route:
+    /// async fn route_get_user(UserPath { user_id }: UserPath) -> String {
+    ///     format!("hello user {user_id}")
+    /// }
+    ///
+    /// let app = Router::new()
+    ///     .typed_get(route_get_user);
+    ///
+    /// // Then test the route:
+    /// let server = TestServer::new(app)?;
+    /// server
+    ///     .typed_get(&UserPath { user_id: 123 })
+    ///     .await
+    ///     .assert_text("hello user 123");
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    #[cfg(feature = "typed-routing")]
+    pub fn typed_get<P>(&self, path: &P) -> TestRequest
+    where
+        P: TypedPath,
+    {
+        self.typed_method(Method::GET, path)
+    }
+
+    /// Creates a HTTP POST request, using the typed path provided.
+    ///
+    /// See [`axum-extra`](https://docs.rs/axum-extra) for full documentation on [`TypedPath`](axum_extra::routing::TypedPath).
+    #[cfg(feature = "typed-routing")]
+    pub fn typed_post<P>(&self, path: &P) -> TestRequest
+    where
+        P: TypedPath,
+    {
+        self.typed_method(Method::POST, path)
+    }
+
+    /// Creates a HTTP PATCH request, using the typed path provided.
+    ///
+    /// See [`axum-extra`](https://docs.rs/axum-extra) for full documentation on [`TypedPath`](axum_extra::routing::TypedPath).
+    #[cfg(feature = "typed-routing")]
+    pub fn typed_patch<P>(&self, path: &P) -> TestRequest
+    where
+        P: TypedPath,
+    {
+        self.typed_method(Method::PATCH, path)
+    }
+
+    /// Creates a HTTP PUT request, using the typed path provided.
+    ///
+    /// See [`axum-extra`](https://docs.rs/axum-extra) for full documentation on [`TypedPath`](axum_extra::routing::TypedPath).
+    #[cfg(feature = "typed-routing")]
+    pub fn typed_put<P>(&self, path: &P) -> TestRequest
+    where
+        P: TypedPath,
+    {
+        self.typed_method(Method::PUT, path)
+    }
+
+    /// Creates a HTTP DELETE request, using the typed path provided.
+    ///
+    /// See [`axum-extra`](https://docs.rs/axum-extra) for full documentation on [`TypedPath`](axum_extra::routing::TypedPath).
+    #[cfg(feature = "typed-routing")]
+    pub fn typed_delete<P>(&self, path: &P) -> TestRequest
+    where
+        P: TypedPath,
+    {
+        self.typed_method(Method::DELETE, path)
+    }
+
+    /// Creates a typed HTTP request, using the method provided.
+    ///
+    /// See [`axum-extra`](https://docs.rs/axum-extra) for full documentation on [`TypedPath`](axum_extra::routing::TypedPath).
+    #[cfg(feature = "typed-routing")]
+    pub fn typed_method<P>(&self, method: Method, path: &P) -> TestRequest
+    where
+        P: TypedPath,
+    {
+        self.method(method, &path.to_string())
+    }
+
+    #[cfg(all(feature = "dyn-features", not(feature = "typed-routing")))]
+    pub fn typed_get<P>(&self, path: &P) -> TestRequest
+    where
+        P: ::std::fmt::Display,
+    {
+        self.typed_method(Method::GET, path)
+    }
+
+    #[cfg(all(feature = "dyn-features", not(feature = "typed-routing")))]
+    pub fn typed_post<P>(&self, path: &P) -> TestRequest
+    where
+        P: ::std::fmt::Display,
+    {
+        self.typed_method(Method::POST, path)
+    }
+
+    #[cfg(all(feature = "dyn-features", not(feature = "typed-routing")))]
+    pub fn typed_patch<P>(&self, path: &P) -> TestRequest
+    where
+        P: ::std::fmt::Display,
+    {
+        self.typed_method(Method::PATCH, path)
+    }
+
+    #[cfg(all(feature = "dyn-features", not(feature = "typed-routing")))]
+    pub fn typed_put<P>(&self, path: &P) -> TestRequest
+    where
+        P: ::std::fmt::Display,
+    {
+        self.typed_method(Method::PUT, path)
+    }
+
+    #[cfg(all(feature = "dyn-features", not(feature = "typed-routing")))]
+    pub fn typed_delete<P>(&self, path: &P) -> TestRequest
+    where
+        P: ::std::fmt::Display,
+    {
+        self.typed_method(Method::DELETE, path)
+    }
+
+    /// Stands in for `typed_method` when built with `dyn-features`, without the `typed-routing` feature.
+    ///
+    /// Sending the request returns an [`Error::MissingFeature`](crate::Error::MissingFeature).
+    #[cfg(all(feature = "dyn-features", not(feature = "typed-routing")))]
+    pub fn typed_method<P>(&self, method: Method, path: &P) -> TestRequest
+    where
+        P: ::std::fmt::Display,
+    {
+        self.method(method, &path.to_string())
+            .with_missing_feature("TestServer::typed_method", "typed-routing")
+    }
+
+    #[cfg(all(
+        feature = "dyn-features",
+        not(all(feature = "typed-routing", feature = "ws"))
+    ))]
+    pub fn typed_get_websocket<P>(&self, path: &P) -> TestRequest
+    where
+        P: ::std::fmt::Display,
+    {
+        let missing_feature = if cfg!(feature = "ws") {
+            "typed-routing"
+        } else {
+            "ws"
+        };
+
+        self.get(&path.to_string())
+            .with_missing_feature("TestServer::typed_get_websocket", missing_feature)
+    }
+
+    /// Creates a request to the server, to start a Websocket connection,
+    /// on the typed path provided.
+    ///
+    /// See [`TestServer::get_websocket()`] for more details.
+    #[cfg(all(feature = "typed-routing", feature = "ws"))]
+    pub fn typed_get_websocket<P>(&self, path: &P) -> TestRequest
+    where
+        P: TypedPath,
+    {
+        self.get_websocket(&path.to_string())
+    }
+
+    /// Creates a HTTP GET request, using Reqwest, to the typed path provided.
+    #[cfg(all(
+        feature = "dyn-features",
+        not(all(feature = "typed-routing", feature = "reqwest"))
+    ))]
+    pub fn typed_reqwest_get<P>(&self, path: &P) -> Result<Infallible, Error>
+    where
+        P: ::std::fmt::Display,
+    {
+        self.typed_reqwest_method(Method::GET, path)
+    }
+
+    #[cfg(all(
+        feature = "dyn-features",
+        not(all(feature = "typed-routing", feature = "reqwest"))
+    ))]
+    pub fn typed_reqwest_post<P>(&self, path: &P) -> Result<Infallible, Error>
+    where
+        P: ::std::fmt::Display,
+    {
+        self.typed_reqwest_method(Method::POST, path)
+    }
+
+    #[cfg(all(
+        feature = "dyn-features",
+        not(all(feature = "typed-routing", feature = "reqwest"))
+    ))]
+    pub fn typed_reqwest_put<P>(&self, path: &P) -> Result<Infallible, Error>
+    where
+        P: ::std::fmt::Display,
+    {
+        self.typed_reqwest_method(Method::PUT, path)
+    }
+
+    #[cfg(all(
+        feature = "dyn-features",
+        not(all(feature = "typed-routing", feature = "reqwest"))
+    ))]
+    pub fn typed_reqwest_patch<P>(&self, path: &P) -> Result<Infallible, Error>
+    where
+        P: ::std::fmt::Display,
+    {
+        self.typed_reqwest_method(Method::PATCH, path)
+    }
+
+    #[cfg(all(
+        feature = "dyn-features",
+        not(all(feature = "typed-routing", feature = "reqwest"))
+    ))]
+    pub fn typed_reqwest_delete<P>(&self, path: &P) -> Result<Infallible, Error>
+    where
+        P: ::std::fmt::Display,
+    {
+        self.typed_reqwest_method(Method::DELETE, path)
+    }
+
+    #[cfg(all(
+        feature = "dyn-features",
+        not(all(feature = "typed-routing", feature = "reqwest"))
+    ))]
+    pub fn typed_reqwest_head<P>(&self, path: &P) -> Result<Infallible, Error>
+    where
+        P: ::std::fmt::Display,
+    {
+        self.typed_reqwest_method(Method::HEAD, path)
+    }
+
+    /// Stands in for `typed_reqwest_method` when built with `dyn-features`,
+    /// without the `typed-routing` or `reqwest` features.
+    ///
+    /// This always returns an [`Error::MissingFeature`](crate::Error::MissingFeature),
+    /// as the Reqwest types are not available.
+    #[cfg(all(
+        feature = "dyn-features",
+        not(all(feature = "typed-routing", feature = "reqwest"))
+    ))]
+    pub fn typed_reqwest_method<P>(&self, method: Method, path: &P) -> Result<Infallible, Error>
+    where
+        P: ::std::fmt::Display,
+    {
+        let _ = (method, path);
+        let missing_feature = if cfg!(feature = "reqwest") {
+            "typed-routing"
+        } else {
+            "reqwest"
+        };
+
+        Err(crate::internals::missing_feature_error(
+            "TestServer::typed_reqwest_method",
+            missing_feature,
+        ))
+    }
+
+    #[cfg(all(feature = "typed-routing", feature = "reqwest"))]
+    pub fn typed_reqwest_get<P>(&self, path: &P) -> RequestBuilder
+    where
+        P: TypedPath,
+    {
+        self.typed_reqwest_method(Method::GET, path)
+    }
+
+    /// Creates a HTTP POST request, using Reqwest, to the typed path provided.
+    #[cfg(all(feature = "typed-routing", feature = "reqwest"))]
+    pub fn typed_reqwest_post<P>(&self, path: &P) -> RequestBuilder
+    where
+        P: TypedPath,
+    {
+        self.typed_reqwest_method(Method::POST, path)
+    }
+
+    /// Creates a HTTP PUT request, using Reqwest, to the typed path provided.
+    #[cfg(all(feature = "typed-routing", feature = "reqwest"))]
+    pub fn typed_reqwest_put<P>(&self, path: &P) -> RequestBuilder
+    where
+        P: TypedPath,
+    {
+        self.typed_reqwest_method(Method::PUT, path)
+    }
+
+    /// Creates a HTTP PATCH request, using Reqwest, to the typed path provided.
+    #[cfg(all(feature = "typed-routing", feature = "reqwest"))]
+    pub fn typed_reqwest_patch<P>(&self, path: &P) -> RequestBuilder
+    where
+        P: TypedPath,
+    {
+        self.typed_reqwest_method(Method::PATCH, path)
+    }
+
+    /// Creates a HTTP DELETE request, using Reqwest, to the typed path provided.
+    #[cfg(all(feature = "typed-routing", feature = "reqwest"))]
+    pub fn typed_reqwest_delete<P>(&self, path: &P) -> RequestBuilder
+    where
+        P: TypedPath,
+    {
+        self.typed_reqwest_method(Method::DELETE, path)
+    }
+
+    /// Creates a HTTP HEAD request, using Reqwest, to the typed path provided.
+    #[cfg(all(feature = "typed-routing", feature = "reqwest"))]
+    pub fn typed_reqwest_head<P>(&self, path: &P) -> RequestBuilder
+    where
+        P: TypedPath,
+    {
+        self.typed_reqwest_method(Method::HEAD, path)
+    }
+
+    /// Creates a HTTP request, using Reqwest, using the method and typed path provided.
+    ///
+    /// See [`TestServer::reqwest_method()`] for more details.
+    #[cfg(all(feature = "typed-routing", feature = "reqwest"))]
+    pub fn typed_reqwest_method<P>(&self, method: Method, path: &P) -> RequestBuilder
+    where
+        P: TypedPath,
+    {
+        self.reqwest_method(method, &path.to_string())
+    }
+
+    /// Returns the local web address for the test server,
+    /// if an address is available.
+    ///
+    /// The address is available when running as a real web server,
+    /// by setting the [`TestServerConfig`](crate::TestServerConfig) `transport` field to `Transport::HttpRandomPort` or `Transport::HttpIpPort`.
+    ///
+    /// This will return `None` when there is mock HTTP transport (the default).
+    pub fn server_address(&self) -> Option<Url> {
+        self.url()
+    }
+
+    /// This turns a relative path, into an absolute path to the server.
+    /// i.e. A path like `/users/123` will become something like `http://127.0.0.1:1234/users/123`.
+    ///
+    /// The absolute address can be used to make requests to the running server,
+    /// using any appropriate client you wish.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum_test::TestServer;
+    ///
+    /// let app = Router::new();
+    /// let server = TestServer::builder()
+    ///         .http_transport()
+    ///         .build(app)?;
+    ///
+    /// let full_url = server.server_url(&"/users/123?filter=enabled")?;
+    ///
+    /// // Prints something like ... http://127.0.0.1:1234/users/123?filter=enabled
+    /// println!("{full_url}");
+    /// #
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// This will return an error if you are using the mock transport.
+    /// Real HTTP transport is required to use this method (see [`TestServerConfig`](crate::TestServerConfig) `transport` field).
+    ///
+    /// It will also return an error if you provide an absolute path,
+    /// for example if you pass in `http://google.com`.
+    ///
+    /// Unicode paths are percent-encoded in the same way as requests,
+    /// so `/café` becomes `/caf%C3%A9`.
+    pub fn server_url(&self, path: &str) -> Result<Url, Error> {
+        let path = encode_unicode_url(path);
+        let path = path.as_ref();
+        let path_uri = parse_path_uri(path)?;
+        if is_absolute_uri(&path_uri) {
+            return Err(Error::InvalidUrl {
+                url: path.to_string(),
+                message:
+                    "absolute path provided for building server url, need to provide a relative uri"
+                        .to_string(),
+            });
+        }
+
+        let server_url = self.url().ok_or(Error::NoServerAddress)?;
+
+        let server_locked = self.state.as_ref().lock().map_err(|err| {
+            Error::Other(anyhow!(
+                "Failed to lock InternalTestServer, for building server_url, received {err:?}",
+            ))
+        })?;
+        let mut query_params = server_locked.query_params().clone();
+        let mut full_server_url = build_url(
+            server_url,
+            path,
+            &mut query_params,
+            self.is_http_path_restricted,
+        )?;
+
+        // Ensure the query params are present
+        if query_params.has_content() {
+            full_server_url.set_query(Some(&query_params.to_string()));
+        }
+
+        Ok(full_server_url)
+    }
+
+    /// Adds a single cookie to be included on *all* future requests.
+    ///
+    /// If a cookie with the same name already exists,
+    /// then it will be replaced.
+    #[track_caller]
+    pub fn add_cookie(&mut self, cookie: Cookie) {
+        ServerSharedState::add_cookie(&self.state, cookie)
+            .context("Trying to call add_cookie")
+            .unwrap()
+    }
+
+    /// Adds extra cookies to be used on *all* future requests.
+    ///
+    /// Any cookies which have the same name as the new cookies,
+    /// will get replaced.
+    #[track_caller]
+    pub fn add_cookies(&mut self, cookies: CookieJar) {
+        ServerSharedState::add_cookies(&self.state, cookies)
+            .context("Trying to call add_cookies")
+            .unwrap()
+    }
+
+    /// Clears all of the cookies stored internally.
+    #[track_caller]
+    pub fn clear_cookies(&mut self) {
+        ServerSharedState::clear_cookies(&self.state)
+            .context("Trying to call clear_cookies")
+            .unwrap()
+    }
+
+    /// Returns a copy of all cookies currently stored on this `TestServer`,
+    /// including any which have expired.
+    ///
+    /// Changes to the copy returned are not stored back onto the server.
+    #[track_caller]
+    #[must_use]
+    pub fn cookies_snapshot(&self) -> CookieJar {
+        ServerSharedState::cookies_snapshot(&self.state)
+            .context("Trying to call cookies_snapshot")
+            .unwrap()
+    }
+
+    /// Expires the stored cookie with the name given,
+    /// as though its expiry time has passed.
+    ///
+    /// It stays stored, but will no longer be sent on future requests.
+    /// This is useful for testing what happens when a session runs out.
+    ///
+    /// # Panics
+    ///
+    /// If no cookie with that name is stored.
+    #[track_caller]
+    pub fn expire_cookie(&mut self, name: &str) {
+        let now = OffsetDateTime::now_utc();
+
+        ServerSharedState::update_cookie(&self.state, name, |cookie| cookie.set_expires(now))
+            .context("Trying to call expire_cookie")
+            .unwrap()
+    }
+
+    /// Replaces the value of the stored cookie with the name given,
+    /// keeping its other attributes.
+    ///
+    /// This is useful for testing how the application handles tampered cookies.
+    ///
+    /// # Panics
+    ///
+    /// If no cookie with that name is stored.
+    #[track_caller]
+    pub fn set_cookie_value(&mut self, name: &str, value: &str) {
+        let value = value.to_string();
+
+        ServerSharedState::update_cookie(&self.state, name, |cookie| cookie.set_value(value))
+            .context("Trying to call set_cookie_value")
+            .unwrap()
+    }
+
+    /// Requests made using this `TestServer` will save their cookies for future requests to send.
+    ///
+    /// This behaviour is off by default.
+    pub fn save_cookies(&mut self) {
+        self.save_cookies = true;
+    }
+
+    /// Requests made using this `TestServer` will _not_ save their cookies for future requests to send up.
+    ///
+    /// This is the default behaviour.
+    pub fn do_not_save_cookies(&mut self) {
+        self.save_cookies = false;
+    }
+
+    /// Returns a new client for the same running application,
+    /// with its own cookies, headers, query params, and expectations.
+    ///
+    /// This shares the transport of this server, so it is cheap to create,
+    /// and the application state is the same for both.
+    /// It is for tests with multiple actors, such as two users talking to one another.
+    ///
+    /// The client starts with the settings this server was built with,
+    /// and the scheme and CSRF settings currently set.
+    /// It starts with no cookies, headers, or query params,
+    /// and changes made to either afterwards do not affect the other.
+    ///
+    /// Checks run when the server is dropped,
+    /// such as for deprecated responses and leaked connections, are only run by this server.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum::routing::get;
+    /// use axum_test::TestServer;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/hello", get(|| async { "hello!" }));
+    ///
+    /// let mut alice = TestServer::new(app)?;
+    /// let mut bob = alice.split_client();
+    ///
+    /// alice.add_header("x-user", "alice");
+    /// bob.add_header("x-user", "bob");
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[track_caller]
+    #[must_use]
+    pub fn split_client(&self) -> TestServer {
+        let client_state = ServerSharedState::new_client(&self.state)
+            .context("Trying to call split_client")
+            .unwrap();
+
+        #[cfg(feature = "reqwest")]
+        let maybe_reqwest_client = build_reqwest_client(
+            self.transport.transport_layer_type(),
+            self.save_cookies,
+            &self.reqwest_configurers,
+        )
+        .context("Trying to call split_client")
+        .unwrap();
+
+        Self {
+            state: Arc::new(Mutex::new(client_state)),
+            transport: self.transport.clone(),
+            save_cookies: self.save_cookies,
+            cookie_domain: self.cookie_domain.clone(),
+            expected_state: self.expected_state,
+            default_content_type: self.default_content_type.clone(),
+            expected_content_type: self.expected_content_type.clone(),
+            is_http_path_restricted: self.is_http_path_restricted,
+            maybe_inner_requests: self.maybe_inner_requests.clone(),
+            maybe_metrics: self.maybe_metrics.clone(),
+            maybe_recorded_exchanges: self.maybe_recorded_exchanges.clone(),
+            maybe_event_log: self.maybe_event_log.clone(),
+            response_validators: self.response_validators.clone(),
+            probes: self.probes.clone(),
+            data_mask: self.data_mask.clone(),
+            response_decoders: self.response_decoders.clone(),
+            request_encoders: self.request_encoders.clone(),
+            _env_vars_guard: None,
+            maybe_deprecated_requests: None,
+            is_failing_on_deprecated: false,
+            is_failing_on_leaked_connections: false,
+            clock_skew: self.clock_skew,
+            seed: self.seed,
+            rng: self.rng.clone(),
+            maybe_readiness_check: self.maybe_readiness_check.clone(),
+            host_aliases: self.host_aliases.clone(),
+            is_verbose: self.is_verbose,
+            max_buffered_body: self.max_buffered_body,
+            max_recorded_request_body: self.max_recorded_request_body,
+            is_catching_panics: self.is_catching_panics,
+            is_capturing_raw_head: self.is_capturing_raw_head,
+            routes: self.routes.clone(),
+
+            #[cfg(feature = "reqwest")]
+            maybe_reqwest_client,
+            #[cfg(feature = "reqwest")]
+            reqwest_configurers: self.reqwest_configurers.clone(),
+        }
+    }
+
+    /// Runs the login flow given as the identity named, and switches to that identity.
+    ///
+    /// Each identity has its own cookies and headers, and starts with none.
+    /// Cookies returned during the login flow are always saved,
+    /// and headers can be added on the server given to the flow.
+    ///
+    /// The login flow only runs the first time an identity is authenticated.
+    /// After that it is cached, and calling this again just switches to it.
+    /// Use [`TestServer::as_user()`](crate::TestServer::as_user()) to switch between identities,
+    /// and [`TestServer::as_anonymous()`](crate::TestServer::as_anonymous()) to switch back to
+    /// the cookies and headers used before authenticating.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// # use axum::Router;
+    /// use axum_test::TestServer;
+    /// use serde_json::json;
+    ///
+    /// # let app = Router::new();
+    /// let mut server = TestServer::new(app)?;
+    ///
+    /// server
+    ///     .authenticate("alice", |server| async move {
+    ///         server
+    ///             .post(&"/login")
+    ///             .json(&json!({ "username": "alice" }))
+    ///             .await;
+    ///     })
+    ///     .await;
+    ///
+    /// server.get(&"/profile").await;
+    /// #
+    /// # Ok(()) }
+    /// ```
+    pub async fn authenticate<'a, F, Fut>(&'a mut self, name: &str, login: F)
+    where
+        F: FnOnce(&'a mut TestServer) -> Fut,
+        Fut: Future<Output = ()> + 'a,
+    {
+        let has_identity = ServerSharedState::has_identity(&self.state, name)
+            .context("Trying to call authenticate")
+            .unwrap();
+
+        ServerSharedState::switch_identity(&self.state, Some(name.to_string()))
+            .context("Trying to call authenticate")
+            .unwrap();
+
+        if has_identity {
+            return;
+        }
+
+        // Cookies from the login flow are kept, whatever the server is set up to do.
+        let state = self.state.clone();
+        ServerSharedState::set_authenticating(&state, true)
+            .context("Trying to call authenticate")
+            .unwrap();
+
+        login(self).await;
+
+        ServerSharedState::set_authenticating(&state, false)
+            .context("Trying to call authenticate")
+            .unwrap();
+    }
+
+    /// Switches to an identity set up before using
+    /// [`TestServer::authenticate()`](crate::TestServer::authenticate()).
+    ///
+    /// Requests will then use the cookies and headers of that identity.
+    /// Any cookies and headers changed whilst using the current identity are kept for it,
+    /// for when it is switched back to.
+    ///
+    /// # Panics
+    ///
+    /// If no identity has been authenticated with the name given.
+    #[track_caller]
+    pub fn as_user(&mut self, name: &str) {
+        let has_identity = ServerSharedState::has_identity(&self.state, name)
+            .context("Trying to call as_user")
+            .unwrap();
+        if !has_identity {
+            panic!("No identity '{name}' has been authenticated, call `authenticate` first");
+        }
+
+        ServerSharedState::switch_identity(&self.state, Some(name.to_string()))
+            .context("Trying to call as_user")
+            .unwrap()
+    }
+
+    /// Switches back to the cookies and headers used before any identity was switched to.
+    #[track_caller]
+    pub fn as_anonymous(&mut self) {
+        ServerSharedState::switch_identity(&self.state, None)
+            .context("Trying to call as_anonymous")
+            .unwrap()
+    }
+
+    /// Requests made using this `TestServer` will assert a HTTP status in the 2xx range will be returned, unless marked otherwise.
+    ///
+    /// By default this behaviour is off.
+    pub fn expect_success(&mut self) {
+        self.expected_state = ExpectedState::Success;
+    }
+
+    /// Requests made using this `TestServer` will assert a HTTP status is outside the 2xx range will be returned, unless marked otherwise.
+    ///
+    /// By default this behaviour is off.
+    pub fn expect_failure(&mut self) {
+        self.expected_state = ExpectedState::Failure;
+    }
+
+    /// Adds a query parameter to be sent on *all* future requests.
+    #[track_caller]
+    pub fn add_query_param<V>(&mut self, key: &str, value: V)
+    where
+        V: Serialize,
+    {
+        ServerSharedState::add_query_param(&self.state, key, value)
+            .context("Trying to call add_query_param")
+            .unwrap()
+    }
+
+    /// Adds query parameters to be sent on *all* future requests.
+    #[track_caller]
+    pub fn add_query_params<V>(&mut self, query_params: V)
+    where
+        V: Serialize,
+    {
+        ServerSharedState::add_query_params(&self.state, query_params)
+            .context("Trying to call add_query_params")
+            .unwrap()
+    }
+
+    /// Adds a raw query param, with no urlencoding of any kind,
+    /// to be send on *all* future requests.
+    #[track_caller]
+    pub fn add_raw_query_param(&mut self, raw_query_param: &str) {
+        ServerSharedState::add_raw_query_param(&self.state, raw_query_param)
+            .context("Trying to call add_raw_query_param")
+            .unwrap()
+    }
+
+    /// Clears all query params set.
+    #[track_caller]
+    pub fn clear_query_params(&mut self) {
+        ServerSharedState::clear_query_params(&self.state)
+            .context("Trying to call clear_query_params")
+            .unwrap()
+    }
+
+    /// Adds a header to be sent with all future requests built from this `TestServer`.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum_test::TestServer;
+    ///
+    /// let app = Router::new();
+    /// let mut server = TestServer::new(app)?;
+    ///
+    /// server.add_header("x-custom-header", "custom-value");
+    /// server.add_header(http::header::CONTENT_LENGTH, 12345);
+    /// server.add_header(http::header::HOST, "example.com");
+    ///
+    /// let response = server.get(&"/my-end-point")
+    ///     .await;
+    /// #
+    /// # Ok(()) }
+    /// ```
+    #[track_caller]
+    pub fn add_header<N, V>(&mut self, name: N, value: V)
+    where
+        N: TryInto<HeaderName>,
+        N::Error: Debug,
+        V: TryInto<HeaderValue>,
+        V::Error: Debug,
+    {
+        let header_name: HeaderName = name
+            .try_into()
+            .expect("Failed to convert header name to HeaderName");
+        let header_value: HeaderValue = value
+            .try_into()
+            .expect("Failed to convert header value to HeaderValue");
+
+        ServerSharedState::add_header(&self.state, header_name, header_value)
+            .context("Trying to call add_header")
+            .unwrap()
+    }
+
+    /// Turns on automatic handling of CSRF tokens, for all future requests.
+    ///
+    /// The token is read from the cookie, or the header, named in the [`CsrfConfig`](crate::CsrfConfig),
+    /// whenever a response carries one. It is then sent as both the cookie and the header
+    /// on all later mutating requests (i.e. `POST`, `PUT`, `PATCH`, and `DELETE`).
+    ///
+    /// Use [`TestRequest::without_csrf()`](crate::TestRequest::without_csrf()) to send a request without the token,
+    /// for testing it is rejected.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum_test::CsrfConfig;
+    /// use axum_test::TestServer;
+    ///
+    /// let app = Router::new();
+    /// let mut server = TestServer::new(app)?;
+    /// server.enable_csrf(CsrfConfig::new("csrf", "x-csrf-token"));
+    ///
+    /// // Receives the CSRF token.
+    /// server.get(&"/login").await;
+    ///
+    /// // Sends the CSRF token back.
+    /// server.post(&"/login").await;
+    /// #
+    /// # Ok(()) }
+    /// ```
+    #[track_caller]
+    pub fn enable_csrf(&mut self, config: CsrfConfig) {
+        let csrf = CsrfState::new(config)
+            .context("Trying to call enable_csrf")
+            .unwrap();
+
+        ServerSharedState::enable_csrf(&self.state, csrf)
+            .context("Trying to call enable_csrf")
+            .unwrap()
+    }
+
+    /// Clears all headers set so far.
+    #[track_caller]
+    pub fn clear_headers(&mut self) {
+        ServerSharedState::clear_headers(&self.state)
+            .context("Trying to call clear_headers")
+            .unwrap()
+    }
+
+    /// Sets the scheme to use when making _all_ requests from the `TestServer`.
+    /// i.e. http or https.
+    ///
+    /// The default scheme is 'http'.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum_test::TestServer;
+    ///
+    /// let app = Router::new();
+    /// let mut server = TestServer::new(app)?;
+    /// server
+    ///     .scheme(&"https");
+    ///
+    /// let response = server
+    ///     .get(&"/my-end-point")
+    ///     .await;
+    /// #
+    /// # Ok(()) }
+    /// ```
+    ///
+    #[track_caller]
+    pub fn scheme(&mut self, scheme: &str) {
+        ServerSharedState::set_scheme(&self.state, scheme.to_string())
+            .context("Trying to call set_scheme")
+            .unwrap()
+    }
+
+    /// Routes requests to absolute urls on the host given to this server,
+    /// as though the host's DNS pointed to it.
+    ///
+    /// The url is sent to the server as a path (with any query),
+    /// and the `Host` header is set to the aliased host.
+    /// Without an alias, requests to other hosts are rejected,
+    /// or sent to that host when `restrict_requests_with_http_schema` is off.
+    ///
+    /// This is useful when the code under test builds absolute urls from config.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum::routing::get;
+    /// use axum_test::TestServer;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/users", get(|| async { "users" }));
+    /// let mut server = TestServer::new(app)?;
+    /// server.alias_host("api.internal");
+    ///
+    /// server.get(&"http://api.internal/users")
+    ///     .await
+    ///     .assert_text("users");
+    /// #
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// Aliases match the host, with any port, or the exact host and port when one is given (i.e. `api.internal:8080`).
+    /// Unicode hosts are converted to punycode, and sent as such in the `Host` header.
+    pub fn alias_host<H>(&mut self, host: H)
+    where
+        H: Into<String>,
+    {
+        self.host_aliases.push(encode_unicode_host(&host.into()));
+    }
+
+    /// Registers a decoder for responses with the content type given,
+    /// for use by [`TestResponse::decode()`](crate::TestResponse::decode()).
+    ///
+    /// The decoder turns the response body into Json,
+    /// which is then deserialized into the type asked for.
+    /// Registering a decoder for a content type replaces any decoder registered before,
+    /// including the built in Json, Yaml, MsgPack, and form decoders.
+    ///
+    /// Content types are matched case insensitively, ignoring parameters such as the `charset`.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum::routing::get;
+    /// use axum_test::TestServer;
+    /// use serde_json::json;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/user", get(|| async {
+    ///         ([("content-type", "application/vnd.my-user")], "Joe:42")
+    ///     }));
+    /// let mut server = TestServer::new(app)?;
+    ///
+    /// server.register_decoder("application/vnd.my-user", |body: &[u8]| {
+    ///     let body = std::str::from_utf8(body)?;
+    ///     let (name, age) = body.split_once(':').ok_or_else(|| anyhow::anyhow!("missing ':'"))?;
+    ///
+    ///     Ok(json!({ "name": name, "age": age.parse::<u32>()? }))
+    /// });
+    ///
+    /// let user = server.get(&"/user").await.decode::<serde_json::Value>();
+    /// assert_eq!(user, json!({ "name": "Joe", "age": 42 }));
+    /// #
+    /// # Ok(()) }
+    /// ```
+    pub fn register_decoder<C, F>(&mut self, content_type: C, decoder: F)
+    where
+        C: AsRef<str>,
+        F: Fn(&[u8]) -> anyhow::Result<Value> + Send + Sync + 'static,
+    {
+        Arc::make_mut(&mut self.response_decoders).register(content_type.as_ref(), decoder);
+    }
+
+    /// Registers an encoder for request bodies with the content type given,
+    /// for use by [`TestRequest::body_as()`](crate::TestRequest::body_as()).
+    ///
+    /// The body is first serialized into Json, which the encoder then turns into bytes.
+    /// Registering an encoder for a content type replaces any encoder registered before,
+    /// including the built in Json, Yaml, MsgPack, and form encoders.
+    ///
+    /// Content types are matched case insensitively, ignoring parameters such as the `charset`.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum::routing::post;
+    /// use axum_test::TestServer;
+    /// use serde_json::json;
+    /// use serde_json::Value;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/user", post(|body: String| async move { body }));
+    /// let mut server = TestServer::new(app)?;
+    ///
+    /// server.register_encoder("application/vnd.my-user", |user: &Value| {
+    ///     Ok(format!("{}:{}", user["name"].as_str().unwrap_or_default(), user["age"]).into_bytes())
+    /// });
+    ///
+    /// server.post(&"/user")
+    ///     .body_as("application/vnd.my-user", &json!({ "name": "Joe", "age": 42 }))
+    ///     .await
+    ///     .assert_text("Joe:42");
+    /// #
+    /// # Ok(()) }
+    /// ```
+    pub fn register_encoder<C, F>(&mut self, content_type: C, encoder: F)
+    where
+        C: AsRef<str>,
+        F: Fn(&Value) -> anyhow::Result<Vec<u8>> + Send + Sync + 'static,
+    {
+        Arc::make_mut(&mut self.request_encoders).register(content_type.as_ref(), encoder);
+    }
+
+    pub(crate) fn url(&self) -> Option<Url> {
+        self.transport.url().cloned()
+    }
+
+    pub(crate) fn build_test_request_config(
+        &self,
+        method: Method,
+        path: &str,
+    ) -> Result<TestRequestConfig> {
+        let url = self
+            .url()
+            .unwrap_or_else(|| DEFAULT_URL_ADDRESS.parse().unwrap());
+
+        let server_locked = self.state.as_ref().lock().map_err(|err| {
+            anyhow!(
+                "Failed to lock InternalTestServer, for request {method} {path}, received {err:?}",
+            )
+        })?;
+
+        let mut cookies = server_locked.cookies().clone();
+        let mut query_params = server_locked.query_params().clone();
+        let mut headers = server_locked.headers().clone();
+
+        // Only mutating requests carry the CSRF token.
+        let maybe_csrf_token = if method.is_safe() {
+            None
+        } else {
+            server_locked.csrf_token()?
+        };
+        let path = encode_unicode_url(path);
+        let maybe_host_alias = find_host_alias(&path, &self.host_aliases);
+        let path = maybe_host_alias
+            .as_ref()
+            .map_or(path.as_ref(), |(_, path_and_query)| path_and_query.as_str());
+        if let Some(cookie_domain) = &self.cookie_domain {
+            let host = maybe_host_alias
+                .as_ref()
+                .map_or(cookie_domain.as_str(), |(host, _)| strip_port(host));
+            cookies = filter_cookies_for_host(&cookies, host);
+        }
+        let mut full_request_url =
+            build_url(url, path, &mut query_params, self.is_http_path_restricted)?;
+
+        if let Some(scheme) = server_locked.scheme() {
+            full_request_url.set_scheme(scheme).map_err(|_| {
+                let debug_request_format = RequestPathFormatter::new(&method, full_request_url.as_str(), Some(&query_params));
+                anyhow!("Scheme '{scheme}' from TestServer cannot be set to request {debug_request_format}")
+            })?;
+        }
+
+        ::std::mem::drop(server_locked);
+
+        if let Some((host, _)) = &maybe_host_alias {
+            let host_header = HeaderValue::from_str(host)
+                .with_context(|| format!("Failed to build Host header, from alias '{host}'"))?;
+            headers.push((header::HOST, host_header));
+        }
+
+        if let Some(clock_skew) = self.clock_skew {
+            let date = format_http_date(OffsetDateTime::now_utc() + clock_skew);
+            let date_header = HeaderValue::from_str(&date)
+                .context("Failed to build Date header, from clock skew")?;
+            headers.push((header::DATE, date_header));
+        }
+
+        Ok(TestRequestConfig {
+            is_saving_cookies: self.save_cookies,
+            expected_state: self.expected_state,
+            content_type: self.default_content_type.clone(),
+            expected_content_type: self.expected_content_type.clone(),
+            method,
+            version: Version::HTTP_11,
+            response_validators: self.response_validators.clone(),
+            probes: self.probes.clone(),
+            data_mask: self.data_mask.clone(),
+            response_decoders: self.response_decoders.clone(),
+            request_encoders: self.request_encoders.clone(),
+            clock_skew: self.clock_skew,
+            rng: self.rng.clone(),
+            maybe_readiness_check: self.maybe_readiness_check.clone(),
+            maybe_csrf_token,
+            is_verbose: self.is_verbose,
+            max_buffered_body: self.max_buffered_body,
+            max_recorded_request_body: self.max_recorded_request_body,
+            is_catching_panics: self.is_catching_panics,
+            is_capturing_raw_head: self.is_capturing_raw_head,
+            maybe_recorded_exchanges: self.maybe_recorded_exchanges.clone(),
+
+            full_request_url,
+            cookies,
+            query_params,
+            headers,
+        })
+    }
+
+    /// Returns true or false if the underlying service inside the `TestServer`
+    /// is still running. For many types of services this will always return `true`.
+    ///
+    /// When a `TestServer` is built using [`axum::serve::WithGracefulShutdown`],
+    /// this will return false if the service has shutdown.
+    pub fn is_running(&self) -> bool {
+        self.transport.is_running()
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            let seed = self.seed;
+            eprintln!("TestServer used seed {seed}, use `TestServerBuilder::with_seed({seed})` to reproduce");
+        }
+
+        // Panicking whilst already panicking would abort the test run.
+        if self.is_failing_on_leaked_connections && !thread::panicking() {
+            self.assert_no_leaked_connections();
+        }
+
+        let Some(deprecated_requests) = &self.maybe_deprecated_requests else {
+            return;
+        };
+
+        // Avoid panicking again if the lock was poisoned by a failing test.
+        let Ok(deprecated_requests) = deprecated_requests.lock() else {
+            return;
+        };
+
+        if deprecated_requests.is_empty() {
+            return;
+        }
+
+        let message = deprecated_requests.iter().fold(
+            "requests received deprecated responses:".to_string(),
+            |message, deprecated_request| format!("{message}\n    {deprecated_request}"),
+        );
+
+        // Panicking whilst already panicking would abort the test run.
+        if self.is_failing_on_deprecated && !thread::panicking() {
+            panic!("Expected no deprecated responses, {message}");
+        }
+
+        eprintln!("Warning, {message}");
+    }
+}
+
+/// Builds the Reqwest client used for requests to HTTP transports.
+#[cfg(feature = "reqwest")]
+fn build_reqwest_client(
+    transport_type: TransportLayerType,
+    save_cookies: bool,
+    reqwest_configurers: &[ReqwestConfigurer],
+) -> Result<Option<Client>, Error> {
+    if transport_type == TransportLayerType::Mock {
+        return Ok(None);
+    }
+
+    let reqwest_builder = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .cookie_store(save_cookies);
+    let reqwest_client = reqwest_configurers
+        .iter()
+        .fold(reqwest_builder, |builder, configurer| {
+            configurer.configure(builder)
+        })
+        .build()
+        .map_err(|error| Error::InvalidConfig {
+            message: format!("Failed to build Reqwest Client, {error}"),
+        })?;
+
+    Ok(Some(reqwest_client))
+}
+
+/// Returns the host, and the path and query, for absolute urls to a host aliased to the server.
+fn find_host_alias(path: &str, host_aliases: &[String]) -> Option<(String, String)> {
+    let path_uri = path.parse::<Uri>().ok()?;
+    path_uri.scheme_str()?;
+    let authority = path_uri.authority()?;
+
+    let host = match authority.port() {
+        Some(port) => format!("{}:{port}", authority.host()),
+        None => authority.host().to_string(),
+    };
+    let is_aliased = host_aliases.iter().any(|alias| {
+        alias.eq_ignore_ascii_case(authority.host()) || alias.eq_ignore_ascii_case(&host)
+    });
+    if !is_aliased {
+        return None;
+    }
+
+    let path_and_query = path_uri
+        .path_and_query()
+        .map_or("/", |path_and_query| path_and_query.as_str());
+
+    Some((host, path_and_query.to_string()))
+}
+
+/// Returns the host, without any port.
+fn strip_port(host: &str) -> &str {
+    match host.rsplit_once(':') {
+        Some((host, port)) if port.bytes().all(|byte| byte.is_ascii_digit()) => host,
+        _ => host,
+    }
+}
+
+fn parse_path_uri(path: &str) -> Result<Uri, Error> {
+    path.parse::<Uri>().map_err(|error| Error::InvalidUrl {
+        url: path.to_string(),
+        message: format!("{error}"),
+    })
+}
+
+fn build_url(
+    mut url: Url,
+    path: &str,
+    query_params: &mut QueryParamsStore,
+    is_http_restricted: bool,
+) -> Result<Url, Error> {
+    let path_uri = parse_path_uri(path)?;
+    let invalid_url = |message: &str| Error::InvalidUrl {
+        url: path.to_string(),
+        message: message.to_string(),
+    };
+
+    // If there is a scheme, then this is an absolute path.
+    if let Some(scheme) = path_uri.scheme_str() {
+        if is_http_restricted {
+            if has_different_schema(&url, &path_uri) || has_different_authority(&url, &path_uri) {
+                return Err(Error::SchemeRejected {
+                    path: path.to_string(),
+                });
+            }
+        } else {
+            url.set_scheme(scheme)
+                .map_err(|_| invalid_url("failed to set scheme for request"))?;
+
+            // We only set the host/port if the scheme is also present.
+            if let Some(authority) = path_uri.authority() {
+                url.set_host(Some(authority.host()))
+                    .map_err(|_| invalid_url("failed to set host for request"))?;
+                url.set_port(authority.port().map(|p| p.as_u16()))
+                    .map_err(|_| invalid_url("failed to set port for request"))?;
+
+                // todo, add username:password support
+            }
+        }
+    }
+
+    // Why does this exist?
+    //
+    // This exists to allow `server.get("/users")` and `server.get("users")` (without a slash)
+    // to go to the same place.
+    //
+    // It does this by saying ...
+    //  - if there is a scheme, it's a full path.
+    //  - if no scheme, it must be a path
+    //
+    if is_absolute_uri(&path_uri) {
+        url.set_path(path_uri.path());
+
+        // In this path we are replacing, so drop any query params on the original url.
+        if url.query().is_some() {
+            url.set_query(None);
+        }
+    } else {
+        // Grab everything up until the query parameters, or everything after that
+        let calculated_path = path.split('?').next().unwrap_or(path);
+        url.set_path(calculated_path);
+
+        // Move any query parameters from the url to the query params store.
+        if let Some(url_query) = url.query() {
+            query_params.add_raw(url_query.to_string());
+            url.set_query(None);
+        }
+    }
+
+    if let Some(path_query) = path_uri.query() {
+        query_params.add_raw(path_query.to_string());
+    }
+
+    Ok(url)
+}
+
+fn is_absolute_uri(path_uri: &Uri) -> bool {
+    path_uri.scheme_str().is_some()
+}
+
+fn has_different_schema(base_url: &Url, path_uri: &Uri) -> bool {
+    if let Some(scheme) = path_uri.scheme_str() {
+        return scheme != base_url.scheme();
+    }
+
+    false
+}
+
+fn has_different_authority(base_url: &Url, path_uri: &Uri) -> bool {
+    if let Some(authority) = path_uri.authority() {
+        return authority.as_str() != base_url.authority();
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod test_build_url {
+    use super::*;
+
+    #[test]
+    fn it_should_copy_path_to_url_returned_when_restricted() {
+        let base_url = "http://example.com".parse::<Url>().unwrap();
+        let path = "/users";
+        let mut query_params = QueryParamsStore::new();
+        let result = build_url(base_url, &path, &mut query_params, true).unwrap();
+
+        assert_eq!("http://example.com/users", result.as_str());
+        assert!(query_params.is_empty());
+    }
+
+    #[test]
+    fn it_should_copy_all_query_params_to_store_when_restricted() {
+        let base_url = "http://example.com?base=aaa".parse::<Url>().unwrap();
+        let path = "/users?path=bbb&path-flag";
+        let mut query_params = QueryParamsStore::new();
+        let result = build_url(base_url, &path, &mut query_params, true).unwrap();
+
+        assert_eq!("http://example.com/users", result.as_str());
+        assert_eq!("base=aaa&path=bbb&path-flag", query_params.to_string());
+    }
+
+    #[test]
+    fn it_should_not_replace_url_when_restricted_with_different_scheme() {
+        let base_url = "http://example.com?base=666".parse::<Url>().unwrap();
+        let path = "ftp://google.com:123/users.csv?limit=456";
+        let mut query_params = QueryParamsStore::new();
+        let result = build_url(base_url, &path, &mut query_params, true);
+
+        assert!(matches!(result, Err(Error::SchemeRejected { .. })));
+    }
+
+    #[test]
+    fn it_should_not_replace_url_when_restricted_with_same_scheme() {
+        let base_url = "http://example.com?base=666".parse::<Url>().unwrap();
+        let path = "http://google.com:123/users.csv?limit=456";
+        let mut query_params = QueryParamsStore::new();
+        let result = build_url(base_url, &path, &mut query_params, true);
+
+        assert!(matches!(result, Err(Error::SchemeRejected { .. })));
+    }
+
+    #[test]
+    fn it_should_block_url_when_restricted_with_same_scheme() {
+        let base_url = "http://example.com?base=666".parse::<Url>().unwrap();
+        let path = "http://google.com";
+        let mut query_params = QueryParamsStore::new();
+        let result = build_url(base_url, &path, &mut query_params, true);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn it_should_block_url_when_restricted_and_same_domain_with_different_scheme() {
+        let base_url = "http://example.com?base=666".parse::<Url>().unwrap();
+        let path = "ftp://example.com/users";
+        let mut query_params = QueryParamsStore::new();
+        let result = build_url(base_url, &path, &mut query_params, true);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn it_should_copy_path_to_url_returned_when_unrestricted() {
+        let base_url = "http://example.com".parse::<Url>().unwrap();
+        let path = "/users";
+        let mut query_params = QueryParamsStore::new();
+        let result = build_url(base_url, &path, &mut query_params, false).unwrap();
+
+        assert_eq!("http://example.com/users", result.as_str());
+        assert!(query_params.is_empty());
+    }
+
+    #[test]
+    fn it_should_copy_all_query_params_to_store_when_unrestricted() {
+        let base_url = "http://example.com?base=aaa".parse::<Url>().unwrap();
+        let path = "/users?path=bbb&path-flag";
+        let mut query_params = QueryParamsStore::new();
+        let result = build_url(base_url, &path, &mut query_params, false).unwrap();
+
+        assert_eq!("http://example.com/users", result.as_str());
+        assert_eq!("base=aaa&path=bbb&path-flag", query_params.to_string());
+    }
+
+    #[test]
+    fn it_should_copy_host_like_a_path_when_unrestricted() {
+        let base_url = "http://example.com".parse::<Url>().unwrap();
+        let path = "google.com";
+        let mut query_params = QueryParamsStore::new();
+        let result = build_url(base_url, &path, &mut query_params, false).unwrap();
+
+        assert_eq!("http://example.com/google.com", result.as_str());
+        assert!(query_params.is_empty());
+    }
+
+    #[test]
+    fn it_should_copy_host_like_a_path_when_restricted() {
+        let base_url = "http://example.com".parse::<Url>().unwrap();
+        let path = "google.com";
+        let mut query_params = QueryParamsStore::new();
+        let result = build_url(base_url, &path, &mut query_params, true).unwrap();
+
+        assert_eq!("http://example.com/google.com", result.as_str());
+        assert!(query_params.is_empty());
+    }
+
+    #[test]
+    fn it_should_replace_url_when_unrestricted() {
+        let base_url = "http://example.com?base=666".parse::<Url>().unwrap();
+        let path = "ftp://google.com:123/users.csv?limit=456";
+        let mut query_params = QueryParamsStore::new();
+        let result = build_url(base_url, &path, &mut query_params, false).unwrap();
+
+        assert_eq!("ftp://google.com:123/users.csv", result.as_str());
+        assert_eq!("limit=456", query_params.to_string());
+    }
+
+    #[test]
+    fn it_should_allow_different_scheme_when_unrestricted() {
+        let base_url = "http://example.com".parse::<Url>().unwrap();
+        let path = "ftp://example.com";
+        let mut query_params = QueryParamsStore::new();
+        let result = build_url(base_url, &path, &mut query_params, false).unwrap();
+
+        assert_eq!("ftp://example.com/", result.as_str());
+    }
+
+    #[test]
+    fn it_should_allow_different_host_when_unrestricted() {
+        let base_url = "http://example.com".parse::<Url>().unwrap();
+        let path = "http://google.com";
+        let mut query_params = QueryParamsStore::new();
+        let result = build_url(base_url, &path, &mut query_params, false).unwrap();
+
+        assert_eq!("http://google.com/", result.as_str());
+    }
+
+    #[test]
+    fn it_should_allow_different_port_when_unrestricted() {
+        let base_url = "http://example.com:123".parse::<Url>().unwrap();
+        let path = "http://example.com:456";
+        let mut query_params = QueryParamsStore::new();
+        let result = build_url(base_url, &path, &mut query_params, false).unwrap();
+
+        assert_eq!("http://example.com:456/", result.as_str());
+    }
+
+    #[test]
+    fn it_should_allow_same_host_port_when_unrestricted() {
+        let base_url = "http://example.com:123".parse::<Url>().unwrap();
+        let path = "http://example.com:123";
+        let mut query_params = QueryParamsStore::new();
+        let result = build_url(base_url, &path, &mut query_params, false).unwrap();
+
+        assert_eq!("http://example.com:123/", result.as_str());
+    }
+
+    #[test]
+    fn it_should_not_allow_different_scheme_when_restricted() {
+        let base_url = "http://example.com".parse::<Url>().unwrap();
+        let path = "ftp://example.com";
+        let mut query_params = QueryParamsStore::new();
+        let result = build_url(base_url, &path, &mut query_params, true);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn it_should_not_allow_different_host_when_restricted() {
+        let base_url = "http://example.com".parse::<Url>().unwrap();
+        let path = "http://google.com";
+        let mut query_params = QueryParamsStore::new();
+        let result = build_url(base_url, &path, &mut query_params, true);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn it_should_not_allow_different_port_when_restricted() {
+        let base_url = "http://example.com:123".parse::<Url>().unwrap();
+        let path = "http://example.com:456";
+        let mut query_params = QueryParamsStore::new();
+        let result = build_url(base_url, &path, &mut query_params, true);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn it_should_allow_same_host_port_when_restricted() {
+        let base_url = "http://example.com:123".parse::<Url>().unwrap();
+        let path = "http://example.com:123";
+        let mut query_params = QueryParamsStore::new();
+        let result = build_url(base_url, &path, &mut query_params, true).unwrap();
+
+        assert_eq!("http://example.com:123/", result.as_str());
+    }
+}
+
+#[cfg(test)]
+mod test_new {
+    use axum::routing::get;
+    use axum::Router;
+    use std::net::SocketAddr;
+
+    use crate::TestServer;
+
+    async fn get_ping() -> &'static str {
+        "pong!"
+    }
+
+    #[tokio::test]
+    async fn it_should_run_into_make_into_service_with_connect_info_by_default() {
+        // Build an application with a route.
+        let app = Router::new()
+            .route("/ping", get(get_ping))
+            .into_make_service_with_connect_info::<SocketAddr>();
+
+        // Run the server.
+        let server = TestServer::new(app).expect("Should create test server");
+
+        // Get the request.
+        server.get(&"/ping").await.assert_text(&"pong!");
+    }
+}
+
+#[cfg(test)]
+mod test_for_layer {
+    use axum::http::HeaderValue;
+    use http::header;
+    use tower_http::set_header::SetRequestHeaderLayer;
+    use tower_http::set_header::SetResponseHeaderLayer;
+
+    use crate::TestServer;
+
+    #[tokio::test]
+    async fn it_should_record_request_changed_by_layer() {
+        let layer = SetRequestHeaderLayer::overriding(
+            header::USER_AGENT,
+            HeaderValue::from_static("my-layer"),
+        );
+        let server = TestServer::for_layer(layer, || async { "inner" }).unwrap();
+
+        server
+            .post("/users")
+            .text("hello!")
+            .await
+            .assert_text("inner");
+
+        let inner_request = server.last_inner_request();
+        inner_request.assert_header(header::USER_AGENT, "my-layer");
+        inner_request.assert_text("hello!");
+        assert_eq!(inner_request.uri().path(), "/users");
+    }
+
+    #[tokio::test]
+    async fn it_should_return_response_changed_by_layer() {
+        let layer = SetResponseHeaderLayer::overriding(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static("no-store"),
+        );
+        let server = TestServer::for_layer(layer, || async { "inner" }).unwrap();
+
+        let response = server.get("/").await;
+
+        response.assert_header(header::CACHE_CONTROL, "no-store");
+        server
+            .last_inner_request()
+            .assert_not_contains_header(header::CACHE_CONTROL);
+    }
+
+    #[tokio::test]
+    async fn it_should_record_all_inner_requests_in_order() {
+        let layer = SetRequestHeaderLayer::if_not_present(
+            header::USER_AGENT,
+            HeaderValue::from_static("my-layer"),
+        );
+        let server = TestServer::for_layer(layer, || async { "inner" }).unwrap();
+
+        server.get("/first").await;
+        server.get("/second").await;
+
+        let paths: Vec<String> = server
+            .inner_requests()
+            .iter()
+            .map(|request| request.uri().path().to_string())
+            .collect();
+        assert_eq!(paths, vec!["/first", "/second"]);
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_reading_inner_requests_without_layer() {
+        let server = TestServer::new(axum::Router::new()).unwrap();
+
+        let _ = server.inner_requests();
+    }
+}
+
+#[cfg(test)]
+mod test_metrics {
+    use axum::routing::get;
+    use axum::routing::post;
+    use axum::Router;
+    use http::StatusCode;
+
+    use crate::ChaosConfig;
+    use crate::TestServer;
+
+    fn new_app() -> Router {
+        Router::new()
+            .route("/login", post(|body: String| async move { body }))
+            .route("/missing", get(|| async { StatusCode::NOT_FOUND }))
+    }
+
+    #[tokio::test]
+    async fn it_should_count_hits_statuses_and_bytes() {
+        let server = TestServer::builder()
+            .record_metrics()
+            .build(new_app())
+            .unwrap();
+
+        server.post("/login").text("abc").await;
+        server.post("/login?user=joe").text("de").await;
+        server.get("/missing").await;
+
+        let metrics = server.metrics();
+        assert_eq!(metrics.total_hits(), 3);
+        assert_eq!(metrics.path_hits("/login"), 2);
+        assert_eq!(metrics.path_hits("/missing"), 1);
+        assert_eq!(metrics.status_hits(StatusCode::OK), 2);
+        assert_eq!(metrics.status_hits(StatusCode::NOT_FOUND), 1);
+        assert_eq!(metrics.bytes_sent(), 5);
+        assert_eq!(metrics.bytes_received(), 5);
+    }
+
+    #[tokio::test]
+    async fn it_should_not_count_requests_failed_by_chaos() {
+        let server = TestServer::builder()
+            .record_metrics()
+            .with_chaos(ChaosConfig {
+                server_error_rate: 1.0,
+                ..ChaosConfig::default()
+            })
+            .build(new_app())
+            .unwrap();
+
+        server.post("/login").await;
+
+        server.assert_route_called("/login", 0);
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_assert_route_called_when_count_matches() {
+        let server = TestServer::builder()
+            .record_metrics()
+            .build(new_app())
+            .unwrap();
+
+        server.post("/login").await;
+
+        server.assert_route_called("/login", 1);
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_fail_assert_route_called_when_count_differs() {
+        let server = TestServer::builder()
+            .record_metrics()
+            .build(new_app())
+            .unwrap();
+
+        server.post("/login").await;
+        server.post("/login").await;
+
+        server.assert_route_called("/login", 1);
+    }
+
+    #[tokio::test]
+    async fn it_should_match_route_patterns_in_assert_route_called() {
+        let app = Router::new().route("/users/:id", get(|| async { "user" }));
+        let server = TestServer::builder().record_metrics().build(app).unwrap();
+
+        server.get("/users/1").await;
+        server.get("/users/2").await;
+
+        server.assert_route_called("/users/:id", 2);
+        server.assert_path_called("/users/1", 1);
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_metrics_not_recorded() {
+        let server = TestServer::new(new_app()).unwrap();
+
+        let _ = server.metrics();
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_assert_no_duplicate_requests_when_bodies_differ() {
+        let server = TestServer::builder()
+            .record_metrics()
+            .build(new_app())
+            .unwrap();
+
+        server.post("/login").text("abc").await;
+        server.post("/login").text("def").await;
+
+        server.assert_no_duplicate_requests();
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "POST /login?user=joe sent 2 times")]
+    async fn it_should_fail_assert_no_duplicate_requests_when_sent_twice() {
+        let server = TestServer::builder()
+            .record_metrics()
+            .build(new_app())
+            .unwrap();
+
+        server.post("/login?user=joe").text("abc").await;
+        server.post("/login?user=joe").text("abc").await;
+
+        server.assert_no_duplicate_requests();
+    }
+}
+
+#[cfg(test)]
+mod test_routes {
+    use crate::RecordingRouter;
+    use crate::RouteInfo;
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+    use http::Method;
+    use http::StatusCode;
+
+    #[tokio::test]
+    async fn it_should_return_routes_recorded() {
+        let app = RecordingRouter::new()
+            .route(Method::GET, "/users", || async {})
+            .route(Method::POST, "/users", || async {})
+            .route(Method::GET, "/users/:id", || async {});
+        let server = TestServer::new(app).unwrap();
+
+        assert_eq!(
+            server.routes(),
+            vec![
+                RouteInfo {
+                    method: Method::GET,
+                    path_pattern: "/users".to_string(),
+                },
+                RouteInfo {
+                    method: Method::POST,
+                    path_pattern: "/users".to_string(),
+                },
+                RouteInfo {
+                    method: Method::GET,
+                    path_pattern: "/users/:id".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn it_should_add_routes_given_by_hand_after_those_recorded() {
+        let app = RecordingRouter::new()
+            .route(Method::GET, "/users", || async {})
+            .map_router(|router| router.route("/health", get(|| async {})));
+        let server = TestServer::builder()
+            .route(Method::GET, "/health")
+            .build(app)
+            .unwrap();
+
+        let paths = server
+            .routes()
+            .into_iter()
+            .map(|route| route.path_pattern)
+            .collect::<Vec<_>>();
+        assert_eq!(paths, vec!["/users", "/health"]);
+    }
+
+    #[tokio::test]
+    async fn it_should_return_routes_given_by_hand_for_any_app() {
+        let app = Router::new().route("/users", get(|| async {}));
+        let server = TestServer::builder()
+            .route(Method::GET, "/users")
+            .build(app.into_make_service())
+            .unwrap();
+
+        assert_eq!(server.routes().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn it_should_drive_requests_to_every_route() {
+        let app = RecordingRouter::new()
+            .route(Method::GET, "/users", || async { StatusCode::UNAUTHORIZED })
+            .route(Method::GET, "/users/:id", || async {
+                StatusCode::UNAUTHORIZED
+            })
+            .route(Method::DELETE, "/users/:id", || async {
+                StatusCode::UNAUTHORIZED
+            });
+        let server = TestServer::new(app).unwrap();
+
+        for route in server.routes() {
+            server
+                .method(route.method.clone(), &route.example_path())
+                .expect_failure()
+                .await
+                .assert_status_unauthorized();
+        }
+    }
+
+    #[tokio::test]
+    async fn it_should_return_no_routes_for_a_plain_router() {
+        let app = Router::new().route("/users", get(|| async {}));
+        let server = TestServer::new(app).unwrap();
+
+        assert_eq!(server.routes(), vec![]);
+    }
+}
+
+#[cfg(test)]
+mod test_assert_all_routes_unauthorized_without {
+    use crate::RecordingRouter;
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+    use http::header;
+    use http::HeaderMap;
+    use http::Method;
+    use http::StatusCode;
+
+    async fn route_protected(headers: HeaderMap) -> StatusCode {
+        match headers.get(header::AUTHORIZATION) {
+            Some(token) if token == "Bearer my-token" => StatusCode::OK,
+            Some(_) => StatusCode::FORBIDDEN,
+            None => StatusCode::UNAUTHORIZED,
+        }
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_when_all_routes_are_protected() {
+        let app = RecordingRouter::new()
+            .route(Method::POST, "/login", || async { "logged in" })
+            .route(Method::GET, "/users", route_protected)
+            .route(Method::POST, "/users", route_protected)
+            .route(Method::GET, "/users/:id", route_protected);
+        let server = TestServer::new(app).unwrap();
+
+        server
+            .assert_all_routes_unauthorized_without(
+                |request| request.authorization_bearer("my-token"),
+                &[(Method::POST, "/login")],
+            )
+            .await;
+    }
+
+    #[tokio::test]
+    async fn it_should_report_unprotected_routes() {
+        let app = RecordingRouter::new()
+            .route(Method::GET, "/health", || async { "ok" })
+            .route(Method::GET, "/users", route_protected)
+            .route(Method::GET, "/admin", || async { StatusCode::FORBIDDEN });
+        let server = TestServer::new(app).unwrap();
+
+        let error = server
+            .check_all_routes_unauthorized_without(
+                |request| request.authorization_bearer("my-token"),
+                &[],
+            )
+            .await
+            .unwrap_err()
+            .to_string();
+
+        assert!(error.contains("GET /health returned 200 OK without credentials"));
+        assert!(error.contains("GET /admin returned 403 Forbidden with credentials"));
+        assert!(!error.contains("/users"));
+    }
+
+    #[tokio::test]
+    async fn it_should_only_skip_public_routes_with_the_same_method() {
+        let app = RecordingRouter::new()
+            .route(Method::GET, "/login", || async { "login page" })
+            .route(Method::POST, "/login", || async { "logged in" });
+        let server = TestServer::new(app).unwrap();
+
+        let error = server
+            .check_all_routes_unauthorized_without(
+                |request| request.authorization_bearer("my-token"),
+                &[(Method::GET, "/login")],
+            )
+            .await
+            .unwrap_err()
+            .to_string();
+
+        assert!(error.contains("POST /login returned 200 OK without credentials"));
+        assert!(!error.contains("GET /login"));
+    }
+
+    #[tokio::test]
+    async fn it_should_check_routes_given_by_hand() {
+        let app = Router::new().route("/health", get(|| async { "ok" }));
+        let server = TestServer::builder()
+            .route(Method::GET, "/health")
+            .build(app)
+            .unwrap();
+
+        let error = server
+            .check_all_routes_unauthorized_without(
+                |request| request.authorization_bearer("my-token"),
+                &[],
+            )
+            .await
+            .unwrap_err()
+            .to_string();
+
+        assert!(error.contains("GET /health returned 200 OK without credentials"));
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_when_there_are_no_routes() {
+        let app = Router::new().route("/users", get(route_protected));
+        let server = TestServer::new(app).unwrap();
+
+        let error = server
+            .check_all_routes_unauthorized_without(
+                |request| request.authorization_bearer("my-token"),
+                &[],
+            )
+            .await
+            .unwrap_err()
+            .to_string();
+
+        assert!(error.contains("No routes to check"));
+    }
+}
+
+#[cfg(test)]
+mod test_assert_scenario {
+    use crate::Scenario;
+    use crate::TestServer;
+    use axum::routing::post;
+    use axum::Json;
+    use axum::Router;
+    use http::HeaderMap;
+    use http::StatusCode;
+    use serde_json::json;
+    use serde_json::Value;
+    use std::collections::HashMap;
+
+    async fn route_post_user(
+        headers: HeaderMap,
+        Json(user): Json<Value>,
+    ) -> (StatusCode, Json<Value>) {
+        match headers.get("x-api-key") {
+            Some(_) => (
+                StatusCode::CREATED,
+                Json(json!({ "id": 1, "name": user["name"] })),
+            ),
+            None => (
+                StatusCode::UNAUTHORIZED,
+                Json(json!({ "error": "missing api key" })),
+            ),
+        }
+    }
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new().route("/users", post(route_post_user));
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_scenario_from_json_file() {
+        let server = new_test_server();
+        let scenario = Scenario::from_json_file("files/scenarios/users.json").unwrap();
+
+        server.assert_scenario(&scenario).await;
+    }
+
+    #[cfg(feature = "yaml")]
+    #[tokio::test]
+    async fn it_should_pass_scenario_from_yaml_file() {
+        let server = new_test_server();
+        let scenario = Scenario::from_yaml_file("files/scenarios/users.yaml").unwrap();
+
+        server.assert_scenario(&scenario).await;
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_scenario_from_postman_collection() {
+        let server = new_test_server();
+        let variables = HashMap::from([("api_key".to_string(), "my-key".to_string())]);
+        let scenario = Scenario::from_postman_collection_file(
+            "files/scenarios/users.postman_collection.json",
+            &variables,
+        )
+        .unwrap();
+
+        assert_eq!(scenario.len(), 2);
+        server.assert_scenario(&scenario).await;
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_postman_collection_test_scripts() {
+        let app = Router::new().route(
+            "/users",
+            post(|| async { (StatusCode::CREATED, Json(json!({ "name": "Joe" }))) }),
+        );
+        let server = TestServer::new(app).unwrap();
+        let variables = HashMap::from([("api_key".to_string(), "my-key".to_string())]);
+        let scenario = Scenario::from_postman_collection_file(
+            "files/scenarios/users.postman_collection.json",
+            &variables,
+        )
+        .unwrap();
+
+        let error = server
+            .check_scenario(&scenario)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("step 2 'Reject missing api key' failed"));
+    }
+
+    #[tokio::test]
+    async fn it_should_run_insomnia_export() {
+        let server = new_test_server();
+        let scenario = Scenario::from_insomnia_export_file(
+            "files/scenarios/users.insomnia.json",
+            &HashMap::new(),
+        )
+        .unwrap();
+
+        assert_eq!(scenario.len(), 1);
+        server.assert_scenario(&scenario).await;
+    }
+
+    #[tokio::test]
+    async fn it_should_report_each_failing_step() {
+        let server = new_test_server();
+        let scenario = Scenario::try_from(json!({
+            "steps": [
+                {
+                    "name": "wrong status",
+                    "method": "POST",
+                    "path": "/users",
+                    "json": { "name": "Joe" },
+                    "expect": { "status": 201 },
+                },
+                {
+                    "method": "POST",
+                    "path": "/users",
+                    "headers": { "x-api-key": "my-key" },
+                    "json": { "name": "Joe" },
+                    "expect": { "status": 201 },
+                },
+                {
+                    "name": "wrong json",
+                    "method": "POST",
+                    "path": "/users",
+                    "headers": { "x-api-key": "my-key" },
+                    "json": { "name": "Joe" },
+                    "expect": { "json_contains": { "name": "Kate" } },
+                },
+            ],
+        }))
+        .unwrap();
+
+        let error = server
+            .check_scenario(&scenario)
+            .await
+            .unwrap_err()
+            .to_string();
+
+        assert!(error.contains("Scenario failed 2 of 3 steps"));
+        assert!(error.contains("step 1 'wrong status' failed"));
+        assert!(error.contains("step 3 'wrong json' failed"));
+        assert!(!error.contains("step 2"));
+    }
+}
+
+#[cfg(test)]
+mod test_events {
+    use crate::TestEventLog;
+    use crate::TestServer;
+    use axum::routing::post;
+    use axum::Extension;
+    use axum::Router;
+
+    async fn route_create_user(Extension(events): Extension<TestEventLog>) {
+        events.push("auth.check");
+        events.push("db.write");
+    }
+
+    #[tokio::test]
+    async fn it_should_return_events_pushed_by_the_app() {
+        let events = TestEventLog::new();
+        let app = Router::new()
+            .route("/users", post(route_create_user))
+            .layer(Extension(events.clone()));
+        let server = TestServer::builder()
+            .http_transport()
+            .event_log(events)
+            .build(app)
+            .unwrap();
+
+        server.post("/users").await;
+
+        server.events().assert_order(["auth.check", "db.write"]);
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_built_without_event_log() {
+        let server = TestServer::new(Router::new()).unwrap();
+
+        let _ = server.events();
+    }
+}
+
+#[cfg(test)]
+mod test_expect_error_body_schema {
+    use axum::routing::get;
+    use axum::Json;
+    use axum::Router;
+    use http::StatusCode;
+    use serde_json::json;
+
+    use crate::TestServer;
+
+    fn new_server() -> TestServer {
+        let app = Router::new()
+            .route(
+                "/json-error",
+                get(|| async {
+                    let body = json!({ "code": "E1", "message": "oops", "trace_id": "abc" });
+                    (StatusCode::BAD_REQUEST, Json(body))
+                }),
+            )
+            .route(
+                "/text-error",
+                get(|| async { (StatusCode::INTERNAL_SERVER_ERROR, "oops") }),
+            )
+            .route("/ok", get(|| async { "ok" }));
+
+        TestServer::builder()
+            .expect_error_body_schema(json!({
+                "code": "string",
+                "message": "string",
+                "trace_id": "string",
+            }))
+            .build(app)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_for_error_body_matching_schema() {
+        new_server()
+            .get("/json-error")
+            .await
+            .assert_status_bad_request();
+    }
+
+    #[tokio::test]
+    async fn it_should_not_check_successful_responses() {
+        new_server().get("/ok").await.assert_text("ok");
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_for_error_body_not_matching_schema() {
+        new_server().get("/text-error").await;
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_even_when_failure_is_expected() {
+        new_server().get("/text-error").expect_failure().await;
+    }
+}
+
+#[cfg(test)]
+mod test_add_response_validator {
+    use anyhow::ensure;
+    use axum::routing::get;
+    use axum::Router;
+    use http::StatusCode;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+
+    use crate::TestServer;
+
+    fn new_server() -> TestServer {
+        let app = Router::new()
+            .route(
+                "/with-header",
+                get(|| async { ([("x-correlation-id", "123")], "ok") }),
+            )
+            .route("/without-header", get(|| async { StatusCode::NOT_FOUND }));
+
+        TestServer::builder()
+            .add_response_validator(|response| {
+                ensure!(
+                    response.maybe_header("x-correlation-id").is_some(),
+                    "missing correlation id"
+                );
+                Ok(())
+            })
+            .build(app)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_when_validator_passes() {
+        new_server().get("/with-header").await.assert_text("ok");
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_validator_fails() {
+        new_server().get("/without-header").await;
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_validator_fails_and_failure_is_expected() {
+        // The failure is expected, so only the validator can panic here.
+        new_server().get("/without-header").expect_failure().await;
+    }
+
+    #[tokio::test]
+    async fn it_should_run_every_validator_on_every_request() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let first_calls = calls.clone();
+        let second_calls = calls.clone();
+
+        let app = Router::new().route("/ping", get(|| async { "pong" }));
+        let server = TestServer::builder()
+            .add_response_validator(move |_| {
+                first_calls.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            })
+            .add_response_validator(move |_| {
+                second_calls.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            })
+            .build(app)
+            .unwrap();
+
+        server.get("/ping").await;
+        server.get("/ping").await;
+
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+}
+
+#[cfg(test)]
+mod test_add_assertion_bundle {
+    use axum::routing::get;
+    use axum::Router;
+
+    use crate::AssertionBundle;
+    use crate::TestServer;
+
+    fn new_server() -> TestServer {
+        let app = Router::new()
+            .route(
+                "/with-header",
+                get(|| async { ([("x-request-id", "123")], "ok") }),
+            )
+            .route("/without-header", get(|| async { "ok" }));
+
+        TestServer::builder()
+            .add_assertion_bundle(AssertionBundle::new("standard").header_present("x-request-id"))
+            .build(app)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_when_bundle_passes() {
+        new_server().get("/with-header").await.assert_text("ok");
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_bundle_fails() {
+        new_server().get("/without-header").await;
+    }
+}
+
+#[cfg(test)]
+mod test_deprecated_requests {
+    use axum::routing::get;
+    use axum::Router;
+
+    use crate::TestServer;
+
+    fn new_server() -> TestServer {
+        let app = Router::new()
+            .route("/current", get(|| async { "current" }))
+            .route(
+                "/deprecated",
+                get(|| async { ([("deprecation", "true")], "deprecated") }),
+            );
+
+        TestServer::builder()
+            .warn_on_deprecated()
+            .build(app)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_collect_deprecated_requests() {
+        let server = new_server();
+
+        server.get("/current").await;
+        server.get("/deprecated").await;
+
+        assert_eq!(
+            server.deprecated_requests(),
+            vec!["GET http://localhost/deprecated".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn it_should_be_empty_when_no_deprecated_requests_made() {
+        let server = new_server();
+
+        server.get("/current").await;
+
+        assert!(server.deprecated_requests().is_empty());
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "GET http://localhost/deprecated")]
+    async fn it_should_panic_on_drop_when_failing_on_deprecated() {
+        let app = Router::new().route(
+            "/deprecated",
+            get(|| async { ([("sunset", "Sat, 01 Jan 2000 00:00:00 GMT")], "deprecated") }),
+        );
+        let server = TestServer::builder()
+            .fail_on_deprecated()
+            .build(app)
+            .unwrap();
+
+        server.get("/deprecated").await;
+    }
+
+    #[tokio::test]
+    async fn it_should_not_panic_on_drop_when_failing_on_deprecated_without_deprecated_requests() {
+        let app = Router::new().route("/current", get(|| async { "current" }));
+        let server = TestServer::builder()
+            .fail_on_deprecated()
+            .build(app)
+            .unwrap();
+
+        server.get("/current").await;
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_not_warning_on_deprecated() {
+        let app = Router::new();
+        let server = TestServer::new(app).unwrap();
+
+        let _ = server.deprecated_requests();
+    }
+}
+
+#[cfg(test)]
+mod test_transport_diagnostics {
+    use crate::TestServer;
+    use crate::TransportDiagnostics;
+    use axum::routing::get;
+    use axum::Router;
+    use std::future::IntoFuture;
+    use std::time::Duration;
+    use tokio::time::sleep;
+
+    fn new_app() -> Router {
+        Router::new()
+            .route("/ping", get(|| async { "pong!" }))
+            .route(
+                "/slow",
+                get(|| async {
+                    sleep(Duration::from_millis(200)).await;
+                    "done"
+                }),
+            )
+    }
+
+    fn new_http_server() -> TestServer {
+        TestServer::builder()
+            .http_transport()
+            .build(new_app())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_be_empty_for_mock_transport() {
+        let server = TestServer::builder()
+            .mock_transport()
+            .build(new_app())
+            .unwrap();
+
+        server.get("/ping").await;
+
+        assert_eq!(
+            server.transport_diagnostics(),
+            TransportDiagnostics::default()
+        );
+    }
+
+    #[tokio::test]
+    async fn it_should_report_the_serve_loop_for_http_transport() {
+        let server = new_http_server();
+
+        server.get("/ping").await;
+
+        let diagnostics = server.transport_diagnostics();
+        assert_eq!(diagnostics.active_requests, 0);
+        assert!(diagnostics.spawned_tasks >= 1);
+        assert!(!diagnostics.has_serve_loop_panicked);
+    }
+
+    #[tokio::test]
+    async fn it_should_count_requests_being_served() {
+        let server = new_http_server();
+
+        let (_, diagnostics) = tokio::join!(server.get("/slow").into_future(), async {
+            sleep(Duration::from_millis(100)).await;
+            server.transport_diagnostics()
+        });
+
+        assert_eq!(diagnostics.active_requests, 1);
+        assert_eq!(diagnostics.open_connections, 1);
+        assert_eq!(diagnostics.spawned_tasks, 2);
+    }
+
+    #[tokio::test]
+    async fn it_should_close_connections_after_requests() {
+        let server = new_http_server();
+
+        server.get("/ping").await;
+        for _ in 0..100 {
+            if server.transport_diagnostics().open_connections == 0 {
+                break;
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+
+        assert_eq!(server.transport_diagnostics().open_connections, 0);
+    }
+}
+
+#[cfg(test)]
+mod test_assert_no_leaked_connections {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+    use std::future::IntoFuture;
+    use std::time::Duration;
+    use tokio::time::sleep;
+
+    fn new_app() -> Router {
+        Router::new()
+            .route("/ping", get(|| async { "pong!" }))
+            .route(
+                "/slow",
+                get(|| async {
+                    sleep(Duration::from_millis(200)).await;
+                    "done"
+                }),
+            )
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_after_requests_have_finished() {
+        let server = TestServer::builder()
+            .http_transport()
+            .build(new_app())
+            .unwrap();
+
+        server.get("/ping").await;
+
+        server.assert_no_leaked_connections();
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "1 requests are still being served")]
+    async fn it_should_panic_whilst_requests_are_being_served() {
+        let server = TestServer::builder()
+            .http_transport()
+            .build(new_app())
+            .unwrap();
+
+        tokio::join!(server.get("/slow").into_future(), async {
+            sleep(Duration::from_millis(100)).await;
+            server.assert_no_leaked_connections();
+        });
+    }
+
+    #[tokio::test]
+    async fn it_should_not_panic_on_drop_when_failing_on_leaked_connections_without_leaks() {
+        let server = TestServer::builder()
+            .http_transport()
+            .fail_on_leaked_connections()
+            .build(new_app())
+            .unwrap();
+
+        server.get("/ping").await;
+    }
+}
+
+#[cfg(test)]
+mod test_replay {
+    use axum::extract::State;
+    use axum::http::HeaderMap;
+    use axum::routing::post;
+    use axum::Router;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    use crate::TestServer;
+
+    type Payments = Arc<Mutex<HashMap<String, String>>>;
+
+    /// Returns the same receipt for the same idempotency key,
+    /// and a new receipt for every request without one.
+    async fn route_make_payment(
+        State(payments): State<Payments>,
+        headers: HeaderMap,
+        body: String,
+    ) -> String {
+        let mut payments = payments.lock().unwrap();
+        let receipt = format!("receipt {} for {body}", payments.len());
+
+        match headers.get("idempotency-key") {
+            Some(key) => payments
+                .entry(key.to_str().unwrap().to_string())
+                .or_insert(receipt)
+                .clone(),
+            None => {
+                payments.insert(receipt.clone(), receipt.clone());
+                receipt
+            }
+        }
+    }
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .route("/payments", post(route_make_payment))
+            .with_state(Payments::default());
+
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_replay_request_with_same_idempotency_key() {
+        let server = new_test_server();
+
+        let response = server
+            .post("/payments")
+            .random_idempotency_key()
+            .text("£10")
+            .await;
+        let replayed = server.replay(&response).await;
+
+        replayed.assert_text("receipt 0 for £10");
+        replayed.assert_same_response(&response);
+        assert_eq!(replayed.idempotency_key(), response.idempotency_key());
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_replaying_request_without_idempotency_key() {
+        let server = new_test_server();
+
+        let response = server.post("/payments").text("£10").await;
+        let _ = server.replay(&response);
+    }
+}
+
+#[cfg(test)]
+mod test_batch {
+    use crate::InterceptAction;
+    use crate::TestServer;
+    use axum::extract::Path;
+    use axum::routing::get;
+    use axum::Router;
+    use http::StatusCode;
+    use std::time::Duration;
+    use tokio::time::sleep;
+
+    fn new_test_router() -> Router {
+        Router::new()
+            .route(
+                "/slow/:millis",
+                get(|Path(millis): Path<u64>| async move {
+                    sleep(Duration::from_millis(millis)).await;
+                    format!("slept {millis}")
+                }),
+            )
+            .route("/missing", get(|| async { StatusCode::NOT_FOUND }))
+    }
+
+    #[tokio::test]
+    async fn it_should_return_responses_in_request_order() {
+        let server = TestServer::new(new_test_router()).unwrap();
+
+        let responses = server
+            .batch([
+                server.get("/slow/60"),
+                server.get("/slow/10"),
+                server.get("/slow/30"),
+            ])
+            .await;
+
+        responses[0].assert_text("slept 60");
+        responses[1].assert_text("slept 10");
+        responses[2].assert_text("slept 30");
+    }
+
+    #[tokio::test]
+    async fn it_should_send_requests_concurrently() {
+        let server = TestServer::new(new_test_router()).unwrap();
+        let requests = (0..10).map(|_| server.get("/slow/100"));
+
+        let started_at = tokio::time::Instant::now();
+        server.batch(requests).await;
+
+        assert!(started_at.elapsed() < Duration::from_millis(800));
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_request_expectation_fails() {
+        let server = TestServer::new(new_test_router()).unwrap();
+
+        server
+            .batch([
+                server.get("/slow/1"),
+                server.get("/missing").expect_success(),
+            ])
+            .await;
+    }
+
+    #[tokio::test]
+    async fn it_should_return_errors_with_request_position() {
+        let server = TestServer::builder()
+            .intercept("/missing", InterceptAction::ConnectionError)
+            .build(new_test_router())
+            .unwrap();
+
+        let results = server
+            .try_batch([server.get("/slow/1"), server.get("/missing")])
+            .await;
+
+        results[0].as_ref().unwrap().assert_text("slept 1");
+        let error = results[1].as_ref().unwrap_err();
+        assert!(format!("{error:#}").contains("Batch request at position 1 failed"));
+    }
+}
+
+#[cfg(test)]
+mod test_supports_range_requests {
+    use axum::routing::get;
+    use axum::Router;
+    use tower_http::services::ServeDir;
+
+    use crate::TestServer;
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .nest_service("/static", ServeDir::new("files/static"))
+            .route("/dynamic", get(|| async { "hello" }));
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_for_static_files() {
+        let server = new_test_server();
+
+        server
+            .assert_supports_range_requests("/static/app.js")
+            .await;
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_for_routes_ignoring_range() {
+        let server = new_test_server();
+        let error = server
+            .check_supports_range_requests("/dynamic")
+            .await
+            .unwrap_err();
+
+        assert!(error.to_string().contains("206"), "{error}");
+    }
+}
+
+#[cfg(test)]
+mod test_serves_precompressed {
+    use axum::Router;
+    use tower_http::services::ServeDir;
+
+    use crate::TestServer;
+
+    fn new_test_server(is_precompressed: bool) -> TestServer {
+        let serve_dir = ServeDir::new("files/static");
+        let serve_dir = if is_precompressed {
+            serve_dir.precompressed_gzip()
+        } else {
+            serve_dir
+        };
+
+        let app = Router::new().nest_service("/static", serve_dir);
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_when_served_precompressed() {
+        let server = new_test_server(true);
+
+        server
+            .assert_serves_precompressed("/static/app.js", "gzip")
+            .await;
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_when_not_precompressed() {
+        let server = new_test_server(false);
+        let result = server
+            .check_serves_precompressed("/static/app.js", "gzip")
+            .await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_for_missing_files() {
+        let server = new_test_server(true);
+        let result = server
+            .check_serves_precompressed("/static/missing.js", "gzip")
+            .await;
+
+        assert!(result.is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_assert_method_not_allowed {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+    use http::header;
+    use http::Method;
+    use http::StatusCode;
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .route(
+                "/users",
+                get(|| async { "users" }).post(|| async { "created" }),
+            )
+            .route(
+                "/no-allow",
+                get(|| async { StatusCode::METHOD_NOT_ALLOWED }),
+            )
+            .route(
+                "/wrong-allow",
+                get(|| async {
+                    (
+                        StatusCode::METHOD_NOT_ALLOWED,
+                        [(header::ALLOW, "GET,HEAD")],
+                    )
+                }),
+            );
+
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_when_method_is_not_allowed() {
+        let server = new_test_server();
+
+        server
+            .assert_method_not_allowed("/users", Method::DELETE)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_when_method_is_allowed() {
+        let server = new_test_server();
+
+        let result = server
+            .check_method_not_allowed("/users", Method::POST)
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_without_allow_header() {
+        let server = new_test_server();
+
+        let error = server
+            .check_method_not_allowed("/no-allow", Method::GET)
+            .await
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("Expected 405 response to have an Allow header, none found"));
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_when_allow_header_lists_method() {
+        let server = new_test_server();
+
+        let error = server
+            .check_method_not_allowed("/wrong-allow", Method::GET)
+            .await
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("Expected Allow header to not list GET, received 'GET,HEAD'"));
+    }
+}
+
+#[cfg(test)]
+mod test_probe_unsupported_methods {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+    use http::Method;
+    use http::StatusCode;
+
+    #[tokio::test]
+    async fn it_should_return_status_for_every_method() {
+        let app = Router::new().route(
+            "/users",
+            get(|| async { "users" }).post(|| async { "created" }),
+        );
+        let server = TestServer::new(app).unwrap();
+
+        let statuses = server.probe_unsupported_methods("/users").await;
+
+        assert_eq!(statuses.len(), 8);
+        assert_eq!(statuses[&Method::GET], StatusCode::OK);
+        assert_eq!(statuses[&Method::HEAD], StatusCode::OK);
+        assert_eq!(statuses[&Method::POST], StatusCode::OK);
+        assert_eq!(statuses[&Method::PUT], StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(statuses[&Method::PATCH], StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(statuses[&Method::DELETE], StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(statuses[&Method::OPTIONS], StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(statuses[&Method::TRACE], StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    #[tokio::test]
+    async fn it_should_return_not_found_for_unknown_paths() {
+        let app = Router::new().route("/users", get(|| async { "users" }));
+        let server = TestServer::new(app).unwrap();
+
+        let statuses = server.probe_unsupported_methods("/unknown").await;
+
+        assert!(statuses
+            .values()
+            .all(|status_code| *status_code == StatusCode::NOT_FOUND));
+    }
+}
+
+#[cfg(test)]
+mod test_get_twice {
+    use crate::TestServer;
+    use axum::http::header;
+    use axum::http::HeaderMap;
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+    use axum::response::Response;
+    use axum::routing::get;
+    use axum::Router;
+
+    async fn get_etag_user(headers: HeaderMap) -> Response {
+        if headers
+            .get(header::IF_NONE_MATCH)
+            .is_some_and(|etag| etag == "\"v1\"")
+        {
+            return StatusCode::NOT_MODIFIED.into_response();
+        }
+
+        ([(header::ETAG, "\"v1\"")], "user").into_response()
+    }
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .route("/etag", get(get_etag_user))
+            .route("/uncached", get(|| async { "user" }))
+            .route(
+                "/cdn",
+                get(|| async { ([("cf-cache-status", "HIT")], "user") }),
+            );
+
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_revalidate_etag_on_second_request() {
+        let server = new_test_server();
+
+        let responses = server.get_twice("/etag").await;
+        responses.first().assert_status_ok();
+        responses.second().assert_status(StatusCode::NOT_MODIFIED);
+        responses.assert_second_is_cache_hit();
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_when_second_is_not_cached() {
+        let server = new_test_server();
+
+        let responses = server.get_twice("/uncached").await;
+        assert!(responses.check_second_is_cache_hit().is_err());
+    }
+
+    #[tokio::test]
+    async fn it_should_assert_custom_cache_headers() {
+        let server = new_test_server();
+
+        let responses = server.get_twice("/cdn").await;
+        responses.assert_second_is_cache_hit_by_header("cf-cache-status", "hit");
+        assert!(responses
+            .check_second_is_cache_hit_by_header("x-cache", "HIT")
+            .is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_split_client {
+    use axum::extract::State;
+    use axum::http::HeaderMap;
+    use axum::routing::get;
+    use axum::routing::put;
+    use axum::Router;
+    use axum_extra::extract::cookie::Cookie as AxumCookie;
+    use axum_extra::extract::cookie::CookieJar as AxumCookieJar;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+
+    use crate::TestServer;
+
+    async fn get_user(headers: HeaderMap) -> String {
+        headers
+            .get("x-user")
+            .map(|value| value.to_str().unwrap().to_string())
+            .unwrap_or_else(|| "anonymous".to_string())
+    }
+
+    async fn put_session(cookies: AxumCookieJar) -> AxumCookieJar {
+        cookies.add(AxumCookie::new("session", "alice"))
+    }
+
+    async fn get_session(cookies: AxumCookieJar) -> String {
+        cookies
+            .get("session")
+            .map(|cookie| cookie.value().to_string())
+            .unwrap_or_else(|| "no-session".to_string())
+    }
+
+    async fn get_count(State(count): State<Arc<AtomicUsize>>) -> String {
+        let count = count.fetch_add(1, Ordering::SeqCst) + 1;
+        format!("count is {count}")
+    }
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .route("/user", get(get_user))
+            .route("/session", put(put_session))
+            .route("/session", get(get_session))
+            .route("/count", get(get_count))
+            .with_state(Arc::new(AtomicUsize::new(0)));
+
+        TestServer::builder().save_cookies().build(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_keep_headers_separate() {
+        let mut alice = new_test_server();
+        let mut bob = alice.split_client();
+
+        alice.add_header("x-user", "alice");
+        bob.add_header("x-user", "bob");
+
+        alice.get("/user").await.assert_text("alice");
+        bob.get("/user").await.assert_text("bob");
+    }
+
+    #[tokio::test]
+    async fn it_should_not_copy_existing_headers() {
+        let mut alice = new_test_server();
+        alice.add_header("x-user", "alice");
+
+        let bob = alice.split_client();
+
+        bob.get("/user").await.assert_text("anonymous");
+    }
+
+    #[tokio::test]
+    async fn it_should_keep_cookies_separate() {
+        let alice = new_test_server();
+        let bob = alice.split_client();
+
+        alice.put("/session").await;
+
+        alice.get("/session").await.assert_text("alice");
+        bob.get("/session").await.assert_text("no-session");
+    }
+
+    #[tokio::test]
+    async fn it_should_keep_expectations_separate() {
+        let mut alice = new_test_server();
+        let bob = alice.split_client();
+
+        alice.expect_failure();
+
+        bob.get("/user").await.assert_status_ok();
+    }
+
+    #[tokio::test]
+    async fn it_should_share_the_running_app() {
+        let alice = new_test_server();
+        let bob = alice.split_client();
+
+        alice.get("/count").await.assert_text("count is 1");
+        bob.get("/count").await.assert_text("count is 2");
+    }
+}
+
+#[cfg(test)]
+mod test_authenticate {
+    use crate::TestServer;
+    use axum::extract::Path;
+    use axum::http::HeaderMap;
+    use axum::routing::get;
+    use axum::routing::post;
+    use axum::Router;
+    use axum_extra::extract::cookie::Cookie as AxumCookie;
+    use axum_extra::extract::cookie::CookieJar as AxumCookieJar;
+    use std::sync::atomic::AtomicU32;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+
+    fn new_test_server(logins: Arc<AtomicU32>) -> TestServer {
+        let app = Router::new()
+            .route(
+                "/login/:name",
+                post(
+                    move |Path(name): Path<String>, jar: AxumCookieJar| async move {
+                        logins.fetch_add(1, Ordering::SeqCst);
+                        jar.add(AxumCookie::new("user", name))
+                    },
+                ),
+            )
+            .route(
+                "/whoami",
+                get(|jar: AxumCookieJar| async move {
+                    jar.get("user")
+                        .map(|cookie| cookie.value().to_string())
+                        .unwrap_or_else(|| "anonymous".to_string())
+                }),
+            )
+            .route(
+                "/token",
+                get(|headers: HeaderMap| async move {
+                    headers
+                        .get("x-token")
+                        .map(|token| token.to_str().unwrap().to_string())
+                        .unwrap_or_default()
+                }),
+            );
+
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_save_cookies_from_the_login_flow() {
+        let mut server = new_test_server(Arc::default());
+
+        server
+            .authenticate("alice", |server| async move {
+                server.post("/login/alice").await;
+            })
+            .await;
+
+        server.get("/whoami").await.assert_text("alice");
+    }
+
+    #[tokio::test]
+    async fn it_should_only_run_the_login_flow_once_per_identity() {
+        let logins = Arc::new(AtomicU32::new(0));
+        let mut server = new_test_server(logins.clone());
+
+        for _ in 0..3 {
+            server
+                .authenticate("alice", |server| async move {
+                    server.post("/login/alice").await;
+                })
+                .await;
+        }
+
+        assert_eq!(logins.load(Ordering::SeqCst), 1);
+        server.get("/whoami").await.assert_text("alice");
+    }
+
+    #[tokio::test]
+    async fn it_should_switch_between_identities() {
+        let mut server = new_test_server(Arc::default());
+
+        server
+            .authenticate("alice", |server| async move {
+                server.post("/login/alice").await;
+            })
+            .await;
+        server
+            .authenticate("bob", |server| async move {
+                server.post("/login/bob").await;
+            })
+            .await;
+
+        server.get("/whoami").await.assert_text("bob");
+
+        server.as_user("alice");
+        server.get("/whoami").await.assert_text("alice");
+
+        server.as_anonymous();
+        server.get("/whoami").await.assert_text("anonymous");
+
+        server.as_user("bob");
+        server.get("/whoami").await.assert_text("bob");
+    }
+
+    #[tokio::test]
+    async fn it_should_keep_headers_added_during_the_login_flow() {
+        let mut server = new_test_server(Arc::default());
+
+        server
+            .authenticate("alice", |server| async move {
+                server.add_header("x-token", "alice-token");
+            })
+            .await;
+        server.get("/token").await.assert_text("alice-token");
+
+        server.as_anonymous();
+        server.get("/token").await.assert_text("");
+
+        server.as_user("alice");
+        server.get("/token").await.assert_text("alice-token");
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_switching_to_unknown_identity() {
+        let mut server = new_test_server(Arc::default());
+
+        server.as_user("alice");
+    }
+}
+
+#[cfg(test)]
+mod test_with_env_vars {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+    use std::env;
+
+    const FEATURE_KEY: &str = "AXUM_TEST_WITH_ENV_VARS_FEATURE";
+
+    async fn get_feature() -> String {
+        env::var(FEATURE_KEY).unwrap_or_else(|_| "off".to_string())
+    }
+
+    fn new_app() -> Router {
+        Router::new().route("/feature", get(get_feature))
+    }
+
+    #[tokio::test]
+    async fn it_should_set_env_vars_while_server_is_alive() {
+        let server = TestServer::builder()
+            .with_env_vars([(FEATURE_KEY, "on")])
+            .build(new_app())
+            .unwrap();
+
+        server.get("/feature").await.assert_text("on");
+    }
+
+    #[tokio::test]
+    async fn it_should_wait_for_other_tests_setting_env_vars() {
+        let server = TestServer::builder()
+            .with_env_vars([(FEATURE_KEY, "first")])
+            .build(new_app())
+            .unwrap();
+
+        let other_thread = std::thread::spawn(|| {
+            TestServer::builder()
+                .with_env_vars([(FEATURE_KEY, "second")])
+                .build(new_app())
+                .unwrap();
+        });
+
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        server.get("/feature").await.assert_text("first");
+
+        drop(server);
+        other_thread.join().unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn it_should_set_env_vars_from_tasks_on_multi_thread_runtimes() {
+        let server = tokio::spawn(async {
+            TestServer::builder()
+                .with_env_vars([(FEATURE_KEY, "spawned")])
+                .build(new_app())
+                .unwrap()
+        })
+        .await
+        .unwrap();
+
+        server.get("/feature").await.assert_text("spawned");
+    }
+}
+
+#[cfg(test)]
+mod test_seed {
+    use axum::routing::get;
+    use axum::Router;
+
+    use crate::ChaosConfig;
+    use crate::TestServer;
+
+    fn new_app() -> Router {
+        Router::new().route("/ping", get(|| async { "pong!" }))
+    }
+
+    fn new_seeded_server(seed: u64) -> TestServer {
+        TestServer::builder()
+            .with_seed(seed)
+            .with_chaos(ChaosConfig {
+                server_error_rate: 0.5,
+                ..ChaosConfig::default()
+            })
+            .build(new_app())
+            .unwrap()
+    }
+
+    async fn collect_idempotency_keys(server: &TestServer) -> Vec<String> {
+        let mut keys = Vec::new();
+        for _ in 0..3 {
+            let response = server.get("/ping").random_idempotency_key().await;
+            keys.push(response.idempotency_key());
+        }
+
+        keys
+    }
+
+    async fn collect_status_codes(server: &TestServer) -> Vec<u16> {
+        let mut status_codes = Vec::new();
+        for _ in 0..20 {
+            status_codes.push(server.get("/ping").await.status_code().as_u16());
+        }
+
+        status_codes
+    }
+
+    #[tokio::test]
+    async fn it_should_return_the_seed_given() {
+        let server = new_seeded_server(123);
+
+        assert_eq!(server.seed(), 123);
+    }
+
+    #[tokio::test]
+    async fn it_should_generate_the_same_idempotency_keys_for_the_same_seed() {
+        let first = collect_idempotency_keys(&new_seeded_server(123)).await;
+        let second = collect_idempotency_keys(&new_seeded_server(123)).await;
+        let other = collect_idempotency_keys(&new_seeded_server(456)).await;
+
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+    }
+
+    #[tokio::test]
+    async fn it_should_seed_chaos_using_the_server_seed() {
+        let first = collect_status_codes(&new_seeded_server(123)).await;
+        let second = collect_status_codes(&new_seeded_server(123)).await;
+        let other = collect_status_codes(&new_seeded_server(456)).await;
+
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+    }
+
+    #[tokio::test]
+    async fn it_should_reproduce_chaos_using_the_seed_reported() {
+        let chaos_server = TestServer::builder()
+            .with_chaos(ChaosConfig {
+                seed: 789,
+                server_error_rate: 0.5,
+                ..ChaosConfig::default()
+            })
+            .build(new_app())
+            .unwrap();
+        let reported_seed = chaos_server.seed();
+
+        let first = collect_status_codes(&chaos_server).await;
+        let second = collect_status_codes(&new_seeded_server(reported_seed)).await;
+
+        assert_eq!(reported_seed, 789);
+        assert_eq!(first, second);
+    }
+
+    #[tokio::test]
+    async fn it_should_pick_the_same_random_port_for_the_same_seed() {
+        let first_port = TestServer::builder()
+            .http_transport()
+            .with_seed(321)
+            .build(new_app())
+            .unwrap()
+            .server_address()
+            .unwrap()
+            .port();
+
+        // Lets the first server shut down, freeing its port.
+        tokio::task::yield_now().await;
+
+        let second_port = TestServer::builder()
+            .http_transport()
+            .with_seed(321)
+            .build(new_app())
+            .unwrap()
+            .server_address()
+            .unwrap()
+            .port();
+
+        assert_eq!(first_port, second_port);
+    }
+
+    #[tokio::test]
+    async fn it_should_pick_different_seeds_by_default() {
+        let first = TestServer::new(new_app()).unwrap();
+        let second = TestServer::new(new_app()).unwrap();
+
+        assert_ne!(first.seed(), second.seed());
+    }
+}
+
+#[cfg(test)]
+mod test_wait_until_ready {
+    use axum::extract::State;
+    use axum::routing::get;
+    use axum::Router;
+    use http::StatusCode;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crate::ChaosConfig;
+    use crate::TestServer;
+
+    /// Becomes ready after being polled a few times.
+    async fn route_get_healthz(State(polls): State<Arc<AtomicUsize>>) -> StatusCode {
+        match polls.fetch_add(1, Ordering::SeqCst) {
+            0..=2 => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::OK,
+        }
+    }
+
+    fn new_app(polls: Arc<AtomicUsize>) -> Router {
+        Router::new()
+            .route("/healthz", get(route_get_healthz))
+            .route(
+                "/never-ready",
+                get(|| async { StatusCode::SERVICE_UNAVAILABLE }),
+            )
+            .route("/ping", get(|| async { "pong!" }))
+            .with_state(polls)
+    }
+
+    #[tokio::test]
+    async fn it_should_wait_for_readiness_before_first_request() {
+        let polls = Arc::new(AtomicUsize::new(0));
+        let server = TestServer::builder()
+            .wait_until_ready("/healthz", Duration::from_secs(5))
+            .build(new_app(polls.clone()))
+            .unwrap();
+
+        server.get("/ping").await.assert_text("pong!");
+        assert_eq!(polls.load(Ordering::SeqCst), 4);
+
+        // Later requests do not poll again.
+        server.get("/ping").await.assert_text("pong!");
+        assert_eq!(polls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn it_should_wait_for_readiness_over_http_transport() {
+        let polls = Arc::new(AtomicUsize::new(0));
+        let server = TestServer::builder()
+            .http_transport()
+            .wait_until_ready("/healthz", Duration::from_secs(5))
+            .build(new_app(polls.clone()))
+            .unwrap();
+
+        server.get("/ping").await.assert_text("pong!");
+        assert_eq!(polls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn it_should_not_record_readiness_polls_in_metrics() {
+        let polls = Arc::new(AtomicUsize::new(0));
+        let server = TestServer::builder()
+            .record_metrics()
+            .wait_until_ready("/healthz", Duration::from_secs(5))
+            .build(new_app(polls.clone()))
+            .unwrap();
+
+        server.get("/ping").await.assert_text("pong!");
+        assert_eq!(polls.load(Ordering::SeqCst), 4);
+        assert_eq!(server.metrics().total_hits(), 1);
+    }
+
+    #[tokio::test]
+    async fn it_should_not_apply_chaos_to_readiness_polls() {
+        let polls = Arc::new(AtomicUsize::new(0));
+        let chaos = ChaosConfig {
+            server_error_rate: 1.0,
+            ..ChaosConfig::default()
+        };
+        let server = TestServer::builder()
+            .with_chaos(chaos)
+            .wait_until_ready("/healthz", Duration::from_secs(5))
+            .build(new_app(polls.clone()))
+            .unwrap();
+
+        server.wait_until_ready().await.unwrap();
+        assert_eq!(polls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn it_should_wait_for_readiness_when_called_directly() {
+        let polls = Arc::new(AtomicUsize::new(0));
+        let server = TestServer::builder()
+            .http_transport()
+            .wait_until_ready("/healthz", Duration::from_secs(5))
+            .build(new_app(polls.clone()))
+            .unwrap();
+
+        server.wait_until_ready().await.unwrap();
+        assert_eq!(polls.load(Ordering::SeqCst), 4);
+
+        // Requests afterwards do not poll again.
+        server.get("/ping").await.assert_text("pong!");
+        assert_eq!(polls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn it_should_error_when_not_ready_within_timeout_when_called_directly() {
+        let polls = Arc::new(AtomicUsize::new(0));
+        let server = TestServer::builder()
+            .wait_until_ready("/never-ready", Duration::from_millis(200))
+            .build(new_app(polls))
+            .unwrap();
+
+        let result = server.wait_until_ready().await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_fail_when_not_ready_within_timeout() {
+        let polls = Arc::new(AtomicUsize::new(0));
+        let server = TestServer::builder()
+            .wait_until_ready("/never-ready", Duration::from_millis(200))
+            .build(new_app(polls))
+            .unwrap();
+
+        server.get("/ping").await;
+    }
+}
+
+#[cfg(test)]
+mod test_get {
+    use super::*;
+
+    use axum::routing::get;
+    use axum::Router;
+    use reserve_port::ReservedSocketAddr;
+
+    async fn get_ping() -> &'static str {
+        "pong!"
+    }
+
+    #[tokio::test]
+    async fn it_should_get_using_relative_path_with_slash() {
+        let app = Router::new().route("/ping", get(get_ping));
+        let server = TestServer::new(app).expect("Should create test server");
+
+        // Get the request _with_ slash
+        server.get(&"/ping").await.assert_text(&"pong!");
+    }
+
+    #[tokio::test]
+    async fn it_should_get_using_relative_path_without_slash() {
+        let app = Router::new().route("/ping", get(get_ping));
+        let server = TestServer::new(app).expect("Should create test server");
+
+        // Get the request _without_ slash
+        server.get(&"ping").await.assert_text(&"pong!");
+    }
+
+    #[tokio::test]
+    async fn it_should_get_using_absolute_path() {
+        // Build an application with a route.
+        let app = Router::new().route("/ping", get(get_ping));
+
+        // Reserve an address
+        let reserved_address = ReservedSocketAddr::reserve_random_socket_addr().unwrap();
+        let ip = reserved_address.ip();
+        let port = reserved_address.port();
+
+        // Run the server.
+        let server = TestServer::builder()
+            .http_transport_with_ip_port(Some(ip), Some(port))
+            .build(app)
+            .with_context(|| format!("Should create test server with address {}:{}", ip, port))
+            .unwrap();
+
+        // Get the request.
+        let absolute_url = format!("http://{ip}:{port}/ping");
+        let response = server.get(&absolute_url).await;
+
+        response.assert_text(&"pong!");
+        let request_path = response.request_url();
+        assert_eq!(request_path.to_string(), format!("http://{ip}:{port}/ping"));
+    }
+
+    #[tokio::test]
+    async fn it_should_get_using_absolute_path_and_restricted_if_path_is_for_server() {
+        // Build an application with a route.
+        let app = Router::new().route("/ping", get(get_ping));
+
+        // Reserve an IP / Port
+        let reserved_address = ReservedSocketAddr::reserve_random_socket_addr().unwrap();
+        let ip = reserved_address.ip();
+        let port = reserved_address.port();
+
+        // Run the server.
+        let server = TestServer::builder()
+            .http_transport_with_ip_port(Some(ip), Some(port))
+            .restrict_requests_with_http_schema() // Key part of the test!
+            .build(app)
+            .with_context(|| format!("Should create test server with address {}:{}", ip, port))
+            .unwrap();
+
+        // Get the request.
+        let absolute_url = format!("http://{ip}:{port}/ping");
+        let response = server.get(&absolute_url).await;
+
+        response.assert_text(&"pong!");
+        let request_path = response.request_url();
+        assert_eq!(request_path.to_string(), format!("http://{ip}:{port}/ping"));
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_not_get_using_absolute_path_if_restricted_and_different_port() {
+        // Build an application with a route.
+        let app = Router::new().route("/ping", get(get_ping));
+
+        // Reserve an IP / Port
+        let reserved_address = ReservedSocketAddr::reserve_random_socket_addr().unwrap();
+        let ip = reserved_address.ip();
+        let mut port = reserved_address.port();
+
+        // Run the server.
+        let server = TestServer::builder()
+            .http_transport_with_ip_port(Some(ip), Some(port))
+            .restrict_requests_with_http_schema() // Key part of the test!
+            .build(app)
+            .with_context(|| format!("Should create test server with address {}:{}", ip, port))
+            .unwrap();
+
+        // Get the request.
+        port += 1; // << Change the port to be off by one and not match the server
+        let absolute_url = format!("http://{ip}:{port}/ping");
+        server.get(&absolute_url).await;
+    }
+
+    #[tokio::test]
+    async fn it_should_work_in_parallel() {
+        let app = Router::new().route("/ping", get(get_ping));
+        let server = TestServer::new(app).expect("Should create test server");
+
+        let future1 = async { server.get("/ping").await };
+        let future2 = async { server.get("/ping").await };
+        let (r1, r2) = tokio::join!(future1, future2);
+
+        assert_eq!(r1.text(), r2.text());
+    }
+
+    #[tokio::test]
+    async fn it_should_work_in_parallel_with_sleeping_requests() {
+        let app = axum::Router::new().route(
+            &"/slow",
+            axum::routing::get(|| async {
+                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                "hello!"
+            }),
+        );
+
+        let server = TestServer::new(app).expect("Should create test server");
+
+        let future1 = async { server.get("/slow").await };
+        let future2 = async { server.get("/slow").await };
+        let (r1, r2) = tokio::join!(future1, future2);
+
+        assert_eq!(r1.text(), r2.text());
+    }
+}
+
+#[cfg(feature = "reqwest")]
+#[cfg(test)]
+mod test_reqwest_get {
+    use super::*;
+
+    use axum::routing::get;
+    use axum::Router;
+
+    async fn get_ping() -> &'static str {
+        "pong!"
+    }
+
+    #[tokio::test]
+    async fn it_should_get_using_relative_path_with_slash() {
+        let app = Router::new().route("/ping", get(get_ping));
+        let server = TestServer::builder()
+            .http_transport()
+            .build(app)
+            .expect("Should create test server");
+
+        let response = server
+            .reqwest_get(&"/ping")
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+
+        assert_eq!(response, "pong!");
+    }
+}
+
+#[cfg(feature = "reqwest")]
+#[cfg(test)]
+mod test_configure_reqwest {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+    use http::HeaderMap;
+    use reqwest::Proxy;
+
+    async fn get_user_agent(headers: HeaderMap) -> String {
+        headers
+            .get("user-agent")
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    fn new_app() -> Router {
+        Router::new().route("/user-agent", get(get_user_agent))
+    }
+
+    #[tokio::test]
+    async fn it_should_apply_configuration_to_the_client() {
+        let server = TestServer::builder()
+            .http_transport()
+            .configure_reqwest(|builder| builder.user_agent("axum-test-agent"))
+            .build(new_app())
+            .unwrap();
+
+        let text = server
+            .reqwest_get("/user-agent")
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+
+        assert_eq!(text, "axum-test-agent");
+    }
+
+    #[tokio::test]
+    async fn it_should_apply_configurations_in_order() {
+        let server = TestServer::builder()
+            .http_transport()
+            .configure_reqwest(|builder| builder.user_agent("first"))
+            .configure_reqwest(|builder| builder.user_agent("second"))
+            .build(new_app())
+            .unwrap();
+
+        let text = server
+            .reqwest_get("/user-agent")
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+
+        assert_eq!(text, "second");
+    }
+
+    #[tokio::test]
+    async fn it_should_send_requests_through_a_proxy() {
+        let proxy_app = Router::new().route("/user-agent", get(|| async { "answered by proxy" }));
+        let proxy_server = TestServer::builder()
+            .http_transport()
+            .build(proxy_app)
+            .unwrap();
+        let proxy_url = proxy_server.server_url("/").unwrap();
+
+        let server = TestServer::builder()
+            .http_transport()
+            .configure_reqwest(move |builder| {
+                builder.proxy(Proxy::http(proxy_url.as_str()).unwrap())
+            })
+            .build(new_app())
+            .unwrap();
+
+        let text = server
+            .reqwest_client()
+            .get("http://upstream.invalid/user-agent")
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+
+        assert_eq!(text, "answered by proxy");
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_getting_the_client_on_mock_transport() {
+        let server = TestServer::builder()
+            .mock_transport()
+            .build(new_app())
+            .unwrap();
+
+        let _ = server.reqwest_client();
+    }
+}
+
+#[cfg(feature = "reqwest")]
+#[cfg(test)]
+mod test_reqwest_post {
+    use super::*;
+
+    use axum::routing::post;
+    use axum::Json;
+    use axum::Router;
+    use serde::Deserialize;
+
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+    struct TestBody {
+        number: u32,
+        text: String,
+    }
+
+    async fn post_json(Json(body): Json<TestBody>) -> Json<TestBody> {
+        let response = TestBody {
+            number: body.number * 2,
+            text: format!("{}_plus_response", body.text),
+        };
+
+        Json(response)
+    }
+
+    #[tokio::test]
+    async fn it_should_post_and_receive_json() {
+        let app = Router::new().route("/json", post(post_json));
+        let server = TestServer::builder()
+            .http_transport()
+            .build(app)
+            .expect("Should create test server");
 
-            // We only set the host/port if the scheme is also present.
-            if let Some(authority) = path_uri.authority() {
-                url.set_host(Some(authority.host()))
-                    .map_err(|_| anyhow!("Failed to set host for request, with path '{path}'"))?;
-                url.set_port(authority.port().map(|p| p.as_u16()))
-                    .map_err(|_| anyhow!("Failed to set port for request, with path '{path}'"))?;
+        let response = server
+            .reqwest_post(&"/json")
+            .json(&TestBody {
+                number: 111,
+                text: format!("request"),
+            })
+            .send()
+            .await
+            .unwrap()
+            .json::<TestBody>()
+            .await
+            .unwrap();
 
-                // todo, add username:password support
+        assert_eq!(
+            response,
+            TestBody {
+                number: 222,
+                text: format!("request_plus_response"),
             }
-        }
+        );
     }
+}
 
-    // Why does this exist?
-    //
-    // This exists to allow `server.get("/users")` and `server.get("users")` (without a slash)
-    // to go to the same place.
-    //
-    // It does this by saying ...
-    //  - if there is a scheme, it's a full path.
-    //  - if no scheme, it must be a path
-    //
-    if is_absolute_uri(&path_uri) {
-        url.set_path(path_uri.path());
+#[cfg(test)]
+mod test_server_address {
+    use super::*;
 
-        // In this path we are replacing, so drop any query params on the original url.
-        if url.query().is_some() {
-            url.set_query(None);
-        }
-    } else {
-        // Grab everything up until the query parameters, or everything after that
-        let calculated_path = path.split('?').next().unwrap_or(path);
-        url.set_path(calculated_path);
+    use axum::Router;
+    use local_ip_address::local_ip;
+    use regex::Regex;
+    use reserve_port::ReservedPort;
 
-        // Move any query parameters from the url to the query params store.
-        if let Some(url_query) = url.query() {
-            query_params.add_raw(url_query.to_string());
-            url.set_query(None);
-        }
-    }
+    #[tokio::test]
+    async fn it_should_return_address_used_from_config() {
+        let reserved_port = ReservedPort::random().unwrap();
+        let ip = local_ip().unwrap();
+        let port = reserved_port.port();
 
-    if let Some(path_query) = path_uri.query() {
-        query_params.add_raw(path_query.to_string());
-    }
+        // Build an application with a route.
+        let app = Router::new();
+        let server = TestServer::builder()
+            .http_transport_with_ip_port(Some(ip), Some(port))
+            .build(app)
+            .with_context(|| format!("Should create test server with address {}:{}", ip, port))
+            .unwrap();
 
-    Ok(url)
-}
+        let expected_ip_port = format!("http://{}:{}/", ip, reserved_port.port());
+        assert_eq!(
+            server.server_address().unwrap().to_string(),
+            expected_ip_port
+        );
+    }
 
-fn is_absolute_uri(path_uri: &Uri) -> bool {
-    path_uri.scheme_str().is_some()
-}
+    #[tokio::test]
+    async fn it_should_return_default_address_without_ending_slash() {
+        let app = Router::new();
+        let server = TestServer::builder()
+            .http_transport()
+            .build(app)
+            .expect("Should create test server");
 
-fn has_different_schema(base_url: &Url, path_uri: &Uri) -> bool {
-    if let Some(scheme) = path_uri.scheme_str() {
-        return scheme != base_url.scheme();
+        let address_regex = Regex::new("^http://127\\.0\\.0\\.1:[0-9]+/$").unwrap();
+        let is_match = address_regex.is_match(&server.server_address().unwrap().to_string());
+        assert!(is_match);
     }
 
-    false
-}
+    #[tokio::test]
+    async fn it_should_return_none_on_mock_transport() {
+        let app = Router::new();
+        let server = TestServer::builder()
+            .mock_transport()
+            .build(app)
+            .expect("Should create test server");
 
-fn has_different_authority(base_url: &Url, path_uri: &Uri) -> bool {
-    if let Some(authority) = path_uri.authority() {
-        return authority.as_str() != base_url.authority();
+        assert!(server.server_address().is_none());
     }
-
-    false
 }
 
 #[cfg(test)]
-mod test_build_url {
+mod test_server_url {
     use super::*;
 
-    #[test]
-    fn it_should_copy_path_to_url_returned_when_restricted() {
-        let base_url = "http://example.com".parse::<Url>().unwrap();
-        let path = "/users";
-        let mut query_params = QueryParamsStore::new();
-        let result = build_url(base_url, &path, &mut query_params, true).unwrap();
+    use axum::Router;
+    use local_ip_address::local_ip;
+    use regex::Regex;
+    use reserve_port::ReservedPort;
 
-        assert_eq!("http://example.com/users", result.as_str());
-        assert!(query_params.is_empty());
-    }
+    #[tokio::test]
+    async fn it_should_return_address_with_url_on_http_ip_port() {
+        let reserved_port = ReservedPort::random().unwrap();
+        let ip = local_ip().unwrap();
+        let port = reserved_port.port();
 
-    #[test]
-    fn it_should_copy_all_query_params_to_store_when_restricted() {
-        let base_url = "http://example.com?base=aaa".parse::<Url>().unwrap();
-        let path = "/users?path=bbb&path-flag";
-        let mut query_params = QueryParamsStore::new();
-        let result = build_url(base_url, &path, &mut query_params, true).unwrap();
+        // Build an application with a route.
+        let app = Router::new();
+        let server = TestServer::builder()
+            .http_transport_with_ip_port(Some(ip), Some(port))
+            .build(app)
+            .with_context(|| format!("Should create test server with address {}:{}", ip, port))
+            .unwrap();
 
-        assert_eq!("http://example.com/users", result.as_str());
-        assert_eq!("base=aaa&path=bbb&path-flag", query_params.to_string());
+        let expected_ip_port_url = format!("http://{}:{}/users", ip, reserved_port.port());
+        let absolute_url = server.server_url("/users").unwrap().to_string();
+        assert_eq!(absolute_url, expected_ip_port_url);
     }
 
-    #[test]
-    fn it_should_not_replace_url_when_restricted_with_different_scheme() {
-        let base_url = "http://example.com?base=666".parse::<Url>().unwrap();
-        let path = "ftp://google.com:123/users.csv?limit=456";
-        let mut query_params = QueryParamsStore::new();
-        let result = build_url(base_url, &path, &mut query_params, true);
-
-        assert!(result.is_err());
-    }
+    #[tokio::test]
+    async fn it_should_return_address_with_url_on_random_http() {
+        let app = Router::new();
+        let server = TestServer::builder()
+            .http_transport()
+            .build(app)
+            .expect("Should create test server");
 
-    #[test]
-    fn it_should_not_replace_url_when_restricted_with_same_scheme() {
-        let base_url = "http://example.com?base=666".parse::<Url>().unwrap();
-        let path = "http://google.com:123/users.csv?limit=456";
-        let mut query_params = QueryParamsStore::new();
-        let result = build_url(base_url, &path, &mut query_params, true);
+        let address_regex =
+            Regex::new("^http://127\\.0\\.0\\.1:[0-9]+/users/123\\?filter=enabled$").unwrap();
+        let absolute_url = &server
+            .server_url(&"/users/123?filter=enabled")
+            .unwrap()
+            .to_string();
 
-        assert!(result.is_err());
+        let is_match = address_regex.is_match(absolute_url);
+        assert!(is_match);
     }
 
-    #[test]
-    fn it_should_block_url_when_restricted_with_same_scheme() {
-        let base_url = "http://example.com?base=666".parse::<Url>().unwrap();
-        let path = "http://google.com";
-        let mut query_params = QueryParamsStore::new();
-        let result = build_url(base_url, &path, &mut query_params, true);
+    #[tokio::test]
+    async fn it_should_error_on_mock_transport() {
+        // Build an application with a route.
+        let app = Router::new();
+        let server = TestServer::builder()
+            .mock_transport()
+            .build(app)
+            .expect("Should create test server");
 
-        assert!(result.is_err());
+        let result = server.server_url("/users");
+        assert!(matches!(result, Err(Error::NoServerAddress)));
     }
 
-    #[test]
-    fn it_should_block_url_when_restricted_and_same_domain_with_different_scheme() {
-        let base_url = "http://example.com?base=666".parse::<Url>().unwrap();
-        let path = "ftp://example.com/users";
-        let mut query_params = QueryParamsStore::new();
-        let result = build_url(base_url, &path, &mut query_params, true);
+    #[tokio::test]
+    async fn it_should_include_path_query_params() {
+        let reserved_port = ReservedPort::random().unwrap();
+        let ip = local_ip().unwrap();
+        let port = reserved_port.port();
 
-        assert!(result.is_err());
-    }
+        // Build an application with a route.
+        let app = Router::new();
+        let server = TestServer::builder()
+            .http_transport_with_ip_port(Some(ip), Some(port))
+            .build(app)
+            .with_context(|| format!("Should create test server with address {}:{}", ip, port))
+            .unwrap();
 
-    #[test]
-    fn it_should_copy_path_to_url_returned_when_unrestricted() {
-        let base_url = "http://example.com".parse::<Url>().unwrap();
-        let path = "/users";
-        let mut query_params = QueryParamsStore::new();
-        let result = build_url(base_url, &path, &mut query_params, false).unwrap();
+        let expected_url = format!(
+            "http://{}:{}/users?filter=enabled",
+            ip,
+            reserved_port.port()
+        );
+        let received_url = server
+            .server_url("/users?filter=enabled")
+            .unwrap()
+            .to_string();
 
-        assert_eq!("http://example.com/users", result.as_str());
-        assert!(query_params.is_empty());
+        assert_eq!(received_url, expected_url);
     }
 
-    #[test]
-    fn it_should_copy_all_query_params_to_store_when_unrestricted() {
-        let base_url = "http://example.com?base=aaa".parse::<Url>().unwrap();
-        let path = "/users?path=bbb&path-flag";
-        let mut query_params = QueryParamsStore::new();
-        let result = build_url(base_url, &path, &mut query_params, false).unwrap();
+    #[tokio::test]
+    async fn it_should_include_server_query_params() {
+        let reserved_port = ReservedPort::random().unwrap();
+        let ip = local_ip().unwrap();
+        let port = reserved_port.port();
 
-        assert_eq!("http://example.com/users", result.as_str());
-        assert_eq!("base=aaa&path=bbb&path-flag", query_params.to_string());
-    }
+        // Build an application with a route.
+        let app = Router::new();
+        let mut server = TestServer::builder()
+            .http_transport_with_ip_port(Some(ip), Some(port))
+            .build(app)
+            .with_context(|| format!("Should create test server with address {}:{}", ip, port))
+            .unwrap();
 
-    #[test]
-    fn it_should_copy_host_like_a_path_when_unrestricted() {
-        let base_url = "http://example.com".parse::<Url>().unwrap();
-        let path = "google.com";
-        let mut query_params = QueryParamsStore::new();
-        let result = build_url(base_url, &path, &mut query_params, false).unwrap();
+        server.add_query_param("filter", "enabled");
 
-        assert_eq!("http://example.com/google.com", result.as_str());
-        assert!(query_params.is_empty());
+        let expected_url = format!(
+            "http://{}:{}/users?filter=enabled",
+            ip,
+            reserved_port.port()
+        );
+        let received_url = server.server_url("/users").unwrap().to_string();
+
+        assert_eq!(received_url, expected_url);
     }
 
-    #[test]
-    fn it_should_copy_host_like_a_path_when_restricted() {
-        let base_url = "http://example.com".parse::<Url>().unwrap();
-        let path = "google.com";
-        let mut query_params = QueryParamsStore::new();
-        let result = build_url(base_url, &path, &mut query_params, true).unwrap();
+    #[tokio::test]
+    async fn it_should_include_server_and_path_query_params() {
+        let reserved_port = ReservedPort::random().unwrap();
+        let ip = local_ip().unwrap();
+        let port = reserved_port.port();
 
-        assert_eq!("http://example.com/google.com", result.as_str());
-        assert!(query_params.is_empty());
-    }
+        // Build an application with a route.
+        let app = Router::new();
+        let mut server = TestServer::builder()
+            .http_transport_with_ip_port(Some(ip), Some(port))
+            .build(app)
+            .with_context(|| format!("Should create test server with address {}:{}", ip, port))
+            .unwrap();
 
-    #[test]
-    fn it_should_replace_url_when_unrestricted() {
-        let base_url = "http://example.com?base=666".parse::<Url>().unwrap();
-        let path = "ftp://google.com:123/users.csv?limit=456";
-        let mut query_params = QueryParamsStore::new();
-        let result = build_url(base_url, &path, &mut query_params, false).unwrap();
+        server.add_query_param("filter", "enabled");
 
-        assert_eq!("ftp://google.com:123/users.csv", result.as_str());
-        assert_eq!("limit=456", query_params.to_string());
+        let expected_url = format!(
+            "http://{}:{}/users?filter=enabled&animal=donkeys",
+            ip,
+            reserved_port.port()
+        );
+        let received_url = server
+            .server_url("/users?animal=donkeys")
+            .unwrap()
+            .to_string();
+
+        assert_eq!(received_url, expected_url);
     }
+}
 
-    #[test]
-    fn it_should_allow_different_scheme_when_unrestricted() {
-        let base_url = "http://example.com".parse::<Url>().unwrap();
-        let path = "ftp://example.com";
-        let mut query_params = QueryParamsStore::new();
-        let result = build_url(base_url, &path, &mut query_params, false).unwrap();
+#[cfg(test)]
+mod test_ipv6_transport {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+    use regex::Regex;
+    use std::net::TcpStream;
 
-        assert_eq!("ftp://example.com/", result.as_str());
+    fn new_app() -> Router {
+        Router::new().route("/ping", get(|| async { "pong" }))
     }
 
-    #[test]
-    fn it_should_allow_different_host_when_unrestricted() {
-        let base_url = "http://example.com".parse::<Url>().unwrap();
-        let path = "http://google.com";
-        let mut query_params = QueryParamsStore::new();
-        let result = build_url(base_url, &path, &mut query_params, false).unwrap();
+    #[tokio::test]
+    async fn it_should_serve_requests_over_ipv6() {
+        let server = TestServer::builder()
+            .ipv6_transport()
+            .build(new_app())
+            .unwrap();
 
-        assert_eq!("http://google.com/", result.as_str());
+        server.get("/ping").await.assert_text("pong");
     }
 
-    #[test]
-    fn it_should_allow_different_port_when_unrestricted() {
-        let base_url = "http://example.com:123".parse::<Url>().unwrap();
-        let path = "http://example.com:456";
-        let mut query_params = QueryParamsStore::new();
-        let result = build_url(base_url, &path, &mut query_params, false).unwrap();
+    #[tokio::test]
+    async fn it_should_return_bracketed_server_url() {
+        let server = TestServer::builder()
+            .ipv6_transport()
+            .build(new_app())
+            .unwrap();
 
-        assert_eq!("http://example.com:456/", result.as_str());
+        let address_regex = Regex::new("^http://\\[::1\\]:[0-9]+/ping\\?name=Joe$").unwrap();
+        let absolute_url = server.server_url("/ping?name=Joe").unwrap().to_string();
+
+        assert!(address_regex.is_match(&absolute_url), "{absolute_url}");
     }
 
-    #[test]
-    fn it_should_allow_same_host_port_when_unrestricted() {
-        let base_url = "http://example.com:123".parse::<Url>().unwrap();
-        let path = "http://example.com:123";
-        let mut query_params = QueryParamsStore::new();
-        let result = build_url(base_url, &path, &mut query_params, false).unwrap();
+    #[tokio::test]
+    async fn it_should_serve_requests_to_the_server_url_host() {
+        let server = TestServer::builder()
+            .ipv6_transport()
+            .build(new_app())
+            .unwrap();
 
-        assert_eq!("http://example.com:123/", result.as_str());
+        let absolute_url = server.server_url("/ping").unwrap().to_string();
+        server.get(&absolute_url).await.assert_text("pong");
     }
 
-    #[test]
-    fn it_should_not_allow_different_scheme_when_restricted() {
-        let base_url = "http://example.com".parse::<Url>().unwrap();
-        let path = "ftp://example.com";
-        let mut query_params = QueryParamsStore::new();
-        let result = build_url(base_url, &path, &mut query_params, true);
+    #[tokio::test]
+    async fn it_should_serve_requests_over_ipv6_and_ipv4_when_dual_stack() {
+        let server = TestServer::builder()
+            .dual_stack_transport()
+            .build(new_app())
+            .unwrap();
 
-        assert!(result.is_err());
+        let server_url = server.server_url("/").unwrap();
+        assert_eq!(server_url.host_str(), Some("[::1]"));
+        server.get("/ping").await.assert_text("pong");
+
+        let port = server_url.port().unwrap();
+        assert!(TcpStream::connect(("127.0.0.1", port)).is_ok());
     }
+}
 
-    #[test]
-    fn it_should_not_allow_different_host_when_restricted() {
-        let base_url = "http://example.com".parse::<Url>().unwrap();
-        let path = "http://google.com";
-        let mut query_params = QueryParamsStore::new();
-        let result = build_url(base_url, &path, &mut query_params, true);
+#[cfg(test)]
+mod test_add_cookie {
+    use crate::TestServer;
 
-        assert!(result.is_err());
-    }
+    use axum::routing::get;
+    use axum::Router;
+    use axum_extra::extract::cookie::CookieJar;
+    use cookie::Cookie;
 
-    #[test]
-    fn it_should_not_allow_different_port_when_restricted() {
-        let base_url = "http://example.com:123".parse::<Url>().unwrap();
-        let path = "http://example.com:456";
-        let mut query_params = QueryParamsStore::new();
-        let result = build_url(base_url, &path, &mut query_params, true);
+    const TEST_COOKIE_NAME: &'static str = &"test-cookie";
 
-        assert!(result.is_err());
+    async fn get_cookie(cookies: CookieJar) -> (CookieJar, String) {
+        let cookie = cookies.get(&TEST_COOKIE_NAME);
+        let cookie_value = cookie
+            .map(|c| c.value().to_string())
+            .unwrap_or_else(|| "cookie-not-found".to_string());
+
+        (cookies, cookie_value)
     }
 
-    #[test]
-    fn it_should_allow_same_host_port_when_restricted() {
-        let base_url = "http://example.com:123".parse::<Url>().unwrap();
-        let path = "http://example.com:123";
-        let mut query_params = QueryParamsStore::new();
-        let result = build_url(base_url, &path, &mut query_params, true).unwrap();
+    #[tokio::test]
+    async fn it_should_send_cookies_added_to_request() {
+        let app = Router::new().route("/cookie", get(get_cookie));
+        let mut server = TestServer::new(app).expect("Should create test server");
 
-        assert_eq!("http://example.com:123/", result.as_str());
+        let cookie = Cookie::new(TEST_COOKIE_NAME, "my-custom-cookie");
+        server.add_cookie(cookie);
+
+        let response_text = server.get(&"/cookie").await.text();
+        assert_eq!(response_text, "my-custom-cookie");
     }
 }
 
 #[cfg(test)]
-mod test_new {
+mod test_add_cookies {
+    use crate::TestServer;
+
     use axum::routing::get;
     use axum::Router;
-    use std::net::SocketAddr;
+    use axum_extra::extract::cookie::CookieJar as AxumCookieJar;
+    use cookie::Cookie;
+    use cookie::CookieJar;
 
-    use crate::TestServer;
+    async fn route_get_cookies(cookies: AxumCookieJar) -> String {
+        let mut all_cookies = cookies
+            .iter()
+            .map(|cookie| format!("{}={}", cookie.name(), cookie.value()))
+            .collect::<Vec<String>>();
+        all_cookies.sort();
 
-    async fn get_ping() -> &'static str {
-        "pong!"
+        all_cookies.join(&", ")
     }
 
     #[tokio::test]
-    async fn it_should_run_into_make_into_service_with_connect_info_by_default() {
-        // Build an application with a route.
-        let app = Router::new()
-            .route("/ping", get(get_ping))
-            .into_make_service_with_connect_info::<SocketAddr>();
+    async fn it_should_send_all_cookies_added_by_jar() {
+        let app = Router::new().route("/cookies", get(route_get_cookies));
+        let mut server = TestServer::new(app).expect("Should create test server");
 
-        // Run the server.
-        let server = TestServer::new(app).expect("Should create test server");
+        // Build cookies to send up
+        let cookie_1 = Cookie::new("first-cookie", "my-custom-cookie");
+        let cookie_2 = Cookie::new("second-cookie", "other-cookie");
+        let mut cookie_jar = CookieJar::new();
+        cookie_jar.add(cookie_1);
+        cookie_jar.add(cookie_2);
 
-        // Get the request.
-        server.get(&"/ping").await.assert_text(&"pong!");
+        server.add_cookies(cookie_jar);
+
+        server
+            .get(&"/cookies")
+            .await
+            .assert_text("first-cookie=my-custom-cookie, second-cookie=other-cookie");
     }
 }
 
 #[cfg(test)]
-mod test_get {
-    use super::*;
+mod test_clear_cookies {
+    use crate::TestServer;
 
     use axum::routing::get;
     use axum::Router;
-    use reserve_port::ReservedSocketAddr;
+    use axum_extra::extract::cookie::CookieJar as AxumCookieJar;
+    use cookie::Cookie;
+    use cookie::CookieJar;
 
-    async fn get_ping() -> &'static str {
-        "pong!"
+    async fn route_get_cookies(cookies: AxumCookieJar) -> String {
+        let mut all_cookies = cookies
+            .iter()
+            .map(|cookie| format!("{}={}", cookie.name(), cookie.value()))
+            .collect::<Vec<String>>();
+        all_cookies.sort();
+
+        all_cookies.join(&", ")
     }
 
     #[tokio::test]
-    async fn it_should_get_using_relative_path_with_slash() {
-        let app = Router::new().route("/ping", get(get_ping));
-        let server = TestServer::new(app).expect("Should create test server");
+    async fn it_should_not_send_cookies_cleared() {
+        let app = Router::new().route("/cookies", get(route_get_cookies));
+        let mut server = TestServer::new(app).expect("Should create test server");
 
-        // Get the request _with_ slash
-        server.get(&"/ping").await.assert_text(&"pong!");
-    }
+        let cookie_1 = Cookie::new("first-cookie", "my-custom-cookie");
+        let cookie_2 = Cookie::new("second-cookie", "other-cookie");
+        let mut cookie_jar = CookieJar::new();
+        cookie_jar.add(cookie_1);
+        cookie_jar.add(cookie_2);
 
-    #[tokio::test]
-    async fn it_should_get_using_relative_path_without_slash() {
-        let app = Router::new().route("/ping", get(get_ping));
-        let server = TestServer::new(app).expect("Should create test server");
+        server.add_cookies(cookie_jar);
 
-        // Get the request _without_ slash
-        server.get(&"ping").await.assert_text(&"pong!");
-    }
+        // The important bit of this test
+        server.clear_cookies();
 
-    #[tokio::test]
-    async fn it_should_get_using_absolute_path() {
-        // Build an application with a route.
-        let app = Router::new().route("/ping", get(get_ping));
+        server.get(&"/cookies").await.assert_text("");
+    }
+}
 
-        // Reserve an address
-        let reserved_address = ReservedSocketAddr::reserve_random_socket_addr().unwrap();
-        let ip = reserved_address.ip();
-        let port = reserved_address.port();
+#[cfg(test)]
+mod test_cookies_snapshot {
+    use crate::TestServer;
 
-        // Run the server.
-        let server = TestServer::builder()
-            .http_transport_with_ip_port(Some(ip), Some(port))
-            .build(app)
-            .with_context(|| format!("Should create test server with address {}:{}", ip, port))
-            .unwrap();
+    use axum::routing::get;
+    use axum::routing::put;
+    use axum::Router;
+    use axum_extra::extract::cookie::Cookie as AxumCookie;
+    use axum_extra::extract::cookie::CookieJar as AxumCookieJar;
+    use cookie::Cookie;
 
-        // Get the request.
-        let absolute_url = format!("http://{ip}:{port}/ping");
-        let response = server.get(&absolute_url).await;
+    async fn put_session(cookies: AxumCookieJar) -> AxumCookieJar {
+        cookies.add(AxumCookie::build(("session", "valid")).path("/app"))
+    }
 
-        response.assert_text(&"pong!");
-        let request_path = response.request_url();
-        assert_eq!(request_path.to_string(), format!("http://{ip}:{port}/ping"));
+    async fn get_session(cookies: AxumCookieJar) -> String {
+        cookies
+            .get("session")
+            .map(|cookie| cookie.value().to_string())
+            .unwrap_or_else(|| "no-session".to_string())
     }
 
-    #[tokio::test]
-    async fn it_should_get_using_absolute_path_and_restricted_if_path_is_for_server() {
-        // Build an application with a route.
-        let app = Router::new().route("/ping", get(get_ping));
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .route("/app/session", put(put_session))
+            .route("/app/session", get(get_session));
 
-        // Reserve an IP / Port
-        let reserved_address = ReservedSocketAddr::reserve_random_socket_addr().unwrap();
-        let ip = reserved_address.ip();
-        let port = reserved_address.port();
+        TestServer::builder().save_cookies().build(app).unwrap()
+    }
 
-        // Run the server.
-        let server = TestServer::builder()
-            .http_transport_with_ip_port(Some(ip), Some(port))
-            .restrict_requests_with_http_schema() // Key part of the test!
-            .build(app)
-            .with_context(|| format!("Should create test server with address {}:{}", ip, port))
-            .unwrap();
+    #[tokio::test]
+    async fn it_should_return_saved_cookies() {
+        let server = new_test_server();
+        server.put("/app/session").await;
 
-        // Get the request.
-        let absolute_url = format!("http://{ip}:{port}/ping");
-        let response = server.get(&absolute_url).await;
+        let snapshot = server.cookies_snapshot();
+        let session = snapshot.get("session").unwrap();
 
-        response.assert_text(&"pong!");
-        let request_path = response.request_url();
-        assert_eq!(request_path.to_string(), format!("http://{ip}:{port}/ping"));
+        assert_eq!(session.value(), "valid");
+        assert_eq!(session.path(), Some("/app"));
     }
 
     #[tokio::test]
-    #[should_panic]
-    async fn it_should_not_get_using_absolute_path_if_restricted_and_different_port() {
-        // Build an application with a route.
-        let app = Router::new().route("/ping", get(get_ping));
-
-        // Reserve an IP / Port
-        let reserved_address = ReservedSocketAddr::reserve_random_socket_addr().unwrap();
-        let ip = reserved_address.ip();
-        let mut port = reserved_address.port();
+    async fn it_should_not_store_changes_to_the_snapshot() {
+        let server = new_test_server();
+        server.put("/app/session").await;
 
-        // Run the server.
-        let server = TestServer::builder()
-            .http_transport_with_ip_port(Some(ip), Some(port))
-            .restrict_requests_with_http_schema() // Key part of the test!
-            .build(app)
-            .with_context(|| format!("Should create test server with address {}:{}", ip, port))
-            .unwrap();
+        let mut snapshot = server.cookies_snapshot();
+        snapshot.add(Cookie::new("session", "changed"));
 
-        // Get the request.
-        port += 1; // << Change the port to be off by one and not match the server
-        let absolute_url = format!("http://{ip}:{port}/ping");
-        server.get(&absolute_url).await;
+        server.get("/app/session").await.assert_text("valid");
     }
 
     #[tokio::test]
-    async fn it_should_work_in_parallel() {
-        let app = Router::new().route("/ping", get(get_ping));
-        let server = TestServer::new(app).expect("Should create test server");
+    async fn it_should_stop_sending_expired_cookies() {
+        let mut server = new_test_server();
+        server.put("/app/session").await;
 
-        let future1 = async { server.get("/ping").await };
-        let future2 = async { server.get("/ping").await };
-        let (r1, r2) = tokio::join!(future1, future2);
+        server.expire_cookie("session");
 
-        assert_eq!(r1.text(), r2.text());
+        server.get("/app/session").await.assert_text("no-session");
+        assert!(server.cookies_snapshot().get("session").is_some());
     }
 
     #[tokio::test]
-    async fn it_should_work_in_parallel_with_sleeping_requests() {
-        let app = axum::Router::new().route(
-            &"/slow",
-            axum::routing::get(|| async {
-                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-                "hello!"
-            }),
-        );
+    async fn it_should_send_changed_cookie_values() {
+        let mut server = new_test_server();
+        server.put("/app/session").await;
 
-        let server = TestServer::new(app).expect("Should create test server");
+        server.set_cookie_value("session", "tampered");
 
-        let future1 = async { server.get("/slow").await };
-        let future2 = async { server.get("/slow").await };
-        let (r1, r2) = tokio::join!(future1, future2);
+        server.get("/app/session").await.assert_text("tampered");
+        let snapshot = server.cookies_snapshot();
+        assert_eq!(snapshot.get("session").unwrap().path(), Some("/app"));
+    }
 
-        assert_eq!(r1.text(), r2.text());
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_panic_when_expiring_unknown_cookie() {
+        let mut server = new_test_server();
+
+        server.expire_cookie("session");
     }
 }
 
-#[cfg(feature = "reqwest")]
 #[cfg(test)]
-mod test_reqwest_get {
+mod test_add_header {
     use super::*;
 
+    use axum::async_trait;
+    use axum::extract::FromRequestParts;
     use axum::routing::get;
     use axum::Router;
+    use http::request::Parts;
+    use http::HeaderName;
+    use http::HeaderValue;
+    use hyper::StatusCode;
+    use std::marker::Sync;
 
-    async fn get_ping() -> &'static str {
-        "pong!"
+    use crate::TestServer;
+
+    const TEST_HEADER_NAME: &'static str = &"test-header";
+    const TEST_HEADER_CONTENT: &'static str = &"Test header content";
+
+    struct TestHeader(Vec<u8>);
+
+    #[async_trait]
+    impl<S: Sync> FromRequestParts<S> for TestHeader {
+        type Rejection = (StatusCode, &'static str);
+
+        async fn from_request_parts(
+            parts: &mut Parts,
+            _state: &S,
+        ) -> Result<TestHeader, Self::Rejection> {
+            parts
+                .headers
+                .get(HeaderName::from_static(TEST_HEADER_NAME))
+                .map(|v| TestHeader(v.as_bytes().to_vec()))
+                .ok_or((StatusCode::BAD_REQUEST, "Missing test header"))
+        }
+    }
+
+    async fn ping_header(TestHeader(header): TestHeader) -> Vec<u8> {
+        header
     }
 
     #[tokio::test]
-    async fn it_should_get_using_relative_path_with_slash() {
-        let app = Router::new().route("/ping", get(get_ping));
-        let server = TestServer::builder()
-            .http_transport()
-            .build(app)
-            .expect("Should create test server");
+    async fn it_should_send_header_added_to_server() {
+        // Build an application with a route.
+        let app = Router::new().route("/header", get(ping_header));
 
-        let response = server
-            .reqwest_get(&"/ping")
-            .send()
-            .await
-            .unwrap()
-            .text()
-            .await
-            .unwrap();
+        // Run the server.
+        let mut server = TestServer::new(app).expect("Should create test server");
+        server.add_header(
+            HeaderName::from_static(TEST_HEADER_NAME),
+            HeaderValue::from_static(TEST_HEADER_CONTENT),
+        );
 
-        assert_eq!(response, "pong!");
+        // Send a request with the header
+        let response = server.get(&"/header").await;
+
+        // Check it sent back the right text
+        response.assert_text(TEST_HEADER_CONTENT)
     }
 }
 
-#[cfg(feature = "reqwest")]
 #[cfg(test)]
-mod test_reqwest_post {
+mod test_clear_headers {
     use super::*;
 
-    use axum::routing::post;
-    use axum::Json;
+    use axum::async_trait;
+    use axum::extract::FromRequestParts;
+    use axum::routing::get;
     use axum::Router;
-    use serde::Deserialize;
+    use http::request::Parts;
+    use http::HeaderName;
+    use http::HeaderValue;
+    use hyper::StatusCode;
+    use std::marker::Sync;
 
-    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-    struct TestBody {
-        number: u32,
-        text: String,
-    }
+    use crate::TestServer;
 
-    async fn post_json(Json(body): Json<TestBody>) -> Json<TestBody> {
-        let response = TestBody {
-            number: body.number * 2,
-            text: format!("{}_plus_response", body.text),
-        };
+    const TEST_HEADER_NAME: &'static str = &"test-header";
+    const TEST_HEADER_CONTENT: &'static str = &"Test header content";
 
-        Json(response)
+    struct TestHeader(Vec<u8>);
+
+    #[async_trait]
+    impl<S: Sync> FromRequestParts<S> for TestHeader {
+        type Rejection = (StatusCode, &'static str);
+
+        async fn from_request_parts(
+            parts: &mut Parts,
+            _state: &S,
+        ) -> Result<TestHeader, Self::Rejection> {
+            parts
+                .headers
+                .get(HeaderName::from_static(TEST_HEADER_NAME))
+                .map(|v| TestHeader(v.as_bytes().to_vec()))
+                .ok_or((StatusCode::BAD_REQUEST, "Missing test header"))
+        }
     }
 
-    #[tokio::test]
-    async fn it_should_post_and_receive_json() {
-        let app = Router::new().route("/json", post(post_json));
-        let server = TestServer::builder()
-            .http_transport()
-            .build(app)
-            .expect("Should create test server");
+    async fn ping_header(TestHeader(header): TestHeader) -> Vec<u8> {
+        header
+    }
 
-        let response = server
-            .reqwest_post(&"/json")
-            .json(&TestBody {
-                number: 111,
-                text: format!("request"),
-            })
-            .send()
-            .await
-            .unwrap()
-            .json::<TestBody>()
-            .await
-            .unwrap();
+    #[tokio::test]
+    async fn it_should_not_send_headers_cleared_by_server() {
+        // Build an application with a route.
+        let app = Router::new().route("/header", get(ping_header));
 
-        assert_eq!(
-            response,
-            TestBody {
-                number: 222,
-                text: format!("request_plus_response"),
-            }
+        // Run the server.
+        let mut server = TestServer::new(app).expect("Should create test server");
+        server.add_header(
+            HeaderName::from_static(TEST_HEADER_NAME),
+            HeaderValue::from_static(TEST_HEADER_CONTENT),
         );
+        server.clear_headers();
+
+        // Send a request with the header
+        let response = server.get(&"/header").await;
+
+        // Check it sent back the right text
+        response.assert_status_bad_request();
+        response.assert_text("Missing test header");
+    }
+}
+
+#[cfg(test)]
+mod test_enable_csrf {
+    use axum::response::AppendHeaders;
+    use axum::routing::get;
+    use axum::routing::post;
+    use axum::Router;
+    use axum_extra::extract::cookie::CookieJar as AxumCookieJar;
+    use http::header::SET_COOKIE;
+    use http::HeaderMap;
+    use http::StatusCode;
+
+    use crate::CsrfConfig;
+    use crate::TestServer;
+
+    const CSRF_COOKIE: &str = "csrf";
+    const CSRF_HEADER: &str = "x-csrf-token";
+
+    async fn get_form() -> AppendHeaders<[(&'static str, &'static str); 1]> {
+        AppendHeaders([(SET_COOKIE.as_str(), "csrf=cookie-token")])
     }
-}
 
-#[cfg(test)]
-mod test_server_address {
-    use super::*;
+    async fn get_token_header() -> AppendHeaders<[(&'static str, &'static str); 2]> {
+        AppendHeaders([
+            (CSRF_HEADER, "header-token"),
+            (SET_COOKIE.as_str(), "csrf=header-token"),
+        ])
+    }
 
-    use axum::Router;
-    use local_ip_address::local_ip;
-    use regex::Regex;
-    use reserve_port::ReservedPort;
+    /// Accepts requests where the CSRF header matches the CSRF cookie.
+    async fn post_submit(cookies: AxumCookieJar, headers: HeaderMap) -> (StatusCode, String) {
+        let maybe_cookie_token = cookies.get(CSRF_COOKIE).map(|cookie| cookie.value());
+        let maybe_header_token = headers
+            .get(CSRF_HEADER)
+            .and_then(|value| value.to_str().ok());
 
-    #[tokio::test]
-    async fn it_should_return_address_used_from_config() {
-        let reserved_port = ReservedPort::random().unwrap();
-        let ip = local_ip().unwrap();
-        let port = reserved_port.port();
+        match (maybe_cookie_token, maybe_header_token) {
+            (Some(cookie_token), Some(header_token)) if cookie_token == header_token => {
+                (StatusCode::OK, header_token.to_string())
+            }
+            _ => (StatusCode::FORBIDDEN, "missing csrf token".to_string()),
+        }
+    }
 
-        // Build an application with a route.
-        let app = Router::new();
-        let server = TestServer::builder()
-            .http_transport_with_ip_port(Some(ip), Some(port))
-            .build(app)
-            .with_context(|| format!("Should create test server with address {}:{}", ip, port))
-            .unwrap();
+    async fn get_has_csrf_header(headers: HeaderMap) -> String {
+        headers.contains_key(CSRF_HEADER).to_string()
+    }
 
-        let expected_ip_port = format!("http://{}:{}/", ip, reserved_port.port());
-        assert_eq!(
-            server.server_address().unwrap().to_string(),
-            expected_ip_port
-        );
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .route("/form", get(get_form))
+            .route("/token", get(get_token_header))
+            .route("/submit", post(post_submit).get(get_has_csrf_header));
+
+        let mut server = TestServer::new(app).unwrap();
+        server.enable_csrf(CsrfConfig::new(CSRF_COOKIE, CSRF_HEADER));
+
+        server
     }
 
     #[tokio::test]
-    async fn it_should_return_default_address_without_ending_slash() {
-        let app = Router::new();
-        let server = TestServer::builder()
-            .http_transport()
-            .build(app)
-            .expect("Should create test server");
+    async fn it_should_send_token_from_cookie_on_mutating_requests() {
+        let server = new_test_server();
 
-        let address_regex = Regex::new("^http://127\\.0\\.0\\.1:[0-9]+/$").unwrap();
-        let is_match = address_regex.is_match(&server.server_address().unwrap().to_string());
-        assert!(is_match);
+        server.get("/form").await;
+        server
+            .post("/submit")
+            .expect_success()
+            .await
+            .assert_text("cookie-token");
     }
 
     #[tokio::test]
-    async fn it_should_return_none_on_mock_transport() {
-        let app = Router::new();
-        let server = TestServer::builder()
-            .mock_transport()
-            .build(app)
-            .expect("Should create test server");
+    async fn it_should_send_token_from_response_header() {
+        let server = new_test_server();
 
-        assert!(server.server_address().is_none());
+        server.get("/token").await;
+        server
+            .post("/submit")
+            .expect_success()
+            .await
+            .assert_text("header-token");
     }
-}
 
-#[cfg(test)]
-mod test_server_url {
-    use super::*;
+    #[tokio::test]
+    async fn it_should_not_send_token_on_get_requests() {
+        let server = new_test_server();
 
-    use axum::Router;
-    use local_ip_address::local_ip;
-    use regex::Regex;
-    use reserve_port::ReservedPort;
+        server.get("/form").await;
+        server.get("/submit").await.assert_text("false");
+    }
 
     #[tokio::test]
-    async fn it_should_return_address_with_url_on_http_ip_port() {
-        let reserved_port = ReservedPort::random().unwrap();
-        let ip = local_ip().unwrap();
-        let port = reserved_port.port();
-
-        // Build an application with a route.
-        let app = Router::new();
-        let server = TestServer::builder()
-            .http_transport_with_ip_port(Some(ip), Some(port))
-            .build(app)
-            .with_context(|| format!("Should create test server with address {}:{}", ip, port))
-            .unwrap();
+    async fn it_should_not_send_token_when_none_received() {
+        let server = new_test_server();
 
-        let expected_ip_port_url = format!("http://{}:{}/users", ip, reserved_port.port());
-        let absolute_url = server.server_url("/users").unwrap().to_string();
-        assert_eq!(absolute_url, expected_ip_port_url);
+        server
+            .post("/submit")
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
     }
 
     #[tokio::test]
-    async fn it_should_return_address_with_url_on_random_http() {
-        let app = Router::new();
-        let server = TestServer::builder()
-            .http_transport()
-            .build(app)
-            .expect("Should create test server");
+    async fn it_should_not_send_token_when_without_csrf() {
+        let server = new_test_server();
 
-        let address_regex =
-            Regex::new("^http://127\\.0\\.0\\.1:[0-9]+/users/123\\?filter=enabled$").unwrap();
-        let absolute_url = &server
-            .server_url(&"/users/123?filter=enabled")
-            .unwrap()
+        server.get("/form").await;
+        server
+            .post("/submit")
+            .without_csrf()
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+    }
+}
+
+#[cfg(test)]
+mod test_alias_host {
+    use crate::Error;
+    use crate::TestServer;
+    use axum::extract::Request;
+    use axum::routing::get;
+    use axum::Router;
+    use http::header;
+
+    async fn route_get_host_and_uri(request: Request) -> String {
+        let host = request
+            .headers()
+            .get(header::HOST)
+            .and_then(|host| host.to_str().ok())
+            .unwrap_or_default()
             .to_string();
+        let path_and_query = request
+            .uri()
+            .path_and_query()
+            .map(ToString::to_string)
+            .unwrap_or_default();
 
-        let is_match = address_regex.is_match(absolute_url);
-        assert!(is_match);
+        format!("{host} {path_and_query}")
+    }
+
+    fn new_test_router() -> Router {
+        Router::new().route("/users", get(route_get_host_and_uri))
     }
 
     #[tokio::test]
-    async fn it_should_error_on_mock_transport() {
-        // Build an application with a route.
-        let app = Router::new();
-        let server = TestServer::builder()
-            .mock_transport()
-            .build(app)
-            .expect("Should create test server");
+    async fn it_should_route_aliased_host_to_server() {
+        let mut server = TestServer::builder()
+            .restrict_requests_with_http_schema()
+            .build(new_test_router())
+            .unwrap();
+        server.alias_host("api.internal");
 
-        let result = server.server_url("/users");
-        assert!(result.is_err());
+        server
+            .get("http://api.internal/users?page=2")
+            .await
+            .assert_text("api.internal /users?page=2");
     }
 
     #[tokio::test]
-    async fn it_should_include_path_query_params() {
-        let reserved_port = ReservedPort::random().unwrap();
-        let ip = local_ip().unwrap();
-        let port = reserved_port.port();
-
-        // Build an application with a route.
-        let app = Router::new();
-        let server = TestServer::builder()
-            .http_transport_with_ip_port(Some(ip), Some(port))
-            .build(app)
-            .with_context(|| format!("Should create test server with address {}:{}", ip, port))
+    async fn it_should_route_aliased_host_over_http_transport() {
+        let mut server = TestServer::builder()
+            .http_transport()
+            .build(new_test_router())
             .unwrap();
+        server.alias_host("api.internal");
 
-        let expected_url = format!(
-            "http://{}:{}/users?filter=enabled",
-            ip,
-            reserved_port.port()
-        );
-        let received_url = server
-            .server_url("/users?filter=enabled")
-            .unwrap()
-            .to_string();
-
-        assert_eq!(received_url, expected_url);
+        server
+            .get("https://api.internal:8443/users")
+            .await
+            .assert_text("api.internal:8443 /users");
     }
 
     #[tokio::test]
-    async fn it_should_include_server_query_params() {
-        let reserved_port = ReservedPort::random().unwrap();
-        let ip = local_ip().unwrap();
-        let port = reserved_port.port();
-
-        // Build an application with a route.
-        let app = Router::new();
+    async fn it_should_match_alias_with_port_exactly() {
         let mut server = TestServer::builder()
-            .http_transport_with_ip_port(Some(ip), Some(port))
-            .build(app)
-            .with_context(|| format!("Should create test server with address {}:{}", ip, port))
+            .restrict_requests_with_http_schema()
+            .build(new_test_router())
             .unwrap();
+        server.alias_host("api.internal:8080");
 
-        server.add_query_param("filter", "enabled");
-
-        let expected_url = format!(
-            "http://{}:{}/users?filter=enabled",
-            ip,
-            reserved_port.port()
-        );
-        let received_url = server.server_url("/users").unwrap().to_string();
+        server
+            .get("http://api.internal:8080/users")
+            .await
+            .assert_text("api.internal:8080 /users");
 
-        assert_eq!(received_url, expected_url);
+        let result = server
+            .build_test_request_config(http::Method::GET, "http://api.internal:9090/users")
+            .unwrap_err();
+        assert!(matches!(
+            result.downcast_ref::<Error>(),
+            Some(Error::SchemeRejected { .. })
+        ));
     }
 
     #[tokio::test]
-    async fn it_should_include_server_and_path_query_params() {
-        let reserved_port = ReservedPort::random().unwrap();
-        let ip = local_ip().unwrap();
-        let port = reserved_port.port();
-
-        // Build an application with a route.
-        let app = Router::new();
+    async fn it_should_still_reject_hosts_not_aliased() {
         let mut server = TestServer::builder()
-            .http_transport_with_ip_port(Some(ip), Some(port))
-            .build(app)
-            .with_context(|| format!("Should create test server with address {}:{}", ip, port))
+            .restrict_requests_with_http_schema()
+            .build(new_test_router())
             .unwrap();
+        server.alias_host("api.internal");
 
-        server.add_query_param("filter", "enabled");
-
-        let expected_url = format!(
-            "http://{}:{}/users?filter=enabled&animal=donkeys",
-            ip,
-            reserved_port.port()
-        );
-        let received_url = server
-            .server_url("/users?animal=donkeys")
-            .unwrap()
-            .to_string();
-
-        assert_eq!(received_url, expected_url);
+        let result = server
+            .build_test_request_config(http::Method::GET, "http://other.internal/users")
+            .unwrap_err();
+        assert!(matches!(
+            result.downcast_ref::<Error>(),
+            Some(Error::SchemeRejected { .. })
+        ));
     }
 }
 
 #[cfg(test)]
-mod test_add_cookie {
+mod test_unicode_paths {
     use crate::TestServer;
-
+    use axum::extract::Path;
+    use axum::extract::Request;
     use axum::routing::get;
     use axum::Router;
-    use axum_extra::extract::cookie::CookieJar;
-    use cookie::Cookie;
 
-    const TEST_COOKIE_NAME: &'static str = &"test-cookie";
+    async fn get_raw_path(request: Request) -> String {
+        request.uri().path().to_string()
+    }
 
-    async fn get_cookie(cookies: CookieJar) -> (CookieJar, String) {
-        let cookie = cookies.get(&TEST_COOKIE_NAME);
-        let cookie_value = cookie
-            .map(|c| c.value().to_string())
-            .unwrap_or_else(|| "cookie-not-found".to_string());
+    async fn get_decoded_name(Path(name): Path<String>) -> String {
+        name
+    }
 
-        (cookies, cookie_value)
+    fn new_app() -> Router {
+        Router::new()
+            .route("/raw/*path", get(get_raw_path))
+            .route("/decoded/:name", get(get_decoded_name))
     }
 
     #[tokio::test]
-    async fn it_should_send_cookies_added_to_request() {
-        let app = Router::new().route("/cookie", get(get_cookie));
-        let mut server = TestServer::new(app).expect("Should create test server");
+    async fn it_should_percent_encode_unicode_paths_with_mock_transport() {
+        let server = TestServer::builder()
+            .mock_transport()
+            .build(new_app())
+            .unwrap();
 
-        let cookie = Cookie::new(TEST_COOKIE_NAME, "my-custom-cookie");
-        server.add_cookie(cookie);
+        let response = server.get("/raw/café").await;
+        response.assert_text("/raw/caf%C3%A9");
+        response.assert_request_path_encoded("/raw/caf%C3%A9");
 
-        let response_text = server.get(&"/cookie").await.text();
-        assert_eq!(response_text, "my-custom-cookie");
+        server.get("/decoded/café").await.assert_text("café");
     }
-}
 
-#[cfg(test)]
-mod test_add_cookies {
-    use crate::TestServer;
+    #[tokio::test]
+    async fn it_should_percent_encode_unicode_paths_with_http_transport() {
+        let server = TestServer::builder()
+            .http_transport()
+            .build(new_app())
+            .unwrap();
 
-    use axum::routing::get;
-    use axum::Router;
-    use axum_extra::extract::cookie::CookieJar as AxumCookieJar;
-    use cookie::Cookie;
-    use cookie::CookieJar;
+        let response = server.get("/raw/café").await;
+        response.assert_text("/raw/caf%C3%A9");
+        response.assert_request_path_encoded("/raw/caf%C3%A9");
 
-    async fn route_get_cookies(cookies: AxumCookieJar) -> String {
-        let mut all_cookies = cookies
-            .iter()
-            .map(|cookie| format!("{}={}", cookie.name(), cookie.value()))
-            .collect::<Vec<String>>();
-        all_cookies.sort();
+        server.get("/decoded/café").await.assert_text("café");
+    }
 
-        all_cookies.join(&", ")
+    #[tokio::test]
+    async fn it_should_fail_check_for_unencoded_path() {
+        let server = TestServer::new(new_app()).unwrap();
+
+        let response = server.get("/raw/café").await;
+        assert!(response.check_request_path_encoded("/raw/café").is_err());
     }
 
     #[tokio::test]
-    async fn it_should_send_all_cookies_added_by_jar() {
-        let app = Router::new().route("/cookies", get(route_get_cookies));
-        let mut server = TestServer::new(app).expect("Should create test server");
+    async fn it_should_encode_unicode_in_server_url() {
+        let server = TestServer::builder()
+            .http_transport()
+            .build(new_app())
+            .unwrap();
 
-        // Build cookies to send up
-        let cookie_1 = Cookie::new("first-cookie", "my-custom-cookie");
-        let cookie_2 = Cookie::new("second-cookie", "other-cookie");
-        let mut cookie_jar = CookieJar::new();
-        cookie_jar.add(cookie_1);
-        cookie_jar.add(cookie_2);
+        let url = server.server_url("/raw/café").unwrap();
+        assert_eq!(url.path(), "/raw/caf%C3%A9");
+    }
 
-        server.add_cookies(cookie_jar);
+    #[tokio::test]
+    async fn it_should_punycode_aliased_unicode_hosts() {
+        let app = Router::new().route(
+            "/host",
+            get(|request: Request| async move {
+                request.headers()["host"].to_str().unwrap().to_string()
+            }),
+        );
+        let mut server = TestServer::new(app).unwrap();
+        server.alias_host("café.example");
 
         server
-            .get(&"/cookies")
+            .get("http://café.example/host")
             .await
-            .assert_text("first-cookie=my-custom-cookie, second-cookie=other-cookie");
+            .assert_text("xn--caf-dma.example");
     }
 }
 
 #[cfg(test)]
-mod test_clear_cookies {
+mod test_cookie_domain {
     use crate::TestServer;
-
+    use axum::response::AppendHeaders;
     use axum::routing::get;
     use axum::Router;
-    use axum_extra::extract::cookie::CookieJar as AxumCookieJar;
-    use cookie::Cookie;
-    use cookie::CookieJar;
+    use axum_extra::extract::cookie::CookieJar;
+    use http::header;
 
-    async fn route_get_cookies(cookies: AxumCookieJar) -> String {
+    async fn route_login() -> AppendHeaders<[(header::HeaderName, &'static str); 3]> {
+        AppendHeaders([
+            (header::SET_COOKIE, "session=abc; Domain=.app.local"),
+            (header::SET_COOKIE, "tracking=xyz; Domain=other.local"),
+            (header::SET_COOKIE, "theme=dark"),
+        ])
+    }
+
+    async fn route_get_cookies(cookies: CookieJar) -> String {
         let mut all_cookies = cookies
             .iter()
             .map(|cookie| format!("{}={}", cookie.name(), cookie.value()))
             .collect::<Vec<String>>();
         all_cookies.sort();
 
-        all_cookies.join(&", ")
+        all_cookies.join(", ")
     }
 
-    #[tokio::test]
-    async fn it_should_not_send_cookies_cleared() {
-        let app = Router::new().route("/cookies", get(route_get_cookies));
-        let mut server = TestServer::new(app).expect("Should create test server");
-
-        let cookie_1 = Cookie::new("first-cookie", "my-custom-cookie");
-        let cookie_2 = Cookie::new("second-cookie", "other-cookie");
-        let mut cookie_jar = CookieJar::new();
-        cookie_jar.add(cookie_1);
-        cookie_jar.add(cookie_2);
-
-        server.add_cookies(cookie_jar);
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .route("/login", get(route_login))
+            .route("/cookies", get(route_get_cookies));
 
-        // The important bit of this test
-        server.clear_cookies();
+        let mut server = TestServer::builder()
+            .save_cookies()
+            .cookie_domain("app.local")
+            .build(app)
+            .unwrap();
+        server.alias_host("auth.app.local");
+        server.alias_host("www.app.local");
+        server.alias_host("other.local");
 
-        server.get(&"/cookies").await.assert_text("");
+        server
     }
-}
-
-#[cfg(test)]
-mod test_add_header {
-    use super::*;
-
-    use axum::async_trait;
-    use axum::extract::FromRequestParts;
-    use axum::routing::get;
-    use axum::Router;
-    use http::request::Parts;
-    use http::HeaderName;
-    use http::HeaderValue;
-    use hyper::StatusCode;
-    use std::marker::Sync;
-
-    use crate::TestServer;
-
-    const TEST_HEADER_NAME: &'static str = &"test-header";
-    const TEST_HEADER_CONTENT: &'static str = &"Test header content";
 
-    struct TestHeader(Vec<u8>);
-
-    #[async_trait]
-    impl<S: Sync> FromRequestParts<S> for TestHeader {
-        type Rejection = (StatusCode, &'static str);
+    #[tokio::test]
+    async fn it_should_send_domain_cookies_to_subdomain_aliases() {
+        let server = new_test_server();
 
-        async fn from_request_parts(
-            parts: &mut Parts,
-            _state: &S,
-        ) -> Result<TestHeader, Self::Rejection> {
-            parts
-                .headers
-                .get(HeaderName::from_static(TEST_HEADER_NAME))
-                .map(|v| TestHeader(v.as_bytes().to_vec()))
-                .ok_or((StatusCode::BAD_REQUEST, "Missing test header"))
-        }
-    }
+        server.get("http://auth.app.local/login").await;
 
-    async fn ping_header(TestHeader(header): TestHeader) -> Vec<u8> {
-        header
+        server
+            .get("http://www.app.local/cookies")
+            .await
+            .assert_text("session=abc, theme=dark");
     }
 
     #[tokio::test]
-    async fn it_should_send_header_added_to_server() {
-        // Build an application with a route.
-        let app = Router::new().route("/header", get(ping_header));
-
-        // Run the server.
-        let mut server = TestServer::new(app).expect("Should create test server");
-        server.add_header(
-            HeaderName::from_static(TEST_HEADER_NAME),
-            HeaderValue::from_static(TEST_HEADER_CONTENT),
-        );
+    async fn it_should_send_domain_cookies_to_the_cookie_domain() {
+        let server = new_test_server();
 
-        // Send a request with the header
-        let response = server.get(&"/header").await;
+        server.get("http://auth.app.local/login").await;
 
-        // Check it sent back the right text
-        response.assert_text(TEST_HEADER_CONTENT)
+        server
+            .get("/cookies")
+            .await
+            .assert_text("session=abc, theme=dark");
     }
-}
-
-#[cfg(test)]
-mod test_clear_headers {
-    use super::*;
-
-    use axum::async_trait;
-    use axum::extract::FromRequestParts;
-    use axum::routing::get;
-    use axum::Router;
-    use http::request::Parts;
-    use http::HeaderName;
-    use http::HeaderValue;
-    use hyper::StatusCode;
-    use std::marker::Sync;
 
-    use crate::TestServer;
-
-    const TEST_HEADER_NAME: &'static str = &"test-header";
-    const TEST_HEADER_CONTENT: &'static str = &"Test header content";
-
-    struct TestHeader(Vec<u8>);
-
-    #[async_trait]
-    impl<S: Sync> FromRequestParts<S> for TestHeader {
-        type Rejection = (StatusCode, &'static str);
+    #[tokio::test]
+    async fn it_should_only_send_cookies_for_other_domains_to_them() {
+        let server = new_test_server();
 
-        async fn from_request_parts(
-            parts: &mut Parts,
-            _state: &S,
-        ) -> Result<TestHeader, Self::Rejection> {
-            parts
-                .headers
-                .get(HeaderName::from_static(TEST_HEADER_NAME))
-                .map(|v| TestHeader(v.as_bytes().to_vec()))
-                .ok_or((StatusCode::BAD_REQUEST, "Missing test header"))
-        }
-    }
+        server.get("/login").await;
 
-    async fn ping_header(TestHeader(header): TestHeader) -> Vec<u8> {
-        header
+        server
+            .get("http://other.local:8080/cookies")
+            .await
+            .assert_text("theme=dark, tracking=xyz");
     }
 
     #[tokio::test]
-    async fn it_should_not_send_headers_cleared_by_server() {
-        // Build an application with a route.
-        let app = Router::new().route("/header", get(ping_header));
-
-        // Run the server.
-        let mut server = TestServer::new(app).expect("Should create test server");
-        server.add_header(
-            HeaderName::from_static(TEST_HEADER_NAME),
-            HeaderValue::from_static(TEST_HEADER_CONTENT),
-        );
-        server.clear_headers();
+    async fn it_should_send_all_cookies_without_cookie_domain() {
+        let app = Router::new()
+            .route("/login", get(route_login))
+            .route("/cookies", get(route_get_cookies));
+        let server = TestServer::builder().save_cookies().build(app).unwrap();
 
-        // Send a request with the header
-        let response = server.get(&"/header").await;
+        server.get("/login").await;
 
-        // Check it sent back the right text
-        response.assert_status_bad_request();
-        response.assert_text("Missing test header");
+        server
+            .get("/cookies")
+            .await
+            .assert_text("session=abc, theme=dark, tracking=xyz");
     }
 }
 
@@ -2637,6 +7348,110 @@ mod test_typed_method {
     }
 }
 
+#[cfg(all(feature = "typed-routing", feature = "ws"))]
+#[cfg(test)]
+mod test_typed_get_websocket {
+    use super::*;
+
+    use axum::extract::WebSocketUpgrade;
+    use axum::response::Response;
+    use axum::Router;
+    use axum_extra::routing::RouterExt;
+    use serde::Deserialize;
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/ws/:room")]
+    struct RoomPath {
+        room: String,
+    }
+
+    async fn route_get_room(RoomPath { room }: RoomPath, ws: WebSocketUpgrade) -> Response {
+        ws.on_upgrade(move |mut socket| async move {
+            let _ = socket
+                .send(axum::extract::ws::Message::Text(format!("joined {room}")))
+                .await;
+        })
+    }
+
+    #[tokio::test]
+    async fn it_should_connect_to_typed_path() {
+        let app = Router::new().typed_get(route_get_room);
+        let server = TestServer::builder().http_transport().build(app).unwrap();
+
+        let mut websocket = server
+            .typed_get_websocket(&RoomPath {
+                room: "lobby".to_string(),
+            })
+            .await
+            .into_websocket()
+            .await;
+
+        websocket.assert_receive_text("joined lobby").await;
+    }
+}
+
+#[cfg(all(feature = "typed-routing", feature = "reqwest"))]
+#[cfg(test)]
+mod test_typed_reqwest_method {
+    use super::*;
+
+    use axum::Router;
+    use axum_extra::routing::RouterExt;
+    use serde::Deserialize;
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/path/:id")]
+    struct TestingPath {
+        id: u32,
+    }
+
+    async fn route_get(TestingPath { id }: TestingPath) -> String {
+        format!("get {id}")
+    }
+
+    async fn route_post(TestingPath { id }: TestingPath) -> String {
+        format!("post {id}")
+    }
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new().typed_get(route_get).typed_post(route_post);
+
+        TestServer::builder().http_transport().build(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_send_get() {
+        let server = new_test_server();
+
+        let text = server
+            .typed_reqwest_get(&TestingPath { id: 123 })
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+
+        assert_eq!(text, "get 123");
+    }
+
+    #[tokio::test]
+    async fn it_should_send_method() {
+        let server = new_test_server();
+
+        let text = server
+            .typed_reqwest_method(Method::POST, &TestingPath { id: 123 })
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+
+        assert_eq!(text, "post 123");
+    }
+}
+
 #[cfg(test)]
 mod test_sync {
     use super::*;
@@ -2706,3 +7521,68 @@ mod test_is_running {
         server.get("/ping").await.assert_status_ok();
     }
 }
+
+#[cfg(all(feature = "dyn-features", not(feature = "ws")))]
+#[cfg(test)]
+mod test_get_websocket_without_feature {
+    use axum::Router;
+
+    use crate::Error;
+    use crate::TestServer;
+
+    #[tokio::test]
+    async fn it_should_return_missing_feature_when_sent() {
+        let server = TestServer::new(Router::new()).unwrap();
+
+        let error = server.get_websocket(&"/ws").try_send().await.unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref::<Error>(),
+            Some(Error::MissingFeature { feature, .. }) if feature == "ws"
+        ));
+    }
+}
+
+#[cfg(all(feature = "dyn-features", not(feature = "reqwest")))]
+#[cfg(test)]
+mod test_reqwest_without_feature {
+    use axum::Router;
+
+    use crate::TestServer;
+
+    #[test]
+    fn it_should_return_missing_feature() {
+        let server = TestServer::builder()
+            .mock_transport()
+            .build(Router::new())
+            .unwrap();
+
+        let error = server.reqwest_get(&"/todo").unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "`TestServer::reqwest_method` requires axum-test to be built with the `reqwest` feature, add it to the axum-test features in your Cargo.toml"
+        );
+    }
+}
+
+#[cfg(all(feature = "dyn-features", not(feature = "typed-routing")))]
+#[cfg(test)]
+mod test_typed_get_without_feature {
+    use axum::Router;
+
+    use crate::Error;
+    use crate::TestServer;
+
+    #[tokio::test]
+    async fn it_should_return_missing_feature_when_sent() {
+        let server = TestServer::new(Router::new()).unwrap();
+
+        let error = server.typed_get(&"/todo").try_send().await.unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref::<Error>(),
+            Some(Error::MissingFeature { feature, .. }) if feature == "typed-routing"
+        ));
+    }
+}
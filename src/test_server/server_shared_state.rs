@@ -1,14 +1,23 @@
+use anyhow::anyhow;
 use anyhow::Context;
 use anyhow::Result;
 use cookie::Cookie;
 use cookie::CookieJar;
+use http::HeaderMap;
 use http::HeaderName;
 use http::HeaderValue;
 use serde::Serialize;
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::Mutex;
 
 use crate::internals::with_this_mut;
+use crate::internals::CsrfState;
+use crate::internals::CsrfToken;
+use crate::internals::InFlight;
+use crate::internals::InFlightHandle;
+use crate::internals::InFlightKind;
+use crate::internals::InFlightReport;
 use crate::internals::QueryParamsStore;
 
 #[derive(Debug)]
@@ -17,6 +26,18 @@ pub(crate) struct ServerSharedState {
     cookies: CookieJar,
     query_params: QueryParamsStore,
     headers: Vec<(HeaderName, HeaderValue)>,
+    maybe_csrf: Option<CsrfState>,
+    maybe_identity: Option<String>,
+    is_authenticating: bool,
+    stored_identities: HashMap<Option<String>, StoredIdentity>,
+    in_flight: InFlight,
+}
+
+/// The cookies and headers of an identity which is not currently in use.
+#[derive(Debug, Default)]
+struct StoredIdentity {
+    cookies: CookieJar,
+    headers: Vec<(HeaderName, HeaderValue)>,
 }
 
 impl ServerSharedState {
@@ -26,9 +47,28 @@ impl ServerSharedState {
             cookies: CookieJar::new(),
             query_params: QueryParamsStore::new(),
             headers: Vec::new(),
+            maybe_csrf: None,
+            maybe_identity: None,
+            is_authenticating: false,
+            stored_identities: HashMap::new(),
+            in_flight: InFlight::new(),
         }
     }
 
+    /// Returns new state for a separate client of the same server.
+    ///
+    /// This keeps the scheme and CSRF settings, and drops everything received or added,
+    /// such as cookies, headers, query params, and identities.
+    pub(crate) fn new_client(this: &Arc<Mutex<Self>>) -> Result<Self> {
+        with_this_mut(this, "new_client", |this| {
+            let mut client_state = Self::new();
+            client_state.scheme = this.scheme.clone();
+            client_state.maybe_csrf = this.maybe_csrf.as_ref().map(CsrfState::without_token);
+
+            client_state
+        })
+    }
+
     pub(crate) fn scheme(&self) -> Option<&str> {
         self.scheme.as_deref()
     }
@@ -45,6 +85,14 @@ impl ServerSharedState {
         &self.headers
     }
 
+    /// Returns the CSRF token to send, if CSRF handling is enabled and a token has been received.
+    pub(crate) fn csrf_token(&self) -> Result<Option<CsrfToken>> {
+        match &self.maybe_csrf {
+            Some(csrf) => csrf.token(),
+            None => Ok(None),
+        }
+    }
+
     /// Adds the given cookies.
     ///
     /// They will be stored over the top of the existing cookies.
@@ -96,6 +144,53 @@ impl ServerSharedState {
         })
     }
 
+    /// Tracks the request or WebSocket described as in flight,
+    /// until the handle returned is dropped.
+    pub(crate) fn start_in_flight(
+        this: &Arc<Mutex<Self>>,
+        kind: InFlightKind,
+        description: String,
+    ) -> Result<InFlightHandle> {
+        let id = with_this_mut(this, "start_in_flight", |this| {
+            this.in_flight.start(kind, description)
+        })?;
+
+        Ok(InFlightHandle::new(this.clone(), id))
+    }
+
+    pub(crate) fn finish_in_flight(this: &Arc<Mutex<Self>>, id: u64) -> Result<()> {
+        with_this_mut(this, "finish_in_flight", |this| this.in_flight.finish(id))
+    }
+
+    pub(crate) fn in_flight_report(this: &Arc<Mutex<Self>>) -> Result<InFlightReport> {
+        with_this_mut(this, "in_flight_report", |this| this.in_flight.report())
+    }
+
+    pub(crate) fn cookies_snapshot(this: &Arc<Mutex<Self>>) -> Result<CookieJar> {
+        with_this_mut(this, "cookies_snapshot", |this| this.cookies.clone())
+    }
+
+    /// Changes the saved cookie with the name given.
+    ///
+    /// Returns an error if no cookie with that name is saved.
+    pub(crate) fn update_cookie<F>(this: &Arc<Mutex<Self>>, name: &str, update: F) -> Result<()>
+    where
+        F: FnOnce(&mut Cookie<'static>),
+    {
+        with_this_mut(this, "update_cookie", |this| {
+            let mut cookie = this
+                .cookies
+                .get(name)
+                .cloned()
+                .ok_or_else(|| anyhow!("Cookie '{name}' is not saved on the TestServer"))?;
+
+            update(&mut cookie);
+            this.cookies.add(cookie);
+
+            Ok(())
+        })?
+    }
+
     pub(crate) fn add_query_params<V>(this: &Arc<Mutex<Self>>, query_params: V) -> Result<()>
     where
         V: Serialize,
@@ -136,6 +231,19 @@ impl ServerSharedState {
         with_this_mut(this, "add_header", |this| this.headers.push((name, value)))
     }
 
+    pub(crate) fn enable_csrf(this: &Arc<Mutex<Self>>, csrf: CsrfState) -> Result<()> {
+        with_this_mut(this, "enable_csrf", |this| this.maybe_csrf = Some(csrf))
+    }
+
+    /// Stores the CSRF token found in the response headers, if CSRF handling is enabled.
+    pub(crate) fn update_csrf_token(this: &Arc<Mutex<Self>>, headers: &HeaderMap) -> Result<()> {
+        with_this_mut(this, "update_csrf_token", |this| {
+            if let Some(csrf) = &mut this.maybe_csrf {
+                csrf.update_from_headers(headers);
+            }
+        })
+    }
+
     pub(crate) fn set_scheme(this: &Arc<Mutex<Self>>, scheme: String) -> Result<()> {
         with_this_mut(this, "set_scheme", |this| this.scheme = Some(scheme))
     }
@@ -143,4 +251,53 @@ impl ServerSharedState {
     pub(crate) fn set_scheme_unlocked(&mut self, scheme: String) {
         self.scheme = Some(scheme);
     }
+
+    /// Returns true when a login flow is running, during which cookies are always saved.
+    pub(crate) fn is_authenticating(this: &Arc<Mutex<Self>>) -> Result<bool> {
+        with_this_mut(this, "is_authenticating", |this| this.is_authenticating)
+    }
+
+    pub(crate) fn set_authenticating(
+        this: &Arc<Mutex<Self>>,
+        is_authenticating: bool,
+    ) -> Result<()> {
+        with_this_mut(this, "set_authenticating", |this| {
+            this.is_authenticating = is_authenticating
+        })
+    }
+
+    pub(crate) fn has_identity(this: &Arc<Mutex<Self>>, name: &str) -> Result<bool> {
+        with_this_mut(this, "has_identity", |this| {
+            this.maybe_identity.as_deref() == Some(name)
+                || this.stored_identities.contains_key(&Some(name.to_string()))
+        })
+    }
+
+    /// Stores the cookies and headers of the current identity,
+    /// and replaces them with those of the identity given.
+    ///
+    /// `None` is the anonymous identity, used before any identity is switched to.
+    /// Identities not seen before start with no cookies or headers.
+    pub(crate) fn switch_identity(
+        this: &Arc<Mutex<Self>>,
+        maybe_identity: Option<String>,
+    ) -> Result<()> {
+        with_this_mut(this, "switch_identity", |this| {
+            if this.maybe_identity == maybe_identity {
+                return;
+            }
+
+            let next = this
+                .stored_identities
+                .remove(&maybe_identity)
+                .unwrap_or_default();
+            let previous = StoredIdentity {
+                cookies: std::mem::replace(&mut this.cookies, next.cookies),
+                headers: std::mem::replace(&mut this.headers, next.headers),
+            };
+            let previous_identity = std::mem::replace(&mut this.maybe_identity, maybe_identity);
+
+            this.stored_identities.insert(previous_identity, previous);
+        })
+    }
 }
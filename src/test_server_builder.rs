@@ -1,10 +1,34 @@
 use anyhow::Result;
+use cookie::time::Duration as TimeDuration;
+use http::Method;
+use std::fmt::Debug;
 use std::net::IpAddr;
+use std::net::Ipv6Addr;
+use std::time::Duration;
 
+use crate::internals::DUAL_STACK_IP_ADDRESS;
 use crate::transport_layer::IntoTransportLayer;
+use crate::AssertionBundle;
+use crate::ChaosConfig;
+use crate::CookieConfig;
+use crate::Error;
+use crate::ErrorBodySchema;
+use crate::ExpectationConfig;
+use crate::InstrumentationProbe;
+use crate::InterceptAction;
+use crate::ProbeHandle;
+use crate::RequestInterceptor;
+use crate::RequestMatcher;
+#[cfg(feature = "reqwest")]
+use crate::ReqwestConfigurer;
+use crate::ResponseValidator;
+use crate::RouteInfo;
+use crate::TestEventLog;
+use crate::TestResponse;
 use crate::TestServer;
 use crate::TestServerConfig;
 use crate::Transport;
+use crate::TransportConfig;
 
 /// A builder for [`crate::TestServer`]. Inside is a [`crate::TestServerConfig`],
 /// configured by each method, and then turn into a server by [`crate::TestServerBuilder::build`].
@@ -76,22 +100,66 @@ impl TestServerBuilder {
         self.transport(Transport::HttpIpPort { ip, port })
     }
 
+    /// Runs the server on a random port of the IPv6 loopback address, `::1`.
+    ///
+    /// The [`TestServer::server_url()`](crate::TestServer::server_url()) will be bracketed,
+    /// such as `http://[::1]:1234`.
+    pub fn ipv6_transport(self) -> Self {
+        self.http_transport_with_ip_port(Some(IpAddr::V6(Ipv6Addr::LOCALHOST)), None)
+    }
+
+    /// Runs the server on a random port of the unspecified IPv6 address, `::`,
+    /// accepting both IPv4 and IPv6 connections.
+    ///
+    /// Requests are sent to the IPv6 loopback address, `::1`,
+    /// and the server can also be reached on `127.0.0.1` using the same port.
+    pub fn dual_stack_transport(self) -> Self {
+        self.http_transport_with_ip_port(Some(DUAL_STACK_IP_ADDRESS), None)
+    }
+
     pub fn mock_transport(self) -> Self {
         self.transport(Transport::MockHttp)
     }
 
     pub fn transport(mut self, transport: Transport) -> Self {
-        self.config.transport = Some(transport);
+        self.config.transport_config.transport = Some(transport);
+        self
+    }
+
+    /// Sets the transport settings, replacing any set before.
+    ///
+    /// See [`TransportConfig`](crate::TransportConfig) for more details.
+    pub fn with_transport_config(mut self, transport_config: TransportConfig) -> Self {
+        self.config.transport_config = transport_config;
         self
     }
 
     pub fn save_cookies(mut self) -> Self {
-        self.config.save_cookies = true;
+        self.config.cookie_config.save_cookies = Some(true);
         self
     }
 
     pub fn do_not_save_cookies(mut self) -> Self {
-        self.config.save_cookies = false;
+        self.config.cookie_config.save_cookies = Some(false);
+        self
+    }
+
+    /// Sets the cookie settings, replacing any set before.
+    ///
+    /// See [`CookieConfig`](crate::CookieConfig) for more details.
+    pub fn with_cookie_config(mut self, cookie_config: CookieConfig) -> Self {
+        self.config.cookie_config = cookie_config;
+        self
+    }
+
+    /// Sets the domain the server is addressed by, for sending saved cookies.
+    ///
+    /// See [`CookieConfig::cookie_domain`](crate::CookieConfig::cookie_domain) for more details.
+    pub fn cookie_domain<D>(mut self, domain: D) -> Self
+    where
+        D: Into<String>,
+    {
+        self.config.cookie_config.cookie_domain = Some(domain.into());
         self
     }
 
@@ -101,17 +169,354 @@ impl TestServerBuilder {
     }
 
     pub fn default_scheme(mut self, scheme: &str) -> Self {
-        self.config.default_scheme = Some(scheme.to_string());
+        self.config.transport_config.default_scheme = Some(scheme.to_string());
+        self
+    }
+
+    /// Injects failures into requests, as described by the [`ChaosConfig`](crate::ChaosConfig) given.
+    pub fn with_chaos(mut self, chaos: ChaosConfig) -> Self {
+        self.config.chaos = Some(chaos);
+        self
+    }
+
+    /// Records metrics on the requests that reach your application,
+    /// which are retrieved using [`TestServer::metrics()`](crate::TestServer::metrics()).
+    pub fn record_metrics(mut self) -> Self {
+        self.config.record_metrics = true;
+        self
+    }
+
+    /// Records every request sent and response received,
+    /// which are retrieved using [`TestServer::recorded_exchanges()`](crate::TestServer::recorded_exchanges()).
+    pub fn record_exchanges(mut self) -> Self {
+        self.config.record_exchanges = true;
+        self
+    }
+
+    /// Sets the event log shared with your application,
+    /// which is returned from [`TestServer::events()`](crate::TestServer::events()).
+    ///
+    /// See [`TestEventLog`](crate::TestEventLog) for more details.
+    pub fn event_log(mut self, event_log: TestEventLog) -> Self {
+        self.config.event_log = Some(event_log);
+        self
+    }
+
+    /// Checks every response with a 4xx or 5xx status code has a body matching the schema given.
+    /// This takes an [`ErrorBodySchema`](crate::ErrorBodySchema), or a Json object describing one.
+    ///
+    /// This will panic if the Json given is not a valid schema.
+    #[track_caller]
+    pub fn expect_error_body_schema<S>(mut self, schema: S) -> Self
+    where
+        S: TryInto<ErrorBodySchema>,
+        S::Error: Debug,
+    {
+        let schema = schema
+            .try_into()
+            .expect("Failed to build ErrorBodySchema from schema given");
+
+        self.config.error_body_schema = Some(schema);
+        self
+    }
+
+    /// Adds a check run against every response received, after each request.
+    /// If it returns an error, then the request will panic.
+    ///
+    /// See [`ResponseValidator`](crate::ResponseValidator) for more details.
+    pub fn add_response_validator<F>(mut self, validator: F) -> Self
+    where
+        F: Fn(&TestResponse) -> Result<()> + Send + Sync + 'static,
+    {
+        self.config
+            .response_validators
+            .push(ResponseValidator::new(validator));
+        self
+    }
+
+    /// Adds an [`AssertionBundle`](crate::AssertionBundle) checked against every response received, after each request.
+    /// If any of its checks fail, then the request will panic.
+    pub fn add_assertion_bundle(mut self, bundle: AssertionBundle) -> Self {
+        self.config.response_validators.push(bundle.into());
+        self
+    }
+
+    /// Intercepts requests matching the matcher given, before they reach the application.
+    /// This can fail them with a connection error, respond on the app's behalf,
+    /// or rewrite their headers.
+    ///
+    /// The matcher can be an Axum route pattern, such as `"/users/:id"`,
+    /// or a [`RequestMatcher`](crate::RequestMatcher).
+    ///
+    /// See [`RequestInterceptor`](crate::RequestInterceptor) for more details.
+    pub fn intercept<M>(mut self, matcher: M, action: InterceptAction) -> Self
+    where
+        M: Into<RequestMatcher>,
+    {
+        self.config
+            .interceptors
+            .push(RequestInterceptor::new(matcher, action));
+        self
+    }
+
+    /// Adds a probe taking a measurement around every request,
+    /// which is read from the response using [`TestResponse::probe()`](crate::TestResponse::probe()).
+    ///
+    /// See [`InstrumentationProbe`](crate::InstrumentationProbe) for more details.
+    pub fn add_probe<P>(mut self, probe: P) -> Self
+    where
+        P: InstrumentationProbe,
+    {
+        self.config.probes.push(ProbeHandle::new(probe));
+        self
+    }
+
+    /// Hides the values of the header given in output produced by the crate,
+    /// such as request descriptions in panic messages.
+    ///
+    /// See [`TestServerConfig::masked_headers`](crate::TestServerConfig::masked_headers) for more details.
+    pub fn mask_header<H>(mut self, header_name: H) -> Self
+    where
+        H: Into<String>,
+    {
+        self.config.masked_headers.push(header_name.into());
+        self
+    }
+
+    /// Hides the Json values at the path given in output produced by the crate,
+    /// such as response bodies printed in panic messages. i.e. `$.password`.
+    ///
+    /// See [`TestServerConfig::masked_json_paths`](crate::TestServerConfig::masked_json_paths) for more details.
+    pub fn mask_json_path<P>(mut self, json_path: P) -> Self
+    where
+        P: Into<String>,
+    {
+        self.config.masked_json_paths.push(json_path.into());
+        self
+    }
+
+    /// Adds a route of the application by hand, for [`TestServer::routes()`](crate::TestServer::routes()).
+    ///
+    /// Call this once for each method of each route. The path uses Axum's syntax (i.e. `/users/:id`).
+    /// The route is not checked against the application, and routes not added are unknown to the `TestServer`.
+    /// Prefer building the application with a [`RecordingRouter`](crate::RecordingRouter),
+    /// which records each route as it is added.
+    ///
+    /// See [`TestServerConfig::routes`](crate::TestServerConfig::routes) for more details.
+    pub fn route<P>(mut self, method: Method, path_pattern: P) -> Self
+    where
+        P: Into<String>,
+    {
+        self.config.routes.push(RouteInfo {
+            method,
+            path_pattern: path_pattern.into(),
+        });
+        self
+    }
+
+    /// Sets environment variables for as long as the server is alive,
+    /// restoring their previous values when it is dropped.
+    ///
+    /// This is for testing handlers which read feature flags, or other config, from the environment.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum::routing::get;
+    /// use axum_test::TestServer;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/feature", get(|| async { std::env::var("FEATURE_X").unwrap_or_default() }));
+    ///
+    /// let server = TestServer::builder()
+    ///     .with_env_vars([("FEATURE_X", "on")])
+    ///     .build(app)?;
+    ///
+    /// server.get(&"/feature").await.assert_text("on");
+    /// #
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// See [`TestServerConfig::env_vars`](crate::TestServerConfig::env_vars) for more details.
+    pub fn with_env_vars<I, K, V>(mut self, env_vars: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.config.env_vars.extend(
+            env_vars
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into())),
+        );
+        self
+    }
+
+    /// Changes the Reqwest client used by [`TestServer::reqwest_get()`](crate::TestServer::reqwest_get())
+    /// and the other Reqwest methods, such as to set a proxy, timeouts, or custom TLS settings.
+    ///
+    /// This can be called multiple times, with the changes applied in order.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// use axum::Router;
+    /// use axum::routing::get;
+    /// use axum_test::TestServer;
+    /// use std::time::Duration;
+    ///
+    /// let app = Router::new()
+    ///     .route(&"/ping", get(|| async { "pong" }));
+    ///
+    /// let server = TestServer::builder()
+    ///     .http_transport()
+    ///     .configure_reqwest(|builder| builder.no_proxy().timeout(Duration::from_secs(5)))
+    ///     .build(app)?;
+    ///
+    /// let text = server.reqwest_get(&"/ping").send().await?.text().await?;
+    /// assert_eq!(text, "pong");
+    /// #
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// See [`ReqwestConfigurer`](crate::ReqwestConfigurer) for more details.
+    #[cfg(feature = "reqwest")]
+    pub fn configure_reqwest<F>(mut self, configurer: F) -> Self
+    where
+        F: Fn(reqwest::ClientBuilder) -> reqwest::ClientBuilder + Send + Sync + 'static,
+    {
+        self.config
+            .reqwest_configurers
+            .push(ReqwestConfigurer::new(configurer));
+        self
+    }
+
+    /// Collects requests which received a deprecated response,
+    /// printing them as a warning when the server is dropped.
+    ///
+    /// Output from passing tests is captured by the test harness,
+    /// so run with `cargo test -- --nocapture` to see the warning.
+    ///
+    /// See [`TestServer::deprecated_requests()`](crate::TestServer::deprecated_requests()) for more details.
+    pub fn warn_on_deprecated(mut self) -> Self {
+        self.config.warn_on_deprecated = true;
+        self
+    }
+
+    /// Collects requests which received a deprecated response,
+    /// and panics when the server is dropped if there were any.
+    ///
+    /// See [`TestServer::deprecated_requests()`](crate::TestServer::deprecated_requests()) for more details.
+    pub fn fail_on_deprecated(mut self) -> Self {
+        self.config.fail_on_deprecated = true;
+        self
+    }
+
+    /// Panics when the server is dropped, if any requests are still being served.
+    ///
+    /// See [`TestServer::assert_no_leaked_connections()`](crate::TestServer::assert_no_leaked_connections()) for more details.
+    pub fn fail_on_leaked_connections(mut self) -> Self {
+        self.config.fail_on_leaked_connections = true;
+        self
+    }
+
+    /// Shifts the dates sent by requests by the skew given,
+    /// as though the client's clock was off by this amount. The skew can be negative.
+    ///
+    /// See [`TestServerConfig::clock_skew`](crate::TestServerConfig::clock_skew) for more details.
+    pub fn with_clock_skew(mut self, clock_skew: TimeDuration) -> Self {
+        self.config.clock_skew = Some(clock_skew);
+        self
+    }
+
+    /// Sets the seed used for all randomness within the `TestServer`,
+    /// for reproducing the same behaviour across test runs.
+    ///
+    /// See [`TestServerConfig::seed`](crate::TestServerConfig::seed) for more details.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.config.seed = Some(seed);
+        self
+    }
+
+    /// Polls the path given until it returns a 2xx response,
+    /// before the first request is sent. Waiting longer than the timeout will fail the request.
+    ///
+    /// See [`TestServerConfig::wait_until_ready`](crate::TestServerConfig::wait_until_ready) for more details.
+    pub fn wait_until_ready(mut self, path: &str, timeout: Duration) -> Self {
+        self.config.wait_until_ready = Some((path.to_string(), timeout));
+        self
+    }
+
+    /// Prints a line for each request sent, with its method, path, status code,
+    /// duration, and response body size.
+    ///
+    /// See [`TestServerConfig::verbose`](crate::TestServerConfig::verbose) for more details.
+    pub fn verbose(mut self) -> Self {
+        self.config.verbose = true;
+        self
+    }
+
+    /// Sets the most bytes of a response body read into memory,
+    /// failing requests with larger bodies.
+    ///
+    /// See [`TestServerConfig::max_buffered_body`](crate::TestServerConfig::max_buffered_body) for more details.
+    pub fn max_buffered_body(mut self, bytes: usize) -> Self {
+        self.config.max_buffered_body = Some(bytes);
+        self
+    }
+
+    /// Sets the most bytes of a request body to keep a copy of on the response.
+    ///
+    /// See [`TestServerConfig::max_recorded_request_body`](crate::TestServerConfig::max_recorded_request_body) for more details.
+    pub fn max_recorded_request_body(mut self, bytes: usize) -> Self {
+        self.config.max_recorded_request_body = bytes;
+        self
+    }
+
+    /// Returns a `500 Internal Server Error` for handlers which panic,
+    /// rather than failing the request.
+    ///
+    /// See [`TestServerConfig::catch_panics`](crate::TestServerConfig::catch_panics) for more details.
+    pub fn catch_panics(mut self) -> Self {
+        self.config.catch_panics = true;
+        self
+    }
+
+    /// Keeps the raw bytes of each response head, as they were sent over the connection.
+    ///
+    /// See [`TestServerConfig::capture_raw_head`](crate::TestServerConfig::capture_raw_head) for more details.
+    pub fn capture_raw_head(mut self) -> Self {
+        self.config.capture_raw_head = true;
+        self
+    }
+
+    /// Sets the expectations for every response, replacing any set before.
+    ///
+    /// See [`ExpectationConfig`](crate::ExpectationConfig) for more details.
+    pub fn with_expectation_config(mut self, expectation_config: ExpectationConfig) -> Self {
+        self.config.expectation_config = expectation_config;
         self
     }
 
     pub fn expect_success_by_default(mut self) -> Self {
-        self.config.expect_success_by_default = true;
+        self.config.expectation_config.expect_success_by_default = Some(true);
+        self
+    }
+
+    /// Asserts all responses have a `Content-Type` matching the one given,
+    /// unless overridden on the request.
+    ///
+    /// See [`ExpectationConfig::expected_content_type`](crate::ExpectationConfig::expected_content_type) for more details.
+    pub fn expect_content_type(mut self, content_type: &str) -> Self {
+        self.config.expectation_config.expected_content_type = Some(content_type.to_string());
         self
     }
 
     pub fn restrict_requests_with_http_schema(mut self) -> Self {
-        self.config.restrict_requests_with_http_schema = true;
+        self.config
+            .transport_config
+            .restrict_requests_with_http_schema = Some(true);
         self
     }
 
@@ -156,7 +561,7 @@ impl TestServerBuilder {
     ///
     /// This is the equivalent to building [`crate::TestServerConfig`] yourself,
     /// and calling [`crate::TestServer::new_with_config`].
-    pub fn build<A>(self, app: A) -> Result<TestServer>
+    pub fn build<A>(self, app: A) -> Result<TestServer, Error>
     where
         A: IntoTransportLayer,
     {
@@ -195,28 +600,60 @@ mod test_build {
     fn it_should_save_cookies_when_set() {
         let config = TestServer::builder().save_cookies().into_config();
 
-        assert_eq!(config.save_cookies, true);
+        assert_eq!(config.cookie_config.save_cookies, Some(true));
     }
 
     #[test]
     fn it_should_not_save_cookies_when_set() {
         let config = TestServer::builder().do_not_save_cookies().into_config();
 
-        assert_eq!(config.save_cookies, false);
+        assert_eq!(config.cookie_config.save_cookies, Some(false));
+    }
+
+    #[test]
+    fn it_should_set_cookie_config_when_set() {
+        let config = TestServer::builder()
+            .with_cookie_config(
+                CookieConfig::new()
+                    .save_cookies()
+                    .cookie_domain("app.local"),
+            )
+            .into_config();
+
+        assert_eq!(config.cookie_config.save_cookies, Some(true));
+        assert_eq!(
+            config.cookie_config.cookie_domain,
+            Some("app.local".to_string())
+        );
+    }
+
+    #[test]
+    fn it_should_set_cookie_domain_when_set() {
+        let config = TestServer::builder()
+            .cookie_domain("app.local")
+            .into_config();
+
+        assert_eq!(
+            config.cookie_config.cookie_domain,
+            Some("app.local".to_string())
+        );
     }
 
     #[test]
     fn it_should_mock_transport_when_set() {
         let config = TestServer::builder().mock_transport().into_config();
 
-        assert_eq!(config.transport, Some(Transport::MockHttp));
+        assert_eq!(config.transport_config.transport, Some(Transport::MockHttp));
     }
 
     #[test]
     fn it_should_use_random_http_transport_when_set() {
         let config = TestServer::builder().http_transport().into_config();
 
-        assert_eq!(config.transport, Some(Transport::HttpRandomPort));
+        assert_eq!(
+            config.transport_config.transport,
+            Some(Transport::HttpRandomPort)
+        );
     }
 
     #[test]
@@ -226,7 +663,7 @@ mod test_build {
             .into_config();
 
         assert_eq!(
-            config.transport,
+            config.transport_config.transport,
             Some(Transport::HttpIpPort {
                 ip: Some(IpAddr::V4(Ipv4Addr::new(123, 4, 5, 6))),
                 port: Some(987),
@@ -234,6 +671,32 @@ mod test_build {
         );
     }
 
+    #[test]
+    fn it_should_use_ipv6_loopback_when_ipv6_transport_set() {
+        let config = TestServer::builder().ipv6_transport().into_config();
+
+        assert_eq!(
+            config.transport_config.transport,
+            Some(Transport::HttpIpPort {
+                ip: Some(IpAddr::V6(Ipv6Addr::LOCALHOST)),
+                port: None,
+            })
+        );
+    }
+
+    #[test]
+    fn it_should_use_unspecified_ipv6_when_dual_stack_transport_set() {
+        let config = TestServer::builder().dual_stack_transport().into_config();
+
+        assert_eq!(
+            config.transport_config.transport,
+            Some(Transport::HttpIpPort {
+                ip: Some(IpAddr::V6(Ipv6Addr::UNSPECIFIED)),
+                port: None,
+            })
+        );
+    }
+
     #[test]
     fn it_should_set_default_content_type_when_set() {
         let config = TestServer::builder()
@@ -247,7 +710,10 @@ mod test_build {
     fn it_should_set_default_scheme_when_set() {
         let config = TestServer::builder().default_scheme("ftps").into_config();
 
-        assert_eq!(config.default_scheme, Some("ftps".to_string()));
+        assert_eq!(
+            config.transport_config.default_scheme,
+            Some("ftps".to_string())
+        );
     }
 
     #[test]
@@ -256,7 +722,22 @@ mod test_build {
             .expect_success_by_default()
             .into_config();
 
-        assert_eq!(config.expect_success_by_default, true);
+        assert_eq!(
+            config.expectation_config.expect_success_by_default,
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn it_should_set_expected_content_type_when_set() {
+        let config = TestServer::builder()
+            .expect_content_type("application/json")
+            .into_config();
+
+        assert_eq!(
+            config.expectation_config.expected_content_type,
+            Some("application/json".to_string())
+        );
     }
 
     #[test]
@@ -265,6 +746,218 @@ mod test_build {
             .restrict_requests_with_http_schema()
             .into_config();
 
-        assert_eq!(config.restrict_requests_with_http_schema, true);
+        assert_eq!(
+            config.transport_config.restrict_requests_with_http_schema,
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn it_should_set_chaos_when_set() {
+        let chaos = ChaosConfig {
+            server_error_rate: 0.5,
+            ..ChaosConfig::default()
+        };
+        let config = TestServer::builder()
+            .with_chaos(chaos.clone())
+            .into_config();
+
+        assert_eq!(config.chaos, Some(chaos));
+    }
+
+    #[test]
+    fn it_should_record_metrics_when_set() {
+        let config = TestServer::builder().record_metrics().into_config();
+
+        assert!(config.record_metrics);
+    }
+
+    #[test]
+    fn it_should_record_exchanges_when_set() {
+        let config = TestServer::builder().record_exchanges().into_config();
+
+        assert!(config.record_exchanges);
+    }
+
+    #[test]
+    fn it_should_set_event_log_when_set() {
+        let event_log = TestEventLog::new();
+        event_log.push("started");
+
+        let config = TestServer::builder().event_log(event_log).into_config();
+
+        assert_eq!(config.event_log.unwrap().events(), vec!["started"]);
+    }
+
+    #[test]
+    fn it_should_set_error_body_schema_when_set() {
+        let config = TestServer::builder()
+            .expect_error_body_schema(serde_json::json!({ "code": "string" }))
+            .into_config();
+
+        assert!(config.error_body_schema.is_some());
+    }
+
+    #[test]
+    #[should_panic]
+    fn it_should_panic_when_error_body_schema_is_invalid() {
+        let _ = TestServer::builder().expect_error_body_schema(serde_json::json!("string"));
+    }
+
+    #[test]
+    fn it_should_add_response_validators_when_set() {
+        let config = TestServer::builder()
+            .add_response_validator(|_| Ok(()))
+            .add_response_validator(|_| Ok(()))
+            .into_config();
+
+        assert_eq!(config.response_validators.len(), 2);
+    }
+
+    #[test]
+    fn it_should_add_assertion_bundles_as_response_validators() {
+        let config = TestServer::builder()
+            .add_assertion_bundle(AssertionBundle::new("standard").status_ok())
+            .into_config();
+
+        assert_eq!(config.response_validators.len(), 1);
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[test]
+    fn it_should_add_reqwest_configurers_when_set() {
+        let config = TestServer::builder()
+            .configure_reqwest(|builder| builder.no_proxy())
+            .configure_reqwest(|builder| builder.https_only(false))
+            .into_config();
+
+        assert_eq!(config.reqwest_configurers.len(), 2);
+    }
+
+    #[test]
+    fn it_should_add_interceptors_when_set() {
+        let config = TestServer::builder()
+            .intercept("/users", InterceptAction::ConnectionError)
+            .intercept(
+                RequestMatcher::any(),
+                InterceptAction::Respond(http::StatusCode::BAD_GATEWAY),
+            )
+            .into_config();
+
+        assert_eq!(config.interceptors.len(), 2);
+        assert_eq!(
+            config.interceptors[1].action,
+            InterceptAction::Respond(http::StatusCode::BAD_GATEWAY)
+        );
+    }
+
+    #[test]
+    fn it_should_add_routes_when_set() {
+        let config = TestServer::builder()
+            .route(Method::GET, "/users")
+            .route(Method::POST, "/users")
+            .into_config();
+
+        assert_eq!(
+            config.routes,
+            vec![
+                RouteInfo {
+                    method: Method::GET,
+                    path_pattern: "/users".to_string(),
+                },
+                RouteInfo {
+                    method: Method::POST,
+                    path_pattern: "/users".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn it_should_warn_on_deprecated_when_set() {
+        let config = TestServer::builder().warn_on_deprecated().into_config();
+
+        assert!(config.warn_on_deprecated);
+    }
+
+    #[test]
+    fn it_should_fail_on_deprecated_when_set() {
+        let config = TestServer::builder().fail_on_deprecated().into_config();
+
+        assert!(config.fail_on_deprecated);
+    }
+
+    #[test]
+    fn it_should_fail_on_leaked_connections_when_set() {
+        let config = TestServer::builder()
+            .fail_on_leaked_connections()
+            .into_config();
+
+        assert!(config.fail_on_leaked_connections);
+    }
+
+    #[test]
+    fn it_should_set_clock_skew_when_set() {
+        let config = TestServer::builder()
+            .with_clock_skew(TimeDuration::minutes(-5))
+            .into_config();
+
+        assert_eq!(config.clock_skew, Some(TimeDuration::minutes(-5)));
+    }
+
+    #[test]
+    fn it_should_set_verbose_when_set() {
+        let config = TestServer::builder().verbose().into_config();
+
+        assert!(config.verbose);
+    }
+
+    #[test]
+    fn it_should_set_max_buffered_body_when_set() {
+        let config = TestServer::builder().max_buffered_body(1024).into_config();
+
+        assert_eq!(config.max_buffered_body, Some(1024));
+    }
+
+    #[test]
+    fn it_should_set_max_recorded_request_body_when_set() {
+        let config = TestServer::builder()
+            .max_recorded_request_body(16)
+            .into_config();
+
+        assert_eq!(config.max_recorded_request_body, 16);
+    }
+
+    #[test]
+    fn it_should_catch_panics_when_set() {
+        let config = TestServer::builder().catch_panics().into_config();
+
+        assert!(config.catch_panics);
+    }
+
+    #[test]
+    fn it_should_capture_raw_head_when_set() {
+        let config = TestServer::builder().capture_raw_head().into_config();
+
+        assert!(config.capture_raw_head);
+    }
+
+    #[test]
+    fn it_should_set_seed_when_set() {
+        let config = TestServer::builder().with_seed(123).into_config();
+
+        assert_eq!(config.seed, Some(123));
+    }
+
+    #[test]
+    fn it_should_wait_until_ready_when_set() {
+        let config = TestServer::builder()
+            .wait_until_ready("/healthz", Duration::from_secs(5))
+            .into_config();
+
+        assert_eq!(
+            config.wait_until_ready,
+            Some(("/healthz".to_string(), Duration::from_secs(5)))
+        );
     }
 }
@@ -1,10 +1,30 @@
-use anyhow::Result;
+use cookie::time::Duration as TimeDuration;
+use std::time::Duration;
 
 use crate::transport_layer::IntoTransportLayer;
+use crate::ChaosConfig;
+use crate::Error;
+use crate::ErrorBodySchema;
+use crate::ProbeHandle;
+use crate::RequestInterceptor;
+#[cfg(feature = "reqwest")]
+use crate::ReqwestConfigurer;
+use crate::ResponseValidator;
+use crate::RouteInfo;
+use crate::TestEventLog;
 use crate::TestServer;
 use crate::TestServerBuilder;
 use crate::Transport;
 
+mod cookie_config;
+pub use self::cookie_config::*;
+
+mod expectation_config;
+pub use self::expectation_config::*;
+
+mod transport_config;
+pub use self::transport_config::*;
+
 /// This is for customising the [`TestServer`](crate::TestServer) on construction.
 /// It implements [`Default`] to ease building.
 ///
@@ -12,7 +32,22 @@ use crate::Transport;
 /// use axum_test::TestServerConfig;
 ///
 /// let config = TestServerConfig {
-///     save_cookies: true,
+///     default_content_type: Some("application/json".to_string()),
+///     ..TestServerConfig::default()
+/// };
+/// ```
+///
+/// Related settings are grouped together into sub-configs.
+/// Such as [`TransportConfig`](crate::TransportConfig), [`CookieConfig`](crate::CookieConfig),
+/// and [`ExpectationConfig`](crate::ExpectationConfig).
+/// These take precedence over the deprecated fields they replace.
+///
+/// ```rust
+/// use axum_test::CookieConfig;
+/// use axum_test::TestServerConfig;
+///
+/// let config = TestServerConfig {
+///     cookie_config: CookieConfig::new().save_cookies(),
 ///     ..TestServerConfig::default()
 /// };
 /// ```
@@ -23,15 +58,14 @@ use crate::Transport;
 /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
 /// #
 /// use axum::Router;
+/// use axum_test::CookieConfig;
 /// use axum_test::TestServer;
 /// use axum_test::TestServerConfig;
 ///
 /// let my_app = Router::new();
 ///
-/// let config = TestServerConfig {
-///     save_cookies: true,
-///     ..TestServerConfig::default()
-/// };
+/// let mut config = TestServerConfig::new();
+/// config.cookie_config = CookieConfig::new().save_cookies();
 ///
 /// // Build the Test Server
 /// let server = TestServer::new_with_config(my_app, config)?;
@@ -40,14 +74,32 @@ use crate::Transport;
 /// # }
 /// ```
 ///
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TestServerConfig {
+    /// Settings for how the server runs, and how requests are addressed to it.
+    ///
+    /// These take precedence over the deprecated `transport`, `default_scheme`,
+    /// and `restrict_requests_with_http_schema` fields.
+    pub transport_config: TransportConfig,
+
+    /// Settings for how cookies are saved and sent.
+    ///
+    /// These take precedence over the deprecated `save_cookies` and `cookie_domain` fields.
+    pub cookie_config: CookieConfig,
+
+    /// Settings for what is expected of every response, by default.
+    ///
+    /// These take precedence over the deprecated `expect_success_by_default`
+    /// and `expected_content_type` fields.
+    pub expectation_config: ExpectationConfig,
+
     /// Which transport mode to use to process requests.
     /// For setting if the server should use mocked http (which uses [`tower::util::Oneshot`](tower::util::Oneshot)),
     /// or if it should run on a named or random IP address.
     ///
     /// The default is to use mocking, apart from services built using [`axum::extract::connect_info::IntoMakeServiceWithConnectInfo`](axum::extract::connect_info::IntoMakeServiceWithConnectInfo)
     /// (this is because it needs a real TCP stream).
+    #[deprecated(note = "Use `transport_config.transport` instead")]
     pub transport: Option<Transport>,
 
     /// Set for the server to save cookies that are returned,
@@ -57,8 +109,23 @@ pub struct TestServerConfig {
     /// like a browser would do.
     ///
     /// **Defaults** to false (being turned off).
+    #[deprecated(note = "Use `cookie_config.save_cookies` instead")]
     pub save_cookies: bool,
 
+    /// The domain the server is treated as being addressed by, for cookies.
+    ///
+    /// When set, saved cookies with a `Domain` are only sent to requests for
+    /// hosts within that domain. Requests to the server use this as their host,
+    /// and requests to hosts aliased using [`TestServer::alias_host()`](crate::TestServer::alias_host())
+    /// use the alias. Cookies without a `Domain` are sent to all hosts.
+    ///
+    /// This allows testing cookies shared across subdomains,
+    /// such as a `Domain=.app.local` session used by `auth.app.local` and `www.app.local`.
+    ///
+    /// **Defaults** to none, where all cookies are sent to all requests.
+    #[deprecated(note = "Use `cookie_config.cookie_domain` instead")]
+    pub cookie_domain: Option<String>,
+
     /// Asserts that requests made to the test server,
     /// will by default,
     /// return a status code in the 2xx range.
@@ -70,8 +137,19 @@ pub struct TestServerConfig {
     /// which you presume should always work.
     ///
     /// **Defaults** to false (being turned off).
+    #[deprecated(note = "Use `expectation_config.expect_success_by_default` instead")]
     pub expect_success_by_default: bool,
 
+    /// Asserts that responses have a `Content-Type` matching this, by default.
+    ///
+    /// This can be overridden on a per request basis using
+    /// [`TestRequest::expect_content_type()`](crate::TestRequest::expect_content_type())
+    /// and [`TestRequest::expect_any_content_type()`](crate::TestRequest::expect_any_content_type()).
+    ///
+    /// **Defaults** to none (the content type is not checked).
+    #[deprecated(note = "Use `expectation_config.expected_content_type` instead")]
+    pub expected_content_type: Option<String>,
+
     /// If you make a request with a 'http://' schema,
     /// then it will ignore the Test Server's address.
     ///
@@ -86,6 +164,7 @@ pub struct TestServerConfig {
     /// `http://localhost:1234/http://google.com`.
     ///
     /// **Defaults** to false (being turned off).
+    #[deprecated(note = "Use `transport_config.restrict_requests_with_http_schema` instead")]
     pub restrict_requests_with_http_schema: bool,
 
     /// Set the default content type for all requests created by the `TestServer`.
@@ -96,7 +175,250 @@ pub struct TestServerConfig {
     /// Set the default scheme to use for all requests created by the `TestServer`.
     ///
     /// This overrides the default 'http'.
+    #[deprecated(note = "Use `transport_config.default_scheme` instead")]
     pub default_scheme: Option<String>,
+
+    /// Injects failures into requests made by the `TestServer`,
+    /// such as random 500 errors, dropped connections, and extra latency.
+    ///
+    /// See [`ChaosConfig`](crate::ChaosConfig) for more details.
+    ///
+    /// **Defaults** to none (no failures are injected).
+    pub chaos: Option<ChaosConfig>,
+
+    /// Set for the server to record metrics on the requests that reach your application.
+    /// Such as hits per path, status codes returned, and bytes transferred.
+    ///
+    /// These are retrieved using [`TestServer::metrics()`](crate::TestServer::metrics()).
+    ///
+    /// **Defaults** to false (being turned off).
+    pub record_metrics: bool,
+
+    /// Set for the server to record every request sent, and the response received.
+    /// These can be turned into axum-test code, for starting regression tests from real traffic.
+    ///
+    /// These are retrieved using [`TestServer::recorded_exchanges()`](crate::TestServer::recorded_exchanges()).
+    ///
+    /// **Defaults** to false (being turned off).
+    pub record_exchanges: bool,
+
+    /// A log of events pushed by your application,
+    /// for asserting the order things happen using [`TestServer::events()`](crate::TestServer::events()).
+    ///
+    /// The same log must also be given to your application as an [`Extension`](::axum::Extension).
+    /// See [`TestEventLog`](crate::TestEventLog) for more details.
+    ///
+    /// **Defaults** to none.
+    pub event_log: Option<TestEventLog>,
+
+    /// Checks the body of every response with a 4xx or 5xx status code
+    /// matches the schema given, panicking if it does not.
+    ///
+    /// This runs before the status code is checked against the expected state.
+    ///
+    /// **Defaults** to none (error bodies are not checked).
+    pub error_body_schema: Option<ErrorBodySchema>,
+
+    /// Checks run against every response received,
+    /// panicking if any of them return an error.
+    ///
+    /// These run before the status code is checked against the expected state.
+    ///
+    /// **Defaults** to none.
+    pub response_validators: Vec<ResponseValidator>,
+
+    /// Interceptors applied to every request before it reaches the application,
+    /// in the order given.
+    ///
+    /// See [`RequestInterceptor`](crate::RequestInterceptor) for more details.
+    ///
+    /// **Defaults** to none.
+    pub interceptors: Vec<RequestInterceptor>,
+
+    /// Probes taking a measurement around every request,
+    /// such as a [`QueryCounter`](crate::QueryCounter).
+    ///
+    /// See [`InstrumentationProbe`](crate::InstrumentationProbe) for more details.
+    ///
+    /// **Defaults** to none.
+    pub probes: Vec<ProbeHandle>,
+
+    /// Headers whose values are hidden in output produced by the crate,
+    /// such as request descriptions in panic messages.
+    ///
+    /// `Authorization`, `Proxy-Authorization`, and `Cookie` are always hidden.
+    ///
+    /// **Defaults** to none.
+    pub masked_headers: Vec<String>,
+
+    /// Json paths whose values are hidden in output produced by the crate,
+    /// such as response bodies printed in panic messages.
+    ///
+    /// Paths start from `$`, and can select keys (`$.user.password`), indexes (`$.items[0]`),
+    /// all children (`$.users[*].token`), or keys at any depth (`$..secret`).
+    ///
+    /// **Defaults** to none.
+    pub masked_json_paths: Vec<String>,
+
+    /// Routes of the application given by hand, returned from [`TestServer::routes()`](crate::TestServer::routes())
+    /// after any recorded by a [`RecordingRouter`](crate::RecordingRouter).
+    ///
+    /// This list is user-supplied, and is not checked against the application.
+    /// Prefer building the application with a `RecordingRouter`,
+    /// which records each route as it is added, so the list cannot drift from the real routes.
+    ///
+    /// **Defaults** to none.
+    pub routes: Vec<RouteInfo>,
+
+    /// Environment variables set when the server is built,
+    /// and restored to their previous values when it is dropped.
+    ///
+    /// Servers with environment variables hold a process-wide lock while they are alive,
+    /// so tests setting environment variables in parallel wait for one another.
+    /// Building the server returns an error if they are not released within 60 seconds.
+    ///
+    /// Multiple servers within the same test can set them, when built on the same thread.
+    /// Code reading environment variables from outside of these servers is not locked.
+    ///
+    /// **Defaults** to none.
+    pub env_vars: Vec<(String, String)>,
+
+    /// Changes applied to the Reqwest client, in the order given,
+    /// such as to set a proxy or custom TLS settings.
+    ///
+    /// See [`ReqwestConfigurer`](crate::ReqwestConfigurer) for more details.
+    ///
+    /// **Defaults** to none.
+    #[cfg(feature = "reqwest")]
+    pub reqwest_configurers: Vec<ReqwestConfigurer>,
+
+    /// Set for the server to collect requests which received a response
+    /// carrying a `Deprecation` or `Sunset` header.
+    ///
+    /// These are printed as a warning when the `TestServer` is dropped,
+    /// and can be retrieved using [`TestServer::deprecated_requests()`](crate::TestServer::deprecated_requests()).
+    ///
+    /// The test harness captures output of passing tests,
+    /// so the warning is only seen when running with `cargo test -- --nocapture`.
+    /// Use `fail_on_deprecated` to fail the test instead.
+    ///
+    /// **Defaults** to false (being turned off).
+    pub warn_on_deprecated: bool,
+
+    /// Set for the server to panic when it is dropped,
+    /// if any requests received a response carrying a `Deprecation` or `Sunset` header.
+    ///
+    /// This collects deprecated requests in the same way as `warn_on_deprecated`,
+    /// but fails the test rather than printing a warning.
+    ///
+    /// **Defaults** to false (being turned off).
+    pub fail_on_deprecated: bool,
+
+    /// Set for the server to panic when it is dropped,
+    /// if any requests are still being served.
+    ///
+    /// See [`TestServer::assert_no_leaked_connections()`](crate::TestServer::assert_no_leaked_connections())
+    /// for what is counted as a leaked connection.
+    ///
+    /// **Defaults** to false (being turned off).
+    pub fail_on_leaked_connections: bool,
+
+    /// Shifts the dates sent by requests, as though the client's clock was off by this amount.
+    ///
+    /// When set, every request sends a `Date` header of the current time plus the skew.
+    /// The skew is also added to dates set using [`TestRequest::date()`](crate::TestRequest::date())
+    /// and [`TestRequest::if_modified_since()`](crate::TestRequest::if_modified_since()).
+    ///
+    /// **Defaults** to none (no `Date` header is added).
+    pub clock_skew: Option<TimeDuration>,
+
+    /// The seed for all randomness within the `TestServer`.
+    /// This includes generated idempotency keys, WebSocket keys,
+    /// and the chaos layer (replacing the seed in the [`ChaosConfig`](crate::ChaosConfig)).
+    ///
+    /// When set, random ports are also picked using the seed.
+    /// If the port picked is already in use, the next port from the seed is tried,
+    /// so the same port is only reproduced when it is free.
+    ///
+    /// The seed in use is printed when a test panics,
+    /// and is available from [`TestServer::seed()`](crate::TestServer::seed()).
+    ///
+    /// **Defaults** to none. The seed from the [`ChaosConfig`](crate::ChaosConfig) is used if set,
+    /// otherwise a seed is picked at random.
+    pub seed: Option<u64>,
+
+    /// A path to poll, and how long to wait, for the server to become ready.
+    ///
+    /// Before the first request is sent, the path is polled with GET requests
+    /// until it returns a 2xx response. If this takes longer than the duration given,
+    /// then the request will fail.
+    ///
+    /// The server is polled on the first request, rather than when it is built,
+    /// as building a `TestServer` is not async.
+    /// Requests made using Reqwest do not wait,
+    /// so call [`TestServer::wait_until_ready()`](crate::TestServer::wait_until_ready()) before using them.
+    ///
+    /// Polling bypasses metrics and chaos, so it is neither recorded nor disrupted.
+    ///
+    /// This is useful for applications which start background work on startup,
+    /// such as running migrations.
+    ///
+    /// **Defaults** to none (requests are sent immediately).
+    pub wait_until_ready: Option<(String, Duration)>,
+
+    /// Set for the server to print a line for each request sent, to stderr.
+    /// This includes the method, path, status code, how long it took, and the response body size.
+    ///
+    /// This helps to find which request broke, in tests which make many requests.
+    /// Logging can also be turned on for all servers, by setting the `AXUM_TEST_VERBOSE`
+    /// environment variable (i.e. `AXUM_TEST_VERBOSE=1 cargo test`).
+    ///
+    /// **Defaults** to false, unless `AXUM_TEST_VERBOSE` is set.
+    pub verbose: bool,
+
+    /// The most bytes of a response body to read into memory.
+    ///
+    /// When a response body grows beyond this, reading stops,
+    /// and the request fails with [`Error::BodyTooLarge`](crate::Error::BodyTooLarge).
+    /// This protects the test process from handlers which stream huge, or endless, bodies.
+    ///
+    /// **Defaults** to none (bodies are read in full).
+    pub max_buffered_body: Option<usize>,
+
+    /// The most bytes of a request body to keep a copy of on the response,
+    /// for reading back using [`TestResponse::request_body_bytes()`](crate::TestResponse::request_body_bytes()).
+    ///
+    /// Bodies larger than this are not kept. The request headers are always kept.
+    /// Set this to zero to stop keeping request bodies.
+    ///
+    /// **Defaults** to 64 KiB.
+    pub max_recorded_request_body: usize,
+
+    /// Set for handlers which panic to return a `500 Internal Server Error`,
+    /// with the panic message available using [`TestResponse::panic_message()`](crate::TestResponse::panic_message()).
+    ///
+    /// When this is off panics are left alone. With the mock transport
+    /// the panic is raised in the test, and with the HTTP transport the connection is dropped.
+    ///
+    /// The panic message is only sent back to requests made by the `TestServer`,
+    /// and not to other clients calling the HTTP transport.
+    ///
+    /// Panics are only caught when the `TestServer` starts the service itself,
+    /// and not when it is given an [`axum::serve::Serve`].
+    ///
+    /// **Defaults** to false (being turned off).
+    pub catch_panics: bool,
+
+    /// Set to keep the raw bytes of each response head, as they were sent over the connection,
+    /// for reading using [`TestResponse::raw_head()`](crate::TestResponse::raw_head()).
+    ///
+    /// This is for testing the exact casing and formatting of headers,
+    /// which is lost when they are parsed into a `HeaderMap`.
+    ///
+    /// Raw heads are only captured by the HTTP transport, for HTTP/1 requests.
+    ///
+    /// **Defaults** to false (being turned off).
+    pub capture_raw_head: bool,
 }
 
 impl TestServerConfig {
@@ -112,37 +434,225 @@ impl TestServerConfig {
     /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
     /// #
     /// use axum::Router;
+    /// use axum_test::CookieConfig;
     /// use axum_test::TestServer;
     /// use axum_test::TestServerConfig;
     ///
     /// let app = Router::new();
-    /// let config = TestServerConfig {
-    ///     save_cookies: true,
-    ///     default_content_type: Some("application/json".to_string()),
-    ///     ..Default::default()
-    /// };
+    /// let mut config = TestServerConfig::new();
+    /// config.cookie_config = CookieConfig::new().save_cookies();
+    /// config.default_content_type = Some("application/json".to_string());
+    ///
     /// let server = TestServer::new_with_config(app, config)?;
     /// #
     /// # Ok(())
     /// # }
     /// ```
-    pub fn build<A>(self, app: A) -> Result<TestServer>
+    pub fn build<A>(self, app: A) -> Result<TestServer, Error>
     where
         A: IntoTransportLayer,
     {
         TestServer::new_with_config(app, self)
     }
+
+    /// Returns the transport settings, falling back to the deprecated fields for those not set.
+    #[allow(deprecated)]
+    pub(crate) fn resolved_transport_config(&self) -> TransportConfig {
+        self.transport_config.clone().or(TransportConfig {
+            transport: self.transport,
+            default_scheme: self.default_scheme.clone(),
+            restrict_requests_with_http_schema: self
+                .restrict_requests_with_http_schema
+                .then_some(true),
+        })
+    }
+
+    /// Returns the cookie settings, falling back to the deprecated fields for those not set.
+    #[allow(deprecated)]
+    pub(crate) fn resolved_cookie_config(&self) -> CookieConfig {
+        self.cookie_config.clone().or(CookieConfig {
+            save_cookies: self.save_cookies.then_some(true),
+            cookie_domain: self.cookie_domain.clone(),
+        })
+    }
+
+    /// Returns the expectation settings, falling back to the deprecated fields for those not set.
+    #[allow(deprecated)]
+    pub(crate) fn resolved_expectation_config(&self) -> ExpectationConfig {
+        self.expectation_config.clone().or(ExpectationConfig {
+            expect_success_by_default: self.expect_success_by_default.then_some(true),
+            expected_content_type: self.expected_content_type.clone(),
+        })
+    }
+
+    /// Combines this config with another, such as team-level settings with those for one test.
+    ///
+    /// Settings given in `other` take priority:
+    ///
+    ///  * Optional settings set on `other` replace those on this config.
+    ///  * Settings within the sub-configs which are set on `other` replace those on this config,
+    ///    including flags turned off, such as with [`CookieConfig::do_not_save_cookies()`](crate::CookieConfig::do_not_save_cookies()).
+    ///    The deprecated fields are read as part of their sub-config, and are set to the merged result.
+    ///  * Other flags turned on in either config stay on.
+    ///  * Response validators, interceptors, probes, masks, and environment variables from both are kept,
+    ///    with these running first.
+    ///  * `max_recorded_request_body` is taken from `other` when it is not the default.
+    ///
+    /// ```rust
+    /// use axum_test::CookieConfig;
+    /// use axum_test::ExpectationConfig;
+    /// use axum_test::TestServerConfig;
+    ///
+    /// let team_config = TestServerConfig {
+    ///     cookie_config: CookieConfig::new().save_cookies(),
+    ///     expectation_config: ExpectationConfig::new().expect_success_by_default(),
+    ///     ..TestServerConfig::default()
+    /// };
+    ///
+    /// let test_config = TestServerConfig {
+    ///     cookie_config: CookieConfig::new().do_not_save_cookies(),
+    ///     ..TestServerConfig::default()
+    /// };
+    ///
+    /// let config = team_config.merge(test_config);
+    /// assert_eq!(config.cookie_config.save_cookies, Some(false));
+    /// assert_eq!(config.expectation_config.expect_success_by_default, Some(true));
+    /// ```
+    #[must_use]
+    #[allow(deprecated)]
+    pub fn merge(self, other: TestServerConfig) -> Self {
+        let transport_config = other
+            .resolved_transport_config()
+            .or(self.resolved_transport_config());
+        let cookie_config = other
+            .resolved_cookie_config()
+            .or(self.resolved_cookie_config());
+        let expectation_config = other
+            .resolved_expectation_config()
+            .or(self.resolved_expectation_config());
+
+        let default_max_recorded_request_body = Self::default().max_recorded_request_body;
+        let max_recorded_request_body =
+            if other.max_recorded_request_body == default_max_recorded_request_body {
+                self.max_recorded_request_body
+            } else {
+                other.max_recorded_request_body
+            };
+
+        let mut response_validators = self.response_validators;
+        response_validators.extend(other.response_validators);
+
+        let mut interceptors = self.interceptors;
+        interceptors.extend(other.interceptors);
+
+        let mut probes = self.probes;
+        probes.extend(other.probes);
+
+        let mut masked_headers = self.masked_headers;
+        masked_headers.extend(other.masked_headers);
+
+        let mut masked_json_paths = self.masked_json_paths;
+        masked_json_paths.extend(other.masked_json_paths);
+
+        let mut routes = self.routes;
+        routes.extend(other.routes);
+
+        let mut env_vars = self.env_vars;
+        env_vars.extend(other.env_vars);
+
+        #[cfg(feature = "reqwest")]
+        let reqwest_configurers = {
+            let mut reqwest_configurers = self.reqwest_configurers;
+            reqwest_configurers.extend(other.reqwest_configurers);
+            reqwest_configurers
+        };
+
+        Self {
+            transport: transport_config.transport,
+            save_cookies: cookie_config.save_cookies.unwrap_or(false),
+            cookie_domain: cookie_config.cookie_domain.clone(),
+            expect_success_by_default: expectation_config
+                .expect_success_by_default
+                .unwrap_or(false),
+            expected_content_type: expectation_config.expected_content_type.clone(),
+            restrict_requests_with_http_schema: transport_config
+                .restrict_requests_with_http_schema
+                .unwrap_or(false),
+            default_scheme: transport_config.default_scheme.clone(),
+            transport_config,
+            cookie_config,
+            expectation_config,
+            default_content_type: other.default_content_type.or(self.default_content_type),
+            chaos: other.chaos.or(self.chaos),
+            record_metrics: self.record_metrics || other.record_metrics,
+            record_exchanges: self.record_exchanges || other.record_exchanges,
+            event_log: other.event_log.or(self.event_log),
+            error_body_schema: other.error_body_schema.or(self.error_body_schema),
+            response_validators,
+            interceptors,
+            probes,
+            masked_headers,
+            masked_json_paths,
+            routes,
+            env_vars,
+            #[cfg(feature = "reqwest")]
+            reqwest_configurers,
+            warn_on_deprecated: self.warn_on_deprecated || other.warn_on_deprecated,
+            fail_on_deprecated: self.fail_on_deprecated || other.fail_on_deprecated,
+            fail_on_leaked_connections: self.fail_on_leaked_connections
+                || other.fail_on_leaked_connections,
+            clock_skew: other.clock_skew.or(self.clock_skew),
+            seed: other.seed.or(self.seed),
+            wait_until_ready: other.wait_until_ready.or(self.wait_until_ready),
+            verbose: self.verbose || other.verbose,
+            max_buffered_body: other.max_buffered_body.or(self.max_buffered_body),
+            max_recorded_request_body,
+            catch_panics: self.catch_panics || other.catch_panics,
+            capture_raw_head: self.capture_raw_head || other.capture_raw_head,
+        }
+    }
 }
 
 impl Default for TestServerConfig {
+    #[allow(deprecated)]
     fn default() -> Self {
         Self {
+            transport_config: TransportConfig::default(),
+            cookie_config: CookieConfig::default(),
+            expectation_config: ExpectationConfig::default(),
             transport: None,
             save_cookies: false,
+            cookie_domain: None,
             expect_success_by_default: false,
+            expected_content_type: None,
             restrict_requests_with_http_schema: false,
             default_content_type: None,
             default_scheme: None,
+            chaos: None,
+            record_metrics: false,
+            record_exchanges: false,
+            event_log: None,
+            error_body_schema: None,
+            response_validators: Vec::new(),
+            interceptors: Vec::new(),
+            probes: Vec::new(),
+            masked_headers: Vec::new(),
+            masked_json_paths: Vec::new(),
+            routes: Vec::new(),
+            env_vars: Vec::new(),
+            #[cfg(feature = "reqwest")]
+            reqwest_configurers: Vec::new(),
+            warn_on_deprecated: false,
+            fail_on_deprecated: false,
+            fail_on_leaked_connections: false,
+            clock_skew: None,
+            seed: None,
+            wait_until_ready: None,
+            verbose: false,
+            max_buffered_body: None,
+            max_recorded_request_body: 64 * 1024,
+            catch_panics: false,
+            capture_raw_head: false,
         }
     }
 }
@@ -161,6 +671,7 @@ mod test_scheme {
 
     use crate::TestServer;
     use crate::TestServerConfig;
+    use crate::TransportConfig;
 
     async fn route_get_scheme(request: Request) -> String {
         request.uri().scheme_str().unwrap().to_string()
@@ -171,7 +682,7 @@ mod test_scheme {
         let router = Router::new().route("/scheme", get(route_get_scheme));
 
         let config = TestServerConfig {
-            default_scheme: Some("https".to_string()),
+            transport_config: TransportConfig::new().default_scheme("https"),
             ..Default::default()
         };
         let server = TestServer::new_with_config(router, config).unwrap();
@@ -179,3 +690,204 @@ mod test_scheme {
         server.get("/scheme").await.assert_text("https");
     }
 }
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod test_sub_configs {
+    use crate::CookieConfig;
+    use crate::ExpectationConfig;
+    use crate::TestServerConfig;
+    use crate::Transport;
+    use crate::TransportConfig;
+
+    #[test]
+    fn it_should_resolve_sub_configs_from_deprecated_fields() {
+        let mut config = TestServerConfig::new();
+        config.default_scheme = Some("https".to_string());
+        config.save_cookies = true;
+        config.expect_success_by_default = true;
+
+        assert_eq!(
+            config.resolved_transport_config(),
+            TransportConfig::new().default_scheme("https")
+        );
+        assert_eq!(
+            config.resolved_cookie_config(),
+            CookieConfig::new().save_cookies()
+        );
+        assert_eq!(
+            config.resolved_expectation_config(),
+            ExpectationConfig::new().expect_success_by_default()
+        );
+    }
+
+    #[test]
+    fn it_should_prefer_sub_configs_over_deprecated_fields() {
+        let mut config = TestServerConfig::new();
+        config.transport = Some(Transport::HttpRandomPort);
+        config.save_cookies = true;
+        config.transport_config = TransportConfig::new().transport(Transport::MockHttp);
+        config.cookie_config = CookieConfig::new().do_not_save_cookies();
+
+        assert_eq!(
+            config.resolved_transport_config().transport,
+            Some(Transport::MockHttp)
+        );
+        assert_eq!(config.resolved_cookie_config().save_cookies, Some(false));
+    }
+
+    #[tokio::test]
+    async fn it_should_serve_requests_using_deprecated_fields() {
+        use axum::routing::get;
+        use axum::Router;
+
+        use crate::TestServer;
+
+        let app = Router::new().route("/ping", get(|| async { "pong!" }));
+        let mut config = TestServerConfig::new();
+        config.transport = Some(Transport::HttpRandomPort);
+
+        let server = TestServer::new_with_config(app, config).unwrap();
+        assert!(server.server_address().is_some());
+        server.get("/ping").await.assert_text("pong!");
+    }
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod test_merge {
+    use std::time::Duration;
+
+    use crate::CookieConfig;
+    use crate::ExpectationConfig;
+    use crate::TestServerConfig;
+    use crate::Transport;
+    use crate::TransportConfig;
+
+    #[test]
+    fn it_should_replace_options_set_on_other() {
+        let mut team_config = TestServerConfig::new();
+        team_config.transport_config = TransportConfig::new().transport(Transport::MockHttp);
+        team_config.default_content_type = Some("application/json".to_string());
+
+        let mut test_config = TestServerConfig::new();
+        test_config.transport_config = TransportConfig::new().transport(Transport::HttpRandomPort);
+
+        let config = team_config.merge(test_config);
+
+        assert_eq!(
+            config.transport_config.transport,
+            Some(Transport::HttpRandomPort)
+        );
+        assert_eq!(
+            config.default_content_type,
+            Some("application/json".to_string())
+        );
+    }
+
+    #[test]
+    fn it_should_let_other_turn_off_sub_config_flags() {
+        let mut team_config = TestServerConfig::new();
+        team_config.cookie_config = CookieConfig::new().save_cookies();
+        team_config.expectation_config = ExpectationConfig::new().expect_success_by_default();
+
+        let mut test_config = TestServerConfig::new();
+        test_config.cookie_config = CookieConfig::new().do_not_save_cookies();
+
+        let config = team_config.merge(test_config);
+
+        assert_eq!(config.cookie_config.save_cookies, Some(false));
+        assert_eq!(
+            config.expectation_config.expect_success_by_default,
+            Some(true)
+        );
+        assert!(!config.save_cookies);
+        assert!(config.expect_success_by_default);
+    }
+
+    #[test]
+    fn it_should_merge_deprecated_fields_with_sub_configs() {
+        let mut team_config = TestServerConfig::new();
+        team_config.save_cookies = true;
+        team_config.transport = Some(Transport::MockHttp);
+
+        let mut test_config = TestServerConfig::new();
+        test_config.transport_config = TransportConfig::new().transport(Transport::HttpRandomPort);
+
+        let config = team_config.merge(test_config);
+
+        assert_eq!(config.cookie_config.save_cookies, Some(true));
+        assert!(config.save_cookies);
+        assert_eq!(
+            config.transport_config.transport,
+            Some(Transport::HttpRandomPort)
+        );
+        assert_eq!(config.transport, Some(Transport::HttpRandomPort));
+    }
+
+    #[test]
+    fn it_should_keep_flags_turned_on_in_either() {
+        let mut team_config = TestServerConfig::new();
+        team_config.record_metrics = true;
+
+        let mut test_config = TestServerConfig::new();
+        test_config.verbose = true;
+
+        let config = team_config.merge(test_config);
+
+        assert!(config.record_metrics);
+        assert!(config.verbose);
+        assert!(!config.record_exchanges);
+    }
+
+    #[test]
+    fn it_should_keep_max_recorded_request_body_when_other_is_default() {
+        let mut team_config = TestServerConfig::new();
+        team_config.max_recorded_request_body = 0;
+        team_config.wait_until_ready = Some(("/health".to_string(), Duration::from_secs(1)));
+
+        let config = team_config.merge(TestServerConfig::new());
+
+        assert_eq!(config.max_recorded_request_body, 0);
+        assert_eq!(
+            config.wait_until_ready,
+            Some(("/health".to_string(), Duration::from_secs(1)))
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_eq {
+    use crate::CookieConfig;
+    use crate::ResponseValidator;
+    use crate::TestServerConfig;
+
+    #[test]
+    fn it_should_equal_a_config_with_the_same_settings() {
+        let config = TestServerConfig {
+            cookie_config: CookieConfig::new().save_cookies(),
+            ..TestServerConfig::default()
+        };
+
+        assert_eq!(config, config.clone());
+        assert_ne!(config, TestServerConfig::default());
+    }
+
+    #[test]
+    fn it_should_compare_hooks_by_identity() {
+        let validator = ResponseValidator::new(|_| Ok(()));
+        let mut config = TestServerConfig::new();
+        config.response_validators.push(validator.clone());
+
+        let mut same_config = TestServerConfig::new();
+        same_config.response_validators.push(validator);
+
+        let mut other_config = TestServerConfig::new();
+        other_config
+            .response_validators
+            .push(ResponseValidator::new(|_| Ok(())));
+
+        assert_eq!(config, same_config);
+        assert_ne!(config, other_config);
+    }
+}
@@ -0,0 +1,64 @@
+/// The settings for how a [`TestServer`](crate::TestServer) saves and sends cookies.
+///
+/// This is held in [`TestServerConfig::cookie_config`](crate::TestServerConfig::cookie_config),
+/// and can be set using [`TestServerBuilder::with_cookie_config()`](crate::TestServerBuilder::with_cookie_config()).
+/// Settings left as `None` fall back to the deprecated fields on `TestServerConfig`.
+///
+/// ```rust
+/// use axum_test::CookieConfig;
+///
+/// let cookie_config = CookieConfig::new()
+///     .save_cookies()
+///     .cookie_domain("app.local");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CookieConfig {
+    /// Saves cookies returned by responses, for use in future requests,
+    /// like a browser would do.
+    ///
+    /// **Defaults** to none (being turned off).
+    pub save_cookies: Option<bool>,
+
+    /// The domain the server is treated as being addressed by, for cookies.
+    ///
+    /// See [`TestServerBuilder::cookie_domain()`](crate::TestServerBuilder::cookie_domain()) for more details.
+    pub cookie_domain: Option<String>,
+}
+
+impl CookieConfig {
+    /// Creates a default `CookieConfig`, where cookies are not saved.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Saves cookies returned by responses, for use in future requests.
+    pub fn save_cookies(mut self) -> Self {
+        self.save_cookies = Some(true);
+        self
+    }
+
+    /// Does not save cookies returned by responses,
+    /// including when a config this is merged into saves them.
+    pub fn do_not_save_cookies(mut self) -> Self {
+        self.save_cookies = Some(false);
+        self
+    }
+
+    /// Sets the domain the server is addressed by, for sending saved cookies.
+    pub fn cookie_domain<D>(mut self, domain: D) -> Self
+    where
+        D: Into<String>,
+    {
+        self.cookie_domain = Some(domain.into());
+        self
+    }
+
+    /// Takes each setting from this, falling back to `other` for those left as `None`.
+    pub(crate) fn or(self, other: Self) -> Self {
+        Self {
+            save_cookies: self.save_cookies.or(other.save_cookies),
+            cookie_domain: self.cookie_domain.or(other.cookie_domain),
+        }
+    }
+}
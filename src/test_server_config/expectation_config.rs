@@ -0,0 +1,58 @@
+/// The settings for what a [`TestServer`](crate::TestServer) expects of every response, by default.
+///
+/// This is held in [`TestServerConfig::expectation_config`](crate::TestServerConfig::expectation_config),
+/// and can be set using [`TestServerBuilder::with_expectation_config()`](crate::TestServerBuilder::with_expectation_config()).
+/// Settings left as `None` fall back to the deprecated fields on `TestServerConfig`.
+///
+/// ```rust
+/// use axum_test::ExpectationConfig;
+///
+/// let expectation_config = ExpectationConfig::new()
+///     .expect_success_by_default()
+///     .expect_content_type("application/json");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ExpectationConfig {
+    /// Asserts requests return a status code in the 2xx range,
+    /// unless overridden using [`TestRequest::expect_failure()`](crate::TestRequest::expect_failure()).
+    ///
+    /// **Defaults** to none (being turned off).
+    pub expect_success_by_default: Option<bool>,
+
+    /// Asserts responses have a `Content-Type` matching this,
+    /// unless overridden using [`TestRequest::expect_content_type()`](crate::TestRequest::expect_content_type())
+    /// or [`TestRequest::expect_any_content_type()`](crate::TestRequest::expect_any_content_type()).
+    ///
+    /// **Defaults** to none (the content type is not checked).
+    pub expected_content_type: Option<String>,
+}
+
+impl ExpectationConfig {
+    /// Creates a default `ExpectationConfig`, where responses are not checked.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Asserts requests return a status code in the 2xx range, unless overridden on the request.
+    pub fn expect_success_by_default(mut self) -> Self {
+        self.expect_success_by_default = Some(true);
+        self
+    }
+
+    /// Asserts responses have a `Content-Type` matching the one given, unless overridden on the request.
+    pub fn expect_content_type(mut self, content_type: &str) -> Self {
+        self.expected_content_type = Some(content_type.to_string());
+        self
+    }
+
+    /// Takes each setting from this, falling back to `other` for those left as `None`.
+    pub(crate) fn or(self, other: Self) -> Self {
+        Self {
+            expect_success_by_default: self
+                .expect_success_by_default
+                .or(other.expect_success_by_default),
+            expected_content_type: self.expected_content_type.or(other.expected_content_type),
+        }
+    }
+}
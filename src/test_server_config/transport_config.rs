@@ -0,0 +1,68 @@
+use crate::Transport;
+
+/// The settings for how a [`TestServer`](crate::TestServer) runs, and how it addresses requests.
+///
+/// This is held in [`TestServerConfig::transport_config`](crate::TestServerConfig::transport_config),
+/// and can be set using [`TestServerBuilder::with_transport_config()`](crate::TestServerBuilder::with_transport_config()).
+/// Settings left as `None` fall back to the deprecated fields on `TestServerConfig`.
+///
+/// ```rust
+/// use axum_test::Transport;
+/// use axum_test::TransportConfig;
+///
+/// let transport_config = TransportConfig::new()
+///     .transport(Transport::HttpRandomPort)
+///     .default_scheme("https");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TransportConfig {
+    /// Which transport mode to use to process requests.
+    ///
+    /// See [`Transport`](crate::Transport) for more details.
+    pub transport: Option<Transport>,
+
+    /// The scheme to use for all requests, replacing the default of 'http'.
+    pub default_scheme: Option<String>,
+
+    /// Sends requests with a 'http://' schema to the server, rather than to the address given.
+    ///
+    /// See [`TestServerBuilder::restrict_requests_with_http_schema()`](crate::TestServerBuilder::restrict_requests_with_http_schema()) for more details.
+    pub restrict_requests_with_http_schema: Option<bool>,
+}
+
+impl TransportConfig {
+    /// Creates a default `TransportConfig`, which uses the default transport for the application.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets which transport mode to use to process requests.
+    pub fn transport(mut self, transport: Transport) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    /// Sets the scheme to use for all requests, replacing the default of 'http'.
+    pub fn default_scheme(mut self, scheme: &str) -> Self {
+        self.default_scheme = Some(scheme.to_string());
+        self
+    }
+
+    /// Sends requests with a 'http://' schema to the server, rather than to the address given.
+    pub fn restrict_requests_with_http_schema(mut self) -> Self {
+        self.restrict_requests_with_http_schema = Some(true);
+        self
+    }
+
+    /// Takes each setting from this, falling back to `other` for those left as `None`.
+    pub(crate) fn or(self, other: Self) -> Self {
+        Self {
+            transport: self.transport.or(other.transport),
+            default_scheme: self.default_scheme.or(other.default_scheme),
+            restrict_requests_with_http_schema: self
+                .restrict_requests_with_http_schema
+                .or(other.restrict_requests_with_http_schema),
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use http::Method;
+use http::StatusCode;
+
+use crate::internals::ExpectedState;
+use crate::TestRequest;
+use crate::TestServer;
+
+/// A handle to a [`TestServer`], where all paths are prefixed.
+///
+/// This is for testing apps nested using [`Router::nest()`](::axum::Router::nest()),
+/// without repeating the prefix on every request.
+/// It is created by calling [`TestServer::scope()`](crate::TestServer::scope()).
+///
+/// Requests are sent through the `TestServer`,
+/// so they share its cookies, headers, query parameters, and other settings.
+///
+/// ```rust
+/// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+/// #
+/// use axum::Router;
+/// use axum::routing::get;
+/// use axum_test::TestServer;
+///
+/// let api = Router::new()
+///     .route(&"/users", get(|| async { "users" }));
+/// let app = Router::new()
+///     .nest(&"/api/v1", api);
+///
+/// let server = TestServer::new(app)?;
+/// let api_v1 = server.scope(&"/api/v1");
+///
+/// // Sends a request to `/api/v1/users`
+/// api_v1.get(&"/users").await.assert_text("users");
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TestServerScope<'a> {
+    server: &'a TestServer,
+    prefix: String,
+}
+
+impl<'a> TestServerScope<'a> {
+    pub(crate) fn new(server: &'a TestServer, prefix: &str) -> Self {
+        Self {
+            server,
+            prefix: normalise_prefix(prefix),
+        }
+    }
+
+    /// The prefix added to paths in this scope.
+    #[must_use]
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Creates a scope nested within this one, with both prefixes added to paths.
+    #[must_use]
+    pub fn scope(&self, prefix: &str) -> TestServerScope<'a> {
+        TestServerScope::new(self.server, &join_path(&self.prefix, prefix))
+    }
+
+    /// Creates a HTTP GET request to the path, within this scope.
+    pub fn get(&self, path: &str) -> TestRequest {
+        self.method(Method::GET, path)
+    }
+
+    /// Creates a HTTP POST request to the path, within this scope.
+    pub fn post(&self, path: &str) -> TestRequest {
+        self.method(Method::POST, path)
+    }
+
+    /// Creates a HTTP PATCH request to the path, within this scope.
+    pub fn patch(&self, path: &str) -> TestRequest {
+        self.method(Method::PATCH, path)
+    }
+
+    /// Creates a HTTP PUT request to the path, within this scope.
+    pub fn put(&self, path: &str) -> TestRequest {
+        self.method(Method::PUT, path)
+    }
+
+    /// Creates a HTTP DELETE request to the path, within this scope.
+    pub fn delete(&self, path: &str) -> TestRequest {
+        self.method(Method::DELETE, path)
+    }
+
+    /// Creates a HTTP request, to the method and path provided, within this scope.
+    ///
+    /// Absolute urls (such as `http://example.com/users`) are sent as is, without the prefix.
+    pub fn method(&self, method: Method, path: &str) -> TestRequest {
+        self.server.method(method, &join_path(&self.prefix, path))
+    }
+
+    /// Asserts the app serves something at this prefix,
+    /// by sending a GET request to it and checking the response is not a `404 Not Found`.
+    ///
+    /// Axum's `Router` does not expose the routes it holds,
+    /// so the prefix cannot be checked against them directly.
+    /// The nested router needs a route, or a fallback, at its root for this to pass.
+    ///
+    /// This is useful for catching a mistyped prefix,
+    /// before it causes confusing failures across many tests.
+    pub async fn assert_prefix_exists(&self) {
+        let response = self
+            .server
+            .get(&self.prefix)
+            .expect_state(ExpectedState::None)
+            .await;
+
+        assert_ne!(
+            response.status_code(),
+            StatusCode::NOT_FOUND,
+            "Expected prefix '{}' to exist on the server, received 404 Not Found",
+            self.prefix
+        );
+    }
+}
+
+fn normalise_prefix(prefix: &str) -> String {
+    let trimmed = prefix.trim_end_matches('/');
+
+    if trimmed.starts_with('/') {
+        trimmed.to_string()
+    } else {
+        format!("/{trimmed}")
+    }
+}
+
+fn join_path(prefix: &str, path: &str) -> String {
+    if path.contains("://") {
+        return path.to_string();
+    }
+
+    if path.is_empty() || path.starts_with('?') {
+        return format!("{prefix}{path}");
+    }
+
+    let path = path.strip_prefix('/').unwrap_or(path);
+    format!("{}/{path}", prefix.trim_end_matches('/'))
+}
+
+#[cfg(test)]
+mod test_join_path {
+    use super::*;
+
+    #[test]
+    fn it_should_join_paths_with_and_without_slashes() {
+        assert_eq!(join_path("/api", "/users"), "/api/users");
+        assert_eq!(join_path("/api", "users"), "/api/users");
+        assert_eq!(join_path("/api", ""), "/api");
+        assert_eq!(join_path("/api", "?page=2"), "/api?page=2");
+        assert_eq!(join_path("/api", "/"), "/api/");
+    }
+
+    #[test]
+    fn it_should_leave_absolute_urls_unchanged() {
+        assert_eq!(
+            join_path("/api", "http://example.com/users"),
+            "http://example.com/users"
+        );
+    }
+
+    #[test]
+    fn it_should_normalise_prefixes() {
+        assert_eq!(normalise_prefix("api/v1/"), "/api/v1");
+        assert_eq!(normalise_prefix("/api/v1"), "/api/v1");
+        assert_eq!(normalise_prefix("/"), "/");
+        assert_eq!(normalise_prefix(""), "/");
+    }
+}
+
+#[cfg(test)]
+mod test_scope {
+    use crate::TestServer;
+    use axum::routing::get;
+    use axum::Router;
+    use cookie::Cookie;
+    use http::HeaderName;
+    use http::HeaderValue;
+    use serde_json::Value;
+
+    fn new_test_app() -> Router {
+        let api = Router::new()
+            .route("/", get(|| async { "api root" }))
+            .route("/users", get(|| async { "users" }))
+            .route("/users/:id", get(|| async { "user" }))
+            .route("/headers", get(crate::routes::echo_headers_json));
+
+        Router::new().nest("/api/v1", api)
+    }
+
+    #[tokio::test]
+    async fn it_should_prefix_paths() {
+        let server = TestServer::new(new_test_app()).unwrap();
+        let api = server.scope("/api/v1");
+
+        api.get("/users").await.assert_text("users");
+        api.get("users").await.assert_text("users");
+    }
+
+    #[tokio::test]
+    async fn it_should_nest_scopes() {
+        let server = TestServer::new(new_test_app()).unwrap();
+        let users = server.scope("/api/v1").scope("/users");
+
+        assert_eq!(users.prefix(), "/api/v1/users");
+        users.get("/123").await.assert_text("user");
+    }
+
+    #[tokio::test]
+    async fn it_should_share_headers_and_cookies_with_the_server() {
+        let mut server = TestServer::new(new_test_app()).unwrap();
+        server.add_header(
+            HeaderName::from_static("x-shared"),
+            HeaderValue::from_static("from-server"),
+        );
+        server.add_cookie(Cookie::new("shared-cookie", "value"));
+
+        let headers = server
+            .scope("/api/v1")
+            .get("/headers")
+            .await
+            .json::<Value>();
+
+        assert_eq!(headers["x-shared"], "from-server");
+        assert_eq!(headers["cookie"], "shared-cookie=value");
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_assert_prefix_exists_for_nested_router() {
+        let server = TestServer::new(new_test_app()).unwrap();
+
+        server.scope("/api/v1").assert_prefix_exists().await;
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "Expected prefix '/api/v2' to exist on the server")]
+    async fn it_should_fail_assert_prefix_exists_for_missing_prefix() {
+        let server = TestServer::new(new_test_app()).unwrap();
+
+        server.scope("/api/v2").assert_prefix_exists().await;
+    }
+}
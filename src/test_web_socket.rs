@@ -2,37 +2,344 @@ use anyhow::anyhow;
 use anyhow::Context;
 use anyhow::Result;
 use bytes::Bytes;
+use cookie::Cookie;
 use futures_util::sink::SinkExt;
+use futures_util::stream::SplitSink;
+use futures_util::stream::SplitStream;
 use futures_util::stream::StreamExt;
+use http::HeaderName;
+use http::HeaderValue;
 use hyper::upgrade::Upgraded;
 use hyper_util::rt::TokioIo;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::fmt::Debug;
 use std::fmt::Display;
+use std::sync::Arc;
+use std::sync::Mutex;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::Data as OpData;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::OpCode;
+use tokio_tungstenite::tungstenite::protocol::frame::Frame;
 use tokio_tungstenite::tungstenite::protocol::Role;
+use tokio_tungstenite::tungstenite::Error as WsError;
 use tokio_tungstenite::WebSocketStream;
 
+use crate::internals::InFlightHandle;
+use crate::ServerSharedState;
 use crate::WsMessage;
 
 #[cfg(feature = "pretty-assertions")]
 use pretty_assertions::assert_eq;
 
 pub struct TestWebSocket {
-    stream: WebSocketStream<TokioIo<Upgraded>>,
+    sender: TestWebSocketSender,
+    receiver: TestWebSocketReceiver,
+    server_state: Arc<Mutex<ServerSharedState>>,
 }
 
 impl TestWebSocket {
-    pub(crate) async fn new(upgraded: Upgraded) -> Self {
+    pub(crate) async fn new(
+        upgraded: Upgraded,
+        server_state: Arc<Mutex<ServerSharedState>>,
+        in_flight: InFlightHandle,
+    ) -> Self {
         let upgraded_io = TokioIo::new(upgraded);
         let stream = WebSocketStream::from_raw_socket(upgraded_io, Role::Client, None).await;
+        let (sink, stream) = stream.split();
+
+        // Shared between both halves, so it is open until they are both dropped.
+        let in_flight = Arc::new(in_flight);
+
+        Self {
+            sender: TestWebSocketSender {
+                sink,
+                _in_flight: in_flight.clone(),
+            },
+            receiver: TestWebSocketReceiver {
+                stream,
+                _in_flight: in_flight,
+            },
+            server_state,
+        }
+    }
+
+    /// Splits this into a sender and a receiver, which can be used independently.
+    ///
+    /// Both halves are `Send` and `'static`, so one can be moved into a spawned task.
+    /// This allows a test to receive messages whilst sending, rather than alternating between them.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// # use axum::Router;
+    /// use axum_test::TestServer;
+    ///
+    /// # let app = Router::new();
+    /// let server = TestServer::builder()
+    ///     .http_transport()
+    ///     .build(app)?;
+    ///
+    /// let websocket = server.get_websocket(&"/ws-echo")
+    ///     .await
+    ///     .into_websocket()
+    ///     .await;
+    /// let (mut sender, mut receiver) = websocket.split();
+    ///
+    /// let receiving = tokio::spawn(async move {
+    ///     receiver.assert_receive_text("one").await;
+    ///     receiver.assert_receive_text("two").await;
+    /// });
+    ///
+    /// sender.send_text("one").await;
+    /// sender.send_text("two").await;
+    /// receiving.await?;
+    /// #
+    /// # Ok(()) }
+    /// ```
+    #[must_use]
+    pub fn split(self) -> (TestWebSocketSender, TestWebSocketReceiver) {
+        (self.sender, self.receiver)
+    }
+
+    /// Adds a header to be sent on *all* future requests from the `TestServer` this came from,
+    /// including later WebSocket upgrades.
+    ///
+    /// This is for using credentials received over the WebSocket, such as a token,
+    /// with the rest of the test.
+    ///
+    /// ```rust
+    /// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+    /// #
+    /// # use axum::Router;
+    /// use axum_test::TestServer;
+    ///
+    /// # let app = Router::new();
+    /// let server = TestServer::builder()
+    ///     .http_transport()
+    ///     .build(app)?;
+    ///
+    /// let mut websocket = server.get_websocket(&"/ws-login")
+    ///     .await
+    ///     .into_websocket()
+    ///     .await;
+    ///
+    /// let token = websocket.receive_text().await;
+    /// websocket.add_server_header("authorization", format!("Bearer {token}"));
+    ///
+    /// server.get(&"/profile").await.assert_status_ok();
+    /// #
+    /// # Ok(()) }
+    /// ```
+    pub fn add_server_header<N, V>(&self, name: N, value: V)
+    where
+        N: TryInto<HeaderName>,
+        N::Error: Debug,
+        V: TryInto<HeaderValue>,
+        V::Error: Debug,
+    {
+        let header_name: HeaderName = name
+            .try_into()
+            .expect("Failed to convert header name to HeaderName");
+        let header_value: HeaderValue = value
+            .try_into()
+            .expect("Failed to convert header value to HeaderValue");
+
+        ServerSharedState::add_header(&self.server_state, header_name, header_value)
+            .context("Trying to call add_server_header")
+            .unwrap()
+    }
+
+    /// Adds a cookie to be sent on *all* future requests from the `TestServer` this came from,
+    /// including later WebSocket upgrades.
+    ///
+    /// If a cookie with the same name already exists, then it will be replaced.
+    pub fn add_server_cookie(&self, cookie: Cookie) {
+        ServerSharedState::add_cookie(&self.server_state, cookie)
+            .context("Trying to call add_server_cookie")
+            .unwrap()
+    }
+
+    pub async fn close(self) {
+        self.sender.close().await
+    }
+
+    pub async fn send_text<T>(&mut self, raw_text: T)
+    where
+        T: Display,
+    {
+        self.sender.send_text(raw_text).await
+    }
+
+    pub async fn send_json<J>(&mut self, body: &J)
+    where
+        J: ?Sized + Serialize,
+    {
+        self.sender.send_json(body).await
+    }
+
+    #[cfg(feature = "yaml")]
+    pub async fn send_yaml<Y>(&mut self, body: &Y)
+    where
+        Y: ?Sized + Serialize,
+    {
+        self.sender.send_yaml(body).await
+    }
+
+    #[cfg(feature = "msgpack")]
+    pub async fn send_msgpack<M>(&mut self, body: &M)
+    where
+        M: ?Sized + Serialize,
+    {
+        self.sender.send_msgpack(body).await
+    }
+
+    pub async fn send_message(&mut self, message: WsMessage) {
+        self.sender.send_message(message).await
+    }
+
+    /// Sends the text as one message, split across frames of `frame_size` bytes,
+    /// for testing how the server reassembles fragmented messages.
+    ///
+    /// Frames are split on byte boundaries, so a frame may end part way through a character,
+    /// as is allowed by the WebSocket protocol.
+    pub async fn send_text_fragmented<T>(&mut self, raw_text: T, frame_size: usize)
+    where
+        T: Display,
+    {
+        self.sender.send_text_fragmented(raw_text, frame_size).await
+    }
+
+    /// Sends the bytes as one binary message, split across frames of `frame_size` bytes.
+    pub async fn send_bytes_fragmented(&mut self, bytes: Bytes, frame_size: usize) {
+        self.sender.send_bytes_fragmented(bytes, frame_size).await
+    }
+
+    /// Sends a text message of the size given, in bytes, made up of the letter `X`.
+    ///
+    /// This is for testing the server rejects messages
+    /// above its `max_message_size` or `max_frame_size`,
+    /// such as with [`TestWebSocket::assert_receive_protocol_error()`].
+    pub async fn send_text_of_size(&mut self, num_bytes: usize) {
+        self.sender.send_text_of_size(num_bytes).await
+    }
+
+    #[must_use]
+    pub async fn receive_text(&mut self) -> String {
+        self.receiver.receive_text().await
+    }
+
+    #[must_use]
+    pub async fn receive_json<T>(&mut self) -> T
+    where
+        T: DeserializeOwned,
+    {
+        self.receiver.receive_json().await
+    }
+
+    #[cfg(feature = "yaml")]
+    #[must_use]
+    pub async fn receive_yaml<T>(&mut self) -> T
+    where
+        T: DeserializeOwned,
+    {
+        self.receiver.receive_yaml().await
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[must_use]
+    pub async fn receive_msgpack<T>(&mut self) -> T
+    where
+        T: DeserializeOwned,
+    {
+        self.receiver.receive_msgpack().await
+    }
+
+    #[must_use]
+    pub async fn receive_bytes(&mut self) -> Bytes {
+        self.receiver.receive_bytes().await
+    }
+
+    #[must_use]
+    pub async fn receive_message(&mut self) -> WsMessage {
+        self.receiver.receive_message().await
+    }
+
+    pub async fn assert_receive_json<T>(&mut self, expected: &T)
+    where
+        T: DeserializeOwned + PartialEq<T> + Debug,
+    {
+        self.receiver.assert_receive_json(expected).await
+    }
+
+    pub async fn assert_receive_text<C>(&mut self, expected: C)
+    where
+        C: AsRef<str>,
+    {
+        self.receiver.assert_receive_text(expected).await
+    }
+
+    pub async fn assert_receive_text_contains<C>(&mut self, expected: C)
+    where
+        C: AsRef<str>,
+    {
+        self.receiver.assert_receive_text_contains(expected).await
+    }
+
+    #[cfg(feature = "yaml")]
+    pub async fn assert_receive_yaml<T>(&mut self, expected: &T)
+    where
+        T: DeserializeOwned + PartialEq<T> + Debug,
+    {
+        self.receiver.assert_receive_yaml(expected).await
+    }
+
+    #[cfg(feature = "msgpack")]
+    pub async fn assert_receive_msgpack<T>(&mut self, expected: &T)
+    where
+        T: DeserializeOwned + PartialEq<T> + Debug,
+    {
+        self.receiver.assert_receive_msgpack(expected).await
+    }
 
-        Self { stream }
+    /// Asserts the next message is a close frame with the status code given,
+    /// such as `1009` when a message is too big.
+    pub async fn assert_receive_close(&mut self, expected_code: u16) {
+        self.receiver.assert_receive_close(expected_code).await
     }
 
+    /// Asserts the server ended the connection because of a protocol error,
+    /// such as when receiving a message above its maximum size.
+    ///
+    /// This passes if a close frame is received with the code `1002` (protocol error)
+    /// or `1009` (message too big), or if the next read fails with a protocol error,
+    /// such as the connection ending without a closing handshake.
+    pub async fn assert_receive_protocol_error(&mut self) {
+        self.receiver.assert_receive_protocol_error().await
+    }
+
+    /// Asserts the server ended the connection with a close frame holding the code given,
+    /// such as `1009` when a message is too big.
+    ///
+    /// Unlike [`TestWebSocket::assert_receive_protocol_error()`],
+    /// a read failing without a close frame does not pass.
+    pub async fn assert_receive_protocol_error_with_code(&mut self, expected_code: u16) {
+        self.receiver
+            .assert_receive_protocol_error_with_code(expected_code)
+            .await
+    }
+}
+
+/// The sending half of a [`TestWebSocket`], created by calling [`TestWebSocket::split()`].
+pub struct TestWebSocketSender {
+    sink: SplitSink<WebSocketStream<TokioIo<Upgraded>>, WsMessage>,
+    _in_flight: Arc<InFlightHandle>,
+}
+
+impl TestWebSocketSender {
     pub async fn close(mut self) {
-        self.stream
-            .close(None)
+        self.sink
+            .send(WsMessage::Close(None))
             .await
             .expect("Failed to close WebSocket stream");
     }
@@ -78,9 +385,58 @@ impl TestWebSocket {
     }
 
     pub async fn send_message(&mut self, message: WsMessage) {
-        self.stream.send(message).await.unwrap();
+        self.sink.send(message).await.unwrap();
+    }
+
+    /// See [`TestWebSocket::send_text_fragmented()`].
+    pub async fn send_text_fragmented<T>(&mut self, raw_text: T, frame_size: usize)
+    where
+        T: Display,
+    {
+        let text = format!("{}", raw_text);
+        self.send_fragmented(text.into_bytes(), OpData::Text, frame_size)
+            .await;
+    }
+
+    /// See [`TestWebSocket::send_bytes_fragmented()`].
+    pub async fn send_bytes_fragmented(&mut self, bytes: Bytes, frame_size: usize) {
+        self.send_fragmented(bytes.into(), OpData::Binary, frame_size)
+            .await;
+    }
+
+    /// See [`TestWebSocket::send_text_of_size()`].
+    pub async fn send_text_of_size(&mut self, num_bytes: usize) {
+        let text = "X".repeat(num_bytes);
+        self.send_message(WsMessage::Text(text)).await;
     }
 
+    async fn send_fragmented(&mut self, data: Vec<u8>, data_type: OpData, frame_size: usize) {
+        assert!(frame_size > 0, "Frame size must be greater than zero");
+
+        let num_frames = data.len().div_ceil(frame_size).max(1);
+        let mut chunks = data.chunks(frame_size);
+        for i in 0..num_frames {
+            let chunk = chunks.next().unwrap_or_default().to_vec();
+            let opcode = if i == 0 {
+                OpCode::Data(data_type)
+            } else {
+                OpCode::Data(OpData::Continue)
+            };
+            let is_final = i + 1 == num_frames;
+
+            self.send_message(WsMessage::Frame(Frame::message(chunk, opcode, is_final)))
+                .await;
+        }
+    }
+}
+
+/// The receiving half of a [`TestWebSocket`], created by calling [`TestWebSocket::split()`].
+pub struct TestWebSocketReceiver {
+    stream: SplitStream<WebSocketStream<TokioIo<Upgraded>>>,
+    _in_flight: Arc<InFlightHandle>,
+}
+
+impl TestWebSocketReceiver {
     #[must_use]
     pub async fn receive_text(&mut self) -> String {
         let message = self.receive_message().await;
@@ -186,6 +542,59 @@ impl TestWebSocket {
         assert_eq!(*expected, self.receive_msgpack::<T>().await);
     }
 
+    /// See [`TestWebSocket::assert_receive_close()`].
+    pub async fn assert_receive_close(&mut self, expected_code: u16) {
+        let message = self.receive_message().await;
+        let received_code = match &message {
+            WsMessage::Close(Some(frame)) => u16::from(frame.code),
+            WsMessage::Close(None) => u16::from(CloseCode::Status),
+            _ => panic!("Expected close frame with code {expected_code}, received {message:?}"),
+        };
+
+        assert_eq!(
+            expected_code, received_code,
+            "Expected close frame with code {expected_code}, received {received_code}"
+        );
+    }
+
+    /// See [`TestWebSocket::assert_receive_protocol_error()`].
+    pub async fn assert_receive_protocol_error(&mut self) {
+        let expected_codes = [u16::from(CloseCode::Protocol), u16::from(CloseCode::Size)];
+
+        match self.stream.next().await {
+            Some(Err(WsError::Protocol(_) | WsError::Capacity(_))) => {}
+            Some(Ok(WsMessage::Close(Some(frame))))
+                if expected_codes.contains(&u16::from(frame.code)) => {}
+            Some(Ok(message)) => {
+                panic!("Expected WebSocket protocol error, with close code 1002 or 1009, received {message:?}")
+            }
+            Some(Err(error)) => {
+                panic!("Expected WebSocket protocol error, received a different error, {error}")
+            }
+            None => panic!("Expected WebSocket protocol error, the connection ended without one"),
+        }
+    }
+
+    /// See [`TestWebSocket::assert_receive_protocol_error_with_code()`].
+    pub async fn assert_receive_protocol_error_with_code(&mut self, expected_code: u16) {
+        match self.stream.next().await {
+            Some(Ok(WsMessage::Close(Some(frame)))) => {
+                let received_code = u16::from(frame.code);
+                assert_eq!(
+                    expected_code, received_code,
+                    "Expected WebSocket protocol error with close code {expected_code}, received {received_code}"
+                );
+            }
+            Some(Ok(message)) => {
+                panic!("Expected WebSocket protocol error with close code {expected_code}, received {message:?}")
+            }
+            Some(Err(error)) => {
+                panic!("Expected WebSocket protocol error with close code {expected_code}, received error {error}")
+            }
+            None => panic!("Expected WebSocket protocol error with close code {expected_code}, the connection ended without one"),
+        }
+    }
+
     #[must_use]
     async fn maybe_receive_message(&mut self) -> Option<WsMessage> {
         let maybe_message = self.stream.next().await;
@@ -649,3 +1058,438 @@ mod test_assert_receive_msgpack {
             .await;
     }
 }
+
+#[cfg(test)]
+mod test_send_text_fragmented {
+    use crate::TestServer;
+
+    use axum::extract::ws::Message;
+    use axum::extract::ws::WebSocket;
+    use axum::extract::WebSocketUpgrade;
+    use axum::response::Response;
+    use axum::routing::get;
+    use axum::Router;
+
+    fn new_test_app() -> TestServer {
+        pub async fn route_get_websocket_echo(ws: WebSocketUpgrade) -> Response {
+            async fn handle_echo(mut socket: WebSocket) {
+                while let Some(Ok(message)) = socket.recv().await {
+                    let reply = match message {
+                        Message::Text(text) => Message::Text(format!("Text: {text}")),
+                        Message::Binary(data) => Message::Text(format!("Binary: {}", data.len())),
+                        _ => continue,
+                    };
+
+                    socket.send(reply).await.unwrap();
+                }
+            }
+
+            ws.max_frame_size(16)
+                .max_message_size(64)
+                .on_upgrade(handle_echo)
+        }
+
+        let app = Router::new().route("/ws-echo", get(route_get_websocket_echo));
+        TestServer::builder().http_transport().build(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_send_text_over_multiple_frames() {
+        let server = new_test_app();
+        let mut websocket = server
+            .get_websocket("/ws-echo")
+            .await
+            .into_websocket()
+            .await;
+
+        // Larger than the max frame size, so would be rejected as one frame.
+        websocket
+            .send_text_fragmented("Hello fragmented World! 🦀", 10)
+            .await;
+
+        websocket
+            .assert_receive_text("Text: Hello fragmented World! 🦀")
+            .await;
+    }
+
+    #[tokio::test]
+    async fn it_should_send_bytes_over_multiple_frames() {
+        let server = new_test_app();
+        let mut websocket = server
+            .get_websocket("/ws-echo")
+            .await
+            .into_websocket()
+            .await;
+
+        websocket
+            .send_bytes_fragmented(vec![7; 50].into(), 16)
+            .await;
+
+        websocket.assert_receive_text("Binary: 50").await;
+    }
+
+    #[tokio::test]
+    async fn it_should_send_empty_text_as_one_frame() {
+        let server = new_test_app();
+        let mut websocket = server
+            .get_websocket("/ws-echo")
+            .await
+            .into_websocket()
+            .await;
+
+        websocket.send_text_fragmented("", 10).await;
+
+        websocket.assert_receive_text("Text: ").await;
+    }
+
+    #[tokio::test]
+    async fn it_should_receive_protocol_error_for_oversized_messages() {
+        let server = new_test_app();
+        let mut websocket = server
+            .get_websocket("/ws-echo")
+            .await
+            .into_websocket()
+            .await;
+
+        websocket.send_text_of_size(100).await;
+
+        websocket.assert_receive_protocol_error().await;
+    }
+
+    #[tokio::test]
+    async fn it_should_receive_protocol_error_for_oversized_fragmented_messages() {
+        let server = new_test_app();
+        let mut websocket = server
+            .get_websocket("/ws-echo")
+            .await
+            .into_websocket()
+            .await;
+
+        websocket.send_text_fragmented("X".repeat(100), 16).await;
+
+        websocket.assert_receive_protocol_error().await;
+    }
+}
+
+#[cfg(test)]
+mod test_assert_receive_close {
+    use crate::TestServer;
+
+    use axum::extract::ws::CloseFrame;
+    use axum::extract::ws::Message;
+    use axum::extract::ws::WebSocket;
+    use axum::extract::WebSocketUpgrade;
+    use axum::response::Response;
+    use axum::routing::get;
+    use axum::Router;
+
+    fn new_test_app() -> TestServer {
+        async fn handle_close(mut socket: WebSocket, code: u16) {
+            if let Some(Ok(_)) = socket.recv().await {
+                let close_frame = CloseFrame {
+                    code,
+                    reason: "closing".into(),
+                };
+                socket
+                    .send(Message::Close(Some(close_frame)))
+                    .await
+                    .unwrap();
+            }
+        }
+
+        pub async fn route_get_websocket_close(ws: WebSocketUpgrade) -> Response {
+            ws.on_upgrade(|socket| handle_close(socket, 1009))
+        }
+
+        pub async fn route_get_websocket_close_normal(ws: WebSocketUpgrade) -> Response {
+            ws.on_upgrade(|socket| handle_close(socket, 1000))
+        }
+
+        let app = Router::new()
+            .route("/ws-close", get(route_get_websocket_close))
+            .route("/ws-close-normal", get(route_get_websocket_close_normal));
+        TestServer::builder().http_transport().build(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_assert_close_code() {
+        let server = new_test_app();
+        let mut websocket = server
+            .get_websocket("/ws-close")
+            .await
+            .into_websocket()
+            .await;
+
+        websocket.send_text("hello").await;
+
+        websocket.assert_receive_close(1009).await;
+    }
+
+    #[tokio::test]
+    async fn it_should_treat_message_too_big_close_as_protocol_error() {
+        let server = new_test_app();
+        let mut websocket = server
+            .get_websocket("/ws-close")
+            .await
+            .into_websocket()
+            .await;
+
+        websocket.send_text("hello").await;
+
+        websocket.assert_receive_protocol_error().await;
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "Expected WebSocket protocol error, with close code 1002 or 1009")]
+    async fn it_should_not_treat_normal_close_as_protocol_error() {
+        let server = new_test_app();
+        let mut websocket = server
+            .get_websocket("/ws-close-normal")
+            .await
+            .into_websocket()
+            .await;
+
+        websocket.send_text("hello").await;
+
+        websocket.assert_receive_protocol_error().await;
+    }
+
+    #[tokio::test]
+    async fn it_should_assert_protocol_error_with_code() {
+        let server = new_test_app();
+        let mut websocket = server
+            .get_websocket("/ws-close")
+            .await
+            .into_websocket()
+            .await;
+
+        websocket.send_text("hello").await;
+
+        websocket
+            .assert_receive_protocol_error_with_code(1009)
+            .await;
+    }
+
+    #[tokio::test]
+    #[should_panic(
+        expected = "Expected WebSocket protocol error with close code 1002, received 1009"
+    )]
+    async fn it_should_panic_on_protocol_error_with_different_code() {
+        let server = new_test_app();
+        let mut websocket = server
+            .get_websocket("/ws-close")
+            .await
+            .into_websocket()
+            .await;
+
+        websocket.send_text("hello").await;
+
+        websocket
+            .assert_receive_protocol_error_with_code(1002)
+            .await;
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "Expected close frame with code 1000, received 1009")]
+    async fn it_should_panic_on_different_close_code() {
+        let server = new_test_app();
+        let mut websocket = server
+            .get_websocket("/ws-close")
+            .await
+            .into_websocket()
+            .await;
+
+        websocket.send_text("hello").await;
+
+        websocket.assert_receive_close(1000).await;
+    }
+}
+
+#[cfg(test)]
+mod test_split {
+    use crate::TestServer;
+
+    use axum::extract::ws::Message;
+    use axum::extract::ws::WebSocket;
+    use axum::extract::WebSocketUpgrade;
+    use axum::response::Response;
+    use axum::routing::get;
+    use axum::Router;
+    use serde_json::json;
+
+    fn new_test_app() -> TestServer {
+        pub async fn route_get_websocket_echo(ws: WebSocketUpgrade) -> Response {
+            async fn handle_echo(mut socket: WebSocket) {
+                while let Some(Ok(message)) = socket.recv().await {
+                    if matches!(message, Message::Close(_)) {
+                        break;
+                    }
+
+                    socket.send(message).await.unwrap();
+                }
+            }
+
+            ws.on_upgrade(handle_echo)
+        }
+
+        let app = Router::new().route("/ws-echo", get(route_get_websocket_echo));
+        TestServer::builder().http_transport().build(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_receive_on_a_spawned_task_whilst_sending() {
+        let server = new_test_app();
+        let websocket = server
+            .get_websocket("/ws-echo")
+            .await
+            .into_websocket()
+            .await;
+        let (mut sender, mut receiver) = websocket.split();
+
+        let receiving = tokio::spawn(async move {
+            for i in 0..10 {
+                receiver.assert_receive_text(format!("message {i}")).await;
+            }
+        });
+
+        for i in 0..10 {
+            sender.send_text(format!("message {i}")).await;
+        }
+
+        receiving.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_should_send_from_a_spawned_task() {
+        let server = new_test_app();
+        let websocket = server
+            .get_websocket("/ws-echo")
+            .await
+            .into_websocket()
+            .await;
+        let (mut sender, mut receiver) = websocket.split();
+
+        let sending = tokio::spawn(async move {
+            sender.send_json(&json!({ "name": "Joe" })).await;
+            sender
+        });
+
+        receiver
+            .assert_receive_json(&json!({ "name": "Joe" }))
+            .await;
+        sending.await.unwrap().close().await;
+    }
+}
+
+#[cfg(test)]
+mod test_add_server_header {
+    use crate::TestServer;
+
+    use axum::extract::ws::Message;
+    use axum::extract::ws::WebSocket;
+    use axum::extract::WebSocketUpgrade;
+    use axum::http::HeaderMap;
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+    use axum::response::Response;
+    use axum::routing::get;
+    use axum::routing::post;
+    use axum::Router;
+    use axum_extra::extract::cookie::Cookie as AxumCookie;
+    use axum_extra::extract::cookie::CookieJar as AxumCookieJar;
+    use cookie::Cookie;
+
+    fn new_test_app() -> TestServer {
+        async fn route_post_login(jar: AxumCookieJar) -> AxumCookieJar {
+            jar.add(AxumCookie::new("session", "alice"))
+        }
+
+        async fn route_get_websocket_login(ws: WebSocketUpgrade, jar: AxumCookieJar) -> Response {
+            let session = jar
+                .get("session")
+                .map(|cookie| cookie.value().to_string())
+                .unwrap_or_else(|| "anonymous".to_string());
+
+            ws.on_upgrade(|mut socket: WebSocket| async move {
+                let token = format!("token-for-{session}");
+                socket.send(Message::Text(token)).await.unwrap();
+            })
+        }
+
+        async fn route_get_profile(headers: HeaderMap, jar: AxumCookieJar) -> Response {
+            let token = headers
+                .get("authorization")
+                .and_then(|value| value.to_str().ok())
+                .map(ToString::to_string)
+                .or_else(|| jar.get("token").map(|cookie| cookie.value().to_string()));
+
+            match token {
+                Some(token) => token.into_response(),
+                None => StatusCode::UNAUTHORIZED.into_response(),
+            }
+        }
+
+        let app = Router::new()
+            .route("/login", post(route_post_login))
+            .route("/ws-login", get(route_get_websocket_login))
+            .route("/profile", get(route_get_profile));
+
+        TestServer::builder()
+            .http_transport()
+            .save_cookies()
+            .build(app)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_send_cookies_from_http_login_on_websocket_upgrade() {
+        let server = new_test_app();
+
+        server.post("/login").await;
+        let mut websocket = server
+            .get_websocket("/ws-login")
+            .await
+            .into_websocket()
+            .await;
+
+        websocket.assert_receive_text("token-for-alice").await;
+    }
+
+    #[tokio::test]
+    async fn it_should_send_header_added_from_websocket_on_http_requests() {
+        let server = new_test_app();
+
+        server.get("/profile").await.assert_status_unauthorized();
+
+        let mut websocket = server
+            .get_websocket("/ws-login")
+            .await
+            .into_websocket()
+            .await;
+        let token = websocket.receive_text().await;
+        websocket.add_server_header("authorization", token);
+
+        server
+            .get("/profile")
+            .await
+            .assert_text("token-for-anonymous");
+    }
+
+    #[tokio::test]
+    async fn it_should_send_cookie_added_from_websocket_on_http_requests() {
+        let server = new_test_app();
+
+        let mut websocket = server
+            .get_websocket("/ws-login")
+            .await
+            .into_websocket()
+            .await;
+        let token = websocket.receive_text().await;
+        websocket.add_server_cookie(Cookie::new("token", token));
+
+        server
+            .get("/profile")
+            .await
+            .assert_text("token-for-anonymous");
+    }
+}
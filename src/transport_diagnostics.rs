@@ -0,0 +1,52 @@
+/// A snapshot of the health of the transport behind a [`TestServer`](crate::TestServer).
+///
+/// This is retrieved using [`TestServer::transport_diagnostics()`](crate::TestServer::transport_diagnostics()),
+/// and is useful for tracking down connections, or tasks, which outlive the requests that made them.
+///
+/// Connections are only tracked when the `TestServer` starts the service itself,
+/// which is when it is built from a [`Router`](::axum::Router), or a make service, using a HTTP transport.
+/// Everything is zero for the mock transport.
+///
+/// ```rust
+/// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+/// #
+/// use axum::Router;
+/// use axum::routing::get;
+/// use axum_test::TestServer;
+///
+/// let app = Router::new()
+///     .route("/ping", get(|| async { "pong!" }));
+///
+/// let server = TestServer::builder()
+///     .http_transport()
+///     .build(app)?;
+///
+/// server.get("/ping").await;
+///
+/// let diagnostics = server.transport_diagnostics();
+/// assert_eq!(diagnostics.active_requests, 0);
+/// assert!(!diagnostics.has_serve_loop_panicked);
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TransportDiagnostics {
+    /// The number of connections accepted by the server, which are still open.
+    ///
+    /// Connections are closed in the background after their last response,
+    /// so this can briefly include connections which have finished.
+    pub open_connections: usize,
+
+    /// The number of requests still being served,
+    /// from when they reach the server until their response body has been sent.
+    pub active_requests: usize,
+
+    /// The number of tasks spawned for the server which are still running.
+    ///
+    /// This is the loop accepting connections, and a task for each open connection.
+    pub spawned_tasks: usize,
+
+    /// True if the loop accepting connections has panicked.
+    pub has_serve_loop_panicked: bool,
+}
@@ -1,20 +1,21 @@
-use anyhow::Result;
-
 use crate::transport_layer::TransportLayer;
 use crate::transport_layer::TransportLayerBuilder;
+use crate::Error;
+use crate::RouteInfo;
 
 // mod into_make_service_tower;
 
 mod into_make_service;
 mod into_make_service_with_connect_info;
+mod recording_router;
+mod result;
 mod router;
+mod router_factory;
 mod serve;
 mod with_graceful_shutdown;
 
 #[cfg(feature = "shuttle")]
 mod axum_service;
-#[cfg(feature = "shuttle")]
-mod shuttle_axum;
 
 ///
 /// This exists to unify how to send mock or real messages to different services.
@@ -30,14 +31,23 @@ pub trait IntoTransportLayer: Sized {
     fn into_http_transport_layer(
         self,
         builder: TransportLayerBuilder,
-    ) -> Result<Box<dyn TransportLayer>>;
+    ) -> Result<Box<dyn TransportLayer>, Error>;
 
-    fn into_mock_transport_layer(self) -> Result<Box<dyn TransportLayer>>;
+    fn into_mock_transport_layer(self) -> Result<Box<dyn TransportLayer>, Error>;
 
     fn into_default_transport(
         self,
         _builder: TransportLayerBuilder,
-    ) -> Result<Box<dyn TransportLayer>> {
+    ) -> Result<Box<dyn TransportLayer>, Error> {
         self.into_mock_transport_layer()
     }
+
+    /// Returns the routes recorded for the application, if it records them.
+    ///
+    /// This is used for [`TestServer::routes()`](crate::TestServer::routes()),
+    /// and returns `None` by default.
+    /// See [`RecordingRouter`](crate::RecordingRouter) for an application which records its routes.
+    fn routes(&self) -> Option<Vec<RouteInfo>> {
+        None
+    }
 }
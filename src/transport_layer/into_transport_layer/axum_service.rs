@@ -1,20 +1,20 @@
-use anyhow::Result;
 use axum::Router;
 use shuttle_axum::AxumService;
 
 use crate::transport_layer::IntoTransportLayer;
 use crate::transport_layer::TransportLayer;
 use crate::transport_layer::TransportLayerBuilder;
+use crate::Error;
 
 impl IntoTransportLayer for AxumService {
     fn into_http_transport_layer(
         self,
         builder: TransportLayerBuilder,
-    ) -> Result<Box<dyn TransportLayer>> {
+    ) -> Result<Box<dyn TransportLayer>, Error> {
         Router::into_http_transport_layer(self.0, builder)
     }
 
-    fn into_mock_transport_layer(self) -> Result<Box<dyn TransportLayer>> {
+    fn into_mock_transport_layer(self) -> Result<Box<dyn TransportLayer>, Error> {
         Router::into_mock_transport_layer(self.0)
     }
 }
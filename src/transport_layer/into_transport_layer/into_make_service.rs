@@ -1,17 +1,17 @@
-use anyhow::Result;
 use axum::extract::Request as AxumRequest;
 use axum::response::Response as AxumResponse;
 use axum::routing::IntoMakeService;
 use std::convert::Infallible;
 use tower::Service;
-use url::Url;
 
+use crate::internals::build_server_url;
 use crate::internals::HttpTransportLayer;
 use crate::internals::MockTransportLayer;
 use crate::transport_layer::IntoTransportLayer;
 use crate::transport_layer::TransportLayer;
 use crate::transport_layer::TransportLayerBuilder;
-use crate::util::spawn_serve;
+use crate::util::spawn_serve_with_tracker;
+use crate::Error;
 
 impl<S> IntoTransportLayer for IntoMakeService<S>
 where
@@ -25,13 +25,13 @@ where
     fn into_http_transport_layer(
         self,
         builder: TransportLayerBuilder,
-    ) -> Result<Box<dyn TransportLayer>> {
+    ) -> Result<Box<dyn TransportLayer>, Error> {
+        let serve_tracker = builder.serve_tracker();
         let (socket_addr, tcp_listener, maybe_reserved_port) =
             builder.tcp_listener_with_reserved_port()?;
 
-        let serve_handle = spawn_serve(tcp_listener, self);
-        let server_address = format!("http://{socket_addr}");
-        let server_url: Url = server_address.parse()?;
+        let serve_handle = spawn_serve_with_tracker(tcp_listener, self, serve_tracker);
+        let server_url = build_server_url(socket_addr)?;
 
         Ok(Box::new(HttpTransportLayer::new(
             serve_handle,
@@ -40,7 +40,7 @@ where
         )))
     }
 
-    fn into_mock_transport_layer(self) -> Result<Box<dyn TransportLayer>> {
+    fn into_mock_transport_layer(self) -> Result<Box<dyn TransportLayer>, Error> {
         let transport_layer = MockTransportLayer::new(self);
         Ok(Box::new(transport_layer))
     }
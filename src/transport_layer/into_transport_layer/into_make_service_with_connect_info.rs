@@ -1,18 +1,17 @@
-use anyhow::anyhow;
-use anyhow::Result;
 use axum::extract::connect_info::IntoMakeServiceWithConnectInfo;
 use axum::extract::Request as AxumRequest;
 use axum::response::Response as AxumResponse;
 use axum::serve::IncomingStream;
 use std::convert::Infallible;
 use tower::Service;
-use url::Url;
 
+use crate::internals::build_server_url;
 use crate::internals::HttpTransportLayer;
 use crate::transport_layer::IntoTransportLayer;
 use crate::transport_layer::TransportLayer;
 use crate::transport_layer::TransportLayerBuilder;
-use crate::util::spawn_serve;
+use crate::util::spawn_serve_with_tracker;
+use crate::Error;
 
 impl<S, C> IntoTransportLayer for IntoMakeServiceWithConnectInfo<S, C>
 where
@@ -23,13 +22,13 @@ where
     fn into_http_transport_layer(
         self,
         builder: TransportLayerBuilder,
-    ) -> Result<Box<dyn TransportLayer>> {
+    ) -> Result<Box<dyn TransportLayer>, Error> {
+        let serve_tracker = builder.serve_tracker();
         let (socket_addr, tcp_listener, maybe_reserved_port) =
             builder.tcp_listener_with_reserved_port()?;
 
-        let serve_handle = spawn_serve(tcp_listener, self);
-        let server_address = format!("http://{socket_addr}");
-        let server_url: Url = server_address.parse()?;
+        let serve_handle = spawn_serve_with_tracker(tcp_listener, self, serve_tracker);
+        let server_url = build_server_url(socket_addr)?;
 
         Ok(Box::new(HttpTransportLayer::new(
             serve_handle,
@@ -38,14 +37,16 @@ where
         )))
     }
 
-    fn into_mock_transport_layer(self) -> Result<Box<dyn TransportLayer>> {
-        Err(anyhow!("`IntoMakeServiceWithConnectInfo` cannot be mocked, as it's underlying implementation requires a real connection. Set the `TestServerConfig` to run with a transport of `HttpRandomPort`, or a `HttpIpPort`."))
+    fn into_mock_transport_layer(self) -> Result<Box<dyn TransportLayer>, Error> {
+        Err(Error::InvalidConfig {
+            message: "`IntoMakeServiceWithConnectInfo` cannot be mocked, as it's underlying implementation requires a real connection. Set the `TestServerConfig` to run with a transport of `HttpRandomPort`, or a `HttpIpPort`.".to_string(),
+        })
     }
 
     fn into_default_transport(
         self,
         builder: TransportLayerBuilder,
-    ) -> Result<Box<dyn TransportLayer>> {
+    ) -> Result<Box<dyn TransportLayer>, Error> {
         self.into_http_transport_layer(builder)
     }
 }
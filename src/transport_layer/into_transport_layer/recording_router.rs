@@ -0,0 +1,65 @@
+use crate::transport_layer::IntoTransportLayer;
+use crate::transport_layer::TransportLayer;
+use crate::transport_layer::TransportLayerBuilder;
+use crate::Error;
+use crate::RecordingRouter;
+use crate::RouteInfo;
+
+impl IntoTransportLayer for RecordingRouter<()> {
+    fn into_http_transport_layer(
+        self,
+        builder: TransportLayerBuilder,
+    ) -> Result<Box<dyn TransportLayer>, Error> {
+        self.into_router().into_http_transport_layer(builder)
+    }
+
+    fn into_mock_transport_layer(self) -> Result<Box<dyn TransportLayer>, Error> {
+        self.into_router().into_mock_transport_layer()
+    }
+
+    fn routes(&self) -> Option<Vec<RouteInfo>> {
+        Some(RecordingRouter::routes(self).to_vec())
+    }
+}
+
+#[cfg(test)]
+mod test_into_transport_layer_for_recording_router {
+    use http::Method;
+
+    use crate::RecordingRouter;
+    use crate::TestServer;
+
+    fn new_app() -> RecordingRouter {
+        RecordingRouter::new().route(Method::GET, "/ping", || async { "pong!" })
+    }
+
+    #[tokio::test]
+    async fn it_should_run_with_http_transport() {
+        let server = TestServer::builder()
+            .http_transport()
+            .build(new_app())
+            .unwrap();
+
+        server.get("/ping").await.assert_text("pong!");
+        assert_eq!(server.routes().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn it_should_run_with_mock_transport() {
+        let server = TestServer::builder()
+            .mock_transport()
+            .build(new_app())
+            .unwrap();
+
+        server.get("/ping").await.assert_text("pong!");
+        assert_eq!(server.routes().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn it_should_return_routes_within_ok_results() {
+        let app: anyhow::Result<RecordingRouter> = Ok(new_app());
+        let server = TestServer::new(app).unwrap();
+
+        assert_eq!(server.routes().len(), 1);
+    }
+}
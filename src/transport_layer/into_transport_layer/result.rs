@@ -0,0 +1,167 @@
+use anyhow::Error as AnyhowError;
+
+use crate::transport_layer::IntoTransportLayer;
+use crate::transport_layer::TransportLayer;
+use crate::transport_layer::TransportLayerBuilder;
+use crate::Error;
+use crate::RouteInfo;
+
+/// Allows an application which failed to build to be passed in,
+/// such as from a constructor returning a `Result`.
+///
+/// The error is returned when building the `TestServer`,
+/// instead of needing to be unwrapped first.
+/// This includes [`shuttle_axum::ShuttleAxum`] applications.
+impl<A, E> IntoTransportLayer for Result<A, E>
+where
+    A: IntoTransportLayer,
+    E: Into<AnyhowError>,
+{
+    fn into_http_transport_layer(
+        self,
+        builder: TransportLayerBuilder,
+    ) -> Result<Box<dyn TransportLayer>, Error> {
+        into_app(self)?.into_http_transport_layer(builder)
+    }
+
+    fn into_mock_transport_layer(self) -> Result<Box<dyn TransportLayer>, Error> {
+        into_app(self)?.into_mock_transport_layer()
+    }
+
+    fn into_default_transport(
+        self,
+        builder: TransportLayerBuilder,
+    ) -> Result<Box<dyn TransportLayer>, Error> {
+        into_app(self)?.into_default_transport(builder)
+    }
+
+    fn routes(&self) -> Option<Vec<RouteInfo>> {
+        self.as_ref().ok().and_then(IntoTransportLayer::routes)
+    }
+}
+
+fn into_app<A, E>(result: Result<A, E>) -> Result<A, Error>
+where
+    E: Into<AnyhowError>,
+{
+    result.map_err(|error| {
+        Error::Other(
+            error
+                .into()
+                .context("Failed to build the application given to the TestServer"),
+        )
+    })
+}
+
+#[cfg(test)]
+mod test_into_transport_layer_for_result {
+    use anyhow::anyhow;
+    use axum::routing::get;
+    use axum::Router;
+    use std::io::Error as IoError;
+    use std::io::ErrorKind;
+
+    use crate::Error;
+    use crate::TestServer;
+
+    fn new_app() -> Router {
+        Router::new().route("/ping", get(|| async { "pong!" }))
+    }
+
+    #[tokio::test]
+    async fn it_should_run_ok_apps_with_mock_transport() {
+        let app: Result<Router, IoError> = Ok(new_app());
+
+        let server = TestServer::builder().mock_transport().build(app).unwrap();
+
+        server.get("/ping").await.assert_text("pong!");
+    }
+
+    #[tokio::test]
+    async fn it_should_run_ok_apps_with_http_transport() {
+        let app: anyhow::Result<Router> = Ok(new_app());
+
+        let server = TestServer::builder().http_transport().build(app).unwrap();
+
+        server.get("/ping").await.assert_text("pong!");
+    }
+
+    #[tokio::test]
+    async fn it_should_return_the_error_from_err_apps() {
+        let app: anyhow::Result<Router> = Err(anyhow!("database is unavailable"));
+
+        let error = TestServer::new(app).unwrap_err();
+
+        assert!(matches!(error, Error::Other(_)));
+        let message = format!("{:#}", anyhow::Error::from(error));
+        assert!(message.contains("Failed to build the application given to the TestServer"));
+        assert!(message.contains("database is unavailable"));
+    }
+
+    #[tokio::test]
+    async fn it_should_return_the_error_with_http_transport() {
+        let app: Result<Router, IoError> = Err(IoError::new(ErrorKind::NotFound, "config missing"));
+
+        let error = TestServer::builder()
+            .http_transport()
+            .build(app)
+            .unwrap_err();
+
+        assert!(format!("{:#}", anyhow::Error::from(error)).contains("config missing"));
+    }
+}
+
+#[cfg(feature = "shuttle")]
+#[cfg(test)]
+mod test_into_transport_layer_for_shuttle_axum {
+    use anyhow::anyhow;
+    use axum::extract::State;
+    use axum::routing::get;
+    use axum::Router;
+    use shuttle_axum::AxumService;
+    use shuttle_axum::ShuttleAxum;
+    use shuttle_runtime::Error as ShuttleError;
+
+    use crate::TestServer;
+
+    async fn get_state(State(count): State<u32>) -> String {
+        format!("count is {}", count)
+    }
+
+    fn new_app() -> ShuttleAxum {
+        let router = Router::new()
+            .route("/count", get(get_state))
+            .with_state(123);
+
+        Ok(AxumService::from(router))
+    }
+
+    #[tokio::test]
+    async fn it_should_run_with_http_transport() {
+        let server = TestServer::builder()
+            .http_transport()
+            .build(new_app())
+            .expect("Should create test server");
+
+        server.get("/count").await.assert_text("count is 123");
+    }
+
+    #[tokio::test]
+    async fn it_should_run_with_mock_transport() {
+        let server = TestServer::builder()
+            .mock_transport()
+            .build(new_app())
+            .expect("Should create test server");
+
+        server.get("/count").await.assert_text("count is 123");
+    }
+
+    #[tokio::test]
+    async fn it_should_return_the_shuttle_error() {
+        let app: ShuttleAxum = Err(ShuttleError::Custom(anyhow!("shuttle failed to start")));
+
+        let error = TestServer::new(app).unwrap_err();
+
+        assert!(format!("{:#}", anyhow::Error::from(error)).contains("shuttle failed to start"));
+    }
+}
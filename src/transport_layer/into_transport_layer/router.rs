@@ -1,19 +1,19 @@
-use anyhow::Result;
 use axum::Router;
 
 use crate::transport_layer::IntoTransportLayer;
 use crate::transport_layer::TransportLayer;
 use crate::transport_layer::TransportLayerBuilder;
+use crate::Error;
 
 impl IntoTransportLayer for Router<()> {
     fn into_http_transport_layer(
         self,
         builder: TransportLayerBuilder,
-    ) -> Result<Box<dyn TransportLayer>> {
+    ) -> Result<Box<dyn TransportLayer>, Error> {
         self.into_make_service().into_http_transport_layer(builder)
     }
 
-    fn into_mock_transport_layer(self) -> Result<Box<dyn TransportLayer>> {
+    fn into_mock_transport_layer(self) -> Result<Box<dyn TransportLayer>, Error> {
         self.into_make_service().into_mock_transport_layer()
     }
 }
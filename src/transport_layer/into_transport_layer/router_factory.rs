@@ -0,0 +1,169 @@
+use axum::body::Body;
+use axum::response::Response as AxumResponse;
+use axum::Router;
+use http::Request;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::Context;
+use std::task::Poll;
+use tower::make::Shared;
+use tower::util::Oneshot;
+use tower::Service;
+use tower::ServiceExt;
+
+use crate::internals::build_server_url;
+use crate::internals::HttpTransportLayer;
+use crate::internals::MockTransportLayer;
+use crate::transport_layer::IntoTransportLayer;
+use crate::transport_layer::TransportLayer;
+use crate::transport_layer::TransportLayerBuilder;
+use crate::util::spawn_serve_with_tracker;
+use crate::Error;
+
+/// Allows a closure returning a [`Router`] to be used as the application.
+///
+/// The closure is called for every request, so each one is served by a freshly built application.
+/// This is for catching state accidentally shared between requests,
+/// such as state captured when the application is constructed.
+impl<F> IntoTransportLayer for F
+where
+    F: FnMut() -> Router + Send + 'static,
+{
+    fn into_http_transport_layer(
+        self,
+        builder: TransportLayerBuilder,
+    ) -> Result<Box<dyn TransportLayer>, Error> {
+        let serve_tracker = builder.serve_tracker();
+        let (socket_addr, tcp_listener, maybe_reserved_port) =
+            builder.tcp_listener_with_reserved_port()?;
+
+        let make_service = Shared::new(RouterPerRequest::new(self));
+        let serve_handle = spawn_serve_with_tracker(tcp_listener, make_service, serve_tracker);
+        let server_url = build_server_url(socket_addr)?;
+
+        Ok(Box::new(HttpTransportLayer::new(
+            serve_handle,
+            maybe_reserved_port,
+            server_url,
+        )))
+    }
+
+    fn into_mock_transport_layer(self) -> Result<Box<dyn TransportLayer>, Error> {
+        let make_service = Shared::new(RouterPerRequest::new(self));
+        let transport_layer = MockTransportLayer::new(make_service);
+        Ok(Box::new(transport_layer))
+    }
+}
+
+/// A service which builds a new [`Router`] for each request it receives.
+struct RouterPerRequest<F> {
+    router_factory: Arc<Mutex<F>>,
+}
+
+impl<F> RouterPerRequest<F> {
+    fn new(router_factory: F) -> Self {
+        Self {
+            router_factory: Arc::new(Mutex::new(router_factory)),
+        }
+    }
+}
+
+impl<F> Clone for RouterPerRequest<F> {
+    fn clone(&self) -> Self {
+        Self {
+            router_factory: self.router_factory.clone(),
+        }
+    }
+}
+
+impl<F> Service<Request<Body>> for RouterPerRequest<F>
+where
+    F: FnMut() -> Router + Send + 'static,
+{
+    type Response = AxumResponse;
+    type Error = Infallible;
+    type Future = Oneshot<Router, Request<Body>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        let router = {
+            let mut router_factory = self
+                .router_factory
+                .lock()
+                .expect("Failed to lock router factory, it may have panicked");
+            (router_factory)()
+        };
+
+        router.oneshot(request)
+    }
+}
+
+#[cfg(test)]
+mod test_into_transport_layer_for_router_factory {
+    use axum::extract::State;
+    use axum::routing::get;
+    use axum::Router;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+
+    use crate::TestServer;
+
+    async fn get_count(State(count): State<Arc<AtomicUsize>>) -> String {
+        let count = count.fetch_add(1, Ordering::SeqCst) + 1;
+        format!("count is {count}")
+    }
+
+    fn new_app() -> Router {
+        Router::new()
+            .route("/count", get(get_count))
+            .with_state(Arc::new(AtomicUsize::new(0)))
+    }
+
+    #[tokio::test]
+    async fn it_should_build_a_new_app_for_each_request_with_mock_transport() {
+        let server = TestServer::builder()
+            .mock_transport()
+            .build(new_app)
+            .expect("Should create test server");
+
+        server.get("/count").await.assert_text("count is 1");
+        server.get("/count").await.assert_text("count is 1");
+    }
+
+    #[tokio::test]
+    async fn it_should_build_a_new_app_for_each_request_with_http_transport() {
+        let server = TestServer::builder()
+            .http_transport()
+            .build(new_app)
+            .expect("Should create test server");
+
+        server.get("/count").await.assert_text("count is 1");
+        server.get("/count").await.assert_text("count is 1");
+    }
+
+    #[tokio::test]
+    async fn it_should_call_the_closure_for_each_request() {
+        let mut num_apps_built = 0;
+        let counter = Arc::new(AtomicUsize::new(0));
+        let apps_built = counter.clone();
+
+        let server = TestServer::new(move || {
+            num_apps_built += 1;
+            apps_built.store(num_apps_built, Ordering::SeqCst);
+
+            new_app()
+        })
+        .expect("Should create test server");
+
+        server.get("/count").await;
+        server.get("/count").await;
+        server.get("/count").await;
+
+        assert_eq!(counter.load(Ordering::SeqCst), 3);
+    }
+}
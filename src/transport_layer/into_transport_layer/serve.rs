@@ -1,20 +1,21 @@
-use anyhow::anyhow;
 use anyhow::Context;
-use anyhow::Result;
 use axum::extract::Request;
 use axum::response::Response;
 use axum::serve::IncomingStream;
 use axum::serve::Serve;
 use std::convert::Infallible;
+use std::sync::Arc;
 use tokio::spawn;
 use tower::Service;
-use url::Url;
 
+use crate::internals::build_server_url;
 use crate::internals::HttpTransportLayer;
+use crate::internals::ServeTracker;
 use crate::transport_layer::IntoTransportLayer;
 use crate::transport_layer::TransportLayer;
 use crate::transport_layer::TransportLayerBuilder;
 use crate::util::ServeHandle;
+use crate::Error;
 
 impl<M, S> IntoTransportLayer for Serve<M, S>
 where
@@ -26,31 +27,39 @@ where
     fn into_http_transport_layer(
         self,
         _builder: TransportLayerBuilder,
-    ) -> Result<Box<dyn TransportLayer>> {
-        Err(anyhow!("`Serve` must be started with http or mock transport. Do not set any transport on `TestServerConfig`."))
+    ) -> Result<Box<dyn TransportLayer>, Error> {
+        Err(Error::InvalidConfig {
+            message: "`Serve` must be started with http or mock transport. Do not set any transport on `TestServerConfig`.".to_string(),
+        })
     }
 
-    fn into_mock_transport_layer(self) -> Result<Box<dyn TransportLayer>> {
-        Err(anyhow!("`Serve` cannot be mocked, as it's underlying implementation requires a real connection. Do not set any transport on `TestServerConfig`."))
+    fn into_mock_transport_layer(self) -> Result<Box<dyn TransportLayer>, Error> {
+        Err(Error::InvalidConfig {
+            message: "`Serve` cannot be mocked, as it's underlying implementation requires a real connection. Do not set any transport on `TestServerConfig`.".to_string(),
+        })
     }
 
     fn into_default_transport(
         self,
         _builder: TransportLayerBuilder,
-    ) -> Result<Box<dyn TransportLayer>> {
-        let socket_addr = self.local_addr()?;
+    ) -> Result<Box<dyn TransportLayer>, Error> {
+        let socket_addr = self
+            .local_addr()
+            .map_err(|error| Error::Other(error.into()))?;
 
+        let tracker = Arc::new(ServeTracker::default());
+        let serve_tracker = Arc::clone(&tracker);
         let join_handle = spawn(async move {
             self.await
                 .context("Failed to create ::axum::Server for TestServer")
                 .expect("Expect server to start serving");
+            serve_tracker.mark_stopped();
         });
 
-        let server_address = format!("http://{socket_addr}");
-        let server_url: Url = server_address.parse()?;
+        let server_url = build_server_url(socket_addr)?;
 
         Ok(Box::new(HttpTransportLayer::new(
-            ServeHandle::new(join_handle),
+            ServeHandle::new(join_handle, tracker),
             None,
             server_url,
         )))
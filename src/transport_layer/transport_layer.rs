@@ -1,4 +1,3 @@
-use anyhow::Result;
 use axum::body::Body;
 use http::Request;
 use http::Response;
@@ -8,12 +7,14 @@ use std::pin::Pin;
 use url::Url;
 
 use crate::transport_layer::TransportLayerType;
+use crate::Error;
+use crate::TransportDiagnostics;
 
 pub trait TransportLayer: Debug + Send + Sync + 'static {
     fn send<'a>(
         &'a self,
         request: Request<Body>,
-    ) -> Pin<Box<dyn 'a + Future<Output = Result<Response<Body>>>>>;
+    ) -> Pin<Box<dyn 'a + Future<Output = Result<Response<Body>, Error>>>>;
 
     fn url(&self) -> Option<&Url> {
         None
@@ -22,6 +23,13 @@ pub trait TransportLayer: Debug + Send + Sync + 'static {
     fn transport_layer_type(&self) -> TransportLayerType;
 
     fn is_running(&self) -> bool;
+
+    /// Returns diagnostics on the connections and tasks behind this transport.
+    ///
+    /// This is all zero by default.
+    fn transport_diagnostics(&self) -> TransportDiagnostics {
+        TransportDiagnostics::default()
+    }
 }
 
 #[cfg(test)]
@@ -1,27 +1,51 @@
-use anyhow::Context;
-use anyhow::Result;
 use reserve_port::ReservedPort;
 use std::net::IpAddr;
 use std::net::SocketAddr;
+use std::sync::Arc;
 use tokio::net::TcpListener;
 
+use crate::internals::ServeTracker;
 use crate::internals::StartingTcpSetup;
+use crate::Error;
 
 pub struct TransportLayerBuilder {
     ip: Option<IpAddr>,
     port: Option<u16>,
+    maybe_port_seed: Option<u64>,
+    is_catching_panics: bool,
 }
 
 impl TransportLayerBuilder {
     pub(crate) fn new(ip: Option<IpAddr>, port: Option<u16>) -> Self {
-        Self { ip, port }
+        Self {
+            ip,
+            port,
+            maybe_port_seed: None,
+            is_catching_panics: false,
+        }
+    }
+
+    /// Sets the seed used to pick a random port, when no port is given.
+    pub(crate) fn with_port_seed(mut self, maybe_port_seed: Option<u64>) -> Self {
+        self.maybe_port_seed = maybe_port_seed;
+        self
+    }
+
+    /// Sets for handlers which panic to return a `500` response, rather than dropping the connection.
+    pub(crate) fn with_catch_panics(mut self, is_catching_panics: bool) -> Self {
+        self.is_catching_panics = is_catching_panics;
+        self
+    }
+
+    /// Creates the tracker for the service being served, which catches panics if set.
+    pub(crate) fn serve_tracker(&self) -> Arc<ServeTracker> {
+        Arc::new(ServeTracker::new(self.is_catching_panics))
     }
 
     pub(crate) fn tcp_listener_with_reserved_port(
         self,
-    ) -> Result<(SocketAddr, TcpListener, Option<ReservedPort>)> {
-        let setup = StartingTcpSetup::new(self.ip, self.port)
-            .context("Cannot create socket address for use")?;
+    ) -> Result<(SocketAddr, TcpListener, Option<ReservedPort>), Error> {
+        let setup = StartingTcpSetup::new(self.ip, self.port, self.maybe_port_seed)?;
 
         let socket_addr = setup.socket_addr;
         let tcp_listener = setup.tcp_listener;
@@ -30,7 +54,7 @@ impl TransportLayerBuilder {
         Ok((socket_addr, tcp_listener, maybe_reserved_port))
     }
 
-    pub fn tcp_listener(self) -> Result<TcpListener> {
+    pub fn tcp_listener(self) -> Result<TcpListener, Error> {
         let (_, tcp_listener, _) = self.tcp_listener_with_reserved_port()?;
         Ok(tcp_listener)
     }
@@ -0,0 +1,632 @@
+use anyhow::anyhow;
+use anyhow::Context;
+use anyhow::Result;
+use bytes::Bytes;
+use http::header;
+use http::Method;
+use http::StatusCode;
+use sha1::Digest;
+use sha1::Sha1;
+use std::future::Future;
+use std::future::IntoFuture;
+use std::pin::Pin;
+
+use crate::internals::check;
+use crate::internals::check_eq;
+use crate::internals::encode_base64;
+use crate::internals::resolve_relative_url;
+use crate::internals::ExpectedState;
+use crate::internals::OrPanic;
+use crate::AssertionError;
+use crate::TestResponse;
+use crate::TestServer;
+
+const TUS_RESUMABLE_HEADER: &str = "tus-resumable";
+const TUS_VERSION: &str = "1.0.0";
+const UPLOAD_LENGTH_HEADER: &str = "upload-length";
+const UPLOAD_OFFSET_HEADER: &str = "upload-offset";
+const UPLOAD_METADATA_HEADER: &str = "upload-metadata";
+const UPLOAD_CHECKSUM_HEADER: &str = "upload-checksum";
+const OFFSET_OCTET_STREAM: &str = "application/offset+octet-stream";
+
+/// The status code tus servers return when an `Upload-Checksum` does not match the chunk.
+const CHECKSUM_MISMATCH: u16 = 460;
+
+const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// An upload using the [tus resumable upload protocol](https://tus.io/protocols/resumable-upload),
+/// created by calling [`TestServer::tus_upload()`](crate::TestServer::tus_upload()).
+///
+/// When awaited this will:
+///
+///  1. Create the upload with a `POST` to the path given, expecting a `201 Created` with a `Location`.
+///  2. Send the bytes in chunks, using a `PATCH` to the location for each chunk.
+///  3. Verify the upload with a `HEAD` to the location.
+///
+/// If the server accepts fewer bytes than were sent,
+/// the next chunk starts from the `Upload-Offset` it returned.
+/// If the server rejects a chunk, the upload stops, and the response is kept for asserting.
+///
+/// ```rust
+/// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+/// #
+/// # use axum::Router;
+/// use axum_test::TestServer;
+///
+/// # let app = Router::new();
+/// let server = TestServer::new(app)?;
+///
+/// let upload = server
+///     .tus_upload(&"/files", vec![0; 2500])
+///     .chunk_size(1024)
+///     .await;
+///
+/// upload.assert_offsets(&[1024, 2048, 2500]);
+/// upload.assert_complete();
+/// #
+/// # Ok(()) }
+/// ```
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct TusUpload<'a> {
+    server: &'a TestServer,
+    path: String,
+    bytes: Bytes,
+    chunk_size: usize,
+    metadata: Vec<(String, Option<String>)>,
+    is_sending_checksums: bool,
+}
+
+impl<'a> TusUpload<'a> {
+    pub(crate) fn new(server: &'a TestServer, path: &str, bytes: Bytes) -> Self {
+        Self {
+            server,
+            path: path.to_string(),
+            bytes,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            metadata: Vec::new(),
+            is_sending_checksums: false,
+        }
+    }
+
+    /// Sets the maximum number of bytes sent in each `PATCH` request.
+    ///
+    /// **Defaults** to 64 KiB.
+    ///
+    /// This will panic if the chunk size is zero.
+    #[track_caller]
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        if chunk_size == 0 {
+            panic!(
+                "Expected tus chunk size to be above zero, for upload to {}",
+                self.path
+            );
+        }
+
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// Adds a key and value to the `Upload-Metadata` sent when creating the upload.
+    ///
+    /// The value is base64 encoded, as the tus protocol requires.
+    pub fn add_metadata(mut self, key: &str, value: &str) -> Self {
+        self.metadata
+            .push((key.to_string(), Some(value.to_string())));
+        self
+    }
+
+    /// Adds a key, without a value, to the `Upload-Metadata` sent when creating the upload.
+    pub fn add_metadata_key(mut self, key: &str) -> Self {
+        self.metadata.push((key.to_string(), None));
+        self
+    }
+
+    /// Sends an `Upload-Checksum` header with each chunk, using SHA-1.
+    ///
+    /// This is the checksum extension of the tus protocol.
+    /// See [`TusUploadResponse::assert_checksums_accepted()`] for asserting the server accepted them.
+    pub fn with_checksums(mut self) -> Self {
+        self.is_sending_checksums = true;
+        self
+    }
+
+    /// Runs the upload, returning an error if the server did not follow the tus protocol.
+    ///
+    /// Such as not returning a `201 Created`, or a `Location`, when creating the upload.
+    pub async fn try_send(self) -> Result<TusUploadResponse> {
+        let upload_length = self.bytes.len();
+        let debug_upload_format = format!("POST {}", self.path);
+
+        let mut creation_request = self
+            .server
+            .post(&self.path)
+            .expect_state(ExpectedState::None)
+            .add_header(TUS_RESUMABLE_HEADER, TUS_VERSION)
+            .add_header(UPLOAD_LENGTH_HEADER, upload_length.to_string());
+        if !self.metadata.is_empty() {
+            creation_request = creation_request
+                .add_header(UPLOAD_METADATA_HEADER, encode_metadata(&self.metadata));
+        }
+
+        let creation = creation_request.try_send().await?;
+        if creation.status_code() != StatusCode::CREATED {
+            return Err(anyhow!(
+                "Expected tus upload creation to return 201 Created, received {}, for upload {debug_upload_format}",
+                creation.status_code()
+            ));
+        }
+
+        let location = creation
+            .maybe_header(header::LOCATION)
+            .ok_or_else(|| {
+                anyhow!("Expected tus upload creation to return a Location header, for upload {debug_upload_format}")
+            })?
+            .to_str()
+            .map(str::to_string)
+            .with_context(|| {
+                format!("Failed to read tus Location header as text, for upload {debug_upload_format}")
+            })?;
+        let location = resolve_relative_url(&creation.request_url(), &location);
+
+        let mut patches = Vec::new();
+        let mut offset = 0;
+        while offset < upload_length {
+            let end = (offset + self.chunk_size).min(upload_length);
+            let chunk = self.bytes.slice(offset..end);
+
+            let mut patch_request = self
+                .server
+                .patch(&location)
+                .expect_state(ExpectedState::None)
+                .add_header(TUS_RESUMABLE_HEADER, TUS_VERSION)
+                .add_header(UPLOAD_OFFSET_HEADER, offset.to_string())
+                .content_type(OFFSET_OCTET_STREAM);
+            if self.is_sending_checksums {
+                let checksum = encode_base64(&Sha1::digest(&chunk));
+                patch_request =
+                    patch_request.add_header(UPLOAD_CHECKSUM_HEADER, format!("sha1 {checksum}"));
+            }
+
+            let patch = patch_request.bytes(chunk).try_send().await?;
+            let maybe_next_offset = read_upload_offset(&patch)
+                .and_then(|next_offset| usize::try_from(next_offset).ok())
+                .filter(|next_offset| *next_offset > offset && *next_offset <= end);
+            let is_accepted = patch.status_code() == StatusCode::NO_CONTENT;
+            patches.push(patch);
+
+            match maybe_next_offset {
+                Some(next_offset) if is_accepted => offset = next_offset,
+                _ => break,
+            }
+        }
+
+        let verification = self
+            .server
+            .method(Method::HEAD, &location)
+            .expect_state(ExpectedState::None)
+            .add_header(TUS_RESUMABLE_HEADER, TUS_VERSION)
+            .try_send()
+            .await?;
+
+        Ok(TusUploadResponse {
+            location,
+            upload_length: upload_length as u64,
+            is_sending_checksums: self.is_sending_checksums,
+            creation,
+            patches,
+            verification,
+        })
+    }
+}
+
+impl<'a> IntoFuture for TusUpload<'a> {
+    type Output = TusUploadResponse;
+    type IntoFuture = Pin<Box<dyn Future<Output = TusUploadResponse> + 'a>>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(async { self.try_send().await.unwrap() })
+    }
+}
+
+/// The responses from running a [`TusUpload`],
+/// for asserting on the offsets and checksums the server accepted.
+#[derive(Debug, Clone)]
+pub struct TusUploadResponse {
+    location: String,
+    upload_length: u64,
+    is_sending_checksums: bool,
+    creation: TestResponse,
+    patches: Vec<TestResponse>,
+    verification: TestResponse,
+}
+
+impl TusUploadResponse {
+    /// The location of the upload, from the `Location` header returned when creating it.
+    ///
+    /// Locations on the `TestServer` are returned as a path.
+    #[must_use]
+    pub fn location(&self) -> &str {
+        &self.location
+    }
+
+    /// The number of bytes being uploaded, as sent in the `Upload-Length` header.
+    #[must_use]
+    pub fn upload_length(&self) -> u64 {
+        self.upload_length
+    }
+
+    /// The response to the `POST` which created the upload.
+    #[must_use]
+    pub fn creation_response(&self) -> &TestResponse {
+        &self.creation
+    }
+
+    /// The responses to each `PATCH` sending a chunk, in the order they were sent.
+    #[must_use]
+    pub fn patch_responses(&self) -> &[TestResponse] {
+        &self.patches
+    }
+
+    /// The response to the `HEAD` sent after the upload, to verify it.
+    #[must_use]
+    pub fn verification_response(&self) -> &TestResponse {
+        &self.verification
+    }
+
+    /// The `Upload-Offset` returned by each `PATCH`, in the order they were sent.
+    ///
+    /// Responses without a valid `Upload-Offset` are skipped.
+    #[must_use]
+    pub fn offsets(&self) -> Vec<u64> {
+        self.patches.iter().filter_map(read_upload_offset).collect()
+    }
+
+    /// The `Upload-Offset` returned by the `HEAD` sent after the upload, if it was valid.
+    #[must_use]
+    pub fn final_offset(&self) -> Option<u64> {
+        read_upload_offset(&self.verification)
+    }
+
+    /// Asserts the `Upload-Offset` returned by each `PATCH` matches those given.
+    #[track_caller]
+    pub fn assert_offsets(&self, expected_offsets: &[u64]) {
+        self.check_offsets(expected_offsets).or_panic()
+    }
+
+    /// Checks the `Upload-Offset` returned by each `PATCH` matches those given.
+    ///
+    /// This is the non-panicking version of [`TusUploadResponse::assert_offsets()`].
+    pub fn check_offsets(&self, expected_offsets: &[u64]) -> Result<(), AssertionError> {
+        check_eq(
+            expected_offsets,
+            self.offsets().as_slice(),
+            format_args!(
+                "Expected tus upload offsets to match, for upload {}",
+                self.location
+            ),
+        )
+    }
+
+    /// Asserts every chunk was accepted, and the server reports the full upload was received.
+    ///
+    /// This checks every `PATCH` returned `204 No Content`,
+    /// and the `HEAD` afterwards returned an `Upload-Offset` equal to the upload length.
+    #[track_caller]
+    pub fn assert_complete(&self) {
+        self.check_complete().or_panic()
+    }
+
+    /// Checks every chunk was accepted, and the server reports the full upload was received.
+    ///
+    /// This is the non-panicking version of [`TusUploadResponse::assert_complete()`].
+    pub fn check_complete(&self) -> Result<(), AssertionError> {
+        for (index, patch) in self.patches.iter().enumerate() {
+            check(
+                patch.status_code() == StatusCode::NO_CONTENT,
+                format_args!(
+                    "Expected tus chunk {index} to return 204 No Content, received {}, for upload {}",
+                    patch.status_code(),
+                    self.location
+                ),
+            )?;
+        }
+
+        let final_offset = self.final_offset();
+        check(
+            final_offset == Some(self.upload_length),
+            format_args!(
+                "Expected tus upload to be complete at offset {}, received offset {final_offset:?}, for upload {}",
+                self.upload_length, self.location
+            ),
+        )
+    }
+
+    /// Asserts checksums were sent with each chunk, and none were rejected by the server
+    /// with a `460 Checksum Mismatch`.
+    ///
+    /// Checksums are sent by calling [`TusUpload::with_checksums()`].
+    #[track_caller]
+    pub fn assert_checksums_accepted(&self) {
+        self.check_checksums_accepted().or_panic()
+    }
+
+    /// Checks checksums were sent with each chunk, and none were rejected by the server.
+    ///
+    /// This is the non-panicking version of [`TusUploadResponse::assert_checksums_accepted()`].
+    pub fn check_checksums_accepted(&self) -> Result<(), AssertionError> {
+        check(
+            self.is_sending_checksums,
+            format_args!(
+                "Expected tus upload to send checksums, use `with_checksums()` to send them, for upload {}",
+                self.location
+            ),
+        )?;
+
+        match self
+            .patches
+            .iter()
+            .position(|patch| patch.status_code().as_u16() == CHECKSUM_MISMATCH)
+        {
+            None => Ok(()),
+            Some(index) => Err(AssertionError::new(format!(
+                "Expected tus checksums to be accepted, chunk {index} was rejected with 460 Checksum Mismatch, for upload {}",
+                self.location
+            ))),
+        }
+    }
+}
+
+fn read_upload_offset(response: &TestResponse) -> Option<u64> {
+    response
+        .maybe_header(UPLOAD_OFFSET_HEADER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+fn encode_metadata(metadata: &[(String, Option<String>)]) -> String {
+    metadata
+        .iter()
+        .map(|(key, maybe_value)| match maybe_value {
+            Some(value) => format!("{key} {}", encode_base64(value.as_bytes())),
+            None => key.clone(),
+        })
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod test_encode_metadata {
+    use super::*;
+
+    #[test]
+    fn it_should_encode_values_as_base64() {
+        let metadata = vec![
+            ("filename".to_string(), Some("cat.png".to_string())),
+            ("is_public".to_string(), None),
+        ];
+
+        assert_eq!(
+            encode_metadata(&metadata),
+            "filename Y2F0LnBuZw==,is_public"
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_tus_upload {
+    use crate::internals::encode_base64;
+    use crate::TestServer;
+    use axum::body::Bytes;
+    use axum::extract::Path;
+    use axum::extract::State;
+    use axum::http::HeaderMap;
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+    use axum::response::Response;
+    use axum::routing::patch;
+    use axum::routing::post;
+    use axum::Router;
+    use sha1::Digest;
+    use sha1::Sha1;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    #[derive(Debug, Default)]
+    struct Upload {
+        length: usize,
+        metadata: String,
+        data: Vec<u8>,
+    }
+
+    type Uploads = Arc<Mutex<HashMap<String, Upload>>>;
+
+    fn header_text(headers: &HeaderMap, name: &str) -> String {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    async fn route_post_file(State(uploads): State<Uploads>, headers: HeaderMap) -> Response {
+        let Ok(length) = header_text(&headers, "upload-length").parse() else {
+            return StatusCode::BAD_REQUEST.into_response();
+        };
+
+        let mut uploads = uploads.lock().unwrap();
+        let id = uploads.len().to_string();
+        uploads.insert(
+            id.clone(),
+            Upload {
+                length,
+                metadata: header_text(&headers, "upload-metadata"),
+                data: Vec::new(),
+            },
+        );
+
+        (
+            StatusCode::CREATED,
+            [("location", format!("http://localhost/files/{id}"))],
+        )
+            .into_response()
+    }
+
+    async fn route_patch_file(
+        State(uploads): State<Uploads>,
+        Path(id): Path<String>,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> Response {
+        let mut uploads = uploads.lock().unwrap();
+        let Some(upload) = uploads.get_mut(&id) else {
+            return StatusCode::NOT_FOUND.into_response();
+        };
+
+        if header_text(&headers, "content-type") != "application/offset+octet-stream" {
+            return StatusCode::UNSUPPORTED_MEDIA_TYPE.into_response();
+        }
+        if header_text(&headers, "upload-offset") != upload.data.len().to_string() {
+            return StatusCode::CONFLICT.into_response();
+        }
+
+        let checksum = header_text(&headers, "upload-checksum");
+        if !checksum.is_empty()
+            && checksum != format!("sha1 {}", encode_base64(&Sha1::digest(&body)))
+        {
+            return StatusCode::from_u16(460).unwrap().into_response();
+        }
+
+        upload.data.extend_from_slice(&body);
+        (
+            StatusCode::NO_CONTENT,
+            [("upload-offset", upload.data.len().to_string())],
+        )
+            .into_response()
+    }
+
+    async fn route_head_file(State(uploads): State<Uploads>, Path(id): Path<String>) -> Response {
+        let uploads = uploads.lock().unwrap();
+        let Some(upload) = uploads.get(&id) else {
+            return StatusCode::NOT_FOUND.into_response();
+        };
+
+        (
+            [
+                ("upload-offset", upload.data.len().to_string()),
+                ("upload-length", upload.length.to_string()),
+                ("x-upload-metadata", upload.metadata.clone()),
+            ],
+            upload.data.clone(),
+        )
+            .into_response()
+    }
+
+    fn new_test_server() -> TestServer {
+        let app = Router::new()
+            .route("/files", post(route_post_file))
+            .route("/files/:id", patch(route_patch_file).get(route_head_file))
+            .route("/no-location", post(|| async { StatusCode::CREATED }))
+            .with_state(Uploads::default());
+
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_upload_in_chunks() {
+        let server = new_test_server();
+        let bytes = (0..2500).map(|i| (i % 256) as u8).collect::<Vec<u8>>();
+
+        let upload = server
+            .tus_upload("/files", bytes.clone())
+            .chunk_size(1024)
+            .await;
+
+        assert_eq!(upload.location(), "/files/0");
+        assert_eq!(upload.patch_responses().len(), 3);
+        upload.assert_offsets(&[1024, 2048, 2500]);
+        upload.assert_complete();
+
+        let response = server.get("/files/0").await;
+        assert_eq!(response.as_bytes().as_ref(), bytes.as_slice());
+    }
+
+    #[tokio::test]
+    async fn it_should_send_metadata() {
+        let server = new_test_server();
+
+        let upload = server
+            .tus_upload("/files", b"hello".to_vec())
+            .add_metadata("filename", "cat.png")
+            .add_metadata_key("is_public")
+            .await;
+
+        upload
+            .verification_response()
+            .assert_header("x-upload-metadata", "filename Y2F0LnBuZw==,is_public");
+    }
+
+    #[tokio::test]
+    async fn it_should_send_checksums_accepted_by_the_server() {
+        let server = new_test_server();
+
+        let upload = server
+            .tus_upload("/files", vec![7; 100])
+            .chunk_size(30)
+            .with_checksums()
+            .await;
+
+        upload.assert_offsets(&[30, 60, 90, 100]);
+        upload.assert_checksums_accepted();
+        upload.assert_complete();
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_fail_checksums_accepted_when_not_sending_checksums() {
+        let server = new_test_server();
+
+        server
+            .tus_upload("/files", vec![7; 100])
+            .await
+            .assert_checksums_accepted();
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn it_should_fail_assert_offsets_when_different() {
+        let server = new_test_server();
+
+        server
+            .tus_upload("/files", vec![7; 100])
+            .chunk_size(50)
+            .await
+            .assert_offsets(&[40, 100]);
+    }
+
+    #[tokio::test]
+    async fn it_should_error_when_creation_has_no_location() {
+        let server = new_test_server();
+
+        let error = server
+            .tus_upload("/no-location", vec![7; 100])
+            .try_send()
+            .await
+            .unwrap_err();
+
+        assert!(error.to_string().contains("Location"), "{error}");
+    }
+
+    #[tokio::test]
+    async fn it_should_error_when_creation_is_not_created() {
+        let server = new_test_server();
+
+        let result = server.tus_upload("/missing", vec![7; 100]).try_send().await;
+
+        assert!(result.is_err());
+    }
+}
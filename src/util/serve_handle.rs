@@ -1,21 +1,42 @@
+use std::sync::Arc;
 use tokio::task::JoinHandle;
 
+use crate::internals::ServeTracker;
+use crate::TransportDiagnostics;
+
 /// A handle to a running Axum service.
 ///
 /// When the handle is dropped, it will attempt to terminate the service.
 #[derive(Debug)]
 pub struct ServeHandle {
     server_handle: JoinHandle<()>,
+    tracker: Arc<ServeTracker>,
 }
 
 impl ServeHandle {
-    pub(crate) fn new(server_handle: JoinHandle<()>) -> Self {
-        Self { server_handle }
+    pub(crate) fn new(server_handle: JoinHandle<()>, tracker: Arc<ServeTracker>) -> Self {
+        Self {
+            server_handle,
+            tracker,
+        }
     }
 
     pub fn is_finished(&self) -> bool {
         self.server_handle.is_finished()
     }
+
+    pub(crate) fn transport_diagnostics(&self) -> TransportDiagnostics {
+        let is_finished = self.is_finished();
+        let open_connections = self.tracker.open_connections();
+
+        TransportDiagnostics {
+            open_connections,
+            active_requests: self.tracker.active_requests(),
+            spawned_tasks: usize::from(!is_finished) + open_connections,
+            // The tracker is only marked as stopped when the service returns normally.
+            has_serve_loop_panicked: is_finished && !self.tracker.has_stopped(),
+        }
+    }
 }
 
 impl Drop for ServeHandle {
@@ -23,3 +44,47 @@ impl Drop for ServeHandle {
         self.server_handle.abort()
     }
 }
+
+#[cfg(test)]
+mod test_transport_diagnostics {
+    use super::*;
+    use tokio::spawn;
+    use tokio::task::yield_now;
+
+    #[tokio::test]
+    async fn it_should_report_a_running_serve_loop() {
+        let tracker = Arc::new(ServeTracker::default());
+        let handle = ServeHandle::new(spawn(std::future::pending()), tracker);
+
+        let diagnostics = handle.transport_diagnostics();
+        assert_eq!(diagnostics.spawned_tasks, 1);
+        assert!(!diagnostics.has_serve_loop_panicked);
+    }
+
+    #[tokio::test]
+    async fn it_should_not_report_a_panic_when_stopped_normally() {
+        let tracker = Arc::new(ServeTracker::default());
+        let task_tracker = Arc::clone(&tracker);
+        let handle = ServeHandle::new(spawn(async move { task_tracker.mark_stopped() }), tracker);
+
+        while !handle.is_finished() {
+            yield_now().await;
+        }
+
+        let diagnostics = handle.transport_diagnostics();
+        assert_eq!(diagnostics.spawned_tasks, 0);
+        assert!(!diagnostics.has_serve_loop_panicked);
+    }
+
+    #[tokio::test]
+    async fn it_should_report_a_panicked_serve_loop() {
+        let tracker = Arc::new(ServeTracker::default());
+        let handle = ServeHandle::new(spawn(async { panic!("serve loop failed") }), tracker);
+
+        while !handle.is_finished() {
+            yield_now().await;
+        }
+
+        assert!(handle.transport_diagnostics().has_serve_loop_panicked);
+    }
+}
@@ -3,10 +3,13 @@ use axum::response::Response;
 use axum::serve;
 use axum::serve::IncomingStream;
 use std::convert::Infallible;
+use std::sync::Arc;
 use tokio::net::TcpListener;
 use tokio::spawn;
 use tower::Service;
+use tower::ServiceExt;
 
+use crate::internals::ServeTracker;
 use crate::util::ServeHandle;
 
 /// A wrapper around [`axum::serve()`] for tests,
@@ -21,11 +24,33 @@ where
     S: Service<Request, Response = Response, Error = Infallible> + Clone + Send + 'static,
     S::Future: Send,
 {
+    spawn_serve_with_tracker(tcp_listener, make_service, Arc::default())
+}
+
+/// Spawns the service in the same way as [`spawn_serve()`],
+/// using the tracker given, such as one catching panics.
+pub(crate) fn spawn_serve_with_tracker<M, S>(
+    tcp_listener: TcpListener,
+    make_service: M,
+    tracker: Arc<ServeTracker>,
+) -> ServeHandle
+where
+    M: for<'a> Service<IncomingStream<'a>, Error = Infallible, Response = S> + Send + 'static,
+    for<'a> <M as Service<IncomingStream<'a>>>::Future: Send,
+    S: Service<Request, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    let connection_tracker = Arc::clone(&tracker);
+    let serve_tracker = Arc::clone(&tracker);
+    let make_service =
+        make_service.map_response(move |service| connection_tracker.track_connection(service));
+
     let server_handle = spawn(async move {
         serve(tcp_listener, make_service)
             .await
             .expect("Expect server to start serving");
+        serve_tracker.mark_stopped();
     });
 
-    ServeHandle::new(server_handle)
+    ServeHandle::new(server_handle, tracker)
 }
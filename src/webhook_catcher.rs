@@ -0,0 +1,496 @@
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::Router;
+use hmac::Hmac;
+use hmac::Mac;
+use http::HeaderMap;
+use http::Method;
+use http::StatusCode;
+use http::Uri;
+use serde::de::DeserializeOwned;
+use sha2::Sha256;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::time::timeout_at;
+use tokio::time::Instant;
+use url::Url;
+
+use crate::internals::check;
+use crate::internals::OrPanic;
+use crate::AssertionError;
+use crate::Error;
+use crate::TestServer;
+
+const GITHUB_SIGNATURE_HEADER: &str = "x-hub-signature-256";
+const STRIPE_SIGNATURE_HEADER: &str = "stripe-signature";
+
+/// A webhook received by a [`WebhookCatcher`].
+#[derive(Debug, Clone)]
+pub struct WebhookDelivery {
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl WebhookDelivery {
+    /// The method the webhook was sent with.
+    #[must_use]
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// The path the webhook was sent to, without the query.
+    #[must_use]
+    pub fn path(&self) -> &str {
+        self.uri.path()
+    }
+
+    /// All of the headers the webhook was sent with.
+    #[must_use]
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Returns the header value given, if it was sent and is valid text.
+    #[must_use]
+    pub fn maybe_header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name)?.to_str().ok()
+    }
+
+    /// The raw body of the webhook.
+    #[must_use]
+    pub fn body(&self) -> &Bytes {
+        &self.body
+    }
+
+    /// The body of the webhook as text.
+    #[must_use]
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).to_string()
+    }
+
+    /// Deserializes the body of the webhook from Json.
+    ///
+    /// This will panic if the body is not valid Json for the type given.
+    #[track_caller]
+    #[must_use]
+    pub fn json<T>(&self) -> T
+    where
+        T: DeserializeOwned,
+    {
+        serde_json::from_slice(&self.body).unwrap_or_else(|error| {
+            panic!(
+                "Failed to deserialize webhook body as Json, for delivery {}, {error}",
+                self.debug_format()
+            )
+        })
+    }
+
+    /// Asserts the header given holds a hex encoded HMAC-SHA256 signature of the body,
+    /// using the secret given. A `sha256=` prefix on the signature is allowed.
+    #[track_caller]
+    pub fn assert_hmac_sha256_signature(&self, header_name: &str, secret: &str) {
+        self.check_hmac_sha256_signature(header_name, secret)
+            .or_panic()
+    }
+
+    /// Checks the header given holds a hex encoded HMAC-SHA256 signature of the body.
+    ///
+    /// This is the non-panicking version of [`WebhookDelivery::assert_hmac_sha256_signature()`].
+    pub fn check_hmac_sha256_signature(
+        &self,
+        header_name: &str,
+        secret: &str,
+    ) -> Result<(), AssertionError> {
+        let signature = self.check_signature_header(header_name)?;
+        let signature = signature.strip_prefix("sha256=").unwrap_or(signature);
+
+        check(
+            is_hmac_sha256_match(secret, &self.body, signature),
+            format_args!(
+                "Expected webhook header '{header_name}' to hold a valid HMAC-SHA256 signature, received '{signature}', for delivery {}",
+                self.debug_format()
+            ),
+        )
+    }
+
+    /// Asserts the webhook is signed the way GitHub signs them,
+    /// with a `sha256=` prefixed signature in the `X-Hub-Signature-256` header.
+    #[track_caller]
+    pub fn assert_github_signature(&self, secret: &str) {
+        self.check_github_signature(secret).or_panic()
+    }
+
+    /// Checks the webhook is signed the way GitHub signs them.
+    ///
+    /// This is the non-panicking version of [`WebhookDelivery::assert_github_signature()`].
+    pub fn check_github_signature(&self, secret: &str) -> Result<(), AssertionError> {
+        self.check_hmac_sha256_signature(GITHUB_SIGNATURE_HEADER, secret)
+    }
+
+    /// Asserts the webhook is signed the way Stripe signs them.
+    ///
+    /// The `Stripe-Signature` header holds a timestamp and signatures, i.e. `t=1700000000,v1=...`,
+    /// where the signed payload is the timestamp and body joined with a `.`.
+    /// It passes if any of the `v1` signatures match.
+    #[track_caller]
+    pub fn assert_stripe_signature(&self, secret: &str) {
+        self.check_stripe_signature(secret).or_panic()
+    }
+
+    /// Checks the webhook is signed the way Stripe signs them.
+    ///
+    /// This is the non-panicking version of [`WebhookDelivery::assert_stripe_signature()`].
+    pub fn check_stripe_signature(&self, secret: &str) -> Result<(), AssertionError> {
+        let header = self.check_signature_header(STRIPE_SIGNATURE_HEADER)?;
+        let pairs = header
+            .split(',')
+            .filter_map(|pair| pair.trim().split_once('='));
+
+        let mut maybe_timestamp = None;
+        let mut signatures = Vec::new();
+        for (key, value) in pairs {
+            match key {
+                "t" => maybe_timestamp = Some(value),
+                "v1" => signatures.push(value),
+                _ => {}
+            }
+        }
+
+        let Some(timestamp) = maybe_timestamp else {
+            return Err(AssertionError::new(format!(
+                "Expected webhook header '{STRIPE_SIGNATURE_HEADER}' to hold a timestamp, received '{header}', for delivery {}",
+                self.debug_format()
+            )));
+        };
+
+        let mut payload = format!("{timestamp}.").into_bytes();
+        payload.extend_from_slice(&self.body);
+        let is_signed = signatures
+            .iter()
+            .any(|signature| is_hmac_sha256_match(secret, &payload, signature));
+
+        check(
+            is_signed,
+            format_args!(
+                "Expected webhook header '{STRIPE_SIGNATURE_HEADER}' to hold a valid signature, received '{header}', for delivery {}",
+                self.debug_format()
+            ),
+        )
+    }
+
+    fn check_signature_header(&self, header_name: &str) -> Result<&str, AssertionError> {
+        self.maybe_header(header_name).ok_or_else(|| {
+            AssertionError::new(format!(
+                "Expected webhook header '{header_name}' to be present, for delivery {}",
+                self.debug_format()
+            ))
+        })
+    }
+
+    fn debug_format(&self) -> String {
+        format!("{} {}", self.method, self.uri)
+    }
+}
+
+fn is_hmac_sha256_match(secret: &str, payload: &[u8], hex_signature: &str) -> bool {
+    let Ok(signature) = hex::decode(hex_signature) else {
+        return false;
+    };
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC should accept keys of any length");
+    mac.update(payload);
+    mac.verify_slice(&signature).is_ok()
+}
+
+#[derive(Debug, Default)]
+struct CatcherState {
+    deliveries: Mutex<Vec<WebhookDelivery>>,
+    num_awaited: Mutex<usize>,
+    notify: Notify,
+}
+
+/// A small embedded web server, which records the webhooks sent to it.
+///
+/// Give the address from [`WebhookCatcher::server_url()`] to your application,
+/// and then wait for deliveries to arrive using [`WebhookCatcher::await_delivery()`].
+/// It answers every request with a `200 OK`, on any path.
+///
+/// ```rust
+/// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+/// #
+/// use axum_test::WebhookCatcher;
+/// use std::time::Duration;
+///
+/// let catcher = WebhookCatcher::start()?;
+/// let webhook_url = catcher.server_url(&"/hooks/orders")?;
+///
+/// // Configure your application to send webhooks to `webhook_url`,
+/// // and do something which triggers one ...
+///
+/// let delivery = catcher.await_delivery(Duration::from_secs(5)).await;
+/// assert_eq!(delivery.path(), "/hooks/orders");
+/// delivery.assert_github_signature("my-webhook-secret");
+/// #
+/// # Ok(()) }
+/// ```
+#[derive(Debug)]
+pub struct WebhookCatcher {
+    server: TestServer,
+    state: Arc<CatcherState>,
+}
+
+impl WebhookCatcher {
+    /// Starts the catcher on a random local port.
+    pub fn start() -> Result<Self, Error> {
+        let state = Arc::new(CatcherState::default());
+        let app = Router::new()
+            .fallback(route_catch_webhook)
+            .with_state(state.clone());
+        let server = TestServer::builder().http_transport().build(app)?;
+
+        Ok(Self { server, state })
+    }
+
+    /// The address the catcher is running on.
+    #[must_use]
+    pub fn server_address(&self) -> Url {
+        self.server
+            .server_address()
+            .expect("Webhook catcher should always run with a HTTP transport")
+    }
+
+    /// Turns a relative path into an absolute address on the catcher,
+    /// for your application to send webhooks to.
+    pub fn server_url(&self, path: &str) -> Result<Url, Error> {
+        self.server.server_url(path)
+    }
+
+    /// Returns all of the webhooks received so far, in the order they arrived.
+    #[must_use]
+    pub fn deliveries(&self) -> Vec<WebhookDelivery> {
+        self.state
+            .deliveries
+            .lock()
+            .expect("Failed to lock webhook deliveries, for reading deliveries")
+            .clone()
+    }
+
+    /// Removes all of the webhooks received so far.
+    pub fn clear(&self) {
+        self.state
+            .deliveries
+            .lock()
+            .expect("Failed to lock webhook deliveries, for clearing deliveries")
+            .clear();
+        *self
+            .state
+            .num_awaited
+            .lock()
+            .expect("Failed to lock webhook deliveries, for clearing deliveries") = 0;
+    }
+
+    /// Waits for the next webhook, returning it.
+    ///
+    /// Each call returns the delivery after the one returned before,
+    /// including deliveries which arrived before it was called.
+    ///
+    /// This will panic if no webhook arrives within the timeout.
+    pub async fn await_delivery(&self, timeout: Duration) -> WebhookDelivery {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            // Created before checking, so deliveries arriving in between are not missed.
+            let notified = self.state.notify.notified();
+            if let Some(delivery) = self.take_next_delivery() {
+                return delivery;
+            }
+
+            if timeout_at(deadline, notified).await.is_err() {
+                let num_deliveries = self.deliveries().len();
+                panic!("Timed out after {timeout:?} waiting for a webhook delivery, {num_deliveries} deliveries were received before");
+            }
+        }
+    }
+
+    fn take_next_delivery(&self) -> Option<WebhookDelivery> {
+        let deliveries = self
+            .state
+            .deliveries
+            .lock()
+            .expect("Failed to lock webhook deliveries, for awaiting delivery");
+        let mut num_awaited = self
+            .state
+            .num_awaited
+            .lock()
+            .expect("Failed to lock webhook deliveries, for awaiting delivery");
+
+        let delivery = deliveries.get(*num_awaited)?.clone();
+        *num_awaited += 1;
+
+        Some(delivery)
+    }
+}
+
+async fn route_catch_webhook(
+    State(state): State<Arc<CatcherState>>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> StatusCode {
+    state
+        .deliveries
+        .lock()
+        .expect("Failed to lock webhook deliveries, for recording delivery")
+        .push(WebhookDelivery {
+            method,
+            uri,
+            headers,
+            body,
+        });
+    state.notify.notify_waiters();
+
+    StatusCode::OK
+}
+
+#[cfg(test)]
+mod test_await_delivery {
+    use super::*;
+    use serde_json::json;
+    use serde_json::Value;
+
+    #[tokio::test]
+    async fn it_should_return_deliveries_in_order() {
+        let catcher = WebhookCatcher::start().unwrap();
+
+        catcher
+            .server
+            .post("/hooks/orders?attempt=1")
+            .json(&json!({ "order": 1 }))
+            .await;
+        catcher.server.put("/hooks/users").text("updated").await;
+
+        let first = catcher.await_delivery(Duration::from_secs(1)).await;
+        assert_eq!(first.method(), Method::POST);
+        assert_eq!(first.path(), "/hooks/orders");
+        assert_eq!(first.json::<Value>(), json!({ "order": 1 }));
+
+        let second = catcher.await_delivery(Duration::from_secs(1)).await;
+        assert_eq!(second.path(), "/hooks/users");
+        assert_eq!(second.text(), "updated");
+
+        assert_eq!(catcher.deliveries().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn it_should_wait_for_deliveries_arriving_later() {
+        let catcher = WebhookCatcher::start().unwrap();
+
+        let send_later = async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            catcher.server.post("/hooks/later").await;
+        };
+        let (delivery, _) =
+            tokio::join!(catcher.await_delivery(Duration::from_secs(5)), send_later);
+
+        assert_eq!(delivery.path(), "/hooks/later");
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "Timed out after 50ms waiting for a webhook delivery")]
+    async fn it_should_panic_on_timeout() {
+        let catcher = WebhookCatcher::start().unwrap();
+
+        let _ = catcher.await_delivery(Duration::from_millis(50)).await;
+    }
+
+    #[tokio::test]
+    async fn it_should_clear_deliveries() {
+        let catcher = WebhookCatcher::start().unwrap();
+        catcher.server.post("/hooks").await;
+        catcher.clear();
+
+        assert!(catcher.deliveries().is_empty());
+    }
+}
+
+#[cfg(test)]
+mod test_signatures {
+    use super::*;
+
+    // The example from GitHub's documentation on validating webhook deliveries.
+    const SECRET: &str = "It's a Secret to Everybody";
+    const BODY: &str = "Hello, World!";
+    const SIGNATURE: &str = "757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17";
+
+    fn new_delivery(header_name: &'static str, header_value: &str) -> WebhookDelivery {
+        let mut headers = HeaderMap::new();
+        headers.insert(header_name, header_value.parse().unwrap());
+
+        WebhookDelivery {
+            method: Method::POST,
+            uri: Uri::from_static("/hooks"),
+            headers,
+            body: Bytes::from_static(BODY.as_bytes()),
+        }
+    }
+
+    #[test]
+    fn it_should_verify_github_signatures() {
+        let delivery = new_delivery(GITHUB_SIGNATURE_HEADER, &format!("sha256={SIGNATURE}"));
+
+        delivery.assert_github_signature(SECRET);
+        assert!(delivery.check_github_signature("wrong secret").is_err());
+    }
+
+    #[test]
+    fn it_should_verify_plain_hex_signatures() {
+        let delivery = new_delivery("x-signature", SIGNATURE);
+
+        delivery.assert_hmac_sha256_signature("x-signature", SECRET);
+    }
+
+    #[test]
+    fn it_should_fail_on_missing_signature_header() {
+        let delivery = new_delivery("x-signature", SIGNATURE);
+        let error = delivery.check_github_signature(SECRET).unwrap_err();
+
+        assert!(error.to_string().contains(
+            "Expected webhook header 'x-hub-signature-256' to be present, for delivery POST /hooks"
+        ));
+    }
+
+    #[test]
+    fn it_should_verify_stripe_signatures() {
+        let mut mac = Hmac::<Sha256>::new_from_slice(SECRET.as_bytes()).unwrap();
+        mac.update(format!("1700000000.{BODY}").as_bytes());
+        let signature = hex::encode(mac.finalize().into_bytes());
+
+        let delivery = new_delivery(
+            STRIPE_SIGNATURE_HEADER,
+            &format!("t=1700000000,v1=00ff,v1={signature}"),
+        );
+        delivery.assert_stripe_signature(SECRET);
+
+        let delivery = new_delivery(
+            STRIPE_SIGNATURE_HEADER,
+            &format!("t=1700000001,v1={signature}"),
+        );
+        assert!(delivery.check_stripe_signature(SECRET).is_err());
+    }
+
+    #[test]
+    fn it_should_fail_on_stripe_signature_without_timestamp() {
+        let delivery = new_delivery(STRIPE_SIGNATURE_HEADER, "v1=00ff");
+        let error = delivery.check_stripe_signature(SECRET).unwrap_err();
+
+        assert!(error
+            .to_string()
+            .contains("Expected webhook header 'stripe-signature' to hold a timestamp"));
+    }
+}
@@ -0,0 +1,237 @@
+use anyhow::Context;
+use anyhow::Result;
+use bytes::Bytes;
+use std::io::Cursor;
+use std::io::Read;
+use zip::ZipArchive as ZipReader;
+
+use crate::internals::check;
+use crate::internals::check_eq;
+use crate::internals::OrPanic;
+use crate::AssertionError;
+
+/// A zip archive response, read for inspecting and asserting on the files inside.
+///
+/// This is returned from [`TestResponse::zip()`](crate::TestResponse::zip()).
+/// Directory entries are skipped, so only files are listed and counted.
+///
+/// ```rust
+/// # async fn test() -> Result<(), Box<dyn ::std::error::Error>> {
+/// #
+/// use axum::Router;
+/// use axum::routing::get;
+/// use axum_test::TestServer;
+///
+/// # async fn route_get_export() -> Vec<u8> { unimplemented!() }
+/// #
+/// let app = Router::new()
+///     .route(&"/export", get(route_get_export));
+/// let server = TestServer::new(app)?;
+///
+/// let archive = server.get(&"/export").await.zip();
+/// archive.assert_file_count(2);
+/// archive.assert_contains_file("report.csv");
+///
+/// let report = archive.file_bytes("report.csv");
+/// #
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ZipArchive {
+    files: Vec<(String, Bytes)>,
+    debug_request_format: String,
+}
+
+impl ZipArchive {
+    pub(crate) fn new(bytes: Bytes, debug_request_format: String) -> Result<Self> {
+        let mut reader = ZipReader::new(Cursor::new(bytes)).with_context(|| {
+            format!("Failed to read response as a zip archive, for request {debug_request_format}")
+        })?;
+
+        let mut files = Vec::with_capacity(reader.len());
+        for index in 0..reader.len() {
+            let mut file = reader.by_index(index).with_context(|| {
+                format!("Failed to read file at position {index} in zip archive, for request {debug_request_format}")
+            })?;
+            if file.is_dir() {
+                continue;
+            }
+
+            let name = file.name().to_string();
+            let mut contents = Vec::with_capacity(file.size() as usize);
+            file.read_to_end(&mut contents).with_context(|| {
+                format!("Failed to decompress file '{name}' in zip archive, for request {debug_request_format}")
+            })?;
+
+            files.push((name, Bytes::from(contents)));
+        }
+
+        Ok(Self {
+            files,
+            debug_request_format,
+        })
+    }
+
+    /// Returns the names of all files in the archive, in the order they are stored.
+    #[must_use]
+    pub fn file_names(&self) -> Vec<String> {
+        self.files.iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    /// Returns the decompressed contents of the file given,
+    /// or `None` if the archive does not contain it.
+    #[must_use]
+    pub fn maybe_file_bytes(&self, name: &str) -> Option<Bytes> {
+        self.files
+            .iter()
+            .find(|(file_name, _)| file_name == name)
+            .map(|(_, contents)| contents.clone())
+    }
+
+    /// Returns the decompressed contents of the file given.
+    ///
+    /// This will panic if the archive does not contain the file.
+    #[track_caller]
+    #[must_use]
+    pub fn file_bytes(&self, name: &str) -> Bytes {
+        self.check_contains_file(name).or_panic();
+        self.maybe_file_bytes(name).unwrap()
+    }
+
+    /// Asserts the archive contains a file with the name given.
+    ///
+    /// Names are the full path within the archive, i.e. `reports/2024.csv`.
+    #[track_caller]
+    pub fn assert_contains_file(&self, name: &str) {
+        self.check_contains_file(name).or_panic()
+    }
+
+    /// Checks the archive contains a file with the name given.
+    ///
+    /// This is the non-panicking version of [`ZipArchive::assert_contains_file()`].
+    pub fn check_contains_file(&self, name: &str) -> Result<(), AssertionError> {
+        let is_found = self.files.iter().any(|(file_name, _)| file_name == name);
+        let debug_request_format = &self.debug_request_format;
+
+        check(
+            is_found,
+            format_args!(
+                "Expected zip archive to contain file '{name}', found files {:?}, for request {debug_request_format}",
+                self.file_names()
+            ),
+        )
+    }
+
+    /// Asserts the archive contains exactly this many files.
+    #[track_caller]
+    pub fn assert_file_count(&self, expected_count: usize) {
+        self.check_file_count(expected_count).or_panic()
+    }
+
+    /// Checks the archive contains exactly this many files.
+    ///
+    /// This is the non-panicking version of [`ZipArchive::assert_file_count()`].
+    pub fn check_file_count(&self, expected_count: usize) -> Result<(), AssertionError> {
+        check_eq(
+            &expected_count,
+            &self.files.len(),
+            format_args!(
+                "Expected zip archive file count to match, for request {}",
+                self.debug_request_format
+            ),
+        )
+    }
+}
+
+#[cfg(test)]
+pub(crate) fn new_zip_bytes(files: &[(&str, &[u8])]) -> Bytes {
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    writer
+        .add_directory("reports/", SimpleFileOptions::default())
+        .unwrap();
+    for (name, contents) in files {
+        writer
+            .start_file(*name, SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(contents).unwrap();
+    }
+
+    Bytes::from(writer.finish().unwrap().into_inner())
+}
+
+#[cfg(test)]
+mod test_new {
+    use super::*;
+
+    #[test]
+    fn it_should_read_files_and_skip_directories() {
+        let bytes = new_zip_bytes(&[
+            ("reports/2024.csv", b"a,b\n1,2\n"),
+            ("readme.txt", b"hello"),
+        ]);
+        let archive = ZipArchive::new(bytes, "GET /export".to_string()).unwrap();
+
+        assert_eq!(archive.file_names(), vec!["reports/2024.csv", "readme.txt"]);
+        assert_eq!(
+            archive.file_bytes("readme.txt"),
+            Bytes::from_static(b"hello")
+        );
+        assert_eq!(archive.maybe_file_bytes("missing.txt"), None);
+    }
+
+    #[test]
+    fn it_should_fail_on_invalid_archive() {
+        let error = ZipArchive::new(Bytes::from_static(b"not a zip"), "GET /export".to_string())
+            .unwrap_err();
+
+        assert!(format!("{error:#}")
+            .contains("Failed to read response as a zip archive, for request GET /export"));
+    }
+}
+
+#[cfg(test)]
+mod test_assert_contains_file {
+    use super::*;
+
+    fn new_archive() -> ZipArchive {
+        let bytes = new_zip_bytes(&[("report.csv", b"a,b\n")]);
+        ZipArchive::new(bytes, "GET /export".to_string()).unwrap()
+    }
+
+    #[test]
+    fn it_should_pass_when_file_exists() {
+        new_archive().assert_contains_file("report.csv");
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Expected zip archive to contain file 'summary.csv', found files [\"report.csv\"]"
+    )]
+    fn it_should_fail_when_file_is_missing() {
+        new_archive().assert_contains_file("summary.csv");
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected zip archive to contain file 'summary.csv'")]
+    fn it_should_panic_reading_missing_file() {
+        let _ = new_archive().file_bytes("summary.csv");
+    }
+}
+
+#[cfg(test)]
+mod test_assert_file_count {
+    use super::*;
+
+    #[test]
+    fn it_should_count_files() {
+        let bytes = new_zip_bytes(&[("a.txt", b"a"), ("b.txt", b"b")]);
+        let archive = ZipArchive::new(bytes, "GET /export".to_string()).unwrap();
+
+        archive.assert_file_count(2);
+        assert!(archive.check_file_count(3).is_err());
+    }
+}
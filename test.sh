@@ -17,9 +17,34 @@ cargo check --features pretty-assertions
 cargo check --features yaml
 cargo check --features msgpack
 cargo check --features reqwest
+cargo check --features macros
 cargo check --features shuttle
 cargo check --features typed-routing
 cargo check --features ws
 cargo check --features reqwest
 
+# Check each feature builds on its own, without the default features
+cargo check --no-default-features --features pretty-assertions
+cargo check --no-default-features --features yaml
+cargo check --no-default-features --features msgpack
+cargo check --no-default-features --features reqwest
+cargo check --no-default-features --features macros
+cargo check --no-default-features --features shuttle
+cargo check --no-default-features --features typed-routing
+cargo check --no-default-features --features ws
+cargo check --no-default-features --features html
+cargo check --no-default-features --features regex
+cargo check --no-default-features --features archives
+cargo check --no-default-features --features webhooks
+cargo check --no-default-features --features mail
+cargo check --no-default-features --features jsonapi
+cargo check --no-default-features --features rejections
+cargo check --no-default-features --features multipart-echo
+cargo check --no-default-features --features blocking
+cargo check --no-default-features --features tus
+cargo check --no-default-features --features matched-route
+cargo check --no-default-features --features etag
+cargo check --no-default-features --features jwe
+cargo check --no-default-features --features dyn-features
+
 cargo clippy --features all